@@ -533,7 +533,7 @@ final class CodexStore: ObservableObject {
 
     func generateCommitMessage(workspaceId: String) async -> String? {
         do {
-            return try await api.generateCommitMessage(workspaceId: workspaceId)
+            return try await api.generateCommitMessage(workspaceId: workspaceId).message
         } catch {
             lastError = error.localizedDescription
             return nil
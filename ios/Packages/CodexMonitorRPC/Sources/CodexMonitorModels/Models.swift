@@ -18,6 +18,9 @@ public struct WorkspaceSettings: Codable, Hashable, Sendable {
     public var applyDomainInstructions: Bool?
     public var purpose: WorkspacePurpose?
     public var obsidianRoot: String?
+    public var obsidianRoots: [String]?
+    public var entityDirs: [String: String]?
+    public var protectedBranches: [String]?
 
     public init(
         sidebarCollapsed: Bool = false,
@@ -29,7 +32,10 @@ public struct WorkspaceSettings: Codable, Hashable, Sendable {
         domainId: String? = nil,
         applyDomainInstructions: Bool? = nil,
         purpose: WorkspacePurpose? = nil,
-        obsidianRoot: String? = nil
+        obsidianRoot: String? = nil,
+        obsidianRoots: [String]? = nil,
+        entityDirs: [String: String]? = nil,
+        protectedBranches: [String]? = nil
     ) {
         self.sidebarCollapsed = sidebarCollapsed
         self.sortOrder = sortOrder
@@ -41,6 +47,9 @@ public struct WorkspaceSettings: Codable, Hashable, Sendable {
         self.applyDomainInstructions = applyDomainInstructions
         self.purpose = purpose
         self.obsidianRoot = obsidianRoot
+        self.obsidianRoots = obsidianRoots
+        self.entityDirs = entityDirs
+        self.protectedBranches = protectedBranches
     }
 
     enum CodingKeys: String, CodingKey {
@@ -54,6 +63,9 @@ public struct WorkspaceSettings: Codable, Hashable, Sendable {
         case applyDomainInstructions
         case purpose
         case obsidianRoot
+        case obsidianRoots
+        case entityDirs
+        case protectedBranches
     }
 }
 
@@ -174,6 +186,57 @@ public struct AppSettings: Codable, Hashable, Sendable {
     public var composerListContinuation: Bool
     public var composerCodeBlockCopyUseModifier: Bool
     public var workspaceGroups: [WorkspaceGroup]
+    public var protectedBranches: [String]?
+    public var notificationRules: [NotificationRule]?
+    public var autoArchive: AutoArchiveSettings?
+    public var terminalShell: String?
+    public var revertUndoRetentionDays: Int?
+    public var reviewGate: ReviewGateSettings?
+    public var redaction: RedactionSettings?
+}
+
+public struct AutoArchiveSettings: Codable, Hashable, Sendable {
+    public var enabled: Bool
+    public var inactiveDays: Int
+}
+
+public struct ReviewGateSettings: Codable, Hashable, Sendable {
+    public var maxFiles: Int
+    public var maxChangedLines: Int
+    public var maxDiffBytes: Int
+}
+
+public struct RedactionSettings: Codable, Hashable, Sendable {
+    public var enabled: Bool
+    public var filePatterns: [String]
+    public var valuePatterns: [String]
+}
+
+public struct CommitMessagePrompt: Codable, Hashable, Sendable {
+    public var prompt: String
+    public var redactions: Int
+}
+
+public struct CommitMessageResult: Codable, Hashable, Sendable {
+    public var message: String
+    public var redactions: Int
+}
+
+/// Flat mirror of the backend's tagged action enum: `type` is
+/// `systemNotification`, `sound` or `none`.
+public struct NotificationAction: Codable, Hashable, Sendable {
+    public var type: String
+    public var title: String?
+    public var body: String?
+    public var soundId: String?
+}
+
+public struct NotificationRule: Codable, Hashable, Sendable, Identifiable {
+    public var id: String
+    public var event: String
+    public var workspaceId: String?
+    public var enabled: Bool?
+    public var action: NotificationAction
 }
 
 // MARK: - Domains
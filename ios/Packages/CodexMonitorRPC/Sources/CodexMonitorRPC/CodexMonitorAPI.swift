@@ -516,12 +516,12 @@ public struct CodexMonitorAPI: Sendable {
         return try await call("account_rate_limits", params: .object(["workspaceId": .string(workspaceId)]), as: [RateLimitSnapshot].self)
     }
 
-    public func getCommitMessagePrompt(workspaceId: String) async throws -> String {
-        return try await call("get_commit_message_prompt", params: .object(["workspaceId": .string(workspaceId)]), as: String.self)
+    public func getCommitMessagePrompt(workspaceId: String) async throws -> CommitMessagePrompt {
+        return try await call("get_commit_message_prompt", params: .object(["workspaceId": .string(workspaceId)]), as: CommitMessagePrompt.self)
     }
 
-    public func generateCommitMessage(workspaceId: String) async throws -> String {
-        return try await call("generate_commit_message", params: .object(["workspaceId": .string(workspaceId)]), as: String.self)
+    public func generateCommitMessage(workspaceId: String) async throws -> CommitMessageResult {
+        return try await call("generate_commit_message", params: .object(["workspaceId": .string(workspaceId)]), as: CommitMessageResult.self)
     }
 
     // MARK: - Git
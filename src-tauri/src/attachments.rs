@@ -0,0 +1,251 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+pub(crate) const ATTACHMENTS_DIR: &str = "attachments";
+/// Prefix of attachment ids; `send_user_message` resolves `images` entries
+/// that carry it to the stored file.
+const ATTACHMENT_ID_PREFIX: &str = "att_";
+/// Separates the id from the original filename in the stored file name.
+const NAME_SEPARATOR: &str = "__";
+pub(crate) const MAX_ATTACHMENT_BYTES: usize = 20 * 1024 * 1024;
+pub(crate) const ATTACHMENT_RETENTION_DAYS: u64 = 14;
+const ALLOWED_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "webp", "bmp", "tiff"];
+const MAX_FILENAME_CHARS: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct AttachmentInfo {
+    pub(crate) id: String,
+    /// Absolute path on the machine running the backend.
+    pub(crate) path: String,
+    pub(crate) filename: String,
+    pub(crate) bytes: u64,
+}
+
+/// `<data dir>/attachments/<workspace id>`.
+pub(crate) fn workspace_attachments_dir(data_dir: &Path, workspace_id: &str) -> PathBuf {
+    data_dir.join(ATTACHMENTS_DIR).join(workspace_id)
+}
+
+/// Keeps the last path segment and replaces anything outside `[A-Za-z0-9._-]`.
+pub(crate) fn sanitize_filename(filename: &str) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    let cleaned: String = cleaned.chars().rev().take(MAX_FILENAME_CHARS).collect();
+    let cleaned: String = cleaned.chars().rev().collect();
+    if cleaned.is_empty() {
+        "attachment".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn allowed_extension(filename: &str) -> Result<(), String> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    if ALLOWED_EXTENSIONS.contains(&extension.as_str()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unsupported attachment type: {filename} (allowed: {})",
+            ALLOWED_EXTENSIONS.join(", ")
+        ))
+    }
+}
+
+fn validate_attachment_id(id: &str) -> Result<(), String> {
+    let valid = id
+        .strip_prefix(ATTACHMENT_ID_PREFIX)
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|ch| ch.is_ascii_alphanumeric()));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid attachment id: {id}"))
+    }
+}
+
+pub(crate) fn is_attachment_id(value: &str) -> bool {
+    validate_attachment_id(value).is_ok()
+}
+
+/// Decodes and stores an upload. `data_base64` may be a bare base64 string or
+/// a `data:` URL.
+pub(crate) fn save_attachment(
+    data_dir: &Path,
+    workspace_id: &str,
+    filename: &str,
+    data_base64: &str,
+) -> Result<AttachmentInfo, String> {
+    let filename = sanitize_filename(filename);
+    allowed_extension(&filename)?;
+    let encoded = match data_base64.split_once(";base64,") {
+        Some((prefix, rest)) if prefix.starts_with("data:") => rest,
+        _ => data_base64,
+    };
+    let encoded: String = encoded.chars().filter(|ch| !ch.is_whitespace()).collect();
+    // Base64 grows data by a third, so oversized uploads are refused before decoding.
+    if encoded.len() / 4 * 3 > MAX_ATTACHMENT_BYTES + 3 {
+        return Err(format!(
+            "Attachment is larger than {} MB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+    let data = STANDARD
+        .decode(encoded.as_bytes())
+        .map_err(|err| format!("Invalid attachment data: {err}"))?;
+    if data.len() > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "Attachment is larger than {} MB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+
+    let dir = workspace_attachments_dir(data_dir, workspace_id);
+    std::fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create attachments folder: {err}"))?;
+    let id = format!("{ATTACHMENT_ID_PREFIX}{}", uuid::Uuid::new_v4().simple());
+    let path = dir.join(format!("{id}{NAME_SEPARATOR}{filename}"));
+    std::fs::write(&path, &data).map_err(|err| format!("Failed to write attachment: {err}"))?;
+    Ok(AttachmentInfo {
+        id,
+        path: path.to_string_lossy().to_string(),
+        filename,
+        bytes: data.len() as u64,
+    })
+}
+
+pub(crate) fn resolve_attachment(
+    data_dir: &Path,
+    workspace_id: &str,
+    id: &str,
+) -> Result<PathBuf, String> {
+    validate_attachment_id(id)?;
+    let prefix = format!("{id}{NAME_SEPARATOR}");
+    std::fs::read_dir(workspace_attachments_dir(data_dir, workspace_id))
+        .ok()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .find(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .map(|entry| entry.path())
+        .ok_or_else(|| format!("Attachment not found: {id}"))
+}
+
+/// Replaces attachment ids in a `send_user_message` image list with the
+/// stored paths. Other entries (paths, URLs, data URLs) pass through.
+pub(crate) fn resolve_image_refs(
+    data_dir: &Path,
+    workspace_id: &str,
+    images: Vec<String>,
+) -> Result<Vec<String>, String> {
+    images
+        .into_iter()
+        .map(|image| {
+            let trimmed = image.trim();
+            if is_attachment_id(trimmed) {
+                resolve_attachment(data_dir, workspace_id, trimmed)
+                    .map(|path| path.to_string_lossy().to_string())
+            } else {
+                Ok(image)
+            }
+        })
+        .collect()
+}
+
+/// Removes attachments last modified more than `retention_days` ago, across
+/// all workspaces.
+pub(crate) fn purge_attachments(data_dir: &Path, retention_days: u64, now: SystemTime) -> usize {
+    let Some(cutoff) = now.checked_sub(Duration::from_secs(retention_days * 24 * 60 * 60)) else {
+        return 0;
+    };
+    let Ok(workspaces) = std::fs::read_dir(data_dir.join(ATTACHMENTS_DIR)) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for workspace in workspaces.flatten() {
+        let Ok(entries) = std::fs::read_dir(workspace.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map(|modified| modified < cutoff)
+                .unwrap_or(false);
+            if expired && std::fs::remove_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{purge_attachments, resolve_image_refs, sanitize_filename, save_attachment};
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    #[test]
+    fn filenames_are_sanitized() {
+        assert_eq!(sanitize_filename("../../etc/passwd.png"), "passwd.png");
+        assert_eq!(
+            sanitize_filename("C:\\shots\\my shot (1).PNG"),
+            "my_shot__1_.PNG"
+        );
+        assert_eq!(sanitize_filename(".hidden.png"), "hidden.png");
+        assert_eq!(sanitize_filename("///"), "attachment");
+    }
+
+    #[test]
+    fn saves_resolves_and_purges_attachments() {
+        let data = tempdir().expect("tempdir");
+        let saved = save_attachment(
+            data.path(),
+            "ws",
+            "screen shot.png",
+            "data:image/png;base64,iVBORw0KGgo=",
+        )
+        .expect("save");
+        assert!(saved.id.starts_with("att_"));
+        assert_eq!(saved.filename, "screen_shot.png");
+        assert_eq!(saved.bytes, 8);
+
+        let images = resolve_image_refs(
+            data.path(),
+            "ws",
+            vec![saved.id.clone(), "/tmp/other.png".to_string()],
+        )
+        .expect("resolve");
+        assert_eq!(
+            images,
+            vec![saved.path.clone(), "/tmp/other.png".to_string()]
+        );
+        assert!(resolve_image_refs(data.path(), "other-ws", vec![saved.id.clone()]).is_err());
+
+        assert!(save_attachment(data.path(), "ws", "run.sh", "ZWNobw==")
+            .unwrap_err()
+            .contains("Unsupported"));
+        assert!(save_attachment(data.path(), "ws", "x.png", "not base64!").is_err());
+
+        assert_eq!(purge_attachments(data.path(), 14, SystemTime::now()), 0);
+        let later = SystemTime::now() + Duration::from_secs(15 * 24 * 60 * 60);
+        assert_eq!(purge_attachments(data.path(), 14, later), 1);
+        assert!(resolve_image_refs(data.path(), "ws", vec![saved.id]).is_err());
+    }
+}
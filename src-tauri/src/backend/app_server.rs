@@ -10,10 +10,19 @@ use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, Command};
 use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
 
+use crate::backend::capabilities::{probe_optional_methods, SessionCapabilities};
 use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::keepalive::{run_keepalive, SessionHealth};
+use crate::backend::spawn_diagnostics::{clear_spawn_failure, record_spawn_failure, SpawnCapture};
+use crate::backend::supervisor::spawn_guarded;
+use crate::backend::turn_budget::{run_turn_budgets, TurnBudgets};
+use crate::thread_activity;
+use crate::turn_changes;
 use crate::types::WorkspaceEntry;
+use crate::usage_overview;
 
 fn extract_thread_id(value: &Value) -> Option<String> {
     value
@@ -31,6 +40,14 @@ pub(crate) struct WorkspaceSession {
     pub(crate) next_id: AtomicU64,
     /// Callbacks for background threads - events for these threadIds are sent through the channel
     pub(crate) background_thread_callbacks: Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>,
+    /// Server version and which optional methods it implements.
+    pub(crate) capabilities: Mutex<SessionCapabilities>,
+    /// Resolved codex args the process was started with.
+    pub(crate) codex_args: Option<String>,
+    /// Last traffic, streaming turns and keep-alive probe results.
+    pub(crate) health: SessionHealth,
+    /// Timers of turns with a `maxTurnDurationMinutes` budget.
+    pub(crate) turn_budgets: TurnBudgets,
 }
 
 impl WorkspaceSession {
@@ -44,6 +61,23 @@ impl WorkspaceSession {
             .map_err(|e| e.to_string())
     }
 
+    /// Like `send_request` for methods in `OPTIONAL_METHODS`: fails with a
+    /// `capability_unsupported` error instead of the server's unknown-method
+    /// response, and remembers the outcome for `session_capabilities`.
+    pub(crate) async fn send_optional_request(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, String> {
+        self.capabilities.lock().await.ensure(method)?;
+        let response = self.send_request(method, params).await?;
+        let mut capabilities = self.capabilities.lock().await;
+        if !capabilities.record(method, &response) {
+            return Err(capabilities.unsupported_error(method));
+        }
+        Ok(response)
+    }
+
     pub(crate) async fn send_request(&self, method: &str, params: Value) -> Result<Value, String> {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         let (tx, rx) = oneshot::channel();
@@ -53,6 +87,25 @@ impl WorkspaceSession {
         rx.await.map_err(|_| "request canceled".to_string())
     }
 
+    /// Sends `method` and waits up to `limit` for any reply. An unanswered
+    /// request is dropped from `pending`.
+    pub(crate) async fn probe(&self, method: &str, params: Value, limit: Duration) -> bool {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+        let answered = timeout(limit, async {
+            self.write_message(json!({ "id": id, "method": method, "params": params }))
+                .await?;
+            rx.await.map_err(|_| "request canceled".to_string())
+        })
+        .await;
+        if matches!(answered, Ok(Ok(_))) {
+            return true;
+        }
+        self.pending.lock().await.remove(&id);
+        false
+    }
+
     pub(crate) async fn send_notification(
         &self,
         method: &str,
@@ -185,6 +238,63 @@ pub(crate) async fn check_codex_installation(
     })
 }
 
+struct LaunchError {
+    message: String,
+    exit_status: Option<String>,
+}
+
+impl From<String> for LaunchError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            exit_status: None,
+        }
+    }
+}
+
+impl From<&str> for LaunchError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// Stops a child that failed the handshake and gives the readers a moment to
+/// drain what it printed on the way out.
+async fn abort_launch(
+    session: &WorkspaceSession,
+    readers: Vec<JoinHandle<()>>,
+    message: &str,
+) -> LaunchError {
+    let exit_status = {
+        let mut child = session.child.lock().await;
+        match timeout(Duration::from_millis(500), child.wait()).await {
+            Ok(Ok(status)) => Some(
+                status
+                    .code()
+                    .map(|code| code.to_string())
+                    .unwrap_or_else(|| status.to_string()),
+            ),
+            _ => {
+                let _ = child.kill().await;
+                None
+            }
+        }
+    };
+    let _ = timeout(Duration::from_secs(1), async {
+        for reader in readers {
+            let _ = reader.await;
+        }
+    })
+    .await;
+    LaunchError {
+        message: message.to_string(),
+        exit_status,
+    }
+}
+
+/// Starts `codex app-server` for a workspace. On failure the error carries the
+/// child's early stderr/stdout, and the failure is kept per workspace for
+/// `workspace_doctor`.
 pub(crate) async fn spawn_workspace_session<E: EventSink>(
     entry: WorkspaceEntry,
     default_codex_bin: Option<String>,
@@ -193,6 +303,41 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     client_version: String,
     event_sink: E,
 ) -> Result<Arc<WorkspaceSession>, String> {
+    let workspace_id = entry.id.clone();
+    let capture = SpawnCapture::new();
+    match launch_workspace_session(
+        entry,
+        default_codex_bin,
+        codex_args,
+        codex_home,
+        client_version,
+        event_sink,
+        capture.clone(),
+    )
+    .await
+    {
+        Ok(session) => {
+            clear_spawn_failure(&workspace_id);
+            Ok(session)
+        }
+        Err(err) => {
+            let (stderr, stdout) = capture.finish();
+            let failure =
+                record_spawn_failure(&workspace_id, err.message, stderr, stdout, err.exit_status);
+            Err(failure.describe())
+        }
+    }
+}
+
+async fn launch_workspace_session<E: EventSink>(
+    entry: WorkspaceEntry,
+    default_codex_bin: Option<String>,
+    codex_args: Option<String>,
+    codex_home: Option<PathBuf>,
+    client_version: String,
+    event_sink: E,
+    capture: SpawnCapture,
+) -> Result<Arc<WorkspaceSession>, LaunchError> {
     let codex_bin = entry
         .codex_bin
         .clone()
@@ -211,7 +356,9 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     command.stdout(std::process::Stdio::piped());
     command.stderr(std::process::Stdio::piped());
 
-    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start Codex app-server: {e}"))?;
     let stdin = child.stdin.take().ok_or("missing stdin")?;
     let stdout = child.stdout.take().ok_or("missing stdout")?;
     let stderr = child.stderr.take().ok_or("missing stderr")?;
@@ -223,17 +370,23 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         pending: Mutex::new(HashMap::new()),
         next_id: AtomicU64::new(1),
         background_thread_callbacks: Mutex::new(HashMap::new()),
+        capabilities: Mutex::new(SessionCapabilities::default()),
+        codex_args,
+        health: SessionHealth::default(),
+        turn_budgets: TurnBudgets::default(),
     });
 
     let session_clone = Arc::clone(&session);
     let workspace_id = entry.id.clone();
     let event_sink_clone = event_sink.clone();
-    tokio::spawn(async move {
+    let stdout_capture = capture.clone();
+    let stdout_reader = spawn_guarded(event_sink.clone(), "app-server reader", async move {
         let mut lines = BufReader::new(stdout).lines();
         while let Ok(Some(line)) = lines.next_line().await {
             if line.trim().is_empty() {
                 continue;
             }
+            stdout_capture.push_stdout(&line);
             eprintln!("[app-server stdout] {line}");
             let value: Value = match serde_json::from_str(&line) {
                 Ok(value) => value,
@@ -256,6 +409,19 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
 
             // Check if this event is for a background thread
             let thread_id = extract_thread_id(&value);
+            let method = value.get("method").and_then(|method| method.as_str());
+            session_clone
+                .health
+                .record_message(method, thread_id.as_deref());
+            session_clone
+                .turn_budgets
+                .record_message(method, thread_id.as_deref(), &value);
+            if let (Some(tid), Some(method)) = (thread_id.as_deref(), method) {
+                thread_activity::record_notification(&workspace_id, tid, method);
+                thread_activity::record_review_event(&workspace_id, tid, &value);
+            }
+            turn_changes::record_turn_message(&workspace_id, &session_clone.entry.path, &value);
+            usage_overview::record_rate_limits(&workspace_id, &value);
 
             if let Some(id) = maybe_id {
                 if has_result_or_error {
@@ -303,16 +469,22 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                 }
             }
         }
+        // The child is gone; fail outstanding requests instead of leaving them
+        // waiting forever.
+        session_clone.health.mark_exited();
+        session_clone.pending.lock().await.clear();
     });
 
     let workspace_id = entry.id.clone();
     let event_sink_clone = event_sink.clone();
-    tokio::spawn(async move {
+    let stderr_capture = capture.clone();
+    let stderr_reader = tokio::spawn(async move {
         let mut lines = BufReader::new(stderr).lines();
         while let Ok(Some(line)) = lines.next_line().await {
             if line.trim().is_empty() {
                 continue;
             }
+            stderr_capture.push_stderr(&line);
             let payload = AppServerEvent {
                 workspace_id: workspace_id.clone(),
                 message: json!({
@@ -336,19 +508,41 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         session.send_request("initialize", init_params),
     )
     .await;
-    let init_response = match init_result {
-        Ok(response) => response,
+    let readers = vec![stdout_reader, stderr_reader];
+    match init_result {
+        Ok(Ok(response)) => {
+            *session.capabilities.lock().await = SessionCapabilities::from_initialize(&response);
+        }
+        Ok(Err(_)) => {
+            return Err(abort_launch(
+                &session,
+                readers,
+                "Codex app-server exited before completing initialize.",
+            )
+            .await);
+        }
         Err(_) => {
-            let mut child = session.child.lock().await;
-            let _ = child.kill().await;
-            return Err(
-                "Codex app-server did not respond to initialize. Check that `codex app-server` works in Terminal."
-                    .to_string(),
-            );
+            return Err(abort_launch(
+                &session,
+                readers,
+                "Codex app-server did not respond to initialize. Check that `codex app-server` works in Terminal.",
+            )
+            .await);
         }
-    };
-    init_response?;
-    session.send_notification("initialized", None).await?;
+    }
+    if session
+        .send_notification("initialized", None)
+        .await
+        .is_err()
+    {
+        return Err(abort_launch(
+            &session,
+            readers,
+            "Codex app-server exited before completing initialize.",
+        )
+        .await);
+    }
+    capture.finish();
 
     let payload = AppServerEvent {
         workspace_id: entry.id.clone(),
@@ -358,6 +552,12 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         }),
     };
     event_sink.emit_app_server_event(payload);
+    tokio::spawn(run_keepalive(Arc::downgrade(&session), event_sink.clone()));
+    tokio::spawn(run_turn_budgets(
+        Arc::downgrade(&session),
+        event_sink.clone(),
+    ));
+    tokio::spawn(probe_optional_methods(Arc::clone(&session), event_sink));
 
     Ok(session)
 }
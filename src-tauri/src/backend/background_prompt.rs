@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::codex_params::build_turn_start_params;
+
+const COMMIT_MESSAGE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Thread id from a `thread/start` response, wherever this app-server
+/// version puts it.
+pub(crate) fn thread_id_from_start_response(response: &Value) -> Option<String> {
+    response
+        .get("result")
+        .and_then(|r| r.get("threadId"))
+        .or_else(|| {
+            response
+                .get("result")
+                .and_then(|r| r.get("thread"))
+                .and_then(|t| t.get("id"))
+        })
+        .or_else(|| response.get("threadId"))
+        .or_else(|| response.get("thread").and_then(|t| t.get("id")))
+        .and_then(|t| t.as_str())
+        .map(|id| id.to_string())
+}
+
+fn response_error(response: &Value, fallback: &str) -> Option<String> {
+    response.get("error").map(|error| {
+        error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or(fallback)
+            .to_string()
+    })
+}
+
+async fn release_thread(session: &WorkspaceSession, thread_id: &str) {
+    session
+        .background_thread_callbacks
+        .lock()
+        .await
+        .remove(thread_id);
+    let _ = session
+        .send_request("thread/archive", json!({ "threadId": thread_id }))
+        .await;
+}
+
+/// Runs `prompt` in a throwaway read-only thread and returns the agent's reply.
+/// The thread never shows up in the UI: its events go to a callback channel and
+/// it is archived afterwards, whether the turn succeeded or not.
+pub(crate) async fn run_background_prompt(
+    session: &WorkspaceSession,
+    prompt: &str,
+    timeout: Duration,
+    task: &str,
+) -> Result<String, String> {
+    let thread_result = session
+        .send_request(
+            "thread/start",
+            json!({ "cwd": session.entry.path, "approvalPolicy": "never" }),
+        )
+        .await?;
+    if let Some(error) = response_error(&thread_result, "Unknown error starting thread") {
+        return Err(error);
+    }
+    let thread_id = thread_id_from_start_response(&thread_result).ok_or_else(|| {
+        format!(
+            "Failed to get threadId from thread/start response: {:?}",
+            thread_result
+        )
+    })?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    session
+        .background_thread_callbacks
+        .lock()
+        .await
+        .insert(thread_id.clone(), tx);
+
+    let turn_params = build_turn_start_params(
+        &thread_id,
+        vec![json!({ "type": "text", "text": prompt })],
+        &session.entry.path,
+        "never",
+        json!({ "type": "readOnly" }),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )?;
+    let turn_result = match session.send_request("turn/start", turn_params).await {
+        Ok(result) => result,
+        Err(error) => {
+            release_thread(session, &thread_id).await;
+            return Err(error);
+        }
+    };
+    if let Some(error) = response_error(&turn_result, "Unknown error starting turn") {
+        release_thread(session, &thread_id).await;
+        return Err(error);
+    }
+
+    let mut reply = String::new();
+    let collect_result = tokio::time::timeout(timeout, async {
+        while let Some(event) = rx.recv().await {
+            let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
+            match method {
+                "item/agentMessage/delta" => {
+                    if let Some(delta) = event.pointer("/params/delta").and_then(|d| d.as_str()) {
+                        reply.push_str(delta);
+                    }
+                }
+                "turn/completed" => break,
+                "turn/error" => {
+                    let error_msg = event
+                        .pointer("/params/error")
+                        .and_then(|e| e.as_str())
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| format!("Unknown error during {task}"));
+                    return Err(error_msg);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    })
+    .await;
+
+    release_thread(session, &thread_id).await;
+
+    match collect_result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => return Err(e),
+        Err(_) => return Err(format!("Timeout waiting for {task}")),
+    }
+
+    let trimmed = reply.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(format!("No output from {task}"));
+    }
+    Ok(trimmed)
+}
+
+/// Asks the agent for a commit message describing `diff`.
+pub(crate) async fn generate_commit_message_for_diff(
+    session: &WorkspaceSession,
+    diff: &str,
+) -> Result<String, String> {
+    let prompt = format!(
+        "Generate a concise git commit message for the following changes. \
+Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
+Focus on the 'why' rather than the 'what'. Keep the summary line under 72 characters. \
+Only output the commit message, nothing else.\n\n\
+Changes:\n{diff}"
+    );
+    run_background_prompt(
+        session,
+        &prompt,
+        COMMIT_MESSAGE_TIMEOUT,
+        "commit message generation",
+    )
+    .await
+}
@@ -0,0 +1,79 @@
+//! Synchronous libgit2 and filesystem work, moved off the async runtime. The
+//! daemon serves every connection from one thread, so a status call on a
+//! large repository would otherwise hold up unrelated requests. Git work also
+//! takes a permit from a shared semaphore so a burst of status polls queues
+//! instead of filling the blocking pool.
+
+use std::sync::{Arc, OnceLock};
+
+use tokio::sync::Semaphore;
+
+/// Git operations allowed on the blocking pool at once.
+const MAX_CONCURRENT_GIT: usize = 4;
+
+static GIT_PERMITS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+fn git_permits() -> Arc<Semaphore> {
+    Arc::clone(GIT_PERMITS.get_or_init(|| Arc::new(Semaphore::new(MAX_CONCURRENT_GIT))))
+}
+
+/// Runs `work` on the blocking pool.
+pub(crate) async fn run_blocking<T, F>(work: F) -> Result<T, String>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Runs fallible git work on the blocking pool once a permit is free. The
+/// permit is held until `work` returns, even if the caller stops waiting.
+pub(crate) async fn run_git_blocking<T, F>(work: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    let permit = git_permits()
+        .acquire_owned()
+        .await
+        .map_err(|err| err.to_string())?;
+    run_blocking(move || {
+        let result = work();
+        drop(permit);
+        result
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_git_blocking, MAX_CONCURRENT_GIT};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn git_work_never_exceeds_the_permit_count() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..MAX_CONCURRENT_GIT * 3)
+            .map(|_| {
+                let running = Arc::clone(&running);
+                let peak = Arc::clone(&peak);
+                tokio::spawn(run_git_blocking(move || {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                }))
+            })
+            .collect();
+        for task in tasks {
+            task.await.expect("join").expect("git work");
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), MAX_CONCURRENT_GIT);
+    }
+}
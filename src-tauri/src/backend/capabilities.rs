@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::time::timeout;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+
+pub(crate) const CAPABILITY_UNSUPPORTED_ERROR: &str = "capability_unsupported";
+pub(crate) const UNSUPPORTED_REVIEW_DELIVERY_ERROR: &str = "unsupported_review_delivery";
+
+/// App-server methods that only some codex versions implement.
+pub(crate) const OPTIONAL_METHODS: &[&str] = &[
+    "collaborationMode/list",
+    "review/start",
+    "skills/config/write",
+];
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// A `delivery` no server accepts; the error names the ones it does.
+const REVIEW_DELIVERY_PROBE: &str = "codex-monitor-probe";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub(crate) struct SessionCapabilities {
+    #[serde(rename = "codexVersion")]
+    pub(crate) codex_version: Option<String>,
+    #[serde(rename = "userAgent")]
+    pub(crate) user_agent: Option<String>,
+    /// Optional method -> supported. Methods not probed yet are missing and
+    /// treated as supported.
+    pub(crate) methods: BTreeMap<String, bool>,
+    /// `review/start` delivery modes the server accepts. Missing until probed,
+    /// and then any delivery is passed through; empty when the server takes
+    /// no `delivery` at all.
+    #[serde(
+        default,
+        rename = "reviewDeliveryModes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) review_delivery_modes: Option<Vec<String>>,
+}
+
+impl SessionCapabilities {
+    /// Reads the server version from the `initialize` response.
+    pub(crate) fn from_initialize(response: &Value) -> Self {
+        let user_agent = response
+            .pointer("/result/userAgent")
+            .or_else(|| response.get("userAgent"))
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string());
+        Self {
+            codex_version: user_agent.as_deref().and_then(parse_codex_version),
+            user_agent,
+            methods: BTreeMap::new(),
+            review_delivery_modes: None,
+        }
+    }
+
+    pub(crate) fn ensure(&self, method: &str) -> Result<(), String> {
+        match self.methods.get(method) {
+            Some(false) => Err(self.unsupported_error(method)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records what a response says about `method`; returns whether it is
+    /// supported.
+    pub(crate) fn record(&mut self, method: &str, response: &Value) -> bool {
+        let supported = !is_unknown_method_response(response);
+        self.methods.insert(method.to_string(), supported);
+        supported
+    }
+
+    /// Rejects a `delivery` the server is known not to accept.
+    pub(crate) fn check_review_delivery(&self, delivery: &str) -> Result<(), String> {
+        match self.review_delivery_modes.as_ref() {
+            Some(modes) if !modes.iter().any(|mode| mode == delivery) => Err(format!(
+                "{UNSUPPORTED_REVIEW_DELIVERY_ERROR}: {}",
+                json!({ "delivery": delivery, "supported": modes })
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn unsupported_error(&self, method: &str) -> String {
+        let version = self.codex_version.as_deref().unwrap_or("unknown");
+        format!("{CAPABILITY_UNSUPPORTED_ERROR}: `{method}` is not supported by codex {version}")
+    }
+}
+
+/// `codex_cli_rs/0.77.0 (Mac OS 15.1; arm64) ...` -> `0.77.0`.
+pub(crate) fn parse_codex_version(user_agent: &str) -> Option<String> {
+    let product = user_agent.split_whitespace().next()?;
+    let (_, version) = product.split_once('/')?;
+    version
+        .starts_with(|ch: char| ch.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+/// The app-server answers unknown methods with a JSON-RPC error; older builds
+/// report them as an unknown request variant instead of -32601.
+pub(crate) fn is_unknown_method_response(response: &Value) -> bool {
+    let Some(error) = response.get("error") else {
+        return false;
+    };
+    if error.get("code").and_then(|code| code.as_i64()) == Some(-32601) {
+        return true;
+    }
+    let message = error
+        .get("message")
+        .and_then(|message| message.as_str())
+        .unwrap_or("")
+        .to_lowercase();
+    ["unknown variant", "method not found", "unknown method"]
+        .iter()
+        .any(|needle| message.contains(needle))
+}
+
+/// Reads the delivery modes out of the answer to a `review/start` probe.
+/// serde lists the variants of an enum field it could not match; a server that
+/// rejects the field itself takes no delivery. Anything else tells nothing.
+pub(crate) fn parse_review_delivery_modes(response: &Value) -> Option<Vec<String>> {
+    let message = response.pointer("/error/message")?.as_str()?;
+    if message.contains("unknown field `delivery`") {
+        return Some(Vec::new());
+    }
+    let marker = format!("unknown variant `{REVIEW_DELIVERY_PROBE}`, expected ");
+    let (_, expected) = message.split_once(&marker)?;
+    let modes: Vec<String> = expected
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string)
+        .collect();
+    (!modes.is_empty()).then_some(modes)
+}
+
+/// Calls every optional method with `null` params. A server that knows the
+/// method rejects the params (or answers a harmless list), one that doesn't
+/// reports an unknown method, and nothing is changed either way. Emits
+/// `codex/capabilities` when done.
+pub(crate) async fn probe_optional_methods<E: EventSink>(
+    session: Arc<WorkspaceSession>,
+    event_sink: E,
+) {
+    for method in OPTIONAL_METHODS {
+        let Ok(Ok(response)) =
+            timeout(PROBE_TIMEOUT, session.send_request(method, Value::Null)).await
+        else {
+            continue;
+        };
+        session.capabilities.lock().await.record(method, &response);
+    }
+    let review_supported = session.capabilities.lock().await.ensure("review/start");
+    if review_supported.is_ok() {
+        let probe =
+            session.send_request("review/start", json!({ "delivery": REVIEW_DELIVERY_PROBE }));
+        if let Ok(Ok(response)) = timeout(PROBE_TIMEOUT, probe).await {
+            if let Some(modes) = parse_review_delivery_modes(&response) {
+                session.capabilities.lock().await.review_delivery_modes = Some(modes);
+            }
+        }
+    }
+    let capabilities = session.capabilities.lock().await.clone();
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: session.entry.id.clone(),
+        message: json!({
+            "method": "codex/capabilities",
+            "params": { "workspaceId": session.entry.id, "capabilities": capabilities },
+        }),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_unknown_method_response, parse_codex_version, parse_review_delivery_modes,
+        SessionCapabilities,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn reads_version_from_user_agent() {
+        assert_eq!(
+            parse_codex_version("codex_cli_rs/0.77.0 (Mac OS 15.1.0; arm64) iTerm.app/3.5"),
+            Some("0.77.0".to_string())
+        );
+        assert_eq!(parse_codex_version("codex_cli_rs"), None);
+        let capabilities = SessionCapabilities::from_initialize(
+            &json!({ "id": 1, "result": { "userAgent": "codex_vscode/1.2.3" } }),
+        );
+        assert_eq!(capabilities.codex_version.as_deref(), Some("1.2.3"));
+    }
+
+    #[test]
+    fn unknown_methods_are_recorded_as_unsupported() {
+        let mut capabilities = SessionCapabilities {
+            codex_version: Some("0.40.0".to_string()),
+            ..SessionCapabilities::default()
+        };
+        let unknown = json!({ "id": 2, "error": {
+            "code": -32600,
+            "message": "Invalid request: unknown variant `review/start`, expected one of ..."
+        }});
+        let invalid_params = json!({ "id": 3, "error": {
+            "code": -32600,
+            "message": "Invalid request: invalid type: null, expected struct"
+        }});
+        assert!(is_unknown_method_response(&unknown));
+        assert!(!is_unknown_method_response(&invalid_params));
+
+        assert!(capabilities.ensure("review/start").is_ok());
+        assert!(!capabilities.record("review/start", &unknown));
+        assert!(capabilities.record("skills/config/write", &invalid_params));
+        assert_eq!(
+            capabilities.ensure("review/start").unwrap_err(),
+            "capability_unsupported: `review/start` is not supported by codex 0.40.0"
+        );
+        assert!(capabilities.ensure("skills/config/write").is_ok());
+    }
+
+    #[test]
+    fn review_delivery_is_checked_against_the_probed_modes() {
+        let variants = json!({ "id": 4, "error": {
+            "code": -32600,
+            "message": "Invalid request: unknown variant `codex-monitor-probe`, expected `inline` or `detached`"
+        }});
+        let mut capabilities = SessionCapabilities::default();
+        assert!(capabilities.check_review_delivery("summary").is_ok());
+
+        capabilities.review_delivery_modes = parse_review_delivery_modes(&variants);
+        assert_eq!(
+            capabilities.review_delivery_modes,
+            Some(vec!["inline".to_string(), "detached".to_string()])
+        );
+        assert!(capabilities.check_review_delivery("detached").is_ok());
+        assert_eq!(
+            capabilities.check_review_delivery("summary").unwrap_err(),
+            r#"unsupported_review_delivery: {"delivery":"summary","supported":["inline","detached"]}"#
+        );
+
+        let no_field =
+            json!({ "error": { "message": "unknown field `delivery`, expected `threadId`" } });
+        assert_eq!(parse_review_delivery_modes(&no_field), Some(Vec::new()));
+        let missing = json!({ "error": { "message": "missing field `threadId`" } });
+        assert_eq!(parse_review_delivery_modes(&missing), None);
+    }
+}
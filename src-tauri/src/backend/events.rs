@@ -16,7 +16,56 @@ pub(crate) struct TerminalOutput {
     pub(crate) data: String,
 }
 
+/// The shell of a terminal exited on its own and the session was dropped.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalExited {
+    pub(crate) workspace_id: String,
+    pub(crate) terminal_id: String,
+    /// `None` when the exit status could not be read.
+    pub(crate) exit_code: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum QuickCommandStream {
+    Stdout,
+    Stderr,
+    /// Last event of an execution; carries `exitCode` or `error`.
+    Exit,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuickCommandOutput {
+    pub(crate) workspace_id: String,
+    pub(crate) command_id: String,
+    pub(crate) execution_id: String,
+    pub(crate) stream: QuickCommandStream,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub(crate) data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) cancelled: bool,
+}
+
+/// A backend subsystem panicked. `restarted` is set when it was started again;
+/// `degraded` when it kept failing and was given up on.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct BackendError {
+    pub(crate) subsystem: String,
+    pub(crate) message: String,
+    pub(crate) restarted: bool,
+    pub(crate) degraded: bool,
+}
+
 pub(crate) trait EventSink: Clone + Send + Sync + 'static {
     fn emit_app_server_event(&self, event: AppServerEvent);
     fn emit_terminal_output(&self, event: TerminalOutput);
+    fn emit_terminal_exited(&self, event: TerminalExited);
+    fn emit_quick_command_output(&self, event: QuickCommandOutput);
+    fn emit_backend_error(&self, event: BackendError);
 }
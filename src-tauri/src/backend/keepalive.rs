@@ -0,0 +1,378 @@
+//! Liveness probe for app-server sessions. A pipe can stay open after the
+//! server stopped reading it; a session that has been quiet for the configured
+//! interval is sent a cheap request, and after enough unanswered probes it is
+//! reported as unhealthy and, with `autoReconnect`, restarted. A session whose
+//! process has exited lists as stale and is dropped the first time it is seen.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::types::{ConnectionState, KeepaliveSettings};
+
+pub(crate) const UNHEALTHY_METHOD: &str = "codex/unhealthy";
+pub(crate) const HEALTHY_METHOD: &str = "codex/healthy";
+pub(crate) const DISCONNECTED_METHOD: &str = "codex/disconnected";
+
+/// Any reply proves the server is reading its input, so an error response
+/// counts too.
+const PROBE_METHOD: &str = "model/list";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often a disabled keep-alive checks whether it was turned on.
+const DISABLED_POLL: Duration = Duration::from_secs(60);
+
+static SETTINGS: OnceLock<Mutex<KeepaliveSettings>> = OnceLock::new();
+static RESTART_HOOK: OnceLock<Box<dyn Fn(String) + Send + Sync>> = OnceLock::new();
+
+fn settings_slot() -> &'static Mutex<KeepaliveSettings> {
+    SETTINGS.get_or_init(|| Mutex::new(KeepaliveSettings::default()))
+}
+
+/// Applies the keep-alive settings to every running and future session.
+pub(crate) fn configure_keepalive(settings: &KeepaliveSettings) {
+    *settings_slot()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner()) = settings.clone();
+}
+
+fn current_settings() -> KeepaliveSettings {
+    settings_slot()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// Installs what `autoReconnect` does with an unhealthy workspace id. Only the
+/// first hook is kept.
+pub(crate) fn set_restart_hook(hook: impl Fn(String) + Send + Sync + 'static) {
+    let _ = RESTART_HOOK.set(Box::new(hook));
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HealthChange {
+    BecameUnhealthy,
+    Recovered,
+}
+
+/// What the stdout reader has seen of a session, and the probe results.
+#[derive(Debug, Default)]
+pub(crate) struct SessionHealth {
+    last_message_ms: AtomicI64,
+    /// Threads with a turn in progress; probes are paused while any streams.
+    active_turns: Mutex<HashSet<String>>,
+    failures: AtomicU32,
+    unhealthy: AtomicBool,
+    /// Set when the stdout reader hits end of file.
+    exited: AtomicBool,
+}
+
+impl SessionHealth {
+    pub(crate) fn record_message(&self, method: Option<&str>, thread_id: Option<&str>) {
+        self.last_message_ms.store(now_ms(), Ordering::Relaxed);
+        let (Some(method), Some(thread_id)) = (method, thread_id) else {
+            return;
+        };
+        let mut turns = self
+            .active_turns
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        match method {
+            "turn/started" => {
+                turns.insert(thread_id.to_string());
+            }
+            "turn/completed" | "turn/error" => {
+                turns.remove(thread_id);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn is_unhealthy(&self) -> bool {
+        self.unhealthy.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn mark_exited(&self) {
+        self.exited.store(true, Ordering::Relaxed);
+    }
+
+    fn should_probe(&self, now_ms: i64, interval: Duration) -> bool {
+        let idle_ms = now_ms - self.last_message_ms.load(Ordering::Relaxed);
+        idle_ms >= interval.as_millis() as i64
+            && self
+                .active_turns
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .is_empty()
+    }
+
+    /// Counts a probe result; returns the transition it caused, if any.
+    fn record_probe(&self, answered: bool, failure_threshold: u32) -> Option<HealthChange> {
+        if answered {
+            self.failures.store(0, Ordering::Relaxed);
+            return self
+                .unhealthy
+                .swap(false, Ordering::Relaxed)
+                .then_some(HealthChange::Recovered);
+        }
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < failure_threshold.max(1) {
+            return None;
+        }
+        (!self.unhealthy.swap(true, Ordering::Relaxed)).then_some(HealthChange::BecameUnhealthy)
+    }
+}
+
+/// Whether the session's process is gone. Polls the child without waiting for
+/// it; a child locked by someone else counts as alive.
+fn has_exited(session: &WorkspaceSession) -> bool {
+    if session.health.exited.load(Ordering::Relaxed) {
+        return true;
+    }
+    session
+        .child
+        .try_lock()
+        .is_ok_and(|mut child| !matches!(child.try_wait(), Ok(None)))
+}
+
+/// `connected` for `list_workspaces` from the workspace's session, if any.
+pub(crate) fn connection_state(session: Option<&Arc<WorkspaceSession>>) -> ConnectionState {
+    match session {
+        Some(session) if has_exited(session) => ConnectionState::Stale,
+        Some(session) if session.health.is_unhealthy() => ConnectionState::Unhealthy,
+        Some(_) => ConnectionState::Connected,
+        None => ConnectionState::Disconnected,
+    }
+}
+
+/// Removes sessions whose process has exited and emits `codex/disconnected`
+/// for each. Returns the removed workspace ids.
+pub(crate) fn remove_stale_sessions<E: EventSink>(
+    sessions: &mut HashMap<String, Arc<WorkspaceSession>>,
+    event_sink: &E,
+) -> Vec<String> {
+    let stale: Vec<String> = sessions
+        .iter()
+        .filter(|(_, session)| has_exited(session))
+        .map(|(workspace_id, _)| workspace_id.clone())
+        .collect();
+    for workspace_id in &stale {
+        sessions.remove(workspace_id);
+        emit_disconnected(event_sink, workspace_id, "exited");
+    }
+    stale
+}
+
+/// Tells clients a workspace's session is gone, e.g. `exited` or
+/// `daemon_shutdown`.
+pub(crate) fn emit_disconnected<E: EventSink>(event_sink: &E, workspace_id: &str, reason: &str) {
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": DISCONNECTED_METHOD,
+            "params": { "workspaceId": workspace_id, "reason": reason },
+        }),
+    });
+}
+
+/// Probes the session until it is dropped or its process exits.
+pub(crate) async fn run_keepalive<E: EventSink>(session: Weak<WorkspaceSession>, event_sink: E) {
+    loop {
+        let settings = current_settings();
+        let interval = if settings.interval_secs == 0 {
+            DISABLED_POLL
+        } else {
+            Duration::from_secs(settings.interval_secs)
+        };
+        tokio::time::sleep(interval).await;
+        let Some(session) = session.upgrade() else {
+            return;
+        };
+        if !matches!(session.child.lock().await.try_wait(), Ok(None)) {
+            return;
+        }
+        if settings.interval_secs == 0 || !session.health.should_probe(now_ms(), interval) {
+            continue;
+        }
+        let answered = session.probe(PROBE_METHOD, json!({}), PROBE_TIMEOUT).await;
+        let workspace_id = session.entry.id.clone();
+        match session
+            .health
+            .record_probe(answered, settings.failure_threshold)
+        {
+            Some(HealthChange::BecameUnhealthy) => {
+                event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id: workspace_id.clone(),
+                    message: json!({
+                        "method": UNHEALTHY_METHOD,
+                        "params": {
+                            "workspaceId": workspace_id,
+                            "failures": session.health.failures.load(Ordering::Relaxed),
+                            "autoReconnect": settings.auto_reconnect,
+                        },
+                    }),
+                });
+                if settings.auto_reconnect {
+                    if let Some(hook) = RESTART_HOOK.get() {
+                        drop(session);
+                        hook(workspace_id);
+                        return;
+                    }
+                }
+            }
+            Some(HealthChange::Recovered) => {
+                event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id: workspace_id.clone(),
+                    message: json!({
+                        "method": HEALTHY_METHOD,
+                        "params": { "workspaceId": workspace_id },
+                    }),
+                });
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        connection_state, remove_stale_sessions, HealthChange, SessionHealth, DISCONNECTED_METHOD,
+    };
+    use crate::backend::app_server::WorkspaceSession;
+    use crate::backend::capabilities::SessionCapabilities;
+    use crate::backend::events::{
+        AppServerEvent, BackendError, EventSink, QuickCommandOutput, TerminalExited, TerminalOutput,
+    };
+    use crate::backend::turn_budget::TurnBudgets;
+    use crate::types::{ConnectionState, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        events: Arc<Mutex<Vec<AppServerEvent>>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, event: AppServerEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exited(&self, _event: TerminalExited) {}
+        fn emit_quick_command_output(&self, _event: QuickCommandOutput) {}
+        fn emit_backend_error(&self, _event: BackendError) {}
+    }
+
+    fn session_for(mut child: tokio::process::Child) -> Arc<WorkspaceSession> {
+        let stdin = child.stdin.take().expect("stdin");
+        Arc::new(WorkspaceSession {
+            entry: WorkspaceEntry {
+                id: "ws-1".to_string(),
+                name: "Demo".to_string(),
+                path: "/repos/demo".to_string(),
+                codex_bin: None,
+                kind: WorkspaceKind::Main,
+                parent_id: None,
+                worktree: None,
+                settings: WorkspaceSettings::default(),
+            },
+            child: tokio::sync::Mutex::new(child),
+            stdin: tokio::sync::Mutex::new(stdin),
+            pending: tokio::sync::Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            background_thread_callbacks: tokio::sync::Mutex::new(HashMap::new()),
+            capabilities: tokio::sync::Mutex::new(SessionCapabilities::default()),
+            codex_args: None,
+            health: SessionHealth::default(),
+            turn_budgets: TurnBudgets::default(),
+        })
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn killed_child_lists_stale_once_then_disconnected() {
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .expect("spawn sleep");
+        let session = session_for(child);
+        let mut sessions = HashMap::from([("ws-1".to_string(), Arc::clone(&session))]);
+        let sink = RecordingSink::default();
+
+        assert_eq!(
+            connection_state(sessions.get("ws-1")),
+            ConnectionState::Connected
+        );
+        assert!(remove_stale_sessions(&mut sessions, &sink).is_empty());
+
+        session.child.lock().await.kill().await.expect("kill");
+        assert_eq!(
+            connection_state(sessions.get("ws-1")),
+            ConnectionState::Stale
+        );
+        assert_eq!(remove_stale_sessions(&mut sessions, &sink), ["ws-1"]);
+        assert_eq!(
+            connection_state(sessions.get("ws-1")),
+            ConnectionState::Disconnected
+        );
+        assert!(remove_stale_sessions(&mut sessions, &sink).is_empty());
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].workspace_id, "ws-1");
+        assert_eq!(events[0].message["method"], DISCONNECTED_METHOD);
+        assert_eq!(events[0].message["params"]["reason"], "exited");
+    }
+
+    #[test]
+    fn probes_pause_during_turns_and_after_recent_messages() {
+        let health = SessionHealth::default();
+        let interval = Duration::from_secs(60);
+        assert!(health.should_probe(60_000, interval));
+
+        health.record_message(Some("turn/started"), Some("thread-1"));
+        let seen = health
+            .last_message_ms
+            .load(std::sync::atomic::Ordering::Relaxed);
+        assert!(!health.should_probe(seen + 120_000, interval));
+
+        health.record_message(Some("turn/completed"), Some("thread-1"));
+        let seen = health
+            .last_message_ms
+            .load(std::sync::atomic::Ordering::Relaxed);
+        assert!(!health.should_probe(seen + 30_000, interval));
+        assert!(health.should_probe(seen + 60_000, interval));
+    }
+
+    #[test]
+    fn marks_unhealthy_after_threshold_and_recovers() {
+        let health = SessionHealth::default();
+        assert_eq!(health.record_probe(false, 3), None);
+        assert_eq!(health.record_probe(false, 3), None);
+        assert!(!health.is_unhealthy());
+        assert_eq!(
+            health.record_probe(false, 3),
+            Some(HealthChange::BecameUnhealthy)
+        );
+        assert_eq!(health.record_probe(false, 3), None);
+        assert!(health.is_unhealthy());
+        assert_eq!(health.record_probe(true, 3), Some(HealthChange::Recovered));
+        assert_eq!(health.record_probe(true, 3), None);
+        assert!(!health.is_unhealthy());
+    }
+}
@@ -1,2 +1,10 @@
 pub(crate) mod app_server;
+pub(crate) mod background_prompt;
+pub(crate) mod blocking;
+pub(crate) mod capabilities;
 pub(crate) mod events;
+pub(crate) mod keepalive;
+pub(crate) mod path_health;
+pub(crate) mod spawn_diagnostics;
+pub(crate) mod supervisor;
+pub(crate) mod turn_budget;
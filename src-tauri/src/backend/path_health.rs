@@ -0,0 +1,178 @@
+//! Reachability of workspace paths. `is_dir` on a network mount that went
+//! offline can hang for minutes, so checks run on the blocking pool under a
+//! short timeout and a path that doesn't answer is reported as unavailable
+//! instead of stalling `list_workspaces`. Unavailable workspaces are checked
+//! again in the background and `workspace/pathAvailable` is emitted for each
+//! one that comes back.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::task::JoinSet;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+
+/// Error prefix for methods refused because the workspace path is unavailable.
+pub(crate) const PATH_UNAVAILABLE_ERROR: &str = "path_unavailable";
+pub(crate) const PATH_AVAILABLE_METHOD: &str = "workspace/pathAvailable";
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+struct PathRegistry {
+    /// Workspace id to path, for workspaces whose last check failed.
+    unavailable: HashMap<String, String>,
+    /// Paths whose check is still blocked in the filesystem.
+    in_flight: HashSet<String>,
+}
+
+static REGISTRY: OnceLock<Mutex<PathRegistry>> = OnceLock::new();
+
+fn registry() -> MutexGuard<'static, PathRegistry> {
+    REGISTRY
+        .get_or_init(|| Mutex::new(PathRegistry::default()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+/// Whether `path` is a directory, giving up after `CHECK_TIMEOUT`. A path
+/// whose previous check is still stuck counts as unavailable without tying
+/// up another blocking thread.
+async fn probe_path(path: &str) -> bool {
+    if !registry().in_flight.insert(path.to_string()) {
+        return false;
+    }
+    let owned = path.to_string();
+    let check = tokio::task::spawn_blocking(move || {
+        let is_dir = Path::new(&owned).is_dir();
+        registry().in_flight.remove(&owned);
+        is_dir
+    });
+    matches!(
+        tokio::time::timeout(CHECK_TIMEOUT, check).await,
+        Ok(Ok(true))
+    )
+}
+
+/// Checks `(workspace id, path)` pairs concurrently and returns the ids whose
+/// path is unavailable. Workspaces already marked unavailable are not checked
+/// again here; the background re-check clears them.
+pub(crate) async fn check_workspace_paths(entries: Vec<(String, String)>) -> HashSet<String> {
+    let mut unavailable = HashSet::new();
+    let mut checks = JoinSet::new();
+    {
+        let registry = registry();
+        for (workspace_id, path) in entries {
+            if registry.unavailable.contains_key(&workspace_id) {
+                unavailable.insert(workspace_id);
+            } else {
+                checks.spawn(async move {
+                    let available = probe_path(&path).await;
+                    (workspace_id, path, available)
+                });
+            }
+        }
+    }
+    while let Some(result) = checks.join_next().await {
+        let Ok((workspace_id, path, available)) = result else {
+            continue;
+        };
+        if !available {
+            registry().unavailable.insert(workspace_id.clone(), path);
+            unavailable.insert(workspace_id);
+        }
+    }
+    unavailable
+}
+
+pub(crate) fn is_path_unavailable(workspace_id: &str) -> bool {
+    registry().unavailable.contains_key(workspace_id)
+}
+
+/// Fails fast for a workspace `list_workspaces` marked `pathUnavailable`.
+pub(crate) fn ensure_path_available(workspace_id: &str) -> Result<(), String> {
+    match registry().unavailable.get(workspace_id) {
+        Some(path) => Err(format!(
+            "{PATH_UNAVAILABLE_ERROR}: {path} is not reachable. Reconnect the drive or network share it is on."
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks every unavailable workspace once and returns the ids that answered.
+async fn recheck_unavailable() -> Vec<String> {
+    let entries: Vec<(String, String)> = registry()
+        .unavailable
+        .iter()
+        .map(|(workspace_id, path)| (workspace_id.clone(), path.clone()))
+        .collect();
+    let mut recovered = Vec::new();
+    for (workspace_id, path) in entries {
+        if probe_path(&path).await {
+            registry().unavailable.remove(&workspace_id);
+            recovered.push(workspace_id);
+        }
+    }
+    recovered
+}
+
+/// Re-checks unavailable workspaces every `RECHECK_INTERVAL` for as long as
+/// the process runs.
+pub(crate) async fn run_path_recheck<E: EventSink>(event_sink: E) {
+    let mut interval = tokio::time::interval(RECHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        for workspace_id in recheck_unavailable().await {
+            event_sink.emit_app_server_event(AppServerEvent {
+                workspace_id: workspace_id.clone(),
+                message: json!({
+                    "method": PATH_AVAILABLE_METHOD,
+                    "params": { "workspaceId": workspace_id },
+                }),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_workspace_paths, ensure_path_available, is_path_unavailable, recheck_unavailable,
+    };
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn marks_missing_paths_and_clears_them_when_they_return() {
+        let dir = tempdir().expect("tempdir");
+        let mount = dir.path().join("share");
+        let entries = vec![
+            (
+                "path-health-present".to_string(),
+                dir.path().to_string_lossy().to_string(),
+            ),
+            (
+                "path-health-missing".to_string(),
+                mount.to_string_lossy().to_string(),
+            ),
+        ];
+        let unavailable = check_workspace_paths(entries).await;
+        assert_eq!(unavailable.len(), 1);
+        assert!(unavailable.contains("path-health-missing"));
+        assert!(ensure_path_available("path-health-present").is_ok());
+        let err = ensure_path_available("path-health-missing").expect_err("unavailable");
+        assert!(err.starts_with("path_unavailable: "));
+
+        assert!(!recheck_unavailable()
+            .await
+            .contains(&"path-health-missing".to_string()));
+        std::fs::create_dir(&mount).unwrap();
+        assert!(recheck_unavailable()
+            .await
+            .contains(&"path-health-missing".to_string()));
+        assert!(!is_path_unavailable("path-health-missing"));
+    }
+}
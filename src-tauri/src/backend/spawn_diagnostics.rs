@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// How much of each stream is kept while an app-server is starting up.
+pub(crate) const SPAWN_CAPTURE_LIMIT: usize = 16 * 1024;
+
+#[derive(Default)]
+struct CapturedOutput {
+    stderr: String,
+    stdout: String,
+}
+
+/// Collects the child's stderr and early stdout until the handshake finishes.
+/// Once `finish` is called the readers stop copying lines, so the protocol
+/// stream is untouched for the rest of the session.
+#[derive(Clone)]
+pub(crate) struct SpawnCapture {
+    inner: Arc<Mutex<Option<CapturedOutput>>>,
+}
+
+fn push_capped(buffer: &mut String, line: &str) {
+    let remaining = SPAWN_CAPTURE_LIMIT.saturating_sub(buffer.len());
+    if remaining == 0 {
+        return;
+    }
+    let mut end = line.len().min(remaining.saturating_sub(1));
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    buffer.push_str(&line[..end]);
+    buffer.push('\n');
+}
+
+impl SpawnCapture {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Some(CapturedOutput::default()))),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<CapturedOutput>> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub(crate) fn push_stderr(&self, line: &str) {
+        if let Some(output) = self.lock().as_mut() {
+            push_capped(&mut output.stderr, line);
+        }
+    }
+
+    pub(crate) fn push_stdout(&self, line: &str) {
+        if let Some(output) = self.lock().as_mut() {
+            push_capped(&mut output.stdout, line);
+        }
+    }
+
+    /// Stops capturing and returns what was collected as `(stderr, stdout)`.
+    pub(crate) fn finish(&self) -> (String, String) {
+        self.lock()
+            .take()
+            .map(|output| (output.stderr, output.stdout))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct SpawnFailure {
+    pub(crate) message: String,
+    pub(crate) stderr: String,
+    pub(crate) stdout: String,
+    #[serde(rename = "exitStatus")]
+    pub(crate) exit_status: Option<String>,
+    #[serde(rename = "occurredAtMs")]
+    pub(crate) occurred_at_ms: i64,
+    /// Consecutive launches that failed with this same message.
+    pub(crate) attempts: u32,
+}
+
+impl SpawnFailure {
+    /// The error returned to callers: the summary followed by the captured output.
+    pub(crate) fn describe(&self) -> String {
+        let mut text = if self.attempts > 1 {
+            format!(
+                "{} (same error as the previous attempt, {} in a row)",
+                self.message, self.attempts
+            )
+        } else {
+            self.message.clone()
+        };
+        if let Some(status) = &self.exit_status {
+            text.push_str(&format!("\nExit status: {status}"));
+        }
+        for (label, output) in [("stderr", &self.stderr), ("stdout", &self.stdout)] {
+            let output = output.trim_end();
+            if !output.is_empty() {
+                text.push_str(&format!("\n--- {label} ---\n{output}"));
+            }
+        }
+        text
+    }
+}
+
+static SPAWN_FAILURES: OnceLock<Mutex<HashMap<String, SpawnFailure>>> = OnceLock::new();
+
+fn spawn_failures() -> MutexGuard<'static, HashMap<String, SpawnFailure>> {
+    SPAWN_FAILURES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Remembers the latest failed launch for a workspace and returns it, with
+/// `attempts` counting how many launches in a row hit the same message.
+pub(crate) fn record_spawn_failure(
+    workspace_id: &str,
+    message: String,
+    stderr: String,
+    stdout: String,
+    exit_status: Option<String>,
+) -> SpawnFailure {
+    let mut failures = spawn_failures();
+    let attempts = failures
+        .get(workspace_id)
+        .filter(|previous| previous.message == message)
+        .map(|previous| previous.attempts + 1)
+        .unwrap_or(1);
+    let failure = SpawnFailure {
+        message,
+        stderr,
+        stdout,
+        exit_status,
+        occurred_at_ms: now_millis(),
+        attempts,
+    };
+    failures.insert(workspace_id.to_string(), failure.clone());
+    failure
+}
+
+pub(crate) fn clear_spawn_failure(workspace_id: &str) {
+    spawn_failures().remove(workspace_id);
+}
+
+pub(crate) fn last_spawn_failure(workspace_id: &str) -> Option<SpawnFailure> {
+    spawn_failures().get(workspace_id).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clear_spawn_failure, last_spawn_failure, record_spawn_failure, SpawnCapture,
+        SPAWN_CAPTURE_LIMIT,
+    };
+
+    #[test]
+    fn capture_is_capped_and_stops_after_finish() {
+        let capture = SpawnCapture::new();
+        capture.push_stderr("error: node not found");
+        capture.push_stdout(&"x".repeat(SPAWN_CAPTURE_LIMIT * 2));
+        let (stderr, stdout) = capture.finish();
+        assert_eq!(stderr, "error: node not found\n");
+        assert_eq!(stdout.len(), SPAWN_CAPTURE_LIMIT);
+
+        capture.push_stderr("after handshake");
+        assert_eq!(capture.finish(), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn repeated_failures_are_counted_and_described() {
+        let workspace_id = "spawn-diagnostics-test";
+        let message = "Codex app-server exited before completing initialize.".to_string();
+        record_spawn_failure(
+            workspace_id,
+            message.clone(),
+            String::new(),
+            String::new(),
+            None,
+        );
+        let failure = record_spawn_failure(
+            workspace_id,
+            message,
+            "Error: invalid config.toml\n".to_string(),
+            String::new(),
+            Some("1".to_string()),
+        );
+        assert_eq!(failure.attempts, 2);
+        assert_eq!(
+            failure.describe(),
+            "Codex app-server exited before completing initialize. \
+             (same error as the previous attempt, 2 in a row)\n\
+             Exit status: 1\n--- stderr ---\nError: invalid config.toml"
+        );
+        assert_eq!(last_spawn_failure(workspace_id), Some(failure));
+
+        clear_spawn_failure(workspace_id);
+        assert!(last_spawn_failure(workspace_id).is_none());
+    }
+}
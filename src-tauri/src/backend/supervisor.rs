@@ -0,0 +1,245 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::backend::events::{BackendError, EventSink};
+
+/// How often a critical subsystem may be restarted before the backend
+/// reports itself degraded.
+struct RestartPolicy {
+    /// Restarts allowed within `window`.
+    max_restarts: u32,
+    window: Duration,
+    /// Delay before a restart, multiplied by the number of recent failures.
+    backoff: Duration,
+}
+
+const CRITICAL_RESTARTS: RestartPolicy = RestartPolicy {
+    max_restarts: 5,
+    window: Duration::from_secs(10 * 60),
+    backoff: Duration::from_secs(1),
+};
+
+static PANIC_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Panics seen by the hook since start, for health reporting.
+pub(crate) fn panic_count() -> u64 {
+    PANIC_COUNT.load(Ordering::Relaxed)
+}
+
+/// Logs every panic with its location and a backtrace, and counts it. Safe to
+/// call more than once.
+pub(crate) fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        std::panic::set_hook(Box::new(|info| {
+            PANIC_COUNT.fetch_add(1, Ordering::Relaxed);
+            let thread = std::thread::current();
+            let location = info
+                .location()
+                .map(|location| location.to_string())
+                .unwrap_or_else(|| "unknown location".to_string());
+            eprintln!(
+                "[panic] thread '{}' panicked at {location}: {}\n{}",
+                thread.name().unwrap_or("<unnamed>"),
+                panic_message(info.payload()),
+                std::backtrace::Backtrace::force_capture()
+            );
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic with a non-string payload".to_string())
+}
+
+fn report(
+    sink: &impl EventSink,
+    subsystem: &str,
+    message: String,
+    restarted: bool,
+    degraded: bool,
+) {
+    eprintln!(
+        "[supervisor] {subsystem} panicked ({}): {message}",
+        if degraded {
+            "giving up"
+        } else if restarted {
+            "restarting"
+        } else {
+            "stopped"
+        }
+    );
+    sink.emit_backend_error(BackendError {
+        subsystem: subsystem.to_string(),
+        message,
+        restarted,
+        degraded,
+    });
+}
+
+/// Resolves to `Err` with the panic payload if polling the inner future
+/// panics.
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = std::thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        match catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// `tokio::spawn` that reports a panic as a `BackendError` for `subsystem`
+/// instead of letting the task die silently.
+pub(crate) fn spawn_guarded<E, F>(sink: E, subsystem: &'static str, future: F) -> JoinHandle<()>
+where
+    E: EventSink,
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(payload) = CatchUnwind(Box::pin(future)).await {
+            report(&sink, subsystem, panic_message(&*payload), false, false);
+        }
+    })
+}
+
+/// `std::thread::spawn` with the same panic reporting as `spawn_guarded`.
+pub(crate) fn spawn_guarded_thread<E, F>(sink: E, subsystem: &'static str, body: F)
+where
+    E: EventSink,
+    F: FnOnce() + Send + 'static,
+{
+    std::thread::spawn(move || {
+        if let Err(payload) = catch_unwind(AssertUnwindSafe(body)) {
+            report(&sink, subsystem, panic_message(&*payload), false, false);
+        }
+    });
+}
+
+/// Runs a critical subsystem, restarting it when it panics. Returns when the
+/// subsystem finishes normally, or once it has panicked more often than
+/// `CRITICAL_RESTARTS` allows, in which case a degraded `BackendError` is
+/// emitted.
+pub(crate) async fn supervise<E, F, Fut>(sink: E, subsystem: &'static str, start: F)
+where
+    E: EventSink,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    supervise_with(sink, subsystem, start, &CRITICAL_RESTARTS).await;
+}
+
+async fn supervise_with<E, F, Fut>(
+    sink: E,
+    subsystem: &'static str,
+    mut start: F,
+    policy: &RestartPolicy,
+) where
+    E: EventSink,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut failures: Vec<Instant> = Vec::new();
+    loop {
+        let Err(payload) = CatchUnwind(Box::pin(start())).await else {
+            return;
+        };
+        let now = Instant::now();
+        failures.retain(|failed_at| now.duration_since(*failed_at) < policy.window);
+        failures.push(now);
+        let message = panic_message(&*payload);
+        if failures.len() as u32 > policy.max_restarts {
+            report(&sink, subsystem, message, false, true);
+            return;
+        }
+        report(&sink, subsystem, message, true, false);
+        tokio::time::sleep(policy.backoff * failures.len() as u32).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::events::{
+        AppServerEvent, QuickCommandOutput, TerminalExited, TerminalOutput,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        errors: Arc<Mutex<Vec<BackendError>>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exited(&self, _event: TerminalExited) {}
+        fn emit_quick_command_output(&self, _event: QuickCommandOutput) {}
+        fn emit_backend_error(&self, event: BackendError) {
+            self.errors.lock().unwrap().push(event);
+        }
+    }
+
+    #[tokio::test]
+    async fn supervise_restarts_then_reports_degraded() {
+        let sink = RecordingSink::default();
+        let policy = RestartPolicy {
+            max_restarts: 3,
+            window: Duration::from_secs(60),
+            backoff: Duration::ZERO,
+        };
+        let mut runs = 0;
+        supervise_with(
+            sink.clone(),
+            "scheduler",
+            || {
+                runs += 1;
+                async { panic!("tick failed") }
+            },
+            &policy,
+        )
+        .await;
+
+        assert_eq!(runs, 4);
+        let errors = sink.errors.lock().unwrap();
+        assert_eq!(errors.len(), 4);
+        assert!(errors[..3]
+            .iter()
+            .all(|error| error.restarted && !error.degraded));
+        let last = errors.last().unwrap();
+        assert!(last.degraded && !last.restarted);
+        assert_eq!(last.subsystem, "scheduler");
+        assert_eq!(last.message, "tick failed");
+    }
+
+    #[tokio::test]
+    async fn spawn_guarded_reports_the_panic() {
+        let sink = RecordingSink::default();
+        let handle = spawn_guarded(sink.clone(), "file watcher", async {
+            panic!("{}", String::from("gone"));
+        });
+        handle.await.unwrap();
+        let errors = sink.errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "gone");
+        assert!(!errors[0].restarted);
+    }
+}
@@ -0,0 +1,393 @@
+//! Time-boxed turns. In a workspace with `maxTurnDurationMinutes` (or
+//! `readOnlyMaxTurnDurationMinutes` for read-only turns) every turn that
+//! `send_user_message` starts is timed from its `turn/started`: a warning goes
+//! out at 80% of the budget and `turn/interrupt` is sent once it runs out.
+//! Turns this session did not start, such as those of a resumed thread, are
+//! never timed.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::thread_activity;
+use crate::types::WorkspaceSettings;
+
+pub(crate) const WARNING_METHOD: &str = "codex/turnBudgetWarning";
+pub(crate) const AUTO_INTERRUPTED_METHOD: &str = "codex/turnAutoInterrupted";
+
+const WARNING_PERCENT: i64 = 80;
+/// How often running turns are checked against their budget.
+const TICK: Duration = Duration::from_secs(5);
+
+type AutoInterruptHook = Box<dyn Fn(String, BudgetNotice, bool) + Send + Sync>;
+
+static AUTO_INTERRUPT_HOOK: OnceLock<AutoInterruptHook> = OnceLock::new();
+
+/// Installs what else happens when a turn is interrupted for its budget. It
+/// gets the workspace id and whether `turn/interrupt` succeeded. Only the
+/// first hook is kept.
+pub(crate) fn set_auto_interrupt_hook(
+    hook: impl Fn(String, BudgetNotice, bool) + Send + Sync + 'static,
+) {
+    let _ = AUTO_INTERRUPT_HOOK.set(Box::new(hook));
+}
+
+/// Source of the current time, so tests can move it by hand.
+pub(crate) trait Clock: Send + Sync {
+    fn now_ms(&self) -> i64;
+}
+
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TurnBudget {
+    pub(crate) minutes: u32,
+    pub(crate) access_mode: &'static str,
+}
+
+/// The budget of a turn started with `access_mode` (as resolved by
+/// `turn_policy`). `0` means no budget, like an unset setting.
+pub(crate) fn turn_budget(
+    settings: &WorkspaceSettings,
+    access_mode: &'static str,
+) -> Option<TurnBudget> {
+    let minutes = if access_mode == "read-only" {
+        settings.read_only_max_turn_duration_minutes
+    } else {
+        settings.max_turn_duration_minutes
+    };
+    minutes
+        .filter(|minutes| *minutes > 0)
+        .map(|minutes| TurnBudget {
+            minutes,
+            access_mode,
+        })
+}
+
+/// A timed turn, as reported in the warning and interruption events.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BudgetNotice {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+    pub(crate) budget: TurnBudget,
+    pub(crate) elapsed_ms: i64,
+}
+
+impl BudgetNotice {
+    /// Why the turn was interrupted, for the activity index and feed.
+    pub(crate) fn reason(&self) -> String {
+        format!(
+            "Ran past its {} minute budget for {} turns",
+            self.budget.minutes, self.budget.access_mode
+        )
+    }
+
+    fn params(&self, workspace_id: &str) -> Value {
+        json!({
+            "workspaceId": workspace_id,
+            "threadId": self.thread_id,
+            "turnId": self.turn_id,
+            "budgetMinutes": self.budget.minutes,
+            "accessMode": self.budget.access_mode,
+            "elapsedMs": self.elapsed_ms,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BudgetAction {
+    Warn(BudgetNotice),
+    Interrupt(BudgetNotice),
+}
+
+struct TimedTurn {
+    turn_id: String,
+    budget: TurnBudget,
+    started_ms: i64,
+    warned: bool,
+}
+
+#[derive(Default)]
+struct BudgetState {
+    /// Budgets for the next turn of each thread, set before `turn/start`.
+    armed: HashMap<String, TurnBudget>,
+    /// Timed turns in progress, by thread.
+    running: HashMap<String, TimedTurn>,
+}
+
+/// The session's timed turns.
+pub(crate) struct TurnBudgets {
+    clock: Arc<dyn Clock>,
+    state: Mutex<BudgetState>,
+}
+
+impl Default for TurnBudgets {
+    fn default() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+}
+
+impl TurnBudgets {
+    pub(crate) fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            state: Mutex::new(BudgetState::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BudgetState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Sets the budget of the next turn started on `thread_id`. Called before
+    /// `turn/start`, as its `turn/started` can arrive ahead of the response.
+    pub(crate) fn arm(&self, thread_id: &str, budget: Option<TurnBudget>) {
+        let mut state = self.lock();
+        match budget {
+            Some(budget) => {
+                state.armed.insert(thread_id.to_string(), budget);
+            }
+            None => {
+                state.armed.remove(thread_id);
+            }
+        }
+    }
+
+    /// Starts and stops timers from the session's notifications.
+    pub(crate) fn record_message(
+        &self,
+        method: Option<&str>,
+        thread_id: Option<&str>,
+        value: &Value,
+    ) {
+        let (Some(method), Some(thread_id)) = (method, thread_id) else {
+            return;
+        };
+        let mut state = self.lock();
+        match method {
+            "turn/started" => {
+                let Some(budget) = state.armed.remove(thread_id) else {
+                    return;
+                };
+                let turn_id = value
+                    .pointer("/params/turn/id")
+                    .or_else(|| value.pointer("/params/turnId"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                state.running.insert(
+                    thread_id.to_string(),
+                    TimedTurn {
+                        turn_id,
+                        budget,
+                        started_ms: self.clock.now_ms(),
+                        warned: false,
+                    },
+                );
+            }
+            "turn/completed" | "turn/error" => {
+                state.running.remove(thread_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Warnings and interruptions due now. An interrupted turn stops being
+    /// timed, so each is reported once.
+    pub(crate) fn due(&self) -> Vec<BudgetAction> {
+        let now = self.clock.now_ms();
+        let mut state = self.lock();
+        let mut actions = Vec::new();
+        state.running.retain(|thread_id, turn| {
+            let elapsed_ms = now - turn.started_ms;
+            let budget_ms = i64::from(turn.budget.minutes) * 60_000;
+            let notice = || BudgetNotice {
+                thread_id: thread_id.clone(),
+                turn_id: turn.turn_id.clone(),
+                budget: turn.budget,
+                elapsed_ms,
+            };
+            if elapsed_ms >= budget_ms {
+                actions.push(BudgetAction::Interrupt(notice()));
+                return false;
+            }
+            if !turn.warned && elapsed_ms * 100 >= budget_ms * WARNING_PERCENT {
+                actions.push(BudgetAction::Warn(notice()));
+                turn.warned = true;
+            }
+            true
+        });
+        actions
+    }
+}
+
+/// Enforces the session's turn budgets until it is dropped or its process
+/// exits.
+pub(crate) async fn run_turn_budgets<E: EventSink>(session: Weak<WorkspaceSession>, event_sink: E) {
+    loop {
+        tokio::time::sleep(TICK).await;
+        let Some(session) = session.upgrade() else {
+            return;
+        };
+        if !matches!(session.child.lock().await.try_wait(), Ok(None)) {
+            return;
+        }
+        let workspace_id = session.entry.id.clone();
+        for action in session.turn_budgets.due() {
+            match action {
+                BudgetAction::Warn(notice) => {
+                    event_sink.emit_app_server_event(AppServerEvent {
+                        workspace_id: workspace_id.clone(),
+                        message: json!({
+                            "method": WARNING_METHOD,
+                            "params": notice.params(&workspace_id),
+                        }),
+                    });
+                }
+                BudgetAction::Interrupt(notice) => {
+                    let interrupted = session
+                        .send_request(
+                            "turn/interrupt",
+                            json!({ "threadId": notice.thread_id, "turnId": notice.turn_id }),
+                        )
+                        .await;
+                    let reason = notice.reason();
+                    thread_activity::record_auto_interrupt(
+                        &workspace_id,
+                        &notice.thread_id,
+                        &notice.turn_id,
+                        &reason,
+                    );
+                    let mut params = notice.params(&workspace_id);
+                    params["reason"] = json!(reason);
+                    if let Err(err) = &interrupted {
+                        params["error"] = json!(err);
+                    }
+                    event_sink.emit_app_server_event(AppServerEvent {
+                        workspace_id: workspace_id.clone(),
+                        message: json!({ "method": AUTO_INTERRUPTED_METHOD, "params": params }),
+                    });
+                    if let Some(hook) = AUTO_INTERRUPT_HOOK.get() {
+                        hook(workspace_id.clone(), notice, interrupted.is_ok());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{turn_budget, BudgetAction, Clock, TurnBudget, TurnBudgets};
+    use crate::types::WorkspaceSettings;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct ManualClock(AtomicI64);
+
+    impl ManualClock {
+        fn advance_minutes(&self, minutes: i64) {
+            self.0.fetch_add(minutes * 60_000, Ordering::Relaxed);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now_ms(&self) -> i64 {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    fn started(budgets: &TurnBudgets, thread_id: &str, turn_id: &str) {
+        budgets.record_message(
+            Some("turn/started"),
+            Some(thread_id),
+            &json!({ "method": "turn/started", "params": { "threadId": thread_id, "turn": { "id": turn_id } } }),
+        );
+    }
+
+    fn completed(budgets: &TurnBudgets, thread_id: &str) {
+        budgets.record_message(Some("turn/completed"), Some(thread_id), &json!({}));
+    }
+
+    #[test]
+    fn budgets_follow_the_access_mode_of_the_turn() {
+        let settings = WorkspaceSettings {
+            max_turn_duration_minutes: Some(30),
+            read_only_max_turn_duration_minutes: Some(0),
+            ..WorkspaceSettings::default()
+        };
+        assert_eq!(
+            turn_budget(&settings, "full-access"),
+            Some(TurnBudget {
+                minutes: 30,
+                access_mode: "full-access"
+            })
+        );
+        assert_eq!(turn_budget(&settings, "read-only"), None);
+        assert_eq!(turn_budget(&WorkspaceSettings::default(), "current"), None);
+    }
+
+    #[test]
+    fn warns_then_interrupts_only_armed_turns_still_running() {
+        let clock = Arc::new(ManualClock::default());
+        let budgets = TurnBudgets::with_clock(clock.clone());
+        let budget = TurnBudget {
+            minutes: 10,
+            access_mode: "full-access",
+        };
+
+        // A resumed thread's turn was never armed.
+        started(&budgets, "t-resumed", "turn-old");
+        budgets.arm("t-done", Some(budget));
+        started(&budgets, "t-done", "turn-1");
+        budgets.arm("t-long", Some(budget));
+        started(&budgets, "t-long", "turn-2");
+
+        clock.advance_minutes(7);
+        assert!(budgets.due().is_empty());
+        completed(&budgets, "t-done");
+
+        clock.advance_minutes(1);
+        let actions = budgets.due();
+        assert_eq!(actions.len(), 1);
+        let BudgetAction::Warn(notice) = &actions[0] else {
+            panic!("expected a warning, got {actions:?}");
+        };
+        assert_eq!(notice.turn_id, "turn-2");
+        assert!(budgets.due().is_empty());
+
+        clock.advance_minutes(2);
+        let actions = budgets.due();
+        let [BudgetAction::Interrupt(notice)] = actions.as_slice() else {
+            panic!("expected an interruption, got {actions:?}");
+        };
+        assert_eq!(notice.thread_id, "t-long");
+        assert_eq!(notice.elapsed_ms, 10 * 60_000);
+        assert!(notice.reason().contains("10 minute"));
+
+        clock.advance_minutes(60);
+        assert!(budgets.due().is_empty());
+
+        // Disarming leaves the next turn untimed.
+        budgets.arm("t-long", Some(budget));
+        budgets.arm("t-long", None);
+        started(&budgets, "t-long", "turn-3");
+        clock.advance_minutes(60);
+        assert!(budgets.due().is_empty());
+    }
+}
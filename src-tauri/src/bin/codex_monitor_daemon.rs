@@ -1,10 +1,16 @@
+#[path = "../attachments.rs"]
+mod attachments;
 #[path = "../memory/auto_flush.rs"]
 mod auto_flush;
 #[allow(dead_code)]
 #[path = "../backend/mod.rs"]
 mod backend;
+#[path = "../branch_protection.rs"]
+mod branch_protection;
 #[path = "../browser/mod.rs"]
 mod browser;
+#[path = "../client_timezone.rs"]
+mod client_timezone;
 #[path = "../codex_args.rs"]
 mod codex_args;
 #[path = "../codex_config.rs"]
@@ -13,43 +19,169 @@ mod codex_config;
 mod codex_home;
 #[path = "../codex_params.rs"]
 mod codex_params;
+#[path = "../daemon_clients.rs"]
+mod daemon_clients;
+#[path = "../daemon_config.rs"]
+mod daemon_config;
+#[path = "../data_usage.rs"]
+mod data_usage;
+#[path = "../delivery_platforms.rs"]
+mod delivery_platforms;
+#[path = "../domain_prompts.rs"]
+mod domain_prompts;
+#[path = "../domain_usage.rs"]
+mod domain_usage;
+#[path = "../file_refs.rs"]
+mod file_refs;
+#[path = "../file_tail.rs"]
+mod file_tail;
+#[path = "../frontmatter.rs"]
+mod frontmatter;
+#[path = "../git_commit.rs"]
+mod git_commit;
+#[path = "../git_hunks.rs"]
+mod git_hunks;
+#[path = "../git_patch.rs"]
+mod git_patch;
+#[path = "../git_stash.rs"]
+mod git_stash;
+#[path = "../git_status_cache.rs"]
+mod git_status_cache;
+#[path = "../git_tags.rs"]
+mod git_tags;
 #[path = "../git_utils.rs"]
 mod git_utils;
+#[path = "../github_cli.rs"]
+mod github_cli;
+#[path = "../github_prefetch.rs"]
+mod github_prefetch;
 #[path = "../life_core.rs"]
 mod life;
+#[path = "../life_report.rs"]
+mod life_report;
+#[path = "../life_transcripts.rs"]
+mod life_transcripts;
 #[path = "../local_usage_core.rs"]
 mod local_usage_core;
 #[path = "../memory/mod.rs"]
 mod memory;
+#[path = "../memory/recall.rs"]
+mod memory_recall;
+#[path = "../message_assembly.rs"]
+mod message_assembly;
+#[path = "../message_compose.rs"]
+mod message_compose;
+#[allow(dead_code)]
+#[path = "../notification_rules.rs"]
+mod notification_rules;
+#[path = "../nutrition_goals.rs"]
+mod nutrition_goals;
 #[path = "../obsidian/mod.rs"]
 mod obsidian;
+#[allow(dead_code)]
+#[path = "../onboarding.rs"]
+mod onboarding;
+#[path = "../operations.rs"]
+mod operations;
+#[path = "../prompt_roots.rs"]
+mod prompt_roots;
+#[path = "../quick_commands.rs"]
+mod quick_commands;
+#[path = "../read_only.rs"]
+mod read_only;
+#[path = "../redaction.rs"]
+mod redaction;
+#[path = "../resource_usage.rs"]
+mod resource_usage;
+#[path = "../revert_undo.rs"]
+mod revert_undo;
+#[path = "../review_gate.rs"]
+mod review_gate;
+#[allow(dead_code)]
+#[path = "../rpc_chunks.rs"]
+mod rpc_chunks;
 #[path = "../rules.rs"]
 mod rules;
+#[path = "../settings_audit.rs"]
+mod settings_audit;
 #[path = "../skills/mod.rs"]
 mod skills;
 #[path = "../storage.rs"]
 mod storage;
 #[allow(dead_code)]
+#[path = "../terminal_audit.rs"]
+mod terminal_audit;
+#[path = "../terminal_core.rs"]
+mod terminal_core;
+#[path = "../terminal_search.rs"]
+mod terminal_search;
+#[path = "../terminal_shell.rs"]
+mod terminal_shell;
+#[cfg(test)]
+#[path = "../test_git.rs"]
+mod test_git;
+#[path = "../thread_activity.rs"]
+mod thread_activity;
+#[path = "../thread_archive.rs"]
+mod thread_archive;
+#[path = "../thread_items.rs"]
+mod thread_items;
+#[path = "../thread_overrides.rs"]
+mod thread_overrides;
+#[path = "../thread_summary.rs"]
+mod thread_summary;
+#[path = "../turn_changes.rs"]
+mod turn_changes;
 #[path = "../types.rs"]
 mod types;
+#[path = "../usage_overview.rs"]
+mod usage_overview;
 #[path = "../utils.rs"]
 mod utils;
+#[path = "../vault_roots.rs"]
+mod vault_roots;
+#[path = "../warm_pool.rs"]
+mod warm_pool;
+#[path = "../workouts.rs"]
+mod workouts;
+#[path = "../workspace_file.rs"]
+mod workspace_file;
+#[path = "../workspace_identity.rs"]
+mod workspace_identity;
+#[path = "../workspace_import.rs"]
+mod workspace_import;
+#[path = "../workspace_search.rs"]
+mod workspace_search;
+#[path = "../workspace_settings.rs"]
+mod workspace_settings;
+#[path = "../workspace_todos.rs"]
+mod workspace_todos;
+#[path = "../worktree_apply.rs"]
+mod worktree_apply;
+#[path = "../worktree_compare.rs"]
+mod worktree_compare;
+#[path = "../worktree_guard.rs"]
+mod worktree_guard;
+#[path = "../worktree_report.rs"]
+mod worktree_report;
+#[path = "../worktree_setup.rs"]
+mod worktree_setup;
+#[path = "../worktree_upstream.rs"]
+mod worktree_upstream;
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
-use std::process::Stdio;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
-use git2::{BranchType, DiffOptions, Repository, Sort, Status, StatusOptions};
+use git2::{BranchType, Repository, Sort, Status, StatusOptions};
 use ignore::WalkBuilder;
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::process::Command;
@@ -58,35 +190,111 @@ use tokio::task;
 use utils::{git_env_path, resolve_git_binary};
 use uuid::Uuid;
 
+use attachments::AttachmentInfo;
 use auto_flush::{
     build_snapshot, parse_memory_flush_result, run_memory_flush_summarizer, write_memory_flush,
     AutoMemoryRuntime,
 };
 use backend::app_server::{spawn_workspace_session, WorkspaceSession};
-use backend::events::{AppServerEvent, EventSink, TerminalOutput};
+use backend::background_prompt::{generate_commit_message_for_diff, thread_id_from_start_response};
+use backend::blocking::{run_blocking, run_git_blocking};
+use backend::capabilities::SessionCapabilities;
+use backend::events::{
+    AppServerEvent, BackendError, EventSink, QuickCommandOutput, TerminalExited, TerminalOutput,
+};
+use backend::keepalive::{
+    configure_keepalive, connection_state, emit_disconnected, remove_stale_sessions,
+    set_restart_hook,
+};
+use backend::path_health::{
+    check_workspace_paths, ensure_path_available, is_path_unavailable, run_path_recheck,
+};
+use backend::spawn_diagnostics::last_spawn_failure;
+use backend::supervisor::{install_panic_hook, panic_count, supervise};
+use backend::turn_budget::{set_auto_interrupt_hook, turn_budget, BudgetNotice, TurnBudget};
+use branch_protection::{
+    current_branch_name, effective_protected_branches, ensure_branch_not_protected,
+    ensure_tag_not_protected,
+};
 use browser::service::BrowserService;
-use codex_params::{build_turn_start_params, build_user_input};
+use client_timezone::{normalize_timezone_setting, resolve_client_timezone, ClientTimezone};
+use daemon_config::{
+    ConfigOverrides, ConfigReloadReport, RuntimeConfig, TokenScope, DAEMON_CONFIG_FILE,
+};
+use domain_prompts::{domain_includes_dir, domain_prompt_warnings};
+use domain_usage::{delete_domain, domain_usage, DomainDeleteResult, DomainUsage};
+use file_refs::attach_file_context_report;
+use file_tail::{FileFollowers, TailChunk};
+use git_commit::CommitOptions;
+use git_hunks::HunkSelector;
 use git_utils::{
-    checkout_branch, commit_to_entry, diff_patch_to_string, diff_stats_for_path,
-    list_git_roots as scan_git_roots, parse_github_repo, resolve_git_root,
+    action_paths_for_file, checkout_branch, collect_workspace_diff, commit_to_entry,
+    diff_patch_to_string, diff_stats_for_path, display_diff_options, intraline_hunks,
+    is_whitespace_only_patch, list_git_roots as scan_git_roots, parse_github_repo,
+    resolve_git_root, resolve_git_sub_root,
 };
+use life_transcripts::{is_transcript_event, transcript_vault, LifeTranscripts};
+use memory::connection_test::{normalize_supabase_url, test_connection, MemoryConnectionReport};
 use memory::MemoryService;
+use message_assembly::{
+    assemble_message, turn_policy, AssembledMessage, MessageSizeEstimate, MessageSources,
+    TurnPolicy,
+};
+use message_compose::{
+    compose_message, ComposeSources, MessagePart, PromptSource, DEFAULT_COMPOSE_BUDGET_BYTES,
+};
+use onboarding::{
+    BootstrapOptions, BootstrapReport, DaemonCheck, OnboardingStatus, STEP_ADD_WORKSPACE,
+    STEP_CONNECT_WORKSPACE, STEP_STARTER_PROMPTS,
+};
+use operations::{run_git_clone, OperationContext, OperationsRegistry};
+use prompt_roots::{ensure_scope_writable, scope_dir, scope_of_path, PromptRoot};
+use quick_commands::{QuickCommandEntry, QuickCommandExecution, QuickCommands};
+use read_only::{ensure_workspace_writable, ensure_writable};
+use redaction::{redact_diff_with, Redactor};
+use resource_usage::{collect_resource_usage, ResourceUsage, RunningSession};
+use revert_undo::RevertUndoEntry;
 use skills::skill_md::{parse_skill_md, validate_skill};
 use storage::{
-    read_domains, read_settings, read_workspaces, seed_domains_from_files, write_domains,
-    write_settings, write_workspaces,
+    pinned_threads_path, read_domains, read_pinned_threads, read_settings, read_workspaces,
+    seed_domains_from_files, write_domains, write_pinned_threads, write_settings, write_workspaces,
 };
+use terminal_core::{TerminalLaunch, TerminalSessionInfo};
+use terminal_search::TerminalSearchOptions;
+use thread_archive::{ArchiveThreadResult, AutoArchiveCandidate};
+use thread_items::normalize_app_server_event;
+use turn_changes::TurnCommitResult;
 use types::{
-    AppSettings, AutoMemorySettings, BranchInfo, Domain, DomainTrendSnapshot, GitCommitDiff,
-    GitFileDiff, GitFileStatus, GitHubIssue, GitHubIssuesResponse, GitHubPullRequest,
-    GitHubPullRequestComment, GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse,
-    LocalUsageSnapshot, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
-    WorktreeInfo,
+    AppSettings, AppSettingsUpdate, AutoMemorySettings, BranchInfo, CommitMessagePrompt,
+    CommitMessageResult, ConnectionState, Domain, DomainSaveResult, DomainTrendSnapshot,
+    GitCommitDiff, GitCommitOutcome, GitFileDiff, GitFileStatus, GitHubIssue, GitHubIssuesResponse,
+    GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff, GitHubPullRequestsResponse,
+    GitLogResponse, GitStashEntry, GitTagInfo, GithubPrefetchSettings, LocalUsageSnapshot,
+    QuickCommand, ThreadSummary, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
+    WorkspaceTodo, WorktreeComparison, WorktreeInfo, WorktreeReport,
 };
+use usage_overview::UsageOverview;
 use utils::normalize_git_path;
+use workspace_file::{read_workspace_file_chunk, WorkspaceFileResponse};
+use workspace_import::{
+    import_repositories, main_workspace_entry, registered_workspace_id, scan_git_repositories,
+    WorkspaceImportReport,
+};
+use workspace_search::{WorkspaceSearchOptions, WorkspaceSearchResult, SEARCH_TIME_BUDGET};
+use worktree_setup::CreatedWorktree;
+use worktree_upstream::RenamedWorktree;
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
 const INDEX_SKIP_WORKTREE_FLAG: u16 = 0x4000;
+const AUTO_ARCHIVE_INITIAL_DELAY: Duration = Duration::from_secs(10 * 60);
+const AUTO_ARCHIVE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Sent to a read-only client that calls a write method.
+const READ_ONLY_FORBIDDEN: &str = "forbidden: read-only token";
+/// Shutdown exits after this even if a session or terminal will not die.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time for the `daemon/shutdown` notification to reach clients before the
+/// process exits.
+const SHUTDOWN_NOTICE_GRACE: Duration = Duration::from_millis(200);
 
 #[derive(Clone)]
 struct DaemonEventSink {
@@ -98,6 +306,12 @@ enum DaemonEvent {
     AppServer(AppServerEvent),
     #[allow(dead_code)]
     TerminalOutput(TerminalOutput),
+    TerminalExited(TerminalExited),
+    QuickCommandOutput(QuickCommandOutput),
+    BackendError(BackendError),
+    /// Sent once on SIGINT/SIGTERM, after every session was reported
+    /// disconnected.
+    Shutdown,
 }
 
 impl EventSink for DaemonEventSink {
@@ -108,19 +322,68 @@ impl EventSink for DaemonEventSink {
     fn emit_terminal_output(&self, event: TerminalOutput) {
         let _ = self.tx.send(DaemonEvent::TerminalOutput(event));
     }
+
+    fn emit_terminal_exited(&self, event: TerminalExited) {
+        let _ = self.tx.send(DaemonEvent::TerminalExited(event));
+    }
+
+    fn emit_quick_command_output(&self, event: QuickCommandOutput) {
+        let _ = self.tx.send(DaemonEvent::QuickCommandOutput(event));
+    }
+
+    fn emit_backend_error(&self, event: BackendError) {
+        let _ = self.tx.send(DaemonEvent::BackendError(event));
+    }
 }
 
 struct DaemonConfig {
     listen: SocketAddr,
-    token: Option<String>,
     data_dir: PathBuf,
+    ready_file: Option<PathBuf>,
+    overrides: ConfigOverrides,
+    /// Replaced whole on reload, so readers never see a half-applied config.
+    runtime: std::sync::RwLock<RuntimeConfig>,
+}
+
+impl DaemonConfig {
+    fn config_path(&self) -> PathBuf {
+        self.data_dir.join(DAEMON_CONFIG_FILE)
+    }
+
+    fn runtime(&self) -> RuntimeConfig {
+        self.runtime
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Re-reads `daemon.json` and swaps in the new runtime settings. Nothing
+    /// changes unless the whole file parses and validates.
+    fn reload(&self) -> Result<ConfigReloadReport, String> {
+        let path = self.config_path();
+        let file = daemon_config::read_config_file(&path)?;
+        let next_listen = daemon_config::resolve_listen(&file, &self.overrides)?
+            .or_else(|| DEFAULT_LISTEN_ADDR.parse().ok());
+        let next = daemon_config::resolve_runtime(
+            &file,
+            &self.overrides,
+            rpc_chunks::DEFAULT_CHUNK_THRESHOLD,
+        )?;
+        let mut runtime = self.runtime.write().unwrap_or_else(|err| err.into_inner());
+        let report = daemon_config::reload_report(&path, &runtime, &next, self.listen, next_listen);
+        *runtime = next;
+        Ok(report)
+    }
 }
 
 struct DaemonState {
     data_dir: PathBuf,
     workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
     sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    terminal_sessions: Mutex<HashMap<String, Arc<TerminalSession>>>,
+    terminal_sessions: terminal_core::TerminalSessions,
+    thread_views: thread_activity::ThreadViews,
+    clients: daemon_clients::ClientRegistry,
+    github_cache: github_prefetch::GithubCache,
     storage_path: PathBuf,
     settings_path: PathBuf,
     domains_path: PathBuf,
@@ -129,13 +392,12 @@ struct DaemonState {
     memory: RwLock<Option<MemoryService>>,
     auto_memory_runtime: Mutex<AutoMemoryRuntime>,
     browser: BrowserService,
+    operations: Arc<OperationsRegistry<DaemonEventSink>>,
+    file_followers: FileFollowers<DaemonEventSink>,
+    quick_commands: QuickCommands<DaemonEventSink>,
+    life_transcripts: LifeTranscripts<DaemonEventSink>,
     event_sink: DaemonEventSink,
-}
-
-#[derive(Serialize, Deserialize)]
-struct WorkspaceFileResponse {
-    content: String,
-    truncated: bool,
+    started_at: Instant,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -145,18 +407,6 @@ struct TextFileResponse {
     truncated: bool,
 }
 
-struct TerminalSession {
-    id: String,
-    master: Mutex<Box<dyn portable_pty::MasterPty + Send>>,
-    writer: Mutex<Box<dyn Write + Send>>,
-    child: Mutex<Box<dyn portable_pty::Child + Send>>,
-}
-
-#[derive(Debug, Serialize, Clone)]
-struct TerminalSessionInfo {
-    id: String,
-}
-
 #[derive(Serialize, Clone)]
 struct CustomPromptEntry {
     name: String,
@@ -176,6 +426,7 @@ impl DaemonState {
         let domains_path = config.data_dir.join("domains.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        configure_keepalive(&app_settings.keepalive);
         let mut domains = read_domains(&domains_path).unwrap_or_default();
         if domains.is_empty() {
             let seeded = seed_domains_from_files();
@@ -205,7 +456,12 @@ impl DaemonState {
             data_dir: config.data_dir.clone(),
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
-            terminal_sessions: Mutex::new(HashMap::new()),
+            terminal_sessions: Arc::new(Mutex::new(HashMap::new())),
+            thread_views: thread_activity::ThreadViews::load(thread_activity::thread_views_path(
+                &settings_path,
+            )),
+            clients: daemon_clients::ClientRegistry::default(),
+            github_cache: github_prefetch::GithubCache::default(),
             storage_path,
             settings_path,
             domains_path,
@@ -214,7 +470,61 @@ impl DaemonState {
             memory: RwLock::new(memory),
             auto_memory_runtime: Mutex::new(AutoMemoryRuntime::default()),
             browser: BrowserService::new(),
+            operations: Arc::new(OperationsRegistry::new(event_sink.clone())),
+            file_followers: FileFollowers::new(event_sink.clone()),
+            quick_commands: QuickCommands::new(event_sink.clone()),
+            life_transcripts: LifeTranscripts::new(event_sink.clone()),
             event_sink,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Payload of the `health` probe. It may be served before auth, so keep it
+    /// to counts and build info: no paths, names, ids or settings.
+    async fn health(&self) -> Value {
+        let workspaces = self.workspaces.lock().await.len();
+        let sessions = self.sessions.lock().await.len();
+        json!({
+            "status": "ok",
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime": self.started_at.elapsed().as_secs(),
+            "workspaces": workspaces,
+            "sessions": sessions,
+            "panics": panic_count(),
+        })
+    }
+
+    /// Stops everything the daemon spawned: every app-server session, PTY and
+    /// quick command. Each session is reported `codex/disconnected` and then
+    /// clients get `daemon/shutdown`. The workspace list and settings are
+    /// written once more so nothing in memory is lost.
+    async fn shutdown(&self, events_tx: &broadcast::Sender<DaemonEvent>) {
+        let sessions: Vec<_> = self.sessions.lock().await.drain().collect();
+        for (workspace_id, _) in &sessions {
+            emit_disconnected(&self.event_sink, workspace_id, "daemon_shutdown");
+        }
+        let _ = events_tx.send(DaemonEvent::Shutdown);
+
+        for (workspace_id, session) in sessions {
+            warm_pool::forget_session(&workspace_id);
+            let _ = session.child.lock().await.kill().await;
+        }
+        let terminals: Vec<_> = self.terminal_sessions.lock().await.drain().collect();
+        for (_, terminal) in terminals {
+            terminal.kill().await;
+        }
+        let workspaces: Vec<WorkspaceEntry> =
+            self.workspaces.lock().await.values().cloned().collect();
+        for workspace in &workspaces {
+            self.quick_commands.cancel_workspace(&workspace.id);
+        }
+
+        if let Err(err) = write_workspaces(&self.storage_path, &workspaces) {
+            eprintln!("Failed to save workspaces on shutdown: {err}");
+        }
+        let settings = self.app_settings.lock().await.clone();
+        if let Err(err) = write_settings(&self.settings_path, &settings) {
+            eprintln!("Failed to save settings on shutdown: {err}");
         }
     }
 
@@ -227,21 +537,87 @@ impl DaemonState {
         let Some(session) = session else {
             return;
         };
+        warm_pool::forget_session(workspace_id);
 
         let mut child = session.child.lock().await;
         let _ = child.kill().await;
     }
 
+    /// Spawns the session of a `keepWarm` workspace unless a healthy one is
+    /// running. A session whose process exited is replaced.
+    async fn keep_session_warm(&self, workspace_id: &str) -> Result<(), String> {
+        let session = self.sessions.lock().await.get(workspace_id).cloned();
+        if let Some(session) = session {
+            let alive = matches!(session.child.lock().await.try_wait(), Ok(None));
+            if alive && !session.health.is_unhealthy() {
+                return Ok(());
+            }
+            self.kill_session(workspace_id).await;
+        }
+        let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
+        self.connect_workspace(workspace_id.to_string(), client_version)
+            .await?;
+        warm_pool::mark_prewarmed(workspace_id);
+        Ok(())
+    }
+
+    async fn resource_usage(&self) -> ResourceUsage {
+        let warm_sessions = self.app_settings.lock().await.warm_sessions;
+        let keep_warm: HashSet<String> = self
+            .workspaces
+            .lock()
+            .await
+            .values()
+            .filter(|entry| entry.settings.keep_warm)
+            .map(|entry| entry.id.clone())
+            .collect();
+        let sessions: Vec<Arc<WorkspaceSession>> =
+            self.sessions.lock().await.values().cloned().collect();
+        let mut running = Vec::with_capacity(sessions.len());
+        for session in sessions {
+            running.push(RunningSession {
+                keep_warm: keep_warm.contains(&session.entry.id),
+                workspace_id: session.entry.id.clone(),
+                pid: session.child.lock().await.id(),
+            });
+        }
+        run_blocking(move || collect_resource_usage(running, warm_sessions))
+            .await
+            .unwrap_or_else(|_| collect_resource_usage(Vec::new(), warm_sessions))
+    }
+
+    /// Replaces a session the keep-alive gave up on with a new app-server
+    /// process. A session that was reconnected in the meantime is kept.
+    async fn restart_unhealthy_session(&self, workspace_id: &str) {
+        let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
+        if let Err(err) = self
+            .connect_workspace(workspace_id.to_string(), client_version)
+            .await
+        {
+            eprintln!("failed to restart unhealthy session for {workspace_id}: {err}");
+        }
+    }
+
     async fn list_workspaces(&self) -> Vec<WorkspaceInfo> {
+        let paths = self
+            .workspaces
+            .lock()
+            .await
+            .values()
+            .map(|entry| (entry.id.clone(), entry.path.clone()))
+            .collect();
+        let unavailable = check_workspace_paths(paths).await;
         let workspaces = self.workspaces.lock().await;
-        let sessions = self.sessions.lock().await;
+        let mut sessions = self.sessions.lock().await;
         let mut result = Vec::new();
         for entry in workspaces.values() {
             result.push(WorkspaceInfo {
                 id: entry.id.clone(),
                 name: entry.name.clone(),
                 path: entry.path.clone(),
-                connected: sessions.contains_key(&entry.id),
+                connected: connection_state(sessions.get(&entry.id)),
+                path_unavailable: unavailable.contains(&entry.id),
+                read_only: entry.settings.read_only,
                 codex_bin: entry.codex_bin.clone(),
                 kind: entry.kind.clone(),
                 parent_id: entry.parent_id.clone(),
@@ -249,6 +625,7 @@ impl DaemonState {
                 settings: entry.settings.clone(),
             });
         }
+        remove_stale_sessions(&mut sessions, &self.event_sink);
         sort_workspaces(&mut result);
         result
     }
@@ -258,12 +635,37 @@ impl DaemonState {
         workspace_id: String,
         domain_id: String,
         range: String,
+        compare: bool,
+        force_refresh: bool,
+        timezone: Option<String>,
     ) -> Result<DomainTrendSnapshot, String> {
-        let workspaces = self.workspaces.lock().await;
-        let workspace = workspaces
-            .get(&workspace_id)
-            .ok_or_else(|| "workspace not found".to_string())?;
-        obsidian::compute_domain_trends(&workspace.path, &domain_id, &range)
+        let workspace = self.workspace_entry(&workspace_id).await?;
+        let today = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+            .await?
+            .today();
+        run_blocking(move || {
+            obsidian::workspace_domain_trends(
+                &workspace,
+                &domain_id,
+                &range,
+                compare,
+                force_refresh,
+                today,
+            )
+        })
+        .await?
+    }
+
+    /// The zone a request counts days in: its `timezone` param, then
+    /// `AppSettings.timezone`, then `fallback`.
+    async fn client_timezone(
+        &self,
+        requested: Option<&str>,
+        fallback: ClientTimezone,
+    ) -> Result<ClientTimezone, String> {
+        let configured = self.app_settings.lock().await.timezone.clone();
+        resolve_client_timezone(requested, configured.as_deref(), fallback)
     }
 
     async fn is_workspace_path_dir(&self, path: String) -> bool {
@@ -276,26 +678,7 @@ impl DaemonState {
         codex_bin: Option<String>,
         client_version: String,
     ) -> Result<WorkspaceInfo, String> {
-        if !PathBuf::from(&path).is_dir() {
-            return Err("Workspace path must be a folder.".to_string());
-        }
-
-        let name = PathBuf::from(&path)
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("Workspace")
-            .to_string();
-
-        let entry = WorkspaceEntry {
-            id: Uuid::new_v4().to_string(),
-            name: name.clone(),
-            path: path.clone(),
-            codex_bin,
-            kind: WorkspaceKind::Main,
-            parent_id: None,
-            worktree: None,
-            settings: WorkspaceSettings::default(),
-        };
+        let entry = main_workspace_entry(&path, codex_bin)?;
 
         let default_bin = {
             let settings = self.app_settings.lock().await;
@@ -330,7 +713,9 @@ impl DaemonState {
             id: entry.id,
             name: entry.name,
             path: entry.path,
-            connected: true,
+            connected: ConnectionState::Connected,
+            path_unavailable: false,
+            read_only: entry.settings.read_only,
             codex_bin: entry.codex_bin,
             kind: entry.kind,
             parent_id: entry.parent_id,
@@ -339,10 +724,38 @@ impl DaemonState {
         })
     }
 
+    /// Registers the git repositories under `root_path` without starting their
+    /// sessions, writing `workspaces.json` once for the whole batch.
+    async fn scan_and_import_workspaces(
+        &self,
+        root_path: String,
+        max_depth: Option<usize>,
+        dry_run: bool,
+    ) -> Result<WorkspaceImportReport, String> {
+        let root = PathBuf::from(&root_path);
+        let (repos, truncated) =
+            tokio::task::spawn_blocking(move || scan_git_repositories(&root, max_depth))
+                .await
+                .map_err(|err| err.to_string())??;
+        let mut workspaces = self.workspaces.lock().await;
+        let mut updated = workspaces.clone();
+        let report = import_repositories(&mut updated, repos, dry_run, truncated);
+        if report.added() {
+            let list: Vec<_> = updated.values().cloned().collect();
+            write_workspaces(&self.storage_path, &list)?;
+            *workspaces = updated;
+        }
+        Ok(report)
+    }
+
+    /// Creates the worktree and starts its session. When the session fails,
+    /// the worktree and any branch created for it are removed, unless
+    /// `keep_on_spawn_failure` registers it disconnected instead.
     async fn add_worktree(
         &self,
         parent_id: String,
         branch: String,
+        keep_on_spawn_failure: bool,
         client_version: String,
     ) -> Result<WorkspaceInfo, String> {
         let branch = branch.trim().to_string();
@@ -401,17 +814,24 @@ impl DaemonState {
             .await?;
         }
 
+        let created = CreatedWorktree {
+            repo_path: &repo_path,
+            worktree_path: &worktree_path_string,
+            branch: &branch,
+            created_branch: !branch_exists,
+        };
+
         let entry = WorkspaceEntry {
             id: Uuid::new_v4().to_string(),
             name: branch.to_string(),
-            path: worktree_path_string,
+            path: worktree_path_string.clone(),
             codex_bin: parent_entry.codex_bin.clone(),
             kind: WorkspaceKind::Worktree,
             parent_id: Some(parent_entry.id.clone()),
             worktree: Some(WorktreeInfo {
                 branch: branch.to_string(),
             }),
-            settings: WorkspaceSettings::default(),
+            settings: workspace_identity::worktree_settings(&parent_entry.settings),
         };
 
         let default_bin = {
@@ -424,7 +844,7 @@ impl DaemonState {
             let settings = self.app_settings.lock().await;
             codex_args::resolve_workspace_codex_args(&entry, Some(&parent_entry), Some(&settings))
         };
-        let session = spawn_workspace_session(
+        let session = match spawn_workspace_session(
             entry.clone(),
             default_bin,
             codex_args,
@@ -432,14 +852,27 @@ impl DaemonState {
             client_version,
             self.event_sink.clone(),
         )
-        .await?;
-
-        let list = {
-            let mut workspaces = self.workspaces.lock().await;
-            workspaces.insert(entry.id.clone(), entry.clone());
-            workspaces.values().cloned().collect::<Vec<_>>()
+        .await
+        {
+            Ok(session) => session,
+            Err(err) if keep_on_spawn_failure => {
+                if let Err(save_err) = self.register_workspace_entry(&entry).await {
+                    let cause = format!("{err}; saving the workspace also failed: {save_err}");
+                    return Err(worktree_setup::roll_back_worktree(&created, &cause).await);
+                }
+                return Err(worktree_setup::kept_worktree_error(
+                    &created, &entry.id, &err,
+                ));
+            }
+            Err(err) => return Err(worktree_setup::roll_back_worktree(&created, &err).await),
         };
-        write_workspaces(&self.storage_path, &list)?;
+
+        if let Err(err) = self.register_workspace_entry(&entry).await {
+            let mut child = session.child.lock().await;
+            let _ = child.kill().await;
+            let cause = format!("saving the workspace failed: {err}");
+            return Err(worktree_setup::roll_back_worktree(&created, &cause).await);
+        }
 
         self.sessions.lock().await.insert(entry.id.clone(), session);
 
@@ -447,7 +880,9 @@ impl DaemonState {
             id: entry.id,
             name: entry.name,
             path: entry.path,
-            connected: true,
+            connected: ConnectionState::Connected,
+            path_unavailable: false,
+            read_only: entry.settings.read_only,
             codex_bin: entry.codex_bin,
             kind: entry.kind,
             parent_id: entry.parent_id,
@@ -456,7 +891,25 @@ impl DaemonState {
         })
     }
 
-    async fn remove_workspace(&self, id: String) -> Result<(), String> {
+    /// Adds `entry` and writes `workspaces.json`; the entry is dropped again
+    /// when the write fails.
+    async fn register_workspace_entry(&self, entry: &WorkspaceEntry) -> Result<(), String> {
+        let mut workspaces = self.workspaces.lock().await;
+        workspaces.insert(entry.id.clone(), entry.clone());
+        let list = workspaces.values().cloned().collect::<Vec<_>>();
+        let result = write_workspaces(&self.storage_path, &list);
+        if result.is_err() {
+            workspaces.remove(&entry.id);
+        }
+        result
+    }
+
+    async fn remove_workspace(
+        &self,
+        id: String,
+        override_protection: bool,
+        force: bool,
+    ) -> Result<(), String> {
         let (entry, child_worktrees) = {
             let workspaces = self.workspaces.lock().await;
             let entry = workspaces.get(&id).cloned().ok_or("workspace not found")?;
@@ -471,6 +924,20 @@ impl DaemonState {
             (entry, children)
         };
 
+        for child in &child_worktrees {
+            self.ensure_branch_unprotected(
+                &child.id,
+                child
+                    .worktree
+                    .as_ref()
+                    .map(|worktree| worktree.branch.as_str()),
+                override_protection,
+                "remove the worktree",
+            )
+            .await?;
+        }
+        worktree_guard::ensure_no_unpushed_commits(&entry, child_worktrees.clone(), force).await?;
+
         let repo_path = PathBuf::from(&entry.path);
         let mut removed_child_ids = Vec::new();
         let mut failures = Vec::new();
@@ -506,6 +973,8 @@ impl DaemonState {
         let mut ids_to_remove = removed_child_ids;
         if failures.is_empty() {
             self.kill_session(&id).await;
+            self.file_followers.unfollow_workspace(&id);
+            self.quick_commands.cancel_workspace(&id);
             ids_to_remove.push(id.clone());
         }
 
@@ -532,7 +1001,12 @@ impl DaemonState {
         Err(message)
     }
 
-    async fn remove_worktree(&self, id: String) -> Result<(), String> {
+    async fn remove_worktree(
+        &self,
+        id: String,
+        override_protection: bool,
+        force: bool,
+    ) -> Result<(), String> {
         let (entry, parent) = {
             let workspaces = self.workspaces.lock().await;
             let entry = workspaces.get(&id).cloned().ok_or("workspace not found")?;
@@ -547,6 +1021,18 @@ impl DaemonState {
             (entry, parent)
         };
 
+        self.ensure_branch_unprotected(
+            &entry.id,
+            entry
+                .worktree
+                .as_ref()
+                .map(|worktree| worktree.branch.as_str()),
+            override_protection,
+            "remove the worktree",
+        )
+        .await?;
+        worktree_guard::ensure_no_unpushed_commits(&parent, vec![entry.clone()], force).await?;
+
         let parent_path = PathBuf::from(&parent.path);
         let entry_path = PathBuf::from(&entry.path);
         if entry_path.exists() {
@@ -581,12 +1067,70 @@ impl DaemonState {
         Ok(())
     }
 
+    /// Disk usage and merge state of a workspace's worktrees. With
+    /// `remove_safe`, the ones flagged safe are removed through
+    /// `remove_worktree`, so branch protection still applies.
+    async fn worktree_report(
+        &self,
+        parent_id: String,
+        remove_safe: bool,
+    ) -> Result<WorktreeReport, String> {
+        let (parent, children) = {
+            let workspaces = self.workspaces.lock().await;
+            let parent = workspaces
+                .get(&parent_id)
+                .cloned()
+                .ok_or("workspace not found")?;
+            if parent.kind.is_worktree() {
+                return Err("worktree_report needs the parent workspace.".to_string());
+            }
+            let mut children = workspaces
+                .values()
+                .filter(|workspace| workspace.parent_id.as_deref() == Some(&parent_id))
+                .cloned()
+                .collect::<Vec<_>>();
+            children.sort_by(|a, b| a.name.cmp(&b.name));
+            (parent, children)
+        };
+
+        let mut report = worktree_report::build_worktree_report(&parent, children).await?;
+        if remove_safe {
+            for worktree in &mut report.worktrees {
+                if worktree.suggested_action != worktree_report::ACTION_SAFE_TO_REMOVE {
+                    continue;
+                }
+                match self
+                    .remove_worktree(worktree.workspace_id.clone(), false, false)
+                    .await
+                {
+                    Ok(()) => worktree.removed = Some(true),
+                    Err(error) => {
+                        worktree.removed = Some(false);
+                        worktree.remove_error = Some(error);
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn compare_worktrees(
+        &self,
+        workspace_id_a: String,
+        workspace_id_b: String,
+        mode: String,
+    ) -> Result<WorktreeComparison, String> {
+        let a = self.workspace_entry_with_parent(&workspace_id_a).await?;
+        let b = self.workspace_entry_with_parent(&workspace_id_b).await?;
+        worktree_compare::compare_worktrees(a, b, &mode).await
+    }
+
     async fn rename_worktree(
         &self,
         id: String,
         branch: String,
         client_version: String,
-    ) -> Result<WorkspaceInfo, String> {
+    ) -> Result<RenamedWorktree, String> {
         let trimmed = branch.trim();
         if trimmed.is_empty() {
             return Err("Branch name is required.".to_string());
@@ -622,6 +1166,10 @@ impl DaemonState {
             return Err("Branch name is unchanged.".to_string());
         }
 
+        // Read before the rename so tracking can be checked against it afterwards.
+        let upstream = worktree_upstream::branch_upstream(&parent_root, &old_branch)
+            .ok()
+            .flatten();
         run_git_command(&parent_root, &["branch", "-m", &old_branch, &final_branch]).await?;
 
         let worktree_root = self.data_dir.join("worktrees").join(&parent.id);
@@ -669,6 +1217,13 @@ impl DaemonState {
             (snapshot, list)
         };
         write_workspaces(&self.storage_path, &list)?;
+        let upstream = worktree_upstream::carry_over_upstream(
+            &parent_root,
+            &entry_snapshot.id,
+            &old_branch,
+            &final_branch,
+            upstream,
+        );
 
         let was_connected = self.sessions.lock().await.contains_key(&entry_snapshot.id);
         if was_connected {
@@ -712,17 +1267,23 @@ impl DaemonState {
             }
         }
 
-        let connected = self.sessions.lock().await.contains_key(&entry_snapshot.id);
-        Ok(WorkspaceInfo {
+        let connected = connection_state(self.sessions.lock().await.get(&entry_snapshot.id));
+        let workspace = WorkspaceInfo {
             id: entry_snapshot.id,
             name: entry_snapshot.name,
             path: entry_snapshot.path,
             connected,
+            path_unavailable: false,
+            read_only: entry_snapshot.settings.read_only,
             codex_bin: entry_snapshot.codex_bin,
             kind: entry_snapshot.kind,
             parent_id: entry_snapshot.parent_id,
             worktree: entry_snapshot.worktree,
             settings: entry_snapshot.settings,
+        };
+        Ok(RenamedWorktree {
+            workspace,
+            upstream,
         })
     }
 
@@ -811,11 +1372,7 @@ impl DaemonState {
         settings: WorkspaceSettings,
     ) -> Result<WorkspaceInfo, String> {
         let mut settings = settings;
-        if matches!(settings.purpose, Some(types::WorkspacePurpose::Life))
-            && settings.obsidian_root.is_none()
-        {
-            settings.obsidian_root = life::default_obsidian_root();
-        }
+        workspace_settings::normalize_workspace_settings(&mut settings)?;
 
         let (entry_snapshot, list) = {
             let mut workspaces = self.workspaces.lock().await;
@@ -830,19 +1387,46 @@ impl DaemonState {
             (entry_snapshot, list)
         };
         write_workspaces(&self.storage_path, &list)?;
+        Ok(self.workspace_info_for(entry_snapshot).await)
+    }
 
-        let connected = self.sessions.lock().await.contains_key(&id);
-        Ok(WorkspaceInfo {
-            id: entry_snapshot.id,
-            name: entry_snapshot.name,
-            path: entry_snapshot.path,
+    /// Applies only the fields in `patch`, read and written under one lock so
+    /// concurrent updates of other fields are kept.
+    async fn update_workspace_settings_partial(
+        &self,
+        id: String,
+        patch: Value,
+    ) -> Result<WorkspaceInfo, String> {
+        let (entry_snapshot, list) = {
+            let mut workspaces = self.workspaces.lock().await;
+            let entry = workspaces.get_mut(&id).ok_or("workspace not found")?;
+            let mut settings =
+                workspace_settings::patch_workspace_settings(&entry.settings, &patch)?;
+            workspace_settings::normalize_workspace_settings(&mut settings)?;
+            entry.settings = settings;
+            let entry_snapshot = entry.clone();
+            let list: Vec<_> = workspaces.values().cloned().collect();
+            (entry_snapshot, list)
+        };
+        write_workspaces(&self.storage_path, &list)?;
+        Ok(self.workspace_info_for(entry_snapshot).await)
+    }
+
+    async fn workspace_info_for(&self, entry: WorkspaceEntry) -> WorkspaceInfo {
+        let connected = connection_state(self.sessions.lock().await.get(&entry.id));
+        WorkspaceInfo {
+            path_unavailable: is_path_unavailable(&entry.id),
+            read_only: entry.settings.read_only,
+            id: entry.id,
+            name: entry.name,
+            path: entry.path,
             connected,
-            codex_bin: entry_snapshot.codex_bin,
-            kind: entry_snapshot.kind,
-            parent_id: entry_snapshot.parent_id,
-            worktree: entry_snapshot.worktree,
-            settings: entry_snapshot.settings,
-        })
+            codex_bin: entry.codex_bin,
+            kind: entry.kind,
+            parent_id: entry.parent_id,
+            worktree: entry.worktree,
+            settings: entry.settings,
+        }
     }
 
     async fn update_workspace_codex_bin(
@@ -864,12 +1448,14 @@ impl DaemonState {
         };
         write_workspaces(&self.storage_path, &list)?;
 
-        let connected = self.sessions.lock().await.contains_key(&id);
+        let connected = connection_state(self.sessions.lock().await.get(&id));
         Ok(WorkspaceInfo {
             id: entry_snapshot.id,
             name: entry_snapshot.name,
             path: entry_snapshot.path,
             connected,
+            path_unavailable: is_path_unavailable(&id),
+            read_only: entry_snapshot.settings.read_only,
             codex_bin: entry_snapshot.codex_bin,
             kind: entry_snapshot.kind,
             parent_id: entry_snapshot.parent_id,
@@ -878,12 +1464,55 @@ impl DaemonState {
         })
     }
 
+    async fn get_effective_codex_args(
+        &self,
+        workspace_id: String,
+    ) -> Result<codex_args::EffectiveCodexArgs, String> {
+        let (entry, parent_entry) = self.workspace_entry_with_parent(&workspace_id).await?;
+        let spawned_with = self
+            .sessions
+            .lock()
+            .await
+            .get(&workspace_id)
+            .map(|session| session.codex_args.clone());
+        let settings = self.app_settings.lock().await;
+        codex_args::effective_codex_args(
+            &entry,
+            parent_entry.as_ref(),
+            Some(&settings),
+            spawned_with.as_ref().map(|args| args.as_deref()),
+        )
+    }
+
+    async fn set_workspace_codex_args(
+        &self,
+        workspace_id: String,
+        args: Vec<String>,
+        confirm_dangerous: bool,
+    ) -> Result<codex_args::EffectiveCodexArgs, String> {
+        codex_args::validate_codex_args(&args, confirm_dangerous)?;
+        let list: Vec<_> = {
+            let mut workspaces = self.workspaces.lock().await;
+            let entry = workspaces
+                .get_mut(&workspace_id)
+                .ok_or("workspace not found")?;
+            entry.settings.codex_args = codex_args::join_codex_args(&args);
+            workspaces.values().cloned().collect()
+        };
+        write_workspaces(&self.storage_path, &list)?;
+        self.get_effective_codex_args(workspace_id).await
+    }
+
     async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
-        {
-            let sessions = self.sessions.lock().await;
-            if sessions.contains_key(&id) {
-                return Ok(());
-            }
+        ensure_path_available(&id)?;
+        // A session the keep-alive marked unhealthy is replaced.
+        let unhealthy = match self.sessions.lock().await.get(&id) {
+            Some(session) if session.health.is_unhealthy() => true,
+            Some(_) => return Ok(()),
+            None => false,
+        };
+        if unhealthy {
+            self.kill_session(&id).await;
         }
 
         let entry = {
@@ -911,6 +1540,7 @@ impl DaemonState {
             let settings = self.app_settings.lock().await;
             codex_args::resolve_workspace_codex_args(&entry, parent_entry.as_ref(), Some(&settings))
         };
+        let spawn_started = Instant::now();
         let session = spawn_workspace_session(
             entry,
             default_bin,
@@ -921,17 +1551,42 @@ impl DaemonState {
         )
         .await?;
 
-        self.sessions.lock().await.insert(id, session);
+        self.sessions.lock().await.insert(id.clone(), session);
+        warm_pool::record_spawn(&id, spawn_started.elapsed());
+        self.prewarm_domain_trends(&id).await;
         Ok(())
     }
 
-    async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
+    async fn prewarm_domain_trends(&self, workspace_id: &str) {
+        let entry = self.workspaces.lock().await.get(workspace_id).cloned();
+        if let Some(entry) = entry {
+            let timezone = self
+                .client_timezone(None, ClientTimezone::Utc)
+                .await
+                .unwrap_or(ClientTimezone::Utc);
+            obsidian::prewarm_domain_trends(&entry, timezone.today());
+        }
+    }
+
+    /// Saves `settings` and records what changed in the settings audit log
+    /// under `method` and the requesting client.
+    async fn update_app_settings(
+        &self,
+        mut settings: AppSettings,
+        method: &str,
+        client: Option<String>,
+    ) -> Result<AppSettings, String> {
+        settings.supabase_url = normalize_supabase_url(&settings.supabase_url);
+        settings.supabase_anon_key = settings.supabase_anon_key.trim().to_string();
+        settings.timezone = normalize_timezone_setting(settings.timezone.take())?;
         let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
         let _ = codex_config::write_steer_enabled(settings.experimental_steer_enabled);
         let _ =
             codex_config::write_unified_exec_enabled(settings.experimental_unified_exec_enabled);
         write_settings(&self.settings_path, &settings)?;
+        configure_keepalive(&settings.keepalive);
         let mut current = self.app_settings.lock().await;
+        settings_audit::record_settings_change(&self.data_dir, method, client, &current, &settings);
         *current = settings.clone();
         let mut memory_lock = self.memory.write().await;
         *memory_lock = if settings.memory_enabled
@@ -959,32 +1614,64 @@ impl DaemonState {
         Ok(domains.clone())
     }
 
-    async fn domains_create(&self, mut domain: Domain) -> Result<Domain, String> {
+    fn domain_save_result(&self, domain: Domain) -> DomainSaveResult {
+        let warnings = domain_prompt_warnings(&domain, &domain_includes_dir(&self.domains_path));
+        DomainSaveResult { domain, warnings }
+    }
+
+    async fn domains_create(&self, mut domain: Domain) -> Result<DomainSaveResult, String> {
         domain.id = Uuid::new_v4().to_string();
         let domain = Self::normalize_domain(domain);
         let mut domains = self.domains.lock().await;
         domains.push(domain.clone());
         write_domains(&self.domains_path, &domains)?;
-        Ok(domain)
+        Ok(self.domain_save_result(domain))
     }
 
-    async fn domains_update(&self, domain: Domain) -> Result<Domain, String> {
+    async fn domains_update(&self, domain: Domain) -> Result<DomainSaveResult, String> {
         let domain = Self::normalize_domain(domain);
         let mut domains = self.domains.lock().await;
         if let Some(idx) = domains.iter().position(|item| item.id == domain.id) {
             domains[idx] = domain.clone();
             write_domains(&self.domains_path, &domains)?;
-            Ok(domain)
+            Ok(self.domain_save_result(domain))
         } else {
             Err(format!("Domain not found: {}", domain.id))
         }
     }
 
-    async fn domains_delete(&self, domain_id: String) -> Result<(), String> {
+    async fn domain_usage(&self, domain_id: String) -> DomainUsage {
+        domain_usage(&*self.workspaces.lock().await, &domain_id)
+    }
+
+    /// Deletes the domain and moves its workspaces as `delete_domain`
+    /// describes. Workspaces are written before domains, so a failed write
+    /// never leaves a workspace pointing at a deleted domain.
+    async fn domains_delete(
+        &self,
+        domain_id: String,
+        reassign_to: Option<String>,
+        force: bool,
+    ) -> Result<DomainDeleteResult, String> {
+        let mut workspaces = self.workspaces.lock().await;
         let mut domains = self.domains.lock().await;
-        domains.retain(|domain| domain.id != domain_id);
-        write_domains(&self.domains_path, &domains)?;
-        Ok(())
+        let mut next_workspaces = workspaces.clone();
+        let mut next_domains = domains.clone();
+        let result = delete_domain(
+            &mut next_domains,
+            &mut next_workspaces,
+            &domain_id,
+            reassign_to.as_deref(),
+            force,
+        )?;
+        if !result.updated_workspace_ids.is_empty() {
+            let list: Vec<_> = next_workspaces.values().cloned().collect();
+            write_workspaces(&self.storage_path, &list)?;
+            *workspaces = next_workspaces;
+        }
+        write_domains(&self.domains_path, &next_domains)?;
+        *domains = next_domains;
+        Ok(result)
     }
 
     fn normalize_domain(mut domain: Domain) -> Domain {
@@ -1042,24 +1729,138 @@ impl DaemonState {
         };
 
         let root = PathBuf::from(entry.path);
-        Ok(list_workspace_files_inner(&root, 20000))
+        run_blocking(move || list_workspace_files_inner(&root, 20000)).await
     }
 
-    async fn read_workspace_file(
+    async fn scan_workspace_todos(
         &self,
         workspace_id: String,
-        path: String,
-    ) -> Result<WorkspaceFileResponse, String> {
-        let entry = {
-            let workspaces = self.workspaces.lock().await;
-            workspaces
+        markers: Option<Vec<String>>,
+        limit: Option<usize>,
+        with_authors: bool,
+    ) -> Result<Vec<WorkspaceTodo>, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        let root = PathBuf::from(entry.path);
+        let markers = workspace_todos::normalize_todo_markers(markers);
+        let limit = limit.unwrap_or(workspace_todos::DEFAULT_TODO_LIMIT);
+        task::spawn_blocking(move || {
+            workspace_todos::scan_workspace_todos(&root, &markers, limit, with_authors)
+        })
+        .await
+        .map_err(|err| err.to_string())
+    }
+
+    async fn search_workspace_files(
+        &self,
+        workspace_id: String,
+        query: String,
+        options: WorkspaceSearchOptions,
+    ) -> Result<WorkspaceSearchResult, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        let root = PathBuf::from(entry.path);
+        run_blocking(move || {
+            workspace_search::search_workspace_files(&root, &query, &options, SEARCH_TIME_BUDGET)
+        })
+        .await?
+    }
+
+    async fn read_workspace_file(
+        &self,
+        workspace_id: String,
+        path: String,
+        offset: u64,
+        length: Option<u64>,
+    ) -> Result<WorkspaceFileResponse, String> {
+        let entry = {
+            let workspaces = self.workspaces.lock().await;
+            workspaces
                 .get(&workspace_id)
                 .cloned()
                 .ok_or("workspace not found")?
         };
 
         let root = PathBuf::from(entry.path);
-        read_workspace_file_inner(&root, &path)
+        read_workspace_file_chunk(&root, &path, offset, length)
+    }
+
+    async fn workspace_root(&self, workspace_id: &str) -> Result<PathBuf, String> {
+        let workspaces = self.workspaces.lock().await;
+        workspaces
+            .get(workspace_id)
+            .map(|entry| PathBuf::from(&entry.path))
+            .ok_or_else(|| "workspace not found".to_string())
+    }
+
+    async fn tail_workspace_file(
+        &self,
+        workspace_id: String,
+        path: String,
+        from_offset: u64,
+        max_bytes: Option<u64>,
+    ) -> Result<TailChunk, String> {
+        let root = self.workspace_root(&workspace_id).await?;
+        task::spawn_blocking(move || {
+            file_tail::tail_workspace_file(&root, &path, from_offset, max_bytes)
+        })
+        .await
+        .map_err(|err| err.to_string())?
+    }
+
+    async fn follow_file(
+        &self,
+        workspace_id: String,
+        path: String,
+        from_offset: Option<u64>,
+    ) -> Result<u64, String> {
+        let root = self.workspace_root(&workspace_id).await?;
+        self.file_followers
+            .follow(workspace_id, &root, path, from_offset)
+    }
+
+    /// The workspace root, its quick commands and the Codex binary whose PATH
+    /// they run with.
+    async fn quick_command_context(
+        &self,
+        workspace_id: &str,
+    ) -> Result<(PathBuf, Vec<QuickCommand>, Option<String>), String> {
+        let default_bin = self.app_settings.lock().await.codex_bin.clone();
+        let workspaces = self.workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        let codex_bin = entry
+            .codex_bin
+            .clone()
+            .filter(|value| !value.trim().is_empty())
+            .or(default_bin);
+        Ok((
+            PathBuf::from(&entry.path),
+            entry.settings.quick_commands.clone(),
+            codex_bin,
+        ))
+    }
+
+    async fn list_quick_commands(
+        &self,
+        workspace_id: String,
+    ) -> Result<Vec<QuickCommandEntry>, String> {
+        let (root, configured, _) = self.quick_command_context(&workspace_id).await?;
+        self.quick_commands.list(&workspace_id, &root, &configured)
+    }
+
+    async fn run_quick_command(
+        &self,
+        workspace_id: String,
+        command_id: String,
+    ) -> Result<QuickCommandExecution, String> {
+        let (root, configured, codex_bin) = self.quick_command_context(&workspace_id).await?;
+        self.quick_commands.run(
+            &workspace_id,
+            &root,
+            &configured,
+            &command_id,
+            codex_bin.as_deref(),
+        )
     }
 
     async fn read_global_agents_md(&self) -> Result<TextFileResponse, String> {
@@ -1086,41 +1887,52 @@ impl DaemonState {
         &self,
         workspace_id: String,
         range: String,
+        timezone: Option<String>,
     ) -> Result<Value, String> {
+        let today = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+            .await?
+            .today();
         let workspaces = self.workspaces.lock().await;
         let entry = workspaces
             .get(&workspace_id)
             .cloned()
             .ok_or("workspace not found")?;
-        let supabase = {
-            let settings = self.app_settings.lock().await;
-            if settings.supabase_url.trim().is_empty()
-                || settings.supabase_anon_key.trim().is_empty()
-            {
-                None
-            } else {
-                Some((
-                    settings.supabase_url.clone(),
-                    settings.supabase_anon_key.clone(),
-                ))
-            }
-        };
+        let supabase = self.supabase_credentials().await;
         let dashboard = life::build_delivery_dashboard(
             &entry.path,
-            entry.settings.obsidian_root.as_deref(),
+            &entry.settings.effective_obsidian_roots(),
             supabase.as_ref().map(|value| value.0.as_str()),
             supabase.as_ref().map(|value| value.1.as_str()),
             &range,
+            today,
         )
         .await?;
         serde_json::to_value(dashboard).map_err(|err| err.to_string())
     }
 
+    async fn supabase_credentials(&self) -> Option<(String, String)> {
+        let settings = self.app_settings.lock().await;
+        if settings.supabase_url.trim().is_empty() || settings.supabase_anon_key.trim().is_empty() {
+            None
+        } else {
+            Some((
+                settings.supabase_url.clone(),
+                settings.supabase_anon_key.clone(),
+            ))
+        }
+    }
+
     async fn get_nutrition_dashboard(
         &self,
         workspace_id: String,
         range: String,
+        timezone: Option<String>,
     ) -> Result<Value, String> {
+        let today = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+            .await?
+            .today();
         let workspaces = self.workspaces.lock().await;
         let entry = workspaces
             .get(&workspace_id)
@@ -1128,8 +1940,10 @@ impl DaemonState {
             .ok_or("workspace not found")?;
         let dashboard = life::build_nutrition_dashboard(
             &entry.path,
-            entry.settings.obsidian_root.as_deref(),
+            &entry.settings.effective_obsidian_roots(),
+            entry.settings.nutrition_goals.as_ref(),
             &range,
+            today,
         )
         .await?;
         serde_json::to_value(dashboard).map_err(|err| err.to_string())
@@ -1139,7 +1953,12 @@ impl DaemonState {
         &self,
         workspace_id: String,
         range: String,
+        timezone: Option<String>,
     ) -> Result<Value, String> {
+        let today = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+            .await?
+            .today();
         let workspaces = self.workspaces.lock().await;
         let entry = workspaces
             .get(&workspace_id)
@@ -1147,8 +1966,10 @@ impl DaemonState {
             .ok_or("workspace not found")?;
         let dashboard = life::build_exercise_dashboard(
             &entry.path,
-            entry.settings.obsidian_root.as_deref(),
+            &entry.settings.effective_obsidian_roots(),
+            entry.settings.date_order,
             &range,
+            today,
         )
         .await?;
         serde_json::to_value(dashboard).map_err(|err| err.to_string())
@@ -1161,7 +1982,8 @@ impl DaemonState {
             .cloned()
             .ok_or("workspace not found")?;
         let dashboard =
-            life::build_media_library(&entry.path, entry.settings.obsidian_root.as_deref()).await?;
+            life::build_media_library(&entry.path, &entry.settings.effective_obsidian_roots())
+                .await?;
         serde_json::to_value(dashboard).map_err(|err| err.to_string())
     }
 
@@ -1172,49 +1994,132 @@ impl DaemonState {
             .cloned()
             .ok_or("workspace not found")?;
         let dashboard =
-            life::build_youtube_library(&entry.path, entry.settings.obsidian_root.as_deref())
+            life::build_youtube_library(&entry.path, &entry.settings.effective_obsidian_roots())
                 .await?;
         serde_json::to_value(dashboard).map_err(|err| err.to_string())
     }
 
+    async fn list_domain_entities(
+        &self,
+        workspace_id: String,
+        domain_id: String,
+        kind: Option<String>,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Result<Value, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        let page = life::list_domain_entities(
+            &entry.path,
+            &entry.settings.effective_obsidian_roots(),
+            &entry.settings.entity_dirs,
+            &domain_id,
+            kind.as_deref(),
+            offset,
+            limit,
+        )?;
+        serde_json::to_value(page).map_err(|err| err.to_string())
+    }
+
+    async fn read_domain_entity(
+        &self,
+        workspace_id: String,
+        path: String,
+    ) -> Result<Value, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        let entity = life::read_domain_entity(
+            &entry.path,
+            &entry.settings.effective_obsidian_roots(),
+            &entry.settings.entity_dirs,
+            &path,
+        )?;
+        serde_json::to_value(entity).map_err(|err| err.to_string())
+    }
+
+    /// Runs as an `enrich_media_covers` operation that reports every lookup.
+    /// With `detach` it returns the operation id at once.
     async fn enrich_media_covers(
         &self,
         workspace_id: String,
         force: bool,
+        dry_run: bool,
+        detach: bool,
     ) -> Result<Value, String> {
-        let workspaces = self.workspaces.lock().await;
-        let entry = workspaces
+        let entry = self
+            .workspaces
+            .lock()
+            .await
             .get(&workspace_id)
             .cloned()
             .ok_or("workspace not found")?;
-        let settings = self.app_settings.lock().await;
-        let tmdb_key = resolve_api_key(settings.tmdb_api_key.as_str(), "TMDB_API_KEY");
-        let igdb_client_id = resolve_api_key(settings.igdb_client_id.as_str(), "IGDB_CLIENT_ID");
-        let igdb_client_secret =
-            resolve_api_key(settings.igdb_client_secret.as_str(), "IGDB_CLIENT_SECRET");
-        let exa_api_key = if !settings.exa_api_key.trim().is_empty() {
-            Some(settings.exa_api_key.clone())
-        } else {
-            resolve_api_key("", "EXA_API_KEY")
-        };
-        let summary = life::enrich_media_covers(
-            &entry.path,
-            entry.settings.obsidian_root.as_deref(),
-            tmdb_key.as_deref(),
-            igdb_client_id.as_deref(),
-            igdb_client_secret.as_deref(),
-            exa_api_key.as_deref(),
-            force,
-        )
-        .await?;
-        serde_json::to_value(summary).map_err(|err| err.to_string())
+        if !dry_run {
+            ensure_writable(&entry, "enrich media covers")?;
+        }
+        let providers = life::MediaProviderConfig::from_settings(&*self.app_settings.lock().await);
+
+        let ctx = self
+            .operations
+            .start("enrich_media_covers", Some(workspace_id));
+        let operation_id = ctx.id().to_string();
+        let task = tokio::spawn(async move {
+            ctx.progress("enriching", Some(0), None);
+            let on_progress = |progress: &life::MediaCoverProgress| {
+                let percent = (progress.done * 100)
+                    .checked_div(progress.total)
+                    .map(|percent| percent as u8);
+                ctx.progress_detail(
+                    "enriching",
+                    percent,
+                    Some(format!(
+                        "{} ({}): {}/{}",
+                        progress.title, progress.provider, progress.done, progress.total
+                    )),
+                    serde_json::to_value(progress).ok(),
+                );
+            };
+            let is_cancelled = || ctx.is_cancelled();
+            let options = life::MediaCoverOptions {
+                force_refresh: force,
+                dry_run,
+                on_progress: &on_progress,
+                is_cancelled: &is_cancelled,
+            };
+            let result = life::enrich_media_covers_for_roots(
+                &entry.path,
+                &entry.settings.effective_obsidian_roots(),
+                &providers,
+                &options,
+            )
+            .await
+            .and_then(|summary| serde_json::to_value(summary).map_err(|err| err.to_string()));
+            ctx.finish(&result);
+            result
+        });
+        if detach {
+            return Ok(json!({ "ok": true, "operationId": operation_id }));
+        }
+        let mut result = task.await.map_err(|e| e.to_string())??;
+        if let Value::Object(map) = &mut result {
+            map.insert("operationId".to_string(), json!(operation_id));
+        }
+        Ok(result)
+    }
+
+    async fn media_providers_status(&self) -> Result<Value, String> {
+        let providers = life::MediaProviderConfig::from_settings(&*self.app_settings.lock().await);
+        let statuses = life::media_providers_status(&providers).await;
+        serde_json::to_value(statuses).map_err(|err| err.to_string())
     }
 
     async fn get_finance_dashboard(
         &self,
         workspace_id: String,
         range: String,
+        timezone: Option<String>,
     ) -> Result<Value, String> {
+        let today = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+            .await?
+            .today();
         let workspaces = self.workspaces.lock().await;
         let entry = workspaces
             .get(&workspace_id)
@@ -1222,13 +2127,101 @@ impl DaemonState {
             .ok_or("workspace not found")?;
         let dashboard = life::build_finance_dashboard(
             &entry.path,
-            entry.settings.obsidian_root.as_deref(),
+            &entry.settings.effective_obsidian_roots(),
+            entry.settings.date_order,
             &range,
+            today,
         )
         .await?;
         serde_json::to_value(dashboard).map_err(|err| err.to_string())
     }
 
+    async fn export_life_report(
+        &self,
+        workspace_id: String,
+        options: life::LifeReportOptions,
+        timezone: Option<String>,
+    ) -> Result<Value, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        if options.save {
+            ensure_writable(&entry, "save a report")?;
+        }
+        let today = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+            .await?
+            .today();
+        let supabase = self.supabase_credentials().await;
+        let report = life::export_life_report(
+            &entry.path,
+            &entry.settings.effective_obsidian_roots(),
+            entry.settings.date_order,
+            entry.settings.nutrition_goals.clone(),
+            supabase,
+            options,
+            today,
+        )
+        .await?;
+        serde_json::to_value(report).map_err(|err| err.to_string())
+    }
+
+    async fn stream_append(
+        &self,
+        workspace_id: String,
+        date: Option<String>,
+        time: String,
+        text: String,
+        timezone: Option<String>,
+    ) -> Result<Value, String> {
+        let today = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+            .await?
+            .today();
+        let entry = self.workspace_entry(&workspace_id).await?;
+        ensure_writable(&entry, "write to the stream")?;
+        let appended =
+            obsidian::workspace_stream_append(&entry, date.as_deref(), &time, &text, today)?;
+        serde_json::to_value(appended).map_err(|err| err.to_string())
+    }
+
+    async fn mark_bill_paid(
+        &self,
+        workspace_id: String,
+        bill_path: String,
+        paid_date: Option<String>,
+    ) -> Result<Value, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        ensure_writable(&entry, "mark a bill paid")?;
+        let bill = life::mark_bill_paid(
+            &entry.path,
+            &entry.settings.effective_obsidian_roots(),
+            entry.settings.date_order,
+            &bill_path,
+            paid_date.as_deref(),
+        )?;
+        obsidian::invalidate_domain_trends("delivery_finance");
+        obsidian::invalidate_domain_trends("finance");
+        serde_json::to_value(bill).map_err(|err| err.to_string())
+    }
+
+    async fn update_media_item(
+        &self,
+        workspace_id: String,
+        path: String,
+        update: life::MediaItemUpdate,
+    ) -> Result<Value, String> {
+        let entry = self.workspace_entry(&workspace_id).await?;
+        ensure_writable(&entry, "update a media item")?;
+        let item = life::update_media_item(
+            &entry.path,
+            &entry.settings.effective_obsidian_roots(),
+            entry.settings.date_order,
+            &path,
+            update,
+        )?;
+        obsidian::invalidate_domain_trends("media");
+        serde_json::to_value(item).map_err(|err| err.to_string())
+    }
+
     async fn start_thread(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
         let is_life = {
@@ -1252,9 +2245,14 @@ impl DaemonState {
             }
             params.insert("systemPrompt".to_string(), json!(prompt));
         }
-        session
+        let started = Instant::now();
+        let result = session
             .send_request("thread/start", Value::Object(params))
-            .await
+            .await;
+        if result.is_ok() {
+            warm_pool::record_thread_start(&workspace_id, started.elapsed());
+        }
+        result
     }
 
     async fn resume_thread(
@@ -1290,7 +2288,132 @@ impl DaemonState {
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
         let params = json!({ "threadId": thread_id });
-        session.send_request("thread/archive", params).await
+        let response = session.send_request("thread/archive", params).await?;
+        if response.get("error").is_none() {
+            self.thread_views
+                .mark_archived(&workspace_id, std::slice::from_ref(&thread_id))?;
+        }
+        Ok(response)
+    }
+
+    async fn archive_threads(
+        &self,
+        workspace_id: String,
+        thread_ids: Vec<String>,
+    ) -> Result<Vec<ArchiveThreadResult>, String> {
+        let session = self.get_session(&workspace_id).await?;
+        let results = thread_archive::archive_threads(&session, &thread_ids).await;
+        let archived: Vec<String> = results
+            .iter()
+            .filter(|result| result.ok)
+            .map(|result| result.thread_id.clone())
+            .collect();
+        self.thread_views.mark_archived(&workspace_id, &archived)?;
+        Ok(results)
+    }
+
+    async fn threads_overview(
+        &self,
+        workspace_id: String,
+        viewer: String,
+    ) -> Result<thread_activity::ThreadsOverview, String> {
+        let session = self.get_session(&workspace_id).await?;
+        let threads = thread_archive::list_all_threads(&session).await?;
+        Ok(self
+            .thread_views
+            .overview(&viewer, &workspace_id, &session.entry.path, &threads))
+    }
+
+    async fn set_pinned_threads(&self, keys: Vec<String>) -> Result<(), String> {
+        write_pinned_threads(&pinned_threads_path(&self.settings_path), &keys)
+    }
+
+    async fn connected_sessions(&self) -> Vec<(String, Arc<WorkspaceSession>)> {
+        self.sessions
+            .lock()
+            .await
+            .iter()
+            .map(|(id, session)| (id.clone(), Arc::clone(session)))
+            .collect()
+    }
+
+    /// Dry run of the auto-archive policy over connected workspaces. Works
+    /// whether or not the policy is enabled so candidates can be reviewed first.
+    async fn auto_archive_preview(
+        &self,
+        inactive_days: Option<u32>,
+    ) -> Result<Vec<AutoArchiveCandidate>, String> {
+        let inactive_days = match inactive_days {
+            Some(days) => days,
+            None => self.app_settings.lock().await.auto_archive.inactive_days,
+        };
+        let pinned = read_pinned_threads(&pinned_threads_path(&self.settings_path))?;
+        let sessions = self.connected_sessions().await;
+        Ok(thread_archive::auto_archive_candidates(&sessions, &pinned, inactive_days).await)
+    }
+
+    async fn run_auto_archive(&self) {
+        let policy = self.app_settings.lock().await.auto_archive.clone();
+        if !policy.enabled {
+            return;
+        }
+        let candidates = match self.auto_archive_preview(Some(policy.inactive_days)).await {
+            Ok(candidates) => candidates,
+            Err(err) => {
+                eprintln!("Auto-archive skipped: {err}");
+                return;
+            }
+        };
+        let mut by_workspace: HashMap<String, Vec<String>> = HashMap::new();
+        for candidate in candidates {
+            by_workspace
+                .entry(candidate.workspace_id)
+                .or_default()
+                .push(candidate.thread_id);
+        }
+        for (workspace_id, thread_ids) in by_workspace {
+            let Ok(session) = self.get_session(&workspace_id).await else {
+                continue;
+            };
+            let results = thread_archive::archive_threads(&session, &thread_ids).await;
+            let (archived, failed): (Vec<_>, Vec<_>) =
+                results.into_iter().partition(|result| result.ok);
+            eprintln!(
+                "Auto-archived {} thread(s) in workspace {workspace_id} ({} failed)",
+                archived.len(),
+                failed.len()
+            );
+            self.event_sink.emit_app_server_event(AppServerEvent {
+                workspace_id,
+                message: json!({
+                    "method": "thread/autoArchived",
+                    "params": {
+                        "inactiveDays": policy.inactive_days,
+                        "threadIds": archived
+                            .iter()
+                            .map(|result| result.thread_id.clone())
+                            .collect::<Vec<_>>(),
+                        "failed": failed,
+                    }
+                }),
+            });
+        }
+    }
+
+    /// Purges expired attachments, then stores the upload for this workspace.
+    async fn upload_attachment(
+        &self,
+        workspace_id: String,
+        filename: String,
+        data_base64: String,
+    ) -> Result<AttachmentInfo, String> {
+        self.workspace_entry(&workspace_id).await?;
+        attachments::purge_attachments(
+            &self.data_dir,
+            attachments::ATTACHMENT_RETENTION_DAYS,
+            SystemTime::now(),
+        );
+        attachments::save_attachment(&self.data_dir, &workspace_id, &filename, &data_base64)
     }
 
     async fn send_user_message(
@@ -1303,34 +2426,90 @@ impl DaemonState {
         access_mode: Option<String>,
         images: Option<Vec<String>>,
         collaboration_mode: Option<Value>,
+        file_refs: Option<Vec<String>>,
+        best_effort: bool,
+        disable_redaction: bool,
+        disable_recall: bool,
+        extras: Option<Map<String, Value>>,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-        let sandbox_policy = match access_mode.as_str() {
-            "full-access" => json!({
-                "type": "dangerFullAccess"
-            }),
-            "read-only" => json!({
-                "type": "readOnly"
-            }),
-            _ => json!({
-                "type": "workspaceWrite",
-                "writableRoots": [session.entry.path],
-                "networkAccess": true
-            }),
+        let (model, effort) = thread_overrides::apply_thread_overrides(
+            &thread_overrides::thread_overrides_path(&self.data_dir),
+            &session,
+            &self.event_sink,
+            &thread_id,
+            model,
+            effort,
+        )
+        .await;
+        let message = self
+            .assemble_user_message(
+                &workspace_id,
+                &session,
+                &text,
+                file_refs.as_deref(),
+                best_effort,
+                images,
+                disable_redaction,
+                disable_recall,
+            )
+            .await?;
+        let policy = self
+            .turn_policy_for(&workspace_id, access_mode.as_deref())
+            .await?;
+        let budget = self.turn_budget_for(&workspace_id, &policy).await?;
+        let extras = self.turn_extras_for(&workspace_id, extras.as_ref()).await?;
+        let params = message.turn_start_params(
+            &thread_id,
+            &session.entry.path,
+            &policy,
+            model,
+            effort,
+            collaboration_mode,
+            extras,
+        )?;
+        session.turn_budgets.arm(&thread_id, budget);
+        let response = session.send_request("turn/start", params).await;
+        if !matches!(&response, Ok(value) if value.get("error").is_none()) {
+            session.turn_budgets.arm(&thread_id, None);
+        }
+        let response = attach_file_context_report(response?, message.file_context.as_ref());
+        Ok(memory_recall::attach_recall_report(
+            response,
+            message.recall_report.as_ref(),
+        ))
+    }
+
+    /// Everything `send_user_message` does before `turn/start`: file refs,
+    /// images, memory recall and domain instructions.
+    #[allow(clippy::too_many_arguments)]
+    async fn assemble_user_message(
+        &self,
+        workspace_id: &str,
+        session: &WorkspaceSession,
+        text: &str,
+        file_refs: Option<&[String]>,
+        best_effort: bool,
+        images: Option<Vec<String>>,
+        disable_redaction: bool,
+        disable_recall: bool,
+    ) -> Result<AssembledMessage, String> {
+        let (redactor, auto_memory) = {
+            let settings = self.app_settings.lock().await;
+            (
+                Redactor::for_call(&settings.redaction, disable_redaction),
+                settings.auto_memory.clone(),
+            )
         };
-
-        let approval_policy = if access_mode == "full-access" {
-            "never"
-        } else {
-            "on-request"
+        let recall = {
+            let memory = self.memory.read().await.clone();
+            memory_recall::recall_for_message(memory.as_ref(), &auto_memory, text, disable_recall)
+                .await
         };
 
-        let input = build_user_input(&text, images.as_deref())?;
-
-        let (is_life_workspace, domain_instructions) = {
+        let (is_life_workspace, domain) = {
             let workspaces = self.workspaces.lock().await;
-            let workspace = workspaces.get(&workspace_id);
+            let workspace = workspaces.get(workspace_id);
             if let Some(workspace) = workspace {
                 let is_life_workspace = life::is_life_workspace(&workspace.settings);
                 if is_life_workspace {
@@ -1346,7 +2525,7 @@ impl DaemonState {
                                 .domain_id
                                 .as_ref()
                                 .and_then(|id| domains.iter().find(|domain| &domain.id == id))
-                                .map(|domain| domain.system_prompt.clone()),
+                                .cloned(),
                         )
                     } else {
                         (false, None)
@@ -1356,73 +2535,310 @@ impl DaemonState {
                 (false, None)
             }
         };
-
         if is_life_workspace && life::life_debug_enabled() {
-            eprintln!(
-                "[life] send_user_message: skipping per-turn domain injection (thread={})",
-                thread_id
-            );
+            eprintln!("[life] skipping per-turn domain injection (workspace={workspace_id})");
         }
 
-        let params = build_turn_start_params(
-            &thread_id,
-            input,
-            &session.entry.path,
-            approval_policy,
-            sandbox_policy,
-            model,
-            effort,
-            collaboration_mode,
-            domain_instructions,
-        );
-        session.send_request("turn/start", params).await
+        let sources = MessageSources {
+            workspace_id,
+            workspace_path: &session.entry.path,
+            data_dir: &self.data_dir,
+            domain,
+            domains_path: &self.domains_path,
+            redactor: redactor.as_ref(),
+        };
+        assemble_message(&sources, text, file_refs, best_effort, images, recall)
     }
 
-    async fn turn_interrupt(
+    /// The approval and sandbox policy `send_user_message` starts a turn with
+    /// for `access_mode`, after the workspace's `accessLock` and `readOnly`.
+    async fn turn_policy_for(
         &self,
-        workspace_id: String,
-        thread_id: String,
-        turn_id: String,
-    ) -> Result<Value, String> {
-        let session = self.get_session(&workspace_id).await?;
-        let params = json!({
-            "threadId": thread_id,
-            "turnId": turn_id
-        });
-        session.send_request("turn/interrupt", params).await
+        workspace_id: &str,
+        access_mode: Option<&str>,
+    ) -> Result<TurnPolicy, String> {
+        let entry = self.workspace_entry(workspace_id).await?;
+        Ok(turn_policy(
+            access_mode,
+            &entry.path,
+            entry.settings.effective_access_lock(),
+        ))
     }
 
-    async fn start_review(
+    /// The `maxTurnDurationMinutes` budget of a turn started with `policy`.
+    async fn turn_budget_for(
+        &self,
+        workspace_id: &str,
+        policy: &TurnPolicy,
+    ) -> Result<Option<TurnBudget>, String> {
+        let entry = self.workspace_entry(workspace_id).await?;
+        Ok(turn_budget(&entry.settings, policy.access_mode))
+    }
+
+    /// The workspace's `turnExtras` with the call's `extras` laid over them.
+    async fn turn_extras_for(
+        &self,
+        workspace_id: &str,
+        extras: Option<&Map<String, Value>>,
+    ) -> Result<Option<Map<String, Value>>, String> {
+        let entry = self.workspace_entry(workspace_id).await?;
+        Ok(codex_params::resolve_turn_extras(
+            entry.settings.turn_extras.as_ref(),
+            extras,
+        ))
+    }
+
+    /// Sizes of what `send_user_message` would send with these arguments,
+    /// without sending it or remembering the model for the thread.
+    #[allow(clippy::too_many_arguments)]
+    async fn estimate_message_size(
         &self,
         workspace_id: String,
-        thread_id: String,
-        target: Value,
-        delivery: Option<String>,
+        thread_id: Option<String>,
+        text: String,
+        model: Option<String>,
+        images: Option<Vec<String>>,
+        collaboration_mode: Option<Value>,
+        file_refs: Option<Vec<String>>,
+        best_effort: bool,
+        disable_redaction: bool,
+        disable_recall: bool,
+    ) -> Result<MessageSizeEstimate, String> {
+        let session = self.get_session(&workspace_id).await?;
+        let message = self
+            .assemble_user_message(
+                &workspace_id,
+                &session,
+                &text,
+                file_refs.as_deref(),
+                best_effort,
+                images,
+                disable_redaction,
+                disable_recall,
+            )
+            .await?;
+        let (model, context_window) = thread_overrides::resolve_model_window(
+            &thread_overrides::thread_overrides_path(&self.data_dir),
+            &session,
+            thread_id.as_deref(),
+            model.as_deref(),
+        )
+        .await;
+        let policy = self.turn_policy_for(&workspace_id, None).await?;
+        let params = message.turn_start_params(
+            thread_id.as_deref().unwrap_or(""),
+            &session.entry.path,
+            &policy,
+            model.clone(),
+            None,
+            collaboration_mode,
+            None,
+        )?;
+        Ok(message.estimate(&params, model, context_window))
+    }
+
+    /// Resolves `parts` into one message and sizes it with
+    /// `estimate_message_size`. With `send`, the message then goes out through
+    /// `send_user_message`.
+    #[allow(clippy::too_many_arguments)]
+    async fn compose_message(
+        &self,
+        workspace_id: String,
+        parts: Vec<MessagePart>,
+        max_bytes: Option<usize>,
+        send: bool,
+        thread_id: Option<String>,
+        model: Option<String>,
+        effort: Option<String>,
+        access_mode: Option<String>,
+        collaboration_mode: Option<Value>,
+        disable_redaction: bool,
+        disable_recall: bool,
+    ) -> Result<Value, String> {
+        if send && thread_id.is_none() {
+            return Err("threadId is required to send".to_string());
+        }
+        let entry = self.workspace_entry(&workspace_id).await?;
+        let prompts = self
+            .prompts_list(workspace_id.clone())
+            .await?
+            .into_iter()
+            .map(|prompt| PromptSource {
+                path: prompt.path,
+                content: prompt.content,
+            })
+            .collect();
+        let redactor = {
+            let settings = self.app_settings.lock().await;
+            Redactor::for_call(&settings.redaction, disable_redaction)
+        };
+        let sources = ComposeSources {
+            workspace_id: workspace_id.clone(),
+            workspace_path: PathBuf::from(&entry.path),
+            data_dir: self.data_dir.clone(),
+            repo_root: resolve_git_root(&entry),
+            ignore_whitespace: entry.settings.diff_ignore_whitespace,
+            prompts,
+            redactor,
+        };
+        let budget = max_bytes.unwrap_or(DEFAULT_COMPOSE_BUDGET_BYTES);
+        let composed = run_git_blocking(move || compose_message(&sources, &parts, budget)).await?;
+        let images = (!composed.images.is_empty()).then(|| composed.images.clone());
+
+        let estimate = self
+            .estimate_message_size(
+                workspace_id.clone(),
+                thread_id.clone(),
+                composed.text.clone(),
+                model.clone(),
+                images.clone(),
+                collaboration_mode.clone(),
+                None,
+                false,
+                disable_redaction,
+                disable_recall,
+            )
+            .await?;
+        let mut response = serde_json::to_value(&composed).map_err(|err| err.to_string())?;
+        response["estimate"] = serde_json::to_value(estimate).map_err(|err| err.to_string())?;
+        if let (true, Some(thread_id)) = (send, thread_id) {
+            response["sent"] = self
+                .send_user_message(
+                    workspace_id,
+                    thread_id,
+                    composed.text,
+                    model,
+                    effort,
+                    access_mode,
+                    images,
+                    collaboration_mode,
+                    None,
+                    false,
+                    disable_redaction,
+                    disable_recall,
+                    None,
+                )
+                .await?;
+        }
+        Ok(response)
+    }
+
+    async fn turn_interrupt(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        turn_id: String,
+    ) -> Result<Value, String> {
+        let session = self.get_session(&workspace_id).await?;
+        let params = json!({
+            "threadId": thread_id,
+            "turnId": turn_id
+        });
+        session.send_request("turn/interrupt", params).await
+    }
+
+    async fn start_review(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        target: Value,
+        delivery: Option<String>,
+        acknowledge_large: bool,
     ) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
+        if !acknowledge_large {
+            let limits = self.app_settings.lock().await.review_gate.clone();
+            let entry = self.workspace_entry(&workspace_id).await?;
+            if let Ok(repo_root) = resolve_git_root(&entry) {
+                if let Some(too_large) =
+                    review_gate::check_review_size(&repo_root, &target, &limits)
+                {
+                    return Ok(too_large);
+                }
+            }
+        }
+        if let Some(delivery) = delivery.as_deref() {
+            session
+                .capabilities
+                .lock()
+                .await
+                .check_review_delivery(delivery)?;
+        }
         let mut params = Map::new();
         params.insert("threadId".to_string(), json!(thread_id));
-        params.insert("target".to_string(), target);
-        if let Some(delivery) = delivery {
+        params.insert("target".to_string(), target.clone());
+        if let Some(delivery) = delivery.as_ref() {
             params.insert("delivery".to_string(), json!(delivery));
         }
-        session
-            .send_request("review/start", Value::Object(params))
-            .await
+        let response = session
+            .send_optional_request("review/start", Value::Object(params))
+            .await?;
+        thread_activity::record_review_started(
+            &workspace_id,
+            &thread_id,
+            target,
+            delivery,
+            &response,
+        );
+        Ok(response)
+    }
+
+    async fn list_reviews(
+        &self,
+        workspace_id: String,
+        limit: Option<usize>,
+    ) -> Result<Vec<thread_activity::ReviewRecord>, String> {
+        self.workspace_entry(&workspace_id).await?;
+        Ok(thread_activity::list_reviews(&workspace_id, limit))
     }
 
     async fn model_list(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
-        session.send_request("model/list", json!({})).await
+        let response = session.send_request("model/list", json!({})).await?;
+        thread_overrides::record_model_list(&workspace_id, &response);
+        Ok(response)
+    }
+
+    async fn get_thread_overrides(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Option<thread_overrides::ThreadOverrides> {
+        thread_overrides::get_thread_overrides(
+            &thread_overrides::thread_overrides_path(&self.data_dir),
+            &workspace_id,
+            &thread_id,
+        )
+    }
+
+    async fn clear_thread_overrides(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<bool, String> {
+        thread_overrides::clear_thread_overrides(
+            &thread_overrides::thread_overrides_path(&self.data_dir),
+            &workspace_id,
+            &thread_id,
+        )
     }
 
     async fn collaboration_mode_list(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
         session
-            .send_request("collaborationMode/list", json!({}))
+            .send_optional_request("collaborationMode/list", json!({}))
             .await
     }
 
+    async fn session_capabilities(
+        &self,
+        workspace_id: String,
+    ) -> Result<SessionCapabilities, String> {
+        let session = self.get_session(&workspace_id).await?;
+        let capabilities = session.capabilities.lock().await.clone();
+        Ok(capabilities)
+    }
+
     async fn account_rate_limits(&self, workspace_id: String) -> Result<Value, String> {
         let session = self.get_session(&workspace_id).await?;
         session
@@ -1443,6 +2859,7 @@ impl DaemonState {
         workspace_id: String,
         config: Value,
     ) -> Result<Value, String> {
+        self.ensure_writable(&workspace_id, "change skills").await?;
         let session = self.get_session(&workspace_id).await?;
         let mut payload = match config {
             Value::Object(map) => map,
@@ -1453,7 +2870,7 @@ impl DaemonState {
             .or_insert(json!(session.entry.path));
         let payload_value = Value::Object(payload.clone());
         let result = session
-            .send_request("skills/config/write", payload_value)
+            .send_optional_request("skills/config/write", payload_value)
             .await?;
 
         if let Ok(config_path) = self.skills_config_path(&workspace_id).await {
@@ -1526,6 +2943,7 @@ impl DaemonState {
         source_url: String,
         target: String,
         workspace_id: Option<String>,
+        detach: bool,
     ) -> Result<Value, String> {
         let root = self
             .resolve_skill_root(&target, workspace_id.as_deref())
@@ -1543,26 +2961,26 @@ impl DaemonState {
             return Err("Destination already exists".to_string());
         }
 
-        let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-        let status = Command::new(git_bin)
-            .arg("clone")
-            .arg(&source_url)
-            .arg(&dest)
-            .env("PATH", git_env_path())
-            .status()
-            .await
-            .map_err(|e| e.to_string())?;
-
-        if !status.success() {
-            return Err("git clone failed".to_string());
+        let ctx = self
+            .operations
+            .start("skills_install_from_git", workspace_id);
+        let operation_id = ctx.id().to_string();
+        let task = tokio::spawn({
+            let dest = dest.clone();
+            async move {
+                let result = install_skill_from_git(&ctx, &source_url, &dest).await;
+                ctx.finish(&result);
+                result
+            }
+        });
+        if detach {
+            return Ok(json!({ "ok": true, "operationId": operation_id, "path": dest }));
         }
-
-        let skill_md = dest.join("SKILL.md");
-        if !skill_md.exists() {
-            return Err("SKILL.md not found in repo".to_string());
+        let mut result = task.await.map_err(|e| e.to_string())??;
+        if let Value::Object(map) = &mut result {
+            map.insert("operationId".to_string(), json!(operation_id));
         }
-
-        Ok(json!({ "ok": true, "path": dest }))
+        Ok(result)
     }
 
     async fn skills_uninstall(
@@ -1597,6 +3015,7 @@ impl DaemonState {
                     workspace_id.ok_or("workspaceId required for workspace target")?;
                 let workspaces = self.workspaces.lock().await;
                 let entry = workspaces.get(workspace_id).ok_or("workspace not found")?;
+                ensure_writable(entry, "change skills")?;
                 Ok(PathBuf::from(&entry.path).join(".codex").join("skills"))
             }
             _ => Err("Invalid target (use 'global' or 'workspace')".to_string()),
@@ -1679,6 +3098,41 @@ async fn perform_memory_flush(
     }))
 }
 
+async fn install_skill_from_git(
+    ctx: &OperationContext<DaemonEventSink>,
+    source_url: &str,
+    dest: &Path,
+) -> Result<Value, String> {
+    run_git_clone(ctx, source_url, dest).await?;
+
+    ctx.progress(
+        "validating",
+        Some(100),
+        Some("Validating SKILL.md".to_string()),
+    );
+    let skill_md = dest.join("SKILL.md");
+    let descriptor = if skill_md.exists() {
+        parse_skill_md(&skill_md)
+    } else {
+        Err("SKILL.md not found in repo".to_string())
+    };
+    let descriptor = match descriptor {
+        Ok(descriptor) => descriptor,
+        Err(err) => {
+            let _ = std::fs::remove_dir_all(dest);
+            return Err(err);
+        }
+    };
+    let issues = validate_skill(&descriptor);
+
+    Ok(json!({
+        "ok": true,
+        "path": dest,
+        "name": descriptor.name,
+        "issues": issues,
+    }))
+}
+
 fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
     workspaces.sort_by(|a, b| {
         let a_order = a.settings.sort_order.unwrap_or(u32::MAX);
@@ -1740,44 +3194,6 @@ fn list_workspace_files_inner(root: &PathBuf, max_files: usize) -> Vec<String> {
     results
 }
 
-const MAX_WORKSPACE_FILE_BYTES: u64 = 400_000;
-
-fn read_workspace_file_inner(
-    root: &PathBuf,
-    relative_path: &str,
-) -> Result<WorkspaceFileResponse, String> {
-    let canonical_root = root
-        .canonicalize()
-        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
-    let candidate = canonical_root.join(relative_path);
-    let canonical_path = candidate
-        .canonicalize()
-        .map_err(|err| format!("Failed to open file: {err}"))?;
-    if !canonical_path.starts_with(&canonical_root) {
-        return Err("Invalid file path".to_string());
-    }
-    let metadata = std::fs::metadata(&canonical_path)
-        .map_err(|err| format!("Failed to read file metadata: {err}"))?;
-    if !metadata.is_file() {
-        return Err("Path is not a file".to_string());
-    }
-
-    let mut file =
-        File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
-    let mut buffer = Vec::new();
-    file.take(MAX_WORKSPACE_FILE_BYTES + 1)
-        .read_to_end(&mut buffer)
-        .map_err(|err| format!("Failed to read file: {err}"))?;
-
-    let truncated = buffer.len() > MAX_WORKSPACE_FILE_BYTES as usize;
-    if truncated {
-        buffer.truncate(MAX_WORKSPACE_FILE_BYTES as usize);
-    }
-
-    let content = String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8".to_string())?;
-    Ok(WorkspaceFileResponse { content, truncated })
-}
-
 fn read_global_file_inner(filename: &str) -> Result<TextFileResponse, String> {
     let Some(root) = resolve_codex_home() else {
         return Err("Unable to resolve CODEX_HOME".to_string());
@@ -1816,6 +3232,9 @@ async fn run_git_command(repo_path: &Path, args: &[&str]) -> Result<String, Stri
         .output()
         .await
         .map_err(|e| format!("Failed to run git: {e}"))?;
+    // Whatever the command changed, the next status must not come from the
+    // cache.
+    git_status_cache::invalidate(repo_path);
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
@@ -1838,143 +3257,6 @@ fn is_missing_worktree_error(error: &str) -> bool {
     error.contains("is not a working tree")
 }
 
-async fn run_git_command_bytes(repo_path: &PathBuf, args: &[&str]) -> Result<Vec<u8>, String> {
-    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-    let output = Command::new(git_bin)
-        .args(args)
-        .current_dir(repo_path)
-        .env("PATH", git_env_path())
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-    if output.status.success() {
-        Ok(output.stdout)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            Err("Git command failed.".to_string())
-        } else {
-            Err(detail.to_string())
-        }
-    }
-}
-
-async fn run_git_diff(repo_path: &PathBuf, args: &[&str]) -> Result<Vec<u8>, String> {
-    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-    let output = Command::new(git_bin)
-        .args(args)
-        .current_dir(repo_path)
-        .env("PATH", git_env_path())
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-    if output.status.success() || output.status.code() == Some(1) {
-        Ok(output.stdout)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            Err("Git command failed.".to_string())
-        } else {
-            Err(detail.to_string())
-        }
-    }
-}
-
-fn terminal_key(workspace_id: &str, terminal_id: &str) -> String {
-    format!("{workspace_id}:{terminal_id}")
-}
-
-fn shell_path() -> String {
-    env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string())
-}
-
-fn resolve_locale() -> String {
-    let candidate = env::var("LC_ALL")
-        .or_else(|_| env::var("LANG"))
-        .unwrap_or_else(|_| "en_US.UTF-8".to_string());
-    let lower = candidate.to_lowercase();
-    if lower.contains("utf-8") || lower.contains("utf8") {
-        return candidate;
-    }
-    "en_US.UTF-8".to_string()
-}
-
-fn spawn_terminal_reader(
-    event_sink: DaemonEventSink,
-    workspace_id: String,
-    terminal_id: String,
-    mut reader: Box<dyn Read + Send>,
-) {
-    std::thread::spawn(move || {
-        let mut buffer = [0u8; 8192];
-        let mut pending: Vec<u8> = Vec::new();
-        loop {
-            match reader.read(&mut buffer) {
-                Ok(0) => break,
-                Ok(count) => {
-                    pending.extend_from_slice(&buffer[..count]);
-                    loop {
-                        match std::str::from_utf8(&pending) {
-                            Ok(decoded) => {
-                                if !decoded.is_empty() {
-                                    let payload = TerminalOutput {
-                                        workspace_id: workspace_id.clone(),
-                                        terminal_id: terminal_id.clone(),
-                                        data: decoded.to_string(),
-                                    };
-                                    event_sink.emit_terminal_output(payload);
-                                }
-                                pending.clear();
-                                break;
-                            }
-                            Err(error) => {
-                                let valid_up_to = error.valid_up_to();
-                                if valid_up_to == 0 {
-                                    if error.error_len().is_none() {
-                                        break;
-                                    }
-                                    let invalid_len = error.error_len().unwrap_or(1);
-                                    pending.drain(..invalid_len.min(pending.len()));
-                                    continue;
-                                }
-                                let chunk =
-                                    String::from_utf8_lossy(&pending[..valid_up_to]).to_string();
-                                if !chunk.is_empty() {
-                                    let payload = TerminalOutput {
-                                        workspace_id: workspace_id.clone(),
-                                        terminal_id: terminal_id.clone(),
-                                        data: chunk,
-                                    };
-                                    event_sink.emit_terminal_output(payload);
-                                }
-                                pending.drain(..valid_up_to);
-                                if error.error_len().is_none() {
-                                    break;
-                                }
-                                let invalid_len = error.error_len().unwrap_or(1);
-                                pending.drain(..invalid_len.min(pending.len()));
-                            }
-                        }
-                    }
-                }
-                Err(_) => break,
-            }
-        }
-    });
-}
-
 fn resolve_home_dir() -> Option<PathBuf> {
     if let Ok(value) = env::var("HOME") {
         if !value.trim().is_empty() {
@@ -2006,28 +3288,18 @@ fn default_prompts_dir() -> Option<PathBuf> {
     resolve_codex_home().map(|home| home.join("prompts"))
 }
 
-fn workspace_prompts_dir(data_dir: &Path, entry: &WorkspaceEntry) -> Result<PathBuf, String> {
-    Ok(data_dir.join("workspaces").join(&entry.id).join("prompts"))
-}
-
-fn prompt_roots_for_workspace(
-    data_dir: &Path,
-    entry: &WorkspaceEntry,
-) -> Result<Vec<PathBuf>, String> {
-    let mut roots = Vec::new();
-    roots.push(workspace_prompts_dir(data_dir, entry)?);
-    if let Some(global_dir) = default_prompts_dir() {
-        roots.push(global_dir);
-    }
-    Ok(roots)
+/// The workspace's own prompts, its parent's for a worktree, then the global
+/// ones.
+fn prompt_roots_for_workspace(data_dir: &Path, entry: &WorkspaceEntry) -> Vec<PromptRoot> {
+    prompt_roots::prompt_roots(data_dir, entry, default_prompts_dir())
 }
 
-fn ensure_path_within_roots(path: &Path, roots: &[PathBuf]) -> Result<(), String> {
+fn ensure_path_within_roots(path: &Path, roots: &[PromptRoot]) -> Result<(), String> {
     let canonical_path = path
         .canonicalize()
         .map_err(|_| "Invalid prompt path.".to_string())?;
     for root in roots {
-        if let Ok(canonical_root) = root.canonicalize() {
+        if let Ok(canonical_root) = root.dir.canonicalize() {
             if canonical_path.starts_with(&canonical_root) {
                 return Ok(());
             }
@@ -2220,68 +3492,6 @@ fn discover_prompts_in(dir: &Path, scope: Option<&str>) -> Vec<CustomPromptEntry
     out
 }
 
-fn action_paths_for_file(repo_root: &Path, path: &str) -> Vec<String> {
-    let target = normalize_git_path(path).trim().to_string();
-    if target.is_empty() {
-        return Vec::new();
-    }
-
-    let repo = match Repository::open(repo_root) {
-        Ok(repo) => repo,
-        Err(_) => return vec![target],
-    };
-
-    let mut status_options = StatusOptions::new();
-    status_options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .renames_head_to_index(true)
-        .renames_index_to_workdir(true)
-        .include_ignored(false);
-
-    let statuses = match repo.statuses(Some(&mut status_options)) {
-        Ok(statuses) => statuses,
-        Err(_) => return vec![target],
-    };
-
-    for entry in statuses.iter() {
-        let status = entry.status();
-        if !(status.contains(Status::WT_RENAMED) || status.contains(Status::INDEX_RENAMED)) {
-            continue;
-        }
-        let delta = entry.index_to_workdir().or_else(|| entry.head_to_index());
-        let Some(delta) = delta else {
-            continue;
-        };
-        let (Some(old_path), Some(new_path)) = (delta.old_file().path(), delta.new_file().path())
-        else {
-            continue;
-        };
-        let old_path = normalize_git_path(old_path.to_string_lossy().as_ref());
-        let new_path = normalize_git_path(new_path.to_string_lossy().as_ref());
-        if old_path != target && new_path != target {
-            continue;
-        }
-        if old_path == new_path || new_path.is_empty() {
-            return vec![target];
-        }
-        let mut result = Vec::new();
-        if !old_path.is_empty() {
-            result.push(old_path);
-        }
-        if !new_path.is_empty() && !result.contains(&new_path) {
-            result.push(new_path);
-        }
-        return if result.is_empty() {
-            vec![target]
-        } else {
-            result
-        };
-    }
-
-    vec![target]
-}
-
 fn parse_upstream_ref(name: &str) -> Option<(String, String)> {
     let trimmed = name.strip_prefix("refs/remotes/").unwrap_or(name);
     let mut parts = trimmed.splitn(2, '/');
@@ -2329,6 +3539,17 @@ async fn push_with_upstream(repo_root: &Path) -> Result<(), String> {
     run_git_command(repo_root, &["push"]).await.map(|_| ())
 }
 
+/// Pushes a tag to the upstream remote of the current branch, or `origin`.
+async fn push_tag(repo_root: &Path, name: &str) -> Result<(), String> {
+    let remote = upstream_remote_and_branch(repo_root)?
+        .map(|(remote, _)| remote)
+        .unwrap_or_else(|| "origin".to_string());
+    let refspec = format!("refs/tags/{name}");
+    run_git_command(repo_root, &["push", remote.as_str(), refspec.as_str()])
+        .await
+        .map(|_| ())
+}
+
 fn status_for_index(status: Status) -> Option<&'static str> {
     if status.contains(Status::INDEX_NEW) {
         Some("A")
@@ -2372,71 +3593,6 @@ fn status_for_delta(status: git2::Delta) -> &'static str {
     }
 }
 
-fn build_combined_diff(diff: &git2::Diff) -> String {
-    let mut combined_diff = String::new();
-    for (index, delta) in diff.deltas().enumerate() {
-        let path = delta.new_file().path().or_else(|| delta.old_file().path());
-        let Some(path) = path else {
-            continue;
-        };
-        let patch = match git2::Patch::from_diff(diff, index) {
-            Ok(patch) => patch,
-            Err(_) => continue,
-        };
-        let Some(mut patch) = patch else {
-            continue;
-        };
-        let content = match diff_patch_to_string(&mut patch) {
-            Ok(content) => content,
-            Err(_) => continue,
-        };
-        if content.trim().is_empty() {
-            continue;
-        }
-        if !combined_diff.is_empty() {
-            combined_diff.push_str("\n\n");
-        }
-        combined_diff.push_str(&format!("=== {} ===\n", path.display()));
-        combined_diff.push_str(&content);
-    }
-    combined_diff
-}
-
-fn collect_workspace_diff(repo_root: &Path) -> Result<String, String> {
-    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
-    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
-
-    let mut options = DiffOptions::new();
-    let index = repo.index().map_err(|e| e.to_string())?;
-    let diff = match head_tree.as_ref() {
-        Some(tree) => repo
-            .diff_tree_to_index(Some(tree), Some(&index), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-        None => repo
-            .diff_tree_to_index(None, Some(&index), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-    };
-    let combined_diff = build_combined_diff(&diff);
-    if !combined_diff.trim().is_empty() {
-        return Ok(combined_diff);
-    }
-
-    let mut options = DiffOptions::new();
-    options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .show_untracked_content(true);
-    let diff = match head_tree.as_ref() {
-        Some(tree) => repo
-            .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-        None => repo
-            .diff_tree_to_workdir_with_index(None, Some(&mut options))
-            .map_err(|e| e.to_string())?,
-    };
-    Ok(build_combined_diff(&diff))
-}
-
 fn github_repo_from_path(path: &Path) -> Result<String, String> {
     let repo = Repository::open(path).map_err(|e| e.to_string())?;
     let remotes = repo.remotes().map_err(|e| e.to_string())?;
@@ -2488,6 +3644,7 @@ fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
             path: normalize_git_path(&path),
             status: status_value,
             diff: diff_text,
+            intraline: None,
         });
     };
 
@@ -2557,6 +3714,17 @@ impl DaemonState {
             .ok_or("workspace not found".to_string())
     }
 
+    /// The repository git methods run in: the nested repo named by `gitRoot`,
+    /// else the workspace's primary git root.
+    async fn git_root_for(
+        &self,
+        workspace_id: &str,
+        git_root: Option<&str>,
+    ) -> Result<PathBuf, String> {
+        let entry = self.workspace_entry(workspace_id).await?;
+        resolve_git_sub_root(&entry, git_root)
+    }
+
     async fn workspace_entry_with_parent(
         &self,
         workspace_id: &str,
@@ -2573,6 +3741,26 @@ impl DaemonState {
             .cloned();
         Ok((entry, parent_entry))
     }
+
+    async fn ensure_branch_unprotected(
+        &self,
+        workspace_id: &str,
+        branch: Option<&str>,
+        override_protection: bool,
+        action: &str,
+    ) -> Result<(), String> {
+        let (entry, parent_entry) = self.workspace_entry_with_parent(workspace_id).await?;
+        let patterns = {
+            let settings = self.app_settings.lock().await;
+            effective_protected_branches(&entry, parent_entry.as_ref(), &settings)
+        };
+        ensure_branch_not_protected(branch, &patterns, override_protection, action)
+    }
+
+    /// Refuses `action` in a workspace with `readOnly` on.
+    async fn ensure_writable(&self, workspace_id: &str, action: &str) -> Result<(), String> {
+        ensure_workspace_writable(&self.workspaces, workspace_id, action).await
+    }
 }
 
 impl DaemonState {
@@ -2648,6 +3836,8 @@ impl DaemonState {
             worktree: None,
             settings: WorkspaceSettings {
                 group_id: inherited_group_id,
+                color: source_entry.settings.color.clone(),
+                icon: source_entry.settings.icon.clone(),
                 ..WorkspaceSettings::default()
             },
         };
@@ -2700,7 +3890,9 @@ impl DaemonState {
             id: entry.id,
             name: entry.name,
             path: entry.path,
-            connected: true,
+            connected: ConnectionState::Connected,
+            path_unavailable: false,
+            read_only: entry.settings.read_only,
             codex_bin: entry.codex_bin,
             kind: entry.kind,
             parent_id: entry.parent_id,
@@ -2709,7 +3901,12 @@ impl DaemonState {
         })
     }
 
-    async fn apply_worktree_changes(&self, workspace_id: String) -> Result<(), String> {
+    async fn apply_worktree_changes(
+        &self,
+        workspace_id: String,
+        dry_run: bool,
+        only_paths: Option<Vec<String>>,
+    ) -> Result<Value, String> {
         let (entry, parent) = {
             let workspaces = self.workspaces.lock().await;
             let entry = workspaces
@@ -2730,107 +3927,19 @@ impl DaemonState {
         let worktree_root = resolve_git_root(&entry)?;
         let parent_root = resolve_git_root(&parent)?;
 
-        let parent_status = run_git_command_bytes(&parent_root, &["status", "--porcelain"]).await?;
-        if !String::from_utf8_lossy(&parent_status).trim().is_empty() {
-            return Err(
-                "Your current branch has uncommitted changes. Please commit, stash, or discard them before applying worktree changes."
-                    .to_string(),
-            );
-        }
-
-        let mut patch: Vec<u8> = Vec::new();
-        let staged_patch = run_git_diff(
-            &worktree_root,
-            &["diff", "--binary", "--no-color", "--cached"],
-        )
-        .await?;
-        patch.extend_from_slice(&staged_patch);
-        let unstaged_patch =
-            run_git_diff(&worktree_root, &["diff", "--binary", "--no-color"]).await?;
-        patch.extend_from_slice(&unstaged_patch);
-
-        let untracked_output = run_git_command_bytes(
-            &worktree_root,
-            &["ls-files", "--others", "--exclude-standard", "-z"],
-        )
-        .await?;
-        for raw_path in untracked_output.split(|byte| *byte == 0) {
-            if raw_path.is_empty() {
-                continue;
-            }
-            let path = String::from_utf8_lossy(raw_path).to_string();
-            let diff = run_git_diff(
+        if dry_run {
+            let preview = worktree_apply::preview_worktree_changes(
                 &worktree_root,
-                &[
-                    "diff",
-                    "--binary",
-                    "--no-color",
-                    "--no-index",
-                    "--",
-                    null_device_path(),
-                    &path,
-                ],
+                &parent_root,
+                only_paths.as_deref(),
             )
             .await?;
-            patch.extend_from_slice(&diff);
-        }
-
-        if String::from_utf8_lossy(&patch).trim().is_empty() {
-            return Err("No changes to apply.".to_string());
-        }
-
-        let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-        let mut child = Command::new(git_bin)
-            .args(["apply", "--3way", "--whitespace=nowarn", "-"])
-            .current_dir(&parent_root)
-            .env("PATH", git_env_path())
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to run git: {e}"))?;
-
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin
-                .write_all(&patch)
-                .await
-                .map_err(|e| format!("Failed to write git apply input: {e}"))?;
-        }
-
-        let output = child
-            .wait_with_output()
-            .await
-            .map_err(|e| format!("Failed to run git: {e}"))?;
-
-        if output.status.success() {
-            return Ok(());
-        }
-
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("Git apply failed.".to_string());
-        }
-
-        if detail.contains("Applied patch to") {
-            if detail.contains("with conflicts") {
-                return Err(
-                    "Applied with conflicts. Resolve conflicts in the parent repo before retrying."
-                        .to_string(),
-                );
-            }
-            return Err(
-                "Patch applied partially. Resolve changes in the parent repo before retrying."
-                    .to_string(),
-            );
+            return serde_json::to_value(preview).map_err(|err| err.to_string());
         }
-
-        Err(detail.to_string())
+        ensure_writable(&parent, "apply worktree changes")?;
+        worktree_apply::apply_worktree_changes(&worktree_root, &parent_root, only_paths.as_deref())
+            .await?;
+        Ok(json!({ "ok": true }))
     }
 }
 
@@ -2846,78 +3955,36 @@ impl DaemonState {
         terminal_id: String,
         cols: u16,
         rows: u16,
+        initial_command: Option<String>,
+        login_shell: bool,
     ) -> Result<TerminalSessionInfo, String> {
-        if terminal_id.is_empty() {
-            return Err("Terminal id is required".to_string());
-        }
-        let key = terminal_key(&workspace_id, &terminal_id);
-        {
-            let sessions = self.terminal_sessions.lock().await;
-            if let Some(existing) = sessions.get(&key) {
-                return Ok(TerminalSessionInfo {
-                    id: existing.id.clone(),
-                });
-            }
-        }
-
+        ensure_path_available(&workspace_id)?;
         let cwd = self.workspace_path(&workspace_id).await?;
-        let pty_system = native_pty_system();
-        let size = PtySize {
-            rows: rows.max(2),
-            cols: cols.max(2),
-            pixel_width: 0,
-            pixel_height: 0,
+        let (shell_override, audit_log, scrollback_bytes) = {
+            let settings = self.app_settings.lock().await;
+            (
+                settings.terminal_shell.clone(),
+                settings.terminal_audit_log,
+                settings.terminal_scrollback_bytes,
+            )
         };
-        let pair = pty_system
-            .openpty(size)
-            .map_err(|e| format!("Failed to open pty: {e}"))?;
-
-        let mut cmd = CommandBuilder::new(shell_path());
-        cmd.cwd(cwd);
-        cmd.arg("-i");
-        cmd.env("TERM", "xterm-256color");
-        let locale = resolve_locale();
-        cmd.env("LANG", &locale);
-        cmd.env("LC_ALL", &locale);
-        cmd.env("LC_CTYPE", &locale);
-
-        let child = pair
-            .slave
-            .spawn_command(cmd)
-            .map_err(|e| format!("Failed to spawn shell: {e}"))?;
-        let reader = pair
-            .master
-            .try_clone_reader()
-            .map_err(|e| format!("Failed to open pty reader: {e}"))?;
-        let writer = pair
-            .master
-            .take_writer()
-            .map_err(|e| format!("Failed to open pty writer: {e}"))?;
-
-        let session = Arc::new(TerminalSession {
-            id: terminal_id.clone(),
-            master: Mutex::new(pair.master),
-            writer: Mutex::new(writer),
-            child: Mutex::new(child),
-        });
-        let session_id = session.id.clone();
-
-        {
-            let mut sessions = self.terminal_sessions.lock().await;
-            if let Some(existing) = sessions.get(&key) {
-                let mut child = session.child.lock().await;
-                let _ = child.kill();
-                return Ok(TerminalSessionInfo {
-                    id: existing.id.clone(),
-                });
-            }
-            sessions.insert(key, session);
-        }
-
-        let event_sink = self.event_sink.clone();
-        spawn_terminal_reader(event_sink, workspace_id, terminal_id, reader);
-
-        Ok(TerminalSessionInfo { id: session_id })
+        terminal_core::open_terminal(
+            &self.terminal_sessions,
+            self.event_sink.clone(),
+            workspace_id,
+            terminal_id,
+            &cwd,
+            TerminalLaunch {
+                shell_override: shell_override.as_deref(),
+                login_shell,
+                initial_command,
+                audit_dir: audit_log.then_some(self.data_dir.as_path()),
+                scrollback_bytes,
+            },
+            cols,
+            rows,
+        )
+        .await
     }
 
     async fn terminal_write(
@@ -2926,19 +3993,12 @@ impl DaemonState {
         terminal_id: String,
         data: String,
     ) -> Result<(), String> {
-        let key = terminal_key(&workspace_id, &terminal_id);
-        let sessions = self.terminal_sessions.lock().await;
-        let session = sessions
-            .get(&key)
-            .ok_or_else(|| "Terminal session not found".to_string())?;
-        let mut writer = session.writer.lock().await;
-        writer
-            .write_all(data.as_bytes())
-            .map_err(|e| format!("Failed to write to pty: {e}"))?;
-        writer
-            .flush()
-            .map_err(|e| format!("Failed to flush pty: {e}"))?;
-        Ok(())
+        self.ensure_writable(&workspace_id, "write to a terminal")
+            .await?;
+        terminal_core::find_terminal(&self.terminal_sessions, &workspace_id, &terminal_id)
+            .await?
+            .write(&data)
+            .await
     }
 
     async fn terminal_resize(
@@ -2948,22 +4008,10 @@ impl DaemonState {
         cols: u16,
         rows: u16,
     ) -> Result<(), String> {
-        let key = terminal_key(&workspace_id, &terminal_id);
-        let sessions = self.terminal_sessions.lock().await;
-        let session = sessions
-            .get(&key)
-            .ok_or_else(|| "Terminal session not found".to_string())?;
-        let size = PtySize {
-            rows: rows.max(2),
-            cols: cols.max(2),
-            pixel_width: 0,
-            pixel_height: 0,
-        };
-        let master = session.master.lock().await;
-        master
-            .resize(size)
-            .map_err(|e| format!("Failed to resize pty: {e}"))?;
-        Ok(())
+        terminal_core::find_terminal(&self.terminal_sessions, &workspace_id, &terminal_id)
+            .await?
+            .resize(cols, rows)
+            .await
     }
 
     async fn terminal_close(
@@ -2971,37 +4019,31 @@ impl DaemonState {
         workspace_id: String,
         terminal_id: String,
     ) -> Result<(), String> {
-        let key = terminal_key(&workspace_id, &terminal_id);
-        let mut sessions = self.terminal_sessions.lock().await;
-        let session = sessions
-            .remove(&key)
-            .ok_or_else(|| "Terminal session not found".to_string())?;
-        let mut child = session.child.lock().await;
-        let _ = child.kill();
-        Ok(())
+        terminal_core::close_terminal(&self.terminal_sessions, &workspace_id, &terminal_id).await
     }
 }
 
 impl DaemonState {
     async fn prompts_list(&self, workspace_id: String) -> Result<Vec<CustomPromptEntry>, String> {
-        let (workspace_dir, global_dir) = {
+        let roots = {
             let workspaces = self.workspaces.lock().await;
-            let entry = workspaces.get(&workspace_id).cloned();
-            let workspace_dir = entry
-                .as_ref()
-                .and_then(|entry| workspace_prompts_dir(&self.data_dir, entry).ok());
-            (workspace_dir, default_prompts_dir())
+            match workspaces.get(&workspace_id) {
+                Some(entry) => prompt_roots_for_workspace(&self.data_dir, entry),
+                None => default_prompts_dir()
+                    .map(|dir| PromptRoot {
+                        scope: "global",
+                        dir,
+                    })
+                    .into_iter()
+                    .collect(),
+            }
         };
 
         task::spawn_blocking(move || {
             let mut out = Vec::new();
-            if let Some(dir) = workspace_dir {
-                let _ = std::fs::create_dir_all(&dir);
-                out.extend(discover_prompts_in(&dir, Some("workspace")));
-            }
-            if let Some(dir) = global_dir {
-                let _ = std::fs::create_dir_all(&dir);
-                out.extend(discover_prompts_in(&dir, Some("global")));
+            for root in &roots {
+                let _ = std::fs::create_dir_all(&root.dir);
+                out.extend(discover_prompts_in(&root.dir, Some(root.scope)));
             }
             out
         })
@@ -3016,7 +4058,7 @@ impl DaemonState {
                 .get(&workspace_id)
                 .cloned()
                 .ok_or("workspace not found")?;
-            workspace_prompts_dir(&self.data_dir, &entry)?
+            prompt_roots::workspace_prompts_dir(&self.data_dir, &entry.id)
         };
         std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
         Ok(dir.to_string_lossy().to_string())
@@ -3038,24 +4080,14 @@ impl DaemonState {
         content: String,
     ) -> Result<CustomPromptEntry, String> {
         let name = sanitize_prompt_name(&name)?;
-        let (target_dir, resolved_scope) = {
+        let target_dir = {
             let workspaces = self.workspaces.lock().await;
             let entry = workspaces
                 .get(&workspace_id)
                 .cloned()
                 .ok_or("workspace not found")?;
-            match scope.as_str() {
-                "workspace" => {
-                    let dir = workspace_prompts_dir(&self.data_dir, &entry)?;
-                    (dir, "workspace")
-                }
-                "global" => {
-                    let dir =
-                        default_prompts_dir().ok_or("Unable to resolve CODEX_HOME".to_string())?;
-                    (dir, "global")
-                }
-                _ => return Err("Invalid scope.".to_string()),
-            }
+            ensure_scope_writable(&entry, &scope)?;
+            scope_dir(&prompt_roots_for_workspace(&self.data_dir, &entry), &scope)?
         };
         let path = target_dir.join(format!("{name}.md"));
         if path.exists() {
@@ -3073,7 +4105,7 @@ impl DaemonState {
             description,
             argument_hint,
             content,
-            scope: Some(resolved_scope.to_string()),
+            scope: Some(scope),
         })
     }
 
@@ -3091,15 +4123,17 @@ impl DaemonState {
         if !target_path.exists() {
             return Err("Prompt not found.".to_string());
         }
-        {
+        let roots = {
             let workspaces = self.workspaces.lock().await;
             let entry = workspaces
                 .get(&workspace_id)
                 .cloned()
                 .ok_or("workspace not found")?;
-            let roots = prompt_roots_for_workspace(&self.data_dir, &entry)?;
-            ensure_path_within_roots(&target_path, &roots)?;
-        }
+            let roots = prompt_roots_for_workspace(&self.data_dir, &entry);
+            ensure_scope_writable(&entry, scope_of_path(&roots, &target_path))?;
+            roots
+        };
+        ensure_path_within_roots(&target_path, &roots)?;
         let dir = target_path
             .parent()
             .ok_or("Unable to resolve prompt directory.".to_string())?;
@@ -3113,26 +4147,13 @@ impl DaemonState {
         if next_path != target_path {
             std::fs::remove_file(&target_path).map_err(|err| err.to_string())?;
         }
-        let scope = {
-            let workspaces = self.workspaces.lock().await;
-            let entry = workspaces
-                .get(&workspace_id)
-                .cloned()
-                .ok_or("workspace not found")?;
-            let workspace_dir = workspace_prompts_dir(&self.data_dir, &entry)?;
-            if next_path.starts_with(&workspace_dir) {
-                Some("workspace".to_string())
-            } else {
-                Some("global".to_string())
-            }
-        };
         Ok(CustomPromptEntry {
             name,
             path: next_path.to_string_lossy().to_string(),
             description,
             argument_hint,
             content,
-            scope,
+            scope: Some(scope_of_path(&roots, &next_path).to_string()),
         })
     }
 
@@ -3147,7 +4168,8 @@ impl DaemonState {
                 .get(&workspace_id)
                 .cloned()
                 .ok_or("workspace not found")?;
-            let roots = prompt_roots_for_workspace(&self.data_dir, &entry)?;
+            let roots = prompt_roots_for_workspace(&self.data_dir, &entry);
+            ensure_scope_writable(&entry, scope_of_path(&roots, &target))?;
             ensure_path_within_roots(&target, &roots)?;
         }
         std::fs::remove_file(&target).map_err(|err| err.to_string())
@@ -3169,27 +4191,17 @@ impl DaemonState {
                 .get(&workspace_id)
                 .cloned()
                 .ok_or("workspace not found")?;
-            prompt_roots_for_workspace(&self.data_dir, &entry)?
+            let roots = prompt_roots_for_workspace(&self.data_dir, &entry);
+            ensure_scope_writable(&entry, scope_of_path(&roots, &target_path))?;
+            ensure_scope_writable(&entry, &scope)?;
+            roots
         };
         ensure_path_within_roots(&target_path, &roots)?;
         let file_name = target_path
             .file_name()
             .and_then(|value| value.to_str())
             .ok_or("Invalid prompt path.".to_string())?;
-        let target_dir = {
-            let workspaces = self.workspaces.lock().await;
-            let entry = workspaces
-                .get(&workspace_id)
-                .cloned()
-                .ok_or("workspace not found")?;
-            match scope.as_str() {
-                "workspace" => workspace_prompts_dir(&self.data_dir, &entry)?,
-                "global" => {
-                    default_prompts_dir().ok_or("Unable to resolve CODEX_HOME".to_string())?
-                }
-                _ => return Err("Invalid scope.".to_string()),
-            }
-        };
+        let target_dir = scope_dir(&roots, &scope)?;
         let next_path = target_dir.join(file_name);
         if next_path == target_path {
             return Err("Prompt is already in that scope.".to_string());
@@ -3234,294 +4246,361 @@ impl DaemonState {
     async fn get_workspace_diff(&self, workspace_id: &str) -> Result<String, String> {
         let entry = self.workspace_entry(workspace_id).await?;
         let repo_root = resolve_git_root(&entry)?;
-        collect_workspace_diff(&repo_root)
+        let ignore_whitespace = entry.settings.diff_ignore_whitespace;
+        run_git_blocking(move || collect_workspace_diff(&repo_root, ignore_whitespace)).await
     }
 
-    async fn get_git_status(&self, workspace_id: String) -> Result<Value, String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
-        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-
-        let branch_name = repo
-            .head()
-            .ok()
-            .and_then(|head| head.shorthand().map(|s| s.to_string()))
-            .unwrap_or_else(|| "unknown".to_string());
-
-        let mut status_options = StatusOptions::new();
-        status_options
-            .include_untracked(true)
-            .recurse_untracked_dirs(true)
-            .renames_head_to_index(true)
-            .renames_index_to_workdir(true)
-            .include_ignored(false);
-
-        let statuses = repo
-            .statuses(Some(&mut status_options))
-            .map_err(|e| e.to_string())?;
-
-        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
-        let index = repo.index().ok();
+    /// The workspace diff as it may go into a prompt.
+    async fn get_redacted_workspace_diff(
+        &self,
+        workspace_id: &str,
+        disable_redaction: bool,
+    ) -> Result<(String, usize), String> {
+        let diff = self.get_workspace_diff(workspace_id).await?;
+        let redactor =
+            Redactor::for_call(&self.app_settings.lock().await.redaction, disable_redaction);
+        Ok(redact_diff_with(redactor.as_ref(), diff))
+    }
 
-        let mut files = Vec::new();
-        let mut staged_files = Vec::new();
-        let mut unstaged_files = Vec::new();
-        let mut total_additions = 0i64;
-        let mut total_deletions = 0i64;
-        for entry in statuses.iter() {
-            let path = entry.path().unwrap_or("");
-            if path.is_empty() {
-                continue;
+    async fn get_git_status(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        force_refresh: bool,
+    ) -> Result<Value, String> {
+        ensure_path_available(&workspace_id)?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        run_git_blocking(move || {
+            let probe = git_status_cache::probe(&repo_root);
+            if !force_refresh {
+                if let Some(cached) = probe.as_ref().and_then(git_status_cache::cached) {
+                    return Ok(cached);
+                }
             }
-            if let Some(index) = index.as_ref() {
-                if let Some(entry) = index.get_path(Path::new(path), 0) {
-                    if entry.flags_extended & INDEX_SKIP_WORKTREE_FLAG != 0 {
-                        continue;
+            let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+
+            let branch_name = repo
+                .head()
+                .ok()
+                .and_then(|head| head.shorthand().map(|s| s.to_string()))
+                .unwrap_or_else(|| "unknown".to_string());
+
+            let mut status_options = StatusOptions::new();
+            status_options
+                .include_untracked(true)
+                .recurse_untracked_dirs(true)
+                .renames_head_to_index(true)
+                .renames_index_to_workdir(true)
+                .include_ignored(false);
+
+            let statuses = repo
+                .statuses(Some(&mut status_options))
+                .map_err(|e| e.to_string())?;
+
+            let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+            let index = repo.index().ok();
+
+            let mut files = Vec::new();
+            let mut staged_files = Vec::new();
+            let mut unstaged_files = Vec::new();
+            let mut total_additions = 0i64;
+            let mut total_deletions = 0i64;
+            for entry in statuses.iter() {
+                let path = entry.path().unwrap_or("");
+                if path.is_empty() {
+                    continue;
+                }
+                if let Some(index) = index.as_ref() {
+                    if let Some(entry) = index.get_path(Path::new(path), 0) {
+                        if entry.flags_extended & INDEX_SKIP_WORKTREE_FLAG != 0 {
+                            continue;
+                        }
                     }
                 }
-            }
-            let status = entry.status();
-            let normalized_path = normalize_git_path(path);
-            let include_index = status.intersects(
-                Status::INDEX_NEW
-                    | Status::INDEX_MODIFIED
-                    | Status::INDEX_DELETED
-                    | Status::INDEX_RENAMED
-                    | Status::INDEX_TYPECHANGE,
-            );
-            let include_workdir = status.intersects(
-                Status::WT_NEW
-                    | Status::WT_MODIFIED
-                    | Status::WT_DELETED
-                    | Status::WT_RENAMED
-                    | Status::WT_TYPECHANGE,
-            );
-            let mut combined_additions = 0i64;
-            let mut combined_deletions = 0i64;
-
-            if include_index {
-                let (additions, deletions) =
-                    diff_stats_for_path(&repo, head_tree.as_ref(), path, true, false)
-                        .unwrap_or((0, 0));
-                if let Some(status_str) = status_for_index(status) {
-                    staged_files.push(GitFileStatus {
-                        path: normalized_path.clone(),
-                        status: status_str.to_string(),
-                        additions,
-                        deletions,
-                    });
+                let status = entry.status();
+                let normalized_path = normalize_git_path(path);
+                let include_index = status.intersects(
+                    Status::INDEX_NEW
+                        | Status::INDEX_MODIFIED
+                        | Status::INDEX_DELETED
+                        | Status::INDEX_RENAMED
+                        | Status::INDEX_TYPECHANGE,
+                );
+                let include_workdir = status.intersects(
+                    Status::WT_NEW
+                        | Status::WT_MODIFIED
+                        | Status::WT_DELETED
+                        | Status::WT_RENAMED
+                        | Status::WT_TYPECHANGE,
+                );
+                let mut combined_additions = 0i64;
+                let mut combined_deletions = 0i64;
+
+                if include_index {
+                    let (additions, deletions) =
+                        diff_stats_for_path(&repo, head_tree.as_ref(), path, true, false)
+                            .unwrap_or((0, 0));
+                    if let Some(status_str) = status_for_index(status) {
+                        staged_files.push(GitFileStatus {
+                            path: normalized_path.clone(),
+                            status: status_str.to_string(),
+                            additions,
+                            deletions,
+                        });
+                    }
+                    combined_additions += additions;
+                    combined_deletions += deletions;
+                    total_additions += additions;
+                    total_deletions += deletions;
+                }
+
+                if include_workdir {
+                    let (additions, deletions) =
+                        diff_stats_for_path(&repo, head_tree.as_ref(), path, false, true)
+                            .unwrap_or((0, 0));
+                    if let Some(status_str) = status_for_workdir(status) {
+                        unstaged_files.push(GitFileStatus {
+                            path: normalized_path.clone(),
+                            status: status_str.to_string(),
+                            additions,
+                            deletions,
+                        });
+                    }
+                    combined_additions += additions;
+                    combined_deletions += deletions;
+                    total_additions += additions;
+                    total_deletions += deletions;
                 }
-                combined_additions += additions;
-                combined_deletions += deletions;
-                total_additions += additions;
-                total_deletions += deletions;
-            }
 
-            if include_workdir {
-                let (additions, deletions) =
-                    diff_stats_for_path(&repo, head_tree.as_ref(), path, false, true)
-                        .unwrap_or((0, 0));
-                if let Some(status_str) = status_for_workdir(status) {
-                    unstaged_files.push(GitFileStatus {
-                        path: normalized_path.clone(),
+                if include_index || include_workdir {
+                    let status_str = status_for_workdir(status)
+                        .or_else(|| status_for_index(status))
+                        .unwrap_or("--");
+                    files.push(GitFileStatus {
+                        path: normalized_path,
                         status: status_str.to_string(),
-                        additions,
-                        deletions,
+                        additions: combined_additions,
+                        deletions: combined_deletions,
                     });
                 }
-                combined_additions += additions;
-                combined_deletions += deletions;
-                total_additions += additions;
-                total_deletions += deletions;
             }
 
-            if include_index || include_workdir {
-                let status_str = status_for_workdir(status)
-                    .or_else(|| status_for_index(status))
-                    .unwrap_or("--");
-                files.push(GitFileStatus {
-                    path: normalized_path,
-                    status: status_str.to_string(),
-                    additions: combined_additions,
-                    deletions: combined_deletions,
-                });
+            let response = json!({
+                "branchName": branch_name,
+                "files": files,
+                "stagedFiles": staged_files,
+                "unstagedFiles": unstaged_files,
+                "totalAdditions": total_additions,
+                "totalDeletions": total_deletions,
+            });
+            if let Some(probe) = probe {
+                git_status_cache::store(probe, &response);
             }
-        }
-
-        Ok(json!({
-            "branchName": branch_name,
-            "files": files,
-            "stagedFiles": staged_files,
-            "unstagedFiles": unstaged_files,
-            "totalAdditions": total_additions,
-            "totalDeletions": total_deletions,
-        }))
+            Ok(response)
+        })
+        .await
     }
 
-    async fn get_git_diffs(&self, workspace_id: String) -> Result<Vec<GitFileDiff>, String> {
+    async fn get_git_diffs(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        intraline: bool,
+        hunks: bool,
+    ) -> Result<Vec<GitFileDiff>, String> {
+        ensure_path_available(&workspace_id)?;
         let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
-        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
-
-        let mut options = DiffOptions::new();
-        options
-            .include_untracked(true)
-            .recurse_untracked_dirs(true)
-            .show_untracked_content(true);
-
-        let diff = match head_tree.as_ref() {
-            Some(tree) => repo
-                .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
-                .map_err(|e| e.to_string())?,
-            None => repo
-                .diff_tree_to_workdir_with_index(None, Some(&mut options))
-                .map_err(|e| e.to_string())?,
-        };
-
-        let mut results = Vec::new();
-        for (index, delta) in diff.deltas().enumerate() {
-            let path = delta.new_file().path().or_else(|| delta.old_file().path());
-            let Some(path) = path else {
-                continue;
+        let ignore_whitespace = entry.settings.diff_ignore_whitespace;
+        let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+        run_git_blocking(move || {
+            let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+            let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+            let mut options = display_diff_options(ignore_whitespace);
+            options
+                .include_untracked(true)
+                .recurse_untracked_dirs(true)
+                .show_untracked_content(true);
+
+            let diff = match head_tree.as_ref() {
+                Some(tree) => repo
+                    .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
+                    .map_err(|e| e.to_string())?,
+                None => repo
+                    .diff_tree_to_workdir_with_index(None, Some(&mut options))
+                    .map_err(|e| e.to_string())?,
             };
-            let patch = match git2::Patch::from_diff(&diff, index) {
-                Ok(patch) => patch,
-                Err(_) => continue,
-            };
-            let Some(mut patch) = patch else {
-                continue;
-            };
-            let content = match diff_patch_to_string(&mut patch) {
-                Ok(content) => content,
-                Err(_) => continue,
-            };
-            if content.trim().is_empty() {
-                continue;
+
+            let mut results = Vec::new();
+            for (index, delta) in diff.deltas().enumerate() {
+                let path = delta.new_file().path().or_else(|| delta.old_file().path());
+                let Some(path) = path else {
+                    continue;
+                };
+                let patch = match git2::Patch::from_diff(&diff, index) {
+                    Ok(patch) => patch,
+                    Err(_) => continue,
+                };
+                let Some(mut patch) = patch else {
+                    continue;
+                };
+                let whitespace_only = is_whitespace_only_patch(&patch, ignore_whitespace);
+                let content = match diff_patch_to_string(&mut patch) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                };
+                if content.trim().is_empty() && !whitespace_only {
+                    continue;
+                }
+                results.push(GitFileDiff {
+                    path: normalize_git_path(path.to_string_lossy().as_ref()),
+                    diff: content,
+                    is_binary: false,
+                    is_image: false,
+                    old_image_data: None,
+                    new_image_data: None,
+                    old_image_mime: None,
+                    new_image_mime: None,
+                    whitespace_only,
+                    intraline: None,
+                    hunks: None,
+                });
+            }
+            if intraline {
+                for file in &mut results {
+                    file.intraline = intraline_hunks(&file.diff);
+                }
+            }
+            if hunks {
+                let mut by_path = git_hunks::collect_hunks(&repo)?;
+                for file in &mut results {
+                    file.hunks = Some(by_path.remove(&file.path).unwrap_or_default());
+                }
             }
-            results.push(GitFileDiff {
-                path: normalize_git_path(path.to_string_lossy().as_ref()),
-                diff: content,
-                is_binary: false,
-                is_image: false,
-                old_image_data: None,
-                new_image_data: None,
-                old_image_mime: None,
-                new_image_mime: None,
-            });
-        }
 
-        Ok(results)
+            Ok(results)
+        })
+        .await
     }
 
     async fn get_git_log(
         &self,
         workspace_id: String,
+        git_root: Option<String>,
         limit: Option<usize>,
     ) -> Result<GitLogResponse, String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
-        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-        let max_items = limit.unwrap_or(40);
-        let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-        revwalk.push_head().map_err(|e| e.to_string())?;
-        revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
-
-        let mut total = 0usize;
-        for oid_result in revwalk {
-            oid_result.map_err(|e| e.to_string())?;
-            total += 1;
-        }
-
-        let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-        revwalk.push_head().map_err(|e| e.to_string())?;
-        revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
-
-        let mut entries = Vec::new();
-        for oid_result in revwalk.take(max_items) {
-            let oid = oid_result.map_err(|e| e.to_string())?;
-            let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
-            entries.push(commit_to_entry(commit));
-        }
-
-        let mut ahead = 0usize;
-        let mut behind = 0usize;
-        let mut ahead_entries = Vec::new();
-        let mut behind_entries = Vec::new();
-        let mut upstream = None;
-
-        if let Ok(head) = repo.head() {
-            if head.is_branch() {
-                if let Some(branch_name) = head.shorthand() {
-                    if let Ok(branch) = repo.find_branch(branch_name, BranchType::Local) {
-                        if let Ok(upstream_branch) = branch.upstream() {
-                            let upstream_ref = upstream_branch.get();
-                            upstream = upstream_ref
-                                .shorthand()
-                                .map(|name| name.to_string())
-                                .or_else(|| upstream_ref.name().map(|name| name.to_string()));
-                            if let (Some(head_oid), Some(upstream_oid)) =
-                                (head.target(), upstream_ref.target())
-                            {
-                                let (ahead_count, behind_count) = repo
-                                    .graph_ahead_behind(head_oid, upstream_oid)
-                                    .map_err(|e| e.to_string())?;
-                                ahead = ahead_count;
-                                behind = behind_count;
-
-                                let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-                                revwalk.push(head_oid).map_err(|e| e.to_string())?;
-                                revwalk.hide(upstream_oid).map_err(|e| e.to_string())?;
-                                revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
-                                for oid_result in revwalk.take(max_items) {
-                                    let oid = oid_result.map_err(|e| e.to_string())?;
-                                    let commit =
-                                        repo.find_commit(oid).map_err(|e| e.to_string())?;
-                                    ahead_entries.push(commit_to_entry(commit));
-                                }
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        run_git_blocking(move || {
+            let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+            let max_items = limit.unwrap_or(40);
+            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+            revwalk.push_head().map_err(|e| e.to_string())?;
+            revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+
+            let mut total = 0usize;
+            for oid_result in revwalk {
+                oid_result.map_err(|e| e.to_string())?;
+                total += 1;
+            }
+
+            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+            revwalk.push_head().map_err(|e| e.to_string())?;
+            revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
 
-                                let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-                                revwalk.push(upstream_oid).map_err(|e| e.to_string())?;
-                                revwalk.hide(head_oid).map_err(|e| e.to_string())?;
-                                revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
-                                for oid_result in revwalk.take(max_items) {
-                                    let oid = oid_result.map_err(|e| e.to_string())?;
-                                    let commit =
-                                        repo.find_commit(oid).map_err(|e| e.to_string())?;
-                                    behind_entries.push(commit_to_entry(commit));
+            let mut entries = Vec::new();
+            for oid_result in revwalk.take(max_items) {
+                let oid = oid_result.map_err(|e| e.to_string())?;
+                let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+                entries.push(commit_to_entry(commit));
+            }
+
+            let mut ahead = 0usize;
+            let mut behind = 0usize;
+            let mut ahead_entries = Vec::new();
+            let mut behind_entries = Vec::new();
+            let mut upstream = None;
+
+            if let Ok(head) = repo.head() {
+                if head.is_branch() {
+                    if let Some(branch_name) = head.shorthand() {
+                        if let Ok(branch) = repo.find_branch(branch_name, BranchType::Local) {
+                            if let Ok(upstream_branch) = branch.upstream() {
+                                let upstream_ref = upstream_branch.get();
+                                upstream = upstream_ref
+                                    .shorthand()
+                                    .map(|name| name.to_string())
+                                    .or_else(|| upstream_ref.name().map(|name| name.to_string()));
+                                if let (Some(head_oid), Some(upstream_oid)) =
+                                    (head.target(), upstream_ref.target())
+                                {
+                                    let (ahead_count, behind_count) = repo
+                                        .graph_ahead_behind(head_oid, upstream_oid)
+                                        .map_err(|e| e.to_string())?;
+                                    ahead = ahead_count;
+                                    behind = behind_count;
+
+                                    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                                    revwalk.push(head_oid).map_err(|e| e.to_string())?;
+                                    revwalk.hide(upstream_oid).map_err(|e| e.to_string())?;
+                                    revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+                                    for oid_result in revwalk.take(max_items) {
+                                        let oid = oid_result.map_err(|e| e.to_string())?;
+                                        let commit =
+                                            repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                        ahead_entries.push(commit_to_entry(commit));
+                                    }
+
+                                    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                                    revwalk.push(upstream_oid).map_err(|e| e.to_string())?;
+                                    revwalk.hide(head_oid).map_err(|e| e.to_string())?;
+                                    revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+                                    for oid_result in revwalk.take(max_items) {
+                                        let oid = oid_result.map_err(|e| e.to_string())?;
+                                        let commit =
+                                            repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                        behind_entries.push(commit_to_entry(commit));
+                                    }
                                 }
                             }
                         }
                     }
                 }
             }
-        }
 
-        Ok(GitLogResponse {
-            total,
-            entries,
-            ahead,
-            behind,
-            ahead_entries,
-            behind_entries,
-            upstream,
+            Ok(GitLogResponse {
+                total,
+                entries,
+                ahead,
+                behind,
+                ahead_entries,
+                behind_entries,
+                upstream,
+            })
         })
+        .await
     }
 
     async fn get_git_commit_diff(
         &self,
         workspace_id: String,
+        git_root: Option<String>,
         sha: String,
+        intraline: bool,
     ) -> Result<Vec<GitCommitDiff>, String> {
         let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+        let ignore_whitespace = entry.settings.diff_ignore_whitespace;
+        let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
         let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
         let oid = git2::Oid::from_str(&sha).map_err(|e| e.to_string())?;
         let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
         let commit_tree = commit.tree().map_err(|e| e.to_string())?;
         let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
 
-        let mut options = DiffOptions::new();
+        let mut options = display_diff_options(ignore_whitespace);
         let diff = repo
             .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut options))
             .map_err(|e| e.to_string())?;
@@ -3539,11 +4618,12 @@ impl DaemonState {
             let Some(mut patch) = patch else {
                 continue;
             };
+            let whitespace_only = is_whitespace_only_patch(&patch, ignore_whitespace);
             let content = match diff_patch_to_string(&mut patch) {
                 Ok(content) => content,
                 Err(_) => continue,
             };
-            if content.trim().is_empty() {
+            if content.trim().is_empty() && !whitespace_only {
                 continue;
             }
             results.push(GitCommitDiff {
@@ -3556,8 +4636,15 @@ impl DaemonState {
                 new_image_data: None,
                 old_image_mime: None,
                 new_image_mime: None,
+                whitespace_only,
+                intraline: None,
             });
         }
+        if intraline {
+            for file in &mut results {
+                file.intraline = intraline_hunks(&file.diff);
+            }
+        }
 
         Ok(results)
     }
@@ -3579,9 +4666,14 @@ impl DaemonState {
         Ok(remote.url().map(|url| url.to_string()))
     }
 
-    async fn list_git_branches(&self, workspace_id: String) -> Result<Value, String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn list_git_branches(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+    ) -> Result<Value, String> {
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
         let mut branches = Vec::new();
         let refs = repo
@@ -3605,16 +4697,32 @@ impl DaemonState {
         Ok(json!({ "branches": branches }))
     }
 
-    async fn checkout_git_branch(&self, workspace_id: String, name: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn checkout_git_branch(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        name: String,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "check out a branch")
+            .await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
         checkout_branch(&repo, &name).map_err(|e| e.to_string())
     }
 
-    async fn create_git_branch(&self, workspace_id: String, name: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn create_git_branch(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        name: String,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "create a branch")
+            .await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
         let head = repo.head().map_err(|e| e.to_string())?;
         let target = head.peel_to_commit().map_err(|e| e.to_string())?;
@@ -3622,12 +4730,118 @@ impl DaemonState {
             .map_err(|e| e.to_string())?;
         checkout_branch(&repo, &name).map_err(|e| e.to_string())
     }
-}
 
-impl DaemonState {
-    async fn stage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
+    async fn list_git_tags(
+        &self,
+        workspace_id: String,
+        pattern: Option<String>,
+        limit: Option<usize>,
+    ) -> Result<Vec<GitTagInfo>, String> {
         let entry = self.workspace_entry(&workspace_id).await?;
         let repo_root = resolve_git_root(&entry)?;
+        git_tags::list_tags(&repo_root, pattern.as_deref(), limit)
+    }
+
+    async fn create_git_tag(
+        &self,
+        workspace_id: String,
+        name: String,
+        message: Option<String>,
+        target: Option<String>,
+        push: bool,
+    ) -> Result<GitTagInfo, String> {
+        self.ensure_writable(&workspace_id, "create a tag").await?;
+        let entry = self.workspace_entry(&workspace_id).await?;
+        let repo_root = resolve_git_root(&entry)?;
+        let tag = git_tags::create_tag(&repo_root, &name, message.as_deref(), target.as_deref())?;
+        if push {
+            push_tag(&repo_root, &tag.name)
+                .await
+                .map_err(|err| format!("Tag {} was created but not pushed: {err}", tag.name))?;
+        }
+        Ok(tag)
+    }
+
+    async fn delete_git_tag(
+        &self,
+        workspace_id: String,
+        name: String,
+        override_protection: bool,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "delete a tag").await?;
+        let (entry, parent_entry) = self.workspace_entry_with_parent(&workspace_id).await?;
+        let patterns = {
+            let settings = self.app_settings.lock().await;
+            effective_protected_branches(&entry, parent_entry.as_ref(), &settings)
+        };
+        ensure_tag_not_protected(&name, &patterns, override_protection, "delete tag")?;
+        let repo_root = resolve_git_root(&entry)?;
+        git_tags::delete_tag(&repo_root, &name)
+    }
+
+    async fn stash_git(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        message: Option<String>,
+        include_untracked: bool,
+    ) -> Result<GitStashEntry, String> {
+        self.ensure_writable(&workspace_id, "stash changes").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        git_stash::stash_changes(&repo_root, message.as_deref(), include_untracked).await
+    }
+
+    async fn list_git_stashes(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+    ) -> Result<Vec<GitStashEntry>, String> {
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        git_stash::list_stashes(&repo_root).await
+    }
+
+    async fn pop_git_stash(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        index: usize,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "pop a stash").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        git_stash::pop_stash(&repo_root, index).await
+    }
+
+    async fn drop_git_stash(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        index: usize,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "drop a stash").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        git_stash::drop_stash(&repo_root, index).await
+    }
+}
+
+impl DaemonState {
+    async fn stage_git_file(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        path: String,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "stage").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         for path in action_paths_for_file(&repo_root, &path) {
             run_git_command(&repo_root, &["add", "-A", "--", &path])
                 .await
@@ -3636,17 +4850,46 @@ impl DaemonState {
         Ok(())
     }
 
-    async fn stage_git_all(&self, workspace_id: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn apply_git_hunk(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        path: String,
+        selector: HunkSelector,
+        unstage: bool,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, if unstage { "unstage" } else { "stage" })
+            .await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        git_hunks::apply_hunk(&repo_root, &path, &selector, unstage).await
+    }
+
+    async fn stage_git_all(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "stage").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         run_git_command(&repo_root, &["add", "-A"])
             .await
             .map(|_| ())
     }
 
-    async fn unstage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn unstage_git_file(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        path: String,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "unstage").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         for path in action_paths_for_file(&repo_root, &path) {
             run_git_command(&repo_root, &["restore", "--staged", "--", &path])
                 .await
@@ -3655,10 +4898,62 @@ impl DaemonState {
         Ok(())
     }
 
-    async fn revert_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
-        for path in action_paths_for_file(&repo_root, &path) {
+    /// Purges expired snapshots, then captures what the revert is about to discard.
+    async fn capture_revert_undo(
+        &self,
+        workspace_id: &str,
+        repo_root: &Path,
+        paths: Option<&[String]>,
+    ) -> Result<(), String> {
+        let retention_days = self.app_settings.lock().await.revert_undo_retention_days;
+        revert_undo::purge_revert_undos(
+            &self.data_dir,
+            retention_days,
+            chrono::Utc::now().timestamp_millis(),
+        );
+        revert_undo::capture_revert_undo(&self.data_dir, workspace_id, repo_root, paths)
+            .await
+            .map(|_| ())
+    }
+
+    async fn list_revert_undos(&self, workspace_id: String) -> Vec<RevertUndoEntry> {
+        let retention_days = self.app_settings.lock().await.revert_undo_retention_days;
+        revert_undo::purge_revert_undos(
+            &self.data_dir,
+            retention_days,
+            chrono::Utc::now().timestamp_millis(),
+        );
+        revert_undo::list_revert_undos(&self.data_dir, &workspace_id)
+    }
+
+    async fn restore_revert_undo(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        undo_id: String,
+    ) -> Result<RevertUndoEntry, String> {
+        self.ensure_writable(&workspace_id, "restore a revert")
+            .await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        revert_undo::restore_revert_undo(&self.data_dir, &workspace_id, &undo_id, &repo_root).await
+    }
+
+    async fn revert_git_file(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        path: String,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "revert").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        let paths = action_paths_for_file(&repo_root, &path);
+        self.capture_revert_undo(&workspace_id, &repo_root, Some(&paths))
+            .await?;
+        for path in paths {
             if run_git_command(
                 &repo_root,
                 &["restore", "--staged", "--worktree", "--", &path],
@@ -3675,9 +4970,26 @@ impl DaemonState {
         Ok(())
     }
 
-    async fn revert_git_all(&self, workspace_id: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn revert_git_all(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        override_protection: bool,
+    ) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "revert all changes")
+            .await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        self.ensure_branch_unprotected(
+            &workspace_id,
+            current_branch_name(&repo_root).as_deref(),
+            override_protection,
+            "revert all changes",
+        )
+        .await?;
+        self.capture_revert_undo(&workspace_id, &repo_root, None)
+            .await?;
         run_git_command(
             &repo_root,
             &["restore", "--staged", "--worktree", "--", "."],
@@ -3689,45 +5001,176 @@ impl DaemonState {
             .map(|_| ())
     }
 
-    async fn commit_git(&self, workspace_id: String, message: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
-        run_git_command(&repo_root, &["commit", "-m", &message])
-            .await
-            .map(|_| ())
+    async fn commit_git(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+        message: String,
+        options: CommitOptions,
+        override_protection: bool,
+    ) -> Result<GitCommitOutcome, String> {
+        self.ensure_writable(&workspace_id, "commit").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        if options.amend {
+            self.ensure_branch_unprotected(
+                &workspace_id,
+                current_branch_name(&repo_root).as_deref(),
+                override_protection,
+                "amend a commit",
+            )
+            .await?;
+        }
+        git_commit::commit(&repo_root, &message, options).await
     }
 
-    async fn push_git(&self, workspace_id: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn get_last_commit_message(
+        &self,
+        workspace_id: String,
+        git_root: Option<String>,
+    ) -> Result<Option<String>, String> {
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
+        git_commit::last_commit_message(&repo_root)
+    }
+
+    async fn push_git(&self, workspace_id: String, git_root: Option<String>) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "push").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         push_with_upstream(&repo_root).await
     }
 
-    async fn pull_git(&self, workspace_id: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn pull_git(&self, workspace_id: String, git_root: Option<String>) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "pull").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         run_git_command(&repo_root, &["pull"]).await.map(|_| ())
     }
 
-    async fn sync_git(&self, workspace_id: String) -> Result<(), String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
-        let repo_root = resolve_git_root(&entry)?;
+    async fn sync_git(&self, workspace_id: String, git_root: Option<String>) -> Result<(), String> {
+        self.ensure_writable(&workspace_id, "sync").await?;
+        let repo_root = self
+            .git_root_for(&workspace_id, git_root.as_deref())
+            .await?;
         run_git_command(&repo_root, &["pull"]).await.map(|_| ())?;
         push_with_upstream(&repo_root).await
     }
 }
 
 impl DaemonState {
+    /// A prefetched workspace's last fetch, while it is younger than the
+    /// prefetch interval.
+    async fn prefetched_github(
+        &self,
+        workspace_id: &str,
+    ) -> Option<github_prefetch::GithubSnapshot> {
+        let interval = github_prefetch_interval(&self.app_settings.lock().await.github_prefetch);
+        self.github_cache.fresh(workspace_id, interval)
+    }
+
     async fn get_github_issues(
         &self,
         workspace_id: String,
     ) -> Result<GitHubIssuesResponse, String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
+        match self.prefetched_github(&workspace_id).await {
+            Some(snapshot) => Ok(snapshot.issues),
+            None => self.fetch_github_issues(&workspace_id).await,
+        }
+    }
+
+    async fn get_github_pull_requests(
+        &self,
+        workspace_id: String,
+    ) -> Result<GitHubPullRequestsResponse, String> {
+        match self.prefetched_github(&workspace_id).await {
+            Some(snapshot) => Ok(snapshot.pull_requests),
+            None => self.fetch_github_pull_requests(&workspace_id).await,
+        }
+    }
+
+    async fn get_github_check_rollups(
+        &self,
+        workspace_id: String,
+    ) -> Result<Vec<github_prefetch::CheckRollup>, String> {
+        match self.prefetched_github(&workspace_id).await {
+            Some(snapshot) => Ok(snapshot.checks),
+            None => self.fetch_github_check_rollups(&workspace_id).await,
+        }
+    }
+
+    /// Fetches everything the GitHub panel shows and caches it. Nothing is
+    /// stored if the workspace disconnected while gh was running.
+    async fn prefetch_github(&self, workspace_id: &str) -> Result<(), String> {
+        let issues = self.fetch_github_issues(workspace_id).await?;
+        let pull_requests = self.fetch_github_pull_requests(workspace_id).await?;
+        let checks = self.fetch_github_check_rollups(workspace_id).await?;
+        if connection_state(self.sessions.lock().await.get(workspace_id))
+            != ConnectionState::Connected
+        {
+            return Ok(());
+        }
+        let update = self.github_cache.store(
+            workspace_id,
+            github_prefetch::GithubSnapshot {
+                issues,
+                pull_requests,
+                checks,
+                fetched_at: chrono::Utc::now().timestamp_millis(),
+            },
+        );
+        self.event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: workspace_id.to_string(),
+            message: json!({
+                "method": "github/dataUpdated",
+                "params": update,
+            }),
+        });
+        Ok(())
+    }
+
+    async fn fetch_github_check_rollups(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<github_prefetch::CheckRollup>, String> {
+        let entry = self.workspace_entry(workspace_id).await?;
+        let repo_root = resolve_git_root(&entry)?;
+        let repo_name = github_repo_from_path(&repo_root)?;
+        let stdout = github_cli::run_gh(
+            &repo_root,
+            &[
+                "pr",
+                "list",
+                "--repo",
+                &repo_name,
+                "--state",
+                "open",
+                "--limit",
+                "50",
+                "--json",
+                github_prefetch::CHECK_ROLLUP_FIELDS,
+            ],
+        )
+        .await?;
+        let value: Value = serde_json::from_slice(&stdout).map_err(|e| e.to_string())?;
+        Ok(github_prefetch::summarize_checks(&value))
+    }
+
+    async fn fetch_github_issues(
+        &self,
+        workspace_id: &str,
+    ) -> Result<GitHubIssuesResponse, String> {
+        let entry = self.workspace_entry(workspace_id).await?;
         let repo_root = resolve_git_root(&entry)?;
         let repo_name = github_repo_from_path(&repo_root)?;
 
-        let output = Command::new("gh")
-            .args([
+        let stdout = github_cli::run_gh(
+            &repo_root,
+            &[
                 "issue",
                 "list",
                 "--repo",
@@ -3736,62 +5179,44 @@ impl DaemonState {
                 "50",
                 "--json",
                 "number,title,url,updatedAt",
-            ])
-            .current_dir(&repo_root)
-            .output()
-            .await
-            .map_err(|e| format!("Failed to run gh: {e}"))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let detail = if stderr.trim().is_empty() {
-                stdout.trim()
-            } else {
-                stderr.trim()
-            };
-            if detail.is_empty() {
-                return Err("GitHub CLI command failed.".to_string());
-            }
-            return Err(detail.to_string());
-        }
+            ],
+        )
+        .await?;
 
         let issues: Vec<GitHubIssue> =
-            serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+            serde_json::from_slice(&stdout).map_err(|e| e.to_string())?;
 
         let search_query = format!("repo:{repo_name} is:issue is:open");
         let search_query = search_query.replace(' ', "+");
-        let total = match Command::new("gh")
-            .args([
-                "api",
-                &format!("/search/issues?q={search_query}"),
-                "--jq",
-                ".total_count",
-            ])
-            .current_dir(&repo_root)
-            .output()
-            .await
-        {
-            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+        let search_endpoint = format!("/search/issues?q={search_query}");
+        let total = github_cli::run_gh(
+            &repo_root,
+            &["api", &search_endpoint, "--jq", ".total_count"],
+        )
+        .await
+        .ok()
+        .and_then(|stdout| {
+            String::from_utf8_lossy(&stdout)
                 .trim()
                 .parse::<usize>()
-                .unwrap_or(issues.len()),
-            _ => issues.len(),
-        };
+                .ok()
+        })
+        .unwrap_or(issues.len());
 
         Ok(GitHubIssuesResponse { total, issues })
     }
 
-    async fn get_github_pull_requests(
+    async fn fetch_github_pull_requests(
         &self,
-        workspace_id: String,
+        workspace_id: &str,
     ) -> Result<GitHubPullRequestsResponse, String> {
-        let entry = self.workspace_entry(&workspace_id).await?;
+        let entry = self.workspace_entry(workspace_id).await?;
         let repo_root = resolve_git_root(&entry)?;
         let repo_name = github_repo_from_path(&repo_root)?;
 
-        let output = Command::new("gh")
-            .args([
+        let stdout = github_cli::run_gh(
+            &repo_root,
+            &[
                 "pr",
                 "list",
                 "--repo",
@@ -3802,48 +5227,29 @@ impl DaemonState {
                 "50",
                 "--json",
                 "number,title,url,updatedAt,createdAt,body,headRefName,baseRefName,isDraft,author",
-            ])
-            .current_dir(&repo_root)
-            .output()
-            .await
-            .map_err(|e| format!("Failed to run gh: {e}"))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let detail = if stderr.trim().is_empty() {
-                stdout.trim()
-            } else {
-                stderr.trim()
-            };
-            if detail.is_empty() {
-                return Err("GitHub CLI command failed.".to_string());
-            }
-            return Err(detail.to_string());
-        }
+            ],
+        )
+        .await?;
 
         let pull_requests: Vec<GitHubPullRequest> =
-            serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+            serde_json::from_slice(&stdout).map_err(|e| e.to_string())?;
 
         let search_query = format!("repo:{repo_name} is:pr is:open");
         let search_query = search_query.replace(' ', "+");
-        let total = match Command::new("gh")
-            .args([
-                "api",
-                &format!("/search/issues?q={search_query}"),
-                "--jq",
-                ".total_count",
-            ])
-            .current_dir(&repo_root)
-            .output()
-            .await
-        {
-            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+        let search_endpoint = format!("/search/issues?q={search_query}");
+        let total = github_cli::run_gh(
+            &repo_root,
+            &["api", &search_endpoint, "--jq", ".total_count"],
+        )
+        .await
+        .ok()
+        .and_then(|stdout| {
+            String::from_utf8_lossy(&stdout)
                 .trim()
                 .parse::<usize>()
-                .unwrap_or(pull_requests.len()),
-            _ => pull_requests.len(),
-        };
+                .ok()
+        })
+        .unwrap_or(pull_requests.len());
 
         Ok(GitHubPullRequestsResponse {
             total,
@@ -3855,13 +5261,15 @@ impl DaemonState {
         &self,
         workspace_id: String,
         pr_number: u64,
+        intraline: bool,
     ) -> Result<Vec<GitHubPullRequestDiff>, String> {
         let entry = self.workspace_entry(&workspace_id).await?;
         let repo_root = resolve_git_root(&entry)?;
         let repo_name = github_repo_from_path(&repo_root)?;
 
-        let output = Command::new("gh")
-            .args([
+        let stdout = github_cli::run_gh(
+            &repo_root,
+            &[
                 "pr",
                 "diff",
                 &pr_number.to_string(),
@@ -3869,28 +5277,18 @@ impl DaemonState {
                 &repo_name,
                 "--color",
                 "never",
-            ])
-            .current_dir(&repo_root)
-            .output()
-            .await
-            .map_err(|e| format!("Failed to run gh: {e}"))?;
+            ],
+        )
+        .await?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let detail = if stderr.trim().is_empty() {
-                stdout.trim()
-            } else {
-                stderr.trim()
-            };
-            if detail.is_empty() {
-                return Err("GitHub CLI command failed.".to_string());
+        let diff_text = String::from_utf8_lossy(&stdout);
+        let mut results = parse_pr_diff(&diff_text);
+        if intraline {
+            for file in &mut results {
+                file.intraline = intraline_hunks(&file.diff);
             }
-            return Err(detail.to_string());
         }
-
-        let diff_text = String::from_utf8_lossy(&output.stdout);
-        Ok(parse_pr_diff(&diff_text))
+        Ok(results)
     }
 
     async fn get_github_pull_request_comments(
@@ -3906,35 +5304,29 @@ impl DaemonState {
             format!("/repos/{repo_name}/issues/{pr_number}/comments?per_page=30");
         let jq_filter = r#"[.[] | {id, body, createdAt: .created_at, url: .html_url, author: (if .user then {login: .user.login} else null end)}]"#;
 
-        let output = Command::new("gh")
-            .args(["api", &comments_endpoint, "--jq", jq_filter])
-            .current_dir(&repo_root)
-            .output()
-            .await
-            .map_err(|e| format!("Failed to run gh: {e}"))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let detail = if stderr.trim().is_empty() {
-                stdout.trim()
-            } else {
-                stderr.trim()
-            };
-            if detail.is_empty() {
-                return Err("GitHub CLI command failed.".to_string());
-            }
-            return Err(detail.to_string());
-        }
+        let stdout =
+            github_cli::run_gh(&repo_root, &["api", &comments_endpoint, "--jq", jq_filter]).await?;
 
         let comments: Vec<GitHubPullRequestComment> =
-            serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+            serde_json::from_slice(&stdout).map_err(|e| e.to_string())?;
 
         Ok(comments)
     }
 }
 
 impl DaemonState {
+    async fn workspace_doctor(&self, workspace_id: String) -> Result<Value, String> {
+        if !self.workspaces.lock().await.contains_key(&workspace_id) {
+            return Err("workspace not found".to_string());
+        }
+        let connected = self.sessions.lock().await.contains_key(&workspace_id);
+        Ok(json!({
+            "workspaceId": workspace_id,
+            "connected": connected,
+            "lastSpawnError": last_spawn_failure(&workspace_id),
+        }))
+    }
+
     async fn codex_doctor(&self, codex_bin: Option<String>) -> Result<Value, String> {
         let default_bin = {
             let settings = self.app_settings.lock().await;
@@ -4033,8 +5425,120 @@ impl DaemonState {
         }))
     }
 
-    async fn get_commit_message_prompt(&self, workspace_id: String) -> Result<String, String> {
-        let diff = self.get_workspace_diff(&workspace_id).await?;
+    async fn onboarding_status(&self) -> OnboardingStatus {
+        let (codex, git, gh) = tokio::join!(
+            self.codex_doctor(None),
+            onboarding::check_tool("git"),
+            github_cli::gh_auth_status(false),
+        );
+        let workspace_count = self.workspaces.lock().await.len();
+        OnboardingStatus::new(
+            codex,
+            workspace_count,
+            self.settings_path.exists(),
+            git,
+            gh,
+            Some(DaemonCheck {
+                reachable: true,
+                error: None,
+            }),
+        )
+    }
+
+    /// Adds and connects the workspace for `path`, checks its session and
+    /// optionally seeds starter prompts. Steps that an earlier run already
+    /// did are skipped, so running it again is safe.
+    async fn onboarding_bootstrap(
+        &self,
+        path: String,
+        options: BootstrapOptions,
+        client_version: String,
+    ) -> BootstrapReport {
+        let mut report = BootstrapReport::default();
+        let existing = registered_workspace_id(&*self.workspaces.lock().await, &path);
+        let workspace_id = match existing {
+            Some(id) => {
+                report.skipped(STEP_ADD_WORKSPACE, "Already a workspace.");
+                id
+            }
+            None => match self
+                .add_workspace(path, options.codex_bin.clone(), client_version.clone())
+                .await
+            {
+                Ok(workspace) => {
+                    report.done(STEP_ADD_WORKSPACE, format!("Added {}.", workspace.name));
+                    workspace.id
+                }
+                Err(err) => {
+                    report.failed(STEP_ADD_WORKSPACE, err);
+                    report.skip_remaining(STEP_ADD_WORKSPACE, &options);
+                    return report.finish(None);
+                }
+            },
+        };
+
+        let connected = connection_state(self.sessions.lock().await.get(&workspace_id))
+            == ConnectionState::Connected;
+        if connected {
+            report.skipped(STEP_CONNECT_WORKSPACE, "Already connected.");
+        } else {
+            match self
+                .connect_workspace(workspace_id.clone(), client_version)
+                .await
+            {
+                Ok(()) => report.done(STEP_CONNECT_WORKSPACE, "Connected."),
+                Err(err) => report.failed(STEP_CONNECT_WORKSPACE, err),
+            }
+        }
+        // Runs even when connecting failed; it reports why.
+        report.doctor(self.workspace_doctor(workspace_id.clone()).await);
+
+        if options.starter_prompts {
+            match self.create_starter_prompts(&workspace_id).await {
+                Ok(created) if created.is_empty() => {
+                    report.skipped(STEP_STARTER_PROMPTS, "The starter prompts already exist.")
+                }
+                Ok(created) => report.done(
+                    STEP_STARTER_PROMPTS,
+                    format!("Created {}.", created.join(", ")),
+                ),
+                Err(err) => report.failed(STEP_STARTER_PROMPTS, err),
+            }
+        }
+        report.finish(Some(workspace_id))
+    }
+
+    async fn create_starter_prompts(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<&'static str>, String> {
+        let existing = self.prompts_list(workspace_id.to_string()).await?;
+        let mut created = Vec::new();
+        for prompt in
+            onboarding::missing_starter_prompts(existing.iter().map(|entry| entry.name.as_str()))
+        {
+            self.prompts_create(
+                workspace_id.to_string(),
+                "workspace".to_string(),
+                prompt.name.to_string(),
+                Some(prompt.description.to_string()),
+                prompt.argument_hint.map(str::to_string),
+                prompt.content.to_string(),
+            )
+            .await?;
+            created.push(prompt.name);
+        }
+        Ok(created)
+    }
+
+    async fn get_commit_message_prompt(
+        &self,
+        workspace_id: String,
+        disable_redaction: bool,
+    ) -> Result<CommitMessagePrompt, String> {
+        let (diff, redactions) = self
+            .get_redacted_workspace_diff(&workspace_id, disable_redaction)
+            .await?;
         if diff.trim().is_empty() {
             return Err("No changes to generate commit message for".to_string());
         }
@@ -4045,23 +5549,54 @@ Focus on the 'why' rather than the 'what'. Keep the summary line under 72 charac
 Only output the commit message, nothing else.\n\n\
 Changes:\n{diff}"
         );
-        Ok(prompt)
+        Ok(CommitMessagePrompt { prompt, redactions })
+    }
+
+    async fn summarize_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        start_new: bool,
+    ) -> Result<ThreadSummary, String> {
+        let session = self.get_session(&workspace_id).await?;
+        let mut summary = thread_summary::summarize_thread(&session, &thread_id).await?;
+        if start_new {
+            let started = self.start_thread(workspace_id.clone()).await?;
+            let new_thread_id = thread_id_from_start_response(&started)
+                .ok_or("Failed to get threadId for the compacted thread")?;
+            self.send_user_message(
+                workspace_id,
+                new_thread_id.clone(),
+                thread_summary::seed_message(&summary.summary),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                false,
+                false,
+                true,
+                None,
+            )
+            .await?;
+            summary.new_thread_id = Some(new_thread_id);
+        }
+        Ok(summary)
     }
 
-    async fn generate_commit_message(&self, workspace_id: String) -> Result<String, String> {
-        let diff = self.get_workspace_diff(&workspace_id).await?;
+    async fn generate_commit_message(
+        &self,
+        workspace_id: String,
+        disable_redaction: bool,
+    ) -> Result<CommitMessageResult, String> {
+        let (diff, redactions) = self
+            .get_redacted_workspace_diff(&workspace_id, disable_redaction)
+            .await?;
         if diff.trim().is_empty() {
             return Err("No changes to generate commit message for".to_string());
         }
 
-        let prompt = format!(
-            "Generate a concise git commit message for the following changes. \
-Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
-Focus on the 'why' rather than the 'what'. Keep the summary line under 72 characters. \
-Only output the commit message, nothing else.\n\n\
-Changes:\n{diff}"
-        );
-
         let session = {
             let sessions = self.sessions.lock().await;
             sessions
@@ -4069,147 +5604,78 @@ Changes:\n{diff}"
                 .ok_or("workspace not connected")?
                 .clone()
         };
+        let message = generate_commit_message_for_diff(&session, &diff).await?;
+        Ok(CommitMessageResult {
+            message,
+            redactions,
+        })
+    }
 
-        let thread_params = json!({
-            "cwd": session.entry.path,
-            "approvalPolicy": "never"
-        });
-        let thread_result = session.send_request("thread/start", thread_params).await?;
-
-        if let Some(error) = thread_result.get("error") {
-            let error_msg = error
-                .get("message")
-                .and_then(|m| m.as_str())
-                .unwrap_or("Unknown error starting thread");
-            return Err(error_msg.to_string());
-        }
-
-        let thread_id = thread_result
-            .get("result")
-            .and_then(|r| r.get("threadId"))
-            .or_else(|| {
-                thread_result
-                    .get("result")
-                    .and_then(|r| r.get("thread"))
-                    .and_then(|t| t.get("id"))
-            })
-            .or_else(|| thread_result.get("threadId"))
-            .or_else(|| thread_result.get("thread").and_then(|t| t.get("id")))
-            .and_then(|t| t.as_str())
-            .ok_or_else(|| {
-                format!(
-                    "Failed to get threadId from thread/start response: {:?}",
-                    thread_result
-                )
-            })?
-            .to_string();
-
-        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.insert(thread_id.clone(), tx);
-        }
-
-        let turn_params = build_turn_start_params(
+    async fn commit_turn_changes(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        turn_id: String,
+        message: Option<String>,
+        allow_dirty: bool,
+    ) -> Result<TurnCommitResult, String> {
+        ensure_path_available(&workspace_id)?;
+        self.ensure_writable(&workspace_id, "commit").await?;
+        let repo_root = self.git_root_for(&workspace_id, None).await?;
+        let staged = turn_changes::stage_turn_changes(
+            &repo_root,
+            &workspace_id,
             &thread_id,
-            vec![json!({ "type": "text", "text": prompt })],
-            &session.entry.path,
-            "never",
-            json!({ "type": "readOnly" }),
-            None,
-            None,
-            None,
-            None,
-        );
-        let turn_result = session.send_request("turn/start", turn_params).await;
-        let turn_result = match turn_result {
-            Ok(result) => result,
-            Err(error) => {
-                {
-                    let mut callbacks = session.background_thread_callbacks.lock().await;
-                    callbacks.remove(&thread_id);
-                }
-                let archive_params = json!({ "threadId": thread_id.as_str() });
-                let _ = session.send_request("thread/archive", archive_params).await;
-                return Err(error);
+            &turn_id,
+            allow_dirty,
+        )
+        .await?;
+        let message = match message.filter(|message| !message.trim().is_empty()) {
+            Some(message) => message,
+            None => {
+                let redactor = Redactor::for_call(&self.app_settings.lock().await.redaction, false);
+                let (diff, _) = redact_diff_with(redactor.as_ref(), staged.diff.clone());
+                let session = {
+                    let sessions = self.sessions.lock().await;
+                    sessions
+                        .get(&workspace_id)
+                        .ok_or("workspace not connected")?
+                        .clone()
+                };
+                generate_commit_message_for_diff(&session, &diff).await?
             }
         };
-
-        if let Some(error) = turn_result.get("error") {
-            let error_msg = error
-                .get("message")
-                .and_then(|m| m.as_str())
-                .unwrap_or("Unknown error starting turn");
-            {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
-            }
-            let archive_params = json!({ "threadId": thread_id.as_str() });
-            let _ = session.send_request("thread/archive", archive_params).await;
-            return Err(error_msg.to_string());
-        }
-
-        let mut commit_message = String::new();
-        let timeout_duration = Duration::from_secs(60);
-        let collect_result = tokio::time::timeout(timeout_duration, async {
-            while let Some(event) = rx.recv().await {
-                let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
-
-                match method {
-                    "item/agentMessage/delta" => {
-                        if let Some(params) = event.get("params") {
-                            if let Some(delta) = params.get("delta").and_then(|d| d.as_str()) {
-                                commit_message.push_str(delta);
-                            }
-                        }
-                    }
-                    "turn/completed" => {
-                        break;
-                    }
-                    "turn/error" => {
-                        let error_msg = event
-                            .get("params")
-                            .and_then(|p| p.get("error"))
-                            .and_then(|e| e.as_str())
-                            .unwrap_or("Unknown error during commit message generation");
-                        return Err(error_msg.to_string());
-                    }
-                    _ => {}
-                }
-            }
-            Ok(())
-        })
-        .await;
-
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
-        }
-
-        let archive_params = json!({ "threadId": thread_id });
-        let _ = session.send_request("thread/archive", archive_params).await;
-
-        match collect_result {
-            Ok(Ok(())) => {}
-            Ok(Err(e)) => return Err(e),
-            Err(_) => return Err("Timeout waiting for commit message generation".to_string()),
-        }
-
-        let trimmed = commit_message.trim().to_string();
-        if trimmed.is_empty() {
-            return Err("No commit message was generated".to_string());
-        }
-
-        Ok(trimmed)
+        turn_changes::commit_staged_turn_changes(&repo_root, staged, message).await
     }
 
     async fn local_usage_snapshot(
         &self,
         days: Option<u32>,
         workspace_path: Option<String>,
+        timezone: Option<String>,
     ) -> Result<LocalUsageSnapshot, String> {
-        local_usage_core::local_usage_snapshot_core(days, workspace_path).await
+        let timezone = self
+            .client_timezone(timezone.as_deref(), ClientTimezone::Local)
+            .await?;
+        local_usage_core::local_usage_snapshot_core(days, workspace_path, timezone).await
+    }
+
+    /// Rate limits are read through the workspace's session, or any
+    /// connected one without a workspace, when none are cached yet.
+    async fn usage_overview(&self, workspace_id: Option<String>) -> Result<UsageOverview, String> {
+        let workspace = match workspace_id.as_deref() {
+            Some(id) => Some(self.workspace_entry(id).await?),
+            None => None,
+        };
+        let session = {
+            let sessions = self.sessions.lock().await;
+            match workspace_id.as_deref() {
+                Some(id) => sessions.get(id).cloned(),
+                None => sessions.values().next().cloned(),
+            }
+        };
+        let pricing = self.app_settings.lock().await.usage_pricing.clone();
+        Ok(usage_overview::usage_overview(workspace.as_ref(), session, pricing).await)
     }
 }
 
@@ -4468,14 +5934,6 @@ async fn git_get_origin_url(repo_path: &PathBuf) -> Option<String> {
     }
 }
 
-fn null_device_path() -> &'static str {
-    if cfg!(windows) {
-        "NUL"
-    } else {
-        "/dev/null"
-    }
-}
-
 fn default_data_dir() -> PathBuf {
     if let Ok(xdg) = env::var("XDG_DATA_HOME") {
         let trimmed = xdg.trim();
@@ -4493,21 +5951,23 @@ fn default_data_dir() -> PathBuf {
 fn usage() -> String {
     format!(
         "\
-USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth]\n\n\
-OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --token <token>        Shared token required by clients\n  --insecure-no-auth      Disable auth (dev only)\n  -h, --help             Show this help\n"
+USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token>] [--tokens-file <path>] [--insecure-no-auth]\n\n\
+OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --token <token>        Shared token required by clients\n  --tokens-file <path>   JSON object mapping more tokens to \"full\" or \"read-only\"\n  --insecure-no-auth      Disable auth (dev only)\n  --ready-file <path>    Write address and PID here once accepting connections\n  --disable-unauth-health  Require auth for the health RPC\n  --chunk-threshold <bytes>  Chunk larger results for clients that support it (default: {chunk_threshold})\n  --allow-disable-redaction  Let clients skip secret redaction with disableRedaction\n  -h, --help             Show this help\n\n\
+FILES:\n  <data-dir>/{DAEMON_CONFIG_FILE}  listen, token, unauthHealth, chunkThreshold, allowDisableRedaction;\n                         flags win over it. SIGHUP or daemon_reload_config re-reads it.\n",
+        chunk_threshold = rpc_chunks::DEFAULT_CHUNK_THRESHOLD
     )
 }
 
 fn parse_args() -> Result<DaemonConfig, String> {
-    let mut listen = DEFAULT_LISTEN_ADDR
-        .parse::<SocketAddr>()
-        .map_err(|err| err.to_string())?;
-    let mut token = env::var("CODEX_MONITOR_DAEMON_TOKEN")
-        .ok()
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
-    let mut insecure_no_auth = false;
+    let mut overrides = ConfigOverrides {
+        env_token: env::var("CODEX_MONITOR_DAEMON_TOKEN")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty()),
+        ..ConfigOverrides::default()
+    };
     let mut data_dir: Option<PathBuf> = None;
+    let mut ready_file: Option<PathBuf> = None;
 
     let mut args = env::args().skip(1);
     while let Some(arg) = args.next() {
@@ -4518,7 +5978,8 @@ fn parse_args() -> Result<DaemonConfig, String> {
             }
             "--listen" => {
                 let value = args.next().ok_or("--listen requires a value")?;
-                listen = value.parse::<SocketAddr>().map_err(|err| err.to_string())?;
+                overrides.listen =
+                    Some(value.parse::<SocketAddr>().map_err(|err| err.to_string())?);
             }
             "--token" => {
                 let value = args.next().ok_or("--token requires a value")?;
@@ -4526,7 +5987,15 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 if trimmed.is_empty() {
                     return Err("--token requires a non-empty value".to_string());
                 }
-                token = Some(trimmed.to_string());
+                overrides.token = Some(trimmed.to_string());
+            }
+            "--tokens-file" => {
+                let value = args.next().ok_or("--tokens-file requires a value")?;
+                let trimmed = value.trim();
+                if trimmed.is_empty() {
+                    return Err("--tokens-file requires a non-empty value".to_string());
+                }
+                overrides.tokens_file = Some(PathBuf::from(trimmed));
             }
             "--data-dir" => {
                 let value = args.next().ok_or("--data-dir requires a value")?;
@@ -4537,24 +6006,54 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 data_dir = Some(PathBuf::from(trimmed));
             }
             "--insecure-no-auth" => {
-                insecure_no_auth = true;
-                token = None;
+                overrides.insecure_no_auth = true;
+            }
+            "--ready-file" => {
+                let value = args.next().ok_or("--ready-file requires a value")?;
+                let trimmed = value.trim();
+                if trimmed.is_empty() {
+                    return Err("--ready-file requires a non-empty value".to_string());
+                }
+                ready_file = Some(PathBuf::from(trimmed));
+            }
+            "--disable-unauth-health" => {
+                overrides.unauth_health = Some(false);
+            }
+            "--chunk-threshold" => {
+                let value = args.next().ok_or("--chunk-threshold requires a value")?;
+                overrides.chunk_threshold = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|bytes| *bytes > 0)
+                        .ok_or("--chunk-threshold requires a positive number of bytes")?,
+                );
+            }
+            "--allow-disable-redaction" => {
+                overrides.allow_disable_redaction = Some(true);
             }
             _ => return Err(format!("Unknown argument: {arg}")),
         }
     }
 
-    if token.is_none() && !insecure_no_auth {
-        return Err(
-            "Missing --token (or set CODEX_MONITOR_DAEMON_TOKEN). Use --insecure-no-auth for local dev only."
-                .to_string(),
-        );
-    }
+    let data_dir = data_dir.unwrap_or_else(default_data_dir);
+    let file = daemon_config::read_config_file(&data_dir.join(DAEMON_CONFIG_FILE))?;
+    let listen = match daemon_config::resolve_listen(&file, &overrides)? {
+        Some(listen) => listen,
+        None => DEFAULT_LISTEN_ADDR
+            .parse::<SocketAddr>()
+            .map_err(|err| err.to_string())?,
+    };
+    let runtime =
+        daemon_config::resolve_runtime(&file, &overrides, rpc_chunks::DEFAULT_CHUNK_THRESHOLD)?;
 
     Ok(DaemonConfig {
         listen,
-        token,
-        data_dir: data_dir.unwrap_or_else(default_data_dir),
+        data_dir,
+        ready_file,
+        overrides,
+        runtime: std::sync::RwLock::new(runtime),
     })
 }
 
@@ -4584,12 +6083,28 @@ fn build_event_notification(event: DaemonEvent) -> Option<String> {
     let payload = match event {
         DaemonEvent::AppServer(payload) => json!({
             "method": "app-server-event",
-            "params": payload,
+            "params": normalize_app_server_event(payload),
         }),
         DaemonEvent::TerminalOutput(payload) => json!({
             "method": "terminal-output",
             "params": payload,
         }),
+        DaemonEvent::TerminalExited(payload) => json!({
+            "method": "terminal-exit",
+            "params": payload,
+        }),
+        DaemonEvent::QuickCommandOutput(payload) => json!({
+            "method": "quick-command-output",
+            "params": payload,
+        }),
+        DaemonEvent::BackendError(payload) => json!({
+            "method": "backend-error",
+            "params": payload,
+        }),
+        DaemonEvent::Shutdown => json!({
+            "method": "daemon/shutdown",
+            "params": {},
+        }),
     };
     serde_json::to_string(&payload).ok()
 }
@@ -4616,13 +6131,6 @@ fn parse_string(value: &Value, key: &str) -> Result<String, String> {
     }
 }
 
-fn resolve_api_key(value: &str, env_key: &str) -> Option<String> {
-    if !value.trim().is_empty() {
-        return Some(value.to_string());
-    }
-    std::env::var(env_key).ok().filter(|v| !v.trim().is_empty())
-}
-
 fn parse_optional_string(value: &Value, key: &str) -> Option<String> {
     match value {
         Value::Object(map) => map
@@ -4633,6 +6141,26 @@ fn parse_optional_string(value: &Value, key: &str) -> Option<String> {
     }
 }
 
+/// Runs the memory connection test against `settings`, which need not be saved.
+async fn test_memory_settings(settings: &AppSettings) -> MemoryConnectionReport {
+    test_connection(
+        &settings.supabase_url,
+        &settings.supabase_anon_key,
+        settings
+            .memory_embedding_enabled
+            .then_some(settings.minimax_api_key.as_str()),
+    )
+    .await
+}
+
+/// Whose unread state a thread request refers to. Clients pass a stable
+/// `viewerId` per device; without one they share the default viewer.
+fn parse_viewer(value: &Value) -> String {
+    parse_optional_string(value, "viewerId")
+        .filter(|viewer| !viewer.trim().is_empty())
+        .unwrap_or_else(|| thread_activity::DEFAULT_VIEWER.to_string())
+}
+
 fn parse_optional_u32(value: &Value, key: &str) -> Option<u32> {
     match value {
         Value::Object(map) => map.get(key).and_then(|value| value.as_u64()).and_then(|v| {
@@ -4646,6 +6174,13 @@ fn parse_optional_u32(value: &Value, key: &str) -> Option<u32> {
     }
 }
 
+fn parse_optional_u64(value: &Value, key: &str) -> Option<u64> {
+    match value {
+        Value::Object(map) => map.get(key).and_then(|value| value.as_u64()),
+        _ => None,
+    }
+}
+
 fn parse_optional_usize(value: &Value, key: &str) -> Option<usize> {
     match value {
         Value::Object(map) => map
@@ -4710,6 +6245,24 @@ fn parse_string_array(value: &Value, key: &str) -> Result<Vec<String>, String> {
     parse_optional_string_array(value, key).ok_or_else(|| format!("missing `{key}`"))
 }
 
+fn parse_flag(value: &Value, key: &str) -> bool {
+    value
+        .get(key)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+// Read-only tokens never reach a method that takes this flag: none of them
+// is in the list `read_only_allows` checks before dispatch.
+fn parse_override_protection(value: &Value) -> bool {
+    parse_flag(value, "overrideProtection")
+}
+
+/// Removal past the `unpushed_commits` check.
+fn parse_force(value: &Value) -> bool {
+    parse_flag(value, "force")
+}
+
 fn parse_optional_value(value: &Value, key: &str) -> Option<Value> {
     match value {
         Value::Object(map) => map.get(key).cloned(),
@@ -4717,14 +6270,24 @@ fn parse_optional_value(value: &Value, key: &str) -> Option<Value> {
     }
 }
 
+fn parse_optional_object(value: &Value, key: &str) -> Result<Option<Map<String, Value>>, String> {
+    match parse_optional_value(value, key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Object(map)) => Ok(Some(map)),
+        Some(_) => Err(format!("{key} must be an object")),
+    }
+}
+
 async fn handle_rpc_request(
     state: &DaemonState,
+    connection_id: &str,
     method: &str,
     params: Value,
     client_version: String,
 ) -> Result<Value, String> {
     match method {
         "ping" => Ok(json!({ "ok": true })),
+        "health" => Ok(state.health().await),
         "list_workspaces" => {
             let workspaces = state.list_workspaces().await;
             serde_json::to_value(workspaces).map_err(|err| err.to_string())
@@ -4740,6 +6303,21 @@ async fn handle_rpc_request(
             let workspace = state.add_workspace(path, codex_bin, client_version).await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
+        "scan_and_import_workspaces" => {
+            let root_path = parse_string(&params, "rootPath")?;
+            let max_depth = params
+                .get("maxDepth")
+                .and_then(|value| value.as_u64())
+                .map(|value| value as usize);
+            let dry_run = params
+                .get("dryRun")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let report = state
+                .scan_and_import_workspaces(root_path, max_depth, dry_run)
+                .await?;
+            serde_json::to_value(report).map_err(|err| err.to_string())
+        }
         "add_clone" => {
             let source_workspace_id = parse_string(&params, "sourceWorkspaceId")?;
             let copies_folder = parse_string(&params, "copiesFolder")?;
@@ -4757,8 +6335,12 @@ async fn handle_rpc_request(
         "add_worktree" => {
             let parent_id = parse_string(&params, "parentId")?;
             let branch = parse_string(&params, "branch")?;
+            let keep_on_spawn_failure = params
+                .get("keepOnSpawnFailure")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
             let workspace = state
-                .add_worktree(parent_id, branch, client_version)
+                .add_worktree(parent_id, branch, keep_on_spawn_failure, client_version)
                 .await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
@@ -4769,14 +6351,40 @@ async fn handle_rpc_request(
         }
         "remove_workspace" => {
             let id = parse_string(&params, "id")?;
-            state.remove_workspace(id).await?;
+            let override_protection = parse_override_protection(&params);
+            let force = parse_force(&params);
+            state
+                .remove_workspace(id, override_protection, force)
+                .await?;
             Ok(json!({ "ok": true }))
         }
         "remove_worktree" => {
             let id = parse_string(&params, "id")?;
-            state.remove_worktree(id).await?;
+            let override_protection = parse_override_protection(&params);
+            let force = parse_force(&params);
+            state
+                .remove_worktree(id, override_protection, force)
+                .await?;
             Ok(json!({ "ok": true }))
         }
+        "worktree_report" => {
+            let parent_id = parse_string(&params, "parentWorkspaceId")?;
+            let remove_safe = params
+                .get("removeSafe")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let report = state.worktree_report(parent_id, remove_safe).await?;
+            serde_json::to_value(report).map_err(|err| err.to_string())
+        }
+        "compare_worktrees" => {
+            let workspace_id_a = parse_string(&params, "workspaceIdA")?;
+            let workspace_id_b = parse_string(&params, "workspaceIdB")?;
+            let mode = parse_string(&params, "mode")?;
+            let comparison = state
+                .compare_worktrees(workspace_id_a, workspace_id_b, mode)
+                .await?;
+            serde_json::to_value(comparison).map_err(|err| err.to_string())
+        }
         "rename_worktree" => {
             let id = parse_string(&params, "id")?;
             let branch = parse_string(&params, "branch")?;
@@ -4794,8 +6402,14 @@ async fn handle_rpc_request(
         }
         "apply_worktree_changes" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.apply_worktree_changes(workspace_id).await?;
-            Ok(json!({ "ok": true }))
+            let dry_run = params
+                .get("dryRun")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let only_paths = parse_optional_string_array(&params, "onlyPaths");
+            state
+                .apply_worktree_changes(workspace_id, dry_run, only_paths)
+                .await
         }
         "open_workspace_in" => {
             Err("open_workspace_in is not supported in daemon mode.".to_string())
@@ -4811,23 +6425,116 @@ async fn handle_rpc_request(
             let workspace = state.update_workspace_settings(id, settings).await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
+        "update_workspace_settings_partial" => {
+            let id = parse_string(&params, "id")?;
+            let patch = parse_optional_value(&params, "patch").ok_or("missing `patch`")?;
+            let workspace = state.update_workspace_settings_partial(id, patch).await?;
+            serde_json::to_value(workspace).map_err(|err| err.to_string())
+        }
         "update_workspace_codex_bin" => {
             let id = parse_string(&params, "id")?;
             let codex_bin = parse_optional_string(&params, "codex_bin");
             let workspace = state.update_workspace_codex_bin(id, codex_bin).await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
+        "get_effective_codex_args" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let effective = state.get_effective_codex_args(workspace_id).await?;
+            serde_json::to_value(effective).map_err(|err| err.to_string())
+        }
+        "set_workspace_codex_args" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let args = parse_string_array(&params, "args")?;
+            let confirm_dangerous = params
+                .get("confirmDangerous")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let effective = state
+                .set_workspace_codex_args(workspace_id, args, confirm_dangerous)
+                .await?;
+            serde_json::to_value(effective).map_err(|err| err.to_string())
+        }
         "list_workspace_files" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let files = state.list_workspace_files(workspace_id).await?;
             serde_json::to_value(files).map_err(|err| err.to_string())
         }
+        "scan_workspace_todos" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let markers = parse_optional_string_array(&params, "markers");
+            let limit = parse_optional_usize(&params, "limit");
+            let with_authors = params
+                .get("withAuthors")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let todos = state
+                .scan_workspace_todos(workspace_id, markers, limit, with_authors)
+                .await?;
+            serde_json::to_value(todos).map_err(|err| err.to_string())
+        }
+        "search_workspace_files" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let query = parse_string(&params, "query")?;
+            let options: WorkspaceSearchOptions =
+                match parse_optional_value(&params, "options").filter(|v| !v.is_null()) {
+                    Some(value) => serde_json::from_value(value)
+                        .map_err(|err| format!("Invalid options: {err}"))?,
+                    None => WorkspaceSearchOptions::default(),
+                };
+            let result = state
+                .search_workspace_files(workspace_id, query, options)
+                .await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
         "read_workspace_file" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let path = parse_string(&params, "path")?;
-            let response = state.read_workspace_file(workspace_id, path).await?;
+            let offset = parse_optional_u64(&params, "offset").unwrap_or(0);
+            let length = parse_optional_u64(&params, "length");
+            let response = state
+                .read_workspace_file(workspace_id, path, offset, length)
+                .await?;
             serde_json::to_value(response).map_err(|err| err.to_string())
         }
+        "tail_workspace_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let from_offset = parse_optional_u64(&params, "fromOffset").unwrap_or(0);
+            let max_bytes = parse_optional_u64(&params, "maxBytes");
+            let chunk = state
+                .tail_workspace_file(workspace_id, path, from_offset, max_bytes)
+                .await?;
+            serde_json::to_value(chunk).map_err(|err| err.to_string())
+        }
+        "follow_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let from_offset = parse_optional_u64(&params, "fromOffset");
+            let offset = state.follow_file(workspace_id, path, from_offset).await?;
+            Ok(json!({ "ok": true, "offset": offset }))
+        }
+        "unfollow_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let removed = state.file_followers.unfollow(&workspace_id, &path);
+            Ok(json!({ "ok": true, "removed": removed }))
+        }
+        "list_quick_commands" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let commands = state.list_quick_commands(workspace_id).await?;
+            serde_json::to_value(commands).map_err(|err| err.to_string())
+        }
+        "run_quick_command" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let command_id = parse_string(&params, "commandId")?;
+            let execution = state.run_quick_command(workspace_id, command_id).await?;
+            serde_json::to_value(execution).map_err(|err| err.to_string())
+        }
+        "cancel_quick_command" => {
+            let execution_id = parse_string(&params, "executionId")?;
+            state.quick_commands.cancel(&execution_id)?;
+            Ok(json!({ "ok": true }))
+        }
         "read_global_agents_md" => {
             let response = state.read_global_agents_md().await?;
             serde_json::to_value(response).map_err(|err| err.to_string())
@@ -4859,15 +6566,75 @@ async fn handle_rpc_request(
             }
             serde_json::to_value(settings).map_err(|err| err.to_string())
         }
+        "notification_rules_test" => {
+            let rule_id = parse_string(&params, "ruleId")?;
+            let settings = state.app_settings.lock().await;
+            let fired = notification_rules::preview_rule(&settings.notification_rules, &rule_id)?;
+            serde_json::to_value(fired).map_err(|err| err.to_string())
+        }
         "update_app_settings" => {
+            let validate = params
+                .get("validateMemoryOnSave")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
             let settings_value = match params {
                 Value::Object(map) => map.get("settings").cloned().unwrap_or(Value::Null),
                 _ => Value::Null,
             };
-            let settings: AppSettings =
-                serde_json::from_value(settings_value).map_err(|err| err.to_string())?;
-            let updated = state.update_app_settings(settings).await?;
-            serde_json::to_value(updated).map_err(|err| err.to_string())
+            let settings: AppSettings =
+                serde_json::from_value(settings_value).map_err(|err| err.to_string())?;
+            let updated = state
+                .update_app_settings(settings, method, Some(client_label(state, connection_id)))
+                .await?;
+            let memory_connection = if validate {
+                Some(test_memory_settings(&updated).await)
+            } else {
+                None
+            };
+            serde_json::to_value(AppSettingsUpdate {
+                settings: updated,
+                memory_connection,
+            })
+            .map_err(|err| err.to_string())
+        }
+        "update_app_settings_partial" => {
+            let validate = params
+                .get("validateMemoryOnSave")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let patch = params.get("patch").cloned().unwrap_or(Value::Null);
+            let current = state.app_settings.lock().await.clone();
+            let settings = settings_audit::merge_settings_patch(&current, &patch)?;
+            let updated = state
+                .update_app_settings(settings, method, Some(client_label(state, connection_id)))
+                .await?;
+            let memory_connection = if validate {
+                Some(test_memory_settings(&updated).await)
+            } else {
+                None
+            };
+            serde_json::to_value(AppSettingsUpdate {
+                settings: updated,
+                memory_connection,
+            })
+            .map_err(|err| err.to_string())
+        }
+        "settings_audit" => {
+            let limit = params
+                .get("limit")
+                .and_then(|v| v.as_u64())
+                .map(|limit| limit as usize)
+                .unwrap_or(settings_audit::DEFAULT_AUDIT_LIMIT);
+            let entries = settings_audit::read_settings_audit(
+                &settings_audit::settings_audit_path(&state.data_dir),
+                limit,
+            );
+            serde_json::to_value(entries).map_err(|err| err.to_string())
+        }
+        "data_usage" => serde_json::to_value(data_usage::collect_data_usage(&state.data_dir))
+            .map_err(|err| err.to_string()),
+        "resource_usage" => {
+            serde_json::to_value(state.resource_usage().await).map_err(|err| err.to_string())
         }
         "domains_list" => {
             let domains = state.domains_list().await?;
@@ -4887,8 +6654,18 @@ async fn handle_rpc_request(
         }
         "domains_delete" => {
             let domain_id = parse_string(&params, "domainId")?;
-            state.domains_delete(domain_id).await?;
-            Ok(json!({ "ok": true }))
+            let reassign_to = parse_optional_string(&params, "reassignTo");
+            let force = params
+                .get("force")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let result = state.domains_delete(domain_id, reassign_to, force).await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
+        "domain_usage" => {
+            let domain_id = parse_string(&params, "domainId")?;
+            let usage = state.domain_usage(domain_id).await;
+            serde_json::to_value(usage).map_err(|err| err.to_string())
         }
         "memory_status" => {
             let memory = state.memory.read().await;
@@ -4904,6 +6681,16 @@ async fn handle_rpc_request(
                 })),
             }
         }
+        "memory_test_connection" => {
+            let settings = match parse_optional_value(&params, "settings").filter(|v| !v.is_null())
+            {
+                Some(value) => serde_json::from_value(value)
+                    .map_err(|err| format!("Invalid settings: {err}"))?,
+                None => state.app_settings.lock().await.clone(),
+            };
+            serde_json::to_value(test_memory_settings(&settings).await)
+                .map_err(|err| err.to_string())
+        }
         "memory_search" => {
             let query = params
                 .get("query")
@@ -5056,6 +6843,26 @@ async fn handle_rpc_request(
             let result = state.codex_doctor(codex_bin).await?;
             Ok(result)
         }
+        "workspace_doctor" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.workspace_doctor(workspace_id).await
+        }
+        "onboarding_status" => {
+            let status = state.onboarding_status().await;
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
+        "onboarding_bootstrap" => {
+            let path = parse_string(&params, "path")?;
+            let options = match parse_optional_value(&params, "options").filter(|v| !v.is_null()) {
+                Some(value) => serde_json::from_value(value)
+                    .map_err(|err| format!("Invalid options: {err}"))?,
+                None => BootstrapOptions::default(),
+            };
+            let report = state
+                .onboarding_bootstrap(path, options, client_version)
+                .await;
+            serde_json::to_value(report).map_err(|err| err.to_string())
+        }
         "get_life_workspace_prompt" => {
             let prompt = state.get_life_workspace_prompt().await?;
             serde_json::to_value(prompt).map_err(|err| err.to_string())
@@ -5063,17 +6870,26 @@ async fn handle_rpc_request(
         "get_delivery_dashboard" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let range = parse_string(&params, "range")?;
-            state.get_delivery_dashboard(workspace_id, range).await
+            let timezone = parse_optional_string(&params, "timezone");
+            state
+                .get_delivery_dashboard(workspace_id, range, timezone)
+                .await
         }
         "get_nutrition_dashboard" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let range = parse_string(&params, "range")?;
-            state.get_nutrition_dashboard(workspace_id, range).await
+            let timezone = parse_optional_string(&params, "timezone");
+            state
+                .get_nutrition_dashboard(workspace_id, range, timezone)
+                .await
         }
         "get_exercise_dashboard" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let range = parse_string(&params, "range")?;
-            state.get_exercise_dashboard(workspace_id, range).await
+            let timezone = parse_optional_string(&params, "timezone");
+            state
+                .get_exercise_dashboard(workspace_id, range, timezone)
+                .await
         }
         "get_media_dashboard" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
@@ -5083,29 +6899,139 @@ async fn handle_rpc_request(
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.get_youtube_dashboard(workspace_id).await
         }
+        "list_domain_entities" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let domain_id = parse_string(&params, "domainId")?;
+            let kind = parse_optional_string(&params, "kind");
+            let offset = parse_optional_usize(&params, "offset").unwrap_or(0);
+            let limit = parse_optional_usize(&params, "limit");
+            state
+                .list_domain_entities(workspace_id, domain_id, kind, offset, limit)
+                .await
+        }
+        "read_domain_entity" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            state.read_domain_entity(workspace_id, path).await
+        }
         "enrich_media_covers" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let force = params
                 .get("force")
                 .and_then(|value| value.as_bool())
                 .unwrap_or(false);
-            state.enrich_media_covers(workspace_id, force).await
+            let dry_run = params
+                .get("dryRun")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let detach = params
+                .get("detach")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            state
+                .enrich_media_covers(workspace_id, force, dry_run, detach)
+                .await
         }
+        "media_providers_status" => state.media_providers_status().await,
         "get_finance_dashboard" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let range = parse_string(&params, "range")?;
-            state.get_finance_dashboard(workspace_id, range).await
+            let timezone = parse_optional_string(&params, "timezone");
+            state
+                .get_finance_dashboard(workspace_id, range, timezone)
+                .await
+        }
+        "export_life_report" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let format = parse_optional_string(&params, "format").unwrap_or_default();
+            let options = life::LifeReportOptions {
+                domains: parse_optional_string_array(&params, "domains").unwrap_or_default(),
+                range: parse_string(&params, "range")?,
+                format: life_report::ReportFormat::parse(&format)?,
+                save: params
+                    .get("save")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                overwrite: params
+                    .get("overwrite")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+            };
+            let timezone = parse_optional_string(&params, "timezone");
+            state
+                .export_life_report(workspace_id, options, timezone)
+                .await
+        }
+        "mark_bill_paid" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let bill_path = parse_string(&params, "billPath")?;
+            let paid_date = parse_optional_string(&params, "paidDate");
+            state
+                .mark_bill_paid(workspace_id, bill_path, paid_date)
+                .await
+        }
+        "stream_append" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let date = parse_optional_string(&params, "date");
+            let time = parse_string(&params, "time")?;
+            let text = parse_string(&params, "text")?;
+            let timezone = parse_optional_string(&params, "timezone");
+            state
+                .stream_append(workspace_id, date, time, text, timezone)
+                .await
+        }
+        "update_media_item" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let update = life::MediaItemUpdate {
+                status: parse_optional_string(&params, "status"),
+                rating: params.get("rating").and_then(|value| value.as_f64()),
+                completed_at: parse_optional_string(&params, "completedAt"),
+            };
+            state.update_media_item(workspace_id, path, update).await
         }
         "get_commit_message_prompt" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            let prompt = state.get_commit_message_prompt(workspace_id).await?;
+            let disable_redaction = redaction::wants_disable(&params);
+            let prompt = state
+                .get_commit_message_prompt(workspace_id, disable_redaction)
+                .await?;
             serde_json::to_value(prompt).map_err(|err| err.to_string())
         }
         "generate_commit_message" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            let message = state.generate_commit_message(workspace_id).await?;
+            let disable_redaction = redaction::wants_disable(&params);
+            let message = state
+                .generate_commit_message(workspace_id, disable_redaction)
+                .await?;
             serde_json::to_value(message).map_err(|err| err.to_string())
         }
+        "commit_turn_changes" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let turn_id = parse_string(&params, "turnId")?;
+            let message = parse_optional_string(&params, "message");
+            let allow_dirty = params
+                .get("allowDirty")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let result = state
+                .commit_turn_changes(workspace_id, thread_id, turn_id, message, allow_dirty)
+                .await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
+        "summarize_thread" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let start_new = params
+                .get("startNew")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let summary = state
+                .summarize_thread(workspace_id, thread_id, start_new)
+                .await?;
+            serde_json::to_value(summary).map_err(|err| err.to_string())
+        }
         "start_thread" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.start_thread(workspace_id).await
@@ -5126,6 +7052,54 @@ async fn handle_rpc_request(
             let thread_id = parse_string(&params, "threadId")?;
             state.archive_thread(workspace_id, thread_id).await
         }
+        "archive_threads" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_ids = parse_string_array(&params, "threadIds")?;
+            let results = state.archive_threads(workspace_id, thread_ids).await?;
+            serde_json::to_value(results).map_err(|err| err.to_string())
+        }
+        "threads_overview" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let viewer = parse_viewer(&params);
+            let overview = state.threads_overview(workspace_id, viewer).await?;
+            serde_json::to_value(overview).map_err(|err| err.to_string())
+        }
+        "mark_thread_viewed" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let viewed_at = state.thread_views.mark_thread_viewed(
+                &parse_viewer(&params),
+                &workspace_id,
+                &thread_id,
+            )?;
+            Ok(json!({ "viewedAt": viewed_at }))
+        }
+        "mark_workspace_viewed" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let viewed_at = state
+                .thread_views
+                .mark_workspace_viewed(&parse_viewer(&params), &workspace_id)?;
+            Ok(json!({ "viewedAt": viewed_at }))
+        }
+        "auto_archive_preview" => {
+            let inactive_days = parse_optional_u32(&params, "inactiveDays");
+            let candidates = state.auto_archive_preview(inactive_days).await?;
+            serde_json::to_value(candidates).map_err(|err| err.to_string())
+        }
+        "set_pinned_threads" => {
+            let keys = parse_string_array(&params, "keys")?;
+            state.set_pinned_threads(keys).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "upload_attachment" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let filename = parse_string(&params, "filename")?;
+            let data_base64 = parse_string(&params, "dataBase64")?;
+            let attachment = state
+                .upload_attachment(workspace_id, filename, data_base64)
+                .await?;
+            serde_json::to_value(attachment).map_err(|err| err.to_string())
+        }
         "send_user_message" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
@@ -5135,6 +7109,17 @@ async fn handle_rpc_request(
             let access_mode = parse_optional_string(&params, "accessMode");
             let images = parse_optional_string_array(&params, "images");
             let collaboration_mode = parse_optional_value(&params, "collaborationMode");
+            let file_refs = parse_optional_string_array(&params, "fileRefs");
+            let best_effort = params
+                .get("bestEffort")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let disable_redaction = redaction::wants_disable(&params);
+            let disable_recall = params
+                .get("disableRecall")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let extras = parse_optional_object(&params, "extras")?;
             state
                 .send_user_message(
                     workspace_id,
@@ -5145,9 +7130,105 @@ async fn handle_rpc_request(
                     access_mode,
                     images,
                     collaboration_mode,
+                    file_refs,
+                    best_effort,
+                    disable_redaction,
+                    disable_recall,
+                    extras,
+                )
+                .await
+        }
+        "preview_turn_policy" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let access_mode = parse_optional_string(&params, "accessMode");
+            let policy = state
+                .turn_policy_for(&workspace_id, access_mode.as_deref())
+                .await?;
+            serde_json::to_value(policy).map_err(|err| err.to_string())
+        }
+        "estimate_message_size" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_optional_string(&params, "threadId");
+            let text = parse_string(&params, "text")?;
+            let model = parse_optional_string(&params, "model");
+            let images = parse_optional_string_array(&params, "images");
+            let collaboration_mode = parse_optional_value(&params, "collaborationMode");
+            let file_refs = parse_optional_string_array(&params, "fileRefs");
+            let best_effort = params
+                .get("bestEffort")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let disable_redaction = redaction::wants_disable(&params);
+            let disable_recall = params
+                .get("disableRecall")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let estimate = state
+                .estimate_message_size(
+                    workspace_id,
+                    thread_id,
+                    text,
+                    model,
+                    images,
+                    collaboration_mode,
+                    file_refs,
+                    best_effort,
+                    disable_redaction,
+                    disable_recall,
+                )
+                .await?;
+            serde_json::to_value(estimate).map_err(|err| err.to_string())
+        }
+        "compose_message" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let parts: Vec<MessagePart> =
+                serde_json::from_value(params.get("parts").cloned().ok_or("missing `parts`")?)
+                    .map_err(|err| format!("Invalid parts: {err}"))?;
+            let max_bytes = parse_optional_usize(&params, "maxBytes");
+            let send = params
+                .get("send")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let thread_id = parse_optional_string(&params, "threadId");
+            let model = parse_optional_string(&params, "model");
+            let effort = parse_optional_string(&params, "effort");
+            let access_mode = parse_optional_string(&params, "accessMode");
+            let collaboration_mode = parse_optional_value(&params, "collaborationMode");
+            let disable_redaction = redaction::wants_disable(&params);
+            let disable_recall = params
+                .get("disableRecall")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            state
+                .compose_message(
+                    workspace_id,
+                    parts,
+                    max_bytes,
+                    send,
+                    thread_id,
+                    model,
+                    effort,
+                    access_mode,
+                    collaboration_mode,
+                    disable_redaction,
+                    disable_recall,
                 )
                 .await
         }
+        "get_thread_overrides" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let overrides = state.get_thread_overrides(workspace_id, thread_id).await;
+            serde_json::to_value(overrides).map_err(|err| err.to_string())
+        }
+        "clear_thread_overrides" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let cleared = state
+                .clear_thread_overrides(workspace_id, thread_id)
+                .await?;
+            Ok(json!({ "cleared": cleared }))
+        }
         "turn_interrupt" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
@@ -5163,10 +7244,20 @@ async fn handle_rpc_request(
                 .cloned()
                 .ok_or("missing `target`")?;
             let delivery = parse_optional_string(&params, "delivery");
+            let acknowledge_large = params
+                .get("acknowledgeLarge")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
             state
-                .start_review(workspace_id, thread_id, target, delivery)
+                .start_review(workspace_id, thread_id, target, delivery, acknowledge_large)
                 .await
         }
+        "list_reviews" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let limit = parse_optional_usize(&params, "limit");
+            let reviews = state.list_reviews(workspace_id, limit).await?;
+            serde_json::to_value(reviews).map_err(|err| err.to_string())
+        }
         "model_list" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.model_list(workspace_id).await
@@ -5175,6 +7266,11 @@ async fn handle_rpc_request(
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.collaboration_mode_list(workspace_id).await
         }
+        "session_capabilities" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let capabilities = state.session_capabilities(workspace_id).await?;
+            serde_json::to_value(capabilities).map_err(|err| err.to_string())
+        }
         "account_rate_limits" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.account_rate_limits(workspace_id).await
@@ -5203,10 +7299,44 @@ async fn handle_rpc_request(
             let source_url = parse_string(&params, "sourceUrl")?;
             let target = parse_string(&params, "target")?;
             let workspace_id = parse_optional_string(&params, "workspaceId");
+            let detach = params
+                .get("detach")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
             state
-                .skills_install_from_git(source_url, target, workspace_id)
+                .skills_install_from_git(source_url, target, workspace_id, detach)
                 .await
         }
+        "clients_list" => serde_json::to_value(state.clients.list()).map_err(|err| err.to_string()),
+        "disconnect_client" => {
+            let connection_id = parse_string(&params, "connectionId")?;
+            if !state.clients.disconnect(&connection_id) {
+                return Err(format!("client not connected: {connection_id}"));
+            }
+            Ok(json!({ "ok": true }))
+        }
+        "workspace_activity" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let limit = params
+                .get("limit")
+                .and_then(|value| value.as_u64())
+                .unwrap_or(50) as usize;
+            serde_json::to_value(state.clients.activity(&workspace_id, limit))
+                .map_err(|err| err.to_string())
+        }
+        "operations_list" => {
+            serde_json::to_value(state.operations.list()).map_err(|err| err.to_string())
+        }
+        "operation_status" => {
+            let id = parse_string(&params, "id")?;
+            let snapshot = state.operations.status(&id).ok_or("operation not found")?;
+            serde_json::to_value(snapshot).map_err(|err| err.to_string())
+        }
+        "operation_cancel" => {
+            let id = parse_string(&params, "id")?;
+            let snapshot = state.operations.cancel(&id)?;
+            serde_json::to_value(snapshot).map_err(|err| err.to_string())
+        }
         "skills_uninstall" => {
             let name = parse_string(&params, "name")?;
             let target = parse_string(&params, "target")?;
@@ -5217,7 +7347,25 @@ async fn handle_rpc_request(
             let workspace_id = parse_string(&params, "workspaceId")?;
             let domain_id = parse_string(&params, "domainId")?;
             let range = parse_string(&params, "range")?;
-            let snapshot = state.domain_trends(workspace_id, domain_id, range).await?;
+            let compare = params
+                .get("compare")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let force_refresh = params
+                .get("forceRefresh")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let timezone = parse_optional_string(&params, "timezone");
+            let snapshot = state
+                .domain_trends(
+                    workspace_id,
+                    domain_id,
+                    range,
+                    compare,
+                    force_refresh,
+                    timezone,
+                )
+                .await?;
             serde_json::to_value(snapshot).map_err(|e| e.to_string())
         }
         "list_git_roots" => {
@@ -5228,23 +7376,49 @@ async fn handle_rpc_request(
         }
         "get_git_status" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.get_git_status(workspace_id).await
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let force_refresh = params
+                .get("forceRefresh")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            state
+                .get_git_status(workspace_id, git_root, force_refresh)
+                .await
         }
         "get_git_diffs" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            let diffs = state.get_git_diffs(workspace_id).await?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let intraline = params
+                .get("intraline")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let hunks = params
+                .get("hunks")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let diffs = state
+                .get_git_diffs(workspace_id, git_root, intraline, hunks)
+                .await?;
             serde_json::to_value(diffs).map_err(|err| err.to_string())
         }
         "get_git_log" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
             let limit = parse_optional_usize(&params, "limit");
-            let log = state.get_git_log(workspace_id, limit).await?;
+            let log = state.get_git_log(workspace_id, git_root, limit).await?;
             serde_json::to_value(log).map_err(|err| err.to_string())
         }
         "get_git_commit_diff" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
             let sha = parse_string(&params, "sha")?;
-            let diffs = state.get_git_commit_diff(workspace_id, sha).await?;
+            let intraline = params
+                .get("intraline")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let diffs = state
+                .get_git_commit_diff(workspace_id, git_root, sha, intraline)
+                .await?;
             serde_json::to_value(diffs).map_err(|err| err.to_string())
         }
         "get_git_remote" => {
@@ -5254,67 +7428,207 @@ async fn handle_rpc_request(
         }
         "stage_git_file" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
             let path = parse_string(&params, "path")?;
-            state.stage_git_file(workspace_id, path).await?;
+            state.stage_git_file(workspace_id, git_root, path).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "stage_git_hunk" | "unstage_git_hunk" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let path = parse_string(&params, "path")?;
+            let selector = HunkSelector {
+                index: parse_optional_usize(&params, "hunkIndex"),
+                header: parse_optional_string(&params, "header"),
+            };
+            state
+                .apply_git_hunk(
+                    workspace_id,
+                    git_root,
+                    path,
+                    selector,
+                    method == "unstage_git_hunk",
+                )
+                .await?;
             Ok(json!({ "ok": true }))
         }
         "stage_git_all" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.stage_git_all(workspace_id).await?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            state.stage_git_all(workspace_id, git_root).await?;
             Ok(json!({ "ok": true }))
         }
         "unstage_git_file" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
             let path = parse_string(&params, "path")?;
-            state.unstage_git_file(workspace_id, path).await?;
+            state.unstage_git_file(workspace_id, git_root, path).await?;
             Ok(json!({ "ok": true }))
         }
+        "list_revert_undos" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let entries = state.list_revert_undos(workspace_id).await;
+            serde_json::to_value(entries).map_err(|err| err.to_string())
+        }
+        "restore_revert_undo" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let undo_id = parse_string(&params, "undoId")?;
+            let restored = state
+                .restore_revert_undo(workspace_id, git_root, undo_id)
+                .await?;
+            serde_json::to_value(restored).map_err(|err| err.to_string())
+        }
         "revert_git_file" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
             let path = parse_string(&params, "path")?;
-            state.revert_git_file(workspace_id, path).await?;
+            state.revert_git_file(workspace_id, git_root, path).await?;
             Ok(json!({ "ok": true }))
         }
         "revert_git_all" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.revert_git_all(workspace_id).await?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let override_protection = parse_override_protection(&params);
+            state
+                .revert_git_all(workspace_id, git_root, override_protection)
+                .await?;
             Ok(json!({ "ok": true }))
         }
         "commit_git" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
             let message = parse_string(&params, "message")?;
-            state.commit_git(workspace_id, message).await?;
-            Ok(json!({ "ok": true }))
+            let options = CommitOptions {
+                amend: parse_flag(&params, "amend"),
+                signoff: parse_flag(&params, "signoff"),
+                no_verify: parse_flag(&params, "noVerify"),
+            };
+            let override_protection = parse_override_protection(&params);
+            let outcome = state
+                .commit_git(
+                    workspace_id,
+                    git_root,
+                    message,
+                    options,
+                    override_protection,
+                )
+                .await?;
+            Ok(json!({
+                "ok": true,
+                "amendedPushedCommit": outcome.amended_pushed_commit,
+            }))
+        }
+        "get_last_commit_message" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let message = state
+                .get_last_commit_message(workspace_id, git_root)
+                .await?;
+            Ok(json!({ "message": message }))
         }
         "pull_git" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.pull_git(workspace_id).await?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            state.pull_git(workspace_id, git_root).await?;
             Ok(json!({ "ok": true }))
         }
         "push_git" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.push_git(workspace_id).await?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            state.push_git(workspace_id, git_root).await?;
             Ok(json!({ "ok": true }))
         }
         "sync_git" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.sync_git(workspace_id).await?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            state.sync_git(workspace_id, git_root).await?;
             Ok(json!({ "ok": true }))
         }
         "list_git_branches" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.list_git_branches(workspace_id).await
+            let git_root = parse_optional_string(&params, "gitRoot");
+            state.list_git_branches(workspace_id, git_root).await
         }
         "checkout_git_branch" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
             let name = parse_string(&params, "name")?;
-            state.checkout_git_branch(workspace_id, name).await?;
+            state
+                .checkout_git_branch(workspace_id, git_root, name)
+                .await?;
             Ok(json!({ "ok": true }))
         }
         "create_git_branch" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let name = parse_string(&params, "name")?;
+            state
+                .create_git_branch(workspace_id, git_root, name)
+                .await?;
+            Ok(json!({ "ok": true }))
+        }
+        "list_git_tags" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let pattern = parse_optional_string(&params, "pattern");
+            let limit = parse_optional_usize(&params, "limit");
+            let tags = state.list_git_tags(workspace_id, pattern, limit).await?;
+            serde_json::to_value(tags).map_err(|err| err.to_string())
+        }
+        "create_git_tag" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            let message = parse_optional_string(&params, "message");
+            let target = parse_optional_string(&params, "target");
+            let push = params
+                .get("push")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let tag = state
+                .create_git_tag(workspace_id, name, message, target, push)
+                .await?;
+            serde_json::to_value(tag).map_err(|err| err.to_string())
+        }
+        "delete_git_tag" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let name = parse_string(&params, "name")?;
-            state.create_git_branch(workspace_id, name).await?;
+            let override_protection = parse_override_protection(&params);
+            state
+                .delete_git_tag(workspace_id, name, override_protection)
+                .await?;
+            Ok(json!({ "ok": true }))
+        }
+        "stash_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let message = parse_optional_string(&params, "message");
+            let include_untracked = params
+                .get("includeUntracked")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
+            let stash = state
+                .stash_git(workspace_id, git_root, message, include_untracked)
+                .await?;
+            serde_json::to_value(stash).map_err(|err| err.to_string())
+        }
+        "list_git_stashes" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let stashes = state.list_git_stashes(workspace_id, git_root).await?;
+            serde_json::to_value(stashes).map_err(|err| err.to_string())
+        }
+        "pop_git_stash" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let index = parse_optional_usize(&params, "index").ok_or("missing `index`")?;
+            state.pop_git_stash(workspace_id, git_root, index).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "drop_git_stash" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let git_root = parse_optional_string(&params, "gitRoot");
+            let index = parse_optional_usize(&params, "index").ok_or("missing `index`")?;
+            state.drop_git_stash(workspace_id, git_root, index).await?;
             Ok(json!({ "ok": true }))
         }
         "get_github_issues" => {
@@ -5327,6 +7641,11 @@ async fn handle_rpc_request(
             let prs = state.get_github_pull_requests(workspace_id).await?;
             serde_json::to_value(prs).map_err(|err| err.to_string())
         }
+        "get_github_check_rollups" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let rollups = state.get_github_check_rollups(workspace_id).await?;
+            serde_json::to_value(rollups).map_err(|err| err.to_string())
+        }
         "get_github_pull_request_diff" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let pr_number = params
@@ -5334,8 +7653,12 @@ async fn handle_rpc_request(
                 .and_then(|map| map.get("prNumber"))
                 .and_then(|value| value.as_u64())
                 .ok_or("missing `prNumber`")?;
+            let intraline = params
+                .get("intraline")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
             let diffs = state
-                .get_github_pull_request_diff(workspace_id, pr_number)
+                .get_github_pull_request_diff(workspace_id, pr_number, intraline)
                 .await?;
             serde_json::to_value(diffs).map_err(|err| err.to_string())
         }
@@ -5351,6 +7674,14 @@ async fn handle_rpc_request(
                 .await?;
             serde_json::to_value(comments).map_err(|err| err.to_string())
         }
+        "gh_auth_status" => {
+            let refresh = params
+                .get("refresh")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let status = github_cli::gh_auth_status(refresh).await;
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
         "prompts_list" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let prompts = state.prompts_list(workspace_id).await?;
@@ -5421,12 +7752,19 @@ async fn handle_rpc_request(
             let terminal_id = parse_string(&params, "terminalId")?;
             let cols = parse_optional_u32(&params, "cols").ok_or("missing `cols`")?;
             let rows = parse_optional_u32(&params, "rows").ok_or("missing `rows`")?;
+            let initial_command = parse_optional_string(&params, "initialCommand");
+            let login_shell = params
+                .get("loginShell")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false);
             let info = state
                 .terminal_open(
                     workspace_id,
                     terminal_id,
                     cols.min(u16::MAX as u32) as u16,
                     rows.min(u16::MAX as u32) as u16,
+                    initial_command,
+                    login_shell,
                 )
                 .await?;
             serde_json::to_value(info).map_err(|err| err.to_string())
@@ -5461,10 +7799,66 @@ async fn handle_rpc_request(
             state.terminal_close(workspace_id, terminal_id).await?;
             Ok(json!({ "ok": true }))
         }
+        "terminal_list" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminals =
+                terminal_core::list_terminals(&state.terminal_sessions, &workspace_id).await;
+            serde_json::to_value(terminals).map_err(|err| err.to_string())
+        }
+        "terminal_scrollback" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let scrollback = terminal_core::terminal_scrollback(
+                &state.terminal_sessions,
+                &workspace_id,
+                &terminal_id,
+            )
+            .await?;
+            serde_json::to_value(scrollback).map_err(|err| err.to_string())
+        }
+        "terminal_snapshot" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let snapshot = terminal_core::terminal_snapshot(
+                &state.terminal_sessions,
+                &workspace_id,
+                &terminal_id,
+            )
+            .await?;
+            serde_json::to_value(snapshot).map_err(|err| err.to_string())
+        }
+        "terminal_search" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let query = parse_string(&params, "query")?;
+            let options: TerminalSearchOptions =
+                match parse_optional_value(&params, "options").filter(|v| !v.is_null()) {
+                    Some(value) => serde_json::from_value(value)
+                        .map_err(|err| format!("Invalid options: {err}"))?,
+                    None => TerminalSearchOptions::default(),
+                };
+            let result = terminal_core::search_terminal(
+                &state.terminal_sessions,
+                &workspace_id,
+                &terminal_id,
+                &query,
+                &options,
+            )
+            .await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
+        "usage_overview" => {
+            let workspace_id = parse_optional_string(&params, "workspaceId");
+            let overview = state.usage_overview(workspace_id).await?;
+            serde_json::to_value(overview).map_err(|err| err.to_string())
+        }
         "local_usage_snapshot" => {
             let days = parse_optional_u32(&params, "days");
             let workspace_path = parse_optional_string(&params, "workspacePath");
-            let snapshot = state.local_usage_snapshot(days, workspace_path).await?;
+            let timezone = parse_optional_string(&params, "timezone");
+            let snapshot = state
+                .local_usage_snapshot(days, workspace_path, timezone)
+                .await?;
             serde_json::to_value(snapshot).map_err(|err| err.to_string())
         }
         "respond_to_server_request" => {
@@ -5565,8 +7959,203 @@ async fn maybe_trigger_auto_memory(
     });
 }
 
+fn github_prefetch_interval(settings: &GithubPrefetchSettings) -> Duration {
+    Duration::from_secs(u64::from(settings.interval_minutes.max(1)) * 60)
+}
+
+/// Keeps a session running for each `keepWarm` workspace while
+/// `warmSessions` is on, one spawn at a time. A workspace whose spawn fails
+/// backs off on its own schedule.
+async fn run_warm_pool(state: Arc<DaemonState>) {
+    let mut schedule = warm_pool::RespawnSchedule::default();
+    let mut ticker = tokio::time::interval(warm_pool::WARM_POOL_TICK);
+    loop {
+        ticker.tick().await;
+        let warm = {
+            let settings = state.app_settings.lock().await;
+            let workspaces = state.workspaces.lock().await;
+            warm_pool::warm_workspace_ids(&settings, workspaces.values())
+        };
+        schedule.retain(&warm);
+        for workspace_id in warm {
+            if !schedule.is_due(&workspace_id, Instant::now()) {
+                continue;
+            }
+            match state.keep_session_warm(&workspace_id).await {
+                Ok(()) => schedule.succeeded(&workspace_id),
+                Err(err) => {
+                    eprintln!("Warm session for workspace {workspace_id} failed to start: {err}");
+                    schedule.failed(&workspace_id, Instant::now());
+                }
+            }
+        }
+    }
+}
+
+/// Refreshes GitHub data for connected workspaces with `githubPrefetch`, at
+/// most `maxConcurrent` at a time. Nothing runs while gh is missing or logged
+/// out; a workspace drops out as soon as it disconnects.
+async fn run_github_prefetch(state: Arc<DaemonState>) {
+    let mut schedule = github_prefetch::PrefetchSchedule::default();
+    let mut ticker = tokio::time::interval(github_prefetch::PREFETCH_TICK);
+    loop {
+        ticker.tick().await;
+        let settings = state.app_settings.lock().await.github_prefetch.clone();
+        let interval = github_prefetch_interval(&settings);
+        let enabled: Vec<String> = state
+            .workspaces
+            .lock()
+            .await
+            .values()
+            .filter(|entry| entry.settings.github_prefetch)
+            .map(|entry| entry.id.clone())
+            .collect();
+        let connected: HashSet<String> = {
+            let sessions = state.sessions.lock().await;
+            enabled
+                .into_iter()
+                .filter(|id| connection_state(sessions.get(id)) == ConnectionState::Connected)
+                .collect()
+        };
+        schedule.retain(&connected);
+        state.github_cache.retain(&connected);
+
+        let now = Instant::now();
+        let due: Vec<String> = connected
+            .into_iter()
+            .filter(|id| schedule.is_due(id, now))
+            .collect();
+        if due.is_empty()
+            || github_cli::gh_auth_status(false).await.state
+                != github_cli::GhAuthState::Authenticated
+        {
+            continue;
+        }
+
+        let slots = Arc::new(tokio::sync::Semaphore::new(
+            settings.max_concurrent.max(1) as usize
+        ));
+        let mut fetches = task::JoinSet::new();
+        for workspace_id in due {
+            let state = Arc::clone(&state);
+            let slots = Arc::clone(&slots);
+            fetches.spawn(async move {
+                let _slot = slots.acquire_owned().await;
+                let result = state.prefetch_github(&workspace_id).await;
+                (workspace_id, result)
+            });
+        }
+        while let Some(Ok((workspace_id, result))) = fetches.join_next().await {
+            let now = Instant::now();
+            match result {
+                Ok(()) => schedule.succeeded(&workspace_id, now, interval),
+                Err(err) => {
+                    eprintln!("GitHub prefetch for workspace {workspace_id} failed: {err}");
+                    schedule.failed(&workspace_id, now, interval);
+                }
+            }
+        }
+    }
+}
+
+/// Triggers auto memory flushes from `thread/tokenUsage/updated` events.
+async fn watch_token_usage(state: Arc<DaemonState>, mut rx: broadcast::Receiver<DaemonEvent>) {
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let DaemonEvent::AppServer(app_event) = event else {
+            continue;
+        };
+        let method = app_event
+            .message
+            .get("method")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if method != "thread/tokenUsage/updated" {
+            continue;
+        }
+        let params = app_event
+            .message
+            .get("params")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+        let thread_id = params
+            .get("threadId")
+            .or_else(|| params.get("thread_id"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        if thread_id.is_empty() {
+            continue;
+        }
+        let token_usage = params
+            .get("tokenUsage")
+            .or_else(|| params.get("token_usage"))
+            .cloned()
+            .unwrap_or(Value::Null);
+        let total_tokens = token_usage
+            .pointer("/total/totalTokens")
+            .or_else(|| token_usage.pointer("/total/total_tokens"))
+            .or_else(|| token_usage.get("totalTokens"))
+            .or_else(|| token_usage.get("total_tokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32;
+        let model_context_window = token_usage
+            .get("modelContextWindow")
+            .or_else(|| token_usage.get("model_context_window"))
+            .or_else(|| params.get("modelContextWindow"))
+            .or_else(|| params.get("model_context_window"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32;
+        if total_tokens == 0 || model_context_window == 0 {
+            continue;
+        }
+        maybe_trigger_auto_memory(
+            Arc::clone(&state),
+            app_event.workspace_id.clone(),
+            thread_id,
+            total_tokens,
+            model_context_window,
+        )
+        .await;
+    }
+}
+
+/// Feeds turn events to the Life transcript archive.
+async fn watch_life_transcripts(state: Arc<DaemonState>, mut rx: broadcast::Receiver<DaemonEvent>) {
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let DaemonEvent::AppServer(app_event) = event else {
+            continue;
+        };
+        if !is_transcript_event(&app_event.message) {
+            continue;
+        }
+        let vault = state
+            .workspaces
+            .lock()
+            .await
+            .get(&app_event.workspace_id)
+            .and_then(transcript_vault);
+        state.life_transcripts.observe(
+            &app_event.workspace_id,
+            vault.as_deref(),
+            &app_event.message,
+        );
+    }
+}
+
 async fn handle_client(
     socket: TcpStream,
+    peer: SocketAddr,
     config: Arc<DaemonConfig>,
     state: Arc<DaemonState>,
     events: broadcast::Sender<DaemonEvent>,
@@ -5586,16 +8175,39 @@ async fn handle_client(
         }
     });
 
-    let mut authenticated = config.token.is_none();
+    let mut authenticated = !config.runtime().requires_auth();
+    // The token this connection authenticated with. Its scope is looked up in
+    // the running config on every request, so a reload that revokes or
+    // downgrades it takes effect straight away.
+    let mut auth_token: Option<String> = None;
+    // Chunked results are only sent once the client asks for them, so clients
+    // that predate `client_capabilities` keep getting single-line responses.
+    let mut chunked_responses = false;
+    let mut next_chunk_op: u64 = 0;
     let mut events_task: Option<tokio::task::JoinHandle<()>> = None;
+    let (connection_id, disconnect) = state.clients.connect(Some(peer));
 
     if authenticated {
         let rx = events.subscribe();
         let out_tx_events = out_tx.clone();
         events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
+        state.clients.update(&connection_id, |client| {
+            client.authenticated = true;
+            client.subscribed_to_events = true;
+        });
     }
 
-    while let Ok(Some(line)) = lines.next_line().await {
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => line,
+                _ => break,
+            },
+            _ = disconnect.notified() => {
+                eprintln!("{connection_id}: disconnected by disconnect_client");
+                break;
+            }
+        };
         let line = line.trim();
         if line.is_empty() {
             continue;
@@ -5615,6 +8227,16 @@ async fn handle_client(
         let params = message.get("params").cloned().unwrap_or(Value::Null);
 
         if !authenticated {
+            // `health` is the only method served without auth, so probes can
+            // check liveness without the token. Its payload is deliberately
+            // non-sensitive (see `DaemonState::health`); `--disable-unauth-health`
+            // turns this off.
+            if method == "health" && config.runtime().unauth_health {
+                if let Some(response) = build_result_response(id, state.health().await) {
+                    let _ = out_tx.send(response);
+                }
+                continue;
+            }
             if method != "auth" {
                 if let Some(response) = build_error_response(id, "unauthorized") {
                     let _ = out_tx.send(response);
@@ -5622,9 +8244,8 @@ async fn handle_client(
                 continue;
             }
 
-            let expected = config.token.clone().unwrap_or_default();
             let provided = parse_auth_token(&params).unwrap_or_default();
-            if expected != provided {
+            if config.runtime().scope_for(&provided).is_none() {
                 if let Some(response) = build_error_response(id, "invalid token") {
                     let _ = out_tx.send(response);
                 }
@@ -5632,6 +8253,15 @@ async fn handle_client(
             }
 
             authenticated = true;
+            auth_token = Some(provided);
+            let info = daemon_clients::ClientInfo::from_params(&params);
+            state.clients.update(&connection_id, |client| {
+                client.authenticated = true;
+                client.subscribed_to_events = true;
+                if let Some(info) = info {
+                    client.client = info;
+                }
+            });
             if let Some(response) = build_result_response(id, json!({ "ok": true })) {
                 let _ = out_tx.send(response);
             }
@@ -5643,9 +8273,134 @@ async fn handle_client(
             continue;
         }
 
+        let runtime = config.runtime();
+        let scope = match auth_token.as_deref() {
+            Some(token) => runtime.scope_for(token),
+            // Without auth every connection has full scope.
+            None => (!runtime.requires_auth()).then_some(TokenScope::Full),
+        };
+        let Some(scope) = scope else {
+            // The token was revoked by a reload: back to unauthenticated,
+            // without notifications, until the client sends a valid `auth`.
+            authenticated = false;
+            auth_token = None;
+            if let Some(task) = events_task.take() {
+                task.abort();
+            }
+            state.clients.update(&connection_id, |client| {
+                client.authenticated = false;
+                client.subscribed_to_events = false;
+            });
+            if let Some(response) = build_error_response(id, "invalid token") {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        };
+
+        if method == "client_capabilities" {
+            let requested =
+                parse_optional_string_array(&params, "capabilities").unwrap_or_default();
+            chunked_responses = requested
+                .iter()
+                .any(|capability| capability == rpc_chunks::CHUNKED_RESPONSES_CAPABILITY);
+            let accepted: Vec<&str> = if chunked_responses {
+                vec![rpc_chunks::CHUNKED_RESPONSES_CAPABILITY]
+            } else {
+                Vec::new()
+            };
+            let info = daemon_clients::ClientInfo::from_params(&params);
+            state.clients.update(&connection_id, |client| {
+                client.capabilities = accepted.iter().map(|value| value.to_string()).collect();
+                if let Some(info) = info {
+                    client.client = info;
+                }
+            });
+            let result = json!({
+                "capabilities": accepted,
+                "chunkThresholdBytes": runtime.chunk_threshold,
+                "connectionId": connection_id,
+            });
+            if let Some(response) = build_result_response(id, result) {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
+        if scope == TokenScope::ReadOnly && !daemon_clients::read_only_allows(&method, &params) {
+            if let Some(response) = build_error_response(id, READ_ONLY_FORBIDDEN) {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
+        // Handled here rather than in `handle_rpc_request`: the daemon config
+        // is owned by the connection loop, not by `DaemonState`.
+        if method == "daemon_reload_config" {
+            let response = match config.reload() {
+                Ok(report) => build_result_response(id, json!(report)),
+                Err(message) => build_error_response(
+                    id,
+                    &format!("Config not reloaded, the running config is unchanged: {message}"),
+                ),
+            };
+            if let Some(response) = response {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
+        // Redaction is the daemon operator's call: a client can only opt out
+        // when the daemon allows it (`--allow-disable-redaction`).
+        if !runtime.allow_disable_redaction && redaction::wants_disable(&params) {
+            let message = format!(
+                "{}: this daemon does not allow disableRedaction",
+                redaction::REDACTION_OPT_OUT_FORBIDDEN
+            );
+            if let Some(response) = build_error_response(id, &message) {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
         let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
-        let result = handle_rpc_request(&state, &method, params, client_version).await;
+        let activity_workspace = daemon_clients::is_mutating_method(&method)
+            .then(|| parse_optional_string(&params, "workspaceId"));
+        let mut result =
+            handle_rpc_request(&state, &connection_id, &method, params, client_version).await;
+        if scope == TokenScope::ReadOnly {
+            if let Ok(value) = result.as_mut() {
+                daemon_clients::mask_read_only_result(&method, value);
+            }
+        }
+        if let Some(workspace_id) = activity_workspace {
+            record_client_activity(
+                &state,
+                &connection_id,
+                &method,
+                workspace_id,
+                result.as_ref().err(),
+            )
+            .await;
+        }
         let response = match result {
+            Ok(result) if chunked_responses && rpc_chunks::is_chunked_method(&method) => {
+                match (id, serde_json::to_string(&result)) {
+                    (Some(id), Ok(serialized)) if serialized.len() > runtime.chunk_threshold => {
+                        next_chunk_op += 1;
+                        let op_id = format!("chunk-{next_chunk_op}");
+                        for line in rpc_chunks::chunked_response_lines(
+                            id,
+                            &op_id,
+                            &serialized,
+                            rpc_chunks::CHUNK_SIZE,
+                        ) {
+                            let _ = out_tx.send(line);
+                        }
+                        continue;
+                    }
+                    _ => build_result_response(id, result),
+                }
+            }
             Ok(result) => build_result_response(id, result),
             Err(message) => build_error_response(id, &message),
         };
@@ -5659,9 +8414,93 @@ async fn handle_client(
         task.abort();
     }
     write_task.abort();
+    state.clients.disconnected(&connection_id);
+}
+
+fn client_label(state: &DaemonState, connection_id: &str) -> String {
+    daemon_clients::client_label(connection_id, &state.clients.client_info(connection_id))
+}
+
+/// Logs a mutating request under the client that made it and, for workspace
+/// requests, adds it to that workspace's activity feed.
+async fn record_client_activity(
+    state: &DaemonState,
+    connection_id: &str,
+    method: &str,
+    workspace_id: Option<String>,
+    error: Option<&String>,
+) {
+    let client = state.clients.client_info(connection_id);
+    let label = daemon_clients::client_label(connection_id, &client);
+    let scope = workspace_id
+        .as_deref()
+        .map(|id| format!(" workspace={id}"))
+        .unwrap_or_default();
+    match error {
+        Some(err) => eprintln!("{label}: {method}{scope} failed: {err}"),
+        None => eprintln!("{label}: {method}{scope}"),
+    }
+    if let Some(workspace_id) = workspace_id {
+        let workspace = state
+            .workspaces
+            .lock()
+            .await
+            .get(&workspace_id)
+            .map(workspace_identity::WorkspaceIdentity::of);
+        state.clients.record_activity(
+            &workspace_id,
+            daemon_clients::ClientActivity {
+                at: chrono::Utc::now().timestamp_millis(),
+                method: method.to_string(),
+                connection_id: connection_id.to_string(),
+                client,
+                ok: error.is_none(),
+                workspace,
+                reason: None,
+            },
+        );
+    }
+}
+
+/// Adds a turn the daemon interrupted for its `maxTurnDurationMinutes` budget
+/// to the workspace's activity feed.
+async fn record_auto_interrupt_activity(
+    state: &DaemonState,
+    workspace_id: &str,
+    notice: &BudgetNotice,
+    ok: bool,
+) {
+    eprintln!(
+        "daemon: turn_interrupt workspace={workspace_id} thread={} turn={}: {}",
+        notice.thread_id,
+        notice.turn_id,
+        notice.reason()
+    );
+    let workspace = state
+        .workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .map(workspace_identity::WorkspaceIdentity::of);
+    state.clients.record_activity(
+        workspace_id,
+        daemon_clients::ClientActivity {
+            at: chrono::Utc::now().timestamp_millis(),
+            method: "turn_interrupt".to_string(),
+            connection_id: "daemon".to_string(),
+            client: daemon_clients::ClientInfo {
+                name: Some("turn budget".to_string()),
+                ..daemon_clients::ClientInfo::default()
+            },
+            ok,
+            workspace,
+            reason: Some(notice.reason()),
+        },
+    );
 }
 
 fn main() {
+    install_panic_hook();
     let config = match parse_args() {
         Ok(config) => config,
         Err(err) => {
@@ -5685,80 +8524,91 @@ fn main() {
 
         {
             let state = Arc::clone(&state);
-            let mut rx = events_tx.subscribe();
-            tokio::spawn(async move {
-                loop {
-                    let event = match rx.recv().await {
-                        Ok(event) => event,
-                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
-                        Err(broadcast::error::RecvError::Closed) => break,
-                    };
-                    let DaemonEvent::AppServer(app_event) = event else {
-                        continue;
-                    };
-                    let method = app_event
-                        .message
-                        .get("method")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("");
-                    if method != "thread/tokenUsage/updated" {
-                        continue;
-                    }
-                    let params = app_event
-                        .message
-                        .get("params")
-                        .and_then(|v| v.as_object())
-                        .cloned()
-                        .unwrap_or_default();
-                    let thread_id = params
-                        .get("threadId")
-                        .or_else(|| params.get("thread_id"))
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    if thread_id.is_empty() {
-                        continue;
-                    }
-                    let token_usage = params
-                        .get("tokenUsage")
-                        .or_else(|| params.get("token_usage"))
-                        .cloned()
-                        .unwrap_or(Value::Null);
-                    let total_tokens = token_usage
-                        .pointer("/total/totalTokens")
-                        .or_else(|| token_usage.pointer("/total/total_tokens"))
-                        .or_else(|| token_usage.get("totalTokens"))
-                        .or_else(|| token_usage.get("total_tokens"))
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(0) as u32;
-                    let model_context_window = token_usage
-                        .get("modelContextWindow")
-                        .or_else(|| token_usage.get("model_context_window"))
-                        .or_else(|| params.get("modelContextWindow"))
-                        .or_else(|| params.get("model_context_window"))
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(0) as u32;
-                    if total_tokens == 0 || model_context_window == 0 {
-                        continue;
-                    }
-                    maybe_trigger_auto_memory(
-                        Arc::clone(&state),
-                        app_event.workspace_id.clone(),
-                        thread_id,
-                        total_tokens,
-                        model_context_window,
-                    )
-                    .await;
-                }
+            set_restart_hook(move |workspace_id| {
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    state.restart_unhealthy_session(&workspace_id).await;
+                });
+            });
+        }
+        {
+            let state = Arc::clone(&state);
+            set_auto_interrupt_hook(move |workspace_id, notice, ok| {
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    record_auto_interrupt_activity(&state, &workspace_id, &notice, ok).await;
+                });
             });
         }
+        tokio::spawn(run_path_recheck(state.event_sink.clone()));
+        #[cfg(unix)]
+        tokio::spawn(reload_config_on_hangup(Arc::clone(&config)));
+
+        {
+            let state = Arc::clone(&state);
+            let events_tx = events_tx.clone();
+            tokio::spawn(supervise(
+                state.event_sink.clone(),
+                "event forwarding",
+                move || watch_token_usage(Arc::clone(&state), events_tx.subscribe()),
+            ));
+        }
+
+        {
+            let state = Arc::clone(&state);
+            let events_tx = events_tx.clone();
+            tokio::spawn(supervise(
+                state.event_sink.clone(),
+                "life transcripts",
+                move || watch_life_transcripts(Arc::clone(&state), events_tx.subscribe()),
+            ));
+        }
+
+        {
+            let state = Arc::clone(&state);
+            tokio::spawn(supervise(
+                state.event_sink.clone(),
+                "scheduler",
+                move || {
+                    let state = Arc::clone(&state);
+                    async move {
+                        // Give clients time to reconnect workspaces before the first pass.
+                        let start = tokio::time::Instant::now() + AUTO_ARCHIVE_INITIAL_DELAY;
+                        let mut interval = tokio::time::interval_at(start, AUTO_ARCHIVE_INTERVAL);
+                        loop {
+                            interval.tick().await;
+                            state.run_auto_archive().await;
+                        }
+                    }
+                },
+            ));
+        }
+
+        {
+            let state = Arc::clone(&state);
+            tokio::spawn(supervise(
+                state.event_sink.clone(),
+                "GitHub prefetch",
+                move || run_github_prefetch(Arc::clone(&state)),
+            ));
+        }
+
+        {
+            let state = Arc::clone(&state);
+            tokio::spawn(supervise(
+                state.event_sink.clone(),
+                "warm pool",
+                move || run_warm_pool(Arc::clone(&state)),
+            ));
+        }
 
         let listener = TcpListener::bind(config.listen)
             .await
             .unwrap_or_else(|err| panic!("failed to bind {}: {err}", config.listen));
+        let bound = listener.local_addr().unwrap_or(config.listen);
         eprintln!(
             "codex-monitor-daemon listening on {} (data dir: {})",
-            config.listen,
+            bound,
             state
                 .storage_path
                 .parent()
@@ -5766,18 +8616,111 @@ fn main() {
                 .display()
         );
 
-        loop {
-            match listener.accept().await {
-                Ok((socket, _addr)) => {
-                    let config = Arc::clone(&config);
-                    let state = Arc::clone(&state);
-                    let events = events_tx.clone();
-                    tokio::spawn(async move {
-                        handle_client(socket, config, state, events).await;
-                    });
+        if let Some(path) = config.ready_file.as_deref() {
+            if let Err(err) = write_ready_file(path, bound) {
+                eprintln!("failed to write ready file {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+
+        let (accepting, config, state, events_tx) = (&listener, &config, &state, &events_tx);
+        let accept_loop = supervise(state.event_sink.clone(), "listener", move || async move {
+            loop {
+                match accepting.accept().await {
+                    Ok((socket, addr)) => {
+                        let config = Arc::clone(config);
+                        let state = Arc::clone(state);
+                        let events = events_tx.clone();
+                        tokio::spawn(async move {
+                            handle_client(socket, addr, config, state, events).await;
+                        });
+                    }
+                    Err(_) => continue,
                 }
-                Err(_) => continue,
+            }
+        });
+
+        let signalled = tokio::select! {
+            _ = accept_loop => false,
+            _ = shutdown_signal() => true,
+        };
+        // No new connections from here on; open ones still get the notices.
+        drop(listener);
+        if signalled {
+            eprintln!("codex-monitor-daemon shutting down");
+            let shutdown = async {
+                state.shutdown(events_tx).await;
+                tokio::time::sleep(SHUTDOWN_NOTICE_GRACE).await;
+            };
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, shutdown)
+                .await
+                .is_err()
+            {
+                eprintln!(
+                    "codex-monitor-daemon: shutdown did not finish in {}s, exiting anyway",
+                    SHUTDOWN_TIMEOUT.as_secs()
+                );
             }
         }
+
+        if let Some(path) = config.ready_file.as_deref() {
+            let _ = std::fs::remove_file(path);
+        }
     });
+    // Blocking tasks (git, file reads) are not waited for.
+    runtime.shutdown_timeout(Duration::from_secs(1));
+}
+
+/// Written once the listener is accepting and state is loaded, so orchestrators
+/// can gate readiness on the file existing.
+fn write_ready_file(path: &Path, bound: SocketAddr) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let contents = json!({ "address": bound.to_string(), "pid": std::process::id() });
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, format!("{contents}\n")).map_err(|err| err.to_string())?;
+    std::fs::rename(&tmp_path, path).map_err(|err| err.to_string())
+}
+
+/// SIGHUP re-reads `daemon.json`, like `daemon_reload_config`.
+#[cfg(unix)]
+async fn reload_config_on_hangup(config: Arc<DaemonConfig>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+        return;
+    };
+    while hangup.recv().await.is_some() {
+        match config.reload() {
+            Ok(report) => eprintln!(
+                "reloaded {}: applied [{}], requires restart [{}]",
+                report.path,
+                report.applied.join(", "),
+                report.requires_restart.join(", ")
+            ),
+            Err(err) => eprintln!("config reload failed, keeping the running config: {err}"),
+        }
+    }
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
 }
@@ -1,10 +1,13 @@
-use codex_monitor_lib::life_core::enrich_media_covers;
+use codex_monitor_lib::life_core::{
+    enrich_media_covers, MediaCoverOptions, MediaCoverProgress, MediaProviderConfig,
+};
 use serde_json::Value;
 use std::path::PathBuf;
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
     let force_refresh = std::env::args().any(|arg| arg == "--force");
+    let dry_run = std::env::args().any(|arg| arg == "--dry-run");
     let obsidian_root = std::env::var("OBSIDIAN_ROOT")
         .ok()
         .filter(|value| !value.trim().is_empty())
@@ -47,18 +50,53 @@ async fn main() -> Result<(), String> {
                 .ok()
                 .filter(|value| !value.trim().is_empty())
         });
+    let enabled = |key: &str| {
+        settings_value
+            .get(key)
+            .and_then(|value| value.as_bool())
+            .unwrap_or(true)
+    };
+    let providers = MediaProviderConfig {
+        tmdb_api_key,
+        igdb_client_id,
+        igdb_client_secret,
+        exa_api_key,
+        tmdb_enabled: enabled("tmdb_enabled"),
+        igdb_enabled: enabled("igdb_enabled"),
+    };
 
     let summary = enrich_media_covers(
         &obsidian_root,
         Some(&obsidian_root),
-        tmdb_api_key.as_deref(),
-        igdb_client_id.as_deref(),
-        igdb_client_secret.as_deref(),
-        exa_api_key.as_deref(),
-        force_refresh,
+        &providers,
+        &MediaCoverOptions {
+            on_progress: &print_progress,
+            ..MediaCoverOptions::new(force_refresh, dry_run)
+        },
     )
     .await?;
 
+    if dry_run {
+        for query in &summary.queries {
+            println!(
+                "{} [{}] via {}",
+                query.title, query.media_type, query.provider
+            );
+        }
+        println!(
+            "Dry run: {} lookups, {} skipped, out of {}",
+            summary.queries.len(),
+            summary.skipped,
+            summary.total
+        );
+        return Ok(());
+    }
+    for failure in &summary.failures {
+        eprintln!(
+            "{} ({}): {}",
+            failure.title, failure.provider, failure.reason
+        );
+    }
     println!(
         "Media cover enrichment complete: total={}, found={}, skipped={}, failed={}",
         summary.total, summary.found, summary.skipped, summary.failed
@@ -66,3 +104,10 @@ async fn main() -> Result<(), String> {
 
     Ok(())
 }
+
+fn print_progress(progress: &MediaCoverProgress) {
+    println!(
+        "[{}/{}] {} ({}): {:?}",
+        progress.done, progress.total, progress.title, progress.provider, progress.outcome
+    );
+}
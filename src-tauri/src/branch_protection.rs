@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use git2::Repository;
+use globset::GlobBuilder;
+
+use crate::types::{AppSettings, WorkspaceEntry};
+
+/// Error prefix clients can match on to offer an explicit override.
+pub(crate) const BRANCH_PROTECTED_ERROR: &str = "branch_protected";
+
+/// Workspace patterns win when set, then the parent's (for worktrees), then
+/// the app-wide defaults.
+pub(crate) fn effective_protected_branches(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+    app: &AppSettings,
+) -> Vec<String> {
+    [Some(entry), parent_entry]
+        .into_iter()
+        .flatten()
+        .map(|workspace| &workspace.settings.protected_branches)
+        .find(|patterns| !patterns.is_empty())
+        .unwrap_or(&app.protected_branches)
+        .clone()
+}
+
+/// Returns the first pattern matching `branch`. `*` stays within one path
+/// segment (`release/*`), `**` crosses segments (`release/**`).
+pub(crate) fn find_protected_pattern(branch: &str, patterns: &[String]) -> Option<String> {
+    patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .find(
+            |pattern| match GlobBuilder::new(pattern).literal_separator(true).build() {
+                Ok(glob) => glob.compile_matcher().is_match(branch),
+                Err(_) => *pattern == branch,
+            },
+        )
+        .map(|pattern| pattern.to_string())
+}
+
+pub(crate) fn ensure_branch_not_protected(
+    branch: Option<&str>,
+    patterns: &[String],
+    override_protection: bool,
+    action: &str,
+) -> Result<(), String> {
+    if override_protection {
+        return Ok(());
+    }
+    let Some(branch) = branch else {
+        return Ok(());
+    };
+    match find_protected_pattern(branch, patterns) {
+        Some(pattern) => Err(format!(
+            "{BRANCH_PROTECTED_ERROR}: cannot {action} on branch `{branch}` (matches protected pattern `{pattern}`)"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Tags are checked against the same patterns as branches, so a `v*` entry
+/// keeps release tags from being deleted without an override.
+pub(crate) fn ensure_tag_not_protected(
+    tag: &str,
+    patterns: &[String],
+    override_protection: bool,
+    action: &str,
+) -> Result<(), String> {
+    if override_protection {
+        return Ok(());
+    }
+    match find_protected_pattern(tag, patterns) {
+        Some(pattern) => Err(format!(
+            "{BRANCH_PROTECTED_ERROR}: cannot {action} `{tag}` (matches protected pattern `{pattern}`)"
+        )),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn current_branch_name(repo_root: &Path) -> Option<String> {
+    let repo = Repository::open(repo_root).ok()?;
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    head.shorthand().map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ensure_branch_not_protected, ensure_tag_not_protected, find_protected_pattern,
+        BRANCH_PROTECTED_ERROR,
+    };
+
+    fn patterns(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn single_star_stays_within_segment() {
+        let list = patterns(&["release/*"]);
+        assert!(find_protected_pattern("release/1.2", &list).is_some());
+        assert!(find_protected_pattern("release/1.2/hotfix", &list).is_none());
+        assert!(find_protected_pattern("release", &list).is_none());
+    }
+
+    #[test]
+    fn double_star_crosses_segments() {
+        let list = patterns(&["release/**"]);
+        assert!(find_protected_pattern("release/1.2", &list).is_some());
+        assert!(find_protected_pattern("release/1.2/hotfix", &list).is_some());
+        assert!(find_protected_pattern("prerelease/1.2", &list).is_none());
+    }
+
+    #[test]
+    fn exact_names_do_not_match_prefixes() {
+        let list = patterns(&["main"]);
+        assert_eq!(
+            find_protected_pattern("main", &list).as_deref(),
+            Some("main")
+        );
+        assert!(find_protected_pattern("main-fix", &list).is_none());
+        assert!(find_protected_pattern("feature/main", &list).is_none());
+    }
+
+    #[test]
+    fn invalid_glob_falls_back_to_exact_match() {
+        let list = patterns(&["release/[", "  "]);
+        assert!(find_protected_pattern("release/[", &list).is_some());
+        assert!(find_protected_pattern("release/1", &list).is_none());
+    }
+
+    #[test]
+    fn error_names_matching_pattern_and_override_bypasses() {
+        let list = patterns(&["feature/*", "main"]);
+        let err = ensure_branch_not_protected(Some("main"), &list, false, "revert all changes")
+            .expect_err("main is protected");
+        assert!(err.starts_with(BRANCH_PROTECTED_ERROR));
+        assert!(err.contains("`main`"));
+        assert!(ensure_branch_not_protected(Some("main"), &list, true, "revert").is_ok());
+        assert!(ensure_branch_not_protected(None, &list, false, "revert").is_ok());
+    }
+
+    #[test]
+    fn tags_use_the_same_patterns() {
+        let list = patterns(&["main", "v*"]);
+        let err = ensure_tag_not_protected("v1.2.0", &list, false, "delete tag")
+            .expect_err("release tags are protected");
+        assert!(err.starts_with(BRANCH_PROTECTED_ERROR));
+        assert!(err.contains("`v*`"));
+        assert!(ensure_tag_not_protected("build-7", &list, false, "delete tag").is_ok());
+        assert!(ensure_tag_not_protected("v1.2.0", &list, true, "delete tag").is_ok());
+    }
+}
@@ -0,0 +1,176 @@
+//! The zone a client counts days in. Dashboards, trends and local usage take an
+//! optional `timezone` param (an IANA name such as `Australia/Brisbane`, or a
+//! UTC offset such as `+10:00` / `UTC-5`), then `AppSettings.timezone`, then a
+//! per-call fallback. Stream entry dates stay naive; only "today" and the range
+//! boundaries derived from it move with the zone.
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ClientTimezone {
+    Utc,
+    /// The zone of the machine running the backend.
+    Local,
+    Named(Tz),
+    Offset(FixedOffset),
+}
+
+impl ClientTimezone {
+    pub(crate) fn parse(value: &str) -> Result<Self, String> {
+        let trimmed = value.trim();
+        if let Ok(tz) = trimmed.parse::<Tz>() {
+            return Ok(Self::Named(tz));
+        }
+        parse_offset(trimmed)
+            .map(|offset| match offset.local_minus_utc() {
+                0 => Self::Utc,
+                _ => Self::Offset(offset),
+            })
+            .ok_or_else(|| format!("Unknown timezone: {trimmed}"))
+    }
+
+    /// The calendar date in this zone at `instant`.
+    pub(crate) fn date_at(&self, instant: DateTime<Utc>) -> NaiveDate {
+        match self {
+            Self::Utc => instant.date_naive(),
+            Self::Local => instant.with_timezone(&Local).date_naive(),
+            Self::Named(tz) => instant.with_timezone(tz).date_naive(),
+            Self::Offset(offset) => instant.with_timezone(offset).date_naive(),
+        }
+    }
+
+    pub(crate) fn today(&self) -> NaiveDate {
+        self.date_at(Utc::now())
+    }
+}
+
+/// `requested`, else `configured`, else `fallback`. Only a bad `requested`
+/// value is an error; settings are validated when saved, so an unparsable
+/// `configured` value falls through to `fallback`.
+pub(crate) fn resolve_client_timezone(
+    requested: Option<&str>,
+    configured: Option<&str>,
+    fallback: ClientTimezone,
+) -> Result<ClientTimezone, String> {
+    if let Some(requested) = requested.filter(|value| !value.trim().is_empty()) {
+        return ClientTimezone::parse(requested);
+    }
+    Ok(configured
+        .filter(|value| !value.trim().is_empty())
+        .and_then(|configured| ClientTimezone::parse(configured).ok())
+        .unwrap_or(fallback))
+}
+
+/// Trims `AppSettings.timezone` before it is saved, rejecting values that
+/// do not parse. Blank means unset.
+pub(crate) fn normalize_timezone_setting(value: Option<String>) -> Result<Option<String>, String> {
+    match value.map(|value| value.trim().to_string()) {
+        Some(value) if !value.is_empty() => {
+            ClientTimezone::parse(&value)?;
+            Ok(Some(value))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// `Z`, `UTC`, `GMT`, optionally followed by (or just) `±HH`, `±HHMM` or
+/// `±HH:MM`.
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let upper = value.to_ascii_uppercase();
+    let rest = upper
+        .strip_prefix("UTC")
+        .or_else(|| upper.strip_prefix("GMT"))
+        .unwrap_or(&upper);
+    if rest.is_empty() || rest == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, digits) = match rest.as_bytes()[0] {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if digits.len() > 2 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    if hours.is_empty() || hours.len() > 2 || minutes.is_empty() || minutes.len() > 2 {
+        return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_timezone_setting, resolve_client_timezone, ClientTimezone};
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[test]
+    fn late_evening_lands_on_the_client_day() {
+        // 23:30 on 14 March in Brisbane (UTC+10) is still 13:30 on the 14th
+        // in UTC, and 00:30 on the 15th is 14:30 on the 14th.
+        let before_midnight = Utc.with_ymd_and_hms(2026, 3, 14, 13, 30, 0).unwrap();
+        let after_midnight = Utc.with_ymd_and_hms(2026, 3, 14, 14, 30, 0).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+
+        let brisbane = ClientTimezone::parse("Australia/Brisbane").expect("iana");
+        assert_eq!(brisbane.date_at(before_midnight), day(14));
+        assert_eq!(brisbane.date_at(after_midnight), day(15));
+        assert_eq!(ClientTimezone::Utc.date_at(after_midnight), day(14));
+
+        let offset = ClientTimezone::parse("UTC+10").expect("offset");
+        assert_eq!(offset.date_at(after_midnight), day(15));
+        let west = ClientTimezone::parse("-05:30").expect("offset");
+        assert_eq!(
+            west.date_at(Utc.with_ymd_and_hms(2026, 3, 15, 5, 0, 0).unwrap()),
+            day(14)
+        );
+    }
+
+    #[test]
+    fn parses_names_and_offsets() {
+        assert_eq!(ClientTimezone::parse("utc"), Ok(ClientTimezone::Utc));
+        assert_eq!(ClientTimezone::parse("+00:00"), Ok(ClientTimezone::Utc));
+        assert!(matches!(
+            ClientTimezone::parse("+0930"),
+            Ok(ClientTimezone::Offset(offset)) if offset.local_minus_utc() == 9 * 3600 + 1800
+        ));
+        assert!(ClientTimezone::parse("Mars/Olympus").is_err());
+        assert!(ClientTimezone::parse("+25").is_err());
+    }
+
+    #[test]
+    fn request_beats_settings_beats_fallback() {
+        let brisbane = ClientTimezone::parse("Australia/Brisbane").unwrap();
+        assert_eq!(
+            resolve_client_timezone(
+                Some("Australia/Brisbane"),
+                Some("UTC-3"),
+                ClientTimezone::Utc
+            ),
+            Ok(brisbane)
+        );
+        assert_eq!(
+            resolve_client_timezone(Some(" "), Some("Australia/Brisbane"), ClientTimezone::Utc),
+            Ok(brisbane)
+        );
+        assert_eq!(
+            resolve_client_timezone(None, Some("nonsense"), ClientTimezone::Local),
+            Ok(ClientTimezone::Local)
+        );
+        assert!(resolve_client_timezone(Some("nonsense"), None, ClientTimezone::Utc).is_err());
+
+        assert_eq!(normalize_timezone_setting(Some("  ".to_string())), Ok(None));
+        assert_eq!(
+            normalize_timezone_setting(Some(" Europe/Paris ".to_string())),
+            Ok(Some("Europe/Paris".to_string()))
+        );
+        assert!(normalize_timezone_setting(Some("nonsense".to_string())).is_err());
+    }
+}
@@ -8,23 +8,49 @@ use chrono::{DateTime, Utc};
 use ignore::WalkBuilder;
 use tauri::{AppHandle, State};
 use tokio::process::Command;
-use tokio::sync::mpsc;
 use tokio::time::timeout;
 
+use crate::attachments::{self, AttachmentInfo};
 pub(crate) use crate::backend::app_server::WorkspaceSession;
 use crate::backend::app_server::{
     build_codex_command_with_bin, build_codex_path_env, check_codex_installation,
     spawn_workspace_session as spawn_workspace_session_inner,
 };
+use crate::backend::background_prompt::{
+    generate_commit_message_for_diff, thread_id_from_start_response,
+};
+use crate::backend::capabilities::SessionCapabilities;
+use crate::backend::path_health::ensure_path_available;
+use crate::backend::spawn_diagnostics::last_spawn_failure;
+use crate::backend::turn_budget::{turn_budget, TurnBudget};
 use crate::codex_home::resolve_codex_home;
 use crate::codex_home::resolve_workspace_codex_home;
-use crate::codex_params::{build_turn_start_params, build_user_input};
 use crate::event_sink::TauriEventSink;
+use crate::file_refs::attach_file_context_report;
+use crate::git_utils::resolve_git_root;
 use crate::life;
+use crate::memory_recall;
+use crate::message_assembly::{
+    assemble_message, turn_policy, AssembledMessage, MessageSources, TurnPolicy,
+};
+use crate::message_compose::{
+    compose_message as compose_message_inner, ComposeSources, MessagePart, PromptSource,
+    DEFAULT_COMPOSE_BUDGET_BYTES,
+};
+use crate::read_only::ensure_writable;
+use crate::redaction::{redact_diff_with, Redactor};
 use crate::remote_backend;
+use crate::review_gate;
 use crate::rules;
 use crate::state::AppState;
-use crate::types::WorkspaceEntry;
+use crate::storage::{pinned_threads_path, read_pinned_threads, write_pinned_threads};
+use crate::thread_activity::{self, ReviewRecord, ThreadsOverview, DEFAULT_VIEWER};
+use crate::thread_archive::{self, ArchiveThreadResult, AutoArchiveCandidate};
+use crate::thread_overrides::{self, ThreadOverrides};
+use crate::thread_summary;
+use crate::turn_changes::{self, TurnCommitResult};
+use crate::types::{CommitMessagePrompt, CommitMessageResult, ThreadSummary, WorkspaceEntry};
+use crate::warm_pool;
 
 pub(crate) async fn spawn_workspace_session(
     entry: WorkspaceEntry,
@@ -156,6 +182,32 @@ pub(crate) async fn codex_doctor(
     }))
 }
 
+#[tauri::command]
+pub(crate) async fn workspace_doctor(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_doctor",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    if !state.workspaces.lock().await.contains_key(&workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    let connected = state.sessions.lock().await.contains_key(&workspace_id);
+    Ok(json!({
+        "workspaceId": workspace_id,
+        "connected": connected,
+        "lastSpawnError": last_spawn_failure(&workspace_id),
+    }))
+}
+
 #[tauri::command]
 pub(crate) async fn start_thread(
     workspace_id: String,
@@ -200,9 +252,14 @@ pub(crate) async fn start_thread(
         }
         params.insert("systemPrompt".to_string(), json!(prompt));
     }
-    session
+    let started = std::time::Instant::now();
+    let result = session
         .send_request("thread/start", Value::Object(params))
-        .await
+        .await;
+    if result.is_ok() {
+        warm_pool::record_thread_start(&workspace_id, started.elapsed());
+    }
+    result
 }
 
 #[tauri::command]
@@ -285,7 +342,169 @@ pub(crate) async fn archive_thread(
     let params = json!({
         "threadId": thread_id
     });
-    session.send_request("thread/archive", params).await
+    let response = session.send_request("thread/archive", params).await?;
+    if response.get("error").is_none() {
+        state
+            .thread_views
+            .mark_archived(&workspace_id, std::slice::from_ref(&thread_id))?;
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+pub(crate) async fn archive_threads(
+    workspace_id: String,
+    thread_ids: Vec<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ArchiveThreadResult>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "archive_threads",
+            json!({ "workspaceId": workspace_id, "threadIds": thread_ids }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = {
+        let sessions = state.sessions.lock().await;
+        sessions
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not connected")?
+    };
+    let results = thread_archive::archive_threads(&session, &thread_ids).await;
+    let archived: Vec<String> = results
+        .iter()
+        .filter(|result| result.ok)
+        .map(|result| result.thread_id.clone())
+        .collect();
+    state.thread_views.mark_archived(&workspace_id, &archived)?;
+    Ok(results)
+}
+
+#[tauri::command]
+pub(crate) async fn threads_overview(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadsOverview, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "threads_overview",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let session = {
+        let sessions = state.sessions.lock().await;
+        sessions
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not connected")?
+    };
+    let threads = thread_archive::list_all_threads(&session).await?;
+    Ok(state
+        .thread_views
+        .overview(DEFAULT_VIEWER, &workspace_id, &session.entry.path, &threads))
+}
+
+#[tauri::command]
+pub(crate) async fn mark_thread_viewed(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "mark_thread_viewed",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await?;
+        return Ok(());
+    }
+    state
+        .thread_views
+        .mark_thread_viewed(DEFAULT_VIEWER, &workspace_id, &thread_id)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn mark_workspace_viewed(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "mark_workspace_viewed",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return Ok(());
+    }
+    state
+        .thread_views
+        .mark_workspace_viewed(DEFAULT_VIEWER, &workspace_id)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn auto_archive_preview(
+    inactive_days: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<AutoArchiveCandidate>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "auto_archive_preview",
+            json!({ "inactiveDays": inactive_days }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let inactive_days = match inactive_days {
+        Some(days) => days,
+        None => state.app_settings.lock().await.auto_archive.inactive_days,
+    };
+    let pinned = read_pinned_threads(&pinned_threads_path(&state.settings_path))?;
+    let sessions: Vec<(String, Arc<WorkspaceSession>)> = state
+        .sessions
+        .lock()
+        .await
+        .iter()
+        .map(|(id, session)| (id.clone(), Arc::clone(session)))
+        .collect();
+    Ok(thread_archive::auto_archive_candidates(&sessions, &pinned, inactive_days).await)
+}
+
+#[tauri::command]
+pub(crate) async fn set_pinned_threads(
+    keys: Vec<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(&*state, app, "set_pinned_threads", json!({ "keys": keys }))
+            .await?;
+        return Ok(());
+    }
+    write_pinned_threads(&pinned_threads_path(&state.settings_path), &keys)
 }
 
 #[derive(serde::Serialize, Clone)]
@@ -412,6 +631,41 @@ mod tests {
     }
 }
 
+#[tauri::command]
+pub(crate) async fn upload_attachment(
+    workspace_id: String,
+    filename: String,
+    data_base64: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<AttachmentInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "upload_attachment",
+            json!({
+                "workspaceId": workspace_id,
+                "filename": filename,
+                "dataBase64": data_base64,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    if !state.workspaces.lock().await.contains_key(&workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    let data_dir = state.data_dir();
+    attachments::purge_attachments(
+        &data_dir,
+        attachments::ATTACHMENT_RETENTION_DAYS,
+        std::time::SystemTime::now(),
+    );
+    attachments::save_attachment(&data_dir, &workspace_id, &filename, &data_base64)
+}
+
 #[tauri::command]
 pub(crate) async fn send_user_message(
     workspace_id: String,
@@ -422,6 +676,11 @@ pub(crate) async fn send_user_message(
     access_mode: Option<String>,
     images: Option<Vec<String>>,
     collaboration_mode: Option<Value>,
+    file_refs: Option<Vec<String>>,
+    best_effort: Option<bool>,
+    disable_redaction: Option<bool>,
+    disable_recall: Option<bool>,
+    extras: Option<Map<String, Value>>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
@@ -439,85 +698,384 @@ pub(crate) async fn send_user_message(
                 "accessMode": access_mode,
                 "images": images,
                 "collaborationMode": collaboration_mode,
+                "fileRefs": file_refs,
+                "bestEffort": best_effort,
+                "disableRedaction": disable_redaction,
+                "disableRecall": disable_recall,
+                "extras": extras,
             }),
         )
         .await;
     }
 
+    // Assembled before taking the sessions lock so a slow recall never
+    // blocks other workspaces.
+    let message = assemble_user_message(
+        &state,
+        &workspace_id,
+        &text,
+        file_refs.as_deref(),
+        best_effort.unwrap_or(false),
+        images,
+        disable_redaction.unwrap_or(false),
+        disable_recall.unwrap_or(false),
+    )
+    .await?;
+    let policy = turn_policy_for(&state, &workspace_id, access_mode.as_deref()).await?;
+    let budget = turn_budget_for(&state, &workspace_id, &policy).await?;
+    let extras = turn_extras_for(&state, &workspace_id, extras.as_ref()).await?;
     let sessions = state.sessions.lock().await;
     let session = sessions
         .get(&workspace_id)
         .ok_or("workspace not connected")?;
-    let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-    let sandbox_policy = match access_mode.as_str() {
-        "full-access" => json!({
-            "type": "dangerFullAccess"
-        }),
-        "read-only" => json!({
-            "type": "readOnly"
-        }),
-        _ => json!({
-            "type": "workspaceWrite",
-            "writableRoots": [session.entry.path],
-            "networkAccess": true
-        }),
-    };
+    let (model, effort) = thread_overrides::apply_thread_overrides(
+        &thread_overrides::thread_overrides_path(&state.data_dir()),
+        session,
+        &TauriEventSink::new(app.clone()),
+        &thread_id,
+        model,
+        effort,
+    )
+    .await;
+    let params = message.turn_start_params(
+        &thread_id,
+        &session.entry.path,
+        &policy,
+        model,
+        effort,
+        collaboration_mode,
+        extras,
+    )?;
+    session.turn_budgets.arm(&thread_id, budget);
+    let response = session.send_request("turn/start", params).await;
+    if !matches!(&response, Ok(value) if value.get("error").is_none()) {
+        session.turn_budgets.arm(&thread_id, None);
+    }
+    let response = attach_file_context_report(response?, message.file_context.as_ref());
+    Ok(memory_recall::attach_recall_report(
+        response,
+        message.recall_report.as_ref(),
+    ))
+}
 
-    let approval_policy = if access_mode == "full-access" {
-        "never"
-    } else {
-        "on-request"
-    };
+/// The approval and sandbox policy `send_user_message` starts a turn with
+/// for `access_mode`, after the workspace's `accessLock` and `readOnly`.
+async fn turn_policy_for(
+    state: &AppState,
+    workspace_id: &str,
+    access_mode: Option<&str>,
+) -> Result<TurnPolicy, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(workspace_id).ok_or("workspace not found")?;
+    Ok(turn_policy(
+        access_mode,
+        &entry.path,
+        entry.settings.effective_access_lock(),
+    ))
+}
+
+/// The `maxTurnDurationMinutes` budget of a turn started with `policy`.
+async fn turn_budget_for(
+    state: &AppState,
+    workspace_id: &str,
+    policy: &TurnPolicy,
+) -> Result<Option<TurnBudget>, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(workspace_id).ok_or("workspace not found")?;
+    Ok(turn_budget(&entry.settings, policy.access_mode))
+}
 
-    let input = build_user_input(&text, images.as_deref())?;
-    let (is_life_workspace, domain_instructions) = {
+#[tauri::command]
+pub(crate) async fn preview_turn_policy(
+    workspace_id: String,
+    access_mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "preview_turn_policy",
+            json!({ "workspaceId": workspace_id, "accessMode": access_mode }),
+        )
+        .await;
+    }
+    let policy = turn_policy_for(&state, &workspace_id, access_mode.as_deref()).await?;
+    serde_json::to_value(policy).map_err(|err| err.to_string())
+}
+
+/// The workspace's `turnExtras` with the call's `extras` laid over them.
+async fn turn_extras_for(
+    state: &AppState,
+    workspace_id: &str,
+    extras: Option<&Map<String, Value>>,
+) -> Result<Option<Map<String, Value>>, String> {
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(workspace_id).ok_or("workspace not found")?;
+    Ok(crate::codex_params::resolve_turn_extras(
+        entry.settings.turn_extras.as_ref(),
+        extras,
+    ))
+}
+
+/// Everything `send_user_message` does before `turn/start`: file refs,
+/// images, memory recall and domain instructions.
+#[allow(clippy::too_many_arguments)]
+async fn assemble_user_message(
+    state: &AppState,
+    workspace_id: &str,
+    text: &str,
+    file_refs: Option<&[String]>,
+    best_effort: bool,
+    images: Option<Vec<String>>,
+    disable_redaction: bool,
+    disable_recall: bool,
+) -> Result<AssembledMessage, String> {
+    let (redactor, auto_memory) = {
+        let settings = state.app_settings.lock().await;
+        (
+            Redactor::for_call(&settings.redaction, disable_redaction),
+            settings.auto_memory.clone(),
+        )
+    };
+    let recall = {
+        let memory = state.memory.read().await.clone();
+        memory_recall::recall_for_message(memory.as_ref(), &auto_memory, text, disable_recall).await
+    };
+    let (workspace_path, is_life_workspace, domain) = {
         let workspaces = state.workspaces.lock().await;
-        let workspace = workspaces.get(&workspace_id);
-        if let Some(workspace) = workspace {
-            let is_life_workspace = life::is_life_workspace(&workspace.settings);
-            if is_life_workspace {
-                (true, None)
+        let workspace = workspaces.get(workspace_id).ok_or("workspace not found")?;
+        let is_life_workspace = life::is_life_workspace(&workspace.settings);
+        let domain =
+            if is_life_workspace || !workspace.settings.apply_domain_instructions.unwrap_or(true) {
+                None
             } else {
-                let apply = workspace.settings.apply_domain_instructions.unwrap_or(true);
-                if apply {
-                    let domains = state.domains.lock().await;
-                    (
-                        false,
-                        workspace
-                            .settings
-                            .domain_id
-                            .as_ref()
-                            .and_then(|id| domains.iter().find(|domain| &domain.id == id))
-                            .map(|domain| domain.system_prompt.clone()),
-                    )
-                } else {
-                    (false, None)
-                }
-            }
-        } else {
-            (false, None)
-        }
+                let domains = state.domains.lock().await;
+                workspace
+                    .settings
+                    .domain_id
+                    .as_ref()
+                    .and_then(|id| domains.iter().find(|domain| &domain.id == id))
+                    .cloned()
+            };
+        (workspace.path.clone(), is_life_workspace, domain)
     };
-
     if is_life_workspace && life::life_debug_enabled() {
-        eprintln!(
-            "[life] send_user_message: skipping per-turn domain injection (thread={})",
-            thread_id
-        );
+        eprintln!("[life] skipping per-turn domain injection (workspace={workspace_id})");
     }
 
-    let params = build_turn_start_params(
-        &thread_id,
-        input,
+    let data_dir = state.data_dir();
+    let sources = MessageSources {
+        workspace_id,
+        workspace_path: &workspace_path,
+        data_dir: &data_dir,
+        domain,
+        domains_path: &state.domains_path,
+        redactor: redactor.as_ref(),
+    };
+    assemble_message(&sources, text, file_refs, best_effort, images, recall)
+}
+
+/// Sizes of what `send_user_message` would send with these arguments,
+/// without sending it or remembering the model for the thread.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn estimate_message_size(
+    workspace_id: String,
+    thread_id: Option<String>,
+    text: String,
+    model: Option<String>,
+    images: Option<Vec<String>>,
+    collaboration_mode: Option<Value>,
+    file_refs: Option<Vec<String>>,
+    best_effort: Option<bool>,
+    disable_redaction: Option<bool>,
+    disable_recall: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "estimate_message_size",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "text": text,
+                "model": model,
+                "images": images,
+                "collaborationMode": collaboration_mode,
+                "fileRefs": file_refs,
+                "bestEffort": best_effort,
+                "disableRedaction": disable_redaction,
+                "disableRecall": disable_recall,
+            }),
+        )
+        .await;
+    }
+
+    let message = assemble_user_message(
+        &state,
+        &workspace_id,
+        &text,
+        file_refs.as_deref(),
+        best_effort.unwrap_or(false),
+        images,
+        disable_redaction.unwrap_or(false),
+        disable_recall.unwrap_or(false),
+    )
+    .await?;
+    let session = state
+        .sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not connected")?;
+    let (model, context_window) = thread_overrides::resolve_model_window(
+        &thread_overrides::thread_overrides_path(&state.data_dir()),
+        &session,
+        thread_id.as_deref(),
+        model.as_deref(),
+    )
+    .await;
+    let policy = turn_policy_for(&state, &workspace_id, None).await?;
+    let params = message.turn_start_params(
+        thread_id.as_deref().unwrap_or(""),
         &session.entry.path,
-        approval_policy,
-        sandbox_policy,
-        model,
-        effort,
+        &policy,
+        model.clone(),
+        None,
         collaboration_mode,
-        domain_instructions,
-    );
-    session.send_request("turn/start", params).await
+        None,
+    )?;
+    serde_json::to_value(message.estimate(&params, model, context_window))
+        .map_err(|err| err.to_string())
+}
+
+/// Resolves `parts` into one message and sizes it with
+/// `estimate_message_size`. With `send`, the message then goes out through
+/// `send_user_message`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn compose_message(
+    workspace_id: String,
+    parts: Vec<Value>,
+    max_bytes: Option<usize>,
+    send: Option<bool>,
+    thread_id: Option<String>,
+    model: Option<String>,
+    effort: Option<String>,
+    access_mode: Option<String>,
+    collaboration_mode: Option<Value>,
+    disable_redaction: Option<bool>,
+    disable_recall: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "compose_message",
+            json!({
+                "workspaceId": workspace_id,
+                "parts": parts,
+                "maxBytes": max_bytes,
+                "send": send,
+                "threadId": thread_id,
+                "model": model,
+                "effort": effort,
+                "accessMode": access_mode,
+                "collaborationMode": collaboration_mode,
+                "disableRedaction": disable_redaction,
+                "disableRecall": disable_recall,
+            }),
+        )
+        .await;
+    }
+
+    let send = send.unwrap_or(false);
+    if send && thread_id.is_none() {
+        return Err("threadId is required to send".to_string());
+    }
+    let parts: Vec<MessagePart> = serde_json::from_value(Value::Array(parts))
+        .map_err(|err| format!("Invalid parts: {err}"))?;
+    let entry = state
+        .workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not found")?;
+    let prompts = crate::prompts::prompts_list(state.clone(), workspace_id.clone(), app.clone())
+        .await?
+        .into_iter()
+        .map(|prompt| PromptSource {
+            path: prompt.path,
+            content: prompt.content,
+        })
+        .collect();
+    let redactor = {
+        let settings = state.app_settings.lock().await;
+        Redactor::for_call(&settings.redaction, disable_redaction.unwrap_or(false))
+    };
+    let sources = ComposeSources {
+        workspace_id: workspace_id.clone(),
+        workspace_path: PathBuf::from(&entry.path),
+        data_dir: state.data_dir(),
+        repo_root: resolve_git_root(&entry),
+        ignore_whitespace: entry.settings.diff_ignore_whitespace,
+        prompts,
+        redactor,
+    };
+    let budget = max_bytes.unwrap_or(DEFAULT_COMPOSE_BUDGET_BYTES);
+    let composed = crate::backend::blocking::run_git_blocking(move || {
+        compose_message_inner(&sources, &parts, budget)
+    })
+    .await?;
+    let images = (!composed.images.is_empty()).then(|| composed.images.clone());
+
+    let estimate = estimate_message_size(
+        workspace_id.clone(),
+        thread_id.clone(),
+        composed.text.clone(),
+        model.clone(),
+        images.clone(),
+        collaboration_mode.clone(),
+        None,
+        None,
+        disable_redaction,
+        disable_recall,
+        state.clone(),
+        app.clone(),
+    )
+    .await?;
+    let mut response = serde_json::to_value(&composed).map_err(|err| err.to_string())?;
+    response["estimate"] = estimate;
+    if let (true, Some(thread_id)) = (send, thread_id) {
+        response["sent"] = send_user_message(
+            workspace_id,
+            thread_id,
+            composed.text,
+            model,
+            effort,
+            access_mode,
+            images,
+            collaboration_mode,
+            None,
+            None,
+            disable_redaction,
+            disable_recall,
+            None,
+            state,
+            app,
+        )
+        .await?;
+    }
+    Ok(response)
 }
 
 #[tauri::command]
@@ -541,10 +1099,35 @@ pub(crate) async fn collaboration_mode_list(
         .get(&workspace_id)
         .ok_or("workspace not connected")?;
     session
-        .send_request("collaborationMode/list", json!({}))
+        .send_optional_request("collaborationMode/list", json!({}))
         .await
 }
 
+#[tauri::command]
+pub(crate) async fn session_capabilities(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<SessionCapabilities, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "session_capabilities",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let sessions = state.sessions.lock().await;
+    let session = sessions
+        .get(&workspace_id)
+        .ok_or("workspace not connected")?;
+    let capabilities = session.capabilities.lock().await.clone();
+    Ok(capabilities)
+}
+
 #[tauri::command]
 pub(crate) async fn turn_interrupt(
     workspace_id: String,
@@ -580,9 +1163,11 @@ pub(crate) async fn start_review(
     thread_id: String,
     target: Value,
     delivery: Option<String>,
+    acknowledge_large: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
+    let acknowledge_large = acknowledge_large.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -593,24 +1178,72 @@ pub(crate) async fn start_review(
                 "threadId": thread_id,
                 "target": target,
                 "delivery": delivery,
+                "acknowledgeLarge": acknowledge_large,
             }),
         )
         .await;
     }
 
+    if !acknowledge_large {
+        let limits = state.app_settings.lock().await.review_gate.clone();
+        let entry = state
+            .workspaces
+            .lock()
+            .await
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?;
+        if let Ok(repo_root) = resolve_git_root(&entry) {
+            if let Some(too_large) = review_gate::check_review_size(&repo_root, &target, &limits) {
+                return Ok(too_large);
+            }
+        }
+    }
     let sessions = state.sessions.lock().await;
     let session = sessions
         .get(&workspace_id)
         .ok_or("workspace not connected")?;
+    if let Some(delivery) = delivery.as_deref() {
+        session
+            .capabilities
+            .lock()
+            .await
+            .check_review_delivery(delivery)?;
+    }
     let mut params = Map::new();
     params.insert("threadId".to_string(), json!(thread_id));
-    params.insert("target".to_string(), target);
-    if let Some(delivery) = delivery {
+    params.insert("target".to_string(), target.clone());
+    if let Some(delivery) = delivery.as_ref() {
         params.insert("delivery".to_string(), json!(delivery));
     }
-    session
-        .send_request("review/start", Value::Object(params))
-        .await
+    let response = session
+        .send_optional_request("review/start", Value::Object(params))
+        .await?;
+    thread_activity::record_review_started(&workspace_id, &thread_id, target, delivery, &response);
+    Ok(response)
+}
+
+#[tauri::command]
+pub(crate) async fn list_reviews(
+    workspace_id: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ReviewRecord>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_reviews",
+            json!({ "workspaceId": workspace_id, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    if !state.workspaces.lock().await.contains_key(&workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    Ok(thread_activity::list_reviews(&workspace_id, limit))
 }
 
 #[tauri::command]
@@ -634,7 +1267,57 @@ pub(crate) async fn model_list(
         .get(&workspace_id)
         .ok_or("workspace not connected")?;
     let params = json!({});
-    session.send_request("model/list", params).await
+    let response = session.send_request("model/list", params).await?;
+    thread_overrides::record_model_list(&workspace_id, &response);
+    Ok(response)
+}
+
+#[tauri::command]
+pub(crate) async fn get_thread_overrides(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<ThreadOverrides>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_thread_overrides",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(thread_overrides::get_thread_overrides(
+        &thread_overrides::thread_overrides_path(&state.data_dir()),
+        &workspace_id,
+        &thread_id,
+    ))
+}
+
+#[tauri::command]
+pub(crate) async fn clear_thread_overrides(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "clear_thread_overrides",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+    let cleared = thread_overrides::clear_thread_overrides(
+        &thread_overrides::thread_overrides_path(&state.data_dir()),
+        &workspace_id,
+        &thread_id,
+    )?;
+    Ok(json!({ "cleared": cleared }))
 }
 
 #[tauri::command]
@@ -718,21 +1401,23 @@ pub(crate) async fn respond_to_server_request(
 #[tauri::command]
 pub(crate) async fn get_commit_message_prompt(
     workspace_id: String,
+    disable_redaction: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<String, String> {
+) -> Result<CommitMessagePrompt, String> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
             "get_commit_message_prompt",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "disableRedaction": disable_redaction }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
     // Get the diff from git
-    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
+    let (diff, redactions) =
+        redacted_workspace_diff(&workspace_id, disable_redaction.unwrap_or(false), &state).await?;
 
     if diff.trim().is_empty() {
         return Err("No changes to generate commit message for".to_string());
@@ -746,7 +1431,21 @@ Only output the commit message, nothing else.\n\n\
 Changes:\n{diff}"
     );
 
-    Ok(prompt)
+    Ok(CommitMessagePrompt { prompt, redactions })
+}
+
+/// The workspace diff as it may go into a prompt.
+async fn redacted_workspace_diff(
+    workspace_id: &str,
+    disable_redaction: bool,
+    state: &State<'_, AppState>,
+) -> Result<(String, usize), String> {
+    let diff = crate::git::get_workspace_diff(workspace_id, state).await?;
+    let redactor = Redactor::for_call(
+        &state.app_settings.lock().await.redaction,
+        disable_redaction,
+    );
+    Ok(redact_diff_with(redactor.as_ref(), diff))
 }
 
 #[tauri::command]
@@ -803,34 +1502,28 @@ pub(crate) async fn remember_approval_rule(
 #[tauri::command]
 pub(crate) async fn generate_commit_message(
     workspace_id: String,
+    disable_redaction: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<String, String> {
+) -> Result<CommitMessageResult, String> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
             "generate_commit_message",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "disableRedaction": disable_redaction }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
     // Get the diff from git
-    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
+    let (diff, redactions) =
+        redacted_workspace_diff(&workspace_id, disable_redaction.unwrap_or(false), &state).await?;
 
     if diff.trim().is_empty() {
         return Err("No changes to generate commit message for".to_string());
     }
 
-    let prompt = format!(
-        "Generate a concise git commit message for the following changes. \
-Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
-Focus on the 'why' rather than the 'what'. Keep the summary line under 72 characters. \
-Only output the commit message, nothing else.\n\n\
-Changes:\n{diff}"
-    );
-
     // Get the session
     let session = {
         let sessions = state.sessions.lock().await;
@@ -840,152 +1533,131 @@ Changes:\n{diff}"
             .clone()
     };
 
-    // Create a background thread
-    let thread_params = json!({
-        "cwd": session.entry.path,
-        "approvalPolicy": "never"  // Never ask for approval in background
-    });
-    let thread_result = session.send_request("thread/start", thread_params).await?;
-
-    // Handle error response
-    if let Some(error) = thread_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting thread");
-        return Err(error_msg.to_string());
-    }
-
-    // Extract threadId - try multiple paths since response format may vary
-    let thread_id = thread_result
-        .get("result")
-        .and_then(|r| r.get("threadId"))
-        .or_else(|| {
-            thread_result
-                .get("result")
-                .and_then(|r| r.get("thread"))
-                .and_then(|t| t.get("id"))
-        })
-        .or_else(|| thread_result.get("threadId"))
-        .or_else(|| thread_result.get("thread").and_then(|t| t.get("id")))
-        .and_then(|t| t.as_str())
-        .ok_or_else(|| {
-            format!(
-                "Failed to get threadId from thread/start response: {:?}",
-                thread_result
-            )
-        })?
-        .to_string();
-
-    // Create channel for receiving events
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-
-    // Register callback for this thread
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.insert(thread_id.clone(), tx);
-    }
-
-    // Start a turn with the commit message prompt
-    let turn_params = build_turn_start_params(
-        &thread_id,
-        vec![json!({ "type": "text", "text": prompt })],
-        &session.entry.path,
-        "never",
-        json!({ "type": "readOnly" }),
-        None,
-        None,
-        None,
-        None,
-    );
-    let turn_result = session.send_request("turn/start", turn_params).await;
-    let turn_result = match turn_result {
-        Ok(result) => result,
-        Err(error) => {
-            // Clean up if turn fails to start
-            {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
-            }
-            let archive_params = json!({ "threadId": thread_id.as_str() });
-            let _ = session.send_request("thread/archive", archive_params).await;
-            return Err(error);
-        }
-    };
+    let message = generate_commit_message_for_diff(&session, &diff).await?;
 
-    if let Some(error) = turn_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting turn");
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
-        }
-        let archive_params = json!({ "threadId": thread_id.as_str() });
-        let _ = session.send_request("thread/archive", archive_params).await;
-        return Err(error_msg.to_string());
-    }
-
-    // Collect assistant text from events
-    let mut commit_message = String::new();
-    let timeout_duration = Duration::from_secs(60);
-    let collect_result = timeout(timeout_duration, async {
-        while let Some(event) = rx.recv().await {
-            let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
-
-            match method {
-                "item/agentMessage/delta" => {
-                    // Extract text delta from agent messages
-                    if let Some(params) = event.get("params") {
-                        if let Some(delta) = params.get("delta").and_then(|d| d.as_str()) {
-                            commit_message.push_str(delta);
-                        }
-                    }
-                }
-                "turn/completed" => {
-                    // Turn completed, we can stop listening
-                    break;
-                }
-                "turn/error" => {
-                    // Error occurred
-                    let error_msg = event
-                        .get("params")
-                        .and_then(|p| p.get("error"))
-                        .and_then(|e| e.as_str())
-                        .unwrap_or("Unknown error during commit message generation");
-                    return Err(error_msg.to_string());
-                }
-                _ => {
-                    // Ignore other events (turn/started, item/started, item/completed, reasoning events, etc.)
-                }
-            }
-        }
-        Ok(())
+    Ok(CommitMessageResult {
+        message,
+        redactions,
     })
-    .await;
+}
 
-    // Unregister callback
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.remove(&thread_id);
+/// Commits just the files a turn changed. Without a message, one is generated
+/// from the staged diff the same way `generate_commit_message` does.
+#[tauri::command]
+pub(crate) async fn commit_turn_changes(
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    message: Option<String>,
+    allow_dirty: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TurnCommitResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "commit_turn_changes",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "message": message,
+                "allowDirty": allow_dirty,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
+    ensure_path_available(&workspace_id)?;
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .ok_or("workspace not found")?
+            .clone()
+    };
+    ensure_writable(&entry, "commit")?;
+    let repo_root = resolve_git_root(&entry)?;
+    let staged = turn_changes::stage_turn_changes(
+        &repo_root,
+        &workspace_id,
+        &thread_id,
+        &turn_id,
+        allow_dirty.unwrap_or(false),
+    )
+    .await?;
+    let message = match message.filter(|message| !message.trim().is_empty()) {
+        Some(message) => message,
+        None => {
+            let redactor = Redactor::for_call(&state.app_settings.lock().await.redaction, false);
+            let (diff, _) = redact_diff_with(redactor.as_ref(), staged.diff.clone());
+            let session = {
+                let sessions = state.sessions.lock().await;
+                sessions
+                    .get(&workspace_id)
+                    .ok_or("workspace not connected")?
+                    .clone()
+            };
+            generate_commit_message_for_diff(&session, &diff).await?
+        }
+    };
+    turn_changes::commit_staged_turn_changes(&repo_root, staged, message).await
+}
 
-    // Archive the thread to clean up
-    let archive_params = json!({ "threadId": thread_id });
-    let _ = session.send_request("thread/archive", archive_params).await;
-
-    // Handle timeout or collection error
-    match collect_result {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => return Err(e),
-        Err(_) => return Err("Timeout waiting for commit message generation".to_string()),
+/// Summarizes a long thread in the background. With `start_new`, a fresh
+/// thread is started and seeded with the summary as its first message.
+#[tauri::command]
+pub(crate) async fn summarize_thread(
+    workspace_id: String,
+    thread_id: String,
+    start_new: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadSummary, String> {
+    let start_new = start_new.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "summarize_thread",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "startNew": start_new }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    let trimmed = commit_message.trim().to_string();
-    if trimmed.is_empty() {
-        return Err("No commit message was generated".to_string());
+    let session = {
+        let sessions = state.sessions.lock().await;
+        sessions
+            .get(&workspace_id)
+            .ok_or("workspace not connected")?
+            .clone()
+    };
+    let mut summary = thread_summary::summarize_thread(&session, &thread_id).await?;
+    if start_new {
+        let started = start_thread(workspace_id.clone(), state.clone(), app.clone()).await?;
+        let new_thread_id = thread_id_from_start_response(&started)
+            .ok_or("Failed to get threadId for the compacted thread")?;
+        send_user_message(
+            workspace_id,
+            new_thread_id.clone(),
+            thread_summary::seed_message(&summary.summary),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+            state,
+            app,
+        )
+        .await?;
+        summary.new_thread_id = Some(new_thread_id);
     }
-
-    Ok(trimmed)
+    Ok(summary)
 }
@@ -1,7 +1,50 @@
+use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 
 use crate::types::{AppSettings, WorkspaceEntry};
 
+pub(crate) const SOURCE_SETTINGS: &str = "settings";
+pub(crate) const SOURCE_WORKSPACE: &str = "workspace";
+pub(crate) const SOURCE_PARENT: &str = "parent";
+
+/// Codex flags that consume the next argument unless written as `--flag=value`.
+const VALUE_FLAGS: &[&str] = &[
+    "-c",
+    "--config",
+    "-p",
+    "--profile",
+    "-m",
+    "--model",
+    "-s",
+    "--sandbox",
+    "-a",
+    "--ask-for-approval",
+    "-C",
+    "--cd",
+    "-i",
+    "--image",
+    "--enable",
+    "--disable",
+    "--local-provider",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct EffectiveCodexArg {
+    pub(crate) value: String,
+    /// `settings`, `workspace` or `parent` (the worktree's parent workspace).
+    pub(crate) source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct EffectiveCodexArgs {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    pub(crate) args: Vec<EffectiveCodexArg>,
+    /// True when the running session was spawned with different args.
+    #[serde(rename = "needsReconnect")]
+    pub(crate) needs_reconnect: bool,
+}
+
 pub(crate) fn parse_codex_args(value: Option<&str>) -> Result<Vec<String>, String> {
     let raw = match value {
         Some(raw) if !raw.trim().is_empty() => raw.trim(),
@@ -25,28 +68,115 @@ pub(crate) fn resolve_workspace_codex_args(
     parent_entry: Option<&WorkspaceEntry>,
     app_settings: Option<&AppSettings>,
 ) -> Option<String> {
+    resolve_workspace_codex_args_with_source(entry, parent_entry, app_settings)
+        .map(|(args, _)| args)
+}
+
+/// Same precedence as `resolve_workspace_codex_args`, also naming which level
+/// the args came from. Levels are not merged: the first non-empty one wins.
+pub(crate) fn resolve_workspace_codex_args_with_source(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+    app_settings: Option<&AppSettings>,
+) -> Option<(String, &'static str)> {
     if let Some(value) = entry.settings.codex_args.as_deref() {
         if let Some(normalized) = normalize_codex_args(value) {
-            return Some(normalized);
+            return Some((normalized, SOURCE_WORKSPACE));
         }
     }
     if entry.kind.is_worktree() {
         if let Some(parent) = parent_entry {
             if let Some(value) = parent.settings.codex_args.as_deref() {
                 if let Some(normalized) = normalize_codex_args(value) {
-                    return Some(normalized);
+                    return Some((normalized, SOURCE_PARENT));
                 }
             }
         }
     }
     if let Some(settings) = app_settings {
         if let Some(value) = settings.codex_args.as_deref() {
-            return normalize_codex_args(value);
+            return normalize_codex_args(value).map(|normalized| (normalized, SOURCE_SETTINGS));
         }
     }
     None
 }
 
+/// The resolved arg list for a workspace, one entry per argument.
+pub(crate) fn effective_codex_args(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+    app_settings: Option<&AppSettings>,
+    spawned_with: Option<Option<&str>>,
+) -> Result<EffectiveCodexArgs, String> {
+    let resolved = resolve_workspace_codex_args_with_source(entry, parent_entry, app_settings);
+    let args = match &resolved {
+        Some((raw, source)) => parse_codex_args(Some(raw))?
+            .into_iter()
+            .map(|value| EffectiveCodexArg {
+                value,
+                source: source.to_string(),
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    let resolved_raw = resolved.as_ref().map(|(raw, _)| raw.as_str());
+    Ok(EffectiveCodexArgs {
+        workspace_id: entry.id.clone(),
+        args,
+        needs_reconnect: spawned_with.is_some_and(|spawned| spawned != resolved_raw),
+    })
+}
+
+fn is_dangerous_arg(arg: &str, previous: Option<&str>) -> bool {
+    if arg.starts_with("--dangerously-") || arg == "--yolo" {
+        return true;
+    }
+    let sandbox_value = match arg.split_once('=') {
+        Some(("--sandbox", value)) => value,
+        Some(("sandbox_mode", value)) => value.trim_matches(['"', '\'']),
+        _ if matches!(previous, Some("-s" | "--sandbox")) => arg,
+        _ => return false,
+    };
+    sandbox_value == "danger-full-access"
+}
+
+/// Checks a workspace arg list before it is saved. Args that turn off
+/// approvals or the sandbox are refused unless `confirm_dangerous` is set.
+pub(crate) fn validate_codex_args(args: &[String], confirm_dangerous: bool) -> Result<(), String> {
+    let mut expecting_value: Option<&str> = None;
+    for (index, arg) in args.iter().enumerate() {
+        if arg.trim().is_empty() {
+            return Err(format!("Codex arg {} is empty", index + 1));
+        }
+        if let Some(flag) = expecting_value.take() {
+            if arg.starts_with('-') {
+                return Err(format!("`{flag}` needs a value before `{arg}`"));
+            }
+        } else if VALUE_FLAGS.contains(&arg.as_str()) {
+            expecting_value = Some(arg.as_str());
+        }
+        let previous = index.checked_sub(1).map(|prev| args[prev].as_str());
+        if !confirm_dangerous && is_dangerous_arg(arg, previous) {
+            return Err(format!(
+                "`{arg}` disables approvals or sandboxing; confirm to keep it"
+            ));
+        }
+    }
+    if let Some(flag) = expecting_value {
+        return Err(format!("`{flag}` needs a value"));
+    }
+    Ok(())
+}
+
+/// Joins an arg list into the shell-quoted form stored in workspace settings.
+pub(crate) fn join_codex_args(args: &[String]) -> Option<String> {
+    if args.is_empty() {
+        None
+    } else {
+        Some(shell_words::join(args))
+    }
+}
+
 fn normalize_codex_args(value: &str) -> Option<String> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -58,7 +188,10 @@ fn normalize_codex_args(value: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_codex_args, resolve_workspace_codex_args};
+    use super::{
+        effective_codex_args, join_codex_args, parse_codex_args, resolve_workspace_codex_args,
+        validate_codex_args,
+    };
     use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
 
     #[test]
@@ -133,4 +266,80 @@ mod tests {
         let resolved_main = resolve_workspace_codex_args(&main, None, Some(&app_settings));
         assert_eq!(resolved_main.as_deref(), Some("--profile app"));
     }
+
+    #[test]
+    fn validates_workspace_args() {
+        let args = |raw: &str| parse_codex_args(Some(raw)).expect("parse args");
+        assert!(validate_codex_args(&args("--profile work -c model=\"o3\""), false).is_ok());
+        assert!(validate_codex_args(&args("--model=o3 --search"), false).is_ok());
+        assert_eq!(
+            validate_codex_args(&args("--profile --search"), false).unwrap_err(),
+            "`--profile` needs a value before `--search`"
+        );
+        assert_eq!(
+            validate_codex_args(&args("--search -m"), false).unwrap_err(),
+            "`-m` needs a value"
+        );
+        assert!(validate_codex_args(&["".to_string()], false).is_err());
+
+        for dangerous in [
+            "--dangerously-bypass-approvals-and-sandbox",
+            "--sandbox danger-full-access",
+            "-c sandbox_mode=\"danger-full-access\"",
+        ] {
+            assert!(validate_codex_args(&args(dangerous), false).is_err());
+            assert!(validate_codex_args(&args(dangerous), true).is_ok());
+        }
+
+        let joined = join_codex_args(&args("--path \"a b\" --flag")).expect("joined");
+        assert_eq!(args(&joined), vec!["--path", "a b", "--flag"]);
+        assert_eq!(join_codex_args(&[]), None);
+    }
+
+    #[test]
+    fn effective_args_carry_their_source() {
+        let app_settings = AppSettings {
+            codex_args: Some("--profile app --search".to_string()),
+            ..AppSettings::default()
+        };
+        let entry = WorkspaceEntry {
+            id: "main".to_string(),
+            name: "Main".to_string(),
+            path: "/tmp/main".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+
+        let effective =
+            effective_codex_args(&entry, None, Some(&app_settings), None).expect("effective");
+        let summary: Vec<_> = effective
+            .args
+            .iter()
+            .map(|arg| (arg.value.as_str(), arg.source.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("--profile", "settings"),
+                ("app", "settings"),
+                ("--search", "settings")
+            ]
+        );
+        assert!(!effective.needs_reconnect);
+
+        let connected = effective_codex_args(
+            &entry,
+            None,
+            Some(&app_settings),
+            Some(Some("--profile app --search")),
+        )
+        .expect("effective");
+        assert!(!connected.needs_reconnect);
+        let stale =
+            effective_codex_args(&entry, None, Some(&app_settings), Some(None)).expect("effective");
+        assert!(stale.needs_reconnect);
+    }
 }
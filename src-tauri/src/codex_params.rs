@@ -102,6 +102,39 @@ pub(crate) fn build_user_input(
     Ok(input)
 }
 
+/// `turn/start` options callers may set beyond the ones built here.
+pub(crate) const TURN_EXTRA_KEYS: [&str; 3] = ["reasoning", "includePlan", "tools"];
+
+/// Error prefix for extras outside `TURN_EXTRA_KEYS`. The rest of the message
+/// is JSON: `{ "unknownKeys", "allowedKeys" }`.
+pub(crate) const UNKNOWN_TURN_EXTRA_ERROR: &str = "unknown_turn_extra";
+
+pub(crate) fn validate_turn_extras(extras: &Map<String, Value>) -> Result<(), String> {
+    let unknown: Vec<&str> = extras
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !TURN_EXTRA_KEYS.contains(key))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "{UNKNOWN_TURN_EXTRA_ERROR}: {}",
+        json!({ "unknownKeys": unknown, "allowedKeys": TURN_EXTRA_KEYS })
+    ))
+}
+
+/// The workspace's `turnExtras` with the call's extras laid over them, key by
+/// key.
+pub(crate) fn resolve_turn_extras(
+    workspace: Option<&Map<String, Value>>,
+    call: Option<&Map<String, Value>>,
+) -> Option<Map<String, Value>> {
+    let mut extras = workspace.cloned().unwrap_or_default();
+    extras.extend(call.cloned().unwrap_or_default());
+    (!extras.is_empty()).then_some(extras)
+}
+
 pub(crate) fn build_turn_start_params(
     thread_id: &str,
     input: Vec<Value>,
@@ -112,13 +145,14 @@ pub(crate) fn build_turn_start_params(
     effort: Option<String>,
     collaboration_mode: Option<Value>,
     instruction_injection: Option<String>,
-) -> Value {
+    extras: Option<Map<String, Value>>,
+) -> Result<Value, String> {
     let collaboration_mode = normalize_collaboration_mode(collaboration_mode);
     let merged_instructions = merge_instruction_injection(
         instruction_injection,
         extract_developer_instructions(&collaboration_mode),
     );
-    json!({
+    let mut params = json!({
         "threadId": thread_id,
         "input": input,
         "cwd": cwd,
@@ -128,7 +162,14 @@ pub(crate) fn build_turn_start_params(
         "effort": effort,
         "instructionInjection": merged_instructions,
         "collaborationMode": collaboration_mode,
-    })
+    });
+    if let Some(extras) = extras {
+        validate_turn_extras(&extras)?;
+        if let Some(params) = params.as_object_mut() {
+            params.extend(extras);
+        }
+    }
+    Ok(params)
 }
 
 fn extract_developer_instructions(collaboration_mode: &Option<Value>) -> Option<String> {
@@ -180,7 +221,10 @@ fn merge_instruction_injection(base: Option<String>, extra: Option<String>) -> O
 
 #[cfg(test)]
 mod tests {
-    use super::{build_turn_start_params, build_user_input, normalize_collaboration_mode};
+    use super::{
+        build_turn_start_params, build_user_input, normalize_collaboration_mode,
+        resolve_turn_extras,
+    };
     use serde_json::json;
 
     #[test]
@@ -293,7 +337,9 @@ mod tests {
             None,
             Some(collaboration_mode),
             None,
-        );
+            None,
+        )
+        .expect("params");
         assert!(params.get("settings").is_none());
         let expected = json!({
             "mode": "plan",
@@ -325,7 +371,9 @@ mod tests {
             None,
             Some(collaboration_mode),
             Some("Domain instructions".to_string()),
-        );
+            None,
+        )
+        .expect("params");
         let merged = params
             .get("instructionInjection")
             .and_then(|value| value.as_str())
@@ -333,4 +381,45 @@ mod tests {
         assert!(merged.contains("Domain instructions"));
         assert!(merged.contains("User instructions"));
     }
+
+    #[test]
+    fn build_turn_start_params_layers_call_extras_over_workspace_defaults() {
+        let build = |extras| {
+            build_turn_start_params(
+                "thread-1",
+                vec![json!({ "type": "text", "text": "hi" })],
+                "/tmp",
+                "never",
+                json!({ "type": "readOnly" }),
+                None,
+                None,
+                None,
+                None,
+                extras,
+            )
+        };
+        let workspace = json!({ "reasoning": { "summary": "none" }, "includePlan": true });
+        let call =
+            json!({ "reasoning": { "summary": "detailed" }, "tools": { "webSearch": false } });
+
+        let params = build(resolve_turn_extras(None, None)).expect("params");
+        assert!(params.get("reasoning").is_none());
+
+        let params = build(resolve_turn_extras(workspace.as_object(), None)).expect("params");
+        assert_eq!(params["reasoning"]["summary"], "none");
+        assert_eq!(params["includePlan"], true);
+
+        let params =
+            build(resolve_turn_extras(workspace.as_object(), call.as_object())).expect("params");
+        assert_eq!(params["reasoning"]["summary"], "detailed");
+        assert_eq!(params["includePlan"], true);
+        assert_eq!(params["tools"]["webSearch"], false);
+
+        let bad = json!({ "sandboxPolicy": {}, "tools": {} });
+        let err = build(bad.as_object().cloned()).expect_err("unknown key");
+        assert_eq!(
+            err,
+            r#"unknown_turn_extra: {"allowedKeys":["reasoning","includePlan","tools"],"unknownKeys":["sandboxPolicy"]}"#
+        );
+    }
 }
@@ -0,0 +1,504 @@
+//! Connections to the daemon and who is behind them. Clients describe
+//! themselves (`client: { name, platform, appVersion }`) on `auth` or
+//! `client_capabilities`; mutating requests are then logged and kept in a
+//! per-workspace activity feed under that description.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use crate::settings_audit;
+use crate::workspace_identity::WorkspaceIdentity;
+
+/// Activity entries kept per workspace.
+const MAX_ACTIVITY_PER_WORKSPACE: usize = 200;
+const MAX_FIELD_CHARS: usize = 80;
+
+/// Methods that change workspaces, threads, files or settings. Reads are left
+/// out of the activity feed.
+const MUTATING_METHODS: &[&str] = &[
+    "add_workspace",
+    "add_clone",
+    "scan_and_import_workspaces",
+    "onboarding_bootstrap",
+    "add_worktree",
+    "remove_workspace",
+    "remove_worktree",
+    "rename_worktree",
+    "rename_worktree_upstream",
+    "apply_worktree_changes",
+    "update_workspace_settings",
+    "update_workspace_codex_bin",
+    "set_workspace_codex_args",
+    "run_quick_command",
+    "cancel_quick_command",
+    "write_global_agents_md",
+    "write_global_config_toml",
+    "update_app_settings",
+    "update_app_settings_partial",
+    "domains_create",
+    "domains_update",
+    "domains_delete",
+    "memory_append",
+    "memory_flush_now",
+    "mark_bill_paid",
+    "stream_append",
+    "export_life_report",
+    "update_media_item",
+    "start_thread",
+    "archive_thread",
+    "archive_threads",
+    "set_pinned_threads",
+    "send_user_message",
+    "clear_thread_overrides",
+    "turn_interrupt",
+    "start_review",
+    "skills_config_write",
+    "skills_install_from_git",
+    "skills_uninstall",
+    "operation_cancel",
+    "stage_git_file",
+    "stage_git_all",
+    "stage_git_hunk",
+    "unstage_git_file",
+    "unstage_git_hunk",
+    "restore_revert_undo",
+    "revert_git_file",
+    "revert_git_all",
+    "commit_git",
+    "commit_turn_changes",
+    "pull_git",
+    "push_git",
+    "sync_git",
+    "checkout_git_branch",
+    "create_git_branch",
+    "create_git_tag",
+    "delete_git_tag",
+    "stash_git",
+    "pop_git_stash",
+    "drop_git_stash",
+    "prompts_create",
+    "prompts_update",
+    "prompts_delete",
+    "prompts_move",
+    "terminal_open",
+    "terminal_close",
+    "respond_to_server_request",
+    "remember_approval_rule",
+    "disconnect_client",
+];
+
+pub(crate) fn is_mutating_method(method: &str) -> bool {
+    MUTATING_METHODS.contains(&method)
+}
+
+/// Methods a read-only token may call. Everything else is refused, so a new
+/// method stays out of reach of read-only clients until it is listed here.
+/// `resume_thread`, `follow_file` and the doctors are left out: they start
+/// sessions, watchers or processes. `read_global_config_toml` is left out
+/// because the config can hold credentials, and `memory_test_connection`
+/// because it connects to whatever URL the client sends.
+const READ_METHODS: &[&str] = &[
+    "ping",
+    "health",
+    "list_workspaces",
+    "is_workspace_path_dir",
+    "worktree_report",
+    "compare_worktrees",
+    "get_effective_codex_args",
+    "list_workspace_files",
+    "scan_workspace_todos",
+    "search_workspace_files",
+    "read_workspace_file",
+    "tail_workspace_file",
+    "list_quick_commands",
+    "read_global_agents_md",
+    "get_app_settings",
+    "settings_audit",
+    "data_usage",
+    "resource_usage",
+    "domains_list",
+    "domain_usage",
+    "memory_status",
+    "memory_search",
+    "browser_list_sessions",
+    "browser_screenshot",
+    "browser_snapshot",
+    "onboarding_status",
+    "get_life_workspace_prompt",
+    "get_delivery_dashboard",
+    "get_nutrition_dashboard",
+    "get_exercise_dashboard",
+    "get_media_dashboard",
+    "get_youtube_dashboard",
+    "list_domain_entities",
+    "read_domain_entity",
+    "media_providers_status",
+    "get_finance_dashboard",
+    "get_commit_message_prompt",
+    "list_threads",
+    "threads_overview",
+    "auto_archive_preview",
+    "preview_turn_policy",
+    "estimate_message_size",
+    "compose_message",
+    "get_thread_overrides",
+    "list_reviews",
+    "model_list",
+    "collaboration_mode_list",
+    "session_capabilities",
+    "account_rate_limits",
+    "skills_list",
+    "skills_config_read",
+    "skills_validate",
+    "clients_list",
+    "workspace_activity",
+    "operations_list",
+    "operation_status",
+    "domain_trends",
+    "list_git_roots",
+    "get_git_status",
+    "get_git_diffs",
+    "get_git_log",
+    "get_git_commit_diff",
+    "get_git_remote",
+    "list_revert_undos",
+    "get_last_commit_message",
+    "list_git_branches",
+    "list_git_tags",
+    "list_git_stashes",
+    "get_github_issues",
+    "get_github_pull_requests",
+    "get_github_check_rollups",
+    "get_github_pull_request_diff",
+    "get_github_pull_request_comments",
+    "gh_auth_status",
+    "prompts_list",
+    "prompts_workspace_dir",
+    "prompts_global_dir",
+    "usage_overview",
+    "local_usage_snapshot",
+];
+
+/// Flags that make a listed read method write: `worktree_report` removes
+/// worktrees with `removeSafe` and `compose_message` sends with `send`.
+const WRITE_FLAGS: &[(&str, &str)] = &[
+    ("worktree_report", "removeSafe"),
+    ("compose_message", "send"),
+];
+
+/// Whether a read-only token may call `method` with `params`.
+pub(crate) fn read_only_allows(method: &str, params: &Value) -> bool {
+    READ_METHODS.contains(&method)
+        && !WRITE_FLAGS.iter().any(|(write_method, flag)| {
+            *write_method == method && params.get(flag).and_then(Value::as_bool) == Some(true)
+        })
+}
+
+/// Masks what a read-only token may not see in a result it is allowed to
+/// get: the secrets in `get_app_settings`, such as `remoteBackendToken`.
+pub(crate) fn mask_read_only_result(method: &str, result: &mut Value) {
+    if method != "get_app_settings" {
+        return;
+    }
+    if let Some(fields) = result.as_object_mut() {
+        for (key, value) in fields.iter_mut() {
+            *value = settings_audit::masked_setting(key, value.take());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClientInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) app_version: Option<String>,
+}
+
+impl ClientInfo {
+    /// Reads `params.client`. Returns `None` when the client sent nothing
+    /// usable, so an earlier description is not wiped.
+    pub(crate) fn from_params(params: &Value) -> Option<Self> {
+        let client = params.get("client")?;
+        let field = |key: &str| {
+            client
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.chars().take(MAX_FIELD_CHARS).collect::<String>())
+        };
+        let info = Self {
+            name: field("name"),
+            platform: field("platform"),
+            app_version: field("appVersion"),
+        };
+        (info != Self::default()).then_some(info)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConnectedClient {
+    pub(crate) connection_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) peer: Option<String>,
+    pub(crate) connected_at: i64,
+    pub(crate) authenticated: bool,
+    /// Whether the connection receives daemon events. Every authenticated
+    /// connection gets all of them; there is no narrower scope yet.
+    pub(crate) subscribed_to_events: bool,
+    pub(crate) capabilities: Vec<String>,
+    pub(crate) client: ClientInfo,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClientActivity {
+    pub(crate) at: i64,
+    pub(crate) method: String,
+    pub(crate) connection_id: String,
+    pub(crate) client: ClientInfo,
+    pub(crate) ok: bool,
+    /// The workspace's name, color and icon when the request finished; absent
+    /// once it has been removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) workspace: Option<WorkspaceIdentity>,
+    /// Why the daemon acted on its own, for entries no client asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) reason: Option<String>,
+}
+
+struct ClientEntry {
+    seq: u64,
+    snapshot: ConnectedClient,
+    disconnect: Arc<Notify>,
+}
+
+/// Open connections and the mutating requests they made.
+#[derive(Default)]
+pub(crate) struct ClientRegistry {
+    next_id: AtomicU64,
+    clients: Mutex<HashMap<String, ClientEntry>>,
+    activity: Mutex<HashMap<String, VecDeque<ClientActivity>>>,
+}
+
+impl ClientRegistry {
+    /// Registers a connection. The returned `Notify` fires when an admin asks
+    /// for it to be closed.
+    pub(crate) fn connect(&self, peer: Option<SocketAddr>) -> (String, Arc<Notify>) {
+        let seq = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let connection_id = format!("conn-{seq}");
+        let disconnect = Arc::new(Notify::new());
+        let entry = ClientEntry {
+            seq,
+            snapshot: ConnectedClient {
+                connection_id: connection_id.clone(),
+                peer: peer.map(|peer| peer.to_string()),
+                connected_at: now_millis(),
+                authenticated: false,
+                subscribed_to_events: false,
+                capabilities: Vec::new(),
+                client: ClientInfo::default(),
+            },
+            disconnect: Arc::clone(&disconnect),
+        };
+        self.lock_clients().insert(connection_id.clone(), entry);
+        (connection_id, disconnect)
+    }
+
+    pub(crate) fn disconnected(&self, connection_id: &str) {
+        self.lock_clients().remove(connection_id);
+    }
+
+    pub(crate) fn update(&self, connection_id: &str, update: impl FnOnce(&mut ConnectedClient)) {
+        if let Some(entry) = self.lock_clients().get_mut(connection_id) {
+            update(&mut entry.snapshot);
+        }
+    }
+
+    pub(crate) fn client_info(&self, connection_id: &str) -> ClientInfo {
+        self.lock_clients()
+            .get(connection_id)
+            .map(|entry| entry.snapshot.client.clone())
+            .unwrap_or_default()
+    }
+
+    /// Connected clients, oldest connection first.
+    pub(crate) fn list(&self) -> Vec<ConnectedClient> {
+        let clients = self.lock_clients();
+        let mut entries: Vec<&ClientEntry> = clients.values().collect();
+        entries.sort_by_key(|entry| entry.seq);
+        entries
+            .into_iter()
+            .map(|entry| entry.snapshot.clone())
+            .collect()
+    }
+
+    /// Asks a connection to close. Returns false for unknown ids.
+    pub(crate) fn disconnect(&self, connection_id: &str) -> bool {
+        match self.lock_clients().get(connection_id) {
+            Some(entry) => {
+                entry.disconnect.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn record_activity(&self, workspace_id: &str, activity: ClientActivity) {
+        let mut feed = self.activity.lock().unwrap_or_else(|err| err.into_inner());
+        let entries = feed.entry(workspace_id.to_string()).or_default();
+        entries.push_back(activity);
+        while entries.len() > MAX_ACTIVITY_PER_WORKSPACE {
+            entries.pop_front();
+        }
+    }
+
+    /// Newest entries first.
+    pub(crate) fn activity(&self, workspace_id: &str, limit: usize) -> Vec<ClientActivity> {
+        let feed = self.activity.lock().unwrap_or_else(|err| err.into_inner());
+        feed.get(workspace_id)
+            .map(|entries| entries.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn lock_clients(&self) -> std::sync::MutexGuard<'_, HashMap<String, ClientEntry>> {
+        self.clients.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Short label for log lines, e.g. `conn-3 (Pixel 8, android)`.
+pub(crate) fn client_label(connection_id: &str, client: &ClientInfo) -> String {
+    let details: Vec<&str> = [client.name.as_deref(), client.platform.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    if details.is_empty() {
+        connection_id.to_string()
+    } else {
+        format!("{connection_id} ({})", details.join(", "))
+    }
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        client_label, mask_read_only_result, read_only_allows, ClientActivity, ClientInfo,
+        ClientRegistry,
+    };
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn reads_client_metadata_from_params() {
+        let params = json!({
+            "token": "secret",
+            "client": { "name": " Pixel 8 ", "platform": "android", "appVersion": "" }
+        });
+        let info = ClientInfo::from_params(&params).expect("client info");
+        assert_eq!(info.name.as_deref(), Some("Pixel 8"));
+        assert_eq!(info.app_version, None);
+        assert_eq!(client_label("conn-3", &info), "conn-3 (Pixel 8, android)");
+        assert!(ClientInfo::from_params(&json!({ "client": {} })).is_none());
+        assert!(ClientInfo::from_params(&json!("secret")).is_none());
+    }
+
+    #[test]
+    fn read_only_scope_fails_closed() {
+        assert!(read_only_allows("get_git_status", &json!({})));
+        assert!(read_only_allows(
+            "worktree_report",
+            &json!({ "removeSafe": false })
+        ));
+        assert!(!read_only_allows(
+            "worktree_report",
+            &json!({ "removeSafe": true })
+        ));
+        assert!(read_only_allows("compose_message", &json!({})));
+        assert!(!read_only_allows(
+            "compose_message",
+            &json!({ "send": true })
+        ));
+        for method in [
+            "commit_git",
+            "terminal_snapshot",
+            "resume_thread",
+            "read_global_config_toml",
+            "memory_test_connection",
+            "a_future_method",
+        ] {
+            assert!(!read_only_allows(method, &json!({})), "{method}");
+        }
+    }
+
+    #[test]
+    fn read_only_settings_hide_secrets() {
+        let mut settings = json!({
+            "remoteBackendToken": "full-scope-token",
+            "exa_api_key": "exa-key",
+            "minimax_api_key": "",
+            "theme": "dark",
+        });
+        mask_read_only_result("get_app_settings", &mut settings);
+        assert_eq!(settings["remoteBackendToken"], json!("<redacted>"));
+        assert_eq!(settings["exa_api_key"], json!("<redacted>"));
+        assert_eq!(settings["minimax_api_key"], json!(""));
+        assert_eq!(settings["theme"], json!("dark"));
+
+        let mut other = json!({ "remoteBackendToken": "kept" });
+        mask_read_only_result("list_workspaces", &mut other);
+        assert_eq!(other["remoteBackendToken"], json!("kept"));
+    }
+
+    #[tokio::test]
+    async fn tracks_connections_and_activity() {
+        let registry = ClientRegistry::default();
+        let (first, _) = registry.connect(None);
+        let (second, notify) = registry.connect(None);
+        registry.update(&second, |client| {
+            client.client.name = Some("laptop".to_string());
+        });
+        assert_eq!(registry.list().len(), 2);
+        assert!(registry.disconnect(&second));
+        assert!(!registry.disconnect("conn-99"));
+        // The permit is stored until the connection loop waits on it.
+        tokio::time::timeout(Duration::from_millis(50), notify.notified())
+            .await
+            .expect("disconnect notified");
+        registry.disconnected(&first);
+        assert_eq!(registry.list()[0].connection_id, second);
+
+        for method in ["commit_git", "push_git"] {
+            registry.record_activity(
+                "ws-1",
+                ClientActivity {
+                    at: 0,
+                    method: method.to_string(),
+                    connection_id: second.clone(),
+                    client: registry.client_info(&second),
+                    ok: true,
+                    workspace: None,
+                    reason: None,
+                },
+            );
+        }
+        let feed = registry.activity("ws-1", 1);
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].method, "push_git");
+        assert_eq!(feed[0].client.name.as_deref(), Some("laptop"));
+        assert!(registry.activity("ws-2", 10).is_empty());
+    }
+}
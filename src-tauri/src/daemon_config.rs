@@ -0,0 +1,313 @@
+//! Daemon settings that can change while it runs. They are read from
+//! `daemon.json` in the data dir, under whatever the command line pinned, and
+//! re-read by `daemon_reload_config` or SIGHUP. A reload either applies a fully
+//! validated config or leaves the running one untouched.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+pub(crate) const DAEMON_CONFIG_FILE: &str = "daemon.json";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct DaemonConfigFile {
+    /// Only read at startup.
+    listen: Option<String>,
+    token: Option<String>,
+    unauth_health: Option<bool>,
+    chunk_threshold: Option<usize>,
+    allow_disable_redaction: Option<bool>,
+}
+
+/// What was given on the command line. These win over the file, so a reload
+/// never changes them.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ConfigOverrides {
+    pub(crate) listen: Option<SocketAddr>,
+    pub(crate) token: Option<String>,
+    /// `CODEX_MONITOR_DAEMON_TOKEN`; used when neither `--token` nor the file
+    /// sets one.
+    pub(crate) env_token: Option<String>,
+    pub(crate) insecure_no_auth: bool,
+    pub(crate) unauth_health: Option<bool>,
+    pub(crate) chunk_threshold: Option<usize>,
+    pub(crate) allow_disable_redaction: Option<bool>,
+    /// `--tokens-file`; re-read on every reload.
+    pub(crate) tokens_file: Option<PathBuf>,
+}
+
+/// What a client authenticated with a token may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum TokenScope {
+    Full,
+    /// Only the methods `daemon_clients::read_only_allows` lets through.
+    ReadOnly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RuntimeConfig {
+    /// The shared token, always full scope.
+    pub(crate) token: Option<String>,
+    /// Extra tokens from `--tokens-file`.
+    pub(crate) tokens: BTreeMap<String, TokenScope>,
+    pub(crate) unauth_health: bool,
+    pub(crate) chunk_threshold: usize,
+    pub(crate) allow_disable_redaction: bool,
+}
+
+impl RuntimeConfig {
+    /// Whether clients must send `auth` first.
+    pub(crate) fn requires_auth(&self) -> bool {
+        self.token.is_some() || !self.tokens.is_empty()
+    }
+
+    /// The scope `provided` grants, or `None` when it is no known token. The
+    /// shared token is full scope even if the tokens file lists it too.
+    pub(crate) fn scope_for(&self, provided: &str) -> Option<TokenScope> {
+        if self.token.as_deref() == Some(provided) {
+            return Some(TokenScope::Full);
+        }
+        self.tokens.get(provided).copied()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigReloadReport {
+    pub(crate) path: String,
+    /// Settings whose new value is now in effect.
+    pub(crate) applied: Vec<String>,
+    pub(crate) unchanged: Vec<String>,
+    /// Settings that differ from the running value but only take effect after
+    /// a restart.
+    pub(crate) requires_restart: Vec<String>,
+}
+
+/// A missing file is an empty config; anything unreadable or unparseable is
+/// an error.
+pub(crate) fn read_config_file(path: &Path) -> Result<DaemonConfigFile, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(DaemonConfigFile::default())
+        }
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+    serde_json::from_str(&contents).map_err(|err| format!("Invalid {}: {err}", path.display()))
+}
+
+/// Reads a tokens file: a JSON object mapping each token to `"full"` or
+/// `"read-only"`.
+pub(crate) fn read_tokens_file(path: &Path) -> Result<BTreeMap<String, TokenScope>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let tokens: BTreeMap<String, TokenScope> = serde_json::from_str(&contents)
+        .map_err(|err| format!("Invalid {}: {err}", path.display()))?;
+    if tokens.keys().any(|token| token.trim().is_empty()) {
+        return Err(format!(
+            "Invalid {}: tokens must not be empty",
+            path.display()
+        ));
+    }
+    Ok(tokens)
+}
+
+/// The listen address to bind, or `None` to use the default.
+pub(crate) fn resolve_listen(
+    file: &DaemonConfigFile,
+    overrides: &ConfigOverrides,
+) -> Result<Option<SocketAddr>, String> {
+    if overrides.listen.is_some() {
+        return Ok(overrides.listen);
+    }
+    file.listen
+        .as_deref()
+        .map(|value| {
+            value
+                .trim()
+                .parse::<SocketAddr>()
+                .map_err(|err| format!("Invalid listen address {value:?}: {err}"))
+        })
+        .transpose()
+}
+
+pub(crate) fn resolve_runtime(
+    file: &DaemonConfigFile,
+    overrides: &ConfigOverrides,
+    default_chunk_threshold: usize,
+) -> Result<RuntimeConfig, String> {
+    let file_token = match file.token.as_deref().map(str::trim) {
+        Some("") => return Err("token must not be empty".to_string()),
+        other => other.map(str::to_string),
+    };
+    let (token, tokens) = if overrides.insecure_no_auth {
+        (None, BTreeMap::new())
+    } else {
+        let token = overrides
+            .token
+            .clone()
+            .or(file_token)
+            .or_else(|| overrides.env_token.clone());
+        let tokens = match overrides.tokens_file.as_deref() {
+            Some(path) => read_tokens_file(path)?,
+            None => BTreeMap::new(),
+        };
+        (token, tokens)
+    };
+    if token.is_none() && tokens.is_empty() && !overrides.insecure_no_auth {
+        return Err(
+            "Missing --token or --tokens-file (or set CODEX_MONITOR_DAEMON_TOKEN). Use --insecure-no-auth for local dev only."
+                .to_string(),
+        );
+    }
+    if file.chunk_threshold == Some(0) {
+        return Err("chunkThreshold must be a positive number of bytes".to_string());
+    }
+    Ok(RuntimeConfig {
+        token,
+        tokens,
+        unauth_health: overrides
+            .unauth_health
+            .or(file.unauth_health)
+            .unwrap_or(true),
+        chunk_threshold: overrides
+            .chunk_threshold
+            .or(file.chunk_threshold)
+            .unwrap_or(default_chunk_threshold),
+        allow_disable_redaction: overrides
+            .allow_disable_redaction
+            .or(file.allow_disable_redaction)
+            .unwrap_or(false),
+    })
+}
+
+/// Compares a freshly resolved config against the running one.
+pub(crate) fn reload_report(
+    path: &Path,
+    current: &RuntimeConfig,
+    next: &RuntimeConfig,
+    bound_listen: SocketAddr,
+    next_listen: Option<SocketAddr>,
+) -> ConfigReloadReport {
+    let mut report = ConfigReloadReport {
+        path: path.display().to_string(),
+        applied: Vec::new(),
+        unchanged: Vec::new(),
+        requires_restart: Vec::new(),
+    };
+    for (name, changed) in [
+        ("token", current.token != next.token),
+        ("tokens", current.tokens != next.tokens),
+        ("unauthHealth", current.unauth_health != next.unauth_health),
+        (
+            "chunkThreshold",
+            current.chunk_threshold != next.chunk_threshold,
+        ),
+        (
+            "allowDisableRedaction",
+            current.allow_disable_redaction != next.allow_disable_redaction,
+        ),
+    ] {
+        if changed {
+            report.applied.push(name.to_string());
+        } else {
+            report.unchanged.push(name.to_string());
+        }
+    }
+    if next_listen.is_some_and(|listen| listen != bound_listen) {
+        report.requires_restart.push("listen".to_string());
+    } else {
+        report.unchanged.push("listen".to_string());
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        read_config_file, reload_report, resolve_listen, resolve_runtime, ConfigOverrides,
+        TokenScope,
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn file_settings_sit_under_the_command_line_and_bad_files_are_rejected() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("daemon.json");
+        let overrides = ConfigOverrides {
+            env_token: Some("env".to_string()),
+            chunk_threshold: Some(4096),
+            ..ConfigOverrides::default()
+        };
+
+        let empty = read_config_file(&path).expect("missing file");
+        let current = resolve_runtime(&empty, &overrides, 1024).expect("defaults");
+        assert_eq!(current.token.as_deref(), Some("env"));
+        assert_eq!(current.chunk_threshold, 4096);
+        assert!(current.unauth_health);
+
+        std::fs::write(
+            &path,
+            r#"{"token":"rotated","chunkThreshold":10,"unauthHealth":false,"listen":"127.0.0.1:9999"}"#,
+        )
+        .unwrap();
+        let file = read_config_file(&path).expect("file");
+        let next = resolve_runtime(&file, &overrides, 1024).expect("resolve");
+        assert_eq!(next.token.as_deref(), Some("rotated"));
+        assert_eq!(next.chunk_threshold, 4096);
+        let listen = resolve_listen(&file, &overrides).expect("listen");
+        let report = reload_report(
+            &path,
+            &current,
+            &next,
+            "127.0.0.1:4732".parse().unwrap(),
+            listen,
+        );
+        assert_eq!(report.applied, vec!["token", "unauthHealth"]);
+        assert_eq!(report.requires_restart, vec!["listen"]);
+
+        std::fs::write(&path, r#"{"token":"half"#).unwrap();
+        assert!(read_config_file(&path).is_err());
+        std::fs::write(&path, r#"{"token":"  "}"#).unwrap();
+        let blank = read_config_file(&path).expect("blank token parses");
+        assert!(resolve_runtime(&blank, &overrides, 1024).is_err());
+        std::fs::write(&path, r#"{"tokn":"typo"}"#).unwrap();
+        assert!(read_config_file(&path).is_err());
+    }
+    #[test]
+    fn tokens_file_adds_scoped_tokens_next_to_the_shared_one() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("tokens.json");
+        std::fs::write(
+            &path,
+            r#"{"tablet":"read-only","laptop":"full","shared":"read-only"}"#,
+        )
+        .unwrap();
+        let mut overrides = ConfigOverrides {
+            token: Some("shared".to_string()),
+            tokens_file: Some(path.clone()),
+            ..ConfigOverrides::default()
+        };
+        let empty = read_config_file(&dir.path().join("daemon.json")).expect("missing file");
+        let runtime = resolve_runtime(&empty, &overrides, 1024).expect("resolve");
+        assert_eq!(runtime.scope_for("tablet"), Some(TokenScope::ReadOnly));
+        assert_eq!(runtime.scope_for("laptop"), Some(TokenScope::Full));
+        assert_eq!(runtime.scope_for("shared"), Some(TokenScope::Full));
+        assert_eq!(runtime.scope_for("other"), None);
+
+        // A tokens file alone is enough to require auth.
+        overrides.token = None;
+        let runtime = resolve_runtime(&empty, &overrides, 1024).expect("file only");
+        assert!(runtime.requires_auth());
+        assert_eq!(runtime.scope_for("shared"), Some(TokenScope::ReadOnly));
+
+        std::fs::write(&path, r#"{"tablet":"admin"}"#).unwrap();
+        assert!(resolve_runtime(&empty, &overrides, 1024).is_err());
+        std::fs::write(&path, r#"{" ":"full"}"#).unwrap();
+        assert!(resolve_runtime(&empty, &overrides, 1024).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::attachments::ATTACHMENTS_DIR;
+use crate::revert_undo::REVERT_UNDO_DIR;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct DataUsageCategory {
+    pub(crate) id: String,
+    pub(crate) files: u64,
+    pub(crate) bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct DataUsage {
+    pub(crate) categories: Vec<DataUsageCategory>,
+    #[serde(rename = "totalBytes")]
+    pub(crate) total_bytes: u64,
+}
+
+/// `(files, bytes)` under `path`, recursively. Symlinks are not followed.
+fn dir_usage(path: &Path) -> (u64, u64) {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return (0, 0);
+    };
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.path().symlink_metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            let (nested_files, nested_bytes) = dir_usage(&entry.path());
+            files += nested_files;
+            bytes += nested_bytes;
+        } else {
+            files += 1;
+            bytes += metadata.len();
+        }
+    }
+    (files, bytes)
+}
+
+/// Disk used by the backend's own storage under the data dir, per category.
+pub(crate) fn collect_data_usage(data_dir: &Path) -> DataUsage {
+    let categories: Vec<DataUsageCategory> = [
+        ("attachments", ATTACHMENTS_DIR),
+        ("revertUndo", REVERT_UNDO_DIR),
+    ]
+    .into_iter()
+    .map(|(id, dir)| {
+        let (files, bytes) = dir_usage(&data_dir.join(dir));
+        DataUsageCategory {
+            id: id.to_string(),
+            files,
+            bytes,
+        }
+    })
+    .collect();
+    let total_bytes = categories.iter().map(|category| category.bytes).sum();
+    DataUsage {
+        categories,
+        total_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_data_usage;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reports_each_category() {
+        let data = tempdir().expect("tempdir");
+        let attachments = data.path().join("attachments").join("ws");
+        std::fs::create_dir_all(&attachments).unwrap();
+        std::fs::write(attachments.join("att_1__a.png"), [0u8; 10]).unwrap();
+        let undo = data
+            .path()
+            .join("revert-undo")
+            .join("ws")
+            .join("1")
+            .join("files");
+        std::fs::create_dir_all(&undo).unwrap();
+        std::fs::write(undo.join("b.txt"), "hello").unwrap();
+
+        let usage = collect_data_usage(data.path());
+        let summary: Vec<_> = usage
+            .categories
+            .iter()
+            .map(|category| (category.id.as_str(), category.files, category.bytes))
+            .collect();
+        assert_eq!(summary, vec![("attachments", 1, 10), ("revertUndo", 1, 5)]);
+        assert_eq!(usage.total_bytes, 15);
+    }
+}
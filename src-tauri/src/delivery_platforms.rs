@@ -0,0 +1,149 @@
+//! Delivery platforms and tips for the delivery trend snapshot and dashboard.
+//! Sessions carry an optional `platform`, `tips` and `base_pay` in their
+//! frontmatter; sessions without them only count towards the overall totals.
+
+/// Bucket for platform strings that match none of `PLATFORMS`.
+pub(crate) const OTHER_PLATFORM: &str = "Other";
+
+/// Display name and the spellings it is recognized by, lowercased with
+/// spaces, dashes, underscores and dots removed.
+const PLATFORMS: &[(&str, &[&str])] = &[
+    ("DoorDash", &["doordash", "dd", "dasher"]),
+    ("Uber Eats", &["ubereats", "uber", "ue"]),
+    ("Grubhub", &["grubhub", "gh"]),
+    ("Instacart", &["instacart", "ic"]),
+];
+
+/// Maps a free-form platform string to its display name, or `Other`.
+pub(crate) fn normalize_platform(raw: &str) -> &'static str {
+    let key: String = raw
+        .chars()
+        .filter(|ch| !matches!(ch, ' ' | '-' | '_' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    PLATFORMS
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&key.as_str()))
+        .map(|(name, _)| *name)
+        .unwrap_or(OTHER_PLATFORM)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PlatformTotals {
+    pub(crate) platform: &'static str,
+    pub(crate) earnings: f64,
+    pub(crate) hours: f64,
+    pub(crate) orders: f64,
+    pub(crate) sessions: u32,
+}
+
+impl PlatformTotals {
+    pub(crate) fn hourly(&self) -> f64 {
+        if self.hours > 0.0 {
+            self.earnings / self.hours
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Per-platform totals of the sessions that name a platform.
+#[derive(Debug, Default)]
+pub(crate) struct PlatformBreakdown {
+    totals: Vec<PlatformTotals>,
+}
+
+impl PlatformBreakdown {
+    pub(crate) fn add(&mut self, platform: Option<&str>, earnings: f64, hours: f64, orders: f64) {
+        let Some(platform) = platform.map(str::trim).filter(|value| !value.is_empty()) else {
+            return;
+        };
+        let platform = normalize_platform(platform);
+        let index = match self
+            .totals
+            .iter()
+            .position(|totals| totals.platform == platform)
+        {
+            Some(index) => index,
+            None => {
+                self.totals.push(PlatformTotals {
+                    platform,
+                    earnings: 0.0,
+                    hours: 0.0,
+                    orders: 0.0,
+                    sessions: 0,
+                });
+                self.totals.len() - 1
+            }
+        };
+        let totals = &mut self.totals[index];
+        totals.earnings += earnings;
+        totals.hours += hours;
+        totals.orders += orders;
+        totals.sessions += 1;
+    }
+
+    /// Highest earnings first; ties keep the order platforms were first seen.
+    pub(crate) fn into_sorted(mut self) -> Vec<PlatformTotals> {
+        self.totals
+            .sort_by(|a, b| b.earnings.total_cmp(&a.earnings));
+        self.totals
+    }
+}
+
+/// Tips and base pay of the sessions that record either. A missing half is
+/// taken to be the rest of the session's earnings.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct TipSplit {
+    pub(crate) tips: f64,
+    pub(crate) base_pay: f64,
+    pub(crate) sessions: u32,
+}
+
+impl TipSplit {
+    pub(crate) fn add(&mut self, earnings: f64, tips: Option<f64>, base_pay: Option<f64>) {
+        let (tips, base_pay) = match (tips, base_pay) {
+            (None, None) => return,
+            (Some(tips), Some(base_pay)) => (tips, base_pay),
+            (Some(tips), None) => (tips, (earnings - tips).max(0.0)),
+            (None, Some(base_pay)) => ((earnings - base_pay).max(0.0), base_pay),
+        };
+        self.tips += tips;
+        self.base_pay += base_pay;
+        self.sessions += 1;
+    }
+
+    /// Tips as a percentage of tips plus base pay, once any session has them.
+    pub(crate) fn tips_percent(&self) -> Option<f64> {
+        let total = self.tips + self.base_pay;
+        (self.sessions > 0 && total > 0.0).then(|| self.tips / total * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_platform, TipSplit, OTHER_PLATFORM};
+
+    #[test]
+    fn normalizes_platform_spellings() {
+        assert_eq!(normalize_platform("DoorDash"), "DoorDash");
+        assert_eq!(normalize_platform("door-dash"), "DoorDash");
+        assert_eq!(normalize_platform("UberEats"), "Uber Eats");
+        assert_eq!(normalize_platform("Uber Eats"), "Uber Eats");
+        assert_eq!(normalize_platform("Roadie"), OTHER_PLATFORM);
+    }
+
+    #[test]
+    fn tip_split_fills_in_the_missing_half() {
+        let mut split = TipSplit::default();
+        assert_eq!(split.tips_percent(), None);
+        split.add(100.0, None, None);
+        split.add(100.0, Some(30.0), None);
+        split.add(50.0, None, Some(40.0));
+        assert_eq!(split.sessions, 2);
+        assert_eq!(split.tips, 40.0);
+        assert_eq!(split.base_pay, 110.0);
+        let percent = split.tips_percent().expect("percent");
+        assert!((percent - 26.666).abs() < 0.01);
+    }
+}
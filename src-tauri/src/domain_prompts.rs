@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::SystemTime;
+
+use crate::types::Domain;
+
+pub(crate) const DOMAIN_INCLUDES_DIR: &str = "domain-includes";
+pub(crate) const MAX_EXPANDED_PROMPT_CHARS: usize = 64_000;
+const INCLUDE_DIRECTIVE: &str = "@include(";
+
+struct CachedInclude {
+    modified: Option<SystemTime>,
+    content: String,
+}
+
+static INCLUDE_CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedInclude>>> = OnceLock::new();
+
+fn include_cache() -> MutexGuard<'static, HashMap<PathBuf, CachedInclude>> {
+    let cache = INCLUDE_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    cache.lock().unwrap_or_else(|err| {
+        let mut guard = err.into_inner();
+        guard.clear();
+        cache.clear_poison();
+        guard
+    })
+}
+
+/// Includes live next to `domains.json`, under `domain-includes/`.
+pub(crate) fn domain_includes_dir(domains_path: &Path) -> PathBuf {
+    domains_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(DOMAIN_INCLUDES_DIR)
+}
+
+/// Include targets referenced by `@include(...)` directives, in order.
+pub(crate) fn include_references(prompt: &str) -> Vec<String> {
+    let mut references = Vec::new();
+    let mut rest = prompt;
+    while let Some(start) = rest.find(INCLUDE_DIRECTIVE) {
+        let after = &rest[start + INCLUDE_DIRECTIVE.len()..];
+        let Some(end) = after.find(')') else {
+            break;
+        };
+        references.push(after[..end].trim().to_string());
+        rest = &after[end + 1..];
+    }
+    references
+}
+
+fn resolve_include(includes_dir: &Path, reference: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(reference);
+    if reference.is_empty()
+        || relative.is_absolute()
+        || relative
+            .components()
+            .any(|component| matches!(component, std::path::Component::ParentDir))
+    {
+        return Err(format!("invalid include path `{reference}`"));
+    }
+    let path = includes_dir.join(relative);
+    if !path.is_file() {
+        return Err(format!("include not found: `{reference}`"));
+    }
+    Ok(path)
+}
+
+fn read_include(path: &Path) -> Result<String, String> {
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if let Some(entry) = include_cache().get(path) {
+        if entry.modified.is_some() && entry.modified == modified {
+            return Ok(entry.content.clone());
+        }
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read include `{}`: {err}", path.display()))?;
+    include_cache().insert(
+        path.to_path_buf(),
+        CachedInclude {
+            modified,
+            content: content.clone(),
+        },
+    );
+    Ok(content)
+}
+
+fn expand(
+    text: &str,
+    includes_dir: &Path,
+    stack: &mut Vec<String>,
+    max_chars: usize,
+) -> Result<String, String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(INCLUDE_DIRECTIVE) {
+        let after = &rest[start + INCLUDE_DIRECTIVE.len()..];
+        let Some(end) = after.find(')') else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let reference = after[..end].trim();
+        if stack.iter().any(|entry| entry == reference) {
+            let mut chain = stack.clone();
+            chain.push(reference.to_string());
+            return Err(format!("include cycle: {}", chain.join(" -> ")));
+        }
+        let path = resolve_include(includes_dir, reference)?;
+        let content = read_include(&path)?;
+        stack.push(reference.to_string());
+        let expanded = expand(&content, includes_dir, stack, max_chars)?;
+        stack.pop();
+        output.push_str(expanded.trim_end_matches('\n'));
+        if output.chars().count() > max_chars {
+            return Err(format!("expands past {max_chars} characters"));
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Resolves `@include(...)` directives in a domain's system prompt. Errors name
+/// the domain so a failing send points at the prompt to fix.
+pub(crate) fn expand_domain_prompt(
+    domain: &Domain,
+    includes_dir: &Path,
+    max_chars: usize,
+) -> Result<String, String> {
+    let expanded = expand(
+        &domain.system_prompt,
+        includes_dir,
+        &mut Vec::new(),
+        max_chars,
+    )
+    .map_err(|err| format!("Domain \"{}\" system prompt: {err}", domain.name))?;
+    let length = expanded.chars().count();
+    if length > max_chars {
+        return Err(format!(
+            "Domain \"{}\" system prompt is {length} characters after includes (max {max_chars})",
+            domain.name
+        ));
+    }
+    Ok(expanded)
+}
+
+/// Save-time check: lists includes that cannot be resolved, without failing.
+pub(crate) fn domain_prompt_warnings(domain: &Domain, includes_dir: &Path) -> Vec<String> {
+    include_references(&domain.system_prompt)
+        .iter()
+        .filter_map(|reference| resolve_include(includes_dir, reference).err())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{domain_prompt_warnings, expand_domain_prompt, include_references};
+    use crate::types::Domain;
+    use tempfile::tempdir;
+
+    fn domain(prompt: &str) -> Domain {
+        serde_json::from_value(serde_json::json!({
+            "id": "d1",
+            "name": "Writing",
+            "systemPrompt": prompt,
+        }))
+        .expect("domain deserializes")
+    }
+
+    #[test]
+    fn parses_include_references() {
+        assert_eq!(
+            include_references("a @include(common.md) b @include( style/tone.md )"),
+            vec!["common.md".to_string(), "style/tone.md".to_string()]
+        );
+    }
+
+    #[test]
+    fn expands_nested_includes() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("style")).unwrap();
+        std::fs::write(
+            dir.path().join("common.md"),
+            "Be concise.\n@include(style/tone.md)\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("style/tone.md"), "Be friendly.\n").unwrap();
+
+        let expanded = expand_domain_prompt(
+            &domain("Intro\n@include(common.md)\nOutro"),
+            dir.path(),
+            1_000,
+        )
+        .expect("expands");
+        assert_eq!(expanded, "Intro\nBe concise.\nBe friendly.\nOutro");
+    }
+
+    #[test]
+    fn rejects_include_cycles() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.md"), "@include(b.md)").unwrap();
+        std::fs::write(dir.path().join("b.md"), "@include(a.md)").unwrap();
+
+        let err = expand_domain_prompt(&domain("@include(a.md)"), dir.path(), 1_000).unwrap_err();
+        assert!(err.contains("Writing"), "{err}");
+        assert!(err.contains("a.md -> b.md -> a.md"), "{err}");
+    }
+
+    #[test]
+    fn enforces_max_length_and_reports_missing_includes() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("long.md"), "x".repeat(50)).unwrap();
+        let err = expand_domain_prompt(&domain("@include(long.md)"), dir.path(), 20).unwrap_err();
+        assert!(err.starts_with("Domain \"Writing\""), "{err}");
+
+        let warnings =
+            domain_prompt_warnings(&domain("@include(long.md) @include(gone.md)"), dir.path());
+        assert_eq!(warnings, vec!["include not found: `gone.md`".to_string()]);
+    }
+}
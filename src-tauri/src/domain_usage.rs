@@ -0,0 +1,185 @@
+//! Workspaces that point at a domain through `settings.domain_id`. Deleting a
+//! domain they use would leave the id dangling and quietly turn off their
+//! domain instructions, so `domains_delete` refuses unless the caller says
+//! where those workspaces go.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::types::{Domain, WorkspaceEntry};
+
+/// Error prefix when workspaces still use the domain being deleted. The rest
+/// of the message is JSON: `{ "domainId", "workspaceIds" }`.
+pub(crate) const DOMAIN_IN_USE_ERROR: &str = "domain_in_use";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct DomainUsageWorkspace {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DomainUsage {
+    pub(crate) domain_id: String,
+    /// Sorted by name.
+    pub(crate) workspaces: Vec<DomainUsageWorkspace>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DomainDeleteResult {
+    /// Workspaces that were moved to `reassignTo` or had their domain cleared.
+    pub(crate) updated_workspace_ids: Vec<String>,
+}
+
+pub(crate) fn domain_usage(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    domain_id: &str,
+) -> DomainUsage {
+    let mut using: Vec<DomainUsageWorkspace> = workspaces
+        .values()
+        .filter(|entry| entry.settings.domain_id.as_deref() == Some(domain_id))
+        .map(|entry| DomainUsageWorkspace {
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            path: entry.path.clone(),
+        })
+        .collect();
+    using.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    DomainUsage {
+        domain_id: domain_id.to_string(),
+        workspaces: using,
+    }
+}
+
+/// Removes `domain_id` from `domains`. Workspaces using it move to
+/// `reassign_to`, or with `force` have their domain cleared; with neither the
+/// delete fails with `domain_in_use`. Nothing is changed when this returns an
+/// error, so callers can apply it to copies and persist those.
+pub(crate) fn delete_domain(
+    domains: &mut Vec<Domain>,
+    workspaces: &mut HashMap<String, WorkspaceEntry>,
+    domain_id: &str,
+    reassign_to: Option<&str>,
+    force: bool,
+) -> Result<DomainDeleteResult, String> {
+    if let Some(target) = reassign_to {
+        if target == domain_id {
+            return Err("Cannot reassign workspaces to the domain being deleted".to_string());
+        }
+        if !domains.iter().any(|domain| domain.id == target) {
+            return Err(format!("Domain not found: {target}"));
+        }
+    }
+    let usage = domain_usage(workspaces, domain_id);
+    if !usage.workspaces.is_empty() && reassign_to.is_none() && !force {
+        let workspace_ids: Vec<&str> = usage
+            .workspaces
+            .iter()
+            .map(|workspace| workspace.id.as_str())
+            .collect();
+        return Err(format!(
+            "{DOMAIN_IN_USE_ERROR}: {}",
+            json!({ "domainId": domain_id, "workspaceIds": workspace_ids })
+        ));
+    }
+    let mut result = DomainDeleteResult::default();
+    for workspace in usage.workspaces {
+        if let Some(entry) = workspaces.get_mut(&workspace.id) {
+            entry.settings.domain_id = reassign_to.map(str::to_string);
+            result.updated_workspace_ids.push(workspace.id);
+        }
+    }
+    domains.retain(|domain| domain.id != domain_id);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{delete_domain, domain_usage};
+    use crate::types::{Domain, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::collections::HashMap;
+
+    fn domain(id: &str) -> Domain {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "systemPrompt": "",
+            "viewType": "chat",
+        }))
+        .expect("domain")
+    }
+
+    fn workspace(id: &str, domain_id: Option<&str>) -> (String, WorkspaceEntry) {
+        let entry = WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/tmp/{id}"),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                domain_id: domain_id.map(str::to_string),
+                ..WorkspaceSettings::default()
+            },
+        };
+        (id.to_string(), entry)
+    }
+
+    #[test]
+    fn refuses_to_orphan_workspaces_and_reassigns_them() {
+        let mut domains = vec![domain("coding"), domain("writing")];
+        let mut workspaces: HashMap<String, WorkspaceEntry> = [
+            workspace("b", Some("coding")),
+            workspace("a", Some("coding")),
+            workspace("c", Some("writing")),
+        ]
+        .into_iter()
+        .collect();
+
+        let usage = domain_usage(&workspaces, "coding");
+        let ids: Vec<&str> = usage.workspaces.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let err = delete_domain(&mut domains, &mut workspaces, "coding", None, false)
+            .expect_err("in use");
+        assert_eq!(
+            err,
+            r#"domain_in_use: {"domainId":"coding","workspaceIds":["a","b"]}"#
+        );
+        assert_eq!(domains.len(), 2);
+        assert!(delete_domain(
+            &mut domains,
+            &mut workspaces,
+            "coding",
+            Some("missing"),
+            false
+        )
+        .is_err());
+
+        let result = delete_domain(
+            &mut domains,
+            &mut workspaces,
+            "coding",
+            Some("writing"),
+            false,
+        )
+        .expect("reassign");
+        assert_eq!(result.updated_workspace_ids, vec!["a", "b"]);
+        assert_eq!(domains.len(), 1);
+        assert_eq!(domain_usage(&workspaces, "writing").workspaces.len(), 3);
+
+        let result =
+            delete_domain(&mut domains, &mut workspaces, "writing", None, true).expect("force");
+        assert_eq!(result.updated_workspace_ids.len(), 3);
+        assert!(domains.is_empty());
+        assert!(workspaces
+            .values()
+            .all(|entry| entry.settings.domain_id.is_none()));
+    }
+}
@@ -1,11 +1,15 @@
 use serde_json::json;
 use tauri::{AppHandle, State};
 
-use crate::obsidian::compute_domain_trends;
+use crate::backend::blocking::run_blocking;
+use crate::client_timezone::ClientTimezone;
+use crate::domain_prompts::{domain_includes_dir, domain_prompt_warnings};
+use crate::domain_usage::{self, delete_domain, DomainDeleteResult, DomainUsage};
+use crate::obsidian::workspace_domain_trends;
 use crate::remote_backend;
 use crate::state::AppState;
-use crate::storage::write_domains;
-use crate::types::{Domain, DomainTrendSnapshot};
+use crate::storage::{write_domains, write_workspaces};
+use crate::types::{Domain, DomainSaveResult, DomainTrendSnapshot};
 
 fn normalize_domain(mut domain: Domain) -> Domain {
     if domain.view_type.trim().is_empty() {
@@ -14,6 +18,11 @@ fn normalize_domain(mut domain: Domain) -> Domain {
     domain
 }
 
+fn domain_save_result(state: &AppState, domain: Domain) -> DomainSaveResult {
+    let warnings = domain_prompt_warnings(&domain, &domain_includes_dir(&state.domains_path));
+    DomainSaveResult { domain, warnings }
+}
+
 #[tauri::command]
 pub(crate) async fn domains_list(
     state: State<'_, AppState>,
@@ -32,7 +41,7 @@ pub(crate) async fn domains_create(
     mut domain: Domain,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Domain, String> {
+) -> Result<DomainSaveResult, String> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -48,7 +57,7 @@ pub(crate) async fn domains_create(
     let mut domains = state.domains.lock().await;
     domains.push(domain.clone());
     write_domains(&state.domains_path, &domains)?;
-    Ok(domain)
+    Ok(domain_save_result(&state, domain))
 }
 
 #[tauri::command]
@@ -56,7 +65,7 @@ pub(crate) async fn domains_update(
     domain: Domain,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Domain, String> {
+) -> Result<DomainSaveResult, String> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -72,32 +81,76 @@ pub(crate) async fn domains_update(
     if let Some(idx) = domains.iter().position(|item| item.id == domain.id) {
         domains[idx] = domain.clone();
         write_domains(&state.domains_path, &domains)?;
-        Ok(domain)
+        Ok(domain_save_result(&state, domain))
     } else {
         Err(format!("Domain not found: {}", domain.id))
     }
 }
 
+/// Deletes the domain and moves its workspaces as `delete_domain` describes.
+/// Workspaces are written before domains, so a failed write never leaves a
+/// workspace pointing at a deleted domain.
 #[tauri::command]
 pub(crate) async fn domains_delete(
     domain_id: String,
+    reassign_to: Option<String>,
+    force: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> Result<DomainDeleteResult, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(
+        let response = remote_backend::call_remote(
             &*state,
             app,
             "domains_delete",
-            json!({ "domainId": domain_id }),
+            json!({
+                "domainId": domain_id,
+                "reassignTo": reassign_to,
+                "force": force,
+            }),
         )
         .await?;
-        return Ok(());
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
+    let mut workspaces = state.workspaces.lock().await;
     let mut domains = state.domains.lock().await;
-    domains.retain(|domain| domain.id != domain_id);
-    write_domains(&state.domains_path, &domains)?;
-    Ok(())
+    let mut next_workspaces = workspaces.clone();
+    let mut next_domains = domains.clone();
+    let result = delete_domain(
+        &mut next_domains,
+        &mut next_workspaces,
+        &domain_id,
+        reassign_to.as_deref(),
+        force.unwrap_or(false),
+    )?;
+    if !result.updated_workspace_ids.is_empty() {
+        let list: Vec<_> = next_workspaces.values().cloned().collect();
+        write_workspaces(&state.storage_path, &list)?;
+        *workspaces = next_workspaces;
+    }
+    write_domains(&state.domains_path, &next_domains)?;
+    *domains = next_domains;
+    Ok(result)
+}
+
+#[tauri::command]
+pub(crate) async fn domain_usage(
+    domain_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DomainUsage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "domain_usage",
+            json!({ "domainId": domain_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let workspaces = state.workspaces.lock().await;
+    Ok(domain_usage::domain_usage(&workspaces, &domain_id))
 }
 
 #[tauri::command]
@@ -105,6 +158,9 @@ pub(crate) async fn domain_trends(
     workspace_id: String,
     domain_id: String,
     range: String,
+    compare: Option<bool>,
+    force_refresh: Option<bool>,
+    timezone: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<DomainTrendSnapshot, String> {
@@ -116,18 +172,38 @@ pub(crate) async fn domain_trends(
             json!({
                 "workspaceId": workspace_id,
                 "domainId": domain_id,
-                "range": range
+                "range": range,
+                "compare": compare,
+                "forceRefresh": force_refresh,
+                "timezone": timezone,
             }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    let workspaces = state.workspaces.lock().await;
-    let workspace = workspaces
+    let workspace = state
+        .workspaces
+        .lock()
+        .await
         .get(&workspace_id)
+        .cloned()
         .ok_or_else(|| "workspace not found".to_string())?;
-    compute_domain_trends(&workspace.path, &domain_id, &range)
+    let today = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+        .await?
+        .today();
+    run_blocking(move || {
+        workspace_domain_trends(
+            &workspace,
+            &domain_id,
+            &range,
+            compare.unwrap_or(false),
+            force_refresh.unwrap_or(false),
+            today,
+        )
+    })
+    .await?
 }
 
 #[tauri::command]
@@ -3,8 +3,16 @@ use tauri::{AppHandle, Emitter, Manager};
 use crate::auto_flush::{
     build_snapshot, parse_memory_flush_result, run_memory_flush_summarizer, write_memory_flush,
 };
-use crate::backend::events::{AppServerEvent, EventSink, TerminalOutput};
+use crate::backend::events::{
+    AppServerEvent, BackendError, EventSink, QuickCommandOutput, TerminalExited, TerminalOutput,
+};
+use crate::life_transcripts::{is_transcript_event, transcript_vault};
+use crate::notification_rules::{
+    evaluate_rules, notification_event_kind, thread_title_from_message, FiredNotification,
+    NotificationContext,
+};
 use crate::state::AppState;
+use crate::thread_items::normalize_app_server_event;
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
@@ -19,9 +27,12 @@ impl TauriEventSink {
 
 impl EventSink for TauriEventSink {
     fn emit_app_server_event(&self, event: AppServerEvent) {
+        let event = normalize_app_server_event(event);
         let _ = self.app.emit("app-server-event", event.clone());
         let app = self.app.clone();
         tauri::async_runtime::spawn(async move {
+            dispatch_notification_rules(&app, &event).await;
+            archive_life_transcript(&app, &event).await;
             maybe_trigger_auto_memory(app, event).await;
         });
     }
@@ -29,6 +40,118 @@ impl EventSink for TauriEventSink {
     fn emit_terminal_output(&self, event: TerminalOutput) {
         let _ = self.app.emit("terminal-output", event);
     }
+
+    fn emit_terminal_exited(&self, event: TerminalExited) {
+        let _ = self.app.emit("terminal-exit", event);
+    }
+
+    fn emit_quick_command_output(&self, event: QuickCommandOutput) {
+        let _ = self.app.emit("quick-command-output", event);
+    }
+
+    fn emit_backend_error(&self, event: BackendError) {
+        let _ = self.app.emit("backend-error", event);
+    }
+}
+
+/// Runs the user's notification rules for an app-server event. System
+/// notifications are shown from here; sounds are played by the frontend on
+/// `notification-rule-fired`.
+pub(crate) async fn dispatch_notification_rules(app: &AppHandle, event: &AppServerEvent) {
+    let Some(kind) = notification_event_kind(&event.message) else {
+        return;
+    };
+    let state = app.state::<AppState>();
+    let rules = state.app_settings.lock().await.notification_rules.clone();
+    if rules.is_empty() {
+        return;
+    }
+    let (workspace_name, workspace_color, workspace_icon) = state
+        .workspaces
+        .lock()
+        .await
+        .get(&event.workspace_id)
+        .map(|entry| {
+            (
+                entry.name.clone(),
+                entry.settings.color.clone(),
+                entry.settings.icon.clone(),
+            )
+        })
+        .unwrap_or_else(|| (event.workspace_id.clone(), None, None));
+    let context = NotificationContext {
+        workspace_id: event.workspace_id.clone(),
+        workspace_name,
+        workspace_color,
+        workspace_icon,
+        thread_title: thread_title_from_message(&event.message),
+        event: kind.to_string(),
+    };
+    for fired in evaluate_rules(&rules, &context) {
+        deliver_notification(app, &fired);
+    }
+}
+
+/// Feeds turn events to the Life transcript archive.
+async fn archive_life_transcript(app: &AppHandle, event: &AppServerEvent) {
+    if !is_transcript_event(&event.message) {
+        return;
+    }
+    let state = app.state::<AppState>();
+    let vault = state
+        .workspaces
+        .lock()
+        .await
+        .get(&event.workspace_id)
+        .and_then(transcript_vault);
+    state
+        .life_transcripts
+        .observe(&event.workspace_id, vault.as_deref(), &event.message);
+}
+
+pub(crate) fn deliver_notification(app: &AppHandle, fired: &FiredNotification) {
+    if let Some(title) = fired.title.as_deref() {
+        if let Err(err) = show_system_notification(title, fired.body.as_deref().unwrap_or("")) {
+            eprintln!(
+                "Failed to show notification for rule {}: {err}",
+                fired.rule_id
+            );
+        }
+    }
+    let _ = app.emit("notification-rule-fired", fired.clone());
+}
+
+fn show_system_notification(title: &str, body: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let quote = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            quote(body),
+            quote(title)
+        );
+        std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .spawn()
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("notify-send")
+            .arg("--app-name=CodexMonitor")
+            .arg(title)
+            .arg(body)
+            .spawn()
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (title, body);
+        Err("system notifications are not supported on this platform".to_string())
+    }
 }
 
 async fn maybe_trigger_auto_memory(app: AppHandle, event: AppServerEvent) {
@@ -0,0 +1,259 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::file_tail::resolve_workspace_file;
+use crate::redaction::Redactor;
+
+pub(crate) const MAX_FILE_REF_BYTES: usize = 64 * 1024;
+pub(crate) const MAX_FILE_REFS_TOTAL_BYTES: usize = 256 * 1024;
+const MAX_FILE_REFS: usize = 20;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct AttachedFileRef {
+    pub(crate) path: String,
+    pub(crate) bytes: usize,
+    pub(crate) truncated: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct FileRefError {
+    pub(crate) path: String,
+    pub(crate) error: String,
+}
+
+/// Reported back on `send_user_message` as `fileContext` so the UI can warn
+/// when a message is close to the budget.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub(crate) struct FileContext {
+    #[serde(skip)]
+    pub(crate) block: String,
+    #[serde(rename = "attachedBytes")]
+    pub(crate) attached_bytes: usize,
+    #[serde(rename = "budgetBytes")]
+    pub(crate) budget_bytes: usize,
+    pub(crate) files: Vec<AttachedFileRef>,
+    pub(crate) errors: Vec<FileRefError>,
+    /// Secrets and sensitive files replaced before the block was built.
+    pub(crate) redactions: usize,
+}
+
+/// Reads a referenced file with the same containment rule as
+/// `read_workspace_file`, returning at most `limit` bytes and the full size.
+pub(crate) fn read_file_ref(
+    root: &Path,
+    relative_path: &str,
+    limit: usize,
+) -> Result<(String, u64), String> {
+    let path = resolve_workspace_file(root, relative_path)?;
+    let file = File::open(&path).map_err(|err| format!("Failed to open file: {err}"))?;
+    let size = file
+        .metadata()
+        .map_err(|err| format!("Failed to read file metadata: {err}"))?
+        .len();
+    let mut buffer = Vec::new();
+    file.take(limit as u64)
+        .read_to_end(&mut buffer)
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+    if buffer.contains(&0) {
+        return Err("File appears to be binary".to_string());
+    }
+    let text = match String::from_utf8(buffer) {
+        Ok(text) => text,
+        // The cap may split a multi-byte character; anything else is binary.
+        Err(err) if err.utf8_error().error_len().is_none() => {
+            let valid = err.utf8_error().valid_up_to();
+            let mut bytes = err.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).map_err(|_| "File is not valid UTF-8".to_string())?
+        }
+        Err(_) => return Err("File is not valid UTF-8".to_string()),
+    };
+    Ok((text, size))
+}
+
+/// A fence longer than any backtick run in the content, so it cannot close early.
+pub(crate) fn fence_for(content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in content.chars() {
+        if ch == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+pub(crate) fn format_file_block(
+    path: &str,
+    content: &str,
+    read: usize,
+    size: u64,
+    truncated: bool,
+) -> String {
+    let fence = fence_for(content);
+    let language = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    let mut block = format!("File: {path}\n{fence}{language}\n{content}");
+    if !content.ends_with('\n') {
+        block.push('\n');
+    }
+    block.push_str(&fence);
+    block.push('\n');
+    if truncated {
+        block.push_str(&format!(
+            "(truncated: showing the first {read} of {size} bytes)\n"
+        ));
+    }
+    block
+}
+
+/// Builds the context block for `fileRefs`. Without `best_effort`, the first
+/// unreadable file fails the whole send; with it, failures are reported per
+/// file and the rest are attached. Contents go through `redactor` when given.
+pub(crate) fn build_file_context(
+    root: &Path,
+    file_refs: &[String],
+    best_effort: bool,
+    redactor: Option<&Redactor>,
+) -> Result<FileContext, String> {
+    if file_refs.len() > MAX_FILE_REFS {
+        return Err(format!("Too many file references (max {MAX_FILE_REFS})"));
+    }
+    let mut context = FileContext {
+        budget_bytes: MAX_FILE_REFS_TOTAL_BYTES,
+        ..FileContext::default()
+    };
+    let mut blocks = Vec::new();
+    for relative_path in file_refs {
+        let remaining = MAX_FILE_REFS_TOTAL_BYTES.saturating_sub(context.attached_bytes);
+        let result = if remaining == 0 {
+            Err("Skipped: total file context budget exhausted".to_string())
+        } else {
+            read_file_ref(root, relative_path, remaining.min(MAX_FILE_REF_BYTES))
+        };
+        match result {
+            Ok((content, size)) => {
+                let read = content.len();
+                let truncated = (read as u64) < size;
+                let content = match redactor {
+                    Some(redactor) => {
+                        let (redacted, count) = redactor.redact_file(relative_path, &content);
+                        context.redactions += count;
+                        redacted
+                    }
+                    None => content,
+                };
+                context.attached_bytes += read;
+                context.files.push(AttachedFileRef {
+                    path: relative_path.clone(),
+                    bytes: read,
+                    truncated,
+                });
+                blocks.push(format_file_block(
+                    relative_path,
+                    &content,
+                    read,
+                    size,
+                    truncated,
+                ));
+            }
+            Err(error) if best_effort => context.errors.push(FileRefError {
+                path: relative_path.clone(),
+                error,
+            }),
+            Err(error) => return Err(format!("{relative_path}: {error}")),
+        }
+    }
+    context.block = blocks.join("\n");
+    Ok(context)
+}
+
+/// Prepends the file context to the user's text.
+pub(crate) fn apply_file_context(text: &str, context: &FileContext) -> String {
+    if context.block.is_empty() {
+        return text.to_string();
+    }
+    format!("{}\n{text}", context.block)
+}
+
+/// Adds `fileContext` to the `turn/start` response when files were referenced.
+pub(crate) fn attach_file_context_report(
+    mut response: Value,
+    context: Option<&FileContext>,
+) -> Value {
+    if let (Some(context), Some(map)) = (context, response.as_object_mut()) {
+        if let Ok(report) = serde_json::to_value(context) {
+            map.insert("fileContext".to_string(), report);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_file_context, build_file_context, MAX_FILE_REF_BYTES};
+    use tempfile::tempdir;
+
+    #[test]
+    fn prepends_fenced_file_blocks() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("notes.md"), "use ```rust``` fences\n").unwrap();
+        let context = build_file_context(dir.path(), &["notes.md".to_string()], false, None)
+            .expect("context");
+        assert_eq!(context.attached_bytes, 22);
+        assert_eq!(
+            apply_file_context("Summarize this", &context),
+            "File: notes.md\n````md\nuse ```rust``` fences\n````\n\nSummarize this"
+        );
+    }
+
+    #[test]
+    fn best_effort_reports_missing_and_binary_files() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), [0u8, 1, 2]).unwrap();
+        let refs = vec![
+            "a.txt".to_string(),
+            "missing.txt".to_string(),
+            "blob.bin".to_string(),
+        ];
+
+        assert!(build_file_context(dir.path(), &refs, false, None)
+            .unwrap_err()
+            .starts_with("missing.txt:"));
+
+        let context = build_file_context(dir.path(), &refs, true, None).expect("best effort");
+        assert_eq!(context.files.len(), 1);
+        assert_eq!(context.attached_bytes, 5);
+        let failed: Vec<_> = context.errors.iter().map(|err| err.path.as_str()).collect();
+        assert_eq!(failed, vec!["missing.txt", "blob.bin"]);
+        assert_eq!(context.errors[1].error, "File appears to be binary");
+    }
+
+    #[test]
+    fn large_files_are_truncated_with_a_note() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(
+            dir.path().join("big.log"),
+            "x".repeat(MAX_FILE_REF_BYTES + 10),
+        )
+        .unwrap();
+        let context =
+            build_file_context(dir.path(), &["big.log".to_string()], false, None).expect("context");
+        assert!(context.files[0].truncated);
+        assert_eq!(context.attached_bytes, MAX_FILE_REF_BYTES);
+        assert!(context.block.contains(&format!(
+            "(truncated: showing the first {MAX_FILE_REF_BYTES} of {} bytes)",
+            MAX_FILE_REF_BYTES + 10
+        )));
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::supervisor::spawn_guarded;
+
+const DEFAULT_TAIL_MAX_BYTES: u64 = 256 * 1024;
+const MAX_TAIL_BYTES: u64 = 4 * 1024 * 1024;
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Per-file budget for `file/appended` events; anything beyond it is picked
+/// up on later ticks rather than flooding the connection.
+const FOLLOW_BYTES_PER_SECOND: u64 = 64 * 1024;
+const MAX_FOLLOWED_FILES: usize = 32;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct TailChunk {
+    pub(crate) path: String,
+    /// Offset the data starts at; 0 after a rotation.
+    pub(crate) offset: u64,
+    #[serde(rename = "nextOffset")]
+    pub(crate) next_offset: u64,
+    pub(crate) size: u64,
+    pub(crate) data: String,
+    /// The file shrank below `fromOffset` (truncated or replaced), so reading
+    /// restarted at 0.
+    pub(crate) rotated: bool,
+    /// More bytes are available past `nextOffset`.
+    pub(crate) truncated: bool,
+}
+
+/// Resolves `relative_path` inside the workspace, rejecting anything that
+/// escapes it after symlinks are resolved.
+pub(crate) fn resolve_workspace_file(root: &Path, relative_path: &str) -> Result<PathBuf, String> {
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let canonical_path = canonical_root
+        .join(relative_path)
+        .canonicalize()
+        .map_err(|err| format!("Failed to open file: {err}"))?;
+    if !canonical_path.starts_with(&canonical_root) {
+        return Err("Invalid file path".to_string());
+    }
+    if !canonical_path.is_file() {
+        return Err("Path is not a file".to_string());
+    }
+    Ok(canonical_path)
+}
+
+/// Cheap identity used to spot a file replaced by rename (logrotate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileIdentity(u64, u64);
+
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> FileIdentity {
+    use std::os::unix::fs::MetadataExt;
+    FileIdentity(metadata.dev(), metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_identity(metadata: &Metadata) -> FileIdentity {
+    let created = metadata
+        .created()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+    FileIdentity(created, 0)
+}
+
+/// Keeps a cut from splitting a UTF-8 sequence; the remainder is returned on
+/// the next read. Invalid bytes elsewhere are replaced.
+fn decode_prefix(buffer: &[u8]) -> (String, usize) {
+    match std::str::from_utf8(buffer) {
+        Ok(text) => (text.to_string(), buffer.len()),
+        Err(err) if err.error_len().is_none() => {
+            let valid = err.valid_up_to();
+            (
+                String::from_utf8_lossy(&buffer[..valid]).into_owned(),
+                valid,
+            )
+        }
+        Err(_) => (String::from_utf8_lossy(buffer).into_owned(), buffer.len()),
+    }
+}
+
+fn read_from(
+    path: &Path,
+    display_path: &str,
+    from_offset: u64,
+    rotated: bool,
+    max_bytes: u64,
+) -> Result<TailChunk, String> {
+    let mut file = File::open(path).map_err(|err| format!("Failed to open file: {err}"))?;
+    let size = file
+        .metadata()
+        .map_err(|err| format!("Failed to read file metadata: {err}"))?
+        .len();
+    let (offset, rotated) = if from_offset > size {
+        (0, true)
+    } else {
+        (from_offset, rotated)
+    };
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|err| format!("Failed to seek file: {err}"))?;
+    let mut buffer = Vec::new();
+    file.take(max_bytes)
+        .read_to_end(&mut buffer)
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+    let (data, consumed) = decode_prefix(&buffer);
+    let next_offset = offset + consumed as u64;
+    Ok(TailChunk {
+        path: display_path.to_string(),
+        offset,
+        next_offset,
+        size,
+        data,
+        rotated,
+        truncated: next_offset < size,
+    })
+}
+
+/// Returns bytes appended since `from_offset`. A file smaller than the offset
+/// is treated as rotated and read from the start.
+pub(crate) fn tail_workspace_file(
+    root: &Path,
+    relative_path: &str,
+    from_offset: u64,
+    max_bytes: Option<u64>,
+) -> Result<TailChunk, String> {
+    let path = resolve_workspace_file(root, relative_path)?;
+    let max_bytes = max_bytes
+        .unwrap_or(DEFAULT_TAIL_MAX_BYTES)
+        .clamp(1, MAX_TAIL_BYTES);
+    read_from(&path, relative_path, from_offset, false, max_bytes)
+}
+
+fn follow_key(workspace_id: &str, path: &str) -> String {
+    format!("{workspace_id}:{path}")
+}
+
+/// Files clients subscribed to via `follow_file`. Each one is polled on its
+/// own task that emits `file/appended` until `unfollow_file` aborts it.
+pub(crate) struct FileFollowers<E: EventSink> {
+    sink: E,
+    tasks: Mutex<HashMap<String, tokio::task::JoinHandle<()>>>,
+}
+
+impl<E: EventSink> FileFollowers<E> {
+    pub(crate) fn new(sink: E) -> Self {
+        Self {
+            sink,
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn follow(
+        &self,
+        workspace_id: String,
+        root: &Path,
+        relative_path: String,
+        from_offset: Option<u64>,
+    ) -> Result<u64, String> {
+        let path = resolve_workspace_file(root, &relative_path)?;
+        let metadata = std::fs::metadata(&path)
+            .map_err(|err| format!("Failed to read file metadata: {err}"))?;
+        let offset = from_offset.unwrap_or(metadata.len());
+        let key = follow_key(&workspace_id, &relative_path);
+        let mut tasks = self.tasks.lock().unwrap_or_else(|err| err.into_inner());
+        tasks.retain(|_, task| !task.is_finished());
+        if !tasks.contains_key(&key) && tasks.len() >= MAX_FOLLOWED_FILES {
+            return Err(format!(
+                "Too many followed files (max {MAX_FOLLOWED_FILES})"
+            ));
+        }
+        let task = spawn_guarded(
+            self.sink.clone(),
+            "file watcher",
+            follow_loop(
+                self.sink.clone(),
+                workspace_id,
+                relative_path,
+                path,
+                file_identity(&metadata),
+                offset,
+            ),
+        );
+        if let Some(previous) = tasks.insert(key, task) {
+            previous.abort();
+        }
+        Ok(offset)
+    }
+
+    pub(crate) fn unfollow(&self, workspace_id: &str, relative_path: &str) -> bool {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|err| err.into_inner());
+        match tasks.remove(&follow_key(workspace_id, relative_path)) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn unfollow_workspace(&self, workspace_id: &str) {
+        let prefix = follow_key(workspace_id, "");
+        let mut tasks = self.tasks.lock().unwrap_or_else(|err| err.into_inner());
+        tasks.retain(|key, task| {
+            if key.starts_with(&prefix) {
+                task.abort();
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+fn emit_follow_event<E: EventSink>(
+    sink: &E,
+    workspace_id: &str,
+    method: &str,
+    params: serde_json::Value,
+) {
+    sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({ "method": method, "params": params }),
+    });
+}
+
+async fn follow_loop<E: EventSink>(
+    sink: E,
+    workspace_id: String,
+    relative_path: String,
+    path: PathBuf,
+    mut identity: FileIdentity,
+    mut offset: u64,
+) {
+    let budget_per_tick =
+        (FOLLOW_BYTES_PER_SECOND * FOLLOW_POLL_INTERVAL.as_millis() as u64 / 1000).max(1);
+    let mut interval = tokio::time::interval(FOLLOW_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let metadata = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            // Mid-rotation the path may briefly not exist.
+            Err(_) => continue,
+        };
+        let current_identity = file_identity(&metadata);
+        let replaced = current_identity != identity;
+        if replaced || metadata.len() < offset {
+            identity = current_identity;
+            offset = 0;
+            emit_follow_event(
+                &sink,
+                &workspace_id,
+                "file/rotated",
+                json!({ "workspaceId": workspace_id, "path": relative_path }),
+            );
+        }
+        if metadata.len() == offset {
+            continue;
+        }
+        match read_from(&path, &relative_path, offset, false, budget_per_tick) {
+            Ok(chunk) => {
+                if chunk.next_offset == offset {
+                    continue;
+                }
+                offset = chunk.next_offset;
+                emit_follow_event(
+                    &sink,
+                    &workspace_id,
+                    "file/appended",
+                    json!({
+                        "workspaceId": workspace_id,
+                        "path": relative_path,
+                        "offset": chunk.offset,
+                        "nextOffset": chunk.next_offset,
+                        "data": chunk.data,
+                    }),
+                );
+            }
+            Err(err) => {
+                eprintln!("Failed to follow {relative_path} in {workspace_id}: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_prefix, tail_workspace_file};
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn tail_returns_appended_bytes_and_flags_remaining() {
+        let dir = tempdir().expect("tempdir");
+        let log = dir.path().join("app.log");
+        std::fs::write(&log, "first\n").unwrap();
+
+        let chunk = tail_workspace_file(dir.path(), "app.log", 0, None).unwrap();
+        assert_eq!(chunk.data, "first\n");
+        assert_eq!(chunk.next_offset, 6);
+        assert!(!chunk.truncated);
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+        file.write_all(b"second\nthird\n").unwrap();
+        let chunk = tail_workspace_file(dir.path(), "app.log", 6, Some(7)).unwrap();
+        assert_eq!(chunk.data, "second\n");
+        assert_eq!(chunk.next_offset, 13);
+        assert!(chunk.truncated);
+    }
+
+    #[test]
+    fn tail_detects_truncation_as_rotation() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("app.log"), "fresh\n").unwrap();
+        let chunk = tail_workspace_file(dir.path(), "app.log", 1_000, None).unwrap();
+        assert!(chunk.rotated);
+        assert_eq!(chunk.offset, 0);
+        assert_eq!(chunk.data, "fresh\n");
+    }
+
+    #[test]
+    fn tail_rejects_paths_outside_workspace() {
+        let dir = tempdir().expect("tempdir");
+        let outside = tempdir().expect("tempdir");
+        std::fs::write(outside.path().join("secret.log"), "x").unwrap();
+        let escape = format!(
+            "../{}/secret.log",
+            outside.path().file_name().unwrap().to_string_lossy()
+        );
+        assert_eq!(
+            tail_workspace_file(dir.path(), &escape, 0, None).unwrap_err(),
+            "Invalid file path"
+        );
+    }
+
+    #[test]
+    fn decode_keeps_split_utf8_for_next_read() {
+        let bytes = "héllo".as_bytes();
+        let (text, consumed) = decode_prefix(&bytes[..2]);
+        assert_eq!(text, "h");
+        assert_eq!(consumed, 1);
+    }
+}
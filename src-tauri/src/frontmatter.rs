@@ -0,0 +1,356 @@
+//! Frontmatter edits that keep the rest of a note as the user wrote it.
+//! Top-level keys a patch does not change keep their original text, quoting
+//! and comments included; changed keys are re-serialized by serde_yaml, so a
+//! comment inside a changed key's block is lost. The body after the closing
+//! `---` is never rewritten.
+
+use serde::de::DeserializeOwned;
+use serde_yaml::{Mapping, Value};
+
+/// Frontmatter YAML and everything after its closing `---` line, verbatim.
+#[cfg(test)]
+pub(crate) fn frontmatter_block(content: &str) -> Option<(&str, &str)> {
+    let (_, raw, rest) = split_note(content)?;
+    let closing_len = rest.split_inclusive('\n').next().map_or(0, str::len);
+    Some((raw, &rest[closing_len..]))
+}
+
+/// Opening `---` line, frontmatter YAML, and the closing line plus body.
+fn split_note(content: &str) -> Option<(&str, &str, &str)> {
+    let opening_len = if content.starts_with("---\n") {
+        4
+    } else if content.starts_with("---\r\n") {
+        5
+    } else {
+        return None;
+    };
+    let rest = &content[opening_len..];
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim() == "---" {
+            return Some((&content[..opening_len], &rest[..offset], &rest[offset..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FrontmatterPatch {
+    Set(String, Value),
+    Remove(String),
+    /// Appends to a list, creating it when the key is missing.
+    Append(String, Value),
+}
+
+/// A top-level key with its value lines, or the comments and blank lines
+/// before the first key.
+struct Segment {
+    key: Option<Value>,
+    text: String,
+}
+
+pub(crate) struct FrontmatterNote {
+    opening: String,
+    raw: String,
+    rest: String,
+    original: Mapping,
+    mapping: Mapping,
+}
+
+impl FrontmatterNote {
+    /// Parses a note's frontmatter. Returns `None` when the note has no
+    /// frontmatter block.
+    pub(crate) fn parse(content: &str) -> Result<Option<Self>, String> {
+        let Some((opening, raw, rest)) = split_note(content) else {
+            return Ok(None);
+        };
+        let mapping = match serde_yaml::from_str::<Value>(raw) {
+            Ok(Value::Mapping(mapping)) => mapping,
+            Ok(Value::Null) => Mapping::new(),
+            Ok(_) => return Err("frontmatter is not a mapping".to_string()),
+            Err(err) => return Err(format!("frontmatter is not valid YAML: {err}")),
+        };
+        Ok(Some(Self {
+            opening: opening.to_string(),
+            raw: raw.to_string(),
+            rest: rest.to_string(),
+            original: mapping.clone(),
+            mapping,
+        }))
+    }
+
+    pub(crate) fn mapping(&self) -> &Mapping {
+        &self.mapping
+    }
+
+    pub(crate) fn deserialize<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_yaml::from_value(Value::Mapping(self.mapping.clone())).map_err(|err| err.to_string())
+    }
+
+    pub(crate) fn apply(&mut self, patch: FrontmatterPatch) -> Result<(), String> {
+        match patch {
+            FrontmatterPatch::Set(key, value) => {
+                self.mapping.insert(key.into(), value);
+            }
+            FrontmatterPatch::Remove(key) => {
+                self.mapping.remove(key.as_str());
+            }
+            FrontmatterPatch::Append(key, value) => {
+                match self
+                    .mapping
+                    .entry(key.clone().into())
+                    .or_insert_with(|| Value::Sequence(Vec::new()))
+                {
+                    Value::Sequence(items) => items.push(value),
+                    _ => return Err(format!("`{key}` is not a list")),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The whole note with the patched frontmatter. Unchanged frontmatter
+    /// comes back byte for byte.
+    pub(crate) fn render(&self) -> Result<String, String> {
+        if self.mapping == self.original {
+            return Ok(format!("{}{}{}", self.opening, self.raw, self.rest));
+        }
+        let raw = match self.render_in_place() {
+            Some(raw) => raw,
+            None => {
+                let yaml = serde_yaml::to_string(&self.mapping).map_err(|err| err.to_string())?;
+                self.with_line_endings(yaml)
+            }
+        };
+        Ok(format!("{}{}{}", self.opening, raw, self.rest))
+    }
+
+    /// Rewrites only the changed keys. Gives up (and the caller re-serializes
+    /// everything) when the frontmatter can't be split into standalone keys,
+    /// for example with anchors or multi-line flow values, or when the result
+    /// would not read back as the patched mapping.
+    fn render_in_place(&self) -> Option<String> {
+        let segments = segments(&self.raw)?;
+        let mut raw = String::new();
+        for segment in &segments {
+            let Some(key) = &segment.key else {
+                raw.push_str(&segment.text);
+                continue;
+            };
+            match self.mapping.get(key) {
+                None => {}
+                Some(value) if self.original.get(key) == Some(value) => {
+                    raw.push_str(&segment.text);
+                }
+                Some(value) => {
+                    if !raw.is_empty() && !raw.ends_with('\n') {
+                        raw.push_str(self.newline());
+                    }
+                    raw.push_str(&self.render_entry(key, value)?);
+                }
+            }
+        }
+        for (key, value) in &self.mapping {
+            if !self.original.contains_key(key) {
+                if !raw.is_empty() && !raw.ends_with('\n') {
+                    raw.push_str(self.newline());
+                }
+                raw.push_str(&self.render_entry(key, value)?);
+            }
+        }
+        let reread = match serde_yaml::from_str::<Value>(&raw).ok()? {
+            Value::Mapping(mapping) => mapping,
+            Value::Null => Mapping::new(),
+            _ => return None,
+        };
+        (reread == self.mapping).then_some(raw)
+    }
+
+    fn render_entry(&self, key: &Value, value: &Value) -> Option<String> {
+        let mut entry = Mapping::new();
+        entry.insert(key.clone(), value.clone());
+        let yaml = serde_yaml::to_string(&entry).ok()?;
+        Some(self.with_line_endings(yaml))
+    }
+
+    fn newline(&self) -> &'static str {
+        if self.opening.ends_with("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+
+    fn with_line_endings(&self, yaml: String) -> String {
+        if self.newline() == "\r\n" {
+            yaml.replace('\n', "\r\n")
+        } else {
+            yaml
+        }
+    }
+}
+
+/// Splits frontmatter into top-level keys. A key starts at a line with no
+/// indentation that is not a comment or a list item; everything up to the
+/// next such line belongs to it.
+fn segments(raw: &str) -> Option<Vec<Segment>> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut keyed = Vec::new();
+    for line in raw.split_inclusive('\n') {
+        let starts_key = line
+            .chars()
+            .next()
+            .is_some_and(|ch| !ch.is_whitespace() && ch != '#' && ch != '-');
+        match segments.last_mut() {
+            Some(segment) if !starts_key => segment.text.push_str(line),
+            _ => {
+                keyed.push(starts_key);
+                segments.push(Segment {
+                    key: None,
+                    text: line.to_string(),
+                });
+            }
+        }
+    }
+    for (segment, keyed) in segments.iter_mut().zip(keyed) {
+        if !keyed {
+            continue;
+        }
+        let Ok(Value::Mapping(mapping)) = serde_yaml::from_str::<Value>(&segment.text) else {
+            return None;
+        };
+        let mut keys = mapping.into_iter().map(|(key, _)| key);
+        segment.key = Some(keys.next()?);
+        if keys.next().is_some() {
+            return None;
+        }
+    }
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{frontmatter_block, FrontmatterNote, FrontmatterPatch};
+    use serde_yaml::Value;
+
+    /// Frontmatter as Obsidian, its plugins and people write it.
+    const CORPUS: &[&str] = &[
+        "---\nname: Rent\namount: 1200\ndue_day: 31\n---\n# Rent\n",
+        "---\ntitle: \"Dune: Part Two\"\nstatus: 'watching'\nrating: 4.5\n---\nBody\n",
+        "---\ntags:\n  - food\n  - protein\naliases: [Chicken, \"Grilled chicken\"]\n---\n",
+        "---\ntags:\n- food\n- protein\ncreated: 2024-06-03T14:30\n---\n\nNotes\n",
+        "---\n# Imported from Notion\nname: Gym   # keep this\nbehaviors:\n  workout: true\n  minutes: 45\n---\n",
+        "---\nsummary: |\n  Line one\n\n  Line two\nnext_due: 06/03/2024\n---\ntext",
+        "---\r\nname: Dentist\r\namount: 80\r\n---\r\nWindows note\r\n",
+        "---\n---\nEmpty frontmatter\n",
+        "---\nlink: \"[[Food/Rice]]\"\nemoji: 🍚\nempty:\nnull_value: ~\nyes_string: \"yes\"\n---\n",
+        "---\nfoods: &base\n  - rice\nlunch: *base\n---\n",
+        "---\nnested:\n  deeper:\n    - {date: 2024-06-01, amount: 10}\n---\n",
+    ];
+
+    fn note(content: &str) -> FrontmatterNote {
+        FrontmatterNote::parse(content)
+            .expect("valid frontmatter")
+            .expect("frontmatter block")
+    }
+
+    #[test]
+    fn unpatched_notes_render_unchanged() {
+        for content in CORPUS {
+            assert_eq!(note(content).render().unwrap(), *content);
+        }
+    }
+
+    #[test]
+    fn setting_a_value_back_keeps_the_note_unchanged() {
+        for content in CORPUS {
+            let mut note = note(content);
+            let entries: Vec<_> = note.mapping().clone().into_iter().collect();
+            for (key, value) in entries {
+                let key = key.as_str().expect("string key").to_string();
+                note.apply(FrontmatterPatch::Set(key, value)).unwrap();
+            }
+            assert_eq!(note.render().unwrap(), *content);
+        }
+    }
+
+    #[test]
+    fn new_keys_leave_existing_lines_and_body_alone() {
+        for content in CORPUS {
+            let mut note = note(content);
+            note.apply(FrontmatterPatch::Set("reviewed".to_string(), true.into()))
+                .unwrap();
+            let rendered = note.render().unwrap();
+            let (raw, body) = frontmatter_block(content).unwrap();
+            let (new_raw, new_body) = frontmatter_block(&rendered).unwrap();
+            assert_eq!(new_body, body, "{content}");
+            if !raw.contains('&') {
+                assert!(new_raw.starts_with(raw), "{content}");
+            }
+            let reread: Value = serde_yaml::from_str(new_raw).unwrap();
+            assert_eq!(reread["reviewed"], Value::Bool(true), "{content}");
+        }
+    }
+
+    #[test]
+    fn patches_only_touch_their_keys() {
+        let content = "---\n# bill\nname: \"Rent\"   # landlord Pat\namount: 1200\npayments:\n  - date: 2026-01-01\n    amount: 1200\nnext_due: 2026-01-31\n---\n# Rent\n\nLease notes  \n";
+        let mut note = note(content);
+        let mut payment = serde_yaml::Mapping::new();
+        payment.insert("date".into(), "2026-01-30".into());
+        payment.insert("amount".into(), 1200.into());
+        note.apply(FrontmatterPatch::Append(
+            "payments".to_string(),
+            payment.into(),
+        ))
+        .unwrap();
+        note.apply(FrontmatterPatch::Set(
+            "next_due".to_string(),
+            "2026-02-28".into(),
+        ))
+        .unwrap();
+        assert_eq!(
+            note.render().unwrap(),
+            "---\n# bill\nname: \"Rent\"   # landlord Pat\namount: 1200\npayments:\n- date: 2026-01-01\n  amount: 1200\n- date: 2026-01-30\n  amount: 1200\nnext_due: 2026-02-28\n---\n# Rent\n\nLease notes  \n"
+        );
+
+        note.apply(FrontmatterPatch::Remove("next_due".to_string()))
+            .unwrap();
+        assert!(note
+            .render()
+            .unwrap()
+            .ends_with("  amount: 1200\n---\n# Rent\n\nLease notes  \n"));
+
+        let err = note
+            .apply(FrontmatterPatch::Append("amount".to_string(), 1.into()))
+            .unwrap_err();
+        assert_eq!(err, "`amount` is not a list");
+    }
+
+    #[test]
+    fn keeps_windows_line_endings() {
+        let mut note = note("---\r\nname: Dentist\r\namount: 80\r\n---\r\nBody\r\n");
+        note.apply(FrontmatterPatch::Set("amount".to_string(), 95.into()))
+            .unwrap();
+        assert_eq!(
+            note.render().unwrap(),
+            "---\r\nname: Dentist\r\namount: 95\r\n---\r\nBody\r\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_frontmatter() {
+        assert!(FrontmatterNote::parse("no frontmatter\n")
+            .unwrap()
+            .is_none());
+        let err = FrontmatterNote::parse("---\nname: [unclosed\n---\n")
+            .err()
+            .expect("invalid");
+        assert!(err.starts_with("frontmatter is not valid YAML"), "{err}");
+        let err = FrontmatterNote::parse("---\n- a\n---\n")
+            .err()
+            .expect("list");
+        assert_eq!(err, "frontmatter is not a mapping");
+    }
+}
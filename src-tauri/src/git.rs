@@ -2,21 +2,37 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use base64::{engine::general_purpose::STANDARD, Engine as _};
-use git2::{BranchType, DiffOptions, Repository, Sort, Status, StatusOptions};
+use git2::{BranchType, Repository, Sort, Status, StatusOptions};
 use serde_json::json;
 use tauri::{AppHandle, State};
 use tokio::process::Command;
 
+use crate::backend::blocking::run_git_blocking;
+use crate::backend::path_health::ensure_path_available;
+use crate::branch_protection::{
+    current_branch_name, effective_protected_branches, ensure_branch_not_protected,
+    ensure_tag_not_protected,
+};
+use crate::git_commit::{self, CommitOptions};
+use crate::git_hunks::{self, HunkSelector};
+use crate::git_stash;
+use crate::git_status_cache;
+use crate::git_tags;
 use crate::git_utils::{
-    checkout_branch, commit_to_entry, diff_patch_to_string, diff_stats_for_path, image_mime_type,
-    list_git_roots as scan_git_roots, parse_github_repo, resolve_git_root,
+    action_paths_for_file, checkout_branch, collect_workspace_diff, commit_to_entry,
+    diff_patch_to_string, diff_stats_for_path, display_diff_options, image_mime_type,
+    intraline_hunks, is_whitespace_only_patch, list_git_roots as scan_git_roots, parse_github_repo,
+    resolve_git_root, resolve_git_sub_root,
 };
+use crate::github_cli::{self, GhAuthStatus};
+use crate::read_only::ensure_writable;
 use crate::remote_backend;
+use crate::revert_undo::{self, RevertUndoEntry};
 use crate::state::AppState;
 use crate::types::{
-    BranchInfo, GitCommitDiff, GitFileDiff, GitFileStatus, GitHubIssue, GitHubIssuesResponse,
-    GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff, GitHubPullRequestsResponse,
-    GitLogResponse,
+    BranchInfo, GitCommitDiff, GitCommitOutcome, GitFileDiff, GitFileStatus, GitHubIssue,
+    GitHubIssuesResponse, GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestsResponse, GitLogResponse, GitStashEntry, GitTagInfo,
 };
 use crate::utils::{git_env_path, normalize_git_path, resolve_git_binary};
 
@@ -55,6 +71,9 @@ async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String>
         .output()
         .await
         .map_err(|e| format!("Failed to run git: {e}"))?;
+    // Whatever the command changed, the next status must not come from the
+    // cache.
+    git_status_cache::invalidate(repo_root);
 
     if output.status.success() {
         return Ok(());
@@ -73,68 +92,6 @@ async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String>
     Err(detail.to_string())
 }
 
-fn action_paths_for_file(repo_root: &Path, path: &str) -> Vec<String> {
-    let target = normalize_git_path(path).trim().to_string();
-    if target.is_empty() {
-        return Vec::new();
-    }
-
-    let repo = match Repository::open(repo_root) {
-        Ok(repo) => repo,
-        Err(_) => return vec![target],
-    };
-
-    let mut status_options = StatusOptions::new();
-    status_options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .renames_head_to_index(true)
-        .renames_index_to_workdir(true)
-        .include_ignored(false);
-
-    let statuses = match repo.statuses(Some(&mut status_options)) {
-        Ok(statuses) => statuses,
-        Err(_) => return vec![target],
-    };
-
-    for entry in statuses.iter() {
-        let status = entry.status();
-        if !(status.contains(Status::WT_RENAMED) || status.contains(Status::INDEX_RENAMED)) {
-            continue;
-        }
-        let delta = entry.index_to_workdir().or_else(|| entry.head_to_index());
-        let Some(delta) = delta else {
-            continue;
-        };
-        let (Some(old_path), Some(new_path)) = (delta.old_file().path(), delta.new_file().path())
-        else {
-            continue;
-        };
-        let old_path = normalize_git_path(old_path.to_string_lossy().as_ref());
-        let new_path = normalize_git_path(new_path.to_string_lossy().as_ref());
-        if old_path != target && new_path != target {
-            continue;
-        }
-        if old_path == new_path || new_path.is_empty() {
-            return vec![target];
-        }
-        let mut result = Vec::new();
-        if !old_path.is_empty() {
-            result.push(old_path);
-        }
-        if !new_path.is_empty() && !result.contains(&new_path) {
-            result.push(new_path);
-        }
-        return if result.is_empty() {
-            vec![target]
-        } else {
-            result
-        };
-    }
-
-    vec![target]
-}
-
 fn parse_upstream_ref(name: &str) -> Option<(String, String)> {
     let trimmed = name.strip_prefix("refs/remotes/").unwrap_or(name);
     let mut parts = trimmed.splitn(2, '/');
@@ -180,6 +137,15 @@ async fn push_with_upstream(repo_root: &Path) -> Result<(), String> {
     run_git_command(repo_root, &["push"]).await
 }
 
+/// Pushes a tag to the upstream remote of the current branch, or `origin`.
+async fn push_tag(repo_root: &Path, name: &str) -> Result<(), String> {
+    let remote = upstream_remote_and_branch(repo_root)?
+        .map(|(remote, _)| remote)
+        .unwrap_or_else(|| "origin".to_string());
+    let refspec = format!("refs/tags/{name}");
+    run_git_command(repo_root, &["push", remote.as_str(), refspec.as_str()]).await
+}
+
 fn status_for_index(status: Status) -> Option<&'static str> {
     if status.contains(Status::INDEX_NEW) {
         Some("A")
@@ -223,71 +189,6 @@ fn status_for_delta(status: git2::Delta) -> &'static str {
     }
 }
 
-fn build_combined_diff(diff: &git2::Diff) -> String {
-    let mut combined_diff = String::new();
-    for (index, delta) in diff.deltas().enumerate() {
-        let path = delta.new_file().path().or_else(|| delta.old_file().path());
-        let Some(path) = path else {
-            continue;
-        };
-        let patch = match git2::Patch::from_diff(diff, index) {
-            Ok(patch) => patch,
-            Err(_) => continue,
-        };
-        let Some(mut patch) = patch else {
-            continue;
-        };
-        let content = match diff_patch_to_string(&mut patch) {
-            Ok(content) => content,
-            Err(_) => continue,
-        };
-        if content.trim().is_empty() {
-            continue;
-        }
-        if !combined_diff.is_empty() {
-            combined_diff.push_str("\n\n");
-        }
-        combined_diff.push_str(&format!("=== {} ===\n", path.display()));
-        combined_diff.push_str(&content);
-    }
-    combined_diff
-}
-
-fn collect_workspace_diff(repo_root: &Path) -> Result<String, String> {
-    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
-    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
-
-    let mut options = DiffOptions::new();
-    let index = repo.index().map_err(|e| e.to_string())?;
-    let diff = match head_tree.as_ref() {
-        Some(tree) => repo
-            .diff_tree_to_index(Some(tree), Some(&index), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-        None => repo
-            .diff_tree_to_index(None, Some(&index), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-    };
-    let combined_diff = build_combined_diff(&diff);
-    if !combined_diff.trim().is_empty() {
-        return Ok(combined_diff);
-    }
-
-    let mut options = DiffOptions::new();
-    options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .show_untracked_content(true);
-    let diff = match head_tree.as_ref() {
-        Some(tree) => repo
-            .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-        None => repo
-            .diff_tree_to_workdir_with_index(None, Some(&mut options))
-            .map_err(|e| e.to_string())?,
-    };
-    Ok(build_combined_diff(&diff))
-}
-
 fn github_repo_from_path(path: &Path) -> Result<String, String> {
     let repo = Repository::open(path).map_err(|e| e.to_string())?;
     let remotes = repo.remotes().map_err(|e| e.to_string())?;
@@ -339,6 +240,7 @@ fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
             path: normalize_git_path(&path),
             status: status_value,
             diff: diff_text,
+            intraline: None,
         });
     };
 
@@ -401,6 +303,8 @@ fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
 #[tauri::command]
 pub(crate) async fn get_git_status(
     workspace_id: String,
+    git_root: Option<String>,
+    force_refresh: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<serde_json::Value, String> {
@@ -409,136 +313,158 @@ pub(crate) async fn get_git_status(
             &*state,
             app,
             "get_git_status",
-            json!({ "workspaceId": workspace_id }),
+            json!({
+                "workspaceId": workspace_id,
+                "gitRoot": git_root,
+                "forceRefresh": force_refresh,
+            }),
         )
         .await;
     }
+    ensure_path_available(&workspace_id)?;
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
         .ok_or("workspace not found")?
         .clone();
+    drop(workspaces);
 
-    let repo_root = resolve_git_root(&entry)?;
-    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-
-    let branch_name = repo
-        .head()
-        .ok()
-        .and_then(|head| head.shorthand().map(|s| s.to_string()))
-        .unwrap_or_else(|| "unknown".to_string());
-
-    let mut status_options = StatusOptions::new();
-    status_options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .renames_head_to_index(true)
-        .renames_index_to_workdir(true)
-        .include_ignored(false);
-
-    let statuses = repo
-        .statuses(Some(&mut status_options))
-        .map_err(|e| e.to_string())?;
-
-    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
-    let index = repo.index().ok();
-
-    let mut files = Vec::new();
-    let mut staged_files = Vec::new();
-    let mut unstaged_files = Vec::new();
-    let mut total_additions = 0i64;
-    let mut total_deletions = 0i64;
-    for entry in statuses.iter() {
-        let path = entry.path().unwrap_or("");
-        if path.is_empty() {
-            continue;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    run_git_blocking(move || {
+        let probe = git_status_cache::probe(&repo_root);
+        if !force_refresh.unwrap_or(false) {
+            if let Some(cached) = probe.as_ref().and_then(git_status_cache::cached) {
+                return Ok(cached);
+            }
         }
-        if let Some(index) = index.as_ref() {
-            if let Some(entry) = index.get_path(Path::new(path), 0) {
-                if entry.flags_extended & INDEX_SKIP_WORKTREE_FLAG != 0 {
-                    continue;
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+
+        let branch_name = repo
+            .head()
+            .ok()
+            .and_then(|head| head.shorthand().map(|s| s.to_string()))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let mut status_options = StatusOptions::new();
+        status_options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .renames_head_to_index(true)
+            .renames_index_to_workdir(true)
+            .include_ignored(false);
+
+        let statuses = repo
+            .statuses(Some(&mut status_options))
+            .map_err(|e| e.to_string())?;
+
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let index = repo.index().ok();
+
+        let mut files = Vec::new();
+        let mut staged_files = Vec::new();
+        let mut unstaged_files = Vec::new();
+        let mut total_additions = 0i64;
+        let mut total_deletions = 0i64;
+        for entry in statuses.iter() {
+            let path = entry.path().unwrap_or("");
+            if path.is_empty() {
+                continue;
+            }
+            if let Some(index) = index.as_ref() {
+                if let Some(entry) = index.get_path(Path::new(path), 0) {
+                    if entry.flags_extended & INDEX_SKIP_WORKTREE_FLAG != 0 {
+                        continue;
+                    }
                 }
             }
-        }
-        let status = entry.status();
-        let normalized_path = normalize_git_path(path);
-        let include_index = status.intersects(
-            Status::INDEX_NEW
-                | Status::INDEX_MODIFIED
-                | Status::INDEX_DELETED
-                | Status::INDEX_RENAMED
-                | Status::INDEX_TYPECHANGE,
-        );
-        let include_workdir = status.intersects(
-            Status::WT_NEW
-                | Status::WT_MODIFIED
-                | Status::WT_DELETED
-                | Status::WT_RENAMED
-                | Status::WT_TYPECHANGE,
-        );
-        let mut combined_additions = 0i64;
-        let mut combined_deletions = 0i64;
-
-        if include_index {
-            let (additions, deletions) =
-                diff_stats_for_path(&repo, head_tree.as_ref(), path, true, false).unwrap_or((0, 0));
-            if let Some(status_str) = status_for_index(status) {
-                staged_files.push(GitFileStatus {
-                    path: normalized_path.clone(),
-                    status: status_str.to_string(),
-                    additions,
-                    deletions,
-                });
+            let status = entry.status();
+            let normalized_path = normalize_git_path(path);
+            let include_index = status.intersects(
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED
+                    | Status::INDEX_TYPECHANGE,
+            );
+            let include_workdir = status.intersects(
+                Status::WT_NEW
+                    | Status::WT_MODIFIED
+                    | Status::WT_DELETED
+                    | Status::WT_RENAMED
+                    | Status::WT_TYPECHANGE,
+            );
+            let mut combined_additions = 0i64;
+            let mut combined_deletions = 0i64;
+
+            if include_index {
+                let (additions, deletions) =
+                    diff_stats_for_path(&repo, head_tree.as_ref(), path, true, false)
+                        .unwrap_or((0, 0));
+                if let Some(status_str) = status_for_index(status) {
+                    staged_files.push(GitFileStatus {
+                        path: normalized_path.clone(),
+                        status: status_str.to_string(),
+                        additions,
+                        deletions,
+                    });
+                }
+                combined_additions += additions;
+                combined_deletions += deletions;
+                total_additions += additions;
+                total_deletions += deletions;
             }
-            combined_additions += additions;
-            combined_deletions += deletions;
-            total_additions += additions;
-            total_deletions += deletions;
-        }
 
-        if include_workdir {
-            let (additions, deletions) =
-                diff_stats_for_path(&repo, head_tree.as_ref(), path, false, true).unwrap_or((0, 0));
-            if let Some(status_str) = status_for_workdir(status) {
-                unstaged_files.push(GitFileStatus {
-                    path: normalized_path.clone(),
+            if include_workdir {
+                let (additions, deletions) =
+                    diff_stats_for_path(&repo, head_tree.as_ref(), path, false, true)
+                        .unwrap_or((0, 0));
+                if let Some(status_str) = status_for_workdir(status) {
+                    unstaged_files.push(GitFileStatus {
+                        path: normalized_path.clone(),
+                        status: status_str.to_string(),
+                        additions,
+                        deletions,
+                    });
+                }
+                combined_additions += additions;
+                combined_deletions += deletions;
+                total_additions += additions;
+                total_deletions += deletions;
+            }
+
+            if include_index || include_workdir {
+                let status_str = status_for_workdir(status)
+                    .or_else(|| status_for_index(status))
+                    .unwrap_or("--");
+                files.push(GitFileStatus {
+                    path: normalized_path,
                     status: status_str.to_string(),
-                    additions,
-                    deletions,
+                    additions: combined_additions,
+                    deletions: combined_deletions,
                 });
             }
-            combined_additions += additions;
-            combined_deletions += deletions;
-            total_additions += additions;
-            total_deletions += deletions;
         }
 
-        if include_index || include_workdir {
-            let status_str = status_for_workdir(status)
-                .or_else(|| status_for_index(status))
-                .unwrap_or("--");
-            files.push(GitFileStatus {
-                path: normalized_path,
-                status: status_str.to_string(),
-                additions: combined_additions,
-                deletions: combined_deletions,
-            });
+        let response = json!({
+            "branchName": branch_name,
+            "files": files,
+            "stagedFiles": staged_files,
+            "unstagedFiles": unstaged_files,
+            "totalAdditions": total_additions,
+            "totalDeletions": total_deletions,
+        });
+        if let Some(probe) = probe {
+            git_status_cache::store(probe, &response);
         }
-    }
-
-    Ok(json!({
-        "branchName": branch_name,
-        "files": files,
-        "stagedFiles": staged_files,
-        "unstagedFiles": unstaged_files,
-        "totalAdditions": total_additions,
-        "totalDeletions": total_deletions,
-    }))
+        Ok(response)
+    })
+    .await
 }
 
 #[tauri::command]
 pub(crate) async fn stage_git_file(
     workspace_id: String,
+    git_root: Option<String>,
     path: String,
     state: State<'_, AppState>,
     app: AppHandle,
@@ -548,7 +474,7 @@ pub(crate) async fn stage_git_file(
             &*state,
             app,
             "stage_git_file",
-            json!({ "workspaceId": workspace_id, "path": path }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "path": path }),
         )
         .await?;
         return Ok(());
@@ -561,7 +487,8 @@ pub(crate) async fn stage_git_file(
             .ok_or("workspace not found")?
     };
 
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "stage")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     // If libgit2 reports a rename, we want a single UI action to stage both the
     // old + new paths so the change actually moves to the staged section.
     for path in action_paths_for_file(&repo_root, &path) {
@@ -570,9 +497,102 @@ pub(crate) async fn stage_git_file(
     Ok(())
 }
 
+#[tauri::command]
+pub(crate) async fn stage_git_hunk(
+    workspace_id: String,
+    git_root: Option<String>,
+    path: String,
+    hunk_index: Option<usize>,
+    header: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    apply_git_hunk(
+        workspace_id,
+        git_root,
+        path,
+        HunkSelector {
+            index: hunk_index,
+            header,
+        },
+        false,
+        state,
+        app,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn unstage_git_hunk(
+    workspace_id: String,
+    git_root: Option<String>,
+    path: String,
+    hunk_index: Option<usize>,
+    header: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    apply_git_hunk(
+        workspace_id,
+        git_root,
+        path,
+        HunkSelector {
+            index: hunk_index,
+            header,
+        },
+        true,
+        state,
+        app,
+    )
+    .await
+}
+
+async fn apply_git_hunk(
+    workspace_id: String,
+    git_root: Option<String>,
+    path: String,
+    selector: HunkSelector,
+    unstage: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            if unstage {
+                "unstage_git_hunk"
+            } else {
+                "stage_git_hunk"
+            },
+            json!({
+                "workspaceId": workspace_id,
+                "gitRoot": git_root,
+                "path": path,
+                "hunkIndex": selector.index,
+                "header": selector.header,
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+
+    ensure_writable(&entry, if unstage { "unstage" } else { "stage" })?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    git_hunks::apply_hunk(&repo_root, &path, &selector, unstage).await
+}
+
 #[tauri::command]
 pub(crate) async fn stage_git_all(
     workspace_id: String,
+    git_root: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
@@ -581,7 +601,7 @@ pub(crate) async fn stage_git_all(
             &*state,
             app,
             "stage_git_all",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root }),
         )
         .await?;
         return Ok(());
@@ -594,13 +614,15 @@ pub(crate) async fn stage_git_all(
             .ok_or("workspace not found")?
     };
 
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "stage")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     run_git_command(&repo_root, &["add", "-A"]).await
 }
 
 #[tauri::command]
 pub(crate) async fn unstage_git_file(
     workspace_id: String,
+    git_root: Option<String>,
     path: String,
     state: State<'_, AppState>,
     app: AppHandle,
@@ -610,7 +632,7 @@ pub(crate) async fn unstage_git_file(
             &*state,
             app,
             "unstage_git_file",
-            json!({ "workspaceId": workspace_id, "path": path }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "path": path }),
         )
         .await?;
         return Ok(());
@@ -623,16 +645,94 @@ pub(crate) async fn unstage_git_file(
             .ok_or("workspace not found")?
     };
 
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "unstage")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     for path in action_paths_for_file(&repo_root, &path) {
         run_git_command(&repo_root, &["restore", "--staged", "--", &path]).await?;
     }
     Ok(())
 }
 
+async fn purge_expired_revert_undos(state: &AppState) {
+    let retention_days = state.app_settings.lock().await.revert_undo_retention_days;
+    revert_undo::purge_revert_undos(
+        &state.data_dir(),
+        retention_days,
+        chrono::Utc::now().timestamp_millis(),
+    );
+}
+
+/// Snapshots what a revert is about to discard so `restore_revert_undo` can
+/// bring it back.
+async fn capture_revert_undo(
+    state: &AppState,
+    workspace_id: &str,
+    repo_root: &Path,
+    paths: Option<&[String]>,
+) -> Result<(), String> {
+    purge_expired_revert_undos(state).await;
+    revert_undo::capture_revert_undo(&state.data_dir(), workspace_id, repo_root, paths)
+        .await
+        .map(|_| ())
+}
+
+#[tauri::command]
+pub(crate) async fn list_revert_undos(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<RevertUndoEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_revert_undos",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    purge_expired_revert_undos(&state).await;
+    Ok(revert_undo::list_revert_undos(
+        &state.data_dir(),
+        &workspace_id,
+    ))
+}
+
+#[tauri::command]
+pub(crate) async fn restore_revert_undo(
+    workspace_id: String,
+    git_root: Option<String>,
+    undo_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RevertUndoEntry, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "restore_revert_undo",
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "undoId": undo_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    ensure_writable(&entry, "restore a revert")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    revert_undo::restore_revert_undo(&state.data_dir(), &workspace_id, &undo_id, &repo_root).await
+}
+
 #[tauri::command]
 pub(crate) async fn revert_git_file(
     workspace_id: String,
+    git_root: Option<String>,
     path: String,
     state: State<'_, AppState>,
     app: AppHandle,
@@ -642,7 +742,7 @@ pub(crate) async fn revert_git_file(
             &*state,
             app,
             "revert_git_file",
-            json!({ "workspaceId": workspace_id, "path": path }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "path": path }),
         )
         .await?;
         return Ok(());
@@ -655,8 +755,11 @@ pub(crate) async fn revert_git_file(
             .ok_or("workspace not found")?
     };
 
-    let repo_root = resolve_git_root(&entry)?;
-    for path in action_paths_for_file(&repo_root, &path) {
+    ensure_writable(&entry, "revert")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    let paths = action_paths_for_file(&repo_root, &path);
+    capture_revert_undo(&state, &workspace_id, &repo_root, Some(&paths)).await?;
+    for path in paths {
         if run_git_command(
             &repo_root,
             &["restore", "--staged", "--worktree", "--", &path],
@@ -674,22 +777,48 @@ pub(crate) async fn revert_git_file(
 #[tauri::command]
 pub(crate) async fn revert_git_all(
     workspace_id: String,
+    git_root: Option<String>,
+    override_protection: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
+    let override_protection = override_protection.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         remote_backend::call_remote(
             &*state,
             app,
             "revert_git_all",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "overrideProtection": override_protection }),
         )
         .await?;
         return Ok(());
     }
-    let workspaces = state.workspaces.lock().await;
-    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
-    let repo_root = resolve_git_root(entry)?;
+    let (entry, parent_entry) = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?;
+        let parent_entry = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned();
+        (entry, parent_entry)
+    };
+    ensure_writable(&entry, "revert all changes")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    {
+        let settings = state.app_settings.lock().await;
+        let patterns = effective_protected_branches(&entry, parent_entry.as_ref(), &settings);
+        ensure_branch_not_protected(
+            current_branch_name(&repo_root).as_deref(),
+            &patterns,
+            override_protection,
+            "revert all changes",
+        )?;
+    }
+    capture_revert_undo(&state, &workspace_id, &repo_root, None).await?;
     run_git_command(
         &repo_root,
         &["restore", "--staged", "--worktree", "--", "."],
@@ -699,35 +828,106 @@ pub(crate) async fn revert_git_all(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn commit_git(
     workspace_id: String,
+    git_root: Option<String>,
     message: String,
+    amend: Option<bool>,
+    signoff: Option<bool>,
+    no_verify: Option<bool>,
+    override_protection: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> Result<GitCommitOutcome, String> {
+    let options = CommitOptions {
+        amend: amend.unwrap_or(false),
+        signoff: signoff.unwrap_or(false),
+        no_verify: no_verify.unwrap_or(false),
+    };
+    let override_protection = override_protection.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(
+        let response = remote_backend::call_remote(
             &*state,
             app,
             "commit_git",
-            json!({ "workspaceId": workspace_id, "message": message }),
+            json!({
+                "workspaceId": workspace_id,
+                "gitRoot": git_root,
+                "message": message,
+                "amend": options.amend,
+                "signoff": options.signoff,
+                "noVerify": options.no_verify,
+                "overrideProtection": override_protection,
+            }),
         )
         .await?;
-        return Ok(());
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    let workspaces = state.workspaces.lock().await;
-    let entry = workspaces
-        .get(&workspace_id)
-        .ok_or("workspace not found")?
-        .clone();
+    let (entry, parent_entry) = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?;
+        let parent_entry = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned();
+        (entry, parent_entry)
+    };
 
-    let repo_root = resolve_git_root(&entry)?;
-    run_git_command(&repo_root, &["commit", "-m", &message]).await
+    ensure_writable(&entry, "commit")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    if options.amend {
+        let settings = state.app_settings.lock().await;
+        let patterns = effective_protected_branches(&entry, parent_entry.as_ref(), &settings);
+        ensure_branch_not_protected(
+            current_branch_name(&repo_root).as_deref(),
+            &patterns,
+            override_protection,
+            "amend a commit",
+        )?;
+    }
+    git_commit::commit(&repo_root, &message, options).await
+}
+
+#[tauri::command]
+pub(crate) async fn get_last_commit_message(
+    workspace_id: String,
+    git_root: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<String>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_last_commit_message",
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root }),
+        )
+        .await?;
+        return Ok(response
+            .get("message")
+            .and_then(|value| value.as_str())
+            .map(str::to_string));
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    git_commit::last_commit_message(&repo_root)
 }
 
 #[tauri::command]
 pub(crate) async fn push_git(
     workspace_id: String,
+    git_root: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
@@ -736,7 +936,7 @@ pub(crate) async fn push_git(
             &*state,
             app,
             "push_git",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root }),
         )
         .await?;
         return Ok(());
@@ -747,13 +947,15 @@ pub(crate) async fn push_git(
         .ok_or("workspace not found")?
         .clone();
 
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "push")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     push_with_upstream(&repo_root).await
 }
 
 #[tauri::command]
 pub(crate) async fn pull_git(
     workspace_id: String,
+    git_root: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
@@ -762,7 +964,7 @@ pub(crate) async fn pull_git(
             &*state,
             app,
             "pull_git",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root }),
         )
         .await?;
         return Ok(());
@@ -773,13 +975,15 @@ pub(crate) async fn pull_git(
         .ok_or("workspace not found")?
         .clone();
 
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "pull")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     run_git_command(&repo_root, &["pull"]).await
 }
 
 #[tauri::command]
 pub(crate) async fn sync_git(
     workspace_id: String,
+    git_root: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
@@ -788,7 +992,7 @@ pub(crate) async fn sync_git(
             &*state,
             app,
             "sync_git",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root }),
         )
         .await?;
         return Ok(());
@@ -799,7 +1003,8 @@ pub(crate) async fn sync_git(
         .ok_or("workspace not found")?
         .clone();
 
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "sync")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     // Pull first, then push (like VSCode sync)
     run_git_command(&repo_root, &["pull"]).await?;
     push_with_upstream(&repo_root).await
@@ -846,137 +1051,175 @@ pub(crate) async fn get_workspace_diff(
     drop(workspaces);
 
     let repo_root = resolve_git_root(&entry)?;
-    collect_workspace_diff(&repo_root)
+    let ignore_whitespace = entry.settings.diff_ignore_whitespace;
+    run_git_blocking(move || collect_workspace_diff(&repo_root, ignore_whitespace)).await
 }
 
 #[tauri::command]
 pub(crate) async fn get_git_diffs(
     workspace_id: String,
+    git_root: Option<String>,
+    intraline: Option<bool>,
+    hunks: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Vec<GitFileDiff>, String> {
+    let intraline = intraline.unwrap_or(false);
+    let hunks = hunks.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
             "get_git_diffs",
-            json!({ "workspaceId": workspace_id }),
+            json!({
+                "workspaceId": workspace_id,
+                "gitRoot": git_root,
+                "intraline": intraline,
+                "hunks": hunks,
+            }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
+    ensure_path_available(&workspace_id)?;
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
         .ok_or("workspace not found")?
         .clone();
+    drop(workspaces);
 
-    let repo_root = resolve_git_root(&entry)?;
-    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
-
-    let mut options = DiffOptions::new();
-    options
-        .include_untracked(true)
-        .recurse_untracked_dirs(true)
-        .show_untracked_content(true);
-
-    let diff = match head_tree.as_ref() {
-        Some(tree) => repo
-            .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
-            .map_err(|e| e.to_string())?,
-        None => repo
-            .diff_tree_to_workdir_with_index(None, Some(&mut options))
-            .map_err(|e| e.to_string())?,
-    };
-
-    let mut results = Vec::new();
-    for (index, delta) in diff.deltas().enumerate() {
-        let old_path = delta.old_file().path();
-        let new_path = delta.new_file().path();
-        let display_path = new_path.or(old_path);
-        let Some(display_path) = display_path else {
-            continue;
+    let ignore_whitespace = entry.settings.diff_ignore_whitespace;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    run_git_blocking(move || {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+        let mut options = display_diff_options(ignore_whitespace);
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+
+        let diff = match head_tree.as_ref() {
+            Some(tree) => repo
+                .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
+                .map_err(|e| e.to_string())?,
+            None => repo
+                .diff_tree_to_workdir_with_index(None, Some(&mut options))
+                .map_err(|e| e.to_string())?,
         };
-        let old_path_str = old_path.map(|path| path.to_string_lossy());
-        let new_path_str = new_path.map(|path| path.to_string_lossy());
-        let display_path_str = display_path.to_string_lossy();
-        let normalized_path = normalize_git_path(&display_path_str);
-        let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
-        let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
-        let is_image = old_image_mime.is_some() || new_image_mime.is_some();
-
-        if is_image {
-            let is_deleted = delta.status() == git2::Delta::Deleted;
-            let is_added = delta.status() == git2::Delta::Added;
 
-            let old_image_data = if !is_added && old_image_mime.is_some() {
-                head_tree
-                    .as_ref()
-                    .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
-                    .and_then(|entry| repo.find_blob(entry.id()).ok())
-                    .and_then(blob_to_base64)
-            } else {
-                None
+        let mut results = Vec::new();
+        for (index, delta) in diff.deltas().enumerate() {
+            let old_path = delta.old_file().path();
+            let new_path = delta.new_file().path();
+            let display_path = new_path.or(old_path);
+            let Some(display_path) = display_path else {
+                continue;
             };
-
-            let new_image_data = if !is_deleted && new_image_mime.is_some() {
-                match new_path {
-                    Some(path) => {
-                        let full_path = repo_root.join(path);
-                        read_image_base64(&full_path)
+            let old_path_str = old_path.map(|path| path.to_string_lossy());
+            let new_path_str = new_path.map(|path| path.to_string_lossy());
+            let display_path_str = display_path.to_string_lossy();
+            let normalized_path = normalize_git_path(&display_path_str);
+            let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
+            let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
+            let is_image = old_image_mime.is_some() || new_image_mime.is_some();
+
+            if is_image {
+                let is_deleted = delta.status() == git2::Delta::Deleted;
+                let is_added = delta.status() == git2::Delta::Added;
+
+                let old_image_data = if !is_added && old_image_mime.is_some() {
+                    head_tree
+                        .as_ref()
+                        .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
+                        .and_then(|entry| repo.find_blob(entry.id()).ok())
+                        .and_then(blob_to_base64)
+                } else {
+                    None
+                };
+
+                let new_image_data = if !is_deleted && new_image_mime.is_some() {
+                    match new_path {
+                        Some(path) => {
+                            let full_path = repo_root.join(path);
+                            read_image_base64(&full_path)
+                        }
+                        None => None,
                     }
-                    None => None,
-                }
-            } else {
-                None
-            };
+                } else {
+                    None
+                };
+
+                results.push(GitFileDiff {
+                    path: normalized_path,
+                    diff: String::new(),
+                    is_binary: true,
+                    is_image: true,
+                    old_image_data,
+                    new_image_data,
+                    old_image_mime: old_image_mime.map(str::to_string),
+                    new_image_mime: new_image_mime.map(str::to_string),
+                    whitespace_only: false,
+                    intraline: None,
+                    hunks: None,
+                });
+                continue;
+            }
 
+            let patch = match git2::Patch::from_diff(&diff, index) {
+                Ok(patch) => patch,
+                Err(_) => continue,
+            };
+            let Some(mut patch) = patch else {
+                continue;
+            };
+            let whitespace_only = is_whitespace_only_patch(&patch, ignore_whitespace);
+            let content = match diff_patch_to_string(&mut patch) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if content.trim().is_empty() && !whitespace_only {
+                continue;
+            }
             results.push(GitFileDiff {
                 path: normalized_path,
-                diff: String::new(),
-                is_binary: true,
-                is_image: true,
-                old_image_data,
-                new_image_data,
-                old_image_mime: old_image_mime.map(str::to_string),
-                new_image_mime: new_image_mime.map(str::to_string),
+                diff: content,
+                is_binary: false,
+                is_image: false,
+                old_image_data: None,
+                new_image_data: None,
+                old_image_mime: None,
+                new_image_mime: None,
+                whitespace_only,
+                intraline: None,
+                hunks: None,
             });
-            continue;
         }
-
-        let patch = match git2::Patch::from_diff(&diff, index) {
-            Ok(patch) => patch,
-            Err(_) => continue,
-        };
-        let Some(mut patch) = patch else {
-            continue;
-        };
-        let content = match diff_patch_to_string(&mut patch) {
-            Ok(content) => content,
-            Err(_) => continue,
-        };
-        if content.trim().is_empty() {
-            continue;
+        if intraline {
+            for file in &mut results {
+                file.intraline = intraline_hunks(&file.diff);
+            }
+        }
+        if hunks {
+            let mut by_path = git_hunks::collect_hunks(&repo)?;
+            for file in &mut results {
+                if !file.is_binary {
+                    file.hunks = Some(by_path.remove(&file.path).unwrap_or_default());
+                }
+            }
         }
-        results.push(GitFileDiff {
-            path: normalized_path,
-            diff: content,
-            is_binary: false,
-            is_image: false,
-            old_image_data: None,
-            new_image_data: None,
-            old_image_mime: None,
-            new_image_mime: None,
-        });
-    }
 
-    Ok(results)
+        Ok(results)
+    })
+    .await
 }
 
 #[tauri::command]
 pub(crate) async fn get_git_log(
     workspace_id: String,
+    git_root: Option<String>,
     limit: Option<usize>,
     state: State<'_, AppState>,
     app: AppHandle,
@@ -986,7 +1229,7 @@ pub(crate) async fn get_git_log(
             &*state,
             app,
             "get_git_log",
-            json!({ "workspaceId": workspace_id, "limit": limit }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "limit": limit }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
@@ -996,106 +1239,120 @@ pub(crate) async fn get_git_log(
         .get(&workspace_id)
         .ok_or("workspace not found")?
         .clone();
+    drop(workspaces);
 
-    let repo_root = resolve_git_root(&entry)?;
-    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-    let max_items = limit.unwrap_or(40);
-    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-    revwalk.push_head().map_err(|e| e.to_string())?;
-    revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
-
-    let mut total = 0usize;
-    for oid_result in revwalk {
-        oid_result.map_err(|e| e.to_string())?;
-        total += 1;
-    }
-
-    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-    revwalk.push_head().map_err(|e| e.to_string())?;
-    revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    run_git_blocking(move || {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let max_items = limit.unwrap_or(40);
+        let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+        revwalk.push_head().map_err(|e| e.to_string())?;
+        revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+
+        let mut total = 0usize;
+        for oid_result in revwalk {
+            oid_result.map_err(|e| e.to_string())?;
+            total += 1;
+        }
 
-    let mut entries = Vec::new();
-    for oid_result in revwalk.take(max_items) {
-        let oid = oid_result.map_err(|e| e.to_string())?;
-        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
-        entries.push(commit_to_entry(commit));
-    }
+        let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+        revwalk.push_head().map_err(|e| e.to_string())?;
+        revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
 
-    let mut ahead = 0usize;
-    let mut behind = 0usize;
-    let mut ahead_entries = Vec::new();
-    let mut behind_entries = Vec::new();
-    let mut upstream = None;
-
-    if let Ok(head) = repo.head() {
-        if head.is_branch() {
-            if let Some(branch_name) = head.shorthand() {
-                if let Ok(branch) = repo.find_branch(branch_name, BranchType::Local) {
-                    if let Ok(upstream_branch) = branch.upstream() {
-                        let upstream_ref = upstream_branch.get();
-                        upstream = upstream_ref
-                            .shorthand()
-                            .map(|name| name.to_string())
-                            .or_else(|| upstream_ref.name().map(|name| name.to_string()));
-                        if let (Some(head_oid), Some(upstream_oid)) =
-                            (head.target(), upstream_ref.target())
-                        {
-                            let (ahead_count, behind_count) = repo
-                                .graph_ahead_behind(head_oid, upstream_oid)
-                                .map_err(|e| e.to_string())?;
-                            ahead = ahead_count;
-                            behind = behind_count;
-
-                            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-                            revwalk.push(head_oid).map_err(|e| e.to_string())?;
-                            revwalk.hide(upstream_oid).map_err(|e| e.to_string())?;
-                            revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
-                            for oid_result in revwalk.take(max_items) {
-                                let oid = oid_result.map_err(|e| e.to_string())?;
-                                let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
-                                ahead_entries.push(commit_to_entry(commit));
-                            }
+        let mut entries = Vec::new();
+        for oid_result in revwalk.take(max_items) {
+            let oid = oid_result.map_err(|e| e.to_string())?;
+            let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+            entries.push(commit_to_entry(commit));
+        }
 
-                            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-                            revwalk.push(upstream_oid).map_err(|e| e.to_string())?;
-                            revwalk.hide(head_oid).map_err(|e| e.to_string())?;
-                            revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
-                            for oid_result in revwalk.take(max_items) {
-                                let oid = oid_result.map_err(|e| e.to_string())?;
-                                let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
-                                behind_entries.push(commit_to_entry(commit));
+        let mut ahead = 0usize;
+        let mut behind = 0usize;
+        let mut ahead_entries = Vec::new();
+        let mut behind_entries = Vec::new();
+        let mut upstream = None;
+
+        if let Ok(head) = repo.head() {
+            if head.is_branch() {
+                if let Some(branch_name) = head.shorthand() {
+                    if let Ok(branch) = repo.find_branch(branch_name, BranchType::Local) {
+                        if let Ok(upstream_branch) = branch.upstream() {
+                            let upstream_ref = upstream_branch.get();
+                            upstream = upstream_ref
+                                .shorthand()
+                                .map(|name| name.to_string())
+                                .or_else(|| upstream_ref.name().map(|name| name.to_string()));
+                            if let (Some(head_oid), Some(upstream_oid)) =
+                                (head.target(), upstream_ref.target())
+                            {
+                                let (ahead_count, behind_count) = repo
+                                    .graph_ahead_behind(head_oid, upstream_oid)
+                                    .map_err(|e| e.to_string())?;
+                                ahead = ahead_count;
+                                behind = behind_count;
+
+                                let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                                revwalk.push(head_oid).map_err(|e| e.to_string())?;
+                                revwalk.hide(upstream_oid).map_err(|e| e.to_string())?;
+                                revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+                                for oid_result in revwalk.take(max_items) {
+                                    let oid = oid_result.map_err(|e| e.to_string())?;
+                                    let commit =
+                                        repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                    ahead_entries.push(commit_to_entry(commit));
+                                }
+
+                                let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                                revwalk.push(upstream_oid).map_err(|e| e.to_string())?;
+                                revwalk.hide(head_oid).map_err(|e| e.to_string())?;
+                                revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+                                for oid_result in revwalk.take(max_items) {
+                                    let oid = oid_result.map_err(|e| e.to_string())?;
+                                    let commit =
+                                        repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                    behind_entries.push(commit_to_entry(commit));
+                                }
                             }
                         }
                     }
                 }
             }
         }
-    }
 
-    Ok(GitLogResponse {
-        total,
-        entries,
-        ahead,
-        behind,
-        ahead_entries,
-        behind_entries,
-        upstream,
+        Ok(GitLogResponse {
+            total,
+            entries,
+            ahead,
+            behind,
+            ahead_entries,
+            behind_entries,
+            upstream,
+        })
     })
+    .await
 }
 
 #[tauri::command]
 pub(crate) async fn get_git_commit_diff(
     workspace_id: String,
+    git_root: Option<String>,
     sha: String,
+    intraline: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Vec<GitCommitDiff>, String> {
+    let intraline = intraline.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
             "get_git_commit_diff",
-            json!({ "workspaceId": workspace_id, "sha": sha }),
+            json!({
+                "workspaceId": workspace_id,
+                "gitRoot": git_root,
+                "sha": sha,
+                "intraline": intraline,
+            }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
@@ -1106,14 +1363,14 @@ pub(crate) async fn get_git_commit_diff(
         .ok_or("workspace not found")?
         .clone();
 
-    let repo_root = resolve_git_root(&entry)?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let oid = git2::Oid::from_str(&sha).map_err(|e| e.to_string())?;
     let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
     let commit_tree = commit.tree().map_err(|e| e.to_string())?;
     let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
 
-    let mut options = DiffOptions::new();
+    let mut options = display_diff_options(entry.settings.diff_ignore_whitespace);
     let diff = repo
         .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut options))
         .map_err(|e| e.to_string())?;
@@ -1167,6 +1424,8 @@ pub(crate) async fn get_git_commit_diff(
                 new_image_data,
                 old_image_mime: old_image_mime.map(str::to_string),
                 new_image_mime: new_image_mime.map(str::to_string),
+                whitespace_only: false,
+                intraline: None,
             });
             continue;
         }
@@ -1178,11 +1437,13 @@ pub(crate) async fn get_git_commit_diff(
         let Some(mut patch) = patch else {
             continue;
         };
+        let whitespace_only =
+            is_whitespace_only_patch(&patch, entry.settings.diff_ignore_whitespace);
         let content = match diff_patch_to_string(&mut patch) {
             Ok(content) => content,
             Err(_) => continue,
         };
-        if content.trim().is_empty() {
+        if content.trim().is_empty() && !whitespace_only {
             continue;
         }
         results.push(GitCommitDiff {
@@ -1195,8 +1456,15 @@ pub(crate) async fn get_git_commit_diff(
             new_image_data: None,
             old_image_mime: None,
             new_image_mime: None,
+            whitespace_only,
+            intraline: None,
         });
     }
+    if intraline {
+        for file in &mut results {
+            file.intraline = intraline_hunks(&file.diff);
+        }
+    }
 
     Ok(results)
 }
@@ -1263,8 +1531,9 @@ pub(crate) async fn get_github_issues(
     let repo_root = resolve_git_root(&entry)?;
     let repo_name = github_repo_from_path(&repo_root)?;
 
-    let output = Command::new("gh")
-        .args([
+    let stdout = github_cli::run_gh(
+        &repo_root,
+        &[
             "issue",
             "list",
             "--repo",
@@ -1273,48 +1542,28 @@ pub(crate) async fn get_github_issues(
             "50",
             "--json",
             "number,title,url,updatedAt",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
-        }
-        return Err(detail.to_string());
-    }
+        ],
+    )
+    .await?;
 
-    let issues: Vec<GitHubIssue> =
-        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+    let issues: Vec<GitHubIssue> = serde_json::from_slice(&stdout).map_err(|e| e.to_string())?;
 
     let search_query = format!("repo:{repo_name} is:issue is:open");
     let search_query = search_query.replace(' ', "+");
-    let total = match Command::new("gh")
-        .args([
-            "api",
-            &format!("/search/issues?q={search_query}"),
-            "--jq",
-            ".total_count",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-    {
-        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+    let search_endpoint = format!("/search/issues?q={search_query}");
+    let total = github_cli::run_gh(
+        &repo_root,
+        &["api", &search_endpoint, "--jq", ".total_count"],
+    )
+    .await
+    .ok()
+    .and_then(|stdout| {
+        String::from_utf8_lossy(&stdout)
             .trim()
             .parse::<usize>()
-            .unwrap_or(issues.len()),
-        _ => issues.len(),
-    };
+            .ok()
+    })
+    .unwrap_or(issues.len());
 
     Ok(GitHubIssuesResponse { total, issues })
 }
@@ -1344,8 +1593,9 @@ pub(crate) async fn get_github_pull_requests(
     let repo_root = resolve_git_root(&entry)?;
     let repo_name = github_repo_from_path(&repo_root)?;
 
-    let output = Command::new("gh")
-        .args([
+    let stdout = github_cli::run_gh(
+        &repo_root,
+        &[
             "pr",
             "list",
             "--repo",
@@ -1356,48 +1606,29 @@ pub(crate) async fn get_github_pull_requests(
             "50",
             "--json",
             "number,title,url,updatedAt,createdAt,body,headRefName,baseRefName,isDraft,author",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
-        }
-        return Err(detail.to_string());
-    }
+        ],
+    )
+    .await?;
 
     let pull_requests: Vec<GitHubPullRequest> =
-        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&stdout).map_err(|e| e.to_string())?;
 
     let search_query = format!("repo:{repo_name} is:pr is:open");
     let search_query = search_query.replace(' ', "+");
-    let total = match Command::new("gh")
-        .args([
-            "api",
-            &format!("/search/issues?q={search_query}"),
-            "--jq",
-            ".total_count",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-    {
-        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+    let search_endpoint = format!("/search/issues?q={search_query}");
+    let total = github_cli::run_gh(
+        &repo_root,
+        &["api", &search_endpoint, "--jq", ".total_count"],
+    )
+    .await
+    .ok()
+    .and_then(|stdout| {
+        String::from_utf8_lossy(&stdout)
             .trim()
             .parse::<usize>()
-            .unwrap_or(pull_requests.len()),
-        _ => pull_requests.len(),
-    };
+            .ok()
+    })
+    .unwrap_or(pull_requests.len());
 
     Ok(GitHubPullRequestsResponse {
         total,
@@ -1409,15 +1640,17 @@ pub(crate) async fn get_github_pull_requests(
 pub(crate) async fn get_github_pull_request_diff(
     workspace_id: String,
     pr_number: u64,
+    intraline: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Vec<GitHubPullRequestDiff>, String> {
+    let intraline = intraline.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
             "get_github_pull_request_diff",
-            json!({ "workspaceId": workspace_id, "prNumber": pr_number }),
+            json!({ "workspaceId": workspace_id, "prNumber": pr_number, "intraline": intraline }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
@@ -1431,8 +1664,9 @@ pub(crate) async fn get_github_pull_request_diff(
     let repo_root = resolve_git_root(&entry)?;
     let repo_name = github_repo_from_path(&repo_root)?;
 
-    let output = Command::new("gh")
-        .args([
+    let stdout = github_cli::run_gh(
+        &repo_root,
+        &[
             "pr",
             "diff",
             &pr_number.to_string(),
@@ -1440,28 +1674,18 @@ pub(crate) async fn get_github_pull_request_diff(
             &repo_name,
             "--color",
             "never",
-        ])
-        .current_dir(&repo_root)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
+        ],
+    )
+    .await?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
+    let diff_text = String::from_utf8_lossy(&stdout);
+    let mut results = parse_pr_diff(&diff_text);
+    if intraline {
+        for file in &mut results {
+            file.intraline = intraline_hunks(&file.diff);
         }
-        return Err(detail.to_string());
     }
-
-    let diff_text = String::from_utf8_lossy(&output.stdout);
-    Ok(parse_pr_diff(&diff_text))
+    Ok(results)
 }
 
 #[tauri::command]
@@ -1493,36 +1717,39 @@ pub(crate) async fn get_github_pull_request_comments(
     let comments_endpoint = format!("/repos/{repo_name}/issues/{pr_number}/comments?per_page=30");
     let jq_filter = r#"[.[] | {id, body, createdAt: .created_at, url: .html_url, author: (if .user then {login: .user.login} else null end)}]"#;
 
-    let output = Command::new("gh")
-        .args(["api", &comments_endpoint, "--jq", jq_filter])
-        .current_dir(&repo_root)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run gh: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
-        }
-        return Err(detail.to_string());
-    }
+    let stdout =
+        github_cli::run_gh(&repo_root, &["api", &comments_endpoint, "--jq", jq_filter]).await?;
 
     let comments: Vec<GitHubPullRequestComment> =
-        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&stdout).map_err(|e| e.to_string())?;
 
     Ok(comments)
 }
 
+#[tauri::command]
+pub(crate) async fn gh_auth_status(
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GhAuthStatus, String> {
+    let refresh = refresh.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "gh_auth_status",
+            json!({ "refresh": refresh }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(github_cli::gh_auth_status(refresh).await)
+}
+
 #[tauri::command]
 pub(crate) async fn list_git_branches(
     workspace_id: String,
+    git_root: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<serde_json::Value, String> {
@@ -1531,7 +1758,7 @@ pub(crate) async fn list_git_branches(
             &*state,
             app,
             "list_git_branches",
-            json!({ "workspaceId": workspace_id }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root }),
         )
         .await;
     }
@@ -1540,7 +1767,7 @@ pub(crate) async fn list_git_branches(
         .get(&workspace_id)
         .ok_or("workspace not found")?
         .clone();
-    let repo_root = resolve_git_root(&entry)?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let mut branches = Vec::new();
     let refs = repo
@@ -1567,6 +1794,7 @@ pub(crate) async fn list_git_branches(
 #[tauri::command]
 pub(crate) async fn checkout_git_branch(
     workspace_id: String,
+    git_root: Option<String>,
     name: String,
     state: State<'_, AppState>,
     app: AppHandle,
@@ -1576,7 +1804,7 @@ pub(crate) async fn checkout_git_branch(
             &*state,
             app,
             "checkout_git_branch",
-            json!({ "workspaceId": workspace_id, "name": name }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "name": name }),
         )
         .await?;
         return Ok(());
@@ -1586,7 +1814,8 @@ pub(crate) async fn checkout_git_branch(
         .get(&workspace_id)
         .ok_or("workspace not found")?
         .clone();
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "check out a branch")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     checkout_branch(&repo, &name).map_err(|e| e.to_string())
 }
@@ -1594,6 +1823,7 @@ pub(crate) async fn checkout_git_branch(
 #[tauri::command]
 pub(crate) async fn create_git_branch(
     workspace_id: String,
+    git_root: Option<String>,
     name: String,
     state: State<'_, AppState>,
     app: AppHandle,
@@ -1603,7 +1833,7 @@ pub(crate) async fn create_git_branch(
             &*state,
             app,
             "create_git_branch",
-            json!({ "workspaceId": workspace_id, "name": name }),
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "name": name }),
         )
         .await?;
         return Ok(());
@@ -1613,7 +1843,8 @@ pub(crate) async fn create_git_branch(
         .get(&workspace_id)
         .ok_or("workspace not found")?
         .clone();
-    let repo_root = resolve_git_root(&entry)?;
+    ensure_writable(&entry, "create a branch")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let head = repo.head().map_err(|e| e.to_string())?;
     let target = head.peel_to_commit().map_err(|e| e.to_string())?;
@@ -1622,6 +1853,251 @@ pub(crate) async fn create_git_branch(
     checkout_branch(&repo, &name).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub(crate) async fn list_git_tags(
+    workspace_id: String,
+    pattern: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<GitTagInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_git_tags",
+            json!({ "workspaceId": workspace_id, "pattern": pattern, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    git_tags::list_tags(&repo_root, pattern.as_deref(), limit)
+}
+
+#[tauri::command]
+pub(crate) async fn create_git_tag(
+    workspace_id: String,
+    name: String,
+    message: Option<String>,
+    target: Option<String>,
+    push: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitTagInfo, String> {
+    let push = push.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "create_git_tag",
+            json!({
+                "workspaceId": workspace_id,
+                "name": name,
+                "message": message,
+                "target": target,
+                "push": push,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    ensure_writable(&entry, "create a tag")?;
+    let repo_root = resolve_git_root(&entry)?;
+    let tag = git_tags::create_tag(&repo_root, &name, message.as_deref(), target.as_deref())?;
+    if push {
+        push_tag(&repo_root, &tag.name)
+            .await
+            .map_err(|err| format!("Tag {} was created but not pushed: {err}", tag.name))?;
+    }
+    Ok(tag)
+}
+
+#[tauri::command]
+pub(crate) async fn delete_git_tag(
+    workspace_id: String,
+    name: String,
+    override_protection: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let override_protection = override_protection.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "delete_git_tag",
+            json!({
+                "workspaceId": workspace_id,
+                "name": name,
+                "overrideProtection": override_protection,
+            }),
+        )
+        .await?;
+        return Ok(());
+    }
+    let (entry, parent_entry) = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?;
+        let parent_entry = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned();
+        (entry, parent_entry)
+    };
+    ensure_writable(&entry, "delete a tag")?;
+    let repo_root = resolve_git_root(&entry)?;
+    {
+        let settings = state.app_settings.lock().await;
+        let patterns = effective_protected_branches(&entry, parent_entry.as_ref(), &settings);
+        ensure_tag_not_protected(&name, &patterns, override_protection, "delete tag")?;
+    }
+    git_tags::delete_tag(&repo_root, &name)
+}
+
+#[tauri::command]
+pub(crate) async fn stash_git(
+    workspace_id: String,
+    git_root: Option<String>,
+    message: Option<String>,
+    include_untracked: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitStashEntry, String> {
+    let include_untracked = include_untracked.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "stash_git",
+            json!({
+                "workspaceId": workspace_id,
+                "gitRoot": git_root,
+                "message": message,
+                "includeUntracked": include_untracked,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    ensure_writable(&entry, "stash changes")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    git_stash::stash_changes(&repo_root, message.as_deref(), include_untracked).await
+}
+
+#[tauri::command]
+pub(crate) async fn list_git_stashes(
+    workspace_id: String,
+    git_root: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<GitStashEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_git_stashes",
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    git_stash::list_stashes(&repo_root).await
+}
+
+#[tauri::command]
+pub(crate) async fn pop_git_stash(
+    workspace_id: String,
+    git_root: Option<String>,
+    index: usize,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "pop_git_stash",
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "index": index }),
+        )
+        .await?;
+        return Ok(());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    ensure_writable(&entry, "pop a stash")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    git_stash::pop_stash(&repo_root, index).await
+}
+
+#[tauri::command]
+pub(crate) async fn drop_git_stash(
+    workspace_id: String,
+    git_root: Option<String>,
+    index: usize,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "drop_git_stash",
+            json!({ "workspaceId": workspace_id, "gitRoot": git_root, "index": index }),
+        )
+        .await?;
+        return Ok(());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    ensure_writable(&entry, "drop a stash")?;
+    let repo_root = resolve_git_sub_root(&entry, git_root.as_deref())?;
+    git_stash::drop_stash(&repo_root, index).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1644,7 +2120,7 @@ mod tests {
         index.add_path(Path::new("staged.txt")).expect("add path");
         index.write().expect("write index");
 
-        let diff = collect_workspace_diff(&root).expect("collect diff");
+        let diff = collect_workspace_diff(&root, false).expect("collect diff");
         assert!(diff.contains("staged.txt"));
         assert!(diff.contains("staged"));
     }
@@ -1655,7 +2131,7 @@ mod tests {
         let file_path = root.join("unstaged.txt");
         fs::write(&file_path, "unstaged\n").expect("write unstaged file");
 
-        let diff = collect_workspace_diff(&root).expect("collect diff");
+        let diff = collect_workspace_diff(&root, false).expect("collect diff");
         assert!(diff.contains("unstaged.txt"));
         assert!(diff.contains("unstaged"));
     }
@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use git2::{BranchType, Repository};
+
+use crate::git_patch::run_git;
+use crate::git_status_cache;
+use crate::types::GitCommitOutcome;
+
+pub(crate) const NOTHING_TO_AMEND_ERROR: &str = "There is no commit to amend yet.";
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CommitOptions {
+    /// Rewrite HEAD instead of adding a commit on top of it.
+    pub(crate) amend: bool,
+    /// Add a `Signed-off-by` trailer.
+    pub(crate) signoff: bool,
+    /// Skip the pre-commit and commit-msg hooks.
+    pub(crate) no_verify: bool,
+}
+
+fn commit_args<'a>(message: &'a str, options: CommitOptions) -> Vec<&'a str> {
+    let mut args = vec!["commit"];
+    if options.amend {
+        args.push("--amend");
+    }
+    if options.signoff {
+        args.push("--signoff");
+    }
+    if options.no_verify {
+        args.push("--no-verify");
+    }
+    args.extend(["-m", message]);
+    args
+}
+
+/// Whether the HEAD commit is already on the branch's upstream, i.e. the
+/// branch is not ahead of it, as counted for `get_git_log`.
+fn head_is_pushed(repo: &Repository) -> Result<bool, String> {
+    let head = repo.head().map_err(|e| e.to_string())?;
+    if !head.is_branch() {
+        return Ok(false);
+    }
+    let (Some(head_oid), Some(branch_name)) = (head.target(), head.shorthand()) else {
+        return Ok(false);
+    };
+    let Ok(branch) = repo.find_branch(branch_name, BranchType::Local) else {
+        return Ok(false);
+    };
+    let Some(upstream_oid) = branch
+        .upstream()
+        .ok()
+        .and_then(|upstream| upstream.get().target())
+    else {
+        return Ok(false);
+    };
+    let (ahead, _behind) = repo
+        .graph_ahead_behind(head_oid, upstream_oid)
+        .map_err(|e| e.to_string())?;
+    Ok(ahead == 0)
+}
+
+/// Commits what is staged. Git's own config still applies, so repositories
+/// with `commit.gpgSign` set get signed commits.
+pub(crate) async fn commit(
+    repo_root: &Path,
+    message: &str,
+    options: CommitOptions,
+) -> Result<GitCommitOutcome, String> {
+    let amended_pushed_commit = if options.amend {
+        let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+        if repo.head().and_then(|head| head.peel_to_commit()).is_err() {
+            return Err(NOTHING_TO_AMEND_ERROR.to_string());
+        }
+        head_is_pushed(&repo)?
+    } else {
+        false
+    };
+    let result = run_git(repo_root, &commit_args(message, options), None, false).await;
+    git_status_cache::invalidate(repo_root);
+    result?;
+    Ok(GitCommitOutcome {
+        amended_pushed_commit,
+    })
+}
+
+/// The full message of the HEAD commit, or `None` before the first commit.
+pub(crate) fn last_commit_message(repo_root: &Path) -> Result<Option<String>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) else {
+        return Ok(None);
+    };
+    Ok(Some(
+        String::from_utf8_lossy(commit.message_bytes())
+            .trim_end()
+            .to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{commit, last_commit_message, CommitOptions, NOTHING_TO_AMEND_ERROR};
+    use crate::test_git::{git, init_repo};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn amend_rewrites_the_tip_and_flags_a_pushed_commit() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path().join("repo");
+        std::fs::create_dir(&root).unwrap();
+        init_repo(&root);
+        std::fs::write(root.join("a.txt"), "one\n").unwrap();
+        git(&root, &["add", "."]);
+
+        let amend = CommitOptions {
+            amend: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            commit(&root, "first", amend).await.unwrap_err(),
+            NOTHING_TO_AMEND_ERROR
+        );
+        assert_eq!(last_commit_message(&root).unwrap(), None);
+
+        commit(&root, "first", CommitOptions::default())
+            .await
+            .expect("commit");
+        std::fs::write(root.join("a.txt"), "two\n").unwrap();
+        git(&root, &["add", "."]);
+        commit(&root, "second", CommitOptions::default())
+            .await
+            .expect("commit");
+        let parent = git(&root, &["rev-parse", "HEAD~1"]);
+        let tip = git(&root, &["rev-parse", "HEAD"]);
+
+        let outcome = commit(&root, "second, reworded", amend)
+            .await
+            .expect("amend");
+        assert!(!outcome.amended_pushed_commit);
+        assert_ne!(git(&root, &["rev-parse", "HEAD"]), tip);
+        assert_eq!(git(&root, &["rev-parse", "HEAD~1"]), parent);
+        assert_eq!(
+            last_commit_message(&root).unwrap().as_deref(),
+            Some("second, reworded")
+        );
+
+        let remote = dir.path().join("remote.git");
+        git(dir.path(), &["init", "-q", "--bare", "remote.git"]);
+        git(
+            &root,
+            &["remote", "add", "origin", &remote.to_string_lossy()],
+        );
+        git(&root, &["push", "-q", "-u", "origin", "HEAD"]);
+        let outcome = commit(
+            &root,
+            "second, signed off",
+            CommitOptions {
+                amend: true,
+                signoff: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("amend pushed");
+        assert!(outcome.amended_pushed_commit);
+        assert!(last_commit_message(&root)
+            .unwrap()
+            .unwrap()
+            .contains("Signed-off-by: t <t@example.com>"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn no_verify_bypasses_a_failing_hook() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        init_repo(root);
+        let hook = root.join(".git/hooks/pre-commit");
+        std::fs::create_dir_all(hook.parent().unwrap()).unwrap();
+        std::fs::write(&hook, "#!/bin/sh\necho 'hook says no' >&2\nexit 1\n").unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(root.join("a.txt"), "one\n").unwrap();
+        git(root, &["add", "."]);
+
+        let err = commit(root, "blocked", CommitOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.contains("hook says no"), "{err}");
+        assert_eq!(last_commit_message(root).unwrap(), None);
+
+        commit(
+            root,
+            "skips hooks",
+            CommitOptions {
+                no_verify: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("commit");
+        assert_eq!(
+            last_commit_message(root).unwrap().as_deref(),
+            Some("skips hooks")
+        );
+    }
+}
@@ -0,0 +1,321 @@
+//! Hunk-level staging. Hunks come from the staged (HEAD to index) and
+//! unstaged (index to worktree) diffs rather than the combined diff
+//! `get_git_diffs` shows, so each one applies to the index on its own.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, Patch, Repository};
+
+use crate::git_patch::run_git;
+use crate::git_status_cache;
+use crate::types::GitDiffHunk;
+use crate::utils::normalize_git_path;
+
+/// The error a hunk that no longer matches the current diff fails with.
+pub(crate) const STALE_HUNK_ERROR: &str = "hunk is stale, refresh diffs";
+
+/// Picks a hunk by its position, its header, or both; with both, the hunk at
+/// `index` must still have `header`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HunkSelector {
+    pub(crate) index: Option<usize>,
+    pub(crate) header: Option<String>,
+}
+
+struct FilePatch {
+    path: String,
+    /// Everything before the first hunk, ready to prefix a single hunk.
+    header: Vec<u8>,
+    hunks: Vec<(GitDiffHunk, Vec<u8>)>,
+}
+
+fn staged_diff(repo: &Repository) -> Result<Diff<'_>, String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut DiffOptions::new()))
+        .map_err(|e| e.to_string())?;
+    // A staged rename then shows as changes to the new path instead of a
+    // whole-file deletion and addition.
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(|e| e.to_string())?;
+    Ok(diff)
+}
+
+fn unstaged_diff<'a>(repo: &'a Repository, path: Option<&str>) -> Result<Diff<'a>, String> {
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    if let Some(path) = path {
+        options.pathspec(path).disable_pathspec_match(true);
+    }
+    repo.diff_index_to_workdir(None, Some(&mut options))
+        .map_err(|e| e.to_string())
+}
+
+fn file_patches(diff: &Diff, staged: bool) -> Vec<FilePatch> {
+    let mut files = Vec::new();
+    for delta_index in 0..diff.deltas().len() {
+        let Ok(Some(mut patch)) = Patch::from_diff(diff, delta_index) else {
+            continue;
+        };
+        if let Some(file) = split_patch(&mut patch, staged) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+/// Splits a file's patch into its header and one buffer per hunk, keeping the
+/// bytes as they are so CRLF lines and missing final newlines survive.
+fn split_patch(patch: &mut Patch, staged: bool) -> Option<FilePatch> {
+    let delta = patch.delta();
+    let renamed = delta.status() == Delta::Renamed;
+    let raw_path = delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())?
+        .to_string_lossy()
+        .to_string();
+    let buf = patch.to_buf().ok()?;
+    let mut header = Vec::new();
+    let mut bodies: Vec<Vec<u8>> = Vec::new();
+    for line in buf.split_inclusive(|byte| *byte == b'\n') {
+        if line.starts_with(b"@@") {
+            bodies.push(line.to_vec());
+        } else if let Some(body) = bodies.last_mut() {
+            body.extend_from_slice(line);
+        } else {
+            header.extend_from_slice(line);
+        }
+    }
+    if bodies.is_empty() || bodies.len() != patch.num_hunks() {
+        return None;
+    }
+    if renamed {
+        // Only the content moves between index and worktree; the rename
+        // itself stays as it is.
+        header =
+            format!("diff --git a/{raw_path} b/{raw_path}\n--- a/{raw_path}\n+++ b/{raw_path}\n")
+                .into_bytes();
+    }
+    let mut hunks = Vec::new();
+    for (index, body) in bodies.into_iter().enumerate() {
+        let (hunk, _) = patch.hunk(index).ok()?;
+        hunks.push((
+            GitDiffHunk {
+                index,
+                staged,
+                header: String::from_utf8_lossy(hunk.header())
+                    .trim_end()
+                    .to_string(),
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                content: String::from_utf8_lossy(&body).into_owned(),
+            },
+            body,
+        ));
+    }
+    Some(FilePatch {
+        path: normalize_git_path(&raw_path),
+        header,
+        hunks,
+    })
+}
+
+/// Staged and unstaged hunks of every changed file, by path.
+pub(crate) fn collect_hunks(
+    repo: &Repository,
+) -> Result<HashMap<String, Vec<GitDiffHunk>>, String> {
+    let mut by_path: HashMap<String, Vec<GitDiffHunk>> = HashMap::new();
+    let staged = file_patches(&staged_diff(repo)?, true);
+    let unstaged = file_patches(&unstaged_diff(repo, None)?, false);
+    for file in staged.into_iter().chain(unstaged) {
+        by_path
+            .entry(file.path)
+            .or_default()
+            .extend(file.hunks.into_iter().map(|(hunk, _)| hunk));
+    }
+    Ok(by_path)
+}
+
+/// A patch holding only the selected hunk of `path`, from the staged diff
+/// when `staged` and the unstaged one otherwise.
+pub(crate) fn hunk_patch(
+    repo_root: &Path,
+    path: &str,
+    staged: bool,
+    selector: &HunkSelector,
+) -> Result<Vec<u8>, String> {
+    if selector.index.is_none() && selector.header.is_none() {
+        return Err("A hunk index or header is required.".to_string());
+    }
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let diff = if staged {
+        staged_diff(&repo)?
+    } else {
+        unstaged_diff(&repo, Some(path))?
+    };
+    let path = normalize_git_path(path);
+    let file = file_patches(&diff, staged)
+        .into_iter()
+        .find(|file| file.path == path)
+        .ok_or(STALE_HUNK_ERROR)?;
+    let header_matches = |hunk: &GitDiffHunk| {
+        selector
+            .header
+            .as_deref()
+            .is_none_or(|header| header.trim_end() == hunk.header)
+    };
+    let (_, body) = match selector.index {
+        Some(index) => file
+            .hunks
+            .get(index)
+            .filter(|(hunk, _)| header_matches(hunk)),
+        None => file.hunks.iter().find(|(hunk, _)| header_matches(hunk)),
+    }
+    .ok_or(STALE_HUNK_ERROR)?;
+    let mut patch = file.header.clone();
+    patch.extend_from_slice(body);
+    Ok(patch)
+}
+
+/// Stages one unstaged hunk, or with `unstage` moves one staged hunk back to
+/// the worktree side. The worktree itself is never touched.
+pub(crate) async fn apply_hunk(
+    repo_root: &Path,
+    path: &str,
+    selector: &HunkSelector,
+    unstage: bool,
+) -> Result<(), String> {
+    let patch = hunk_patch(repo_root, path, unstage, selector)?;
+    let mut args = vec!["apply", "--cached", "--whitespace=nowarn"];
+    if unstage {
+        args.push("--reverse");
+    }
+    args.push("-");
+    // The hunk was just taken from the current diff, so a failure here is
+    // git's own (a locked index, say) and is reported as such.
+    let result = run_git(repo_root, &args, Some(&patch), false).await;
+    git_status_cache::invalidate(repo_root);
+    result.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_hunk, collect_hunks, HunkSelector, STALE_HUNK_ERROR};
+    use crate::test_git::{git, repo_with};
+    use git2::{Repository, Status};
+    use std::path::Path;
+
+    fn numbered_lines(eol: &str) -> String {
+        (1..=30).map(|line| format!("line {line}{eol}")).collect()
+    }
+
+    fn index(position: usize) -> HunkSelector {
+        HunkSelector {
+            index: Some(position),
+            header: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn staging_one_of_two_hunks_leaves_the_file_partly_staged() {
+        let repo = repo_with("file.txt", &numbered_lines("\n"));
+        let root = repo.path();
+        let edited = numbered_lines("\n")
+            .replace("line 2\n", "line two\n")
+            .replace("line 28\n", "line twenty-eight\n");
+        std::fs::write(root.join("file.txt"), edited).unwrap();
+
+        let hunks = collect_hunks(&Repository::open(root).unwrap()).unwrap();
+        let file_hunks = &hunks["file.txt"];
+        assert_eq!(file_hunks.len(), 2);
+        assert!(file_hunks.iter().all(|hunk| !hunk.staged));
+        assert_eq!(file_hunks[1].old_start, 25);
+
+        apply_hunk(root, "file.txt", &index(1), false)
+            .await
+            .expect("stage hunk");
+        let status = Repository::open(root)
+            .unwrap()
+            .status_file(Path::new("file.txt"))
+            .unwrap();
+        assert!(status.contains(Status::INDEX_MODIFIED | Status::WT_MODIFIED));
+        let staged = git(root, &["diff", "--cached"]);
+        assert!(staged.contains("+line twenty-eight"));
+        assert!(!staged.contains("+line two\n"));
+
+        let hunks = collect_hunks(&Repository::open(root).unwrap()).unwrap();
+        let sides: Vec<bool> = hunks["file.txt"].iter().map(|hunk| hunk.staged).collect();
+        assert_eq!(sides, vec![true, false]);
+
+        // The unstaged hunk was re-numbered; its old header is gone.
+        let stale = HunkSelector {
+            index: Some(0),
+            header: Some(file_hunks[1].header.clone()),
+        };
+        assert_eq!(
+            apply_hunk(root, "file.txt", &stale, false)
+                .await
+                .unwrap_err(),
+            STALE_HUNK_ERROR
+        );
+
+        // A failing `git apply` keeps git's reason rather than blaming the hunk.
+        std::fs::write(root.join(".git").join("index.lock"), "").unwrap();
+        let err = apply_hunk(root, "file.txt", &index(0), true)
+            .await
+            .unwrap_err();
+        assert!(err.contains("index.lock"), "{err}");
+        std::fs::remove_file(root.join(".git").join("index.lock")).unwrap();
+
+        apply_hunk(root, "file.txt", &index(0), true)
+            .await
+            .expect("unstage hunk");
+        assert!(git(root, &["diff", "--cached"]).is_empty());
+        assert!(git(root, &["diff"]).contains("+line twenty-eight"));
+    }
+
+    #[tokio::test]
+    async fn crlf_lines_and_renamed_files_stage_by_hunk() {
+        let repo = repo_with("crlf.txt", &numbered_lines("\r\n"));
+        let root = repo.path();
+        let edited = numbered_lines("\r\n").replace("line 3\r\n", "line three\r\n");
+        std::fs::write(root.join("crlf.txt"), edited).unwrap();
+        let header = collect_hunks(&Repository::open(root).unwrap()).unwrap()["crlf.txt"][0]
+            .header
+            .clone();
+        let by_header = HunkSelector {
+            index: None,
+            header: Some(header),
+        };
+        apply_hunk(root, "crlf.txt", &by_header, false)
+            .await
+            .expect("stage crlf hunk");
+        assert!(git(root, &["diff", "--cached"]).contains("+line three\r\n"));
+        assert!(git(root, &["diff"]).is_empty());
+
+        git(root, &["commit", "-q", "-m", "three"]);
+        git(root, &["mv", "crlf.txt", "moved.txt"]);
+        let edited = numbered_lines("\r\n")
+            .replace("line 3\r\n", "line three\r\n")
+            .replace("line 29\r\n", "line 29 moved\r\n");
+        std::fs::write(root.join("moved.txt"), edited).unwrap();
+        git(root, &["add", "moved.txt"]);
+
+        let hunks = collect_hunks(&Repository::open(root).unwrap()).unwrap();
+        assert_eq!(hunks["moved.txt"].len(), 1);
+        apply_hunk(root, "moved.txt", &index(0), true)
+            .await
+            .expect("unstage renamed hunk");
+        // The rename stays staged; only the edit moved back to the worktree.
+        let staged = git(root, &["diff", "--cached", "-M", "--name-status"]);
+        assert!(staged.starts_with("R100"), "{staged}");
+        assert!(git(root, &["diff"]).contains("+line 29 moved\r\n"));
+    }
+}
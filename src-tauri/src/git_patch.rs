@@ -0,0 +1,139 @@
+use std::path::Path;
+use std::process::{Output, Stdio};
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::utils::{git_env_path, resolve_git_binary};
+
+fn null_device_path() -> &'static str {
+    if cfg!(windows) {
+        "NUL"
+    } else {
+        "/dev/null"
+    }
+}
+
+pub(crate) fn git_failure_detail(stdout: &[u8], stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    let stdout = String::from_utf8_lossy(stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if detail.is_empty() {
+        "Git command failed.".to_string()
+    } else {
+        detail.to_string()
+    }
+}
+
+/// Runs git, optionally feeding `stdin`. `git diff` exits 1 when there are
+/// differences, so `allow_exit_one` treats that as success.
+pub(crate) async fn run_git(
+    repo_root: &Path,
+    args: &[&str],
+    stdin: Option<&[u8]>,
+    allow_exit_one: bool,
+) -> Result<Vec<u8>, String> {
+    let output = run_git_output(repo_root, args, stdin).await?;
+    if output.status.success() || (allow_exit_one && output.status.code() == Some(1)) {
+        Ok(output.stdout)
+    } else {
+        Err(git_failure_detail(&output.stdout, &output.stderr))
+    }
+}
+
+/// Runs git and returns its exit status and both streams, for commands such
+/// as `git apply --check` that report on stderr even when they succeed.
+pub(crate) async fn run_git_output(
+    repo_root: &Path,
+    args: &[&str],
+    stdin: Option<&[u8]>,
+) -> Result<Output, String> {
+    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
+    let mut child = Command::new(git_bin)
+        .args(args)
+        .current_dir(repo_root)
+        .env("PATH", git_env_path())
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input)
+            .await
+            .map_err(|e| format!("Failed to write git input: {e}"))?;
+    }
+    child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))
+}
+
+/// Untracked, non-ignored files, optionally limited to `paths`.
+pub(crate) async fn list_untracked_files(
+    repo_root: &Path,
+    paths: Option<&[String]>,
+) -> Result<Vec<String>, String> {
+    let mut args = vec!["ls-files", "--others", "--exclude-standard", "-z"];
+    if let Some(paths) = paths {
+        args.push("--");
+        args.extend(paths.iter().map(String::as_str));
+    }
+    let output = run_git(repo_root, &args, None, false).await?;
+    Ok(output
+        .split(|byte| *byte == 0)
+        .filter(|raw| !raw.is_empty())
+        .map(|raw| String::from_utf8_lossy(raw).to_string())
+        .collect())
+}
+
+/// Binary patch of staged then unstaged changes (plus untracked files as
+/// additions when `include_untracked`), optionally limited to `paths`.
+/// Applying it in order onto a clean checkout of HEAD reproduces the worktree.
+pub(crate) async fn collect_changes_patch(
+    repo_root: &Path,
+    paths: Option<&[String]>,
+    include_untracked: bool,
+) -> Result<Vec<u8>, String> {
+    let mut patch: Vec<u8> = Vec::new();
+    for cached in [true, false] {
+        let mut args = vec!["diff", "--binary", "--no-color"];
+        if cached {
+            args.push("--cached");
+        }
+        if let Some(paths) = paths {
+            args.push("--");
+            args.extend(paths.iter().map(String::as_str));
+        }
+        patch.extend_from_slice(&run_git(repo_root, &args, None, true).await?);
+    }
+    if include_untracked {
+        for path in list_untracked_files(repo_root, paths).await? {
+            let diff = run_git(
+                repo_root,
+                &[
+                    "diff",
+                    "--binary",
+                    "--no-color",
+                    "--no-index",
+                    "--",
+                    null_device_path(),
+                    &path,
+                ],
+                None,
+                true,
+            )
+            .await?;
+            patch.extend_from_slice(&diff);
+        }
+    }
+    Ok(patch)
+}
@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use crate::git_patch::{git_failure_detail, run_git, run_git_output};
+use crate::git_status_cache;
+use crate::types::GitStashEntry;
+
+/// Prefix of the error a pop fails with when the stash does not apply
+/// cleanly. The stash is kept in that case.
+pub(crate) const STASH_CONFLICT_PREFIX: &str = "stash_conflict:";
+
+fn stash_ref(index: usize) -> String {
+    format!("stash@{{{index}}}")
+}
+
+fn parse_stash_list(output: &str) -> Vec<GitStashEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\0');
+            let reference = fields.next()?;
+            let date = fields.next()?.parse().ok()?;
+            let relative_date = fields.next()?.to_string();
+            let message = fields.next()?.to_string();
+            let index = reference
+                .strip_prefix("stash@{")?
+                .strip_suffix('}')?
+                .parse()
+                .ok()?;
+            Some(GitStashEntry {
+                index,
+                message,
+                date,
+                relative_date,
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn list_stashes(repo_root: &Path) -> Result<Vec<GitStashEntry>, String> {
+    let output = run_git(
+        repo_root,
+        &["stash", "list", "--format=%gd%x00%ct%x00%cr%x00%gs"],
+        None,
+        false,
+    )
+    .await?;
+    Ok(parse_stash_list(&String::from_utf8_lossy(&output)))
+}
+
+/// Stashes staged and unstaged changes, plus untracked files when
+/// `include_untracked`, and returns the new entry.
+pub(crate) async fn stash_changes(
+    repo_root: &Path,
+    message: Option<&str>,
+    include_untracked: bool,
+) -> Result<GitStashEntry, String> {
+    let mut args = vec!["stash", "push"];
+    if include_untracked {
+        args.push("--include-untracked");
+    }
+    if let Some(message) = message.map(str::trim).filter(|value| !value.is_empty()) {
+        args.extend(["--message", message]);
+    }
+    let output = run_git(repo_root, &args, None, false).await;
+    git_status_cache::invalidate(repo_root);
+    // `git stash` succeeds without creating an entry when the tree is clean.
+    if String::from_utf8_lossy(&output?).contains("No local changes to save") {
+        return Err("No local changes to stash.".to_string());
+    }
+    list_stashes(repo_root)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Stash was not created.".to_string())
+}
+
+/// Applies and drops stash `index`. When it does not apply cleanly the error
+/// starts with `stash_conflict:` and the stash stays in the list.
+pub(crate) async fn pop_stash(repo_root: &Path, index: usize) -> Result<(), String> {
+    let reference = stash_ref(index);
+    let output = run_git_output(repo_root, &["stash", "pop", &reference], None).await;
+    git_status_cache::invalidate(repo_root);
+    let output = output?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let combined = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let conflicted: Vec<&str> = combined
+        .lines()
+        .filter_map(|line| {
+            line.split_once("Merge conflict in ")
+                .map(|(_, path)| path.trim())
+        })
+        .collect();
+    if !conflicted.is_empty() {
+        return Err(format!(
+            "{STASH_CONFLICT_PREFIX} {reference} conflicts with the working tree in {}. Resolve the conflict markers; the stash was kept.",
+            conflicted.join(", ")
+        ));
+    }
+    if combined.contains("would be overwritten") {
+        let blocked: Vec<&str> = combined
+            .lines()
+            .skip_while(|line| !line.contains("would be overwritten"))
+            .skip(1)
+            .take_while(|line| line.starts_with('\t'))
+            .map(str::trim)
+            .collect();
+        return Err(format!(
+            "{STASH_CONFLICT_PREFIX} {reference} would overwrite local changes to {}. Commit, stash or discard them first; nothing was changed.",
+            blocked.join(", ")
+        ));
+    }
+    Err(git_failure_detail(&output.stdout, &output.stderr))
+}
+
+pub(crate) async fn drop_stash(repo_root: &Path, index: usize) -> Result<(), String> {
+    run_git(
+        repo_root,
+        &["stash", "drop", "--quiet", &stash_ref(index)],
+        None,
+        false,
+    )
+    .await
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{drop_stash, list_stashes, pop_stash, stash_changes, STASH_CONFLICT_PREFIX};
+    use crate::test_git::{git, repo_with};
+
+    #[tokio::test]
+    async fn stashes_untracked_files_and_pops_them_back() {
+        let repo = repo_with("tracked.txt", "one\n");
+        let root = repo.path();
+        std::fs::write(root.join("tracked.txt"), "two\n").unwrap();
+        std::fs::write(root.join("new.txt"), "draft\n").unwrap();
+
+        let entry = stash_changes(root, Some("wip"), true).await.expect("stash");
+        assert_eq!(entry.index, 0);
+        assert!(entry.message.ends_with(": wip"), "{}", entry.message);
+        assert!(!entry.relative_date.is_empty());
+        assert!(!root.join("new.txt").exists());
+        assert_eq!(
+            std::fs::read_to_string(root.join("tracked.txt")).unwrap(),
+            "one\n"
+        );
+        assert!(stash_changes(root, None, false)
+            .await
+            .unwrap_err()
+            .contains("No local changes"));
+
+        pop_stash(root, 0).await.expect("pop");
+        assert_eq!(
+            std::fs::read_to_string(root.join("new.txt")).unwrap(),
+            "draft\n"
+        );
+        assert!(list_stashes(root).await.expect("list").is_empty());
+
+        stash_changes(root, None, false)
+            .await
+            .expect("stash tracked");
+        let stashes = list_stashes(root).await.expect("list");
+        assert_eq!(stashes.len(), 1);
+        // Without `includeUntracked` the untracked file stays behind.
+        assert!(root.join("new.txt").exists());
+        drop_stash(root, 0).await.expect("drop");
+        assert!(list_stashes(root).await.expect("list").is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_conflicting_pop_keeps_the_stash_and_says_so() {
+        let repo = repo_with("tracked.txt", "one\n");
+        let root = repo.path();
+        std::fs::write(root.join("tracked.txt"), "stashed\n").unwrap();
+        stash_changes(root, None, false).await.expect("stash");
+        std::fs::write(root.join("tracked.txt"), "committed\n").unwrap();
+        git(root, &["commit", "-q", "-am", "change"]);
+
+        let err = pop_stash(root, 0).await.unwrap_err();
+        assert!(err.starts_with(STASH_CONFLICT_PREFIX), "{err}");
+        assert!(err.contains("tracked.txt"), "{err}");
+        assert_eq!(list_stashes(root).await.expect("list").len(), 1);
+
+        git(root, &["reset", "-q", "--hard"]);
+        std::fs::write(root.join("tracked.txt"), "local\n").unwrap();
+        let err = pop_stash(root, 0).await.unwrap_err();
+        assert!(err.starts_with(STASH_CONFLICT_PREFIX), "{err}");
+        assert!(
+            err.contains("would overwrite local changes to tracked.txt"),
+            "{err}"
+        );
+        assert_eq!(list_stashes(root).await.expect("list").len(), 1);
+    }
+}
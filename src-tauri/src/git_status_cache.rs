@@ -0,0 +1,289 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use git2::Repository;
+use serde_json::Value;
+
+/// Last `get_git_status` response per repository root.
+static CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedStatus>>> = OnceLock::new();
+/// Bumped by `invalidate`, so a status computed across an app action is not
+/// cached.
+static GENERATIONS: OnceLock<Mutex<HashMap<PathBuf, u64>>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq)]
+struct RepoState {
+    head: Option<String>,
+    index_mtime: Option<SystemTime>,
+    tracked: u64,
+}
+
+struct CachedStatus {
+    state: RepoState,
+    /// Paths listed in the response, re-checked on lookup so edits to
+    /// untracked files are noticed too.
+    listed: Vec<String>,
+    listed_hash: u64,
+    response: Value,
+}
+
+/// What the repository looked like before a status was computed.
+pub(crate) struct StatusProbe {
+    key: PathBuf,
+    root: PathBuf,
+    generation: u64,
+    state: RepoState,
+}
+
+fn cache_key(repo_root: &Path) -> PathBuf {
+    repo_root
+        .canonicalize()
+        .unwrap_or_else(|_| repo_root.to_path_buf())
+}
+
+fn generation(key: &Path) -> u64 {
+    let generations = GENERATIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    generations
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(key)
+        .copied()
+        .unwrap_or(0)
+}
+
+fn hash_stat(hasher: &mut DefaultHasher, root: &Path, relative: &str) {
+    relative.hash(hasher);
+    std::fs::symlink_metadata(root.join(relative))
+        .ok()
+        .map(|metadata| (metadata.modified().ok(), metadata.len()))
+        .hash(hasher);
+}
+
+/// Adds every directory above `relative`, up to the root (`""`).
+fn insert_ancestors(dirs: &mut BTreeSet<String>, relative: &str) {
+    let mut dir = relative;
+    while let Some((parent, _)) = dir.rsplit_once('/') {
+        dirs.insert(parent.to_string());
+        dir = parent;
+    }
+    dirs.insert(String::new());
+}
+
+/// Stats every tracked file and every directory above one. A new file or
+/// directory changes its parent's mtime, so untracked additions are caught
+/// without walking the tree.
+fn tracked_fingerprint(repo: &Repository, root: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let mut dirs = BTreeSet::new();
+    dirs.insert(String::new());
+    if let Ok(index) = repo.index() {
+        for entry in index.iter() {
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            hash_stat(&mut hasher, root, &path);
+            insert_ancestors(&mut dirs, &path);
+        }
+    }
+    for dir in &dirs {
+        hash_stat(&mut hasher, root, dir);
+    }
+    hasher.finish()
+}
+
+/// Like `tracked_fingerprint`, for the listed paths. Their ancestors include
+/// untracked directories, where a file added in a new subdirectory only
+/// changes the mtime of a directory further up.
+fn listed_fingerprint(root: &Path, listed: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let mut dirs = BTreeSet::new();
+    for path in listed {
+        hash_stat(&mut hasher, root, path);
+        insert_ancestors(&mut dirs, path);
+    }
+    for dir in &dirs {
+        hash_stat(&mut hasher, root, dir);
+    }
+    hasher.finish()
+}
+
+fn listed_paths(response: &Value) -> Vec<String> {
+    response
+        .get("files")
+        .and_then(|files| files.as_array())
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|file| file.get("path").and_then(|path| path.as_str()))
+                .map(|path| path.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Captures HEAD, the index mtime and the worktree fingerprint. `None` when
+/// the repository can't be opened; the caller then computes without caching.
+pub(crate) fn probe(repo_root: &Path) -> Option<StatusProbe> {
+    let key = cache_key(repo_root);
+    let generation = generation(&key);
+    let repo = Repository::open(repo_root).ok()?;
+    let head = repo.head().ok().map(|head| {
+        format!(
+            "{}@{}",
+            head.name().unwrap_or(""),
+            head.target().map(|oid| oid.to_string()).unwrap_or_default()
+        )
+    });
+    let index_mtime = std::fs::metadata(repo.path().join("index"))
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    let tracked = tracked_fingerprint(&repo, repo_root);
+    Some(StatusProbe {
+        key,
+        root: repo_root.to_path_buf(),
+        generation,
+        state: RepoState {
+            head,
+            index_mtime,
+            tracked,
+        },
+    })
+}
+
+/// The cached response when nothing changed since it was computed.
+pub(crate) fn cached(probe: &StatusProbe) -> Option<Value> {
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let (listed, listed_hash, response) = {
+        let cache = cache.lock().unwrap_or_else(|err| err.into_inner());
+        let entry = cache.get(&probe.key)?;
+        if entry.state != probe.state {
+            return None;
+        }
+        (
+            entry.listed.clone(),
+            entry.listed_hash,
+            entry.response.clone(),
+        )
+    };
+    (listed_fingerprint(&probe.root, &listed) == listed_hash).then_some(response)
+}
+
+/// Caches `response` unless the repository was invalidated since `probe`.
+pub(crate) fn store(probe: StatusProbe, response: &Value) {
+    if generation(&probe.key) != probe.generation {
+        return;
+    }
+    let listed = listed_paths(response);
+    let listed_hash = listed_fingerprint(&probe.root, &listed);
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    cache.lock().unwrap_or_else(|err| err.into_inner()).insert(
+        probe.key,
+        CachedStatus {
+            state: probe.state,
+            listed,
+            listed_hash,
+            response: response.clone(),
+        },
+    );
+}
+
+/// Drops the cached status after the app changes the repository itself.
+pub(crate) fn invalidate(repo_root: &Path) {
+    let key = cache_key(repo_root);
+    let generations = GENERATIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    *generations
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .entry(key.clone())
+        .or_insert(0) += 1;
+    if let Some(cache) = CACHE.get() {
+        cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cached, invalidate, probe, store};
+    use git2::{Repository, Signature};
+    use serde_json::json;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn commit_all(repo: &Repository, name: &str) {
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn reuses_status_until_the_worktree_changes() {
+        let dir = tempdir().expect("tempdir");
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "fn a() {}\n").unwrap();
+        commit_all(&repo, "src/lib.rs");
+
+        let clean = json!({ "files": [] });
+        store(probe(dir.path()).unwrap(), &clean);
+        assert_eq!(cached(&probe(dir.path()).unwrap()), Some(clean.clone()));
+
+        // Same length, different content: caught by the mtime.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(dir.path().join("src/lib.rs"), "fn b() {}\n").unwrap();
+        assert_eq!(cached(&probe(dir.path()).unwrap()), None);
+
+        let dirty = json!({ "files": [{ "path": "src/lib.rs" }] });
+        store(probe(dir.path()).unwrap(), &dirty);
+        assert_eq!(cached(&probe(dir.path()).unwrap()), Some(dirty));
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(dir.path().join("src/new.rs"), "").unwrap();
+        assert_eq!(cached(&probe(dir.path()).unwrap()), None);
+    }
+
+    #[test]
+    fn a_file_in_a_new_subdirectory_of_an_untracked_directory_is_noticed() {
+        let dir = tempdir().expect("tempdir");
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        commit_all(&repo, "a.txt");
+        std::fs::create_dir_all(dir.path().join("vendor/lib")).unwrap();
+        std::fs::write(dir.path().join("vendor/lib/x.rs"), "").unwrap();
+
+        let listed = json!({ "files": [{ "path": "vendor/lib/x.rs" }] });
+        store(probe(dir.path()).unwrap(), &listed);
+        assert_eq!(cached(&probe(dir.path()).unwrap()), Some(listed));
+
+        // Only `vendor` changes: neither the root nor `vendor/lib` does.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::create_dir(dir.path().join("vendor/bin")).unwrap();
+        std::fs::write(dir.path().join("vendor/bin/y.rs"), "").unwrap();
+        assert_eq!(cached(&probe(dir.path()).unwrap()), None);
+    }
+
+    #[test]
+    fn invalidation_drops_and_blocks_in_flight_results() {
+        let dir = tempdir().expect("tempdir");
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        commit_all(&repo, "a.txt");
+
+        let response = json!({ "files": [] });
+        store(probe(dir.path()).unwrap(), &response);
+        invalidate(dir.path());
+        assert_eq!(cached(&probe(dir.path()).unwrap()), None);
+
+        let in_flight = probe(dir.path()).unwrap();
+        invalidate(dir.path());
+        store(in_flight, &response);
+        assert_eq!(cached(&probe(dir.path()).unwrap()), None);
+    }
+}
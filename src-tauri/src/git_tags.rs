@@ -0,0 +1,211 @@
+use std::path::Path;
+
+use git2::{ObjectType, Reference, Repository};
+
+use crate::types::GitTagInfo;
+
+pub(crate) fn validate_tag_name(name: &str) -> Result<(), String> {
+    if name.trim() != name || name.is_empty() {
+        return Err("Tag name is required and cannot start or end with spaces.".to_string());
+    }
+    if !Reference::is_valid_name(&format!("refs/tags/{name}")) {
+        return Err(format!("Invalid tag name: {name}"));
+    }
+    Ok(())
+}
+
+fn tag_info(repo: &Repository, name: &str) -> Result<GitTagInfo, String> {
+    let reference = repo
+        .find_reference(&format!("refs/tags/{name}"))
+        .map_err(|e| e.to_string())?;
+    let object = reference.peel(ObjectType::Any).map_err(|e| e.to_string())?;
+    let commit = reference.peel_to_commit().ok();
+    let target = commit
+        .as_ref()
+        .map(|commit| commit.id().to_string())
+        .unwrap_or_else(|| object.id().to_string());
+    let commit_time = commit
+        .as_ref()
+        .map(|commit| commit.time().seconds())
+        .unwrap_or(0);
+
+    let tag = reference.target().and_then(|oid| repo.find_tag(oid).ok());
+    Ok(match tag {
+        Some(tag) => {
+            let tagger = tag.tagger();
+            GitTagInfo {
+                name: name.to_string(),
+                target,
+                annotated: true,
+                tagger: tagger
+                    .as_ref()
+                    .and_then(|signature| signature.name().map(|value| value.to_string())),
+                message: tag
+                    .message()
+                    .map(|message| message.trim_end().to_string())
+                    .filter(|message| !message.is_empty()),
+                date: tagger
+                    .as_ref()
+                    .map(|signature| signature.when().seconds())
+                    .unwrap_or(commit_time),
+            }
+        }
+        None => GitTagInfo {
+            name: name.to_string(),
+            target,
+            annotated: false,
+            tagger: None,
+            message: None,
+            date: commit_time,
+        },
+    })
+}
+
+/// Tags matching the fnmatch `pattern` (all tags when `None`), newest first.
+pub(crate) fn list_tags(
+    repo_root: &Path,
+    pattern: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Vec<GitTagInfo>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let pattern = pattern.map(str::trim).filter(|value| !value.is_empty());
+    let names = repo.tag_names(pattern).map_err(|e| e.to_string())?;
+    let mut tags = Vec::new();
+    for name in names.iter().flatten() {
+        tags.push(tag_info(&repo, name)?);
+    }
+    tags.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.name.cmp(&b.name)));
+    if let Some(limit) = limit {
+        tags.truncate(limit);
+    }
+    Ok(tags)
+}
+
+/// Creates an annotated tag when `message` is non-empty, a lightweight one
+/// otherwise. `target` is any revision and defaults to `HEAD`.
+pub(crate) fn create_tag(
+    repo_root: &Path,
+    name: &str,
+    message: Option<&str>,
+    target: Option<&str>,
+) -> Result<GitTagInfo, String> {
+    validate_tag_name(name)?;
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    if repo.find_reference(&format!("refs/tags/{name}")).is_ok() {
+        return Err(format!("Tag already exists: {name}"));
+    }
+    let target = target.map(str::trim).filter(|value| !value.is_empty());
+    let object = repo
+        .revparse_single(target.unwrap_or("HEAD"))
+        .and_then(|object| object.peel(ObjectType::Commit))
+        .map_err(|e| format!("Unknown tag target {}: {e}", target.unwrap_or("HEAD")))?;
+    match message.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(message) => {
+            let tagger = repo.signature().map_err(|e| e.to_string())?;
+            repo.tag(name, &object, &tagger, message, false)
+                .map_err(|e| e.to_string())?;
+        }
+        None => {
+            repo.tag_lightweight(name, &object, false)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tag_info(&repo, name)
+}
+
+pub(crate) fn delete_tag(repo_root: &Path, name: &str) -> Result<(), String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    repo.tag_delete(name).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_tag, delete_tag, list_tags, validate_tag_name};
+    use git2::{Repository, Signature, Time};
+    use std::path::{Path, PathBuf};
+
+    fn repo_with_commits() -> (PathBuf, Repository) {
+        let root =
+            std::env::temp_dir().join(format!("codex-monitor-tags-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create repo root");
+        let repo = Repository::init(&root).expect("init repo");
+        {
+            let mut config = repo.config().expect("config");
+            config.set_str("user.name", "Tagger").expect("user.name");
+            config
+                .set_str("user.email", "tagger@example.com")
+                .expect("user.email");
+        }
+        let mut parent = None;
+        for (index, seconds) in [1_700_000_000, 1_700_100_000].into_iter().enumerate() {
+            std::fs::write(root.join("file.txt"), format!("{index}\n")).expect("write file");
+            let mut repo_index = repo.index().expect("index");
+            repo_index
+                .add_path(Path::new("file.txt"))
+                .expect("add path");
+            let tree_id = repo_index.write_tree().expect("write tree");
+            let tree = repo.find_tree(tree_id).expect("tree");
+            let sig = Signature::new("Test", "test@example.com", &Time::new(seconds, 0))
+                .expect("signature");
+            let parents: Vec<_> = parent.iter().collect();
+            let oid = repo
+                .commit(Some("HEAD"), &sig, &sig, "commit", &tree, &parents)
+                .expect("commit");
+            parent = Some(repo.find_commit(oid).expect("find commit"));
+        }
+        drop(parent);
+        (root, repo)
+    }
+
+    #[test]
+    fn creates_lists_and_deletes_tags() {
+        let (root, _repo) = repo_with_commits();
+        let light = create_tag(&root, "build-1", None, Some("HEAD~1")).expect("lightweight");
+        assert!(!light.annotated);
+        assert_eq!(light.date, 1_700_000_000);
+
+        let annotated =
+            create_tag(&root, "v1.0.0", Some("First release\n"), None).expect("annotated");
+        assert!(annotated.annotated);
+        assert_eq!(annotated.tagger.as_deref(), Some("Tagger"));
+        assert_eq!(annotated.message.as_deref(), Some("First release"));
+
+        let names: Vec<_> = list_tags(&root, None, None)
+            .expect("list")
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["v1.0.0", "build-1"]);
+        assert_eq!(
+            list_tags(&root, Some("v*"), Some(5)).expect("list").len(),
+            1
+        );
+
+        assert!(create_tag(&root, "v1.0.0", None, None)
+            .unwrap_err()
+            .contains("already exists"));
+        delete_tag(&root, "v1.0.0").expect("delete");
+        assert_eq!(list_tags(&root, None, None).expect("list").len(), 1);
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_invalid_tag_names() {
+        assert!(validate_tag_name("v1.2.3").is_ok());
+        assert!(validate_tag_name("release/2024-01").is_ok());
+        for name in [
+            "",
+            " v1",
+            "bad..name",
+            "ends.lock",
+            "has space",
+            "x~1",
+            "a:b",
+        ] {
+            assert!(
+                validate_tag_name(name).is_err(),
+                "{name} should be rejected"
+            );
+        }
+    }
+}
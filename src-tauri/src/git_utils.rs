@@ -1,10 +1,10 @@
 use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
-use git2::{DiffOptions, Repository, Tree};
+use git2::{DiffOptions, Repository, Status, StatusOptions, Tree};
 use ignore::WalkBuilder;
 
-use crate::types::{GitLogEntry, WorkspaceEntry};
+use crate::types::{GitLogEntry, IntralineHunk, IntralinePair, WorkspaceEntry};
 use crate::utils::normalize_git_path;
 
 pub(crate) fn commit_to_entry(commit: git2::Commit) -> GitLogEntry {
@@ -19,13 +19,17 @@ pub(crate) fn commit_to_entry(commit: git2::Commit) -> GitLogEntry {
     }
 }
 
+/// Switches to local branch `name`. The worktree is checked out before HEAD
+/// moves, so local changes the switch would overwrite fail it and leave HEAD
+/// and the files as they were. Other local changes are carried over, as with
+/// `git switch`.
 pub(crate) fn checkout_branch(repo: &Repository, name: &str) -> Result<(), git2::Error> {
     let refname = format!("refs/heads/{name}");
-    repo.set_head(&refname)?;
+    let target = repo.find_reference(&refname)?.peel_to_commit()?;
     let mut options = git2::build::CheckoutBuilder::new();
     options.safe();
-    repo.checkout_head(Some(&mut options))?;
-    Ok(())
+    repo.checkout_tree(target.as_object(), Some(&mut options))?;
+    repo.set_head(&refname)
 }
 
 pub(crate) fn diff_stats_for_path(
@@ -63,6 +67,46 @@ pub(crate) fn diff_stats_for_path(
     Ok((additions, deletions))
 }
 
+/// Line counts and patch size for one file of a diff.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DiffFileStat {
+    pub(crate) path: String,
+    pub(crate) additions: u64,
+    pub(crate) deletions: u64,
+    pub(crate) bytes: u64,
+}
+
+/// Per-file stats for every delta in `diff`. Binary files count with zero
+/// lines.
+pub(crate) fn diff_file_stats(diff: &git2::Diff) -> Result<Vec<DiffFileStat>, git2::Error> {
+    let mut stats = Vec::new();
+    for index in 0..diff.deltas().len() {
+        let Some(delta) = diff.get_delta(index) else {
+            continue;
+        };
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|path| normalize_git_path(&path.to_string_lossy()))
+            .unwrap_or_default();
+        let (additions, deletions, bytes) = match git2::Patch::from_diff(diff, index)? {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (additions, deletions, patch.size(false, false, false))
+            }
+            None => (0, 0, 0),
+        };
+        stats.push(DiffFileStat {
+            path,
+            additions: additions as u64,
+            deletions: deletions as u64,
+            bytes: bytes as u64,
+        });
+    }
+    Ok(stats)
+}
+
 pub(crate) fn diff_patch_to_string(patch: &mut git2::Patch) -> Result<String, git2::Error> {
     let buf = patch.to_buf()?;
     Ok(buf
@@ -71,6 +115,181 @@ pub(crate) fn diff_patch_to_string(patch: &mut git2::Patch) -> Result<String, gi
         .unwrap_or_else(|| String::from_utf8_lossy(&buf).to_string()))
 }
 
+/// Diff options for diffs that are shown or summarized, with `-w` semantics
+/// when the workspace sets `diffIgnoreWhitespace`. Staging and commits never
+/// go through these.
+pub(crate) fn display_diff_options(ignore_whitespace: bool) -> DiffOptions {
+    let mut options = DiffOptions::new();
+    if ignore_whitespace {
+        options.ignore_whitespace(true);
+    }
+    options
+}
+
+/// Whether a modified file changed only in whitespace, such as CRLF line
+/// endings replacing LF: the removed and added lines are the same once
+/// whitespace is dropped. A patch made with `ignore_whitespace` has no hunks
+/// for such a file.
+pub(crate) fn is_whitespace_only_patch(patch: &git2::Patch, ignore_whitespace: bool) -> bool {
+    let delta = patch.delta();
+    if delta.status() != git2::Delta::Modified {
+        return false;
+    }
+    if patch.num_hunks() == 0 {
+        return ignore_whitespace && delta.old_file().mode() == delta.new_file().mode();
+    }
+    let mut removed = String::new();
+    let mut added = String::new();
+    for hunk in 0..patch.num_hunks() {
+        let Ok(lines) = patch.num_lines_in_hunk(hunk) else {
+            return false;
+        };
+        for index in 0..lines {
+            let Ok(line) = patch.line_in_hunk(hunk, index) else {
+                return false;
+            };
+            let target = match line.origin() {
+                '-' => &mut removed,
+                '+' => &mut added,
+                _ => continue,
+            };
+            target.extend(
+                String::from_utf8_lossy(line.content())
+                    .chars()
+                    .filter(|ch| !ch.is_whitespace()),
+            );
+        }
+    }
+    removed == added
+}
+
+fn build_combined_diff(diff: &git2::Diff, ignore_whitespace: bool) -> String {
+    let mut combined_diff = String::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let path = delta.new_file().path().or_else(|| delta.old_file().path());
+        let Some(path) = path else {
+            continue;
+        };
+        let patch = match git2::Patch::from_diff(diff, index) {
+            Ok(patch) => patch,
+            Err(_) => continue,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        if ignore_whitespace && is_whitespace_only_patch(&patch, true) {
+            continue;
+        }
+        let content = match diff_patch_to_string(&mut patch) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        if !combined_diff.is_empty() {
+            combined_diff.push_str("\n\n");
+        }
+        combined_diff.push_str(&format!("=== {} ===\n", path.display()));
+        combined_diff.push_str(&content);
+    }
+    combined_diff
+}
+
+fn staged_diff(
+    repo: &Repository,
+    head_tree: Option<&Tree>,
+    ignore_whitespace: bool,
+) -> Result<String, String> {
+    let mut options = display_diff_options(ignore_whitespace);
+    let index = repo.index().map_err(|e| e.to_string())?;
+    let diff = repo
+        .diff_tree_to_index(head_tree, Some(&index), Some(&mut options))
+        .map_err(|e| e.to_string())?;
+    Ok(build_combined_diff(&diff, ignore_whitespace))
+}
+
+/// The staged diff, or the working tree diff when nothing is staged, as one
+/// text for commit message generation. With `ignore_whitespace`, files that
+/// changed only in whitespace are left out.
+pub(crate) fn collect_workspace_diff(
+    repo_root: &Path,
+    ignore_whitespace: bool,
+) -> Result<String, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let combined_diff = staged_diff(&repo, head_tree.as_ref(), ignore_whitespace)?;
+    if !combined_diff.trim().is_empty() {
+        return Ok(combined_diff);
+    }
+
+    let mut options = display_diff_options(ignore_whitespace);
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = match head_tree.as_ref() {
+        Some(tree) => repo
+            .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
+            .map_err(|e| e.to_string())?,
+        None => repo
+            .diff_tree_to_workdir_with_index(None, Some(&mut options))
+            .map_err(|e| e.to_string())?,
+    };
+    Ok(build_combined_diff(&diff, ignore_whitespace))
+}
+
+/// Only the staged changes, in the format of `collect_workspace_diff`.
+pub(crate) fn collect_staged_diff(
+    repo_root: &Path,
+    ignore_whitespace: bool,
+) -> Result<String, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    staged_diff(&repo, head_tree.as_ref(), ignore_whitespace)
+}
+
+/// Changes in the working tree that are not staged, untracked files
+/// included, in the format of `collect_workspace_diff`.
+pub(crate) fn collect_unstaged_diff(
+    repo_root: &Path,
+    ignore_whitespace: bool,
+) -> Result<String, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let mut options = display_diff_options(ignore_whitespace);
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = repo
+        .diff_index_to_workdir(None, Some(&mut options))
+        .map_err(|e| e.to_string())?;
+    Ok(build_combined_diff(&diff, ignore_whitespace))
+}
+
+/// What a commit changed against its first parent, in the format of
+/// `collect_workspace_diff`. `rev` is anything `git rev-parse` accepts, such
+/// as a short sha or `HEAD~1`.
+pub(crate) fn collect_commit_diff(
+    repo_root: &Path,
+    rev: &str,
+    ignore_whitespace: bool,
+) -> Result<String, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| e.to_string())?;
+    let commit_tree = commit.tree().map_err(|e| e.to_string())?;
+    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+    let mut options = display_diff_options(ignore_whitespace);
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut options))
+        .map_err(|e| e.to_string())?;
+    Ok(build_combined_diff(&diff, ignore_whitespace))
+}
+
 pub(crate) fn parse_github_repo(remote_url: &str) -> Option<String> {
     let trimmed = remote_url.trim();
     if trimmed.is_empty() {
@@ -121,13 +340,54 @@ pub(crate) fn resolve_git_root(entry: &WorkspaceEntry) -> Result<PathBuf, String
     }
 }
 
-fn should_skip_dir(name: &str) -> bool {
+pub(crate) fn should_skip_dir(name: &str) -> bool {
     matches!(
         name,
         ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
     )
 }
 
+pub(crate) fn has_git_marker(dir: &Path) -> bool {
+    let git_marker = dir.join(".git");
+    git_marker.is_dir() || git_marker.is_file()
+}
+
+/// Resolves the repository git commands run in. `git_root` is a nested repo as
+/// returned by `list_git_roots` (relative to the workspace folder); without
+/// one this is `resolve_git_root`. The sub-root must pass the same rules the
+/// scan applies, and may not escape the workspace through `..` or symlinks.
+pub(crate) fn resolve_git_sub_root(
+    entry: &WorkspaceEntry,
+    git_root: Option<&str>,
+) -> Result<PathBuf, String> {
+    let Some(sub_root) = git_root
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+    else {
+        return resolve_git_root(entry);
+    };
+    let normalized = normalize_git_path(sub_root);
+    let relative = Path::new(normalized.trim_end_matches('/'));
+    let well_formed = relative.components().all(|component| match component {
+        Component::Normal(name) => !should_skip_dir(&name.to_string_lossy()),
+        _ => false,
+    });
+    if !well_formed || relative.as_os_str().is_empty() {
+        return Err(format!("Invalid git root: {sub_root}"));
+    }
+    let base = PathBuf::from(&entry.path)
+        .canonicalize()
+        .map_err(|err| format!("Workspace folder not found: {err}"))?;
+    let candidate = base
+        .join(relative)
+        .canonicalize()
+        .map_err(|_| format!("Git root not found: {sub_root}"))?;
+    if candidate == base || !candidate.starts_with(&base) || !has_git_marker(&candidate) {
+        return Err(format!("Not a git root in this workspace: {sub_root}"));
+    }
+    Ok(candidate)
+}
+
 pub(crate) fn list_git_roots(root: &Path, max_depth: usize, max_results: usize) -> Vec<String> {
     if !root.is_dir() {
         return Vec::new();
@@ -166,8 +426,7 @@ pub(crate) fn list_git_roots(root: &Path, max_depth: usize, max_results: usize)
             continue;
         }
         let candidate = entry.path();
-        let git_marker = candidate.join(".git");
-        if !git_marker.is_dir() && !git_marker.is_file() {
+        if !has_git_marker(candidate) {
             continue;
         }
         let rel = match candidate.strip_prefix(root) {
@@ -216,3 +475,511 @@ pub(crate) fn image_mime_type(path: &str) -> Option<&'static str> {
     }
     None
 }
+
+/// The paths to stage or revert for `path`: both sides of a rename git
+/// reports for it, or just `path`.
+pub(crate) fn action_paths_for_file(repo_root: &Path, path: &str) -> Vec<String> {
+    let target = normalize_git_path(path).trim().to_string();
+    if target.is_empty() {
+        return Vec::new();
+    }
+
+    let repo = match Repository::open(repo_root) {
+        Ok(repo) => repo,
+        Err(_) => return vec![target],
+    };
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .include_ignored(false);
+
+    let statuses = match repo.statuses(Some(&mut status_options)) {
+        Ok(statuses) => statuses,
+        Err(_) => return vec![target],
+    };
+
+    for entry in statuses.iter() {
+        let status = entry.status();
+        if !(status.contains(Status::WT_RENAMED) || status.contains(Status::INDEX_RENAMED)) {
+            continue;
+        }
+        let delta = entry.index_to_workdir().or_else(|| entry.head_to_index());
+        let Some(delta) = delta else {
+            continue;
+        };
+        let (Some(old_path), Some(new_path)) = (delta.old_file().path(), delta.new_file().path())
+        else {
+            continue;
+        };
+        let old_path = normalize_git_path(old_path.to_string_lossy().as_ref());
+        let new_path = normalize_git_path(new_path.to_string_lossy().as_ref());
+        if old_path != target && new_path != target {
+            continue;
+        }
+        if old_path == new_path || new_path.is_empty() {
+            return vec![target];
+        }
+        let mut result = Vec::new();
+        if !old_path.is_empty() {
+            result.push(old_path);
+        }
+        if !new_path.is_empty() && !result.contains(&new_path) {
+            result.push(new_path);
+        }
+        return if result.is_empty() {
+            vec![target]
+        } else {
+            result
+        };
+    }
+
+    vec![target]
+}
+
+/// Lines longer than this are not compared word by word.
+const INTRALINE_MAX_LINE_CHARS: usize = 1_000;
+/// Largest token table one line pair may need, after the common prefix and
+/// suffix are set aside.
+const INTRALINE_MAX_CELLS: usize = 40_000;
+
+/// Word-level changes of a unified diff: each `-` line is paired with the
+/// `+` line at the same position in its change block, and the tokens the two
+/// do not share are reported as ranges. `None` when no line pairs up.
+pub(crate) fn intraline_hunks(diff: &str) -> Option<Vec<IntralineHunk>> {
+    let mut hunks: Vec<IntralineHunk> = Vec::new();
+    let mut hunk_index: Option<usize> = None;
+    let (mut old_line, mut new_line) = (0u32, 0u32);
+    let mut removed: Vec<(u32, &str)> = Vec::new();
+    let mut added: Vec<(u32, &str)> = Vec::new();
+
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("@@ ") {
+            flush_change_block(hunk_index, &mut removed, &mut added, &mut hunks);
+            let Some((old_start, new_start)) = parse_hunk_starts(header) else {
+                hunk_index = None;
+                continue;
+            };
+            hunk_index = Some(hunk_index.map_or(0, |index| index + 1));
+            (old_line, new_line) = (old_start, new_start);
+            continue;
+        }
+        if hunk_index.is_none() {
+            continue;
+        }
+        match line.as_bytes().first() {
+            Some(b'-') => {
+                if !added.is_empty() {
+                    flush_change_block(hunk_index, &mut removed, &mut added, &mut hunks);
+                }
+                removed.push((old_line, &line[1..]));
+                old_line += 1;
+            }
+            Some(b'+') => {
+                added.push((new_line, &line[1..]));
+                new_line += 1;
+            }
+            Some(b'\\') => {}
+            Some(b' ') | None => {
+                flush_change_block(hunk_index, &mut removed, &mut added, &mut hunks);
+                old_line += 1;
+                new_line += 1;
+            }
+            Some(_) => {
+                // Headers of the next file in a multi-file diff.
+                flush_change_block(hunk_index, &mut removed, &mut added, &mut hunks);
+                hunk_index = None;
+            }
+        }
+    }
+    flush_change_block(hunk_index, &mut removed, &mut added, &mut hunks);
+    (!hunks.is_empty()).then_some(hunks)
+}
+
+/// Pairs up the lines of one change block and adds the pairs with changes
+/// to hunk `hunk`.
+fn flush_change_block(
+    hunk: Option<usize>,
+    removed: &mut Vec<(u32, &str)>,
+    added: &mut Vec<(u32, &str)>,
+    hunks: &mut Vec<IntralineHunk>,
+) {
+    let pairs: Vec<IntralinePair> = removed
+        .iter()
+        .zip(added.iter())
+        .map(|(&(old_line, old), &(new_line, new))| intraline_pair(old_line, old, new_line, new))
+        .filter(|pair| pair.skipped || !pair.old_ranges.is_empty() || !pair.new_ranges.is_empty())
+        .collect();
+    removed.clear();
+    added.clear();
+    let Some(hunk) = hunk.filter(|_| !pairs.is_empty()) else {
+        return;
+    };
+    match hunks.last_mut().filter(|last| last.hunk == hunk) {
+        Some(last) => last.pairs.extend(pairs),
+        None => hunks.push(IntralineHunk { hunk, pairs }),
+    }
+}
+
+/// The old and new start lines of a `-a,b +c,d @@` hunk header.
+fn parse_hunk_starts(header: &str) -> Option<(u32, u32)> {
+    let mut parts = header.split_whitespace();
+    let start = |part: Option<&str>, sign: char| -> Option<u32> {
+        part?.strip_prefix(sign)?.split(',').next()?.parse().ok()
+    };
+    Some((start(parts.next(), '-')?, start(parts.next(), '+')?))
+}
+
+/// A token of a line with its offset and length in UTF-16 code units, the
+/// units JavaScript strings are indexed by.
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    offset: u32,
+    length: u32,
+}
+
+/// Splits a line into words, runs of whitespace and single other characters.
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let class = |ch: char| {
+        if ch.is_alphanumeric() || ch == '_' {
+            0
+        } else if ch.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut offset = 0u32;
+    let mut start = 0usize;
+    let mut start_offset = 0u32;
+    let mut previous: Option<u8> = None;
+    for (index, ch) in line.char_indices() {
+        let current = class(ch);
+        if index > start && (previous != Some(current) || current == 2) {
+            tokens.push(Token {
+                text: &line[start..index],
+                offset: start_offset,
+                length: offset - start_offset,
+            });
+            start = index;
+            start_offset = offset;
+        }
+        previous = Some(current);
+        offset += ch.len_utf16() as u32;
+    }
+    if start < line.len() {
+        tokens.push(Token {
+            text: &line[start..],
+            offset: start_offset,
+            length: offset - start_offset,
+        });
+    }
+    tokens
+}
+
+fn intraline_pair(old_line: u32, old: &str, new_line: u32, new: &str) -> IntralinePair {
+    let mut pair = IntralinePair {
+        old_line,
+        new_line,
+        old_ranges: Vec::new(),
+        new_ranges: Vec::new(),
+        skipped: false,
+    };
+    if old.chars().count() > INTRALINE_MAX_LINE_CHARS
+        || new.chars().count() > INTRALINE_MAX_LINE_CHARS
+    {
+        pair.skipped = true;
+        return pair;
+    }
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let prefix = old_tokens
+        .iter()
+        .zip(&new_tokens)
+        .take_while(|(a, b)| a.text == b.text)
+        .count();
+    let suffix = old_tokens[prefix..]
+        .iter()
+        .rev()
+        .zip(new_tokens[prefix..].iter().rev())
+        .take_while(|(a, b)| a.text == b.text)
+        .count();
+    let old_middle = &old_tokens[prefix..old_tokens.len() - suffix];
+    let new_middle = &new_tokens[prefix..new_tokens.len() - suffix];
+    if old_middle.len() * new_middle.len() > INTRALINE_MAX_CELLS {
+        pair.skipped = true;
+        return pair;
+    }
+    let (old_kept, new_kept) = common_tokens(old_middle, new_middle);
+    pair.old_ranges = changed_ranges(old_middle, &old_kept);
+    pair.new_ranges = changed_ranges(new_middle, &new_kept);
+    pair
+}
+
+/// Marks the tokens of a longest common subsequence of `old` and `new`.
+fn common_tokens(old: &[Token], new: &[Token]) -> (Vec<bool>, Vec<bool>) {
+    let (rows, cols) = (old.len(), new.len());
+    // lengths[i][j]: LCS length of old[i..] and new[j..].
+    let mut lengths = vec![0u32; (rows + 1) * (cols + 1)];
+    let at = |i: usize, j: usize| i * (cols + 1) + j;
+    for i in (0..rows).rev() {
+        for j in (0..cols).rev() {
+            lengths[at(i, j)] = if old[i].text == new[j].text {
+                lengths[at(i + 1, j + 1)] + 1
+            } else {
+                lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+            };
+        }
+    }
+    let mut old_kept = vec![false; rows];
+    let mut new_kept = vec![false; cols];
+    let (mut i, mut j) = (0, 0);
+    while i < rows && j < cols {
+        if old[i].text == new[j].text {
+            old_kept[i] = true;
+            new_kept[j] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[at(i + 1, j)] >= lengths[at(i, j + 1)] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (old_kept, new_kept)
+}
+
+/// `[offset, length]` of each run of tokens not in the common subsequence.
+fn changed_ranges(tokens: &[Token], kept: &[bool]) -> Vec<[u32; 2]> {
+    let mut ranges: Vec<[u32; 2]> = Vec::new();
+    for (token, _) in tokens.iter().zip(kept).filter(|(_, kept)| !**kept) {
+        match ranges.last_mut() {
+            Some(last) if last[0] + last[1] == token.offset => last[1] += token.length,
+            _ => ranges.push([token.offset, token.length]),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        checkout_branch, collect_commit_diff, collect_staged_diff, collect_unstaged_diff,
+        collect_workspace_diff, display_diff_options, intraline_hunks, is_whitespace_only_patch,
+        list_git_roots, resolve_git_sub_root, INTRALINE_MAX_LINE_CHARS,
+    };
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use tempfile::tempdir;
+
+    #[test]
+    fn sub_roots_must_be_listed_git_roots() {
+        let workspace = tempdir().expect("tempdir");
+        let vendored = workspace.path().join("vendor").join("lib");
+        std::fs::create_dir_all(vendored.join(".git")).unwrap();
+        std::fs::create_dir_all(workspace.path().join("docs")).unwrap();
+        std::fs::create_dir_all(workspace.path().join("node_modules/pkg/.git")).unwrap();
+        let entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "ws".to_string(),
+            path: workspace.path().to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+
+        assert_eq!(list_git_roots(workspace.path(), 3, 10), vec!["vendor/lib"]);
+        let resolved = resolve_git_sub_root(&entry, Some("vendor\\lib/")).expect("sub-root");
+        assert_eq!(resolved, vendored.canonicalize().unwrap());
+        assert_eq!(
+            resolve_git_sub_root(&entry, None).expect("primary"),
+            workspace.path()
+        );
+        for invalid in ["docs", "../outside", "/etc", "node_modules/pkg", "missing"] {
+            assert!(
+                resolve_git_sub_root(&entry, Some(invalid)).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    fn whitespace_flags(repo: &git2::Repository, ignore_whitespace: bool) -> Vec<(String, bool)> {
+        let head_tree = repo.head().unwrap().peel_to_tree().unwrap();
+        let mut options = display_diff_options(ignore_whitespace);
+        let diff = repo
+            .diff_tree_to_workdir_with_index(Some(&head_tree), Some(&mut options))
+            .unwrap();
+        (0..diff.deltas().len())
+            .map(|index| {
+                let patch = git2::Patch::from_diff(&diff, index).unwrap().unwrap();
+                let path = patch.delta().new_file().path().unwrap();
+                (
+                    path.to_string_lossy().to_string(),
+                    is_whitespace_only_patch(&patch, ignore_whitespace),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flags_crlf_only_changes_and_leaves_them_out_when_ignoring_whitespace() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        let repo = git2::Repository::init(root).expect("init");
+        repo.config()
+            .unwrap()
+            .set_bool("core.autocrlf", false)
+            .unwrap();
+        std::fs::write(root.join("crlf.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(root.join("edit.txt"), "before\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        std::fs::write(root.join("crlf.txt"), "one\r\ntwo\r\n").unwrap();
+        std::fs::write(root.join("edit.txt"), "after\r\n").unwrap();
+
+        let expected = vec![
+            ("crlf.txt".to_string(), true),
+            ("edit.txt".to_string(), false),
+        ];
+        assert_eq!(whitespace_flags(&repo, false), expected);
+        assert_eq!(whitespace_flags(&repo, true), expected);
+
+        let full = collect_workspace_diff(root, false).expect("diff");
+        assert!(full.contains("=== crlf.txt ===") && full.contains("=== edit.txt ==="));
+        let ignoring = collect_workspace_diff(root, true).expect("diff");
+        assert!(!ignoring.contains("crlf.txt"));
+        assert!(ignoring.contains("+after"));
+    }
+
+    #[test]
+    fn staged_unstaged_and_commit_diffs_are_collected_apart() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        let repo = git2::Repository::init(root).expect("init");
+        std::fs::write(root.join("first.txt"), "first\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("first.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        std::fs::write(root.join("staged.txt"), "staged\n").unwrap();
+        index.add_path(std::path::Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+        std::fs::write(root.join("loose.txt"), "loose\n").unwrap();
+
+        let staged = collect_staged_diff(root, false).expect("staged");
+        assert!(staged.contains("=== staged.txt ===") && !staged.contains("loose.txt"));
+        let unstaged = collect_unstaged_diff(root, false).expect("unstaged");
+        assert!(unstaged.contains("=== loose.txt ===") && !unstaged.contains("staged.txt"));
+        let commit = collect_commit_diff(root, "HEAD", false).expect("commit");
+        assert!(commit.contains("=== first.txt ===") && commit.contains("+first"));
+        assert!(collect_commit_diff(root, "no-such-rev", false).is_err());
+    }
+
+    #[test]
+    fn a_branch_switch_that_would_overwrite_local_changes_leaves_head_alone() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        let repo = git2::Repository::init(root).expect("init");
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let commit_file = |content: &str, parents: &[&git2::Commit]| {
+            std::fs::write(root.join("tracked.txt"), content).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new("tracked.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "c", &tree, parents)
+                .unwrap()
+        };
+        let first = repo.find_commit(commit_file("one\n", &[])).unwrap();
+        let base = repo.head().unwrap().shorthand().unwrap().to_string();
+        repo.branch("feature", &first, false).unwrap();
+        commit_file("two\n", &[&first]);
+
+        std::fs::write(root.join("notes.txt"), "untracked\n").unwrap();
+        std::fs::write(root.join("tracked.txt"), "local edit\n").unwrap();
+        assert!(checkout_branch(&repo, "feature").is_err());
+        assert_eq!(repo.head().unwrap().shorthand(), Some(base.as_str()));
+        assert_eq!(
+            std::fs::read_to_string(root.join("tracked.txt")).unwrap(),
+            "local edit\n"
+        );
+
+        // Changes the switch does not touch are carried over.
+        std::fs::write(root.join("tracked.txt"), "two\n").unwrap();
+        checkout_branch(&repo, "feature").expect("switch");
+        assert_eq!(repo.head().unwrap().shorthand(), Some("feature"));
+        assert_eq!(
+            std::fs::read_to_string(root.join("tracked.txt")).unwrap(),
+            "one\n"
+        );
+        assert!(root.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn intraline_ranges_mark_changed_words_of_paired_lines() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n\
+@@ -1,3 +1,3 @@\n let total = 1;\n-let name = \"café\";\n+let label = \"café\";\n end\n\
+@@ -10,2 +10,3 @@\n-fn run(a: u32) {}\n+fn run(a: u64) {}\n+// added\n";
+        let hunks = intraline_hunks(diff).expect("pairs");
+        assert_eq!(hunks.len(), 2);
+        let first = &hunks[0].pairs[0];
+        assert_eq!((hunks[0].hunk, first.old_line, first.new_line), (0, 2, 2));
+        assert_eq!(first.old_ranges, vec![[4, 4]]);
+        assert_eq!(first.new_ranges, vec![[4, 5]]);
+        let second = &hunks[1];
+        assert_eq!(second.hunk, 1);
+        assert_eq!(second.pairs.len(), 1);
+        assert_eq!(second.pairs[0].old_ranges, vec![[10, 3]]);
+        assert_eq!(second.pairs[0].new_ranges, vec![[10, 3]]);
+
+        assert!(intraline_hunks("@@ -1 +1,2 @@\n same\n+new\n").is_none());
+        let long = "x".repeat(INTRALINE_MAX_LINE_CHARS + 1);
+        let skipped = intraline_hunks(&format!("@@ -1 +1 @@\n-{long}\n+{long}y\n")).unwrap();
+        assert!(skipped[0].pairs[0].skipped && skipped[0].pairs[0].new_ranges.is_empty());
+    }
+
+    #[test]
+    fn intraline_ranges_stay_fast_on_large_diffs() {
+        let mut diff = String::from("@@ -1,20000 +1,20000 @@\n");
+        for line in 0..10_000 {
+            diff.push_str(&format!(
+                "-    let value_{line} = compute(alpha, beta, gamma, {line}) + offset * scale;\n"
+            ));
+            diff.push_str(&format!(
+                "+    let value_{line} = compute(alpha, delta, gamma, {line}) - offset * scale;\n"
+            ));
+        }
+        let wide = "token ".repeat(150);
+        for _ in 0..200 {
+            diff.push_str(&format!("-{wide}a\n+b {wide}\n"));
+        }
+        let started = std::time::Instant::now();
+        let hunks = intraline_hunks(&diff).expect("pairs");
+        let elapsed = started.elapsed();
+        assert_eq!(hunks[0].pairs.len(), 10_200);
+        assert_eq!(hunks[0].pairs[0].old_ranges, vec![[33, 4], [49, 1]]);
+        assert!(hunks[0].pairs[10_000].skipped);
+        assert!(
+            elapsed < std::time::Duration::from_secs(5),
+            "intraline took {elapsed:?}"
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Runs the GitHub CLI for the issue, pull request, diff and comment views.
+//! A missing `gh` or a logged-out one comes back as a short `gh_missing:` or
+//! `gh_unauthenticated:` error instead of gh's own stderr, and that state is
+//! remembered for a minute so a broken login doesn't spawn gh on every poll.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+/// Error prefix when gh is installed but not logged in, or its token expired.
+pub(crate) const GH_UNAUTHENTICATED_ERROR: &str = "gh_unauthenticated";
+/// Error prefix when gh is not on the PATH.
+pub(crate) const GH_MISSING_ERROR: &str = "gh_missing";
+const GITHUB_HOST: &str = "github.com";
+const AUTH_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Lines gh prints when it has no usable token.
+const AUTH_FAILURE_SIGNATURES: &[&str] = &[
+    "gh auth login",
+    "http 401",
+    "bad credentials",
+    "authentication required",
+    "not logged into",
+    "requires authentication",
+    "token has expired",
+    "token is invalid",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum GhAuthState {
+    Authenticated,
+    Unauthenticated,
+    Missing,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GhAuthStatus {
+    pub(crate) state: GhAuthState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<String>,
+    pub(crate) host: String,
+    /// What to run to fix the state; `None` when authenticated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) fix_command: Option<String>,
+    /// Unix ms of the check.
+    pub(crate) checked_at: i64,
+}
+
+impl GhAuthStatus {
+    fn new(state: GhAuthState, version: Option<String>) -> Self {
+        let fix_command = match state {
+            GhAuthState::Authenticated => None,
+            GhAuthState::Unauthenticated => Some(login_command()),
+            GhAuthState::Missing => Some("https://cli.github.com".to_string()),
+        };
+        Self {
+            state,
+            version,
+            host: GITHUB_HOST.to_string(),
+            fix_command,
+            checked_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// The error a GitHub request returns in this state, if any.
+    fn error(&self) -> Option<String> {
+        match self.state {
+            GhAuthState::Authenticated => None,
+            GhAuthState::Unauthenticated => {
+                let version = self
+                    .version
+                    .as_deref()
+                    .map(|version| format!(" (gh {version})"))
+                    .unwrap_or_default();
+                Some(format!(
+                    "{GH_UNAUTHENTICATED_ERROR}: GitHub CLI{version} is not logged in to {}. Run `{}`.",
+                    self.host,
+                    login_command()
+                ))
+            }
+            GhAuthState::Missing => Some(format!(
+                "{GH_MISSING_ERROR}: GitHub CLI (gh) is not installed. Install it from https://cli.github.com and run `{}`.",
+                login_command()
+            )),
+        }
+    }
+}
+
+fn login_command() -> String {
+    format!("gh auth login --hostname {GITHUB_HOST}")
+}
+
+/// Last known auth state and when it was recorded.
+struct AuthCache {
+    entry: Option<(Instant, GhAuthStatus)>,
+}
+
+impl AuthCache {
+    fn fresh(&self, now: Instant) -> Option<&GhAuthStatus> {
+        self.entry
+            .as_ref()
+            .filter(|(at, _)| now.duration_since(*at) < AUTH_CACHE_TTL)
+            .map(|(_, status)| status)
+    }
+}
+
+fn auth_cache() -> std::sync::MutexGuard<'static, AuthCache> {
+    static CACHE: OnceLock<Mutex<AuthCache>> = OnceLock::new();
+    CACHE
+        .get_or_init(|| Mutex::new(AuthCache { entry: None }))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+fn remember(status: GhAuthStatus) -> GhAuthStatus {
+    auth_cache().entry = Some((Instant::now(), status.clone()));
+    status
+}
+
+fn is_auth_failure(output: &str) -> bool {
+    let lower = output.to_lowercase();
+    AUTH_FAILURE_SIGNATURES
+        .iter()
+        .any(|signature| lower.contains(signature))
+}
+
+/// `2.40.1` from `gh version 2.40.1 (2023-12-13)`.
+fn parse_gh_version(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .nth(2)
+        .map(str::to_string)
+}
+
+async fn gh_version() -> Result<Option<String>, std::io::Error> {
+    let output = Command::new("gh").arg("--version").output().await?;
+    Ok(parse_gh_version(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Checks gh's install and login, reusing a recent result unless `refresh`.
+pub(crate) async fn gh_auth_status(refresh: bool) -> GhAuthStatus {
+    if !refresh {
+        if let Some(status) = auth_cache().fresh(Instant::now()) {
+            return status.clone();
+        }
+    }
+    let version = match gh_version().await {
+        Ok(version) => version,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return remember(GhAuthStatus::new(GhAuthState::Missing, None));
+        }
+        Err(_) => None,
+    };
+    let authenticated = Command::new("gh")
+        .args(["auth", "status", "--hostname", GITHUB_HOST])
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success());
+    let state = if authenticated {
+        GhAuthState::Authenticated
+    } else {
+        GhAuthState::Unauthenticated
+    };
+    remember(GhAuthStatus::new(state, version))
+}
+
+/// Runs `gh args` in `cwd` and returns its stdout. Fails fast while gh is
+/// known to be missing or logged out.
+pub(crate) async fn run_gh(cwd: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    let cached_error = auth_cache()
+        .fresh(Instant::now())
+        .and_then(GhAuthStatus::error);
+    if let Some(error) = cached_error {
+        return Err(error);
+    }
+    let output = match Command::new("gh")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .await
+    {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let status = remember(GhAuthStatus::new(GhAuthState::Missing, None));
+            return Err(status.error().unwrap_or_default());
+        }
+        Err(err) => return Err(format!("Failed to run gh: {err}")),
+    };
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if is_auth_failure(detail) {
+        let version = gh_version().await.ok().flatten();
+        let status = remember(GhAuthStatus::new(GhAuthState::Unauthenticated, version));
+        return Err(status.error().unwrap_or_default());
+    }
+    if detail.is_empty() {
+        return Err("GitHub CLI command failed.".to_string());
+    }
+    Err(detail.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_auth_failure, parse_gh_version, AuthCache, GhAuthState, GhAuthStatus, AUTH_CACHE_TTL,
+    };
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn recognizes_auth_failures() {
+        assert!(is_auth_failure(
+            "To get started with GitHub CLI, please run:  gh auth login\nAlternatively, populate the GH_TOKEN environment variable."
+        ));
+        assert!(is_auth_failure("gh: Bad credentials (HTTP 401)"));
+        assert!(!is_auth_failure(
+            "GraphQL: Could not resolve to a PullRequest with the number of 99999."
+        ));
+        assert_eq!(
+            parse_gh_version(
+                "gh version 2.40.1 (2023-12-13)\nhttps://github.com/cli/cli/releases/tag/v2.40.1\n"
+            ),
+            Some("2.40.1".to_string())
+        );
+        assert_eq!(parse_gh_version(""), None);
+    }
+
+    #[test]
+    fn errors_name_the_state_and_fix() {
+        let status = GhAuthStatus::new(GhAuthState::Unauthenticated, Some("2.40.1".to_string()));
+        assert_eq!(
+            status.error().as_deref(),
+            Some("gh_unauthenticated: GitHub CLI (gh 2.40.1) is not logged in to github.com. Run `gh auth login --hostname github.com`.")
+        );
+        assert_eq!(
+            status.fix_command.as_deref(),
+            Some("gh auth login --hostname github.com")
+        );
+        let missing = GhAuthStatus::new(GhAuthState::Missing, None);
+        assert!(missing.error().unwrap().starts_with("gh_missing: "));
+        assert!(GhAuthStatus::new(GhAuthState::Authenticated, None)
+            .error()
+            .is_none());
+    }
+
+    #[test]
+    fn cached_state_expires() {
+        let at = Instant::now();
+        let cache = AuthCache {
+            entry: Some((at, GhAuthStatus::new(GhAuthState::Missing, None))),
+        };
+        assert!(cache.fresh(at + Duration::from_secs(5)).is_some());
+        assert!(cache.fresh(at + AUTH_CACHE_TTL).is_none());
+    }
+}
@@ -0,0 +1,327 @@
+//! Background refresh of GitHub data for workspaces with `githubPrefetch`. The
+//! daemon keeps the last fetch of each workspace's issues, pull requests and
+//! check rollups here; the GitHub requests answer from it while it is younger
+//! than the prefetch interval. A failing workspace backs off on its own
+//! schedule without holding up the others.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::types::{GitHubIssuesResponse, GitHubPullRequestsResponse};
+
+/// How often the scheduler looks for workspaces that are due.
+pub(crate) const PREFETCH_TICK: Duration = Duration::from_secs(30);
+/// Longest wait between attempts for a workspace whose fetches keep failing.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// `gh pr list --json` fields read by `summarize_checks`.
+pub(crate) const CHECK_ROLLUP_FIELDS: &str = "number,statusCheckRollup";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CheckState {
+    Success,
+    Failure,
+    Pending,
+    /// The pull request has no checks.
+    None,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CheckRollup {
+    pub(crate) number: u64,
+    pub(crate) state: CheckState,
+    pub(crate) passed: u32,
+    pub(crate) failed: u32,
+    pub(crate) pending: u32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct GithubSnapshot {
+    pub(crate) issues: GitHubIssuesResponse,
+    pub(crate) pull_requests: GitHubPullRequestsResponse,
+    pub(crate) checks: Vec<CheckRollup>,
+    /// Unix ms.
+    pub(crate) fetched_at: i64,
+}
+
+/// Params of the `github/dataUpdated` event.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GithubDataUpdated {
+    pub(crate) open_issues: usize,
+    pub(crate) open_pull_requests: usize,
+    /// Issues and pull requests not in the previous fetch; zero on the first.
+    pub(crate) new_issues: usize,
+    pub(crate) new_pull_requests: usize,
+    pub(crate) failing_checks: usize,
+    pub(crate) fetched_at: i64,
+}
+
+/// Reads the `statusCheckRollup` of each pull request from `gh pr list`.
+/// Check runs report `status`/`conclusion`, commit statuses `state`.
+pub(crate) fn summarize_checks(value: &Value) -> Vec<CheckRollup> {
+    let Some(pull_requests) = value.as_array() else {
+        return Vec::new();
+    };
+    pull_requests
+        .iter()
+        .filter_map(|pull_request| {
+            let number = pull_request.get("number")?.as_u64()?;
+            let mut rollup = CheckRollup {
+                number,
+                state: CheckState::None,
+                passed: 0,
+                failed: 0,
+                pending: 0,
+            };
+            let checks = pull_request
+                .get("statusCheckRollup")
+                .and_then(Value::as_array);
+            for check in checks.into_iter().flatten() {
+                let text = |key: &str| {
+                    check
+                        .get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .to_ascii_uppercase()
+                };
+                let status = text("status");
+                let outcome = if status.is_empty() || status == "COMPLETED" {
+                    let conclusion = text("conclusion");
+                    if conclusion.is_empty() {
+                        text("state")
+                    } else {
+                        conclusion
+                    }
+                } else {
+                    status
+                };
+                match outcome.as_str() {
+                    "SUCCESS" | "NEUTRAL" | "SKIPPED" => rollup.passed += 1,
+                    "FAILURE" | "ERROR" | "TIMED_OUT" | "CANCELLED" | "ACTION_REQUIRED"
+                    | "STARTUP_FAILURE" => rollup.failed += 1,
+                    _ => rollup.pending += 1,
+                }
+            }
+            rollup.state = if rollup.failed > 0 {
+                CheckState::Failure
+            } else if rollup.pending > 0 {
+                CheckState::Pending
+            } else if rollup.passed > 0 {
+                CheckState::Success
+            } else {
+                CheckState::None
+            };
+            Some(rollup)
+        })
+        .collect()
+}
+
+#[derive(Default)]
+pub(crate) struct GithubCache {
+    entries: Mutex<HashMap<String, GithubSnapshot>>,
+}
+
+impl GithubCache {
+    /// The workspace's last fetch, unless it is older than `max_age`.
+    pub(crate) fn fresh(&self, workspace_id: &str, max_age: Duration) -> Option<GithubSnapshot> {
+        let max_age_ms = i64::try_from(max_age.as_millis()).unwrap_or(i64::MAX);
+        let now = chrono::Utc::now().timestamp_millis();
+        self.lock()
+            .get(workspace_id)
+            .filter(|snapshot| now.saturating_sub(snapshot.fetched_at) < max_age_ms)
+            .cloned()
+    }
+
+    /// Replaces the workspace's snapshot and summarizes what changed.
+    pub(crate) fn store(&self, workspace_id: &str, snapshot: GithubSnapshot) -> GithubDataUpdated {
+        let mut entries = self.lock();
+        let (new_issues, new_pull_requests) = match entries.get(workspace_id) {
+            Some(previous) => (
+                count_new(issue_numbers(&snapshot), issue_numbers(previous)),
+                count_new(
+                    pull_request_numbers(&snapshot),
+                    pull_request_numbers(previous),
+                ),
+            ),
+            None => (0, 0),
+        };
+        let update = GithubDataUpdated {
+            open_issues: snapshot.issues.total,
+            open_pull_requests: snapshot.pull_requests.total,
+            new_issues,
+            new_pull_requests,
+            failing_checks: snapshot
+                .checks
+                .iter()
+                .filter(|rollup| rollup.state == CheckState::Failure)
+                .count(),
+            fetched_at: snapshot.fetched_at,
+        };
+        entries.insert(workspace_id.to_string(), snapshot);
+        update
+    }
+
+    /// Drops snapshots of workspaces that are no longer prefetched.
+    pub(crate) fn retain(&self, workspace_ids: &HashSet<String>) {
+        self.lock().retain(|id, _| workspace_ids.contains(id));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, GithubSnapshot>> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn issue_numbers(snapshot: &GithubSnapshot) -> impl Iterator<Item = u64> + '_ {
+    snapshot.issues.issues.iter().map(|issue| issue.number)
+}
+
+fn pull_request_numbers(snapshot: &GithubSnapshot) -> impl Iterator<Item = u64> + '_ {
+    snapshot
+        .pull_requests
+        .pull_requests
+        .iter()
+        .map(|pull_request| pull_request.number)
+}
+
+fn count_new(now: impl Iterator<Item = u64>, before: impl Iterator<Item = u64>) -> usize {
+    let before: HashSet<u64> = before.collect();
+    now.filter(|number| !before.contains(number)).count()
+}
+
+/// When each prefetched workspace is next due. Owned by the scheduler loop.
+#[derive(Default)]
+pub(crate) struct PrefetchSchedule {
+    /// Next attempt and consecutive failures.
+    entries: HashMap<String, (Instant, u32)>,
+}
+
+impl PrefetchSchedule {
+    /// Forgets workspaces outside `workspace_ids`; new ones are due at once.
+    pub(crate) fn retain(&mut self, workspace_ids: &HashSet<String>) {
+        self.entries.retain(|id, _| workspace_ids.contains(id));
+    }
+
+    pub(crate) fn is_due(&self, workspace_id: &str, now: Instant) -> bool {
+        self.entries
+            .get(workspace_id)
+            .is_none_or(|(next, _)| *next <= now)
+    }
+
+    pub(crate) fn succeeded(&mut self, workspace_id: &str, now: Instant, interval: Duration) {
+        self.entries
+            .insert(workspace_id.to_string(), (now + interval, 0));
+    }
+
+    /// Doubles the wait for each failure in a row, up to an hour.
+    pub(crate) fn failed(&mut self, workspace_id: &str, now: Instant, interval: Duration) {
+        let failures = self
+            .entries
+            .get(workspace_id)
+            .map_or(0, |(_, failures)| *failures)
+            + 1;
+        let backoff = interval
+            .saturating_mul(1 << failures.min(10))
+            .min(MAX_BACKOFF.max(interval));
+        self.entries
+            .insert(workspace_id.to_string(), (now + backoff, failures));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        summarize_checks, CheckState, GithubCache, GithubSnapshot, PrefetchSchedule, MAX_BACKOFF,
+    };
+    use crate::types::{GitHubIssue, GitHubIssuesResponse, GitHubPullRequestsResponse};
+    use serde_json::json;
+    use std::collections::HashSet;
+    use std::time::{Duration, Instant};
+
+    fn snapshot(issue_numbers: &[u64]) -> GithubSnapshot {
+        GithubSnapshot {
+            issues: GitHubIssuesResponse {
+                total: issue_numbers.len(),
+                issues: issue_numbers
+                    .iter()
+                    .map(|number| GitHubIssue {
+                        number: *number,
+                        title: format!("Issue {number}"),
+                        url: String::new(),
+                        updated_at: String::new(),
+                    })
+                    .collect(),
+            },
+            pull_requests: GitHubPullRequestsResponse {
+                total: 0,
+                pull_requests: Vec::new(),
+            },
+            checks: Vec::new(),
+            fetched_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    #[test]
+    fn rolls_up_check_runs_and_statuses() {
+        let rollups = summarize_checks(&json!([
+            { "number": 1, "statusCheckRollup": [
+                { "__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS" },
+                { "__typename": "StatusContext", "state": "FAILURE" }
+            ] },
+            { "number": 2, "statusCheckRollup": [
+                { "__typename": "CheckRun", "status": "IN_PROGRESS", "conclusion": "" },
+                { "__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SKIPPED" }
+            ] },
+            { "number": 3, "statusCheckRollup": [] }
+        ]));
+        let states: Vec<_> = rollups
+            .iter()
+            .map(|rollup| (rollup.number, rollup.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (1, CheckState::Failure),
+                (2, CheckState::Pending),
+                (3, CheckState::None)
+            ]
+        );
+        assert_eq!((rollups[1].passed, rollups[1].pending), (1, 1));
+    }
+
+    #[test]
+    fn counts_new_items_against_the_previous_fetch() {
+        let cache = GithubCache::default();
+        assert_eq!(cache.store("ws", snapshot(&[1, 2])).new_issues, 0);
+        let update = cache.store("ws", snapshot(&[2, 3, 4]));
+        assert_eq!((update.open_issues, update.new_issues), (3, 2));
+        assert!(cache.fresh("ws", Duration::from_secs(60)).is_some());
+        assert!(cache.fresh("ws", Duration::ZERO).is_none());
+        cache.retain(&HashSet::new());
+        assert!(cache.fresh("ws", Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn failures_back_off_until_a_success() {
+        let interval = Duration::from_secs(300);
+        let now = Instant::now();
+        let mut schedule = PrefetchSchedule::default();
+        assert!(schedule.is_due("ws", now));
+        schedule.failed("ws", now, interval);
+        assert!(!schedule.is_due("ws", now + interval));
+        assert!(schedule.is_due("ws", now + interval * 2));
+        for _ in 0..8 {
+            schedule.failed("ws", now, interval);
+        }
+        assert!(schedule.is_due("ws", now + MAX_BACKOFF));
+        schedule.succeeded("ws", now, interval);
+        assert!(schedule.is_due("ws", now + interval));
+        schedule.retain(&HashSet::new());
+        assert!(schedule.is_due("ws", now));
+    }
+}
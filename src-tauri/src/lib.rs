@@ -1,46 +1,110 @@
 use tauri::Manager;
 
+mod attachments;
 #[path = "memory/auto_flush.rs"]
 mod auto_flush;
 mod backend;
+mod branch_protection;
+mod client_timezone;
 mod codex;
 mod codex_args;
 mod codex_config;
 mod codex_home;
 mod codex_params;
+mod data_usage;
+mod delivery_platforms;
 #[cfg(not(target_os = "windows"))]
 #[path = "dictation.rs"]
 mod dictation;
 #[cfg(target_os = "windows")]
 #[path = "dictation_stub.rs"]
 mod dictation;
+mod domain_prompts;
+mod domain_usage;
 mod domains;
 mod event_sink;
+mod file_refs;
+mod file_tail;
 mod files;
+mod frontmatter;
 mod git;
+mod git_commit;
+mod git_hunks;
+mod git_patch;
+mod git_stash;
+mod git_status_cache;
+mod git_tags;
 mod git_utils;
+mod github_cli;
 mod life;
 pub mod life_core;
+mod life_report;
+mod life_transcripts;
 mod local_usage;
 mod local_usage_core;
 mod memory;
 mod memory_commands;
+#[path = "memory/recall.rs"]
+mod memory_recall;
 mod menu;
+mod message_assembly;
+mod message_compose;
+mod notification_rules;
+mod nutrition_goals;
 mod obsidian;
+mod onboarding;
+mod prompt_roots;
 mod prompts;
+mod quick_commands;
+mod read_only;
+mod redaction;
 mod remote_backend;
+mod resource_usage;
+mod revert_undo;
+mod review_gate;
+mod rpc_chunks;
 mod rules;
 mod settings;
+mod settings_audit;
 mod state;
 mod storage;
 mod terminal;
+mod terminal_audit;
+mod terminal_core;
+mod terminal_search;
+mod terminal_shell;
+#[cfg(test)]
+mod test_git;
+mod thread_activity;
+mod thread_archive;
+mod thread_items;
+mod thread_overrides;
+mod thread_summary;
+mod turn_changes;
 mod types;
+mod usage_overview;
 mod utils;
+mod vault_roots;
+mod warm_pool;
 mod window;
+mod workouts;
+mod workspace_file;
+mod workspace_identity;
+mod workspace_import;
+mod workspace_search;
+mod workspace_settings;
+mod workspace_todos;
 mod workspaces;
+mod worktree_apply;
+mod worktree_compare;
+mod worktree_guard;
+mod worktree_report;
+mod worktree_setup;
+mod worktree_upstream;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    backend::supervisor::install_panic_hook();
     #[cfg(target_os = "linux")]
     {
         // Avoid WebKit compositing issues on some Linux setups (GBM buffer errors).
@@ -57,6 +121,17 @@ pub fn run() {
         .setup(|app| {
             let state = state::AppState::load(&app.handle());
             app.manage(state);
+            let handle = app.handle().clone();
+            backend::keepalive::set_restart_hook(move |workspace_id| {
+                let app = handle.clone();
+                tauri::async_runtime::spawn(workspaces::restart_unhealthy_session(
+                    app,
+                    workspace_id,
+                ));
+            });
+            tauri::async_runtime::spawn(backend::path_health::run_path_recheck(
+                event_sink::TauriEventSink::new(app.handle().clone()),
+            ));
             #[cfg(desktop)]
             {
                 app.handle()
@@ -76,37 +151,66 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             settings::get_app_settings,
             settings::update_app_settings,
+            settings::update_app_settings_partial,
+            settings::settings_audit,
+            settings::notification_rules_test,
+            settings::data_usage,
+            settings::resource_usage,
             menu::menu_set_accelerators,
             codex::codex_doctor,
+            codex::workspace_doctor,
             domains::domains_list,
             domains::domains_create,
             domains::domains_update,
             domains::domains_delete,
+            domains::domain_usage,
             workspaces::list_workspaces,
             workspaces::is_workspace_path_dir,
             workspaces::add_workspace,
+            workspaces::onboarding_bootstrap,
+            workspaces::onboarding_status,
+            workspaces::scan_and_import_workspaces,
             workspaces::add_clone,
             workspaces::add_worktree,
             workspaces::remove_workspace,
             workspaces::remove_worktree,
+            workspaces::worktree_report,
+            workspaces::compare_worktrees,
             workspaces::rename_worktree,
             workspaces::rename_worktree_upstream,
             workspaces::apply_worktree_changes,
             workspaces::update_workspace_settings,
+            workspaces::update_workspace_settings_partial,
+            workspaces::get_effective_codex_args,
+            workspaces::set_workspace_codex_args,
             workspaces::update_workspace_codex_bin,
             codex::start_thread,
+            codex::upload_attachment,
             codex::send_user_message,
+            codex::estimate_message_size,
+            codex::compose_message,
+            codex::preview_turn_policy,
             codex::turn_interrupt,
             codex::start_review,
+            codex::list_reviews,
             codex::respond_to_server_request,
             codex::remember_approval_rule,
             codex::get_commit_message_prompt,
             codex::generate_commit_message,
+            codex::commit_turn_changes,
+            codex::summarize_thread,
             codex::resume_thread,
             codex::list_threads,
             codex::list_session_threads,
             codex::archive_thread,
+            codex::archive_threads,
+            codex::auto_archive_preview,
+            codex::set_pinned_threads,
+            codex::threads_overview,
+            codex::mark_thread_viewed,
+            codex::mark_workspace_viewed,
             codex::collaboration_mode_list,
+            codex::session_capabilities,
             workspaces::connect_workspace,
             git::get_git_status,
             git::list_git_roots,
@@ -115,11 +219,16 @@ pub fn run() {
             git::get_git_commit_diff,
             git::get_git_remote,
             git::stage_git_file,
+            git::stage_git_hunk,
             git::stage_git_all,
             git::unstage_git_file,
+            git::unstage_git_hunk,
             git::revert_git_file,
+            git::list_revert_undos,
+            git::restore_revert_undo,
             git::revert_git_all,
             git::commit_git,
+            git::get_last_commit_message,
             files::read_global_agents_md,
             files::write_global_agents_md,
             files::read_global_config_toml,
@@ -131,13 +240,31 @@ pub fn run() {
             git::get_github_pull_requests,
             git::get_github_pull_request_diff,
             git::get_github_pull_request_comments,
+            git::gh_auth_status,
             workspaces::list_workspace_files,
             workspaces::read_workspace_file,
+            workspaces::tail_workspace_file,
+            workspaces::follow_file,
+            workspaces::unfollow_file,
+            workspaces::list_quick_commands,
+            workspaces::run_quick_command,
+            workspaces::cancel_quick_command,
+            workspaces::scan_workspace_todos,
+            workspaces::search_workspace_files,
             workspaces::open_workspace_in,
             git::list_git_branches,
             git::checkout_git_branch,
             git::create_git_branch,
+            git::list_git_tags,
+            git::create_git_tag,
+            git::delete_git_tag,
+            git::stash_git,
+            git::list_git_stashes,
+            git::pop_git_stash,
+            git::drop_git_stash,
             codex::model_list,
+            codex::get_thread_overrides,
+            codex::clear_thread_overrides,
             codex::account_rate_limits,
             codex::skills_list,
             prompts::prompts_list,
@@ -153,9 +280,17 @@ pub fn run() {
             life::get_media_dashboard,
             life::get_youtube_dashboard,
             life::enrich_media_covers,
+            life::media_providers_status,
             life::get_finance_dashboard,
+            life::export_life_report,
+            life::mark_bill_paid,
+            life::stream_append,
+            life::update_media_item,
+            life::list_domain_entities,
+            life::read_domain_entity,
             prompts::prompts_global_dir,
             memory_commands::memory_status,
+            memory_commands::memory_test_connection,
             memory_commands::memory_search,
             memory_commands::memory_append,
             memory_commands::memory_bootstrap,
@@ -164,12 +299,17 @@ pub fn run() {
             domains::domains_create,
             domains::domains_update,
             domains::domains_delete,
+            domains::domain_usage,
             domains::domain_trends,
             domains::read_text_file,
             terminal::terminal_open,
             terminal::terminal_write,
             terminal::terminal_resize,
             terminal::terminal_close,
+            terminal::terminal_list,
+            terminal::terminal_scrollback,
+            terminal::terminal_snapshot,
+            terminal::terminal_search,
             dictation::dictation_model_status,
             dictation::dictation_download_model,
             dictation::dictation_cancel_download,
@@ -177,7 +317,8 @@ pub fn run() {
             dictation::dictation_start,
             dictation::dictation_stop,
             dictation::dictation_cancel,
-            local_usage::local_usage_snapshot
+            local_usage::local_usage_snapshot,
+            local_usage::usage_overview
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,15 +1,26 @@
 use serde_json::json;
 use tauri::{AppHandle, State};
 
+use crate::client_timezone::ClientTimezone;
 pub(crate) use crate::life_core::{
     build_delivery_dashboard, build_exercise_dashboard, build_finance_dashboard,
     build_life_workspace_prompt, build_media_library, build_nutrition_dashboard,
-    build_youtube_library, enrich_media_covers as enrich_media_covers_inner, is_life_workspace,
-    life_debug_enabled, DeliveryDashboard, ExerciseDashboard, FinanceDashboard, MediaCoverSummary,
-    MediaLibrary, NutritionDashboard, YouTubeLibrary,
+    build_youtube_library, default_obsidian_root,
+    enrich_media_covers_for_roots as enrich_media_covers_inner,
+    export_life_report as export_life_report_inner, is_life_workspace, life_debug_enabled,
+    list_domain_entities as list_domain_entities_inner, mark_bill_paid as mark_bill_paid_inner,
+    media_providers_status as media_providers_status_inner,
+    read_domain_entity as read_domain_entity_inner, update_media_item as update_media_item_inner,
+    Bill, DeliveryDashboard, DomainEntity, DomainEntityPage, ExerciseDashboard, FinanceDashboard,
+    LifeReportOptions, MediaCoverOptions, MediaCoverSummary, MediaItem, MediaItemUpdate,
+    MediaLibrary, MediaProviderConfig, MediaProviderStatus, NutritionDashboard, YouTubeLibrary,
 };
+use crate::life_report::{LifeReport, ReportFormat};
+use crate::obsidian::{invalidate_domain_trends, workspace_stream_append};
+use crate::read_only::ensure_writable;
 use crate::remote_backend;
 use crate::state::AppState;
+use crate::types::StreamAppend;
 
 #[tauri::command]
 pub(crate) async fn get_life_workspace_prompt(
@@ -29,6 +40,7 @@ pub(crate) async fn get_life_workspace_prompt(
 pub(crate) async fn get_delivery_dashboard(
     workspace_id: String,
     range: String,
+    timezone: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<DeliveryDashboard, String> {
@@ -37,39 +49,47 @@ pub(crate) async fn get_delivery_dashboard(
             &*state,
             app,
             "get_delivery_dashboard",
-            json!({ "workspaceId": workspace_id, "range": range }),
+            json!({ "workspaceId": workspace_id, "range": range, "timezone": timezone }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
+    let today = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+        .await?
+        .today();
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
-    let supabase = {
-        let settings = state.app_settings.lock().await;
-        if settings.supabase_url.trim().is_empty() || settings.supabase_anon_key.trim().is_empty() {
-            None
-        } else {
-            Some((
-                settings.supabase_url.clone(),
-                settings.supabase_anon_key.clone(),
-            ))
-        }
-    };
+    let supabase = supabase_credentials(&state).await;
 
     build_delivery_dashboard(
         &entry.path,
-        entry.settings.obsidian_root.as_deref(),
+        &entry.settings.effective_obsidian_roots(),
         supabase.as_ref().map(|value| value.0.as_str()),
         supabase.as_ref().map(|value| value.1.as_str()),
         &range,
+        today,
     )
     .await
 }
 
+async fn supabase_credentials(state: &AppState) -> Option<(String, String)> {
+    let settings = state.app_settings.lock().await;
+    if settings.supabase_url.trim().is_empty() || settings.supabase_anon_key.trim().is_empty() {
+        None
+    } else {
+        Some((
+            settings.supabase_url.clone(),
+            settings.supabase_anon_key.clone(),
+        ))
+    }
+}
+
 #[tauri::command]
 pub(crate) async fn get_nutrition_dashboard(
     workspace_id: String,
     range: String,
+    timezone: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<NutritionDashboard, String> {
@@ -78,21 +98,33 @@ pub(crate) async fn get_nutrition_dashboard(
             &*state,
             app,
             "get_nutrition_dashboard",
-            json!({ "workspaceId": workspace_id, "range": range }),
+            json!({ "workspaceId": workspace_id, "range": range, "timezone": timezone }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
+    let today = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+        .await?
+        .today();
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
 
-    build_nutrition_dashboard(&entry.path, entry.settings.obsidian_root.as_deref(), &range).await
+    build_nutrition_dashboard(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        entry.settings.nutrition_goals.as_ref(),
+        &range,
+        today,
+    )
+    .await
 }
 
 #[tauri::command]
 pub(crate) async fn get_exercise_dashboard(
     workspace_id: String,
     range: String,
+    timezone: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<ExerciseDashboard, String> {
@@ -101,15 +133,26 @@ pub(crate) async fn get_exercise_dashboard(
             &*state,
             app,
             "get_exercise_dashboard",
-            json!({ "workspaceId": workspace_id, "range": range }),
+            json!({ "workspaceId": workspace_id, "range": range, "timezone": timezone }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
+    let today = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+        .await?
+        .today();
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
 
-    build_exercise_dashboard(&entry.path, entry.settings.obsidian_root.as_deref(), &range).await
+    build_exercise_dashboard(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        entry.settings.date_order,
+        &range,
+        today,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -131,7 +174,7 @@ pub(crate) async fn get_media_dashboard(
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
 
-    build_media_library(&entry.path, entry.settings.obsidian_root.as_deref()).await
+    build_media_library(&entry.path, &entry.settings.effective_obsidian_roots()).await
 }
 
 #[tauri::command]
@@ -153,13 +196,14 @@ pub(crate) async fn get_youtube_dashboard(
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
 
-    build_youtube_library(&entry.path, entry.settings.obsidian_root.as_deref()).await
+    build_youtube_library(&entry.path, &entry.settings.effective_obsidian_roots()).await
 }
 
 #[tauri::command]
 pub(crate) async fn enrich_media_covers(
     workspace_id: String,
     force: Option<bool>,
+    dry_run: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<MediaCoverSummary, String> {
@@ -168,48 +212,51 @@ pub(crate) async fn enrich_media_covers(
             &*state,
             app,
             "enrich_media_covers",
-            json!({ "workspaceId": workspace_id, "force": force.unwrap_or(false) }),
+            json!({
+                "workspaceId": workspace_id,
+                "force": force.unwrap_or(false),
+                "dryRun": dry_run.unwrap_or(false),
+            }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
-    let settings = state.app_settings.lock().await;
-    let tmdb_key = resolve_api_key(settings.tmdb_api_key.as_str(), "TMDB_API_KEY");
-    let igdb_client_id = resolve_api_key(settings.igdb_client_id.as_str(), "IGDB_CLIENT_ID");
-    let igdb_client_secret =
-        resolve_api_key(settings.igdb_client_secret.as_str(), "IGDB_CLIENT_SECRET");
-    let exa_api_key = if !settings.exa_api_key.trim().is_empty() {
-        Some(settings.exa_api_key.clone())
-    } else {
-        resolve_api_key("", "EXA_API_KEY")
-    };
-    let force_refresh = force.unwrap_or(false);
+    if !dry_run.unwrap_or(false) {
+        ensure_writable(entry, "enrich media covers")?;
+    }
+    let providers = MediaProviderConfig::from_settings(&*state.app_settings.lock().await);
+    let options = MediaCoverOptions::new(force.unwrap_or(false), dry_run.unwrap_or(false));
 
     enrich_media_covers_inner(
         &entry.path,
-        entry.settings.obsidian_root.as_deref(),
-        tmdb_key.as_deref(),
-        igdb_client_id.as_deref(),
-        igdb_client_secret.as_deref(),
-        exa_api_key.as_deref(),
-        force_refresh,
+        &entry.settings.effective_obsidian_roots(),
+        &providers,
+        &options,
     )
     .await
 }
 
-fn resolve_api_key(value: &str, env_key: &str) -> Option<String> {
-    if !value.trim().is_empty() {
-        return Some(value.to_string());
+#[tauri::command]
+pub(crate) async fn media_providers_status(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<MediaProviderStatus>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "media_providers_status", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    std::env::var(env_key).ok().filter(|v| !v.trim().is_empty())
+    let providers = MediaProviderConfig::from_settings(&*state.app_settings.lock().await);
+    Ok(media_providers_status_inner(&providers).await)
 }
 
 #[tauri::command]
 pub(crate) async fn get_finance_dashboard(
     workspace_id: String,
     range: String,
+    timezone: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<FinanceDashboard, String> {
@@ -218,7 +265,230 @@ pub(crate) async fn get_finance_dashboard(
             &*state,
             app,
             "get_finance_dashboard",
-            json!({ "workspaceId": workspace_id, "range": range }),
+            json!({ "workspaceId": workspace_id, "range": range, "timezone": timezone }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let today = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+        .await?
+        .today();
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+
+    build_finance_dashboard(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        entry.settings.date_order,
+        &range,
+        today,
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn export_life_report(
+    workspace_id: String,
+    domains: Option<Vec<String>>,
+    range: String,
+    format: Option<String>,
+    save: Option<bool>,
+    overwrite: Option<bool>,
+    timezone: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<LifeReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "export_life_report",
+            json!({
+                "workspaceId": workspace_id,
+                "domains": domains,
+                "range": range,
+                "format": format,
+                "save": save,
+                "overwrite": overwrite,
+                "timezone": timezone,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?
+    };
+    let options = LifeReportOptions {
+        domains: domains.unwrap_or_default(),
+        range,
+        format: ReportFormat::parse(format.as_deref().unwrap_or(""))?,
+        save: save.unwrap_or(false),
+        overwrite: overwrite.unwrap_or(false),
+    };
+    if options.save {
+        ensure_writable(&entry, "save a report")?;
+    }
+    let today = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+        .await?
+        .today();
+    export_life_report_inner(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        entry.settings.date_order,
+        entry.settings.nutrition_goals.clone(),
+        supabase_credentials(&state).await,
+        options,
+        today,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn mark_bill_paid(
+    workspace_id: String,
+    bill_path: String,
+    paid_date: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Bill, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "mark_bill_paid",
+            json!({ "workspaceId": workspace_id, "billPath": bill_path, "paidDate": paid_date }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+    ensure_writable(entry, "mark a bill paid")?;
+
+    let bill = mark_bill_paid_inner(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        entry.settings.date_order,
+        &bill_path,
+        paid_date.as_deref(),
+    )?;
+    invalidate_domain_trends("delivery_finance");
+    invalidate_domain_trends("finance");
+    Ok(bill)
+}
+
+#[tauri::command]
+pub(crate) async fn stream_append(
+    workspace_id: String,
+    date: Option<String>,
+    time: String,
+    text: String,
+    timezone: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<StreamAppend, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "stream_append",
+            json!({
+                "workspaceId": workspace_id,
+                "date": date,
+                "time": time,
+                "text": text,
+                "timezone": timezone,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let today = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Utc)
+        .await?
+        .today();
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+    ensure_writable(entry, "write to the stream")?;
+
+    workspace_stream_append(entry, date.as_deref(), &time, &text, today)
+}
+
+#[tauri::command]
+pub(crate) async fn update_media_item(
+    workspace_id: String,
+    path: String,
+    status: Option<String>,
+    rating: Option<f64>,
+    completed_at: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<MediaItem, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "update_media_item",
+            json!({
+                "workspaceId": workspace_id,
+                "path": path,
+                "status": status,
+                "rating": rating,
+                "completedAt": completed_at,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+    ensure_writable(entry, "update a media item")?;
+
+    let item = update_media_item_inner(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        entry.settings.date_order,
+        &path,
+        MediaItemUpdate {
+            status,
+            rating,
+            completed_at,
+        },
+    )?;
+    invalidate_domain_trends("media");
+    Ok(item)
+}
+
+#[tauri::command]
+pub(crate) async fn list_domain_entities(
+    workspace_id: String,
+    domain_id: String,
+    kind: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DomainEntityPage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_domain_entities",
+            json!({
+                "workspaceId": workspace_id,
+                "domainId": domain_id,
+                "kind": kind,
+                "offset": offset,
+                "limit": limit,
+            }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
@@ -226,5 +496,41 @@ pub(crate) async fn get_finance_dashboard(
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
 
-    build_finance_dashboard(&entry.path, entry.settings.obsidian_root.as_deref(), &range).await
+    list_domain_entities_inner(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        &entry.settings.entity_dirs,
+        &domain_id,
+        kind.as_deref(),
+        offset.unwrap_or(0),
+        limit,
+    )
+}
+
+#[tauri::command]
+pub(crate) async fn read_domain_entity(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DomainEntity, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "read_domain_entity",
+            json!({ "workspaceId": workspace_id, "path": path }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+
+    read_domain_entity_inner(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        &entry.settings.entity_dirs,
+        &path,
+    )
 }
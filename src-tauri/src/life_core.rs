@@ -1,11 +1,35 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
 
 use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
-use reqwest::{Client, Url};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url};
 use serde::{Deserialize, Serialize};
 
-use crate::types::{WorkspacePurpose, WorkspaceSettings};
+use crate::delivery_platforms::{PlatformBreakdown, TipSplit};
+use crate::frontmatter::{FrontmatterNote, FrontmatterPatch};
+use crate::life_report::{
+    render_report, report_file_name, trend_section, write_report, LifeReport, ReportFormat,
+    ReportSection,
+};
+use crate::nutrition_goals::{
+    goal_cards, goals_report, resolve_nutrition_goals, MacroTotals, NutritionGoalsReport,
+};
+use crate::obsidian::cards::{TrendBuilder, FOOD_LAYOUT};
+use crate::obsidian::compute_domain_trends;
+use crate::obsidian::dates::parse_vault_date;
+use crate::types::{
+    AppSettings, DateOrder, NutritionGoals, VaultContribution, WorkspacePurpose, WorkspaceSettings,
+};
+use crate::vault_roots::{
+    display_name, names_in_several_vaults, qualified_name, resolve_vault_roots, vault_contribution,
+    VaultRoot,
+};
+use crate::workouts::{
+    load_workout_entities, unmatched_stream_workouts, WorkoutEntity, MALFORMED_WORKOUTS_KEY,
+};
 
 const LIFE_PROMPT_FILES: [&str; 4] = [
     "workspace-delivery-finance.md",
@@ -29,6 +53,9 @@ pub(crate) struct DashboardMeta {
     pub(crate) sources: Vec<String>,
     #[serde(rename = "cacheHit", skip_serializing_if = "Option::is_none")]
     pub(crate) cache_hit: Option<bool>,
+    /// Per-vault counts behind the dashboard.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) roots: Vec<VaultContribution>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -53,6 +80,13 @@ pub(crate) struct DeliveryStats {
     pub(crate) ending_ar: Option<f64>,
     #[serde(rename = "whaleCatches", skip_serializing_if = "Option::is_none")]
     pub(crate) whale_catches: Option<u32>,
+    /// Set when any session records `tips` or `base_pay`.
+    #[serde(rename = "tipsTotal", skip_serializing_if = "Option::is_none")]
+    pub(crate) tips_total: Option<f64>,
+    #[serde(rename = "basePayTotal", skip_serializing_if = "Option::is_none")]
+    pub(crate) base_pay_total: Option<f64>,
+    #[serde(rename = "tipsPercent", skip_serializing_if = "Option::is_none")]
+    pub(crate) tips_percent: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -95,6 +129,19 @@ pub(crate) struct MerchantStats {
     pub(crate) tier: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct PlatformStats {
+    pub(crate) platform: String,
+    pub(crate) earnings: f64,
+    pub(crate) hours: f64,
+    #[serde(rename = "orderCount")]
+    pub(crate) order_count: u32,
+    #[serde(rename = "sessionCount")]
+    pub(crate) session_count: u32,
+    #[serde(rename = "hourlyRate")]
+    pub(crate) hourly_rate: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct DeliveryDashboard {
     pub(crate) meta: DashboardMeta,
@@ -102,6 +149,9 @@ pub(crate) struct DeliveryDashboard {
     pub(crate) orders: Vec<DeliveryOrder>,
     #[serde(rename = "topMerchants")]
     pub(crate) top_merchants: Vec<MerchantStats>,
+    /// Sessions that name a platform, highest earnings first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) platforms: Vec<PlatformStats>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -135,6 +185,9 @@ pub(crate) struct NutritionDashboard {
     pub(crate) meals: Vec<MealEntry>,
     #[serde(rename = "weeklyTrend", skip_serializing_if = "Option::is_none")]
     pub(crate) weekly_trend: Option<HashMap<String, f64>>,
+    /// Empty unless goals are set or could not be read.
+    #[serde(default)]
+    pub(crate) goals: NutritionGoalsReport,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -147,6 +200,12 @@ pub(crate) struct ExerciseStats {
     pub(crate) active_days: u32,
     #[serde(rename = "currentStreak")]
     pub(crate) current_streak: u32,
+    #[serde(rename = "totalDurationMin")]
+    pub(crate) total_duration_min: f64,
+    #[serde(rename = "distanceKm")]
+    pub(crate) distance_km: f64,
+    #[serde(rename = "caloriesBurned")]
+    pub(crate) calories_burned: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -160,6 +219,12 @@ pub(crate) struct ExerciseEntry {
     pub(crate) miles: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) duration: Option<f64>,
+    #[serde(rename = "distanceKm", skip_serializing_if = "Option::is_none")]
+    pub(crate) distance_km: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) calories: Option<f64>,
+    /// `stream` for a line in a stream note, `entity` for a workout note.
+    pub(crate) source: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -266,12 +331,209 @@ pub(crate) struct YouTubeLibrary {
     pub(crate) items: Vec<YouTubeIdea>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct MediaCoverSummary {
     pub total: u32,
     pub found: u32,
     pub skipped: u32,
     pub failed: u32,
+    /// Why each failed item got no cover.
+    #[serde(default)]
+    pub failures: Vec<MediaCoverFailure>,
+    #[serde(default, rename = "dryRun")]
+    pub dry_run: bool,
+    /// Dry runs only: the items a real run would look up.
+    #[serde(default)]
+    pub queries: Vec<MediaCoverQuery>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MediaCoverFailure {
+    pub title: String,
+    pub provider: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MediaCoverQuery {
+    pub title: String,
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
+    pub provider: String,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaCoverOutcome {
+    Hit,
+    Miss,
+    Error,
+}
+
+/// One looked-up item, reported as soon as its provider answers.
+#[derive(Debug, Serialize, Clone)]
+pub struct MediaCoverProgress {
+    /// Root of the vault the item is in.
+    pub vault: String,
+    pub title: String,
+    pub provider: String,
+    pub outcome: MediaCoverOutcome,
+    pub error: Option<String>,
+    /// Items looked up so far in this vault, out of `total`.
+    pub done: u32,
+    pub total: u32,
+}
+
+/// How an `enrich_media_covers` run behaves and reports progress.
+pub struct MediaCoverOptions<'a> {
+    pub force_refresh: bool,
+    /// List the lookups without calling any provider.
+    pub dry_run: bool,
+    pub on_progress: &'a (dyn Fn(&MediaCoverProgress) + Sync),
+    /// Checked before each lookup starts; lookups in flight still finish.
+    pub is_cancelled: &'a (dyn Fn() -> bool + Sync),
+}
+
+impl MediaCoverOptions<'static> {
+    /// No progress reporting and no cancellation.
+    pub fn new(force_refresh: bool, dry_run: bool) -> Self {
+        fn ignore_progress(_: &MediaCoverProgress) {}
+        fn never_cancelled() -> bool {
+            false
+        }
+        Self {
+            force_refresh,
+            dry_run,
+            on_progress: &ignore_progress,
+            is_cancelled: &never_cancelled,
+        }
+    }
+}
+
+/// Error prefix when an enabled provider's key was rejected before a run.
+/// The rest of the message is JSON: `{ "providers" }`, each a
+/// `MediaProviderStatus`.
+pub const MEDIA_PROVIDERS_INVALID_ERROR: &str = "media_providers_invalid";
+
+/// Credentials of the cover providers that need them, and whether TMDB and
+/// IGDB are used at all.
+#[derive(Debug, Clone)]
+pub struct MediaProviderConfig {
+    pub tmdb_api_key: Option<String>,
+    pub igdb_client_id: Option<String>,
+    pub igdb_client_secret: Option<String>,
+    pub exa_api_key: Option<String>,
+    pub tmdb_enabled: bool,
+    pub igdb_enabled: bool,
+}
+
+impl Default for MediaProviderConfig {
+    fn default() -> Self {
+        Self {
+            tmdb_api_key: None,
+            igdb_client_id: None,
+            igdb_client_secret: None,
+            exa_api_key: None,
+            tmdb_enabled: true,
+            igdb_enabled: true,
+        }
+    }
+}
+
+fn non_empty_key(value: &str, env_key: &str) -> Option<String> {
+    if !value.trim().is_empty() {
+        return Some(value.to_string());
+    }
+    std::env::var(env_key)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+impl MediaProviderConfig {
+    /// Keys left empty in the settings fall back to `TMDB_API_KEY`,
+    /// `IGDB_CLIENT_ID`, `IGDB_CLIENT_SECRET` and `EXA_API_KEY`.
+    pub(crate) fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            tmdb_api_key: non_empty_key(&settings.tmdb_api_key, "TMDB_API_KEY"),
+            igdb_client_id: non_empty_key(&settings.igdb_client_id, "IGDB_CLIENT_ID"),
+            igdb_client_secret: non_empty_key(&settings.igdb_client_secret, "IGDB_CLIENT_SECRET"),
+            exa_api_key: non_empty_key(&settings.exa_api_key, "EXA_API_KEY"),
+            tmdb_enabled: settings.tmdb_enabled,
+            igdb_enabled: settings.igdb_enabled,
+        }
+    }
+
+    /// Providers without a toggle are always enabled.
+    fn is_enabled(&self, provider: &str) -> bool {
+        match provider {
+            "tmdb" => self.tmdb_enabled,
+            "igdb" => self.igdb_enabled,
+            _ => true,
+        }
+    }
+
+    /// Why a provider cannot be asked, when it is missing credentials.
+    fn missing_credentials(&self, provider: &str) -> Option<&'static str> {
+        match provider {
+            "tmdb" if self.tmdb_api_key.is_none() => Some("TMDB API key is not set"),
+            "igdb" if self.igdb_client_id.is_none() || self.igdb_client_secret.is_none() => {
+                Some("IGDB client id and secret are not set")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether a provider's credentials work, from `media_providers_status`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MediaProviderStatus {
+    pub provider: String,
+    pub enabled: bool,
+    pub configured: bool,
+    /// Only true once the provider accepted the credentials.
+    pub valid: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Credentials shared by the lookups of one run.
+struct MediaCoverKeys {
+    tmdb_api_key: Option<String>,
+    igdb_client_id: Option<String>,
+    igdb_token: String,
+    exa_api_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DomainEntitySummary {
+    /// Absolute path of the note on the machine running the backend.
+    pub(crate) path: String,
+    pub(crate) vault: String,
+    pub(crate) title: String,
+    pub(crate) frontmatter: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DomainEntityPage {
+    #[serde(rename = "domainId")]
+    pub(crate) domain_id: String,
+    pub(crate) kind: String,
+    pub(crate) entities: Vec<DomainEntitySummary>,
+    pub(crate) total: usize,
+    pub(crate) offset: usize,
+    #[serde(rename = "nextOffset")]
+    pub(crate) next_offset: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DomainEntity {
+    pub(crate) path: String,
+    pub(crate) vault: String,
+    pub(crate) title: String,
+    /// Entity kind when the note sits in a mapped folder.
+    pub(crate) kind: Option<String>,
+    pub(crate) frontmatter: serde_json::Value,
+    pub(crate) body: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -305,7 +567,7 @@ struct MediaCoverOverrideItem {
     updated_at: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct DeliverySessionFrontmatter {
     id: Option<String>,
     date: Option<String>,
@@ -323,9 +585,12 @@ struct DeliverySessionFrontmatter {
     ending_ar: Option<f64>,
     #[serde(rename = "whale_catches")]
     whale_catches: Option<f64>,
+    platform: Option<String>,
+    tips: Option<f64>,
+    base_pay: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct FoodFrontmatter {
     name: Option<String>,
     calories: Option<f64>,
@@ -336,7 +601,7 @@ struct FoodFrontmatter {
     category: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct BillFrontmatter {
     name: Option<String>,
     amount: Option<f64>,
@@ -346,6 +611,9 @@ struct BillFrontmatter {
     category: Option<String>,
     #[serde(rename = "auto_pay")]
     auto_pay: Option<bool>,
+    next_due: Option<String>,
+    #[serde(default)]
+    payments: Vec<serde_yaml::Value>,
 }
 
 #[derive(Debug, Clone)]
@@ -360,6 +628,9 @@ struct DeliverySessionRecord {
     ending_ar: Option<f64>,
     whale_catches: u32,
     orders: Vec<DeliveryOrder>,
+    platform: Option<String>,
+    tips: Option<f64>,
+    base_pay: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -398,6 +669,9 @@ struct ExerciseParseRecord {
     entry_type: String,
     miles: Option<f64>,
     duration: Option<f64>,
+    distance_km: Option<f64>,
+    calories: Option<f64>,
+    source: &'static str,
 }
 
 #[derive(Debug, Clone)]
@@ -405,6 +679,8 @@ struct BillRecord {
     bill: Bill,
     monthly_equivalent: f64,
     due_date: NaiveDate,
+    /// A one-off bill that has been paid; it no longer shows as upcoming.
+    settled: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -431,7 +707,7 @@ struct DeliveryMerchantMeta {
     tier: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct MediaFrontmatter {
     id: Option<String>,
     title: Option<String>,
@@ -446,9 +722,10 @@ struct MediaFrontmatter {
     cover_url: Option<String>,
     url: Option<String>,
     youtube_id: Option<String>,
+    rating_scale: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct YouTubeIdeaFrontmatter {
     id: Option<String>,
     title: Option<String>,
@@ -586,19 +863,13 @@ pub(crate) fn build_life_workspace_prompt() -> Result<String, String> {
 
 pub(crate) async fn build_delivery_dashboard(
     workspace_path: &str,
-    obsidian_root: Option<&str>,
+    obsidian_roots: &[String],
     supabase_url: Option<&str>,
     supabase_key: Option<&str>,
     range: &str,
+    today: NaiveDate,
 ) -> Result<DeliveryDashboard, String> {
-    let root = resolve_obsidian_root(workspace_path, obsidian_root);
-    if !root.exists() {
-        return Err(format!(
-            "Obsidian root not found: {}",
-            root.to_string_lossy()
-        ));
-    }
-    let today = Utc::now().date_naive();
+    let vaults = resolve_existing_vaults(workspace_path, obsidian_roots)?;
     let (period, start_date, end_date) = match range {
         "today" => (None, Some(today), Some(today)),
         "week" | "7d" => (Some("week"), Some(today - Duration::days(6)), Some(today)),
@@ -607,11 +878,20 @@ pub(crate) async fn build_delivery_dashboard(
         _ => (None, None, Some(today)),
     };
 
-    let sessions_dir = root.join("Entities").join("Delivery").join("Sessions");
-    if !sessions_dir.exists() {
+    let sessions_dirs: Vec<PathBuf> = vaults
+        .iter()
+        .map(|vault| {
+            vault
+                .path
+                .join("Entities")
+                .join("Delivery")
+                .join("Sessions")
+        })
+        .collect();
+    if !sessions_dirs.iter().any(|dir| dir.exists()) {
         return Err(format!(
             "Delivery sessions not found at {}",
-            sessions_dir.to_string_lossy()
+            sessions_dirs[0].to_string_lossy()
         ));
     }
 
@@ -629,6 +909,9 @@ pub(crate) async fn build_delivery_dashboard(
                     starting_ar: None,
                     ending_ar: None,
                     whale_catches: None,
+                    tips_total: None,
+                    base_pay_total: None,
+                    tips_percent: None,
                 };
                 let meta = DashboardMeta {
                     domain: "delivery".to_string(),
@@ -638,18 +921,33 @@ pub(crate) async fn build_delivery_dashboard(
                     generated_at: row.computed_at.unwrap_or_else(|| Utc::now().to_rfc3339()),
                     sources: vec!["supabase".to_string()],
                     cache_hit: None,
+                    roots: Vec::new(),
                 };
                 return Ok(DeliveryDashboard {
                     meta,
                     stats,
                     orders: Vec::new(),
                     top_merchants: Vec::new(),
+                    platforms: Vec::new(),
                 });
             }
         }
     }
 
-    let sessions = load_delivery_sessions(&root);
+    let mut sessions = Vec::new();
+    let mut merchant_tiers = HashMap::new();
+    let mut roots = Vec::new();
+    for vault in &vaults {
+        let vault_sessions = load_delivery_sessions(&vault.path);
+        roots.push(vault_contribution(
+            vault,
+            &[("sessions", vault_sessions.len())],
+        ));
+        sessions.extend(vault_sessions);
+        for (merchant, tier) in load_delivery_merchant_tiers(&vault.path) {
+            merchant_tiers.entry(merchant).or_insert(tier);
+        }
+    }
     let filtered: Vec<_> = sessions
         .into_iter()
         .filter(|session| {
@@ -668,6 +966,8 @@ pub(crate) async fn build_delivery_dashboard(
     let mut order_count = 0u32;
     let mut whale_catches = 0u32;
     let mut orders = Vec::new();
+    let mut platforms = PlatformBreakdown::default();
+    let mut tip_split = TipSplit::default();
 
     for session in &filtered {
         total_earnings += session.earnings;
@@ -679,6 +979,13 @@ pub(crate) async fn build_delivery_dashboard(
         }
         orders.extend(session.orders.clone());
         whale_catches += session.whale_catches;
+        platforms.add(
+            session.platform.as_deref(),
+            session.earnings,
+            session.hours,
+            session.orders_count as f64,
+        );
+        tip_split.add(session.earnings, session.tips, session.base_pay);
     }
 
     let hourly_rate = if total_hours > 0.0 {
@@ -717,10 +1024,24 @@ pub(crate) async fn build_delivery_dashboard(
         } else {
             None
         },
+        tips_total: (tip_split.sessions > 0).then_some(tip_split.tips),
+        base_pay_total: (tip_split.sessions > 0).then_some(tip_split.base_pay),
+        tips_percent: tip_split.tips_percent(),
     };
 
-    let merchant_tiers = load_delivery_merchant_tiers(&root);
     let top_merchants = build_top_merchants(&orders, &merchant_tiers);
+    let platforms = platforms
+        .into_sorted()
+        .into_iter()
+        .map(|totals| PlatformStats {
+            platform: totals.platform.to_string(),
+            earnings: totals.earnings,
+            hours: totals.hours,
+            order_count: totals.orders as u32,
+            session_count: totals.sessions,
+            hourly_rate: totals.hourly(),
+        })
+        .collect();
 
     let period_start = start_date
         .or_else(|| filtered.iter().map(|s| s.date).min())
@@ -739,6 +1060,7 @@ pub(crate) async fn build_delivery_dashboard(
         generated_at: Utc::now().to_rfc3339(),
         sources: vec!["obsidian".to_string()],
         cache_hit: None,
+        roots,
     };
 
     Ok(DeliveryDashboard {
@@ -746,22 +1068,18 @@ pub(crate) async fn build_delivery_dashboard(
         stats,
         orders,
         top_merchants,
+        platforms,
     })
 }
 
 pub(crate) async fn build_nutrition_dashboard(
     workspace_path: &str,
-    obsidian_root: Option<&str>,
+    obsidian_roots: &[String],
+    nutrition_goals: Option<&NutritionGoals>,
     range: &str,
+    today: NaiveDate,
 ) -> Result<NutritionDashboard, String> {
-    let root = resolve_obsidian_root(workspace_path, obsidian_root);
-    if !root.exists() {
-        return Err(format!(
-            "Obsidian root not found: {}",
-            root.to_string_lossy()
-        ));
-    }
-    let today = Utc::now().date_naive();
+    let vaults = resolve_existing_vaults(workspace_path, obsidian_roots)?;
     let (start_date, end_date) = match range {
         "today" => (Some(today), Some(today)),
         "week" | "7d" => (Some(today - Duration::days(6)), Some(today)),
@@ -770,8 +1088,32 @@ pub(crate) async fn build_nutrition_dashboard(
         _ => (None, Some(today)),
     };
 
-    let food_map = load_food_library(&root);
-    let meals = load_meal_entries(&root, start_date, end_date, &food_map);
+    let libraries: Vec<_> = vaults
+        .iter()
+        .map(|vault| (vault.name.clone(), load_food_library(&vault.path)))
+        .collect();
+    let duplicate_foods = names_in_several_vaults(libraries.iter().flat_map(|(vault, library)| {
+        library
+            .keys()
+            .map(move |key| (vault.as_str(), key.as_str()))
+    }));
+    let mut meals = Vec::new();
+    let mut roots = Vec::new();
+    for (vault, (vault_name, library)) in vaults.iter().zip(&libraries) {
+        let food_map = food_map_for_vault(&libraries, &duplicate_foods, vault_name);
+        let vault_meals = load_meal_entries(&vault.path, start_date, end_date, &food_map);
+        let food_count = library
+            .values()
+            .map(|food| food.name.as_str())
+            .collect::<HashSet<_>>()
+            .len();
+        roots.push(vault_contribution(
+            vault,
+            &[("meals", vault_meals.len()), ("foods", food_count)],
+        ));
+        meals.extend(vault_meals);
+    }
+    meals.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
 
     let mut stats = NutritionStats::default();
     let mut fiber_total = 0.0;
@@ -801,6 +1143,23 @@ pub(crate) async fn build_nutrition_dashboard(
         .to_string();
 
     let weekly_trend = build_weekly_calorie_trend(&meals, end_date.unwrap_or(today));
+    let mut daily: BTreeMap<NaiveDate, MacroTotals> = BTreeMap::new();
+    for meal in &meals {
+        daily
+            .entry(meal.date)
+            .or_default()
+            .add(meal.calories, meal.protein, meal.carbs, meal.fat);
+    }
+    let vault_paths: Vec<&Path> = vaults.iter().map(|vault| vault.path.as_path()).collect();
+    let (goals, goals_source, diagnostics) = resolve_nutrition_goals(&vault_paths, nutrition_goals);
+    let goals = goals_report(
+        goals,
+        goals_source,
+        diagnostics,
+        range,
+        &daily,
+        end_date.filter(|end| *end == today),
+    );
 
     let meta = DashboardMeta {
         domain: "nutrition".to_string(),
@@ -810,6 +1169,7 @@ pub(crate) async fn build_nutrition_dashboard(
         generated_at: Utc::now().to_rfc3339(),
         sources: vec!["obsidian".to_string()],
         cache_hit: None,
+        roots,
     };
 
     let meals = meals
@@ -830,22 +1190,18 @@ pub(crate) async fn build_nutrition_dashboard(
         stats,
         meals,
         weekly_trend,
+        goals,
     })
 }
 
 pub(crate) async fn build_exercise_dashboard(
     workspace_path: &str,
-    obsidian_root: Option<&str>,
+    obsidian_roots: &[String],
+    date_order: DateOrder,
     range: &str,
+    today: NaiveDate,
 ) -> Result<ExerciseDashboard, String> {
-    let root = resolve_obsidian_root(workspace_path, obsidian_root);
-    if !root.exists() {
-        return Err(format!(
-            "Obsidian root not found: {}",
-            root.to_string_lossy()
-        ));
-    }
-    let today = Utc::now().date_naive();
+    let vaults = resolve_existing_vaults(workspace_path, obsidian_roots)?;
     let (start_date, end_date) = match range {
         "today" => (Some(today), Some(today)),
         "week" | "7d" => (Some(today - Duration::days(6)), Some(today)),
@@ -854,8 +1210,37 @@ pub(crate) async fn build_exercise_dashboard(
         _ => (None, Some(today)),
     };
 
-    let entries = load_exercise_entries(&root, start_date, end_date);
-    let all_activity_dates = load_activity_dates(&root);
+    let mut entries = Vec::new();
+    let mut all_activity_dates = HashSet::new();
+    let mut roots = Vec::new();
+    for vault in &vaults {
+        let stream_entries = load_exercise_entries(&vault.path, start_date, end_date);
+        let workouts = load_workout_entities(&vault.path, &mut |value| {
+            parse_vault_date(value, date_order)
+        });
+        all_activity_dates.extend(workouts.workouts.iter().map(|workout| workout.date));
+        let in_range: Vec<WorkoutEntity> = workouts
+            .workouts
+            .into_iter()
+            .filter(|workout| date_in_range(workout.date, start_date, end_date))
+            .collect();
+        // A session logged both in the stream and as a note counts once.
+        let stream_entries = unmatched_stream_workouts(stream_entries, &in_range, |entry| {
+            (entry.date, entry.entry_type.clone(), entry.duration)
+        });
+        let mut counts = vec![
+            ("entries", stream_entries.len()),
+            ("workouts", in_range.len()),
+        ];
+        if workouts.malformed > 0 {
+            counts.push((MALFORMED_WORKOUTS_KEY, workouts.malformed));
+        }
+        roots.push(vault_contribution(vault, &counts));
+        entries.extend(stream_entries);
+        entries.extend(in_range.into_iter().map(workout_record));
+        all_activity_dates.extend(load_activity_dates(&vault.path));
+    }
+    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
 
     let mut stats = ExerciseStats::default();
     let mut by_type: HashMap<String, u32> = HashMap::new();
@@ -865,6 +1250,9 @@ pub(crate) async fn build_exercise_dashboard(
         let counter = by_type.entry(entry.entry_type.clone()).or_insert(0);
         *counter += 1;
         active_days.insert(entry.date, true);
+        stats.total_duration_min += entry.duration.unwrap_or(0.0);
+        stats.distance_km += entry.distance_km.unwrap_or(0.0);
+        stats.calories_burned += entry.calories.unwrap_or(0.0);
         if entry.entry_type == "walk" {
             if let Some(miles) = entry.miles {
                 stats.walking_miles += miles;
@@ -894,6 +1282,7 @@ pub(crate) async fn build_exercise_dashboard(
         generated_at: Utc::now().to_rfc3339(),
         sources: vec!["obsidian".to_string()],
         cache_hit: None,
+        roots,
     };
 
     let entries = entries
@@ -906,6 +1295,9 @@ pub(crate) async fn build_exercise_dashboard(
             description: entry.description,
             miles: entry.miles,
             duration: entry.duration,
+            distance_km: entry.distance_km,
+            calories: entry.calories,
+            source: entry.source.to_string(),
         })
         .collect();
 
@@ -919,18 +1311,13 @@ pub(crate) async fn build_exercise_dashboard(
 
 pub(crate) async fn build_finance_dashboard(
     workspace_path: &str,
-    obsidian_root: Option<&str>,
+    obsidian_roots: &[String],
+    date_order: DateOrder,
     range: &str,
+    today: NaiveDate,
 ) -> Result<FinanceDashboard, String> {
-    let root = resolve_obsidian_root(workspace_path, obsidian_root);
-    if !root.exists() {
-        return Err(format!(
-            "Obsidian root not found: {}",
-            root.to_string_lossy()
-        ));
-    }
+    let vaults = resolve_existing_vaults(workspace_path, obsidian_roots)?;
 
-    let today = Utc::now().date_naive();
     let (start_date, end_date) = match range {
         "today" => (Some(today), Some(today)),
         "week" | "7d" => (Some(today - Duration::days(6)), Some(today)),
@@ -938,8 +1325,32 @@ pub(crate) async fn build_finance_dashboard(
         "lifetime" => (None, Some(today)),
         _ => (None, Some(today)),
     };
-    let bills_dir = root.join("Entities").join("Finance").join("Bills");
-    let bill_records = load_bill_records(&bills_dir, today);
+    let mut tagged = Vec::new();
+    let mut bills_dir_exists = false;
+    let mut roots = Vec::new();
+    for vault in &vaults {
+        let bills_dir = vault.path.join("Entities").join("Finance").join("Bills");
+        bills_dir_exists |= bills_dir.exists();
+        let records = load_bill_records(&bills_dir, today, date_order);
+        roots.push(vault_contribution(vault, &[("bills", records.len())]));
+        tagged.extend(
+            records
+                .into_iter()
+                .map(|record| (vault.name.clone(), record)),
+        );
+    }
+    let duplicate_bills = names_in_several_vaults(
+        tagged
+            .iter()
+            .map(|(vault, record)| (vault.as_str(), record.bill.name.as_str())),
+    );
+    let bill_records: Vec<BillRecord> = tagged
+        .into_iter()
+        .map(|(vault, mut record)| {
+            record.bill.name = display_name(&vault, &record.bill.name, &duplicate_bills);
+            record
+        })
+        .collect();
 
     let earliest_due = bill_records.iter().map(|record| record.due_date).min();
     let latest_due = bill_records.iter().map(|record| record.due_date).max();
@@ -953,9 +1364,10 @@ pub(crate) async fn build_finance_dashboard(
         generated_at: Utc::now().to_rfc3339(),
         sources: vec!["obsidian".to_string()],
         cache_hit: None,
+        roots,
     };
 
-    if bill_records.is_empty() && bills_dir.exists() {
+    if bill_records.is_empty() && bills_dir_exists {
         return Ok(FinanceDashboard {
             meta,
             stats: FinanceStats::default(),
@@ -998,29 +1410,409 @@ pub(crate) async fn build_finance_dashboard(
     })
 }
 
+/// Dashboards `export_life_report` can include, in report order.
+pub(crate) const REPORT_DOMAINS: &[&str] = &["delivery", "nutrition", "exercise", "finance"];
+
+/// What to put in a Life report and where it goes.
+#[derive(Debug, Clone)]
+pub(crate) struct LifeReportOptions {
+    /// Entries of `REPORT_DOMAINS`; empty means all of them.
+    pub(crate) domains: Vec<String>,
+    pub(crate) range: String,
+    pub(crate) format: ReportFormat,
+    /// Save to `Reports/` in the first vault instead of only returning it.
+    pub(crate) save: bool,
+    pub(crate) overwrite: bool,
+}
+
+/// Renders the requested dashboards, followed by the trend snapshots of their
+/// domains, into one document. Dashboards and trends are built concurrently;
+/// trends go through the trend cache. A dashboard that fails shows its error
+/// in its section instead of failing the report.
+pub(crate) async fn export_life_report(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+    date_order: DateOrder,
+    nutrition_goals: Option<NutritionGoals>,
+    supabase: Option<(String, String)>,
+    options: LifeReportOptions,
+    today: NaiveDate,
+) -> Result<LifeReport, String> {
+    let vaults = resolve_existing_vaults(workspace_path, obsidian_roots)?;
+    let mut domains: Vec<&str> = Vec::new();
+    for domain in &options.domains {
+        let domain = REPORT_DOMAINS
+            .iter()
+            .find(|known| known.eq_ignore_ascii_case(domain.trim()))
+            .ok_or_else(|| format!("Unknown report domain: {domain}"))?;
+        if !domains.contains(domain) {
+            domains.push(domain);
+        }
+    }
+    if domains.is_empty() {
+        domains = REPORT_DOMAINS.to_vec();
+    }
+    let mut trend_domains: Vec<&str> = Vec::new();
+    for domain in &domains {
+        let trend_domain = report_trend_domain(domain);
+        if !trend_domains.contains(&trend_domain) {
+            trend_domains.push(trend_domain);
+        }
+    }
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, domain) in domains.iter().enumerate() {
+        let domain = domain.to_string();
+        let workspace_path = workspace_path.to_string();
+        let obsidian_roots = obsidian_roots.to_vec();
+        let supabase = supabase.clone();
+        let nutrition_goals = nutrition_goals.clone();
+        let range = options.range.clone();
+        tasks.spawn(async move {
+            let section = dashboard_report_section(
+                &domain,
+                &workspace_path,
+                &obsidian_roots,
+                nutrition_goals.as_ref(),
+                supabase,
+                &range,
+                today,
+            )
+            .await;
+            (index, section)
+        });
+    }
+    if let Some(trend_range) = report_trend_range(&options.range) {
+        for (offset, trend_domain) in trend_domains.iter().enumerate() {
+            let index = domains.len() + offset;
+            let trend_domain = trend_domain.to_string();
+            let workspace_path = workspace_path.to_string();
+            let obsidian_roots = obsidian_roots.to_vec();
+            let trend_range = trend_range.to_string();
+            let nutrition_goals = nutrition_goals.clone();
+            tasks.spawn_blocking(move || {
+                let title = match trend_domain.as_str() {
+                    "delivery_finance" => "Delivery & finance trends",
+                    _ => "Food & exercise trends",
+                };
+                let section = match compute_domain_trends(
+                    &workspace_path,
+                    &obsidian_roots,
+                    date_order,
+                    nutrition_goals.as_ref(),
+                    &trend_domain,
+                    &trend_range,
+                    false,
+                    false,
+                    today,
+                ) {
+                    Ok(snapshot) => trend_section(title, &snapshot),
+                    Err(err) => ReportSection {
+                        note: Some(err),
+                        ..ReportSection::new(title)
+                    },
+                };
+                (index, section)
+            });
+        }
+    }
+    let mut sections = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        sections.push(result.map_err(|err| err.to_string())?);
+    }
+    sections.sort_by_key(|(index, _)| *index);
+    let sections: Vec<ReportSection> = sections.into_iter().map(|(_, section)| section).collect();
+
+    let now = Utc::now();
+    let content = render_report(&options.range, &now.to_rfc3339(), &sections, options.format);
+    let file_name = report_file_name(&today.to_string(), &options.range, options.format);
+    let path = if options.save {
+        let path = write_report(&vaults[0].path, &file_name, &content, options.overwrite)?;
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    };
+    Ok(LifeReport {
+        format: options.format,
+        file_name,
+        content,
+        path,
+    })
+}
+
+fn report_trend_domain(domain: &str) -> &'static str {
+    match domain {
+        "delivery" | "finance" => "delivery_finance",
+        _ => "food_exercise",
+    }
+}
+
+/// The trend range matching a dashboard range. Trends have no single-day
+/// range, so `today` reports leave them out.
+fn report_trend_range(range: &str) -> Option<&str> {
+    match range {
+        "today" => None,
+        "week" => Some("7d"),
+        "month" => Some("30d"),
+        "lifetime" => Some("all"),
+        other => Some(other),
+    }
+}
+
+async fn dashboard_report_section(
+    domain: &str,
+    workspace_path: &str,
+    obsidian_roots: &[String],
+    nutrition_goals: Option<&NutritionGoals>,
+    supabase: Option<(String, String)>,
+    range: &str,
+    today: NaiveDate,
+) -> ReportSection {
+    let result = match domain {
+        "delivery" => build_delivery_dashboard(
+            workspace_path,
+            obsidian_roots,
+            supabase.as_ref().map(|value| value.0.as_str()),
+            supabase.as_ref().map(|value| value.1.as_str()),
+            range,
+            today,
+        )
+        .await
+        .map(|dashboard| delivery_report_section(&dashboard)),
+        "nutrition" => build_nutrition_dashboard(
+            workspace_path,
+            obsidian_roots,
+            nutrition_goals,
+            range,
+            today,
+        )
+        .await
+        .map(|dashboard| nutrition_report_section(&dashboard)),
+        "exercise" => {
+            build_exercise_dashboard(workspace_path, obsidian_roots, date_order, range, today)
+                .await
+                .map(|dashboard| exercise_report_section(&dashboard))
+        }
+        _ => build_finance_dashboard(workspace_path, obsidian_roots, date_order, range, today)
+            .await
+            .map(|dashboard| finance_report_section(&dashboard)),
+    };
+    result.unwrap_or_else(|err| {
+        let mut title = domain.to_string();
+        if let Some(first) = title.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+        ReportSection {
+            note: Some(err),
+            ..ReportSection::new(&title)
+        }
+    })
+}
+
+fn period_note(meta: &DashboardMeta) -> Option<String> {
+    Some(format!("{} to {}", meta.period_start, meta.period_end))
+}
+
+fn delivery_report_section(dashboard: &DeliveryDashboard) -> ReportSection {
+    let stats = &dashboard.stats;
+    let mut section = ReportSection::new("Delivery");
+    section.note = period_note(&dashboard.meta);
+    section.card("Earnings", format!("${:.2}", stats.total_earnings));
+    section.card("Orders", stats.order_count.to_string());
+    section.card("Active hours", format!("{:.1}", stats.active_hours));
+    section.card("Hourly rate", format!("${:.2}", stats.hourly_rate));
+    if let Some(miles) = stats.total_miles {
+        section.card("Miles", format!("{miles:.1}"));
+        section.card("Per mile", format!("${:.2}", stats.per_mile_rate));
+    }
+    if let Some(tips_percent) = stats.tips_percent {
+        section.card("Tips", format!("{tips_percent:.0}%"));
+    }
+    let merchants = dashboard
+        .top_merchants
+        .iter()
+        .map(|merchant| {
+            vec![
+                merchant.merchant_name.clone(),
+                merchant.order_count.to_string(),
+                format!("${:.2}", merchant.total_earnings),
+                format!("${:.2}", merchant.avg_payout),
+            ]
+        })
+        .collect();
+    section.table(
+        "Top merchants",
+        &["Merchant", "Orders", "Earnings", "Avg payout"],
+        merchants,
+    );
+    let platforms = dashboard
+        .platforms
+        .iter()
+        .map(|platform| {
+            vec![
+                platform.platform.clone(),
+                format!("${:.2}", platform.earnings),
+                format!("{:.1}", platform.hours),
+                platform.order_count.to_string(),
+                format!("${:.2}", platform.hourly_rate),
+            ]
+        })
+        .collect();
+    section.table(
+        "Platforms",
+        &["Platform", "Earnings", "Hours", "Orders", "Hourly"],
+        platforms,
+    );
+    section
+}
+
+fn nutrition_report_section(dashboard: &NutritionDashboard) -> ReportSection {
+    let stats = &dashboard.stats;
+    let mut section = ReportSection::new("Nutrition");
+    section.note = period_note(&dashboard.meta);
+    section.card("Calories", format!("{:.0}", stats.calories));
+    section.card("Protein", format!("{:.0}g", stats.protein));
+    section.card("Carbs", format!("{:.0}g", stats.carbs));
+    section.card("Fat", format!("{:.0}g", stats.fat));
+    if let Some(fiber) = stats.fiber {
+        section.card("Fiber", format!("{fiber:.0}g"));
+    }
+    section.card("Meals", stats.meal_count.to_string());
+    let mut goal_trends = TrendBuilder::new(&FOOD_LAYOUT);
+    goal_cards(&dashboard.goals, &mut goal_trends);
+    for card in goal_trends.finish().0 {
+        section.card(&card.label, card.value);
+    }
+    let mut days: Vec<(&String, &f64)> = dashboard
+        .weekly_trend
+        .iter()
+        .flat_map(|trend| trend.iter())
+        .collect();
+    days.sort_by_key(|(day, _)| *day);
+    let rows = days
+        .into_iter()
+        .map(|(day, calories)| vec![day.clone(), format!("{calories:.0}")])
+        .collect();
+    section.table("Calories by day", &["Day", "Calories"], rows);
+    section
+}
+
+fn exercise_report_section(dashboard: &ExerciseDashboard) -> ReportSection {
+    let stats = &dashboard.stats;
+    let mut section = ReportSection::new("Exercise");
+    section.note = period_note(&dashboard.meta);
+    section.card("Workouts", stats.workout_count.to_string());
+    section.card("Active days", stats.active_days.to_string());
+    section.card("Current streak", stats.current_streak.to_string());
+    section.card("Minutes", format!("{:.0}", stats.total_duration_min));
+    section.card("Distance", format!("{:.1} km", stats.distance_km));
+    section.card("Walking miles", format!("{:.1}", stats.walking_miles));
+    section.card("Calories burned", format!("{:.0}", stats.calories_burned));
+    let mut by_type: Vec<(&String, &u32)> = dashboard.by_type.iter().collect();
+    by_type.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let rows = by_type
+        .into_iter()
+        .map(|(kind, count)| vec![kind.clone(), count.to_string()])
+        .collect();
+    section.table("By type", &["Type", "Workouts"], rows);
+    section
+}
+
+fn finance_report_section(dashboard: &FinanceDashboard) -> ReportSection {
+    let stats = &dashboard.stats;
+    let mut section = ReportSection::new("Finance");
+    section.note = dashboard
+        .status_message
+        .clone()
+        .or_else(|| period_note(&dashboard.meta));
+    section.card("Monthly bills", format!("${:.2}", stats.monthly_total));
+    section.card("Due in 7 days", stats.due_soon_count.to_string());
+    section.card("Autopay", stats.auto_pay_count.to_string());
+    let bills = dashboard
+        .bills
+        .iter()
+        .map(|bill| {
+            vec![
+                bill.name.clone(),
+                format!("${:.2}", bill.amount),
+                bill.next_due_date.clone(),
+                bill.category.clone(),
+                if bill.auto_pay { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+    section.table(
+        "Bills",
+        &["Bill", "Amount", "Next due", "Category", "Autopay"],
+        bills,
+    );
+    let mut categories: Vec<(&String, &f64)> = dashboard.by_category.iter().collect();
+    categories.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let rows = categories
+        .into_iter()
+        .map(|(category, amount)| vec![category.clone(), format!("${amount:.2}")])
+        .collect();
+    section.table("Monthly by category", &["Category", "Amount"], rows);
+    section
+}
+
 pub(crate) async fn build_media_library(
     workspace_path: &str,
-    obsidian_root: Option<&str>,
+    obsidian_roots: &[String],
 ) -> Result<MediaLibrary, String> {
-    let root = resolve_obsidian_root(workspace_path, obsidian_root);
-    if !root.exists() {
-        return Err(format!(
-            "Obsidian root not found: {}",
-            root.to_string_lossy()
-        ));
-    }
+    let vaults = resolve_existing_vaults(workspace_path, obsidian_roots)?;
 
-    let media_dir = root.join("Entities").join("Media");
-    if !media_dir.exists() {
+    let media_dirs: Vec<PathBuf> = vaults
+        .iter()
+        .map(|vault| vault.path.join("Entities").join("Media"))
+        .collect();
+    if !media_dirs.iter().any(|dir| dir.exists()) {
         return Err(format!(
             "Media entries not found at {}",
-            media_dir.to_string_lossy()
+            media_dirs[0].to_string_lossy()
         ));
     }
 
-    let mut records = load_media_items(&root);
-    let overrides = load_media_cover_overrides(&root);
-    let cache = load_media_cover_cache(&root);
+    let mut tagged = Vec::new();
+    let mut roots = Vec::new();
+    for vault in &vaults {
+        let mut vault_records = load_media_items(&vault.path);
+        let overrides = load_media_cover_overrides(&vault.path);
+        let cache = load_media_cover_cache(&vault.path);
+        for record in &mut vault_records {
+            if let Some(entry) = overrides.get(&record.item.id) {
+                record.item.cover_url = Some(entry.cover_url.clone());
+            } else if record.item.cover_url.is_none() {
+                if let Some(entry) = cache.get(&record.item.id) {
+                    record.item.cover_url = Some(entry.cover_url.clone());
+                }
+            }
+        }
+        roots.push(vault_contribution(vault, &[("items", vault_records.len())]));
+        tagged.extend(
+            vault_records
+                .into_iter()
+                .map(|record| (vault.name.clone(), record)),
+        );
+    }
+    let duplicate_titles = names_in_several_vaults(
+        tagged
+            .iter()
+            .map(|(vault, record)| (vault.as_str(), record.item.title.as_str())),
+    );
+    let duplicate_ids = names_in_several_vaults(
+        tagged
+            .iter()
+            .map(|(vault, record)| (vault.as_str(), record.item.id.as_str())),
+    );
+    let records: Vec<MediaRecord> = tagged
+        .into_iter()
+        .map(|(vault, mut record)| {
+            record.item.title = display_name(&vault, &record.item.title, &duplicate_titles);
+            record.item.id = display_name(&vault, &record.item.id, &duplicate_ids);
+            record
+        })
+        .collect();
     let total_count = records.len() as u32;
     let mut completed_count = 0u32;
     let mut backlog_count = 0u32;
@@ -1029,14 +1821,7 @@ pub(crate) async fn build_media_library(
     let mut earliest: Option<DateTime<Utc>> = None;
     let mut latest: Option<DateTime<Utc>> = None;
 
-    for record in &mut records {
-        if let Some(entry) = overrides.get(&record.item.id) {
-            record.item.cover_url = Some(entry.cover_url.clone());
-        } else if record.item.cover_url.is_none() {
-            if let Some(entry) = cache.get(&record.item.id) {
-                record.item.cover_url = Some(entry.cover_url.clone());
-            }
-        }
+    for record in &records {
         match record.item.status.as_str() {
             "Completed" => completed_count += 1,
             "Backlog" => backlog_count += 1,
@@ -1079,6 +1864,7 @@ pub(crate) async fn build_media_library(
         generated_at: Utc::now().to_rfc3339(),
         sources: vec!["obsidian".to_string()],
         cache_hit: None,
+        roots,
     };
 
     let items = records.into_iter().map(|record| record.item).collect();
@@ -1095,25 +1881,44 @@ pub(crate) async fn build_media_library(
 
 pub(crate) async fn build_youtube_library(
     workspace_path: &str,
-    obsidian_root: Option<&str>,
+    obsidian_roots: &[String],
 ) -> Result<YouTubeLibrary, String> {
-    let root = resolve_obsidian_root(workspace_path, obsidian_root);
-    if !root.exists() {
-        return Err(format!(
-            "Obsidian root not found: {}",
-            root.to_string_lossy()
-        ));
-    }
+    let vaults = resolve_existing_vaults(workspace_path, obsidian_roots)?;
 
-    let ideas_dir = root.join("Entities").join("YouTube");
-    if !ideas_dir.exists() {
+    let ideas_dirs: Vec<PathBuf> = vaults
+        .iter()
+        .map(|vault| vault.path.join("Entities").join("YouTube"))
+        .collect();
+    if !ideas_dirs.iter().any(|dir| dir.exists()) {
         return Err(format!(
             "YouTube ideas not found at {}",
-            ideas_dir.to_string_lossy()
+            ideas_dirs[0].to_string_lossy()
         ));
     }
 
-    let ideas = load_youtube_ideas(&root);
+    let mut tagged = Vec::new();
+    let mut roots = Vec::new();
+    for vault in &vaults {
+        let vault_ideas = load_youtube_ideas(&vault.path);
+        roots.push(vault_contribution(vault, &[("ideas", vault_ideas.len())]));
+        tagged.extend(
+            vault_ideas
+                .into_iter()
+                .map(|idea| (vault.name.clone(), idea)),
+        );
+    }
+    let duplicate_titles = names_in_several_vaults(
+        tagged
+            .iter()
+            .map(|(vault, idea)| (vault.as_str(), idea.title.as_str())),
+    );
+    let ideas: Vec<YouTubeIdeaRecord> = tagged
+        .into_iter()
+        .map(|(vault, mut idea)| {
+            idea.title = display_name(&vault, &idea.title, &duplicate_titles);
+            idea
+        })
+        .collect();
     let mut earliest: Option<DateTime<Utc>> = None;
     let mut latest: Option<DateTime<Utc>> = None;
     let mut in_progress_count = 0u32;
@@ -1152,6 +1957,7 @@ pub(crate) async fn build_youtube_library(
         generated_at: Utc::now().to_rfc3339(),
         sources: vec!["obsidian".to_string()],
         cache_hit: None,
+        roots,
     };
     let total_count = ideas.len() as u32;
     let items = ideas.into_iter().map(|idea| idea.into_item()).collect();
@@ -1165,169 +1971,865 @@ pub(crate) async fn build_youtube_library(
     })
 }
 
+/// Cover lookups in flight at once, across providers.
+const MEDIA_COVER_CONCURRENCY: usize = 4;
+/// Hits between writes of the cover cache, so an interrupted run keeps most
+/// of its work.
+const MEDIA_COVER_FLUSH_EVERY: usize = 10;
+/// Tries per provider request while it answers 429.
+const PROVIDER_ATTEMPTS: u32 = 4;
+const PROVIDER_MAX_RETRY_WAIT: StdDuration = StdDuration::from_secs(30);
+
+/// Checks the TMDB key with its cheapest authenticated endpoint.
+async fn check_tmdb(config: &MediaProviderConfig) -> MediaProviderStatus {
+    let mut status = MediaProviderStatus {
+        provider: "tmdb".to_string(),
+        enabled: config.tmdb_enabled,
+        configured: false,
+        valid: false,
+        error: config.missing_credentials("tmdb").map(str::to_string),
+    };
+    let Some(api_key) = config.tmdb_api_key.as_deref() else {
+        return status;
+    };
+    status.configured = true;
+    let result = async {
+        let mut url = Url::parse("https://api.themoviedb.org/3/configuration")
+            .map_err(|err| err.to_string())?;
+        url.query_pairs_mut().append_pair("api_key", api_key);
+        let resp = send_provider_request(Client::new().get(url)).await?;
+        if resp.status().is_success() {
+            return Ok(());
+        }
+        let code = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|body| {
+                body.get("status_message")
+                    .and_then(|value| value.as_str())
+                    .map(str::to_string)
+            })
+            .unwrap_or(text);
+        Err(format!("TMDB rejected the key ({code}): {message}"))
+    }
+    .await;
+    status.valid = result.is_ok();
+    status.error = result.err();
+    status
+}
+
+/// Checks the IGDB client id and secret with a token exchange. Returns the
+/// token when it worked, for the run to reuse.
+async fn check_igdb(config: &MediaProviderConfig) -> (MediaProviderStatus, Option<String>) {
+    let mut status = MediaProviderStatus {
+        provider: "igdb".to_string(),
+        enabled: config.igdb_enabled,
+        configured: false,
+        valid: false,
+        error: config.missing_credentials("igdb").map(str::to_string),
+    };
+    let (Some(client_id), Some(client_secret)) = (
+        config.igdb_client_id.as_deref(),
+        config.igdb_client_secret.as_deref(),
+    ) else {
+        return (status, None);
+    };
+    status.configured = true;
+    match fetch_igdb_token(client_id, client_secret).await {
+        Ok(token) => {
+            status.valid = true;
+            (status, Some(token))
+        }
+        Err(err) => {
+            status.error = Some(err);
+            (status, None)
+        }
+    }
+}
+
+/// Whether the TMDB and IGDB credentials are set and accepted. Disabled
+/// providers are checked too, so they can be fixed before being turned on.
+pub async fn media_providers_status(config: &MediaProviderConfig) -> Vec<MediaProviderStatus> {
+    let (tmdb, (igdb, _)) = tokio::join!(check_tmdb(config), check_igdb(config));
+    vec![tmdb, igdb]
+}
+
 pub async fn enrich_media_covers(
     workspace_path: &str,
-    obsidian_root: Option<&str>,
-    tmdb_api_key: Option<&str>,
-    igdb_client_id: Option<&str>,
-    igdb_client_secret: Option<&str>,
-    exa_api_key: Option<&str>,
-    force_refresh: bool,
-) -> Result<MediaCoverSummary, String> {
-    let root = resolve_obsidian_root(workspace_path, obsidian_root);
-    if !root.exists() {
+    obsidian_root: Option<&str>,
+    providers: &MediaProviderConfig,
+    options: &MediaCoverOptions<'_>,
+) -> Result<MediaCoverSummary, String> {
+    let root = resolve_obsidian_root(workspace_path, obsidian_root);
+    if !root.exists() {
+        return Err(format!(
+            "Obsidian root not found: {}",
+            root.to_string_lossy()
+        ));
+    }
+    let vault = root.to_string_lossy().to_string();
+
+    let records = load_media_items(&root);
+    let mut summary = MediaCoverSummary {
+        total: records.len() as u32,
+        ..MediaCoverSummary::default()
+    };
+    summary.dry_run = options.dry_run;
+    let overrides = load_media_cover_overrides(&root);
+    let mut cache = load_media_cover_cache(&root);
+
+    // Covers already in the cache or the note are the cursor: only the rest
+    // is queried, so a re-run picks up where an interrupted one stopped.
+    let mut queue = Vec::new();
+    for record in records {
+        if overrides.contains_key(&record.item.id)
+            || !providers.is_enabled(media_cover_provider(&record.item.media_type))
+            || (!options.force_refresh
+                && (cache.contains_key(&record.item.id) || record.item.cover_url.is_some()))
+        {
+            summary.skipped += 1;
+            continue;
+        }
+        queue.push(record);
+    }
+
+    if options.dry_run {
+        summary.queries = queue
+            .iter()
+            .map(|record| MediaCoverQuery {
+                title: record.item.title.clone(),
+                media_type: record.item.media_type.clone(),
+                provider: media_cover_provider(&record.item.media_type).to_string(),
+            })
+            .collect();
+        return Ok(summary);
+    }
+
+    // Configured keys are checked up front, so a bad one fails the run here
+    // instead of on every item. Items of a provider without keys are
+    // reported as failures below.
+    let needs = |provider: &str| {
+        queue
+            .iter()
+            .any(|record| media_cover_provider(&record.item.media_type) == provider)
+    };
+    let mut rejected = Vec::new();
+    if needs("tmdb") && providers.tmdb_api_key.is_some() {
+        let status = check_tmdb(providers).await;
+        if !status.valid {
+            rejected.push(status);
+        }
+    }
+    let mut igdb_token = String::new();
+    if needs("igdb") && providers.missing_credentials("igdb").is_none() {
+        match check_igdb(providers).await {
+            (_, Some(token)) => igdb_token = token,
+            (status, None) => rejected.push(status),
+        }
+    }
+    if !rejected.is_empty() {
+        return Err(format!(
+            "{MEDIA_PROVIDERS_INVALID_ERROR}: {}",
+            serde_json::json!({ "providers": rejected })
+        ));
+    }
+    let keys = Arc::new(MediaCoverKeys {
+        tmdb_api_key: providers.tmdb_api_key.clone(),
+        igdb_client_id: providers.igdb_client_id.clone(),
+        igdb_token,
+        exa_api_key: providers.exa_api_key.clone(),
+    });
+
+    let queued = queue.len() as u32;
+    let mut done = 0u32;
+    let mut unflushed = 0usize;
+    let mut pending = queue.into_iter();
+    let mut in_flight = tokio::task::JoinSet::new();
+    loop {
+        while in_flight.len() < MEDIA_COVER_CONCURRENCY && !(options.is_cancelled)() {
+            let Some(record) = pending.next() else {
+                break;
+            };
+            let keys = Arc::clone(&keys);
+            in_flight.spawn(async move {
+                let cover = fetch_media_cover(&record, &keys).await;
+                (record, cover)
+            });
+        }
+        let Some(joined) = in_flight.join_next().await else {
+            break;
+        };
+        let (record, cover) = joined.map_err(|err| err.to_string())?;
+        done += 1;
+        let provider = media_cover_provider(&record.item.media_type);
+        let (outcome, error) = match cover {
+            Ok(Some((cover_url, source))) => {
+                cache.insert(
+                    record.item.id.clone(),
+                    MediaCoverEntry {
+                        cover_url,
+                        source,
+                        fetched_at: Utc::now().to_rfc3339(),
+                    },
+                );
+                summary.found += 1;
+                unflushed += 1;
+                if unflushed >= MEDIA_COVER_FLUSH_EVERY {
+                    write_media_cover_cache(&root, &cache)?;
+                    unflushed = 0;
+                }
+                (MediaCoverOutcome::Hit, None)
+            }
+            Ok(None) => {
+                let cached = cache
+                    .get(&record.item.id)
+                    .is_some_and(|existing| !existing.cover_url.is_empty());
+                if options.force_refresh && cached {
+                    summary.skipped += 1;
+                } else {
+                    summary.failed += 1;
+                    summary.failures.push(MediaCoverFailure {
+                        title: record.item.title.clone(),
+                        provider: provider.to_string(),
+                        reason: if provider == "none" {
+                            format!("No cover provider for {}", record.item.media_type)
+                        } else if let Some(missing) = providers.missing_credentials(provider) {
+                            missing.to_string()
+                        } else {
+                            "No match".to_string()
+                        },
+                    });
+                }
+                (MediaCoverOutcome::Miss, None)
+            }
+            Err(err) => {
+                summary.failed += 1;
+                summary.failures.push(MediaCoverFailure {
+                    title: record.item.title.clone(),
+                    provider: provider.to_string(),
+                    reason: err.clone(),
+                });
+                (MediaCoverOutcome::Error, Some(err))
+            }
+        };
+        (options.on_progress)(&MediaCoverProgress {
+            vault: vault.clone(),
+            title: record.item.title,
+            provider: provider.to_string(),
+            outcome,
+            error,
+            done,
+            total: queued,
+        });
+    }
+
+    write_media_cover_cache(&root, &cache)?;
+    if (options.is_cancelled)() {
+        return Err("Operation cancelled".to_string());
+    }
+    Ok(summary)
+}
+
+/// Runs `enrich_media_covers` for each vault of a workspace. Every vault keeps
+/// its own cover cache, so the summaries are simply added up.
+pub(crate) async fn enrich_media_covers_for_roots(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+    providers: &MediaProviderConfig,
+    options: &MediaCoverOptions<'_>,
+) -> Result<MediaCoverSummary, String> {
+    let mut summary = MediaCoverSummary {
+        dry_run: options.dry_run,
+        ..MediaCoverSummary::default()
+    };
+    for vault in resolve_vault_roots(workspace_path, obsidian_roots) {
+        let root = vault.path.to_string_lossy().to_string();
+        let vault_summary =
+            enrich_media_covers(workspace_path, Some(&root), providers, options).await?;
+        summary.total += vault_summary.total;
+        summary.found += vault_summary.found;
+        summary.skipped += vault_summary.skipped;
+        summary.failed += vault_summary.failed;
+        summary.failures.extend(vault_summary.failures);
+        summary.queries.extend(vault_summary.queries);
+    }
+    Ok(summary)
+}
+
+/// Provider `enrich_media_covers` asks for a media type's cover.
+fn media_cover_provider(media_type: &str) -> &'static str {
+    match media_type {
+        "Film" | "TV" | "Anime" => "tmdb",
+        "Book" => "openlibrary",
+        "Game" => "igdb",
+        "YouTube" => "youtube",
+        _ => "none",
+    }
+}
+
+async fn fetch_media_cover(
+    record: &MediaRecord,
+    keys: &MediaCoverKeys,
+) -> Result<Option<(String, String)>, String> {
+    let title = &record.item.title;
+    let variants = title_variants(title);
+    let tmdb = |kind| {
+        fetch_tmdb_cover(
+            title,
+            &variants,
+            kind,
+            keys.tmdb_api_key.as_deref(),
+            record.year_hint,
+            keys.exa_api_key.as_deref(),
+        )
+    };
+    match record.item.media_type.as_str() {
+        "Film" => tmdb("movie").await,
+        "TV" => tmdb("tv").await,
+        "Anime" if has_movie_hint(title) => tmdb("movie").await,
+        "Anime" => {
+            let cover = tmdb("tv").await?;
+            if cover.is_some() || has_season_hint(title) {
+                return Ok(cover);
+            }
+            tmdb("movie").await
+        }
+        "Book" => fetch_open_library_cover(title).await,
+        "Game" => {
+            fetch_igdb_cover(
+                title,
+                record.year_hint,
+                keys.igdb_client_id.as_deref(),
+                &keys.igdb_token,
+            )
+            .await
+        }
+        "YouTube" => Ok(fetch_youtube_cover(
+            record.youtube_id.as_deref(),
+            record.url.as_deref(),
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// Sends a cover provider request, retrying while the provider answers 429.
+/// Waits for its `Retry-After` when given (capped), otherwise 1, 2, then 4
+/// seconds.
+async fn send_provider_request(request: RequestBuilder) -> Result<Response, String> {
+    let mut request = request;
+    let mut backoff = StdDuration::from_secs(1);
+    let mut attempt = 1;
+    loop {
+        let retry = request.try_clone();
+        let resp = request.send().await.map_err(|err| err.to_string())?;
+        if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(resp);
+        }
+        let host = resp.url().host_str().unwrap_or("provider").to_string();
+        let Some(next) = retry.filter(|_| attempt < PROVIDER_ATTEMPTS) else {
+            return Err(format!(
+                "{host} is rate limiting requests (429 after {attempt} attempts)"
+            ));
+        };
+        let wait = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(StdDuration::from_secs)
+            .unwrap_or(backoff)
+            .min(PROVIDER_MAX_RETRY_WAIT);
+        tokio::time::sleep(wait).await;
+        backoff *= 2;
+        attempt += 1;
+        request = next;
+    }
+}
+
+/// The workspace's vaults for a dashboard. Every configured vault has to be
+/// reachable, so an unmounted drive is reported instead of silently skipped.
+fn resolve_existing_vaults(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+) -> Result<Vec<VaultRoot>, String> {
+    let vaults = resolve_vault_roots(workspace_path, obsidian_roots);
+    for vault in &vaults {
+        if !vault.path.exists() {
+            return Err(format!(
+                "Obsidian root not found: {}",
+                vault.path.to_string_lossy()
+            ));
+        }
+    }
+    Ok(vaults)
+}
+
+fn resolve_obsidian_root(workspace_path: &str, obsidian_root: Option<&str>) -> PathBuf {
+    obsidian_root
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(workspace_path))
+}
+
+/// Browsable entity folders as `(domain, kind, vault-relative folder)`. The
+/// workspace's `entityDirs` setting overrides or adds entries by `domain/kind`.
+const DOMAIN_ENTITY_DIRS: &[(&str, &str, &str)] = &[
+    ("delivery_finance", "sessions", "Entities/Delivery/Sessions"),
+    ("delivery_finance", "bills", "Entities/Finance/Bills"),
+    ("food_exercise", "food", "Entities/Food"),
+    ("food_exercise", "workouts", "Entities/Behaviors/Workouts"),
+    ("media", "media", "Entities/Media"),
+    ("youtube", "ideas", "Entities/YouTube"),
+];
+const DEFAULT_ENTITY_PAGE_SIZE: usize = 100;
+const MAX_ENTITY_PAGE_SIZE: usize = 500;
+
+fn normalize_entity_key(value: &str) -> String {
+    value.trim().to_lowercase().replace(['-', ' '], "_")
+}
+
+/// Default folders merged with the `entityDirs` overrides.
+fn domain_entity_dirs(overrides: &BTreeMap<String, String>) -> Vec<(String, String, String)> {
+    let mut dirs: Vec<(String, String, String)> = DOMAIN_ENTITY_DIRS
+        .iter()
+        .map(|(domain, kind, dir)| (domain.to_string(), kind.to_string(), dir.to_string()))
+        .collect();
+    for (key, dir) in overrides {
+        let Some((domain, kind)) = key.split_once('/') else {
+            continue;
+        };
+        let (domain, kind) = (normalize_entity_key(domain), normalize_entity_key(kind));
+        match dirs.iter_mut().find(|(existing_domain, existing_kind, _)| {
+            *existing_domain == domain && *existing_kind == kind
+        }) {
+            Some(existing) => existing.2 = dir.trim().to_string(),
+            None => dirs.push((domain, kind, dir.trim().to_string())),
+        }
+    }
+    dirs
+}
+
+/// Mapped folders have to stay inside the vault.
+fn validate_entity_dir(dir: &str) -> Result<&Path, String> {
+    let path = Path::new(dir);
+    let inside = !dir.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)));
+    if inside {
+        Ok(path)
+    } else {
+        Err(format!("Invalid entity folder: {dir}"))
+    }
+}
+
+/// Frontmatter as JSON: through the dashboard's struct for known kinds, so
+/// values come out with the types the dashboards use, otherwise as plain YAML.
+fn entity_frontmatter_json(kind: Option<&str>, raw: &str) -> serde_json::Value {
+    fn typed<T: serde::de::DeserializeOwned + Serialize>(raw: &str) -> Option<serde_json::Value> {
+        let parsed = serde_yaml::from_str::<T>(raw).ok()?;
+        serde_json::to_value(parsed).ok()
+    }
+    let value = match kind {
+        Some("sessions") => typed::<DeliverySessionFrontmatter>(raw),
+        Some("bills") => typed::<BillFrontmatter>(raw),
+        Some("food") => typed::<FoodFrontmatter>(raw),
+        Some("media") => typed::<MediaFrontmatter>(raw),
+        Some("ideas") => typed::<YouTubeIdeaFrontmatter>(raw),
+        _ => None,
+    };
+    value
+        .or_else(|| typed::<serde_yaml::Value>(raw))
+        .unwrap_or(serde_json::Value::Null)
+}
+
+fn entity_title(frontmatter: &serde_json::Value, path: &Path) -> String {
+    ["title", "name"]
+        .iter()
+        .find_map(|key| frontmatter.get(key).and_then(|value| value.as_str()))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("")
+                .to_string()
+        })
+}
+
+/// Lists the notes of one entity kind across the workspace's vaults, sorted by
+/// file name. Only the notes on the requested page are read.
+pub(crate) fn list_domain_entities(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+    entity_dirs: &BTreeMap<String, String>,
+    domain_id: &str,
+    kind: Option<&str>,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<DomainEntityPage, String> {
+    let domain_id = normalize_entity_key(domain_id);
+    let kind = kind.map(normalize_entity_key);
+    let dirs = domain_entity_dirs(entity_dirs);
+    let (_, kind, dir) = dirs
+        .into_iter()
+        .find(|(domain, candidate, _)| {
+            *domain == domain_id && kind.as_ref().is_none_or(|kind| kind == candidate)
+        })
+        .ok_or_else(|| match &kind {
+            Some(kind) => format!("No entity folder mapped for {domain_id}/{kind}"),
+            None => format!("No entity folders mapped for {domain_id}"),
+        })?;
+    let relative = validate_entity_dir(&dir)?;
+
+    let mut files: Vec<(String, String, PathBuf)> = Vec::new();
+    for vault in resolve_vault_roots(workspace_path, obsidian_roots) {
+        let Ok(entries) = std::fs::read_dir(vault.path.join(relative)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            files.push((name, vault.name.clone(), path));
+        }
+    }
+    files.sort();
+
+    let total = files.len();
+    let limit = limit
+        .unwrap_or(DEFAULT_ENTITY_PAGE_SIZE)
+        .clamp(1, MAX_ENTITY_PAGE_SIZE);
+    let entities: Vec<DomainEntitySummary> = files
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(_, vault, path)| {
+            let content = std::fs::read_to_string(&path).unwrap_or_default();
+            let (raw, _) = split_frontmatter(&content);
+            let frontmatter = raw
+                .map(|raw| entity_frontmatter_json(Some(&kind), &raw))
+                .unwrap_or(serde_json::Value::Null);
+            DomainEntitySummary {
+                title: entity_title(&frontmatter, &path),
+                path: path.to_string_lossy().to_string(),
+                vault,
+                frontmatter,
+            }
+        })
+        .collect();
+    let next_offset = offset + entities.len();
+    Ok(DomainEntityPage {
+        domain_id,
+        kind,
+        entities,
+        total,
+        offset,
+        next_offset: (next_offset < total).then_some(next_offset),
+    })
+}
+
+/// Reads one note with its frontmatter and body separated. `path` has to be a
+/// Markdown file inside one of the workspace's vaults.
+pub(crate) fn read_domain_entity(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+    entity_dirs: &BTreeMap<String, String>,
+    path: &str,
+) -> Result<DomainEntity, String> {
+    let canonical = std::fs::canonicalize(path)
+        .map_err(|err| format!("Failed to open entity {path}: {err}"))?;
+    if !canonical.is_file() || canonical.extension().and_then(|ext| ext.to_str()) != Some("md") {
+        return Err(format!("Not a Markdown note: {path}"));
+    }
+    let (vault, vault_path) = resolve_vault_roots(workspace_path, obsidian_roots)
+        .into_iter()
+        .find_map(|vault| {
+            let root = std::fs::canonicalize(&vault.path).ok()?;
+            canonical.starts_with(&root).then_some((vault.name, root))
+        })
+        .ok_or_else(|| format!("Path is outside the workspace's Obsidian vaults: {path}"))?;
+    let kind = domain_entity_dirs(entity_dirs)
+        .into_iter()
+        .find(|(_, _, dir)| {
+            validate_entity_dir(dir)
+                .map(|dir| canonical.parent() == Some(vault_path.join(dir).as_path()))
+                .unwrap_or(false)
+        })
+        .map(|(_, kind, _)| kind);
+
+    let content = std::fs::read_to_string(&canonical)
+        .map_err(|err| format!("Failed to read entity: {err}"))?;
+    let (raw, body) = split_frontmatter(&content);
+    let (frontmatter, body) = match raw {
+        Some(raw) => (entity_frontmatter_json(kind.as_deref(), &raw), body),
+        None => (serde_json::Value::Null, content.clone()),
+    };
+    Ok(DomainEntity {
+        title: entity_title(&frontmatter, &canonical),
+        path: canonical.to_string_lossy().to_string(),
+        vault,
+        kind,
+        frontmatter,
+        body,
+    })
+}
+
+/// Records a payment on a bill note: appends `{date, amount}` to `payments`
+/// and moves `next_due` one recurrence on, or removes it for one-off bills.
+/// Other frontmatter keys and the note body are kept as they are (see
+/// `frontmatter`).
+pub(crate) fn mark_bill_paid(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+    date_order: DateOrder,
+    bill_path: &str,
+    paid_date: Option<&str>,
+) -> Result<Bill, String> {
+    let canonical = std::fs::canonicalize(bill_path)
+        .map_err(|err| format!("Failed to open bill {bill_path}: {err}"))?;
+    let in_bills_dir = resolve_vault_roots(workspace_path, obsidian_roots)
+        .into_iter()
+        .filter_map(|vault| std::fs::canonicalize(&vault.path).ok())
+        .any(|root| {
+            let bills_dir = root.join("Entities").join("Finance").join("Bills");
+            canonical.parent() == Some(bills_dir.as_path())
+        });
+    if !in_bills_dir || canonical.extension().and_then(|ext| ext.to_str()) != Some("md") {
         return Err(format!(
-            "Obsidian root not found: {}",
-            root.to_string_lossy()
+            "Not a bill note in the workspace's Obsidian vaults: {bill_path}"
         ));
     }
+    let today = Utc::now().date_naive();
+    let paid = match paid_date {
+        Some(value) => parse_vault_date(value, date_order)
+            .ok_or_else(|| format!("Invalid paid date: {value}"))?,
+        None => today,
+    };
 
-    let records = load_media_items(&root);
-    let total = records.len() as u32;
-    let overrides = load_media_cover_overrides(&root);
-    let mut cache = load_media_cover_cache(&root);
-    let mut found = 0u32;
-    let mut skipped = 0u32;
-    let mut failed = 0u32;
+    let content =
+        std::fs::read_to_string(&canonical).map_err(|err| format!("Failed to read bill: {err}"))?;
+    let mut note = FrontmatterNote::parse(&content)
+        .map_err(|err| format!("Bill {err}, the note was left unchanged"))?
+        .ok_or_else(|| format!("Bill note has no frontmatter block: {bill_path}"))?;
+    let parsed: BillFrontmatter = note
+        .deserialize()
+        .map_err(|err| format!("Bill frontmatter has unexpected values: {err}"))?;
+    let amount = note
+        .mapping()
+        .get("amount")
+        .cloned()
+        .ok_or_else(|| format!("Bill has no amount: {bill_path}"))?;
+
+    let mut payment = serde_yaml::Mapping::new();
+    payment.insert("date".into(), paid.to_string().into());
+    payment.insert("amount".into(), amount);
+    note.apply(FrontmatterPatch::Append(
+        "payments".to_string(),
+        payment.into(),
+    ))
+    .map_err(|err| format!("Bill {err}: {bill_path}"))?;
+
+    let frequency = normalize_frequency(parsed.frequency.as_deref());
+    let current_due = parsed
+        .next_due
+        .as_deref()
+        .and_then(|value| parse_vault_date(value, date_order))
+        .unwrap_or_else(|| {
+            parsed
+                .due_day
+                .map(|due_day| compute_next_due_date(paid, due_day, &frequency))
+                .unwrap_or(paid)
+        });
+    let next_due = match advance_due_date(current_due, parsed.due_day, &frequency) {
+        Some(next_due) => {
+            FrontmatterPatch::Set("next_due".to_string(), next_due.to_string().into())
+        }
+        None => FrontmatterPatch::Remove("next_due".to_string()),
+    };
+    note.apply(next_due)?;
 
-    let igdb_token = if records
-        .iter()
-        .any(|record| record.item.media_type == "Game")
+    std::fs::write(&canonical, note.render()?)
+        .map_err(|err| format!("Failed to write bill: {err}"))?;
+
+    let updated: BillFrontmatter = note.deserialize()?;
+    bill_record(&canonical, updated, today, date_order)
+        .map(|record| record.bill)
+        .ok_or_else(|| format!("Bill needs `amount` and `due_day`: {bill_path}"))
+}
+
+/// Changes to a media note requested from the dashboard. `None` leaves the
+/// field as it is.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct MediaItemUpdate {
+    pub(crate) status: Option<String>,
+    pub(crate) rating: Option<f64>,
+    pub(crate) completed_at: Option<String>,
+}
+
+/// Updates `status`, `rating` and `completed_at` on a media note. Moving to
+/// completed without a date stamps today; completing an item that is already
+/// completed keeps its date, and a request that changes nothing leaves the
+/// file untouched. Ratings must fit the item's scale (see `media_rating_scale`).
+pub(crate) fn update_media_item(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+    date_order: DateOrder,
+    item_path: &str,
+    update: MediaItemUpdate,
+) -> Result<MediaItem, String> {
+    let canonical = std::fs::canonicalize(item_path)
+        .map_err(|err| format!("Failed to open media item {item_path}: {err}"))?;
+    let vault_root = resolve_vault_roots(workspace_path, obsidian_roots)
+        .into_iter()
+        .filter_map(|vault| std::fs::canonicalize(&vault.path).ok())
+        .find(|root| canonical.parent() == Some(root.join("Entities").join("Media").as_path()));
+    let stem = canonical
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("")
+        .to_string();
+    let is_note = canonical.extension().and_then(|ext| ext.to_str()) == Some("md");
+    let Some(vault_root) = vault_root.filter(|_| is_note && !stem.starts_with('_')) else {
+        return Err(format!(
+            "Not a media note in the workspace's Obsidian vaults: {item_path}"
+        ));
+    };
+    let status = match update
+        .status
+        .as_deref()
+        .map(|value| value.trim().to_lowercase())
     {
-        match (igdb_client_id, igdb_client_secret) {
-            (Some(id), Some(secret)) if !id.is_empty() && !secret.is_empty() => {
-                fetch_igdb_token(id, secret).await?
+        None => None,
+        Some(value) => match value.as_str() {
+            "completed" | "complete" => Some("completed"),
+            "backlog" | "queue" | "queued" => Some("backlog"),
+            _ => {
+                return Err(format!(
+                    "Invalid media status: {value} (expected completed or backlog)"
+                ))
             }
-            _ => String::new(),
-        }
-    } else {
-        String::new()
+        },
+    };
+    let completed_at = match update.completed_at.as_deref() {
+        Some(value) => Some(
+            parse_vault_date(value, date_order)
+                .ok_or_else(|| format!("Invalid completed date: {value}"))?,
+        ),
+        None => None,
     };
 
-    for record in records {
-        if overrides.contains_key(&record.item.id) {
-            skipped += 1;
-            continue;
+    let content = std::fs::read_to_string(&canonical)
+        .map_err(|err| format!("Failed to read media item: {err}"))?;
+    let mut note = FrontmatterNote::parse(&content)
+        .map_err(|err| format!("Media item {err}, the note was left unchanged"))?
+        .ok_or_else(|| format!("Media note has no frontmatter block: {item_path}"))?;
+    let parsed: MediaFrontmatter = note
+        .deserialize()
+        .map_err(|err| format!("Media frontmatter has unexpected values: {err}"))?;
+
+    if let Some(rating) = update.rating {
+        let scale = media_rating_scale(parsed.rating_scale, &load_media_items(&vault_root));
+        if !(0.0..=scale).contains(&rating) {
+            return Err(format!(
+                "Rating {rating} is outside this item's 0-{scale} scale"
+            ));
         }
-        if !force_refresh
-            && (cache.contains_key(&record.item.id) || record.item.cover_url.is_some())
-        {
-            skipped += 1;
-            continue;
+        if parsed.rating != Some(rating) {
+            let value: serde_yaml::Value = if rating.fract() == 0.0 {
+                (rating as i64).into()
+            } else {
+                rating.into()
+            };
+            note.apply(FrontmatterPatch::Set("rating".to_string(), value))?;
         }
-        let title_variants = title_variants(&record.item.title);
-        let movie_hint = has_movie_hint(&record.item.title);
-        let season_hint = has_season_hint(&record.item.title);
-        let maybe_cover = match record.item.media_type.as_str() {
-            "Film" => {
-                fetch_tmdb_cover(
-                    &record.item.title,
-                    &title_variants,
-                    "movie",
-                    tmdb_api_key,
-                    record.year_hint,
-                    exa_api_key,
-                )
-                .await?
-            }
-            "TV" => {
-                fetch_tmdb_cover(
-                    &record.item.title,
-                    &title_variants,
-                    "tv",
-                    tmdb_api_key,
-                    record.year_hint,
-                    exa_api_key,
-                )
-                .await?
+    }
+
+    let was_completed = normalize_media_status(parsed.status.as_deref()) == "Completed";
+    let today = Utc::now().date_naive();
+    match status {
+        Some("completed") => {
+            if !was_completed {
+                note.apply(FrontmatterPatch::Set(
+                    "status".to_string(),
+                    "completed".into(),
+                ))?;
             }
-            "Anime" => {
-                let mut cover = None;
-                if movie_hint {
-                    cover = fetch_tmdb_cover(
-                        &record.item.title,
-                        &title_variants,
-                        "movie",
-                        tmdb_api_key,
-                        record.year_hint,
-                        exa_api_key,
-                    )
-                    .await?;
-                } else {
-                    cover = fetch_tmdb_cover(
-                        &record.item.title,
-                        &title_variants,
-                        "tv",
-                        tmdb_api_key,
-                        record.year_hint,
-                        exa_api_key,
-                    )
-                    .await?;
-                    if cover.is_none() && !season_hint {
-                        cover = fetch_tmdb_cover(
-                            &record.item.title,
-                            &title_variants,
-                            "movie",
-                            tmdb_api_key,
-                            record.year_hint,
-                            exa_api_key,
-                        )
-                        .await?;
-                    }
-                }
-                cover
+            let has_date = parsed
+                .completed_at
+                .as_deref()
+                .is_some_and(|value| !value.trim().is_empty());
+            let date = completed_at.or((!was_completed || !has_date).then_some(today));
+            if let Some(date) = date {
+                set_media_date(&mut note, parsed.completed_at.as_deref(), date)?;
             }
-            "Book" => fetch_open_library_cover(&record.item.title).await?,
-            "Game" => {
-                fetch_igdb_cover(
-                    &record.item.title,
-                    record.year_hint,
-                    igdb_client_id,
-                    &igdb_token,
-                )
-                .await?
+        }
+        Some(_) => {
+            if was_completed || parsed.status.is_none() {
+                note.apply(FrontmatterPatch::Set(
+                    "status".to_string(),
+                    "backlog".into(),
+                ))?;
             }
-            "YouTube" => fetch_youtube_cover(record.youtube_id.as_deref(), record.url.as_deref()),
-            _ => None,
-        };
-
-        if let Some((cover_url, source)) = maybe_cover {
-            cache.insert(
-                record.item.id,
-                MediaCoverEntry {
-                    cover_url,
-                    source,
-                    fetched_at: Utc::now().to_rfc3339(),
-                },
-            );
-            found += 1;
-        } else if force_refresh {
-            if let Some(existing) = cache.get(&record.item.id) {
-                if !existing.cover_url.is_empty() {
-                    skipped += 1;
-                    continue;
-                }
+            note.apply(FrontmatterPatch::Remove("completed_at".to_string()))?;
+        }
+        None => {
+            if let Some(date) = completed_at {
+                set_media_date(&mut note, parsed.completed_at.as_deref(), date)?;
             }
-            failed += 1;
-        } else {
-            failed += 1;
         }
     }
 
-    write_media_cover_cache(&root, &cache)?;
+    let rendered = note.render()?;
+    if rendered != content {
+        let updated_at = FrontmatterPatch::Set("updated_at".to_string(), today.to_string().into());
+        note.apply(updated_at)?;
+        std::fs::write(&canonical, note.render()?)
+            .map_err(|err| format!("Failed to write media item: {err}"))?;
+    }
 
-    Ok(MediaCoverSummary {
-        total,
-        found,
-        skipped,
-        failed,
-    })
+    let updated: MediaFrontmatter = note.deserialize()?;
+    Ok(media_record(updated, &stem).item)
 }
 
-fn resolve_obsidian_root(workspace_path: &str, obsidian_root: Option<&str>) -> PathBuf {
-    obsidian_root
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from(workspace_path))
+/// Sets `completed_at` unless the note already holds that date, in any of the
+/// formats the dashboards read.
+fn set_media_date(
+    note: &mut FrontmatterNote,
+    current: Option<&str>,
+    date: NaiveDate,
+) -> Result<(), String> {
+    let current = current
+        .and_then(|value| parse_datetime(value.trim()))
+        .map(|value| value.date_naive());
+    if current == Some(date) {
+        return Ok(());
+    }
+    note.apply(FrontmatterPatch::Set(
+        "completed_at".to_string(),
+        date.to_string().into(),
+    ))
+}
+
+/// Upper bound for a media rating: the note's own `rating_scale` when set,
+/// otherwise 5, 10 or 100 depending on the highest rating in the vault.
+fn media_rating_scale(declared: Option<f64>, vault_items: &[MediaRecord]) -> f64 {
+    if let Some(scale) = declared.filter(|scale| *scale > 0.0) {
+        return scale;
+    }
+    let highest = vault_items
+        .iter()
+        .filter_map(|record| record.item.rating)
+        .fold(0.0, f64::max);
+    if highest > 10.0 {
+        100.0
+    } else if highest > 5.0 {
+        10.0
+    } else {
+        5.0
+    }
 }
 
 async fn fetch_delivery_aggregation(
@@ -1415,6 +2917,9 @@ fn load_delivery_sessions(root: &Path) -> Vec<DeliverySessionRecord> {
                 .map(|value| value.max(0.0) as u32)
                 .unwrap_or(0),
             orders,
+            platform: parsed.platform,
+            tips: parsed.tips,
+            base_pay: parsed.base_pay,
         });
     }
 
@@ -1602,55 +3107,59 @@ fn load_media_items(root: &Path) -> Vec<MediaRecord> {
         let Ok(parsed) = serde_yaml::from_str::<MediaFrontmatter>(&frontmatter) else {
             continue;
         };
-        let fallback_title = if stem.is_empty() {
-            "untitled".to_string()
-        } else {
-            stem.to_string()
-        };
-        let title = parsed
-            .title
-            .clone()
-            .unwrap_or_else(|| fallback_title.clone());
-        let id = parsed.id.unwrap_or_else(|| fallback_title.clone());
-        let media_type = normalize_media_type(parsed.media_type.as_deref());
-        let status = normalize_media_status(parsed.status.as_deref());
-        let created_at = parsed
-            .created_at
-            .clone()
-            .or_else(|| parsed.updated_at.clone())
-            .unwrap_or_else(|| Utc::now().to_rfc3339());
-        let updated_at = parsed
-            .updated_at
-            .clone()
-            .or_else(|| Some(created_at.clone()))
-            .unwrap_or_else(|| created_at.clone());
-        let year_hint = parsed
-            .year
-            .as_ref()
-            .and_then(parse_year_value)
-            .or_else(|| extract_year_from_title(&title));
-        let item = MediaItem {
-            id,
-            title,
-            media_type,
-            status,
-            rating: parsed.rating,
-            cover_url: parsed.cover_url,
-            created_at,
-            updated_at,
-            completed_at: parsed.completed_at,
-        };
-        items.push(MediaRecord {
-            item,
-            url: parsed.url,
-            youtube_id: parsed.youtube_id,
-            year_hint,
-        });
+        items.push(media_record(parsed, stem));
     }
 
     items
 }
 
+fn media_record(parsed: MediaFrontmatter, stem: &str) -> MediaRecord {
+    let fallback_title = if stem.is_empty() {
+        "untitled".to_string()
+    } else {
+        stem.to_string()
+    };
+    let title = parsed
+        .title
+        .clone()
+        .unwrap_or_else(|| fallback_title.clone());
+    let id = parsed.id.unwrap_or_else(|| fallback_title.clone());
+    let media_type = normalize_media_type(parsed.media_type.as_deref());
+    let status = normalize_media_status(parsed.status.as_deref());
+    let created_at = parsed
+        .created_at
+        .clone()
+        .or_else(|| parsed.updated_at.clone())
+        .unwrap_or_else(|| Utc::now().to_rfc3339());
+    let updated_at = parsed
+        .updated_at
+        .clone()
+        .or_else(|| Some(created_at.clone()))
+        .unwrap_or_else(|| created_at.clone());
+    let year_hint = parsed
+        .year
+        .as_ref()
+        .and_then(parse_year_value)
+        .or_else(|| extract_year_from_title(&title));
+    let item = MediaItem {
+        id,
+        title,
+        media_type,
+        status,
+        rating: parsed.rating,
+        cover_url: parsed.cover_url,
+        created_at,
+        updated_at,
+        completed_at: parsed.completed_at,
+    };
+    MediaRecord {
+        item,
+        url: parsed.url,
+        youtube_id: parsed.youtube_id,
+        year_hint,
+    }
+}
+
 fn media_cover_cache_path(root: &Path) -> PathBuf {
     root.join("Indexes").join("media.covers.v1.json")
 }
@@ -1880,6 +3389,32 @@ fn load_food_library(root: &Path) -> HashMap<String, FoodNutrition> {
     foods
 }
 
+/// The food map used to parse one vault's stream. A food defined in more than
+/// one vault is kept per vault as `Vault/Name` instead of one overwriting the
+/// other; a bare link resolves to the copy in `own_vault`, if it has one.
+fn food_map_for_vault(
+    libraries: &[(String, HashMap<String, FoodNutrition>)],
+    duplicates: &HashSet<String>,
+    own_vault: &str,
+) -> HashMap<String, FoodNutrition> {
+    let mut map = HashMap::new();
+    for (vault, library) in libraries {
+        for (key, food) in library {
+            if !duplicates.contains(key) {
+                map.entry(key.clone()).or_insert_with(|| food.clone());
+                continue;
+            }
+            let mut qualified = food.clone();
+            qualified.name = qualified_name(vault, &food.name);
+            if vault == own_vault {
+                map.insert(key.clone(), qualified.clone());
+            }
+            map.insert(normalize_food_key(&qualified_name(vault, key)), qualified);
+        }
+    }
+    map
+}
+
 fn parse_food_table(content: &str, fallback_name: &str) -> Option<FoodNutrition> {
     let mut calories = None;
     let mut protein = None;
@@ -2006,6 +3541,23 @@ fn load_exercise_entries(
     entries
 }
 
+const KM_PER_MILE: f64 = 1.609_344;
+
+fn workout_record(workout: WorkoutEntity) -> ExerciseParseRecord {
+    let positive = |value: f64| (value > 0.0).then_some(value);
+    ExerciseParseRecord {
+        date: workout.date,
+        timestamp: format_timestamp(workout.date, None),
+        description: workout.title,
+        entry_type: workout.kind,
+        miles: positive(workout.distance_km / KM_PER_MILE),
+        duration: positive(workout.duration_min),
+        distance_km: positive(workout.distance_km),
+        calories: positive(workout.calories),
+        source: "entity",
+    }
+}
+
 fn load_activity_dates(root: &Path) -> HashSet<NaiveDate> {
     let mut dates = HashSet::new();
     let files = list_stream_files(root);
@@ -2078,7 +3630,7 @@ fn compute_activity_streak(today: NaiveDate, dates: &HashSet<NaiveDate>) -> u32
     streak
 }
 
-fn load_bill_records(bills_dir: &Path, today: NaiveDate) -> Vec<BillRecord> {
+fn load_bill_records(bills_dir: &Path, today: NaiveDate, date_order: DateOrder) -> Vec<BillRecord> {
     let entries = match std::fs::read_dir(bills_dir) {
         Ok(entries) => entries,
         Err(_) => return Vec::new(),
@@ -2101,50 +3653,70 @@ fn load_bill_records(bills_dir: &Path, today: NaiveDate) -> Vec<BillRecord> {
         let Ok(parsed) = serde_yaml::from_str::<BillFrontmatter>(&frontmatter) else {
             continue;
         };
-
-        let name = parsed.name.clone().unwrap_or_else(|| {
-            path.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("Bill")
-                .to_string()
-        });
-        let Some(amount) = parsed.amount else {
-            continue;
-        };
-        let Some(due_day) = parsed.due_day else {
-            continue;
-        };
-        let frequency = normalize_frequency(parsed.frequency.as_deref());
-        let category = parsed
-            .category
-            .unwrap_or_else(|| "uncategorized".to_string());
-        let auto_pay = parsed.auto_pay.unwrap_or(false);
-        let due_date = compute_next_due_date(today, due_day, &frequency);
-        let bill = Bill {
-            id: path
-                .file_stem()
-                .and_then(|stem| stem.to_str())
-                .unwrap_or(&name)
-                .to_string(),
-            name,
-            amount,
-            due_day,
-            frequency: frequency.clone(),
-            category,
-            auto_pay,
-            next_due_date: due_date.to_string(),
-        };
-        let monthly_equivalent = monthly_equivalent_amount(amount, &frequency);
-        records.push(BillRecord {
-            bill,
-            monthly_equivalent,
-            due_date,
-        });
+        if let Some(record) = bill_record(&path, parsed, today, date_order) {
+            if !record.settled {
+                records.push(record);
+            }
+        }
     }
 
     records
 }
 
+/// A `next_due` written back by `mark_bill_paid` wins over the date derived
+/// from `due_day`.
+fn bill_record(
+    path: &Path,
+    parsed: BillFrontmatter,
+    today: NaiveDate,
+    date_order: DateOrder,
+) -> Option<BillRecord> {
+    let name = parsed.name.clone().unwrap_or_else(|| {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Bill")
+            .to_string()
+    });
+    let amount = parsed.amount?;
+    let due_day = parsed.due_day?;
+    let frequency = normalize_frequency(parsed.frequency.as_deref());
+    let category = parsed
+        .category
+        .unwrap_or_else(|| "uncategorized".to_string());
+    let auto_pay = parsed.auto_pay.unwrap_or(false);
+    let next_due = parsed
+        .next_due
+        .as_deref()
+        .and_then(|value| parse_vault_date(value, date_order));
+    let settled = frequency == "once" && next_due.is_none() && !parsed.payments.is_empty();
+    let due_date = next_due.unwrap_or_else(|| compute_next_due_date(today, due_day, &frequency));
+    let bill = Bill {
+        id: path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(&name)
+            .to_string(),
+        name,
+        amount,
+        due_day,
+        frequency: frequency.clone(),
+        category,
+        auto_pay,
+        next_due_date: if settled {
+            String::new()
+        } else {
+            due_date.to_string()
+        },
+    };
+    let monthly_equivalent = monthly_equivalent_amount(amount, &frequency);
+    Some(BillRecord {
+        bill,
+        monthly_equivalent,
+        due_date,
+        settled,
+    })
+}
+
 fn list_stream_files(root: &Path) -> Vec<PathBuf> {
     let dir = root.join("Stream");
     let entries = match std::fs::read_dir(&dir) {
@@ -2310,6 +3882,9 @@ fn parse_exercise_entry(content: &str, date: NaiveDate) -> Option<ExerciseParseR
         entry_type,
         miles,
         duration,
+        distance_km: miles.map(|miles| miles * KM_PER_MILE),
+        calories: None,
+        source: "stream",
     })
 }
 
@@ -2497,6 +4072,7 @@ fn normalize_frequency(value: Option<&str>) -> String {
     match value.unwrap_or("monthly").trim().to_lowercase().as_str() {
         "weekly" => "weekly".to_string(),
         "annual" | "yearly" => "annual".to_string(),
+        "once" | "one-time" | "one_time" | "one-off" => "once".to_string(),
         _ => "monthly".to_string(),
     }
 }
@@ -2540,6 +4116,22 @@ fn compute_next_due_date(today: NaiveDate, due_day: u32, frequency: &str) -> Nai
     }
 }
 
+/// The due date after `due`, one recurrence later. `None` for one-off bills.
+fn advance_due_date(due: NaiveDate, due_day: Option<u32>, frequency: &str) -> Option<NaiveDate> {
+    let months = match frequency {
+        "weekly" => return Some(due + Duration::days(7)),
+        "once" => return None,
+        "annual" => 12,
+        _ => 1,
+    };
+    let index = due.year() * 12 + due.month0() as i32 + months;
+    Some(safe_date(
+        index.div_euclid(12),
+        index.rem_euclid(12) as u32 + 1,
+        due_day.unwrap_or(due.day()),
+    ))
+}
+
 fn safe_date(year: i32, month: u32, due_day: u32) -> NaiveDate {
     let last_day = last_day_of_month(year, month);
     let day = due_day.min(last_day);
@@ -2903,11 +4495,7 @@ async fn fetch_tmdb_cover(
                 }
             }
         }
-        let resp = client
-            .get(url)
-            .send()
-            .await
-            .map_err(|err| err.to_string())?;
+        let resp = send_provider_request(client.get(url)).await?;
         if !resp.status().is_success() {
             continue;
         }
@@ -3115,13 +4703,13 @@ async fn fetch_exa_tmdb_cover(
         "num_results": 5,
         "type": "neural",
     });
-    let resp = Client::new()
-        .post("https://api.exa.ai/search")
-        .header("Authorization", format!("Bearer {exa_api_key}"))
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+    let resp = send_provider_request(
+        Client::new()
+            .post("https://api.exa.ai/search")
+            .header("Authorization", format!("Bearer {exa_api_key}"))
+            .json(&payload),
+    )
+    .await?;
     if !resp.status().is_success() {
         return Ok(None);
     }
@@ -3173,11 +4761,7 @@ async fn fetch_tmdb_cover_by_id(
     preferred_language: Option<&str>,
 ) -> Result<Option<String>, String> {
     let url = format!("https://api.themoviedb.org/3/{media_type}/{id}?api_key={tmdb_api_key}");
-    let resp = Client::new()
-        .get(url)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+    let resp = send_provider_request(Client::new().get(url)).await?;
     if !resp.status().is_success() {
         return Ok(None);
     }
@@ -3201,20 +4785,15 @@ async fn fetch_tmdb_best_poster(
     id: u64,
     preferred_language: Option<&str>,
 ) -> Result<Option<String>, String> {
-    let url = format!("https://api.themoviedb.org/3/{media_type}/{id}/images?api_key={tmdb_api_key}");
-    let resp = Client::new()
-        .get(url)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+    let url =
+        format!("https://api.themoviedb.org/3/{media_type}/{id}/images?api_key={tmdb_api_key}");
+    let resp = send_provider_request(Client::new().get(url)).await?;
     if !resp.status().is_success() {
         return Ok(None);
     }
     let payload: TmdbImagesResponse = resp.json().await.map_err(|err| err.to_string())?;
     if let Some(file_path) = pick_tmdb_poster(&payload.posters, preferred_language) {
-        return Ok(Some(format!(
-            "https://image.tmdb.org/t/p/w500{file_path}"
-        )));
+        return Ok(Some(format!("https://image.tmdb.org/t/p/w500{file_path}")));
     }
     Ok(None)
 }
@@ -3290,11 +4869,7 @@ async fn fetch_open_library_cover(title: &str) -> Result<Option<(String, String)
         url.query_pairs_mut()
             .append_pair("title", &variant)
             .append_pair("limit", "1");
-        let resp = Client::new()
-            .get(url)
-            .send()
-            .await
-            .map_err(|err| err.to_string())?;
+        let resp = send_provider_request(Client::new().get(url)).await?;
         if !resp.status().is_success() {
             continue;
         }
@@ -3313,11 +4888,7 @@ async fn fetch_igdb_token(client_id: &str, client_secret: &str) -> Result<String
     let url = format!(
         "https://id.twitch.tv/oauth2/token?client_id={client_id}&client_secret={client_secret}&grant_type=client_credentials"
     );
-    let resp = Client::new()
-        .post(url)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
+    let resp = send_provider_request(Client::new().post(url)).await?;
     if !resp.status().is_success() {
         let text = resp.text().await.unwrap_or_default();
         return Err(format!("IGDB auth failed: {text}"));
@@ -3346,14 +4917,14 @@ async fn fetch_igdb_cover(
             "search \"{}\"; fields name, first_release_date, cover.image_id; limit 10;",
             variant.replace('\"', "")
         );
-        let resp = Client::new()
-            .post("https://api.igdb.com/v4/games")
-            .header("Client-ID", client_id)
-            .header("Authorization", format!("Bearer {access_token}"))
-            .body(body)
-            .send()
-            .await
-            .map_err(|err| err.to_string())?;
+        let resp = send_provider_request(
+            Client::new()
+                .post("https://api.igdb.com/v4/games")
+                .header("Client-ID", client_id)
+                .header("Authorization", format!("Bearer {access_token}"))
+                .body(body),
+        )
+        .await?;
         if !resp.status().is_success() {
             continue;
         }
@@ -3366,9 +4937,7 @@ async fn fetch_igdb_cover(
             if name.trim().is_empty() {
                 continue;
             }
-            let year = result
-                .first_release_date
-                .and_then(igdb_year_from_timestamp);
+            let year = result.first_release_date.and_then(igdb_year_from_timestamp);
             let score = score_igdb_candidate(&name, &variants, year_hint, year);
             candidates.push(IgdbCandidate {
                 name,
@@ -3509,14 +5078,20 @@ fn split_frontmatter(content: &str) -> (Option<String>, String) {
 #[cfg(test)]
 mod tests {
     use super::{
-        build_life_workspace_prompt, load_bill_records, load_exercise_entries, load_meal_entries,
-        normalize_food_key, parse_exercise_entry, parse_meal_entry, FoodNutrition,
+        build_delivery_dashboard, build_life_workspace_prompt, enrich_media_covers,
+        list_domain_entities, load_bill_records, load_exercise_entries, load_meal_entries,
+        mark_bill_paid, media_cover_cache_path, normalize_food_key, parse_exercise_entry,
+        parse_meal_entry, read_domain_entity, update_media_item, FoodNutrition, MediaCoverOptions,
+        MediaCoverOutcome, MediaCoverProgress, MediaItemUpdate, MediaProviderConfig,
         LIFE_PROMPT_FILES, LIFE_PROMPT_TAIL,
     };
+    use crate::frontmatter::frontmatter_block;
+    use crate::types::DateOrder;
     use chrono::NaiveDate;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::fs;
     use std::path::PathBuf;
+    use std::sync::Mutex;
     use tempfile::tempdir;
 
     fn prompt_files_present() -> bool {
@@ -3595,7 +5170,7 @@ mod tests {
         )
         .expect("write bill");
         let today = NaiveDate::from_ymd_opt(2026, 1, 28).unwrap();
-        let records = load_bill_records(dir.path(), today);
+        let records = load_bill_records(dir.path(), today, DateOrder::MonthFirst);
         assert_eq!(records.len(), 1);
         let bill = &records[0].bill;
         assert_eq!(bill.next_due_date, "2026-02-01");
@@ -3603,6 +5178,165 @@ mod tests {
         assert!(bill.auto_pay);
     }
 
+    #[test]
+    fn mark_bill_paid_records_payment_and_rolls_next_due() {
+        let dir = tempdir().expect("temp dir");
+        let bills = dir.path().join("Entities").join("Finance").join("Bills");
+        fs::create_dir_all(&bills).expect("bills dir");
+        let workspace = dir.path().to_string_lossy().to_string();
+        let rent = bills.join("Rent.md");
+        fs::write(
+            &rent,
+            "---\nname: Rent\namount: 1200\ndue_day: 31\nfrequency: monthly\nnext_due: 2026-01-31\nlandlord: Pat\n---\n# Rent\n\nLease notes  \n",
+        )
+        .expect("write rent");
+
+        let bill = mark_bill_paid(
+            &workspace,
+            &[],
+            DateOrder::MonthFirst,
+            &rent.to_string_lossy(),
+            Some("2026-01-30"),
+        )
+        .expect("mark paid");
+        assert_eq!(bill.next_due_date, "2026-02-28");
+        let content = fs::read_to_string(&rent).expect("read rent");
+        assert!(content.ends_with("---\n# Rent\n\nLease notes  \n"));
+        let (raw, _) = frontmatter_block(&content).expect("frontmatter");
+        let yaml: serde_yaml::Value = serde_yaml::from_str(raw).expect("yaml");
+        assert_eq!(yaml["landlord"], "Pat");
+        assert_eq!(yaml["payments"][0]["date"], "2026-01-30");
+        assert_eq!(yaml["payments"][0]["amount"], 1200);
+
+        let deposit = bills.join("Deposit.md");
+        fs::write(
+            &deposit,
+            "---\nname: Deposit\namount: 500\ndue_day: 5\nfrequency: once\n---\n",
+        )
+        .expect("write deposit");
+        let bill = mark_bill_paid(
+            &workspace,
+            &[],
+            DateOrder::MonthFirst,
+            &deposit.to_string_lossy(),
+            Some("2026-02-03"),
+        )
+        .expect("mark deposit paid");
+        assert_eq!(bill.next_due_date, "");
+        let today = NaiveDate::from_ymd_opt(2026, 2, 4).unwrap();
+        let names: Vec<_> = load_bill_records(&bills, today, DateOrder::MonthFirst)
+            .into_iter()
+            .map(|record| record.bill.name)
+            .collect();
+        assert_eq!(names, vec!["Rent".to_string()]);
+
+        let broken = bills.join("Broken.md");
+        let original = "---\nname: [unclosed\namount: 10\n---\nBody\n";
+        fs::write(&broken, original).expect("write broken");
+        let err = mark_bill_paid(
+            &workspace,
+            &[],
+            DateOrder::MonthFirst,
+            &broken.to_string_lossy(),
+            None,
+        )
+        .unwrap_err();
+        assert!(err.contains("not valid YAML"));
+        assert_eq!(fs::read_to_string(&broken).expect("read broken"), original);
+    }
+
+    #[test]
+    fn bill_dates_follow_the_workspace_date_order() {
+        let dir = tempdir().expect("temp dir");
+        let bills = dir.path().join("Entities").join("Finance").join("Bills");
+        fs::create_dir_all(&bills).expect("bills dir");
+        let workspace = dir.path().to_string_lossy().to_string();
+        let rent = bills.join("Rent.md");
+        fs::write(
+            &rent,
+            "---\nname: Rent\namount: 1200\ndue_day: 31\nfrequency: monthly\nnext_due: 31/01/2026\n---\n",
+        )
+        .expect("write rent");
+
+        let today = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap();
+        let records = load_bill_records(&bills, today, DateOrder::DayFirst);
+        assert_eq!(records[0].bill.next_due_date, "2026-01-31");
+
+        let bill = mark_bill_paid(
+            &workspace,
+            &[],
+            DateOrder::DayFirst,
+            &rent.to_string_lossy(),
+            Some("30/01/2026"),
+        )
+        .expect("mark paid");
+        assert_eq!(bill.next_due_date, "2026-02-28");
+        let content = fs::read_to_string(&rent).expect("read rent");
+        let (raw, _) = frontmatter_block(&content).expect("frontmatter");
+        let yaml: serde_yaml::Value = serde_yaml::from_str(raw).expect("yaml");
+        assert_eq!(yaml["payments"][0]["date"], "2026-01-30");
+    }
+
+    #[test]
+    fn update_media_item_completes_once_within_the_rating_scale() {
+        let dir = tempdir().expect("temp dir");
+        let media = dir.path().join("Entities").join("Media");
+        fs::create_dir_all(&media).expect("media dir");
+        let workspace = dir.path().to_string_lossy().to_string();
+        let arrival = "---\ntitle: Arrival\nrating: 8\n---\n";
+        fs::write(media.join("Arrival.md"), arrival).expect("write arrival");
+        let dune = media.join("Dune.md");
+        let path = dune.to_string_lossy().to_string();
+        fs::write(
+            &dune,
+            "---\ntitle: Dune\ntype: book\nstatus: backlog  # shelf\n---\n# Dune\n\nNotes  \n",
+        )
+        .expect("write dune");
+        let complete = MediaItemUpdate {
+            status: Some("completed".to_string()),
+            rating: Some(9.0),
+            completed_at: None,
+        };
+
+        let item = update_media_item(
+            &workspace,
+            &[],
+            DateOrder::MonthFirst,
+            &path,
+            complete.clone(),
+        )
+        .expect("update");
+        let today = chrono::Utc::now().date_naive().to_string();
+        assert_eq!(item.status, "Completed");
+        assert_eq!(item.rating, Some(9.0));
+        assert_eq!(item.completed_at.as_deref(), Some(today.as_str()));
+        let content = fs::read_to_string(&dune).expect("read dune");
+        assert!(content.starts_with("---\ntitle: Dune\ntype: book\n"));
+        assert!(content.ends_with("---\n# Dune\n\nNotes  \n"));
+
+        update_media_item(&workspace, &[], DateOrder::MonthFirst, &path, complete).expect("repeat");
+        assert_eq!(fs::read_to_string(&dune).expect("reread dune"), content);
+
+        let too_high = MediaItemUpdate {
+            rating: Some(11.0),
+            ..MediaItemUpdate::default()
+        };
+        let err =
+            update_media_item(&workspace, &[], DateOrder::MonthFirst, &path, too_high).unwrap_err();
+        assert!(err.contains("0-10 scale"), "{err}");
+        let outside = dir.path().join("Dune.md");
+        fs::write(&outside, "---\ntitle: Dune\n---\n").unwrap();
+        let err = update_media_item(
+            &workspace,
+            &[],
+            DateOrder::MonthFirst,
+            &outside.to_string_lossy(),
+            MediaItemUpdate::default(),
+        )
+        .unwrap_err();
+        assert!(err.contains("Not a media note"), "{err}");
+    }
+
     #[test]
     fn load_meal_entries_reads_stream_rows() {
         let dir = tempdir().expect("temp dir");
@@ -3682,4 +5416,263 @@ mod tests {
         assert_eq!(entry.duration, Some(40.0));
         assert_eq!(entry.timestamp, "2026-01-21T07:10:00");
     }
+
+    #[tokio::test]
+    async fn delivery_dashboard_breaks_down_platforms_and_tips() {
+        let dir = tempdir().expect("temp dir");
+        let sessions = dir
+            .path()
+            .join("Entities")
+            .join("Delivery")
+            .join("Sessions");
+        fs::create_dir_all(&sessions).expect("sessions dir");
+        let workspace = dir.path().to_string_lossy().to_string();
+        let today = NaiveDate::from_ymd_opt(2026, 1, 31).expect("date");
+        fs::write(
+            sessions.join("2026-01-10.md"),
+            "---\ndate: 2026-01-10\nearnings: 100\nhours: 4\norders_count: 8\n---\n",
+        )
+        .expect("write old session");
+
+        let old_only = build_delivery_dashboard(&workspace, &[], None, None, "lifetime", today)
+            .await
+            .expect("old dashboard");
+        assert_eq!(old_only.stats.total_earnings, 100.0);
+        assert_eq!(old_only.stats.hourly_rate, 25.0);
+        assert!(old_only.platforms.is_empty());
+        let json = serde_json::to_value(&old_only).expect("serialize");
+        assert!(json.get("platforms").is_none());
+        assert!(json["stats"].get("tipsPercent").is_none());
+
+        for (name, frontmatter) in [
+            (
+                "2026-01-11.md",
+                "platform: DoorDash\nearnings: 60\nhours: 2\norders_count: 5\ntips: 20\nbase_pay: 40",
+            ),
+            (
+                "2026-01-12.md",
+                "platform: ubereats\nearnings: 90\nhours: 3\norders_count: 6\ntips: 30",
+            ),
+            (
+                "2026-01-13.md",
+                "platform: Roadie\nearnings: 10\nhours: 1\norders_count: 1",
+            ),
+        ] {
+            let date = name.trim_end_matches(".md");
+            fs::write(
+                sessions.join(name),
+                format!("---\ndate: {date}\n{frontmatter}\n---\n"),
+            )
+            .expect("write new session");
+        }
+
+        let mixed = build_delivery_dashboard(&workspace, &[], None, None, "lifetime", today)
+            .await
+            .expect("mixed dashboard");
+        assert_eq!(mixed.stats.total_earnings, 260.0);
+        assert_eq!(mixed.stats.order_count, 20);
+        assert_eq!(mixed.stats.active_hours, 10.0);
+        let platforms: Vec<(&str, f64, u32)> = mixed
+            .platforms
+            .iter()
+            .map(|stats| (stats.platform.as_str(), stats.earnings, stats.order_count))
+            .collect();
+        assert_eq!(
+            platforms,
+            vec![
+                ("Uber Eats", 90.0, 6),
+                ("DoorDash", 60.0, 5),
+                ("Other", 10.0, 1)
+            ]
+        );
+        assert_eq!(mixed.platforms[0].hourly_rate, 30.0);
+        assert_eq!(mixed.stats.tips_total, Some(50.0));
+        assert_eq!(mixed.stats.base_pay_total, Some(100.0));
+        let percent = mixed.stats.tips_percent.expect("tips percent");
+        assert!((percent - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn domain_entities_page_and_read_within_vaults() {
+        let dir = tempdir().expect("temp dir");
+        let media = dir.path().join("Entities").join("Media");
+        fs::create_dir_all(&media).expect("media dir");
+        for name in ["b", "a", "c"] {
+            fs::write(
+                media.join(format!("{name}.md")),
+                format!("---\ntitle: Title {name}\nrating: 4\nextra: x\n---\nNotes on {name}\n"),
+            )
+            .expect("write note");
+        }
+        let books = dir.path().join("Books");
+        fs::create_dir_all(&books).expect("books dir");
+        fs::write(books.join("dune.md"), "---\nauthor: Herbert\n---\nSpice").expect("write book");
+        let workspace = dir.path().to_string_lossy().to_string();
+        let overrides = BTreeMap::from([("media/books".to_string(), "Books".to_string())]);
+
+        let page = list_domain_entities(&workspace, &[], &overrides, "media", None, 0, Some(2))
+            .expect("list media");
+        assert_eq!(page.kind, "media");
+        assert_eq!(page.total, 3);
+        assert_eq!(page.next_offset, Some(2));
+        assert_eq!(page.entities[0].title, "Title a");
+        assert_eq!(page.entities[0].frontmatter["rating"], 4.0);
+        assert!(page.entities[0].frontmatter.get("extra").is_none());
+
+        let books_page =
+            list_domain_entities(&workspace, &[], &overrides, "media", Some("books"), 0, None)
+                .expect("list books");
+        assert_eq!(books_page.entities[0].title, "dune");
+        assert_eq!(books_page.entities[0].frontmatter["author"], "Herbert");
+        assert!(
+            list_domain_entities(&workspace, &[], &overrides, "media", Some("x"), 0, None).is_err()
+        );
+
+        let entity = read_domain_entity(&workspace, &[], &overrides, &page.entities[1].path)
+            .expect("read entity");
+        assert_eq!(entity.kind.as_deref(), Some("media"));
+        assert_eq!(entity.body, "Notes on b");
+
+        let outside = tempdir().expect("outside dir");
+        let stray = outside.path().join("stray.md");
+        fs::write(&stray, "---\ntitle: x\n---\n").expect("write stray");
+        assert!(
+            read_domain_entity(&workspace, &[], &overrides, &stray.to_string_lossy())
+                .unwrap_err()
+                .contains("outside")
+        );
+    }
+
+    #[tokio::test]
+    async fn media_covers_resume_after_earlier_hits_and_dry_runs_list_lookups() {
+        let dir = tempdir().expect("temp dir");
+        let media = dir.path().join("Entities").join("Media");
+        fs::create_dir_all(&media).expect("media dir");
+        let notes = [
+            (
+                "dune",
+                "type: book\ncover_url: https://covers.example/dune.jpg",
+            ),
+            ("clip", "type: youtube\nyoutube_id: dQw4w9WgXcQ"),
+            ("halo", "type: game"),
+        ];
+        for (name, frontmatter) in notes {
+            fs::write(
+                media.join(format!("{name}.md")),
+                format!("---\n{frontmatter}\n---\n"),
+            )
+            .expect("write note");
+        }
+        let workspace = dir.path().to_string_lossy().to_string();
+
+        let dry = enrich_media_covers(
+            &workspace,
+            None,
+            &MediaProviderConfig::default(),
+            &MediaCoverOptions::new(false, true),
+        )
+        .await
+        .expect("dry run");
+        let mut lookups: Vec<(&str, &str)> = dry
+            .queries
+            .iter()
+            .map(|query| (query.title.as_str(), query.provider.as_str()))
+            .collect();
+        lookups.sort();
+        assert_eq!(lookups, vec![("clip", "youtube"), ("halo", "igdb")]);
+        assert_eq!(dry.skipped, 1);
+        assert!(!media_cover_cache_path(dir.path()).exists());
+
+        let events = Mutex::new(Vec::new());
+        let record = |progress: &MediaCoverProgress| {
+            events
+                .lock()
+                .unwrap()
+                .push((progress.title.clone(), progress.outcome));
+        };
+        let run = enrich_media_covers(
+            &workspace,
+            None,
+            &MediaProviderConfig::default(),
+            &MediaCoverOptions {
+                on_progress: &record,
+                ..MediaCoverOptions::new(false, false)
+            },
+        )
+        .await
+        .expect("run");
+        assert_eq!((run.found, run.skipped, run.failed), (1, 1, 1));
+        assert_eq!(run.failures[0].title, "halo");
+        assert_eq!(run.failures[0].provider, "igdb");
+        assert_eq!(
+            run.failures[0].reason,
+            "IGDB client id and secret are not set"
+        );
+        let mut events = events.into_inner().unwrap();
+        events.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            events,
+            vec![
+                ("clip".to_string(), MediaCoverOutcome::Hit),
+                ("halo".to_string(), MediaCoverOutcome::Miss),
+            ]
+        );
+
+        let rerun = enrich_media_covers(
+            &workspace,
+            None,
+            &MediaProviderConfig::default(),
+            &MediaCoverOptions::new(false, true),
+        )
+        .await
+        .expect("rerun");
+        assert_eq!(rerun.skipped, 2);
+        assert_eq!(rerun.queries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn disabled_media_providers_are_skipped_without_lookups() {
+        let dir = tempdir().expect("temp dir");
+        let media = dir.path().join("Entities").join("Media");
+        fs::create_dir_all(&media).expect("media dir");
+        for (name, kind) in [("halo", "game"), ("heat", "film")] {
+            fs::write(
+                media.join(format!("{name}.md")),
+                format!("---\ntype: {kind}\n---\n"),
+            )
+            .expect("write note");
+        }
+        let workspace = dir.path().to_string_lossy().to_string();
+        let providers = MediaProviderConfig {
+            igdb_client_id: Some("id".to_string()),
+            igdb_client_secret: Some("secret".to_string()),
+            igdb_enabled: false,
+            ..MediaProviderConfig::default()
+        };
+
+        let dry = enrich_media_covers(
+            &workspace,
+            None,
+            &providers,
+            &MediaCoverOptions::new(false, true),
+        )
+        .await
+        .expect("dry run");
+        assert_eq!(dry.skipped, 1);
+        assert_eq!(dry.queries.len(), 1);
+        assert_eq!(dry.queries[0].provider, "tmdb");
+
+        // IGDB is off, so its keys are never exchanged, and TMDB has no key
+        // to check: the film fails without a request.
+        let run = enrich_media_covers(
+            &workspace,
+            None,
+            &providers,
+            &MediaCoverOptions::new(false, false),
+        )
+        .await
+        .expect("run");
+        assert_eq!((run.found, run.skipped, run.failed), (0, 1, 1));
+        assert_eq!(run.failures[0].reason, "TMDB API key is not set");
+    }
 }
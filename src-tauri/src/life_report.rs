@@ -0,0 +1,330 @@
+//! Static Markdown or HTML reports of the Life dashboards. The dashboards and
+//! trend snapshots are turned into `ReportSection`s elsewhere; this module
+//! only lays them out and saves the result under `Reports/` in the vault.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::types::DomainTrendSnapshot;
+
+/// Error prefix when a report for the same day and range already exists and
+/// `overwrite` was not set.
+pub(crate) const REPORT_EXISTS_ERROR: &str = "report_exists";
+pub(crate) const REPORTS_DIR: &str = "Reports";
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;max-width:860px;margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.45}\
+h1{margin-bottom:.2rem}h2{margin-top:2rem;border-bottom:1px solid #d0d7de;padding-bottom:.3rem}\
+.meta,.note{color:#57606a}.cards{display:flex;flex-wrap:wrap;gap:.6rem}\
+.card{border:1px solid #d0d7de;border-radius:8px;padding:.5rem .8rem;min-width:120px}\
+.card .label{font-size:.8rem;color:#57606a}.card .value{font-size:1.2rem;font-weight:600}\
+table{border-collapse:collapse;width:100%;margin:.5rem 0 1rem}th,td{border:1px solid #d0d7de;padding:.3rem .6rem;text-align:left}\
+th{background:#f6f8fa}";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub(crate) fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            other => Err(format!("Unknown report format: {other}")),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReportCard {
+    pub(crate) label: String,
+    pub(crate) value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReportTable {
+    pub(crate) title: String,
+    pub(crate) headers: Vec<String>,
+    pub(crate) rows: Vec<Vec<String>>,
+}
+
+/// One dashboard or trend snapshot in the report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ReportSection {
+    pub(crate) title: String,
+    /// Shown under the title, e.g. why a dashboard could not be built.
+    pub(crate) note: Option<String>,
+    pub(crate) cards: Vec<ReportCard>,
+    pub(crate) tables: Vec<ReportTable>,
+}
+
+impl ReportSection {
+    pub(crate) fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn card(&mut self, label: &str, value: impl Into<String>) {
+        self.cards.push(ReportCard {
+            label: label.to_string(),
+            value: value.into(),
+        });
+    }
+
+    /// Adds a table unless it has no rows.
+    pub(crate) fn table(&mut self, title: &str, headers: &[&str], rows: Vec<Vec<String>>) {
+        if rows.is_empty() {
+            return;
+        }
+        self.tables.push(ReportTable {
+            title: title.to_string(),
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows,
+        });
+    }
+}
+
+/// A trend snapshot's cards, with their delta when compared, and its lists.
+pub(crate) fn trend_section(title: &str, snapshot: &DomainTrendSnapshot) -> ReportSection {
+    let mut section = ReportSection::new(title);
+    for card in &snapshot.cards {
+        let mut value = card.value.clone();
+        if let Some(sub_label) = card.sub_label.as_deref().filter(|text| !text.is_empty()) {
+            value.push_str(&format!(" ({sub_label})"));
+        }
+        if let Some(delta) = &card.delta {
+            value.push_str(&format!(", {} vs previous", delta.label));
+        }
+        section.card(&card.label, value);
+    }
+    for list in &snapshot.lists {
+        let rows = list
+            .items
+            .iter()
+            .map(|item| {
+                vec![
+                    item.label.clone(),
+                    item.value.clone(),
+                    item.sub_label.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        section.table(&list.title, &["Item", "Value", "Detail"], rows);
+    }
+    section
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LifeReport {
+    pub(crate) format: ReportFormat,
+    pub(crate) file_name: String,
+    pub(crate) content: String,
+    /// Where the report was saved; `None` when it was only returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) path: Option<String>,
+}
+
+/// `2026-03-02-7d.md`. Characters that can't be in a file name are dropped
+/// from the range.
+pub(crate) fn report_file_name(date: &str, range: &str, format: ReportFormat) -> String {
+    let range: String = range
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '_' || *ch == '-')
+        .collect();
+    let range = if range.is_empty() { "all" } else { &range };
+    format!("{date}-{range}.{}", format.extension())
+}
+
+pub(crate) fn render_report(
+    range: &str,
+    generated_at: &str,
+    sections: &[ReportSection],
+    format: ReportFormat,
+) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(range, generated_at, sections),
+        ReportFormat::Html => render_html(range, generated_at, sections),
+    }
+}
+
+fn render_markdown(range: &str, generated_at: &str, sections: &[ReportSection]) -> String {
+    let mut out = format!(
+        "# Life report: {range}\n\nGenerated {generated_at}\n",
+        range = markdown_cell(range)
+    );
+    for section in sections {
+        out.push_str(&format!("\n## {}\n", markdown_cell(&section.title)));
+        if let Some(note) = &section.note {
+            out.push_str(&format!("\n_{}_\n", markdown_cell(note)));
+        }
+        if !section.cards.is_empty() {
+            out.push_str("\n| Metric | Value |\n| --- | --- |\n");
+            for card in &section.cards {
+                out.push_str(&format!(
+                    "| {} | {} |\n",
+                    markdown_cell(&card.label),
+                    markdown_cell(&card.value)
+                ));
+            }
+        }
+        for table in &section.tables {
+            out.push_str(&format!("\n### {}\n\n", markdown_cell(&table.title)));
+            let headers: Vec<String> = table.headers.iter().map(|h| markdown_cell(h)).collect();
+            out.push_str(&format!("| {} |\n", headers.join(" | ")));
+            out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+            for row in &table.rows {
+                let cells: Vec<String> = row.iter().map(|cell| markdown_cell(cell)).collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+    }
+    out
+}
+
+fn render_html(range: &str, generated_at: &str, sections: &[ReportSection]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Life report: {range}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>Life report: {range}</h1>\n<p class=\"meta\">Generated {generated_at}</p>\n",
+        range = html_escape(range),
+        generated_at = html_escape(generated_at)
+    );
+    for section in sections {
+        out.push_str(&format!("<h2>{}</h2>\n", html_escape(&section.title)));
+        if let Some(note) = &section.note {
+            out.push_str(&format!("<p class=\"note\">{}</p>\n", html_escape(note)));
+        }
+        if !section.cards.is_empty() {
+            out.push_str("<div class=\"cards\">\n");
+            for card in &section.cards {
+                out.push_str(&format!(
+                    "<div class=\"card\"><div class=\"label\">{}</div><div class=\"value\">{}</div></div>\n",
+                    html_escape(&card.label),
+                    html_escape(&card.value)
+                ));
+            }
+            out.push_str("</div>\n");
+        }
+        for table in &section.tables {
+            out.push_str(&format!(
+                "<h3>{}</h3>\n<table>\n<tr>",
+                html_escape(&table.title)
+            ));
+            for header in &table.headers {
+                out.push_str(&format!("<th>{}</th>", html_escape(header)));
+            }
+            out.push_str("</tr>\n");
+            for row in &table.rows {
+                out.push_str("<tr>");
+                for cell in row {
+                    out.push_str(&format!("<td>{}</td>", html_escape(cell)));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</table>\n");
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writes the report to `Reports/<file_name>` in `vault`. An existing report
+/// is only replaced with `overwrite`.
+pub(crate) fn write_report(
+    vault: &Path,
+    file_name: &str,
+    content: &str,
+    overwrite: bool,
+) -> Result<PathBuf, String> {
+    let dir = vault.join(REPORTS_DIR);
+    let path = dir.join(file_name);
+    if path.exists() && !overwrite {
+        return Err(format!(
+            "{REPORT_EXISTS_ERROR}: {REPORTS_DIR}/{file_name} already exists. Pass overwrite to replace it."
+        ));
+    }
+    fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    fs::write(&path, content).map_err(|err| err.to_string())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_report, report_file_name, write_report, ReportFormat, ReportSection};
+    use tempfile::tempdir;
+
+    fn section() -> ReportSection {
+        let mut section = ReportSection::new("Delivery");
+        section.card("Earnings", "$412.50");
+        section.table(
+            "Top merchants",
+            &["Merchant", "Orders"],
+            vec![vec!["Tom & Jerry's | Grill".to_string(), "4".to_string()]],
+        );
+        section.table("Platforms", &["Platform"], Vec::new());
+        section
+    }
+
+    #[test]
+    fn renders_markdown_and_standalone_html() {
+        let sections = vec![section()];
+        let markdown = render_report(
+            "7d",
+            "2026-03-02T10:00:00Z",
+            &sections,
+            ReportFormat::Markdown,
+        );
+        assert!(markdown.starts_with("# Life report: 7d\n\nGenerated 2026-03-02T10:00:00Z\n"));
+        assert!(markdown.contains("| Earnings | $412.50 |\n"));
+        assert!(markdown
+            .contains("| Merchant | Orders |\n| --- | --- |\n| Tom & Jerry's \\| Grill | 4 |\n"));
+        assert!(!markdown.contains("Platforms"));
+
+        let html = render_report("7d", "2026-03-02T10:00:00Z", &sections, ReportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(html.contains("<td>Tom &amp; Jerry's | Grill</td>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn saves_under_reports_without_overwriting() {
+        let dir = tempdir().expect("tempdir");
+        let name = report_file_name("2026-03-02", "7d", ReportFormat::Markdown);
+        assert_eq!(name, "2026-03-02-7d.md");
+        assert_eq!(
+            report_file_name("2026-03-02", "../x", ReportFormat::Html),
+            "2026-03-02-x.html"
+        );
+
+        let path = write_report(dir.path(), &name, "first", false).expect("write");
+        assert_eq!(path, dir.path().join("Reports").join("2026-03-02-7d.md"));
+        let err = write_report(dir.path(), &name, "second", false).expect_err("exists");
+        assert!(err.starts_with("report_exists: "));
+        write_report(dir.path(), &name, "second", true).expect("overwrite");
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "second");
+        assert!(ReportFormat::parse("pdf").is_err());
+    }
+}
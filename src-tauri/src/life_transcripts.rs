@@ -0,0 +1,359 @@
+//! Archives finished turns in Life workspaces with `lifeTranscriptEnabled`
+//! into the first vault, one file per month under `Stream/Transcripts/`. The
+//! stream parsers only read files directly in `Stream/`, so transcripts never
+//! show up as stream entries.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::backend::events::EventSink;
+use crate::backend::supervisor::spawn_guarded;
+use crate::life::is_life_workspace;
+use crate::notification_rules::thread_title_from_message;
+use crate::thread_items::parse_thread_item;
+use crate::types::{KnownThreadItem, ThreadItem, UserMessageItem, WorkspaceEntry};
+use crate::vault_roots::resolve_vault_roots;
+
+const TRANSCRIPTS_DIR: &str = "Transcripts";
+/// Each side of a turn is cut to this many characters.
+const MAX_MESSAGE_CHARS: usize = 4000;
+/// Finished turns waiting to be written. Turns past this are logged and
+/// dropped.
+const TRANSCRIPT_BACKLOG: usize = 32;
+
+/// The vault a workspace's transcripts go to, when it archives them.
+pub(crate) fn transcript_vault(entry: &WorkspaceEntry) -> Option<PathBuf> {
+    if !is_life_workspace(&entry.settings) || !entry.settings.life_transcript_enabled {
+        return None;
+    }
+    resolve_vault_roots(&entry.path, &entry.settings.effective_obsidian_roots())
+        .into_iter()
+        .next()
+        .map(|vault| vault.path)
+}
+
+/// Whether `observe` needs to see `message`, so callers only look up the
+/// workspace for events that matter.
+pub(crate) fn is_transcript_event(message: &Value) -> bool {
+    matches!(
+        message.get("method").and_then(Value::as_str),
+        Some("item/completed" | "turn/completed")
+    )
+}
+
+#[derive(Default)]
+struct PendingTurn {
+    user: Vec<String>,
+    agent: Vec<String>,
+}
+
+struct TurnTranscript {
+    vault: PathBuf,
+    thread: String,
+    finished_at: DateTime<Local>,
+    user: String,
+    agent: String,
+}
+
+/// Collects the messages of running turns and hands finished ones to a single
+/// writer task, which appends them one at a time.
+pub(crate) struct LifeTranscripts<E: EventSink> {
+    sink: E,
+    pending: Mutex<HashMap<String, PendingTurn>>,
+    queue: Mutex<Option<mpsc::Sender<TurnTranscript>>>,
+}
+
+impl<E: EventSink> LifeTranscripts<E> {
+    pub(crate) fn new(sink: E) -> Self {
+        Self {
+            sink,
+            pending: Mutex::new(HashMap::new()),
+            queue: Mutex::new(None),
+        }
+    }
+
+    /// Feeds an app-server event for a workspace. `vault` is the workspace's
+    /// `transcript_vault`; without one, messages are not collected.
+    pub(crate) fn observe(&self, workspace_id: &str, vault: Option<&Path>, message: &Value) {
+        let params = message.get("params");
+        let Some(thread_id) = params
+            .and_then(|params| {
+                params
+                    .get("threadId")
+                    .or_else(|| params.get("thread_id"))
+                    .or_else(|| params.pointer("/turn/threadId"))
+            })
+            .and_then(Value::as_str)
+        else {
+            return;
+        };
+        let key = format!("{workspace_id}:{thread_id}");
+        match message.get("method").and_then(Value::as_str) {
+            Some("item/completed") => {
+                let Some(item) = params.and_then(|params| params.get("item")) else {
+                    return;
+                };
+                if vault.is_none() {
+                    return;
+                }
+                let ThreadItem::Known(item) = parse_thread_item(item.clone()) else {
+                    return;
+                };
+                let mut pending = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+                match item {
+                    KnownThreadItem::UserMessage(item) => pending
+                        .entry(key)
+                        .or_default()
+                        .user
+                        .push(user_message_text(&item)),
+                    KnownThreadItem::AgentMessage(item) => {
+                        pending.entry(key).or_default().agent.push(item.text)
+                    }
+                    _ => {}
+                }
+            }
+            Some("turn/completed") => {
+                let turn = self
+                    .pending
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .remove(&key);
+                let (Some(turn), Some(vault)) = (turn, vault) else {
+                    return;
+                };
+                if turn.user.is_empty() && turn.agent.is_empty() {
+                    return;
+                }
+                self.enqueue(TurnTranscript {
+                    vault: vault.to_path_buf(),
+                    thread: thread_title_from_message(message),
+                    finished_at: Local::now(),
+                    user: turn.user.join("\n\n"),
+                    agent: turn.agent.join("\n\n"),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn enqueue(&self, transcript: TurnTranscript) {
+        let mut queue = self.queue.lock().unwrap_or_else(|err| err.into_inner());
+        // A writer that panicked is replaced on the next turn.
+        if queue.as_ref().is_some_and(mpsc::Sender::is_closed) {
+            *queue = None;
+        }
+        let sender = queue.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel(TRANSCRIPT_BACKLOG);
+            spawn_guarded(self.sink.clone(), "life transcripts", write_transcripts(rx));
+            tx
+        });
+        if let Err(err) = sender.try_send(transcript) {
+            let transcript = match err {
+                mpsc::error::TrySendError::Full(transcript)
+                | mpsc::error::TrySendError::Closed(transcript) => transcript,
+            };
+            eprintln!(
+                "Dropped the transcript for thread {}: {TRANSCRIPT_BACKLOG} turns are already waiting to be written",
+                transcript.thread
+            );
+        }
+    }
+}
+
+async fn write_transcripts(mut rx: mpsc::Receiver<TurnTranscript>) {
+    while let Some(transcript) = rx.recv().await {
+        if let Err(err) = append_transcript(&transcript) {
+            eprintln!(
+                "Failed to archive the transcript for thread {}: {err}",
+                transcript.thread
+            );
+        }
+    }
+}
+
+fn user_message_text(item: &UserMessageItem) -> String {
+    item.content
+        .iter()
+        .flatten()
+        .filter_map(|part| match part.get("type").and_then(Value::as_str) {
+            Some("text") => part.get("text").and_then(Value::as_str).map(str::to_string),
+            Some("image" | "localImage") => Some("[image]".to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn transcript_path(vault: &Path, finished_at: &DateTime<Local>) -> PathBuf {
+    vault
+        .join("Stream")
+        .join(TRANSCRIPTS_DIR)
+        .join(format!("{}.md", finished_at.format("%Y-%m")))
+}
+
+/// Stream files head each day with `## Wed Jan 21`.
+fn day_heading(finished_at: &DateTime<Local>) -> String {
+    format!("## {}", finished_at.format("%a %b %-d"))
+}
+
+fn append_transcript(transcript: &TurnTranscript) -> Result<(), String> {
+    let path = transcript_path(&transcript.vault, &transcript.finished_at);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let existing = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.to_string()),
+    };
+    let last_heading = existing.lines().rev().find(|line| line.starts_with("## "));
+    let text = render_transcript(transcript, last_heading, existing.is_empty());
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Messages are quoted so headings inside them cannot be mistaken for the
+/// file's own.
+fn render_transcript(
+    transcript: &TurnTranscript,
+    last_heading: Option<&str>,
+    starts_file: bool,
+) -> String {
+    let mut text = String::new();
+    let heading = day_heading(&transcript.finished_at);
+    if last_heading != Some(heading.as_str()) {
+        if !starts_file {
+            text.push('\n');
+        }
+        text.push_str(&heading);
+        text.push('\n');
+    }
+    text.push_str(&format!(
+        "\n### {} · {}\n",
+        transcript.finished_at.format("%-I:%M%P"),
+        transcript.thread
+    ));
+    for (label, message) in [("You", &transcript.user), ("Codex", &transcript.agent)] {
+        if message.trim().is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n**{label}**\n"));
+        for line in truncate_message(message).lines() {
+            text.push_str(if line.is_empty() { ">" } else { "> " });
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+fn truncate_message(message: &str) -> String {
+    let message = message.trim();
+    match message.char_indices().nth(MAX_MESSAGE_CHARS) {
+        Some((end, _)) => format!("{}\n\n… (truncated)", &message[..end]),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{transcript_path, LifeTranscripts, MAX_MESSAGE_CHARS};
+    use crate::backend::events::{
+        AppServerEvent, BackendError, EventSink, QuickCommandOutput, TerminalExited, TerminalOutput,
+    };
+    use chrono::Local;
+    use serde_json::json;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[derive(Clone)]
+    struct NullSink;
+
+    impl EventSink for NullSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exited(&self, _event: TerminalExited) {}
+        fn emit_quick_command_output(&self, _event: QuickCommandOutput) {}
+        fn emit_backend_error(&self, _event: BackendError) {}
+    }
+
+    fn turn(transcripts: &LifeTranscripts<NullSink>, vault: &std::path::Path, reply: &str) {
+        let user = json!({
+            "method": "item/completed",
+            "params": {
+                "threadId": "thread-1",
+                "item": {
+                    "type": "userMessage",
+                    "id": "u1",
+                    "content": [{ "type": "text", "text": "How did I sleep?" }]
+                }
+            }
+        });
+        let agent = json!({
+            "method": "item/completed",
+            "params": {
+                "threadId": "thread-1",
+                "item": { "type": "agentMessage", "id": "a1", "text": reply }
+            }
+        });
+        let done = json!({
+            "method": "turn/completed",
+            "params": { "threadId": "thread-1", "turn": { "id": "turn-1" } }
+        });
+        for message in [user, agent, done] {
+            transcripts.observe("ws", Some(vault), &message);
+        }
+    }
+
+    #[tokio::test]
+    async fn appends_finished_turns_under_one_day_heading() {
+        let vault = tempdir().expect("vault");
+        let transcripts = LifeTranscripts::new(NullSink);
+        turn(&transcripts, vault.path(), "## Summary\nSeven hours.");
+        turn(
+            &transcripts,
+            vault.path(),
+            &"z".repeat(MAX_MESSAGE_CHARS + 10),
+        );
+
+        let path = transcript_path(vault.path(), &Local::now());
+        let mut content = String::new();
+        for _ in 0..200 {
+            content = std::fs::read_to_string(&path).unwrap_or_default();
+            if content.contains("(truncated)") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(content.starts_with("## "), "{content}");
+        assert_eq!(
+            content
+                .lines()
+                .filter(|line| line.starts_with("## "))
+                .count(),
+            1,
+            "{content}"
+        );
+        assert_eq!(content.matches("### ").count(), 2, "{content}");
+        assert!(
+            content.contains("**You**\n> How did I sleep?\n"),
+            "{content}"
+        );
+        assert!(
+            content.contains("**Codex**\n> ## Summary\n> Seven hours.\n"),
+            "{content}"
+        );
+        assert!(!content.contains(&"z".repeat(MAX_MESSAGE_CHARS + 1)));
+    }
+}
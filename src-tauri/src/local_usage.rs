@@ -1,5 +1,7 @@
+use serde_json::Value;
 use tauri::{AppHandle, State};
 
+use crate::client_timezone::ClientTimezone;
 use crate::local_usage_core::local_usage_snapshot_core;
 use crate::remote_backend;
 use crate::state::AppState;
@@ -9,6 +11,7 @@ use crate::types::LocalUsageSnapshot;
 pub(crate) async fn local_usage_snapshot(
     days: Option<u32>,
     workspace_path: Option<String>,
+    timezone: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<LocalUsageSnapshot, String> {
@@ -17,10 +20,57 @@ pub(crate) async fn local_usage_snapshot(
             &*state,
             app,
             "local_usage_snapshot",
-            serde_json::json!({ "days": days.unwrap_or(30), "workspacePath": workspace_path }),
+            serde_json::json!({
+                "days": days.unwrap_or(30),
+                "workspacePath": workspace_path,
+                "timezone": timezone,
+            }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    local_usage_snapshot_core(days, workspace_path).await
+    let timezone = state
+        .client_timezone(timezone.as_deref(), ClientTimezone::Local)
+        .await?;
+    local_usage_snapshot_core(days, workspace_path, timezone).await
+}
+
+#[tauri::command]
+pub(crate) async fn usage_overview(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "usage_overview",
+            serde_json::json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+    let workspace = match workspace_id.as_deref() {
+        Some(id) => Some(
+            state
+                .workspaces
+                .lock()
+                .await
+                .get(id)
+                .cloned()
+                .ok_or("workspace not found")?,
+        ),
+        None => None,
+    };
+    let session = {
+        let sessions = state.sessions.lock().await;
+        match workspace_id.as_deref() {
+            Some(id) => sessions.get(id).cloned(),
+            None => sessions.values().next().cloned(),
+        }
+    };
+    let pricing = state.app_settings.lock().await.usage_pricing.clone();
+    let overview =
+        crate::usage_overview::usage_overview(workspace.as_ref(), session, pricing).await;
+    serde_json::to_value(overview).map_err(|err| err.to_string())
 }
@@ -1,4 +1,4 @@
-use chrono::{Duration, Local, TimeZone, Utc};
+use chrono::{Duration, TimeZone, Utc};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
@@ -6,6 +6,7 @@ use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::client_timezone::ClientTimezone;
 use crate::types::{LocalUsageDay, LocalUsageModel, LocalUsageSnapshot, LocalUsageTotals};
 
 #[derive(Default, Clone, Copy)]
@@ -26,9 +27,11 @@ struct UsageTotals {
 
 const MAX_ACTIVITY_GAP_MS: i64 = 2 * 60 * 1000;
 
+/// Buckets usage into the last `days` calendar days of `timezone`.
 pub(crate) async fn local_usage_snapshot_core(
     days: Option<u32>,
     workspace_path: Option<String>,
+    timezone: ClientTimezone,
 ) -> Result<LocalUsageSnapshot, String> {
     let days = days.unwrap_or(30).clamp(1, 90);
     let workspace_path = workspace_path.and_then(|value| {
@@ -39,23 +42,25 @@ pub(crate) async fn local_usage_snapshot_core(
             Some(PathBuf::from(trimmed))
         }
     });
-    let snapshot =
-        tokio::task::spawn_blocking(move || scan_local_usage(days, workspace_path.as_deref()))
-            .await
-            .map_err(|err| err.to_string())??;
+    let snapshot = tokio::task::spawn_blocking(move || {
+        scan_local_usage(days, workspace_path.as_deref(), timezone)
+    })
+    .await
+    .map_err(|err| err.to_string())??;
     Ok(snapshot)
 }
 
 fn scan_local_usage(
     days: u32,
     workspace_path: Option<&Path>,
+    timezone: ClientTimezone,
 ) -> Result<LocalUsageSnapshot, String> {
     let updated_at = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis() as i64;
 
-    let day_keys = make_day_keys(days);
+    let day_keys = make_day_keys(days, timezone);
     let mut daily: HashMap<String, DailyTotals> = day_keys
         .iter()
         .map(|key| (key.clone(), DailyTotals::default()))
@@ -80,7 +85,13 @@ fn scan_local_usage(
             if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
                 continue;
             }
-            scan_file(&path, &mut daily, &mut model_totals, workspace_path)?;
+            scan_file(
+                &path,
+                &mut daily,
+                &mut model_totals,
+                workspace_path,
+                timezone,
+            )?;
         }
     }
 
@@ -171,6 +182,7 @@ fn scan_file(
     daily: &mut HashMap<String, DailyTotals>,
     model_totals: &mut HashMap<String, i64>,
     workspace_path: Option<&Path>,
+    timezone: ClientTimezone,
 ) -> Result<(), String> {
     let file = match File::open(path) {
         Ok(file) => file,
@@ -247,20 +259,20 @@ fn scan_file(
             if payload_type == Some("agent_message") {
                 if let Some(timestamp_ms) = read_timestamp_ms(&value) {
                     if seen_runs.insert(timestamp_ms) {
-                        if let Some(day_key) = day_key_for_timestamp_ms(timestamp_ms) {
+                        if let Some(day_key) = day_key_for_timestamp_ms(timestamp_ms, timezone) {
                             if let Some(entry) = daily.get_mut(&day_key) {
                                 entry.agent_runs += 1;
                             }
                         }
                     }
-                    track_activity(daily, &mut last_activity_ms, timestamp_ms);
+                    track_activity(daily, &mut last_activity_ms, timestamp_ms, timezone);
                 }
                 continue;
             }
 
             if payload_type == Some("agent_reasoning") {
                 if let Some(timestamp_ms) = read_timestamp_ms(&value) {
-                    track_activity(daily, &mut last_activity_ms, timestamp_ms);
+                    track_activity(daily, &mut last_activity_ms, timestamp_ms, timezone);
                 }
                 continue;
             }
@@ -340,14 +352,14 @@ fn scan_file(
             }
 
             if let Some(timestamp_ms) = read_timestamp_ms(&value) {
-                if let Some(day_key) = day_key_for_timestamp_ms(timestamp_ms) {
+                if let Some(day_key) = day_key_for_timestamp_ms(timestamp_ms, timezone) {
                     if let Some(entry) = daily.get_mut(&day_key) {
                         entry.input += delta.input;
                         entry.cached += delta.cached;
                         entry.output += delta.output;
                     }
                 }
-                track_activity(daily, &mut last_activity_ms, timestamp_ms);
+                track_activity(daily, &mut last_activity_ms, timestamp_ms, timezone);
             }
 
             if let Some(model) = current_model.clone() {
@@ -364,6 +376,7 @@ fn track_activity(
     daily: &mut HashMap<String, DailyTotals>,
     last_activity_ms: &mut Option<i64>,
     timestamp_ms: i64,
+    timezone: ClientTimezone,
 ) {
     if let Some(last) = *last_activity_ms {
         if timestamp_ms - last > MAX_ACTIVITY_GAP_MS {
@@ -373,7 +386,7 @@ fn track_activity(
         *last_activity_ms = Some(timestamp_ms);
     }
 
-    if let Some(day_key) = day_key_for_timestamp_ms(timestamp_ms) {
+    if let Some(day_key) = day_key_for_timestamp_ms(timestamp_ms, timezone) {
         if let Some(entry) = daily.get_mut(&day_key) {
             entry.agent_ms += MAX_ACTIVITY_GAP_MS;
         }
@@ -407,21 +420,16 @@ fn read_timestamp_ms(value: &Value) -> Option<i64> {
     })
 }
 
-fn day_key_for_timestamp_ms(timestamp_ms: i64) -> Option<String> {
+fn day_key_for_timestamp_ms(timestamp_ms: i64, timezone: ClientTimezone) -> Option<String> {
     let timestamp = Utc.timestamp_millis_opt(timestamp_ms).single()?;
-    Some(
-        timestamp
-            .with_timezone(&Local)
-            .format("%Y-%m-%d")
-            .to_string(),
-    )
+    Some(timezone.date_at(timestamp).format("%Y-%m-%d").to_string())
 }
 
-fn make_day_keys(days: u32) -> Vec<String> {
-    let now = Local::now();
+fn make_day_keys(days: u32, timezone: ClientTimezone) -> Vec<String> {
+    let today = timezone.today();
     (0..days)
         .map(|offset| {
-            let day = now - Duration::days(offset as i64);
+            let day = today - Duration::days(offset as i64);
             day.format("%Y-%m-%d").to_string()
         })
         .collect::<Vec<_>>()
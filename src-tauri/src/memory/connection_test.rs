@@ -0,0 +1,327 @@
+//! Connection test for the memory settings: URL shape, Supabase reachability,
+//! API key, memory table and, when enabled, the MiniMax embeddings key.
+
+use super::embeddings::EmbeddingsClient;
+use super::supabase::SupabaseClient;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+// MiniMax calls are spaced 15s apart and retried on rate limits.
+const EMBEDDINGS_TIMEOUT: Duration = Duration::from_secs(60);
+const MEMORY_TABLE_SQL: &str = include_str!("../../../migrations/001_memory_table.sql");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryConnectionCheck {
+    /// `config`, `reachable`, `auth`, `table` or `embeddings`.
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// SQL that creates the missing table, on a failed `table` check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sql: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryConnectionReport {
+    /// True when no check failed.
+    pub ok: bool,
+    /// The URL as it was tested, after normalization.
+    pub supabase_url: String,
+    pub checks: Vec<MemoryConnectionCheck>,
+}
+
+fn check(name: &str, status: CheckStatus, message: impl Into<String>) -> MemoryConnectionCheck {
+    MemoryConnectionCheck {
+        name: name.to_string(),
+        status,
+        message: message.into(),
+        sql: None,
+    }
+}
+
+/// Trims whitespace, trailing slashes and a pasted `/rest/v1` suffix, and
+/// assumes https when no scheme is given.
+pub fn normalize_supabase_url(url: &str) -> String {
+    let trimmed = url.trim().trim_end_matches('/');
+    let trimmed = trimmed.strip_suffix("/rest/v1").unwrap_or(trimmed);
+    if trimmed.is_empty() || trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        format!("https://{trimmed}")
+    }
+}
+
+fn config_error(url: &str, anon_key: &str) -> Option<String> {
+    if url.is_empty() {
+        return Some("Supabase URL is empty".to_string());
+    }
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => {}
+        Ok(_) => return Some(format!("Supabase URL must be an http(s) URL: {url}")),
+        Err(err) => return Some(format!("Supabase URL is invalid: {err}")),
+    }
+    if anon_key.is_empty() {
+        return Some("Supabase anon key is empty".to_string());
+    }
+    None
+}
+
+/// PostgREST error text: `message` (plus `hint`) from the JSON body, or the
+/// raw body.
+fn error_message(body: &str) -> String {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return body.trim().to_string();
+    };
+    let message = value
+        .get("message")
+        .or_else(|| value.get("msg"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(body.trim());
+    match value.get("hint").and_then(|v| v.as_str()) {
+        Some(hint) if !hint.is_empty() => format!("{message} ({hint})"),
+        _ => message.to_string(),
+    }
+}
+
+fn is_missing_table(status: u16, body: &str) -> bool {
+    let code = serde_json::from_str::<Value>(body).ok().and_then(|value| {
+        value
+            .get("code")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    });
+    matches!(code.as_deref(), Some("PGRST205") | Some("42P01"))
+        || (status == 404 && body.contains("memory"))
+}
+
+/// Tests the given settings without touching the running memory service.
+/// `minimax_api_key` is `None` when embeddings are disabled.
+pub async fn test_connection(
+    supabase_url: &str,
+    supabase_anon_key: &str,
+    minimax_api_key: Option<&str>,
+) -> MemoryConnectionReport {
+    let embeddings = minimax_api_key.map(|key| {
+        let key = key.trim();
+        (!key.is_empty()).then(|| EmbeddingsClient::new(key))
+    });
+    check_connection(
+        &normalize_supabase_url(supabase_url),
+        supabase_anon_key.trim(),
+        embeddings,
+    )
+    .await
+}
+
+async fn check_connection(
+    url: &str,
+    anon_key: &str,
+    embeddings: Option<Option<EmbeddingsClient>>,
+) -> MemoryConnectionReport {
+    let mut checks = Vec::new();
+    match config_error(url, anon_key) {
+        Some(error) => {
+            checks.push(check("config", CheckStatus::Failed, error));
+            for name in ["reachable", "auth", "table"] {
+                checks.push(check(
+                    name,
+                    CheckStatus::Skipped,
+                    "Fix the configuration first",
+                ));
+            }
+        }
+        None => {
+            checks.push(check("config", CheckStatus::Ok, "URL and anon key are set"));
+            checks.extend(check_supabase(url, anon_key).await);
+        }
+    }
+    checks.push(check_embeddings(embeddings).await);
+
+    MemoryConnectionReport {
+        ok: checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Failed),
+        supabase_url: url.to_string(),
+        checks,
+    }
+}
+
+async fn check_supabase(url: &str, anon_key: &str) -> Vec<MemoryConnectionCheck> {
+    let client = SupabaseClient::new(url, anon_key);
+    let (status, body) = match client.probe_memory_table(PROBE_TIMEOUT).await {
+        Ok(response) => response,
+        Err(err) => {
+            return vec![
+                check(
+                    "reachable",
+                    CheckStatus::Failed,
+                    format!("Could not reach {url}: {err}"),
+                ),
+                check("auth", CheckStatus::Skipped, "Supabase is unreachable"),
+                check("table", CheckStatus::Skipped, "Supabase is unreachable"),
+            ];
+        }
+    };
+    let reachable = check("reachable", CheckStatus::Ok, format!("{url} responded"));
+    if status == 401 || status == 403 {
+        return vec![
+            reachable,
+            check(
+                "auth",
+                CheckStatus::Failed,
+                format!(
+                    "Supabase rejected the anon key ({status}): {}",
+                    error_message(&body)
+                ),
+            ),
+            check("table", CheckStatus::Skipped, "The anon key was rejected"),
+        ];
+    }
+    let auth = check("auth", CheckStatus::Ok, "Anon key accepted");
+    let table = if (200..300).contains(&status) {
+        check("table", CheckStatus::Ok, "Table `memory` is readable")
+    } else if is_missing_table(status, &body) {
+        MemoryConnectionCheck {
+            sql: Some(MEMORY_TABLE_SQL.to_string()),
+            ..check(
+                "table",
+                CheckStatus::Failed,
+                format!("Table `memory` not found: {}", error_message(&body)),
+            )
+        }
+    } else {
+        check(
+            "table",
+            CheckStatus::Failed,
+            format!(
+                "Reading `memory` failed ({status}): {}",
+                error_message(&body)
+            ),
+        )
+    };
+    vec![reachable, auth, table]
+}
+
+async fn check_embeddings(embeddings: Option<Option<EmbeddingsClient>>) -> MemoryConnectionCheck {
+    let client = match embeddings {
+        None => {
+            return check(
+                "embeddings",
+                CheckStatus::Skipped,
+                "Embeddings are disabled",
+            )
+        }
+        Some(None) => {
+            return check(
+                "embeddings",
+                CheckStatus::Failed,
+                "MiniMax API key is empty",
+            );
+        }
+        Some(Some(client)) => client,
+    };
+    match tokio::time::timeout(
+        EMBEDDINGS_TIMEOUT,
+        client.generate("CodexMonitor connection test", "query"),
+    )
+    .await
+    {
+        Ok(Ok(result)) => check(
+            "embeddings",
+            CheckStatus::Ok,
+            format!("MiniMax returned a {}-dimension embedding", result.dim),
+        ),
+        Ok(Err(err)) => check("embeddings", CheckStatus::Failed, err),
+        Err(_) => check(
+            "embeddings",
+            CheckStatus::Failed,
+            "MiniMax did not respond in time",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::Method::GET;
+    use httpmock::MockServer;
+    use serde_json::json;
+
+    #[test]
+    fn normalizes_pasted_urls() {
+        assert_eq!(
+            normalize_supabase_url(" abc.supabase.co/rest/v1/ "),
+            "https://abc.supabase.co"
+        );
+        assert_eq!(
+            normalize_supabase_url("http://localhost:54321/"),
+            "http://localhost:54321"
+        );
+        assert_eq!(normalize_supabase_url("  "), "");
+    }
+
+    #[tokio::test]
+    async fn reports_missing_table_with_sql() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/rest/v1/memory");
+            then.status(404).json_body(json!({
+                "code": "PGRST205",
+                "message": "Could not find the table 'public.memory' in the schema cache"
+            }));
+        });
+
+        let report = check_connection(&server.base_url(), "anon", None).await;
+        assert!(!report.ok);
+        let statuses: Vec<(&str, CheckStatus)> = report
+            .checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("config", CheckStatus::Ok),
+                ("reachable", CheckStatus::Ok),
+                ("auth", CheckStatus::Ok),
+                ("table", CheckStatus::Failed),
+                ("embeddings", CheckStatus::Skipped),
+            ]
+        );
+        let table = &report.checks[3];
+        assert!(table.message.contains("schema cache"));
+        assert!(table
+            .sql
+            .as_deref()
+            .is_some_and(|sql| sql.contains("CREATE TABLE IF NOT EXISTS memory")));
+    }
+
+    #[tokio::test]
+    async fn rejected_key_fails_auth() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/rest/v1/memory");
+            then.status(401)
+                .json_body(json!({ "message": "Invalid API key" }));
+        });
+
+        let report = check_connection(&server.base_url(), "wrong", Some(None)).await;
+        assert!(!report.ok);
+        assert_eq!(report.checks[2].status, CheckStatus::Failed);
+        assert!(report.checks[2].message.contains("Invalid API key"));
+        assert_eq!(report.checks[3].status, CheckStatus::Skipped);
+        assert_eq!(report.checks[4].status, CheckStatus::Failed);
+    }
+}
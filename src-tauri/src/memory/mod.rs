@@ -1,3 +1,4 @@
+pub mod connection_test;
 pub mod embeddings;
 pub mod service;
 pub mod supabase;
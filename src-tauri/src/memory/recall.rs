@@ -0,0 +1,178 @@
+//! Memory recall for outgoing messages: when `autoMemory.recallEnabled` is on,
+//! the message text is used as a memory search and the top results are
+//! prepended to the turn's instructions as a compact context block.
+
+use crate::memory::supabase::MemorySearchResult;
+use crate::memory::MemoryService;
+use crate::types::AutoMemorySettings;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// How long a send waits on the search before going ahead without recall.
+pub(crate) const RECALL_TIMEOUT: Duration = Duration::from_millis(500);
+/// Longest prefix of the message used as the search query.
+const MAX_QUERY_CHARS: usize = 500;
+/// Longest snippet kept per recalled entry.
+const MAX_SNIPPET_CHARS: usize = 240;
+const RECALL_HEADER: &str = "Relevant memory (recalled automatically, may be outdated):";
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecallReport {
+    pub(crate) included: bool,
+    pub(crate) entries: usize,
+    /// Why nothing was included: `disabled`, `timeout`, `error` or `empty`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) skipped: Option<&'static str>,
+}
+
+impl RecallReport {
+    fn skipped(reason: &'static str) -> Self {
+        Self {
+            included: false,
+            entries: 0,
+            skipped: Some(reason),
+        }
+    }
+}
+
+/// Runs the recall search for `text`. Returns nothing when recall is off in
+/// settings; otherwise the context block (if any) and a report for the response.
+pub(crate) async fn recall_for_message(
+    memory: Option<&MemoryService>,
+    settings: &AutoMemorySettings,
+    text: &str,
+    disable_recall: bool,
+) -> Option<(Option<String>, RecallReport)> {
+    if !settings.recall_enabled {
+        return None;
+    }
+    if disable_recall {
+        return Some((None, RecallReport::skipped("disabled")));
+    }
+    let query: String = text.trim().chars().take(MAX_QUERY_CHARS).collect();
+    let (Some(memory), false) = (memory, query.is_empty()) else {
+        return Some((None, RecallReport::skipped("empty")));
+    };
+    let results = match timeout(RECALL_TIMEOUT, memory.search(&query, settings.recall_limit)).await
+    {
+        Ok(Ok(results)) => results,
+        Ok(Err(_)) => return Some((None, RecallReport::skipped("error"))),
+        Err(_) => return Some((None, RecallReport::skipped("timeout"))),
+    };
+    let Some((block, entries)) = format_recall_block(&results, settings.recall_max_bytes) else {
+        return Some((None, RecallReport::skipped("empty")));
+    };
+    let report = RecallReport {
+        included: true,
+        entries,
+        skipped: None,
+    };
+    Some((Some(block), report))
+}
+
+/// Formats results as `- [type, date] snippet` lines under a header, adding
+/// entries only while the block stays within `max_bytes`.
+pub(crate) fn format_recall_block(
+    results: &[MemorySearchResult],
+    max_bytes: usize,
+) -> Option<(String, usize)> {
+    let mut block = RECALL_HEADER.to_string();
+    let mut entries = 0;
+    for result in results {
+        let snippet = snippet(&result.content);
+        if snippet.is_empty() {
+            continue;
+        }
+        let date = result.created_at.get(..10).unwrap_or(&result.created_at);
+        let line = format!("\n- [{}, {}] {}", result.memory_type, date, snippet);
+        if block.len() + line.len() > max_bytes {
+            break;
+        }
+        block.push_str(&line);
+        entries += 1;
+    }
+    (entries > 0).then_some((block, entries))
+}
+
+/// Puts the recall block ahead of the domain instructions.
+pub(crate) fn prepend_recall(
+    block: Option<String>,
+    instructions: Option<String>,
+) -> Option<String> {
+    match (block, instructions) {
+        (Some(block), Some(instructions)) => Some(format!("{block}\n\n{instructions}")),
+        (block, instructions) => block.or(instructions),
+    }
+}
+
+pub(crate) fn attach_recall_report(mut response: Value, report: Option<&RecallReport>) -> Value {
+    if let (Some(report), Some(map)) = (report, response.as_object_mut()) {
+        if let Ok(report) = serde_json::to_value(report) {
+            map.insert("memoryRecall".to_string(), report);
+        }
+    }
+    response
+}
+
+fn snippet(content: &str) -> String {
+    let collapsed = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= MAX_SNIPPET_CHARS {
+        return collapsed;
+    }
+    let mut snippet: String = collapsed.chars().take(MAX_SNIPPET_CHARS).collect();
+    snippet.push('…');
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_recall_block, prepend_recall, RECALL_HEADER};
+    use crate::memory::supabase::MemorySearchResult;
+
+    fn result(memory_type: &str, content: &str) -> MemorySearchResult {
+        MemorySearchResult {
+            id: "id".to_string(),
+            content: content.to_string(),
+            memory_type: memory_type.to_string(),
+            tags: Vec::new(),
+            workspace_id: None,
+            created_at: "2026-10-01T12:00:00Z".to_string(),
+            distance: None,
+            score: None,
+            rank: None,
+        }
+    }
+
+    #[test]
+    fn formats_entries_within_the_byte_budget() {
+        let results = vec![
+            result("daily", "Switched the\n  daemon to TCP."),
+            result("curated", &"long ".repeat(200)),
+            result("daily", "never reached"),
+        ];
+        let (block, entries) = format_recall_block(&results, 200).expect("block");
+        assert_eq!(entries, 1);
+        assert_eq!(
+            block,
+            format!("{RECALL_HEADER}\n- [daily, 2026-10-01] Switched the daemon to TCP.")
+        );
+        assert!(format_recall_block(&results[1..2], 100).is_none());
+        assert!(format_recall_block(&[], 2_000).is_none());
+    }
+
+    #[test]
+    fn recall_block_goes_before_domain_instructions() {
+        assert_eq!(
+            prepend_recall(Some("recall".to_string()), Some("domain".to_string())),
+            Some("recall\n\ndomain".to_string())
+        );
+        assert_eq!(
+            prepend_recall(None, Some("domain".to_string())),
+            Some("domain".to_string())
+        );
+        assert_eq!(prepend_recall(None, None), None);
+    }
+}
@@ -4,6 +4,7 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct SupabaseClient {
@@ -227,6 +228,25 @@ impl SupabaseClient {
         resp.json().await.map_err(|e| e.to_string())
     }
 
+    /// Fetch at most one row id from the memory table. Returns the HTTP status
+    /// and body so callers can tell a bad key from a missing table.
+    pub async fn probe_memory_table(&self, timeout: Duration) -> Result<(u16, String), String> {
+        let url = format!("{}/rest/v1/memory?select=id&limit=1", self.url);
+
+        let resp = self
+            .client
+            .get(&url)
+            .headers(self.headers())
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let status = resp.status().as_u16();
+        let text = resp.text().await.unwrap_or_default();
+        Ok((status, text))
+    }
+
     /// Get memory status (counts by status)
     pub async fn get_status(&self) -> Result<Value, String> {
         // Count total, pending, ready, error
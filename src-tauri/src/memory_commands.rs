@@ -4,11 +4,13 @@ use tauri::{AppHandle, State};
 use crate::auto_flush::{
     build_snapshot, parse_memory_flush_result, run_memory_flush_summarizer, write_memory_flush,
 };
+use crate::memory::connection_test::MemoryConnectionReport;
 use crate::memory::service::MemoryStatus;
 use crate::memory::supabase::{MemoryEntry, MemorySearchResult};
 use crate::remote_backend;
+use crate::settings::test_memory_settings;
 use crate::state::AppState;
-use crate::types::AutoMemorySettings;
+use crate::types::{AppSettings, AutoMemorySettings};
 
 #[tauri::command]
 pub(crate) async fn memory_status(
@@ -35,6 +37,32 @@ pub(crate) async fn memory_status(
     }
 }
 
+/// Tests `settings`, or the saved settings when omitted, so the settings
+/// screen can check a candidate before saving it.
+#[tauri::command]
+pub(crate) async fn memory_test_connection(
+    settings: Option<AppSettings>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<MemoryConnectionReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "memory_test_connection",
+            json!({ "settings": settings }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let settings = match settings {
+        Some(settings) => settings,
+        None => state.app_settings.lock().await.clone(),
+    };
+    Ok(test_memory_settings(&settings).await)
+}
+
 #[tauri::command]
 pub(crate) async fn memory_search(
     query: String,
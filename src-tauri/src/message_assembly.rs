@@ -0,0 +1,377 @@
+//! The steps between `send_user_message`'s arguments and its `turn/start`
+//! params. `estimate_message_size` runs the same steps and measures the
+//! params instead of sending them, so the two cannot drift apart.
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::attachments::resolve_image_refs;
+use crate::codex_params::{build_turn_start_params, build_user_input};
+use crate::domain_prompts::{domain_includes_dir, expand_domain_prompt, MAX_EXPANDED_PROMPT_CHARS};
+use crate::file_refs::{apply_file_context, build_file_context, FileContext};
+use crate::memory_recall::{prepend_recall, RecallReport};
+use crate::redaction::Redactor;
+use crate::types::{AccessLock, Domain};
+
+/// Token estimate for text; swap this for a real tokenizer when one is
+/// available.
+const CHARS_PER_TOKEN: u64 = 4;
+/// Rough cost of one image in the model's input.
+const IMAGE_TOKEN_ESTIMATE: u64 = 765;
+
+/// Workspace state the message is assembled against.
+pub(crate) struct MessageSources<'a> {
+    pub(crate) workspace_id: &'a str,
+    pub(crate) workspace_path: &'a str,
+    pub(crate) data_dir: &'a Path,
+    /// The workspace's domain when its instructions go with every turn.
+    pub(crate) domain: Option<Domain>,
+    pub(crate) domains_path: &'a Path,
+    pub(crate) redactor: Option<&'a Redactor>,
+}
+
+pub(crate) struct AssembledMessage {
+    pub(crate) input: Vec<Value>,
+    /// Domain instructions with the memory recall block in front.
+    pub(crate) instructions: Option<String>,
+    pub(crate) file_context: Option<FileContext>,
+    pub(crate) recall_report: Option<RecallReport>,
+}
+
+/// Attaches `file_refs`, resolves `images` and expands the domain prompt.
+/// `recall` is the result of `recall_for_message` for the typed text.
+pub(crate) fn assemble_message(
+    sources: &MessageSources,
+    text: &str,
+    file_refs: Option<&[String]>,
+    best_effort: bool,
+    images: Option<Vec<String>>,
+    recall: Option<(Option<String>, RecallReport)>,
+) -> Result<AssembledMessage, String> {
+    let file_context = match file_refs {
+        Some(refs) if !refs.is_empty() => Some(build_file_context(
+            Path::new(sources.workspace_path),
+            refs,
+            best_effort,
+            sources.redactor,
+        )?),
+        _ => None,
+    };
+    let text = match &file_context {
+        Some(context) => apply_file_context(text, context),
+        None => text.to_string(),
+    };
+    let images = match images {
+        Some(images) => Some(resolve_image_refs(
+            sources.data_dir,
+            sources.workspace_id,
+            images,
+        )?),
+        None => None,
+    };
+    let input = build_user_input(&text, images.as_deref())?;
+    let domain_instructions = match &sources.domain {
+        Some(domain) => Some(expand_domain_prompt(
+            domain,
+            &domain_includes_dir(sources.domains_path),
+            MAX_EXPANDED_PROMPT_CHARS,
+        )?),
+        None => None,
+    };
+    let (recall_block, recall_report) = recall.unzip();
+    Ok(AssembledMessage {
+        input,
+        instructions: prepend_recall(recall_block.flatten(), domain_instructions),
+        file_context,
+        recall_report,
+    })
+}
+
+/// Access modes from least to most permissive. Anything else is `current`.
+const ACCESS_MODES: [&str; 3] = ["read-only", "current", "full-access"];
+
+fn access_rank(mode: Option<&str>) -> usize {
+    ACCESS_MODES
+        .iter()
+        .position(|known| Some(*known) == mode)
+        .unwrap_or(1)
+}
+
+/// The approval and sandbox policy a turn is started with. Returned as is
+/// by `preview_turn_policy`, so what the UI shows is what gets sent.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnPolicy {
+    pub(crate) requested_access_mode: String,
+    pub(crate) access_mode: &'static str,
+    /// The workspace's `accessLock` when it lowered the requested mode.
+    pub(crate) locked_by: Option<AccessLock>,
+    pub(crate) approval_policy: &'static str,
+    pub(crate) sandbox_policy: Value,
+}
+
+/// Resolves `accessMode` for a workspace: `full-access`, `read-only`, or
+/// workspace-write on the workspace root for anything else, lowered to the
+/// workspace's `accessLock` when that is stricter.
+pub(crate) fn turn_policy(
+    access_mode: Option<&str>,
+    workspace_path: &str,
+    access_lock: Option<AccessLock>,
+) -> TurnPolicy {
+    let requested = access_rank(access_mode);
+    let lock = access_lock.map(|lock| access_rank(Some(lock.as_str())));
+    let effective = lock.map_or(requested, |lock| lock.min(requested));
+    let (approval_policy, sandbox_policy) = match ACCESS_MODES[effective] {
+        "full-access" => ("never", json!({ "type": "dangerFullAccess" })),
+        "read-only" => ("on-request", json!({ "type": "readOnly" })),
+        _ => (
+            "on-request",
+            json!({
+                "type": "workspaceWrite",
+                "writableRoots": [workspace_path],
+                "networkAccess": true
+            }),
+        ),
+    };
+    TurnPolicy {
+        requested_access_mode: access_mode.unwrap_or("current").to_string(),
+        access_mode: ACCESS_MODES[effective],
+        locked_by: access_lock.filter(|_| effective < requested),
+        approval_policy,
+        sandbox_policy,
+    }
+}
+
+impl AssembledMessage {
+    /// `extras` are already resolved with `resolve_turn_extras`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn turn_start_params(
+        &self,
+        thread_id: &str,
+        workspace_path: &str,
+        policy: &TurnPolicy,
+        model: Option<String>,
+        effort: Option<String>,
+        collaboration_mode: Option<Value>,
+        extras: Option<Map<String, Value>>,
+    ) -> Result<Value, String> {
+        build_turn_start_params(
+            thread_id,
+            self.input.clone(),
+            workspace_path,
+            policy.approval_policy,
+            policy.sandbox_policy.clone(),
+            model,
+            effort,
+            collaboration_mode,
+            self.instructions.clone(),
+            extras,
+        )
+    }
+
+    /// Measures `params` (from `turn_start_params`) against the model's
+    /// context window. Earlier turns in the thread are not counted.
+    pub(crate) fn estimate(
+        &self,
+        params: &Value,
+        model: Option<String>,
+        context_window: Option<u64>,
+    ) -> MessageSizeEstimate {
+        let block = self
+            .file_context
+            .as_ref()
+            .map(|context| context.block.as_str())
+            .unwrap_or("");
+        let mut text = ComponentSize::default();
+        let mut images = ComponentSize::default();
+        for item in params["input"].as_array().into_iter().flatten() {
+            match item["type"].as_str() {
+                Some("text") => {
+                    let value = item["text"].as_str().unwrap_or("");
+                    text.add_text(value);
+                }
+                Some("localImage") => {
+                    let path = item["path"].as_str().unwrap_or("");
+                    let bytes = std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+                    images.add(bytes, IMAGE_TOKEN_ESTIMATE);
+                }
+                Some("image") => {
+                    let url = item["url"].as_str().unwrap_or("");
+                    images.add(url.len() as u64, IMAGE_TOKEN_ESTIMATE);
+                }
+                _ => {}
+            }
+        }
+        let mut file_context = ComponentSize::default();
+        file_context.add_text(block);
+        text.bytes = text.bytes.saturating_sub(file_context.bytes);
+        text.tokens = text.tokens.saturating_sub(file_context.tokens);
+        let mut instructions = ComponentSize::default();
+        instructions.add_text(params["instructionInjection"].as_str().unwrap_or(""));
+
+        let components = [&text, &file_context, &images, &instructions];
+        let total_bytes = components.iter().map(|part| part.bytes).sum();
+        let estimated_tokens = components.iter().map(|part| part.tokens).sum();
+        MessageSizeEstimate {
+            text,
+            file_context,
+            images,
+            instructions,
+            total_bytes,
+            estimated_tokens,
+            fits: context_window.map(|window| estimated_tokens <= window),
+            model,
+            context_window,
+            files: self.file_context.clone(),
+            memory_recall: self.recall_report.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub(crate) struct ComponentSize {
+    pub(crate) bytes: u64,
+    pub(crate) tokens: u64,
+}
+
+impl ComponentSize {
+    fn add(&mut self, bytes: u64, tokens: u64) {
+        self.bytes += bytes;
+        self.tokens += tokens;
+    }
+
+    fn add_text(&mut self, text: &str) {
+        let chars = text.chars().count() as u64;
+        self.add(text.len() as u64, chars.div_ceil(CHARS_PER_TOKEN));
+    }
+}
+
+/// Response of `estimate_message_size`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MessageSizeEstimate {
+    pub(crate) text: ComponentSize,
+    pub(crate) file_context: ComponentSize,
+    pub(crate) images: ComponentSize,
+    /// Domain instructions, memory recall and collaboration mode instructions.
+    pub(crate) instructions: ComponentSize,
+    pub(crate) total_bytes: u64,
+    pub(crate) estimated_tokens: u64,
+    pub(crate) model: Option<String>,
+    pub(crate) context_window: Option<u64>,
+    /// `None` when the model's context window is unknown.
+    pub(crate) fits: Option<bool>,
+    /// The files that would be attached or skipped, as in `fileContext` on
+    /// `send_user_message`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) files: Option<FileContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) memory_recall: Option<RecallReport>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assemble_message, turn_policy, MessageSources};
+    use crate::types::AccessLock;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn turn_policy_resolves_each_mode_and_applies_the_access_lock() {
+        let full = turn_policy(Some("full-access"), "/ws", None);
+        assert_eq!(full.approval_policy, "never");
+        assert_eq!(full.sandbox_policy, json!({ "type": "dangerFullAccess" }));
+        assert_eq!(full.locked_by, None);
+
+        let read_only = turn_policy(Some("read-only"), "/ws", None);
+        assert_eq!(read_only.approval_policy, "on-request");
+        assert_eq!(read_only.sandbox_policy, json!({ "type": "readOnly" }));
+
+        let current = turn_policy(None, "/ws", None);
+        assert_eq!(current.requested_access_mode, "current");
+        assert_eq!(current.access_mode, "current");
+        assert_eq!(
+            current.sandbox_policy,
+            json!({
+                "type": "workspaceWrite",
+                "writableRoots": ["/ws"],
+                "networkAccess": true
+            })
+        );
+        assert_eq!(
+            turn_policy(Some("bogus"), "/ws", None).access_mode,
+            "current"
+        );
+
+        let lowered = turn_policy(Some("full-access"), "/ws", Some(AccessLock::Current));
+        assert_eq!(lowered.requested_access_mode, "full-access");
+        assert_eq!(lowered.access_mode, "current");
+        assert_eq!(lowered.approval_policy, "on-request");
+        assert_eq!(lowered.sandbox_policy, current.sandbox_policy);
+        assert_eq!(lowered.locked_by, Some(AccessLock::Current));
+
+        let locked = turn_policy(None, "/ws", Some(AccessLock::ReadOnly));
+        assert_eq!(locked.sandbox_policy, read_only.sandbox_policy);
+        assert_eq!(locked.locked_by, Some(AccessLock::ReadOnly));
+
+        // A mode already within the lock is left alone and not reported.
+        let within = turn_policy(Some("read-only"), "/ws", Some(AccessLock::Current));
+        assert_eq!(within, read_only);
+        assert_eq!(
+            serde_json::to_value(&lowered).unwrap()["lockedBy"],
+            json!("current")
+        );
+    }
+
+    #[test]
+    fn estimate_splits_typed_text_files_and_images() {
+        let workspace = tempdir().expect("tempdir");
+        std::fs::write(workspace.path().join("notes.md"), "a".repeat(4000)).unwrap();
+        std::fs::write(workspace.path().join("shot.png"), [0u8; 1234]).unwrap();
+        let workspace_path = workspace.path().to_string_lossy().to_string();
+        let sources = MessageSources {
+            workspace_id: "ws",
+            workspace_path: &workspace_path,
+            data_dir: workspace.path(),
+            domain: None,
+            domains_path: &workspace.path().join("domains.json"),
+            redactor: None,
+        };
+        let image = workspace
+            .path()
+            .join("shot.png")
+            .to_string_lossy()
+            .to_string();
+        let message = assemble_message(
+            &sources,
+            "Summarize these notes",
+            Some(&["notes.md".to_string()]),
+            false,
+            Some(vec![image]),
+            None,
+        )
+        .expect("assemble");
+        let policy = turn_policy(Some("read-only"), &workspace_path, None);
+        let params = message
+            .turn_start_params("", &workspace_path, &policy, None, None, None, None)
+            .expect("params");
+        assert_eq!(params["sandboxPolicy"]["type"], "readOnly");
+
+        let estimate = message.estimate(&params, Some("gpt-5-codex".to_string()), Some(1000));
+        assert_eq!(estimate.text.bytes, "\nSummarize these notes".len() as u64);
+        assert!(estimate.file_context.bytes > 4000);
+        assert_eq!(estimate.images.bytes, 1234);
+        assert_eq!(estimate.instructions.bytes, 0);
+        assert_eq!(
+            estimate.estimated_tokens,
+            estimate.text.tokens + estimate.file_context.tokens + estimate.images.tokens
+        );
+        assert_eq!(estimate.fits, Some(false));
+        assert_eq!(estimate.files.expect("files").files.len(), 1);
+
+        let unknown = message.estimate(&params, None, None);
+        assert_eq!(unknown.fits, None);
+    }
+}
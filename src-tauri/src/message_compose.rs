@@ -0,0 +1,524 @@
+//! `compose_message`: builds one message body from an ordered list of parts
+//! (free text, custom prompts, file references, diffs and images) under a
+//! single byte budget. The result goes through the same assembly as
+//! `send_user_message` and `estimate_message_size`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::attachments::resolve_image_refs;
+use crate::file_refs::{fence_for, format_file_block, read_file_ref, MAX_FILE_REF_BYTES};
+use crate::git_utils::{collect_commit_diff, collect_staged_diff, collect_unstaged_diff};
+use crate::redaction::Redactor;
+
+/// Budget for the composed text when the call does not set `maxBytes`.
+pub(crate) const DEFAULT_COMPOSE_BUDGET_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum MessagePart {
+    Text {
+        text: String,
+    },
+    /// A custom prompt, by the `path` that `prompts_list` reports.
+    Prompt {
+        path: String,
+        #[serde(default)]
+        args: PromptArgs,
+    },
+    /// A workspace-relative file, attached like an entry of `fileRefs`.
+    File {
+        path: String,
+    },
+    Diff {
+        scope: DiffScope,
+        /// Commit to diff for `commit`: a sha, short sha or `HEAD~1`.
+        #[serde(default)]
+        sha: Option<String>,
+    },
+    /// An attachment id, path or URL, as in `images` on `send_user_message`.
+    Image {
+        image: String,
+    },
+}
+
+impl MessagePart {
+    fn kind(&self) -> &'static str {
+        match self {
+            MessagePart::Text { .. } => "text",
+            MessagePart::Prompt { .. } => "prompt",
+            MessagePart::File { .. } => "file",
+            MessagePart::Diff { .. } => "diff",
+            MessagePart::Image { .. } => "image",
+        }
+    }
+}
+
+/// `key=value` pairs for prompts with `$NAME` placeholders, or a list for
+/// `$1`…`$9` and `$ARGUMENTS`.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum PromptArgs {
+    Named(BTreeMap<String, String>),
+    Positional(Vec<String>),
+}
+
+impl Default for PromptArgs {
+    fn default() -> Self {
+        PromptArgs::Positional(Vec::new())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DiffScope {
+    Staged,
+    Unstaged,
+    Commit,
+}
+
+/// A custom prompt's body, without its frontmatter.
+pub(crate) struct PromptSource {
+    pub(crate) path: String,
+    pub(crate) content: String,
+}
+
+/// Workspace state the parts are resolved against. Owned, so composing can
+/// run on a blocking thread.
+pub(crate) struct ComposeSources {
+    pub(crate) workspace_id: String,
+    pub(crate) workspace_path: PathBuf,
+    pub(crate) data_dir: PathBuf,
+    /// The workspace's git root, or why it has none; only diff parts need it.
+    pub(crate) repo_root: Result<PathBuf, String>,
+    pub(crate) ignore_whitespace: bool,
+    pub(crate) prompts: Vec<PromptSource>,
+    pub(crate) redactor: Option<Redactor>,
+}
+
+/// How much of one part made it into the message.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ComposedPart {
+    #[serde(rename = "type")]
+    pub(crate) kind: &'static str,
+    /// Content bytes in the message, headers and fences not included.
+    pub(crate) bytes: usize,
+    /// Content bytes before truncation.
+    pub(crate) original_bytes: usize,
+    pub(crate) truncated: bool,
+    pub(crate) redactions: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ComposedMessage {
+    pub(crate) text: String,
+    /// Image parts with attachment ids resolved to stored paths.
+    pub(crate) images: Vec<String>,
+    pub(crate) parts: Vec<ComposedPart>,
+    pub(crate) budget_bytes: usize,
+    /// Sum of the parts' `bytes`.
+    pub(crate) used_bytes: usize,
+    pub(crate) truncated: bool,
+}
+
+/// Resolves `parts` in order. Each part takes what it needs from what is
+/// left of `budget_bytes` and is cut when that runs out, so later parts can
+/// be dropped entirely. Images do not count against the budget.
+pub(crate) fn compose_message(
+    sources: &ComposeSources,
+    parts: &[MessagePart],
+    budget_bytes: usize,
+) -> Result<ComposedMessage, String> {
+    if parts.is_empty() {
+        return Err("parts is empty".to_string());
+    }
+    let mut sections = Vec::new();
+    let mut images = Vec::new();
+    let mut reports = Vec::new();
+    let mut used = 0;
+    for (index, part) in parts.iter().enumerate() {
+        let remaining = budget_bytes.saturating_sub(used);
+        let resolved = resolve_part(sources, part, remaining, &mut images)
+            .map_err(|err| format!("part {index} ({}): {err}", part.kind()))?;
+        used += resolved.report.bytes;
+        if let Some(section) = resolved.section {
+            sections.push(section);
+        }
+        reports.push(resolved.report);
+    }
+    let images = resolve_image_refs(&sources.data_dir, &sources.workspace_id, images)?;
+    Ok(ComposedMessage {
+        text: sections.join("\n\n"),
+        images,
+        truncated: reports.iter().any(|report| report.truncated),
+        parts: reports,
+        budget_bytes,
+        used_bytes: used,
+    })
+}
+
+struct ResolvedPart {
+    /// `None` when nothing of the part fit.
+    section: Option<String>,
+    report: ComposedPart,
+}
+
+fn resolve_part(
+    sources: &ComposeSources,
+    part: &MessagePart,
+    remaining: usize,
+    images: &mut Vec<String>,
+) -> Result<ResolvedPart, String> {
+    let content = match part {
+        MessagePart::Text { text } => text.clone(),
+        MessagePart::Prompt { path, args } => {
+            let prompt = sources
+                .prompts
+                .iter()
+                .find(|prompt| &prompt.path == path)
+                .ok_or_else(|| format!("Prompt not found: {path}"))?;
+            render_prompt(&prompt.content, args)?
+        }
+        MessagePart::File { path } => return resolve_file(sources, path, remaining),
+        MessagePart::Diff { scope, sha } => {
+            let repo_root = sources.repo_root.as_ref().map_err(Clone::clone)?;
+            let diff = match (scope, sha.as_deref()) {
+                (DiffScope::Staged, _) => {
+                    collect_staged_diff(repo_root, sources.ignore_whitespace)?
+                }
+                (DiffScope::Unstaged, _) => {
+                    collect_unstaged_diff(repo_root, sources.ignore_whitespace)?
+                }
+                (DiffScope::Commit, Some(sha)) => {
+                    collect_commit_diff(repo_root, sha, sources.ignore_whitespace)?
+                }
+                (DiffScope::Commit, None) => return Err("sha is required".to_string()),
+            };
+            let (diff, redactions) = match sources.redactor.as_ref() {
+                Some(redactor) => redactor.redact_diff(&diff),
+                None => (diff, 0),
+            };
+            let label = match (scope, sha) {
+                (DiffScope::Staged, _) => "staged".to_string(),
+                (DiffScope::Unstaged, _) => "unstaged".to_string(),
+                (DiffScope::Commit, sha) => format!("commit {}", sha.as_deref().unwrap_or("")),
+            };
+            return Ok(diff_section(&label, &diff, remaining, redactions));
+        }
+        MessagePart::Image { image } => {
+            images.push(image.clone());
+            return Ok(ResolvedPart {
+                section: None,
+                report: ComposedPart {
+                    kind: part.kind(),
+                    bytes: 0,
+                    original_bytes: 0,
+                    truncated: false,
+                    redactions: 0,
+                },
+            });
+        }
+    };
+    let shown = truncate_at_char(&content, remaining);
+    let truncated = shown.len() < content.len();
+    let mut section = shown.to_string();
+    if truncated {
+        section.push_str(&truncation_note(shown.len(), content.len()));
+    }
+    Ok(ResolvedPart {
+        section: (!shown.is_empty()).then_some(section),
+        report: ComposedPart {
+            kind: part.kind(),
+            bytes: shown.len(),
+            original_bytes: content.len(),
+            truncated,
+            redactions: 0,
+        },
+    })
+}
+
+/// Reads the file like `fileRefs` does, capped by both `MAX_FILE_REF_BYTES`
+/// and the budget left.
+fn resolve_file(
+    sources: &ComposeSources,
+    path: &str,
+    remaining: usize,
+) -> Result<ResolvedPart, String> {
+    let (content, size) = read_file_ref(
+        &sources.workspace_path,
+        path,
+        remaining.min(MAX_FILE_REF_BYTES),
+    )?;
+    let read = content.len();
+    let truncated = (read as u64) < size;
+    let (content, redactions) = match sources.redactor.as_ref() {
+        Some(redactor) => redactor.redact_file(path, &content),
+        None => (content, 0),
+    };
+    let section = format_file_block(path, &content, read, size, truncated);
+    Ok(ResolvedPart {
+        section: (read > 0 || size == 0).then(|| section.trim_end().to_string()),
+        report: ComposedPart {
+            kind: "file",
+            bytes: read,
+            original_bytes: size as usize,
+            truncated,
+            redactions,
+        },
+    })
+}
+
+fn diff_section(label: &str, diff: &str, remaining: usize, redactions: usize) -> ResolvedPart {
+    let shown = truncate_at_char(diff, remaining);
+    let truncated = shown.len() < diff.len();
+    let section = if diff.trim().is_empty() {
+        Some(format!("Diff: {label} (no changes)"))
+    } else if shown.is_empty() {
+        None
+    } else {
+        let fence = fence_for(shown);
+        let mut section = format!("Diff: {label}\n{fence}diff\n{shown}");
+        if !shown.ends_with('\n') {
+            section.push('\n');
+        }
+        section.push_str(&fence);
+        if truncated {
+            section.push_str(&truncation_note(shown.len(), diff.len()));
+        }
+        Some(section)
+    };
+    ResolvedPart {
+        section,
+        report: ComposedPart {
+            kind: "diff",
+            bytes: shown.len(),
+            original_bytes: diff.len(),
+            truncated,
+            redactions,
+        },
+    }
+}
+
+fn truncation_note(shown: usize, total: usize) -> String {
+    format!("\n(truncated: showing the first {shown} of {total} bytes)")
+}
+
+/// The longest prefix of `text` within `limit` bytes that ends on a
+/// character boundary.
+fn truncate_at_char(text: &str, limit: usize) -> &str {
+    if text.len() <= limit {
+        return text;
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// `$NAME` placeholders in `content`, in order of first use. `$$NAME` is an
+/// escaped dollar sign and `$ARGUMENTS` is positional.
+fn prompt_argument_names(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, name) in named_placeholders(content) {
+        if name != "ARGUMENTS" && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Byte offset of each unescaped `$NAME` and its name.
+fn named_placeholders(content: &str) -> Vec<(usize, &str)> {
+    let bytes = content.as_bytes();
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] != b'$' || !bytes.get(index + 1).is_some_and(u8::is_ascii_uppercase) {
+            index += 1;
+            continue;
+        }
+        let start = index + 1;
+        let mut end = start + 1;
+        while end < bytes.len()
+            && (bytes[end].is_ascii_uppercase()
+                || bytes[end].is_ascii_digit()
+                || bytes[end] == b'_')
+        {
+            end += 1;
+        }
+        if index == 0 || bytes[index - 1] != b'$' {
+            found.push((index, &content[start..end]));
+        }
+        index = end;
+    }
+    found
+}
+
+/// Fills a custom prompt's placeholders the way the composer's
+/// `/prompts:name` expansion does.
+pub(crate) fn render_prompt(content: &str, args: &PromptArgs) -> Result<String, String> {
+    let names = prompt_argument_names(content);
+    if names.is_empty() {
+        return match args {
+            PromptArgs::Positional(args) => Ok(expand_numeric_placeholders(content, args)),
+            PromptArgs::Named(values) if values.is_empty() => {
+                Ok(expand_numeric_placeholders(content, &[]))
+            }
+            PromptArgs::Named(_) => Err("This prompt takes positional args".to_string()),
+        };
+    }
+    let empty = BTreeMap::new();
+    let values = match args {
+        PromptArgs::Named(values) => values,
+        PromptArgs::Positional(args) if args.is_empty() => &empty,
+        PromptArgs::Positional(_) => {
+            return Err(format!(
+                "This prompt takes named args: {}",
+                names.join(", ")
+            ))
+        }
+    };
+    let missing: Vec<&str> = names
+        .iter()
+        .filter(|name| !values.contains_key(name.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing required args: {}", missing.join(", ")));
+    }
+    let mut output = String::with_capacity(content.len());
+    let mut copied = 0;
+    for (offset, name) in named_placeholders(content) {
+        if let Some(value) = values.get(name) {
+            output.push_str(&content[copied..offset]);
+            output.push_str(value);
+            copied = offset + 1 + name.len();
+        }
+    }
+    output.push_str(&content[copied..]);
+    Ok(output)
+}
+
+/// `$1`…`$9` become the matching argument (or nothing), `$ARGUMENTS` all of
+/// them joined by spaces, and `$$` stays as written.
+fn expand_numeric_placeholders(content: &str, args: &[String]) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(next) = rest.find('$') {
+        output.push_str(&rest[..next]);
+        let after = &rest[next + 1..];
+        if after.starts_with('$') {
+            output.push_str("$$");
+            rest = &after[1..];
+        } else if let Some(digit) = after.chars().next().and_then(|ch| ch.to_digit(10)) {
+            if digit > 0 {
+                if let Some(arg) = args.get(digit as usize - 1) {
+                    output.push_str(arg);
+                }
+                rest = &after[1..];
+            } else {
+                output.push('$');
+                rest = after;
+            }
+        } else if let Some(tail) = after.strip_prefix("ARGUMENTS") {
+            output.push_str(&args.join(" "));
+            rest = tail;
+        } else {
+            output.push('$');
+            rest = after;
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compose_message, render_prompt, ComposeSources, MessagePart, PromptArgs, PromptSource,
+    };
+    use serde_json::json;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+
+    #[test]
+    fn renders_named_and_positional_prompts() {
+        let named = "Fix $FILE using $$HOME and $FILE again";
+        let values = BTreeMap::from([("FILE".to_string(), "main.rs".to_string())]);
+        assert_eq!(
+            render_prompt(named, &PromptArgs::Named(values)).unwrap(),
+            "Fix main.rs using $$HOME and main.rs again"
+        );
+        assert_eq!(
+            render_prompt(named, &PromptArgs::default()).unwrap_err(),
+            "Missing required args: FILE"
+        );
+
+        let positional = "Explain $1 then $ARGUMENTS, $9 costs $$5";
+        let args = PromptArgs::Positional(vec!["a".to_string(), "b c".to_string()]);
+        assert_eq!(
+            render_prompt(positional, &args).unwrap(),
+            "Explain a then a b c,  costs $$5"
+        );
+        let parsed: PromptArgs = serde_json::from_value(json!({ "FILE": "x" })).unwrap();
+        assert!(render_prompt(positional, &parsed).is_err());
+    }
+
+    #[test]
+    fn parts_share_one_budget_and_report_truncation() {
+        let workspace = tempdir().expect("tempdir");
+        std::fs::write(workspace.path().join("test.rs"), "x".repeat(40)).unwrap();
+        let sources = ComposeSources {
+            workspace_id: "ws".to_string(),
+            workspace_path: workspace.path().to_path_buf(),
+            data_dir: workspace.path().to_path_buf(),
+            repo_root: Err("not a git repository".to_string()),
+            ignore_whitespace: false,
+            prompts: vec![PromptSource {
+                path: "/prompts/review.md".to_string(),
+                content: "Review $1".to_string(),
+            }],
+            redactor: None,
+        };
+        let parts: Vec<MessagePart> = serde_json::from_value(json!([
+            { "type": "prompt", "path": "/prompts/review.md", "args": ["carefully"] },
+            { "type": "file", "path": "test.rs" },
+            { "type": "image", "image": "https://example.com/shot.png" },
+            { "type": "text", "text": "dropped" }
+        ]))
+        .unwrap();
+
+        let composed = compose_message(&sources, &parts, 30).expect("compose");
+        let sizes: Vec<(usize, usize, bool)> = composed
+            .parts
+            .iter()
+            .map(|part| (part.bytes, part.original_bytes, part.truncated))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![(16, 16, false), (14, 40, true), (0, 0, false), (0, 7, true)]
+        );
+        assert_eq!(composed.used_bytes, 30);
+        assert!(composed.truncated);
+        assert!(composed
+            .text
+            .starts_with("Review carefully\n\nFile: test.rs\n"));
+        assert!(composed
+            .text
+            .contains("(truncated: showing the first 14 of 40 bytes)"));
+        assert!(!composed.text.contains("dropped"));
+        assert_eq!(composed.images, vec!["https://example.com/shot.png"]);
+
+        let diff: Vec<MessagePart> =
+            serde_json::from_value(json!([{ "type": "diff", "scope": "staged" }])).unwrap();
+        assert_eq!(
+            compose_message(&sources, &diff, 30).unwrap_err(),
+            "part 0 (diff): not a git repository"
+        );
+    }
+}
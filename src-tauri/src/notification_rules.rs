@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{NotificationAction, NotificationRule};
+
+pub(crate) const EVENT_TURN_COMPLETED: &str = "turnCompleted";
+pub(crate) const EVENT_APPROVAL_REQUESTED: &str = "approvalRequested";
+pub(crate) const EVENT_ERROR: &str = "error";
+
+/// Values available to `{workspace}`, `{thread}` and `{event}` placeholders.
+pub(crate) struct NotificationContext {
+    pub(crate) workspace_id: String,
+    pub(crate) workspace_name: String,
+    pub(crate) workspace_color: Option<String>,
+    pub(crate) workspace_icon: Option<String>,
+    pub(crate) thread_title: String,
+    pub(crate) event: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct FiredNotification {
+    #[serde(rename = "ruleId")]
+    pub(crate) rule_id: String,
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    /// Lets the frontend badge the notification without a workspace lookup.
+    #[serde(
+        default,
+        rename = "workspaceColor",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) workspace_color: Option<String>,
+    #[serde(
+        default,
+        rename = "workspaceIcon",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) workspace_icon: Option<String>,
+    pub(crate) event: String,
+    pub(crate) title: Option<String>,
+    pub(crate) body: Option<String>,
+    #[serde(rename = "soundId")]
+    pub(crate) sound_id: Option<String>,
+}
+
+/// Maps an app-server message to the rule event it triggers, if any.
+pub(crate) fn notification_event_kind(message: &Value) -> Option<&'static str> {
+    let method = message.get("method").and_then(|value| value.as_str())?;
+    if method == "turn/completed" {
+        return Some(EVENT_TURN_COMPLETED);
+    }
+    if method.ends_with("requestApproval") {
+        return Some(EVENT_APPROVAL_REQUESTED);
+    }
+    if method == "error" {
+        return Some(EVENT_ERROR);
+    }
+    None
+}
+
+/// Best-effort thread label: the thread name or preview when the event carries
+/// one, otherwise the thread id.
+pub(crate) fn thread_title_from_message(message: &Value) -> String {
+    let params = message.get("params");
+    let thread = params.and_then(|params| params.get("thread"));
+    thread
+        .and_then(|thread| thread.get("name").or_else(|| thread.get("preview")))
+        .and_then(|value| value.as_str())
+        .filter(|value| !value.trim().is_empty())
+        .or_else(|| {
+            params
+                .and_then(|params| params.get("threadId").or_else(|| params.get("thread_id")))
+                .and_then(|value| value.as_str())
+        })
+        .unwrap_or("")
+        .to_string()
+}
+
+pub(crate) fn render_template(template: &str, context: &NotificationContext) -> String {
+    template
+        .replace("{workspace}", &context.workspace_name)
+        .replace("{thread}", &context.thread_title)
+        .replace("{event}", &context.event)
+}
+
+pub(crate) fn fire_rule(
+    rule: &NotificationRule,
+    context: &NotificationContext,
+) -> FiredNotification {
+    let (title, body, sound_id) = match &rule.action {
+        NotificationAction::SystemNotification {
+            title,
+            body,
+            sound_id,
+        } => (
+            Some(render_template(title, context)),
+            body.as_deref().map(|body| render_template(body, context)),
+            sound_id.clone(),
+        ),
+        NotificationAction::Sound { sound_id } => (None, None, Some(sound_id.clone())),
+        NotificationAction::None => (None, None, None),
+    };
+    FiredNotification {
+        rule_id: rule.id.clone(),
+        workspace_id: context.workspace_id.clone(),
+        workspace_color: context.workspace_color.clone(),
+        workspace_icon: context.workspace_icon.clone(),
+        event: context.event.clone(),
+        title,
+        body,
+        sound_id,
+    }
+}
+
+/// Evaluates every enabled rule matching the event and workspace.
+pub(crate) fn evaluate_rules(
+    rules: &[NotificationRule],
+    context: &NotificationContext,
+) -> Vec<FiredNotification> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled && rule.event == context.event)
+        .filter(|rule| {
+            rule.workspace_id
+                .as_deref()
+                .is_none_or(|workspace_id| workspace_id == context.workspace_id)
+        })
+        .filter(|rule| !matches!(rule.action, NotificationAction::None))
+        .map(|rule| fire_rule(rule, context))
+        .collect()
+}
+
+/// Renders a rule against sample values so `notification_rules_test` can
+/// preview it, even when the rule is disabled.
+pub(crate) fn preview_rule(
+    rules: &[NotificationRule],
+    rule_id: &str,
+) -> Result<FiredNotification, String> {
+    let rule = rules
+        .iter()
+        .find(|rule| rule.id == rule_id)
+        .ok_or_else(|| format!("notification rule not found: {rule_id}"))?;
+    let context = NotificationContext {
+        workspace_id: rule.workspace_id.clone().unwrap_or_default(),
+        workspace_name: "Sample workspace".to_string(),
+        workspace_color: None,
+        workspace_icon: None,
+        thread_title: "Sample thread".to_string(),
+        event: rule.event.clone(),
+    };
+    Ok(fire_rule(rule, &context))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        evaluate_rules, notification_event_kind, preview_rule, thread_title_from_message,
+        NotificationContext, EVENT_APPROVAL_REQUESTED, EVENT_TURN_COMPLETED,
+    };
+    use crate::types::{NotificationAction, NotificationRule};
+    use serde_json::json;
+
+    fn context(workspace_id: &str, event: &str) -> NotificationContext {
+        NotificationContext {
+            workspace_id: workspace_id.to_string(),
+            workspace_name: "Repo".to_string(),
+            workspace_color: Some("#3366cc".to_string()),
+            workspace_icon: None,
+            thread_title: "Fix bug".to_string(),
+            event: event.to_string(),
+        }
+    }
+
+    #[test]
+    fn maps_app_server_methods_to_events() {
+        assert_eq!(
+            notification_event_kind(&json!({ "method": "turn/completed" })),
+            Some(EVENT_TURN_COMPLETED)
+        );
+        assert_eq!(
+            notification_event_kind(&json!({ "method": "item/commandExecution/requestApproval" })),
+            Some(EVENT_APPROVAL_REQUESTED)
+        );
+        assert_eq!(
+            notification_event_kind(&json!({ "method": "item/started" })),
+            None
+        );
+    }
+
+    #[test]
+    fn thread_title_prefers_name_then_id() {
+        let named = json!({ "params": { "thread": { "name": "Refactor" }, "threadId": "t1" } });
+        assert_eq!(thread_title_from_message(&named), "Refactor");
+        let bare = json!({ "params": { "threadId": "t1" } });
+        assert_eq!(thread_title_from_message(&bare), "t1");
+    }
+
+    #[test]
+    fn evaluates_matching_rules_with_templates() {
+        let rules: Vec<NotificationRule> = serde_json::from_value(json!([
+            {
+                "id": "done",
+                "event": "turnCompleted",
+                "action": { "type": "systemNotification", "title": "{workspace} finished", "body": "{thread}" }
+            },
+            {
+                "id": "other-workspace",
+                "event": "turnCompleted",
+                "workspaceId": "ws-2",
+                "action": { "type": "sound", "soundId": "success" }
+            },
+            {
+                "id": "approval",
+                "event": "approvalRequested",
+                "action": { "type": "sound", "soundId": "error" }
+            },
+            {
+                "id": "disabled",
+                "event": "turnCompleted",
+                "enabled": false,
+                "action": { "type": "sound", "soundId": "success" }
+            }
+        ]))
+        .expect("rules deserialize");
+
+        let fired = evaluate_rules(&rules, &context("ws-1", EVENT_TURN_COMPLETED));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule_id, "done");
+        assert_eq!(fired[0].title.as_deref(), Some("Repo finished"));
+        assert_eq!(fired[0].body.as_deref(), Some("Fix bug"));
+        assert_eq!(fired[0].workspace_color.as_deref(), Some("#3366cc"));
+
+        let fired = evaluate_rules(&rules, &context("ws-2", EVENT_TURN_COMPLETED));
+        assert_eq!(fired.len(), 2);
+    }
+
+    #[test]
+    fn none_action_never_fires() {
+        let rules = vec![NotificationRule {
+            id: "mute".to_string(),
+            event: EVENT_TURN_COMPLETED.to_string(),
+            workspace_id: None,
+            enabled: true,
+            action: NotificationAction::None,
+        }];
+        assert!(evaluate_rules(&rules, &context("ws-1", EVENT_TURN_COMPLETED)).is_empty());
+    }
+
+    #[test]
+    fn preview_renders_sample_values() {
+        let rules: Vec<NotificationRule> = serde_json::from_value(json!([{
+            "id": "done",
+            "event": "turnCompleted",
+            "enabled": false,
+            "action": { "type": "systemNotification", "title": "{workspace}: {event}" }
+        }]))
+        .expect("rules deserialize");
+        let fired = preview_rule(&rules, "done").expect("rule exists");
+        assert_eq!(
+            fired.title.as_deref(),
+            Some("Sample workspace: turnCompleted")
+        );
+        assert!(preview_rule(&rules, "missing").is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! Daily nutrition goals and how logged days measure up to them. Goals come
+//! from the `calories`, `protein`, `carbs` and `fat` frontmatter of
+//! `Entities/Food/Goals.md` in the first vault that has the note, otherwise
+//! from the workspace's `nutritionGoals` setting. A goal source that cannot be
+//! read is ignored rather than guessed at: the dashboards run without goals
+//! and say why in `diagnostics`. Both the nutrition dashboard and the
+//! food/exercise trends report through here, so they agree.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::frontmatter::FrontmatterNote;
+use crate::obsidian::cards::TrendBuilder;
+use crate::types::{CardFormat, NutritionGoals};
+
+const GOALS_NOTE: [&str; 3] = ["Entities", "Food", "Goals.md"];
+/// A logged day is on target within this fraction of the calorie goal.
+const CALORIE_TOLERANCE: f64 = 0.10;
+
+/// What was eaten over some span, usually one day.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct MacroTotals {
+    pub(crate) calories: f64,
+    pub(crate) protein: f64,
+    pub(crate) carbs: f64,
+    pub(crate) fat: f64,
+}
+
+impl MacroTotals {
+    pub(crate) fn add(&mut self, calories: f64, protein: f64, carbs: f64, fat: f64) {
+        self.calories += calories;
+        self.protein += protein;
+        self.carbs += carbs;
+        self.fat += fat;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NutritionAdherence {
+    /// Days in the range with at least one food logged.
+    pub(crate) logged_days: u32,
+    /// Logged days within ±10% of the calorie goal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) calorie_goal_days: Option<u32>,
+    /// Protein per logged day.
+    pub(crate) avg_protein: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) protein_goal_percent: Option<f64>,
+}
+
+/// Goal minus what today's entries add up to; negative once over the goal.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NutritionRemaining {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) calories: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) protein: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) carbs: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) fat: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NutritionGoalsReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) goals: Option<NutritionGoals>,
+    /// The goals note's path, or `settings`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<String>,
+    /// Only for the 7- and 30-day ranges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) adherence: Option<NutritionAdherence>,
+    /// Only when the range includes today.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) remaining_today: Option<NutritionRemaining>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) diagnostics: Vec<String>,
+}
+
+/// The goals in effect for a workspace, plus why none are when a source
+/// could not be used.
+pub(crate) fn resolve_nutrition_goals(
+    vault_roots: &[&Path],
+    settings_goals: Option<&NutritionGoals>,
+) -> (Option<NutritionGoals>, Option<String>, Vec<String>) {
+    for root in vault_roots {
+        let path = GOALS_NOTE
+            .iter()
+            .fold(root.to_path_buf(), |path, part| path.join(part));
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let source = path.display().to_string();
+        return match parse_goals_note(&content) {
+            Ok(goals) => (Some(goals), Some(source), Vec::new()),
+            Err(err) => (
+                None,
+                None,
+                vec![format!("Ignoring nutrition goals in {source}: {err}")],
+            ),
+        };
+    }
+    match settings_goals {
+        None => (None, None, Vec::new()),
+        Some(goals) => match validate_goals(goals) {
+            Ok(()) => (
+                Some(goals.clone()),
+                Some("settings".to_string()),
+                Vec::new(),
+            ),
+            Err(err) => (
+                None,
+                None,
+                vec![format!("Ignoring the workspace's nutritionGoals: {err}")],
+            ),
+        },
+    }
+}
+
+fn parse_goals_note(content: &str) -> Result<NutritionGoals, String> {
+    let note = FrontmatterNote::parse(content)?.ok_or("the note has no frontmatter")?;
+    let mapping = note.mapping();
+    let read = |key: &str| -> Result<Option<f64>, String> {
+        match mapping.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::Number(number)) => Ok(number.as_f64()),
+            // `140g` and `2,000 kcal` read as written.
+            Some(Value::String(text)) => text
+                .trim()
+                .trim_end_matches("kcal")
+                .trim_end_matches('g')
+                .trim()
+                .replace(',', "")
+                .parse::<f64>()
+                .map(Some)
+                .map_err(|_| format!("{key} is not a number: {text:?}")),
+            Some(_) => Err(format!("{key} is not a number")),
+        }
+    };
+    let goals = NutritionGoals {
+        calories: read("calories")?,
+        protein: read("protein")?,
+        carbs: read("carbs")?,
+        fat: read("fat")?,
+    };
+    validate_goals(&goals)?;
+    Ok(goals)
+}
+
+fn validate_goals(goals: &NutritionGoals) -> Result<(), String> {
+    let fields = [
+        ("calories", goals.calories),
+        ("protein", goals.protein),
+        ("carbs", goals.carbs),
+        ("fat", goals.fat),
+    ];
+    if fields.iter().all(|(_, value)| value.is_none()) {
+        return Err("no calories, protein, carbs or fat goal is set".to_string());
+    }
+    for (key, value) in fields {
+        if value.is_some_and(|value| !value.is_finite() || value <= 0.0) {
+            return Err(format!("{key} must be a positive number"));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a range gets adherence stats; the dashboard's `week`/`month` are
+/// the same windows as the trends' `7d`/`30d`.
+fn has_adherence(range: &str) -> bool {
+    matches!(range, "7d" | "30d" | "week" | "month")
+}
+
+/// Measures `daily` (only the range's days) against the goals.
+pub(crate) fn goals_report(
+    goals: Option<NutritionGoals>,
+    source: Option<String>,
+    diagnostics: Vec<String>,
+    range: &str,
+    daily: &BTreeMap<NaiveDate, MacroTotals>,
+    today: Option<NaiveDate>,
+) -> NutritionGoalsReport {
+    let Some(goals) = goals else {
+        return NutritionGoalsReport {
+            diagnostics,
+            ..NutritionGoalsReport::default()
+        };
+    };
+    let adherence = has_adherence(range).then(|| {
+        let logged: Vec<&MacroTotals> = daily.values().filter(|day| day.calories > 0.0).collect();
+        let logged_days = logged.len() as u32;
+        let avg_protein = if logged.is_empty() {
+            0.0
+        } else {
+            logged.iter().map(|day| day.protein).sum::<f64>() / logged.len() as f64
+        };
+        NutritionAdherence {
+            logged_days,
+            calorie_goal_days: goals.calories.map(|goal| {
+                logged
+                    .iter()
+                    .filter(|day| (day.calories - goal).abs() <= goal * CALORIE_TOLERANCE)
+                    .count() as u32
+            }),
+            avg_protein,
+            protein_goal_percent: goals.protein.map(|goal| avg_protein / goal * 100.0),
+        }
+    });
+    let remaining_today = today.map(|today| {
+        let eaten = daily.get(&today).copied().unwrap_or_default();
+        NutritionRemaining {
+            calories: goals.calories.map(|goal| goal - eaten.calories),
+            protein: goals.protein.map(|goal| goal - eaten.protein),
+            carbs: goals.carbs.map(|goal| goal - eaten.carbs),
+            fat: goals.fat.map(|goal| goal - eaten.fat),
+        }
+    });
+    NutritionGoalsReport {
+        goals: Some(goals),
+        source,
+        adherence,
+        remaining_today,
+        diagnostics,
+    }
+}
+
+/// Adds the food layout's goal cards: adherence first, then what is left
+/// today.
+pub(crate) fn goal_cards(report: &NutritionGoalsReport, trends: &mut TrendBuilder) {
+    let Some(goals) = report.goals.as_ref() else {
+        return;
+    };
+    if let Some(adherence) = &report.adherence {
+        if let (Some(days), Some(goal)) = (adherence.calorie_goal_days, goals.calories) {
+            let card = trends.card(
+                "calorie_goal_days",
+                "Days on Calorie Goal",
+                format!("{days}"),
+                days as f64,
+                CardFormat::Count,
+            );
+            card.sub_label = Some(format!(
+                "of {} logged • ±10% of {goal:.0}",
+                adherence.logged_days
+            ));
+        }
+        if let (Some(percent), Some(goal)) = (adherence.protein_goal_percent, goals.protein) {
+            let card = trends.card(
+                "protein_goal",
+                "Protein vs Goal",
+                format!("{percent:.0}%"),
+                percent,
+                CardFormat::Percent,
+            );
+            card.sub_label = Some(format!("{:.0}g/day of {goal:.0}g", adherence.avg_protein));
+        }
+    }
+    if let Some(remaining) = &report.remaining_today {
+        for (id, label, left, goal, unit, format) in [
+            (
+                "calories_left",
+                "Calories Left Today",
+                remaining.calories,
+                goals.calories,
+                "",
+                CardFormat::Number,
+            ),
+            (
+                "protein_left",
+                "Protein Left Today",
+                remaining.protein,
+                goals.protein,
+                "g",
+                CardFormat::Grams,
+            ),
+            (
+                "carbs_left",
+                "Carbs Left Today",
+                remaining.carbs,
+                goals.carbs,
+                "g",
+                CardFormat::Grams,
+            ),
+            (
+                "fat_left",
+                "Fat Left Today",
+                remaining.fat,
+                goals.fat,
+                "g",
+                CardFormat::Grams,
+            ),
+        ] {
+            if let (Some(left), Some(goal)) = (left, goal) {
+                let card = trends.card(id, label, format!("{left:.0}{unit}"), left, format);
+                card.sub_label = Some(format!("of {goal:.0}{unit}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{goal_cards, goals_report, resolve_nutrition_goals, MacroTotals};
+    use crate::obsidian::cards::{TrendBuilder, FOOD_LAYOUT};
+    use crate::types::NutritionGoals;
+    use chrono::NaiveDate;
+    use std::collections::BTreeMap;
+    use tempfile::tempdir;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    #[test]
+    fn vault_goals_win_and_bad_ones_fall_back_to_no_goals() {
+        let dir = tempdir().expect("tempdir");
+        let settings = NutritionGoals {
+            protein: Some(100.0),
+            ..NutritionGoals::default()
+        };
+        let (goals, source, _) = resolve_nutrition_goals(&[dir.path()], Some(&settings));
+        assert_eq!(goals, Some(settings.clone()));
+        assert_eq!(source.as_deref(), Some("settings"));
+
+        let food = dir.path().join("Entities").join("Food");
+        std::fs::create_dir_all(&food).unwrap();
+        std::fs::write(
+            food.join("Goals.md"),
+            "---\ncalories: \"2,000 kcal\"\nprotein: 140g\ntags: [goals]\n---\n",
+        )
+        .unwrap();
+        let (goals, _, diagnostics) = resolve_nutrition_goals(&[dir.path()], Some(&settings));
+        let goals = goals.expect("vault goals");
+        assert_eq!(goals.calories, Some(2000.0));
+        assert_eq!(goals.protein, Some(140.0));
+        assert!(diagnostics.is_empty());
+
+        std::fs::write(food.join("Goals.md"), "---\nprotein: lots\n---\n").unwrap();
+        let (goals, _, diagnostics) = resolve_nutrition_goals(&[dir.path()], Some(&settings));
+        assert!(goals.is_none());
+        assert!(diagnostics[0].contains("protein is not a number"));
+    }
+
+    #[test]
+    fn reports_adherence_and_what_is_left_today() {
+        let goals = NutritionGoals {
+            calories: Some(2000.0),
+            protein: Some(150.0),
+            ..NutritionGoals::default()
+        };
+        let mut daily = BTreeMap::new();
+        for (day, calories, protein) in [(1, 1900.0, 120.0), (2, 2500.0, 150.0), (3, 1200.0, 60.0)]
+        {
+            let mut totals = MacroTotals::default();
+            totals.add(calories, protein, 0.0, 0.0);
+            daily.insert(date(day), totals);
+        }
+        let report = goals_report(Some(goals), None, Vec::new(), "7d", &daily, Some(date(3)));
+        let adherence = report.adherence.as_ref().expect("adherence");
+        assert_eq!(adherence.logged_days, 3);
+        assert_eq!(adherence.calorie_goal_days, Some(1));
+        assert_eq!(adherence.avg_protein, 110.0);
+        let remaining = report.remaining_today.as_ref().expect("remaining");
+        assert_eq!(remaining.calories, Some(800.0));
+        assert_eq!(remaining.protein, Some(90.0));
+        assert_eq!(remaining.fat, None);
+
+        let mut trends = TrendBuilder::new(&FOOD_LAYOUT);
+        goal_cards(&report, &mut trends);
+        let ids: Vec<String> = trends.finish().0.into_iter().map(|card| card.id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "calorie_goal_days",
+                "protein_goal",
+                "calories_left",
+                "protein_left"
+            ]
+        );
+
+        let lifetime = goals_report(report.goals.clone(), None, Vec::new(), "all", &daily, None);
+        assert!(lifetime.adherence.is_none());
+        assert!(lifetime.remaining_today.is_none());
+    }
+}
@@ -0,0 +1,246 @@
+//! Card and list construction for trend snapshots. Each domain declares its
+//! cards and lists up front; a builder may leave any of them out, but cannot
+//! add one the layout does not name or use an id twice, so every card keeps
+//! its `order` from snapshot to snapshot.
+
+use crate::types::{CardFormat, TrendCard, TrendList, TrendListItem};
+
+pub(crate) struct TrendLayout {
+    cards: &'static [&'static str],
+    lists: &'static [&'static str],
+}
+
+pub(crate) static DELIVERY_LAYOUT: TrendLayout = TrendLayout {
+    cards: &[
+        "earnings",
+        "hours",
+        "sessions",
+        "hourly",
+        "per_mile",
+        "orders",
+        "avg_order",
+        "bills_due",
+        "tips",
+    ],
+    lists: &["sessions", "bills", "platforms"],
+};
+
+pub(crate) static FOOD_LAYOUT: TrendLayout = TrendLayout {
+    cards: &[
+        "calories",
+        "calories_avg",
+        "protein",
+        "protein_avg",
+        "meals",
+        "workouts",
+        "workout_minutes",
+        "workout_minutes_avg",
+        "workout_distance",
+        "calories_burned",
+        "calorie_goal_days",
+        "protein_goal",
+        "calories_left",
+        "protein_left",
+        "carbs_left",
+        "fat_left",
+    ],
+    lists: &["macros", "top_foods", "workout_types", "activity_minutes"],
+};
+
+pub(crate) static FINANCE_LAYOUT: TrendLayout = TrendLayout {
+    cards: &["monthly_obligations", "due_7d", "largest_upcoming"],
+    lists: &["upcoming_bills"],
+};
+
+pub(crate) static MEDIA_LAYOUT: TrendLayout = TrendLayout {
+    cards: &["completed", "avg_rating", "rated", "backlog"],
+    lists: &["recent_media", "top_rated"],
+};
+
+pub(crate) static YOUTUBE_LAYOUT: TrendLayout = TrendLayout {
+    cards: &["created", "total", "ready", "published"],
+    lists: &["stages", "tiers", "newest"],
+};
+
+pub(crate) static VAULT_LAYOUT: TrendLayout = TrendLayout {
+    cards: &[
+        "notes",
+        "created",
+        "modified",
+        "tags",
+        "orphans",
+        "broken_links",
+    ],
+    lists: &["top_tags", "most_linked", "broken_links"],
+};
+
+pub(crate) struct TrendBuilder {
+    layout: &'static TrendLayout,
+    cards: Vec<TrendCard>,
+    lists: Vec<TrendList>,
+}
+
+impl TrendBuilder {
+    pub(crate) fn new(layout: &'static TrendLayout) -> Self {
+        Self {
+            layout,
+            cards: Vec::new(),
+            lists: Vec::new(),
+        }
+    }
+
+    /// Adds a card; `value` is what the card displays and `value_num` the
+    /// same amount in the unit `format` names.
+    pub(crate) fn card(
+        &mut self,
+        id: &str,
+        label: &str,
+        value: String,
+        value_num: f64,
+        format: CardFormat,
+    ) -> &mut TrendCard {
+        let order = slot(
+            self.layout.cards,
+            id,
+            self.cards.iter().map(|card| card.id.as_str()),
+        );
+        self.cards.push(TrendCard {
+            id: id.to_string(),
+            label: label.to_string(),
+            value,
+            sub_label: None,
+            delta: None,
+            order: Some(order),
+            format: Some(format),
+            value_num: value_num.is_finite().then_some(value_num),
+        });
+        self.cards.last_mut().expect("card was just pushed")
+    }
+
+    pub(crate) fn list(&mut self, id: &str, title: &str, items: Vec<TrendListItem>) {
+        let order = slot(
+            self.layout.lists,
+            id,
+            self.lists.iter().map(|list| list.id.as_str()),
+        );
+        self.lists.push(TrendList {
+            id: id.to_string(),
+            title: title.to_string(),
+            items,
+            order: Some(order),
+        });
+    }
+
+    /// Cards and lists in layout order.
+    pub(crate) fn finish(mut self) -> (Vec<TrendCard>, Vec<TrendList>) {
+        self.cards.sort_by_key(|card| card.order);
+        self.lists.sort_by_key(|list| list.order);
+        (self.cards, self.lists)
+    }
+}
+
+/// Ids missing from the layout or used twice are bugs in a builder; they fail
+/// tests and debug builds, and sort last otherwise.
+fn slot<'a>(layout: &[&str], id: &str, mut taken: impl Iterator<Item = &'a str>) -> u32 {
+    debug_assert!(
+        !taken.any(|existing| existing == id),
+        "duplicate trend id {id:?}"
+    );
+    let position = layout.iter().position(|known| *known == id);
+    debug_assert!(position.is_some(), "trend id {id:?} is not in the layout");
+    position.unwrap_or(layout.len()) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{build_snapshot, periods::TrendPeriod, TREND_DOMAINS};
+    use super::{
+        TrendBuilder, DELIVERY_LAYOUT, FINANCE_LAYOUT, FOOD_LAYOUT, MEDIA_LAYOUT, VAULT_LAYOUT,
+        YOUTUBE_LAYOUT,
+    };
+    use crate::types::{CardFormat, DateOrder};
+    use crate::vault_roots::VaultRoot;
+    use chrono::NaiveDate;
+    use std::collections::HashSet;
+    use tempfile::tempdir;
+
+    #[test]
+    fn layouts_have_unique_ids_and_builders_sort_by_layout() {
+        for layout in [
+            &DELIVERY_LAYOUT,
+            &FINANCE_LAYOUT,
+            &FOOD_LAYOUT,
+            &MEDIA_LAYOUT,
+            &YOUTUBE_LAYOUT,
+            &VAULT_LAYOUT,
+        ] {
+            let cards: HashSet<_> = layout.cards.iter().collect();
+            let lists: HashSet<_> = layout.lists.iter().collect();
+            assert_eq!(cards.len(), layout.cards.len());
+            assert_eq!(lists.len(), layout.lists.len());
+        }
+
+        let mut builder = TrendBuilder::new(&MEDIA_LAYOUT);
+        builder.card(
+            "backlog",
+            "Backlog",
+            "3".to_string(),
+            3.0,
+            CardFormat::Count,
+        );
+        builder.card(
+            "completed",
+            "Completed",
+            "1".to_string(),
+            1.0,
+            CardFormat::Count,
+        );
+        builder.list("top_rated", "Top Rated", Vec::new());
+        builder.list("recent_media", "Recent Completions", Vec::new());
+        let (cards, lists) = builder.finish();
+        let cards: Vec<_> = cards
+            .iter()
+            .map(|card| (card.id.as_str(), card.order))
+            .collect();
+        assert_eq!(cards, vec![("completed", Some(0)), ("backlog", Some(3))]);
+        assert_eq!(lists[0].id, "recent_media");
+    }
+
+    #[test]
+    fn every_domain_builds_in_layout_order() {
+        let dir = tempdir().expect("tempdir");
+        let vaults = [VaultRoot {
+            name: "vault".to_string(),
+            path: dir.path().to_path_buf(),
+        }];
+        let period = TrendPeriod::for_range("7d", NaiveDate::from_ymd_opt(2026, 3, 9).unwrap());
+        for domain in TREND_DOMAINS {
+            let snapshot =
+                build_snapshot(domain, "7d", period, &vaults, DateOrder::default(), None);
+            assert!(!snapshot.cards.is_empty(), "{domain}");
+            let orders: Vec<_> = snapshot.cards.iter().map(|card| card.order).collect();
+            assert!(orders.windows(2).all(|pair| pair[0] < pair[1]), "{domain}");
+            assert!(snapshot
+                .cards
+                .iter()
+                .all(|card| card.format.is_some() && card.value_num.is_some()));
+            let orders: Vec<_> = snapshot.lists.iter().map(|list| list.order).collect();
+            assert!(orders.windows(2).all(|pair| pair[0] < pair[1]), "{domain}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate trend id")]
+    fn duplicate_ids_fail() {
+        let mut builder = TrendBuilder::new(&YOUTUBE_LAYOUT);
+        builder.card("ready", "Ready", "1".to_string(), 1.0, CardFormat::Count);
+        builder.card("ready", "Ready", "2".to_string(), 2.0, CardFormat::Count);
+    }
+
+    #[test]
+    #[should_panic(expected = "not in the layout")]
+    fn unknown_ids_fail() {
+        let mut builder = TrendBuilder::new(&YOUTUBE_LAYOUT);
+        builder.list("ideas", "Ideas", Vec::new());
+    }
+}
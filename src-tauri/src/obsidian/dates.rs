@@ -0,0 +1,140 @@
+//! Frontmatter dates as Obsidian and its plugins write them. Formats are tried
+//! in a fixed order so a value never parses differently from one run to the
+//! next; only slash dates depend on the workspace's `dateOrder`.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+use crate::types::DateOrder;
+
+/// Count key on a vault's contribution for dates that could not be parsed.
+pub(crate) const INVALID_DATES_KEY: &str = "invalidDates";
+
+const DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+const MONTH_NAME_FORMATS: &[&str] = &["%b %d, %Y", "%B %d, %Y", "%d %b %Y", "%d %B %Y"];
+
+/// Parses a frontmatter date, trying in order: `YYYY-MM-DD`, RFC 3339, local
+/// date-times (`2024-06-03T14:30`), slash dates (`06/03/2024`, read per
+/// `order`), month names (`Jun 3, 2024`, `3 June 2024`) and bare `YYYY-MM`
+/// (first of the month). Wiki links such as `[[Daily/2024-06-03|Monday]]` are
+/// reduced to the note name first.
+pub(crate) fn parse_vault_date(value: &str, order: DateOrder) -> Option<NaiveDate> {
+    let value = strip_wiki_link(value.trim().trim_matches(|ch| ch == '"' || ch == '\''));
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.date_naive());
+    }
+    for format in DATE_TIME_FORMATS {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(datetime.date());
+        }
+    }
+    let slash_format = match order {
+        DateOrder::MonthFirst => "%m/%d/%Y",
+        DateOrder::DayFirst => "%d/%m/%Y",
+    };
+    if let Ok(date) = NaiveDate::parse_from_str(value, slash_format) {
+        return Some(date);
+    }
+    for format in MONTH_NAME_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return Some(date);
+        }
+    }
+    NaiveDate::parse_from_str(&format!("{value}-01"), "%Y-%m-%d").ok()
+}
+
+fn strip_wiki_link(value: &str) -> &str {
+    let Some(inner) = value
+        .strip_prefix("[[")
+        .and_then(|rest| rest.strip_suffix("]]"))
+    else {
+        return value;
+    };
+    let target = inner.split('|').next().unwrap_or(inner);
+    target.rsplit('/').next().unwrap_or(target).trim()
+}
+
+/// Parses the dates of one vault's notes and counts the ones that fail, so
+/// loaders can keep the entity and report the bad value instead.
+pub(crate) struct DateReader {
+    order: DateOrder,
+    invalid: usize,
+}
+
+impl DateReader {
+    pub(crate) fn new(order: DateOrder) -> Self {
+        Self { order, invalid: 0 }
+    }
+
+    /// Missing or blank values are not counted as invalid.
+    pub(crate) fn read(&mut self, value: Option<&str>) -> Option<NaiveDate> {
+        let value = value.map(str::trim).filter(|value| !value.is_empty())?;
+        let date = parse_vault_date(value, self.order);
+        if date.is_none() {
+            self.invalid += 1;
+        }
+        date
+    }
+
+    pub(crate) fn invalid(&self) -> usize {
+        self.invalid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_vault_date, DateReader};
+    use crate::types::DateOrder;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn parses_every_accepted_format() {
+        let june_3 = NaiveDate::from_ymd_opt(2024, 6, 3);
+        let june_1 = NaiveDate::from_ymd_opt(2024, 6, 1);
+        let cases: &[(&str, DateOrder, Option<NaiveDate>)] = &[
+            ("2024-06-03", DateOrder::MonthFirst, june_3),
+            ("  2024-06-03 ", DateOrder::MonthFirst, june_3),
+            ("\"2024-06-03\"", DateOrder::MonthFirst, june_3),
+            ("2024-06-03T14:30:00Z", DateOrder::MonthFirst, june_3),
+            ("2024-06-03T23:30:00-04:00", DateOrder::MonthFirst, june_3),
+            ("2024-06-03T14:30", DateOrder::MonthFirst, june_3),
+            ("2024-06-03T14:30:15", DateOrder::MonthFirst, june_3),
+            ("2024-06-03 14:30", DateOrder::MonthFirst, june_3),
+            ("2024-06-03 14:30:15", DateOrder::MonthFirst, june_3),
+            ("06/03/2024", DateOrder::MonthFirst, june_3),
+            ("6/3/2024", DateOrder::MonthFirst, june_3),
+            ("03/06/2024", DateOrder::DayFirst, june_3),
+            ("Jun 3, 2024", DateOrder::MonthFirst, june_3),
+            ("June 3, 2024", DateOrder::MonthFirst, june_3),
+            ("3 Jun 2024", DateOrder::DayFirst, june_3),
+            ("3 June 2024", DateOrder::MonthFirst, june_3),
+            ("[[2024-06-03]]", DateOrder::MonthFirst, june_3),
+            ("[[Daily/2024-06-03|Monday]]", DateOrder::MonthFirst, june_3),
+            ("2024-06", DateOrder::MonthFirst, june_1),
+            ("13/03/2024", DateOrder::MonthFirst, None),
+            ("2024-13", DateOrder::MonthFirst, None),
+            ("next Tuesday", DateOrder::MonthFirst, None),
+            ("[[Someday]]", DateOrder::MonthFirst, None),
+        ];
+        for (value, order, expected) in cases {
+            assert_eq!(parse_vault_date(value, *order), *expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn reader_counts_only_unparseable_values() {
+        let mut reader = DateReader::new(DateOrder::MonthFirst);
+        assert!(reader.read(Some("2024-06-03")).is_some());
+        assert!(reader.read(None).is_none());
+        assert!(reader.read(Some("  ")).is_none());
+        assert!(reader.read(Some("soon")).is_none());
+        assert_eq!(reader.invalid(), 1);
+    }
+}
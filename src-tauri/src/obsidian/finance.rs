@@ -0,0 +1,311 @@
+//! The `finance` trend domain: the bills in `Entities/Finance/Bills`, with
+//! recurring bills projected forward. A `next_due` already in the past is
+//! rolled on by its recurrence until it reaches today, so a bill nobody
+//! marked paid still shows its coming due dates.
+
+use chrono::{Days, Duration, Months, NaiveDate, Utc};
+
+use super::cards::{TrendBuilder, FINANCE_LAYOUT};
+use super::dates::DateReader;
+use super::periods::TrendPeriod;
+use super::{contribution_with_dates, load_bills, Bill};
+use crate::types::{CardFormat, DateOrder, DomainTrendSnapshot, TrendListItem};
+use crate::vault_roots::{display_name, names_in_several_vaults, VaultRoot};
+
+/// Due dates projected for each recurring bill.
+const PROJECTED_DUES: usize = 3;
+const DUE_SOON_DAYS: i64 = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Recurrence {
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Recurrence {
+    /// `weekly`, `monthly` or `yearly` (also `annual`/`annually`), in any
+    /// case. Anything else is a one-off bill.
+    pub(super) fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "weekly" => Some(Self::Weekly),
+            "monthly" => Some(Self::Monthly),
+            "yearly" | "annual" | "annually" => Some(Self::Yearly),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+            Self::Yearly => "yearly",
+        }
+    }
+
+    /// The amount per month of a bill with this recurrence.
+    fn monthly_amount(self, amount: f64) -> f64 {
+        match self {
+            Self::Weekly => amount * 52.0 / 12.0,
+            Self::Monthly => amount,
+            Self::Yearly => amount / 12.0,
+        }
+    }
+
+    /// The due date `step` recurrences after `anchor`. Months are counted
+    /// from the anchor, so a bill due on the 31st comes back to the 31st
+    /// after a shorter month.
+    fn nth(self, anchor: NaiveDate, step: u32) -> Option<NaiveDate> {
+        match self {
+            Self::Weekly => anchor.checked_add_days(Days::new(u64::from(step) * 7)),
+            Self::Monthly => anchor.checked_add_months(Months::new(step)),
+            Self::Yearly => anchor.checked_add_months(Months::new(step.checked_mul(12)?)),
+        }
+    }
+}
+
+/// The next due dates of a bill from `today` on: up to three for a recurring
+/// bill, or `next_due` itself for a one-off bill that is not past.
+pub(super) fn projected_due_dates(
+    next_due: NaiveDate,
+    recurrence: Option<Recurrence>,
+    today: NaiveDate,
+) -> Vec<NaiveDate> {
+    let Some(recurrence) = recurrence else {
+        return if next_due >= today {
+            vec![next_due]
+        } else {
+            Vec::new()
+        };
+    };
+    (0..)
+        .map_while(|step| recurrence.nth(next_due, step))
+        .skip_while(|due| *due < today)
+        .take(PROJECTED_DUES)
+        .collect()
+}
+
+pub(super) fn build_finance_snapshot(
+    domain_id: &str,
+    range: &str,
+    period: TrendPeriod,
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
+) -> DomainTrendSnapshot {
+    let mut bills: Vec<(&str, Bill)> = Vec::new();
+    let mut roots = Vec::new();
+    for vault in vaults {
+        let mut dates = DateReader::new(date_order);
+        let vault_bills = load_bills(&vault.path, &mut dates);
+        roots.push(contribution_with_dates(
+            vault,
+            &[("bills", vault_bills.len())],
+            &dates,
+        ));
+        bills.extend(
+            vault_bills
+                .into_iter()
+                .map(|bill| (vault.name.as_str(), bill)),
+        );
+    }
+    let duplicate_bills = names_in_several_vaults(
+        bills
+            .iter()
+            .map(|(vault, bill)| (*vault, bill.name.as_str())),
+    );
+
+    let today = period.today;
+    let due_soon_end = today + Duration::days(DUE_SOON_DAYS);
+    let mut monthly_total = 0.0;
+    let mut due_soon = 0.0;
+    let mut upcoming: Vec<(NaiveDate, String, &Bill)> = Vec::new();
+    for (vault, bill) in &bills {
+        if let Some(recurrence) = bill.recurrence {
+            monthly_total += recurrence.monthly_amount(bill.amount);
+        }
+        let Some(next_due) = bill.next_due else {
+            continue;
+        };
+        let label = display_name(vault, &bill.name, &duplicate_bills);
+        for due in projected_due_dates(next_due, bill.recurrence, today) {
+            if due <= due_soon_end {
+                due_soon += bill.amount;
+            }
+            upcoming.push((due, label.clone(), bill));
+        }
+    }
+    upcoming.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+    // The earliest of the largest amounts.
+    let largest = upcoming
+        .iter()
+        .rev()
+        .max_by(|a, b| a.2.amount.total_cmp(&b.2.amount));
+
+    let mut trends = TrendBuilder::new(&FINANCE_LAYOUT);
+    trends
+        .card(
+            "monthly_obligations",
+            "Monthly Obligations",
+            format!("${:.2}", monthly_total),
+            monthly_total,
+            CardFormat::Currency,
+        )
+        .sub_label = Some("recurring bills".to_string());
+    trends.card(
+        "due_7d",
+        "Due in 7 Days",
+        format!("${:.2}", due_soon),
+        due_soon,
+        CardFormat::Currency,
+    );
+    let largest_amount = largest.map_or(0.0, |(_, _, bill)| bill.amount);
+    trends
+        .card(
+            "largest_upcoming",
+            "Largest Upcoming",
+            format!("${:.2}", largest_amount),
+            largest_amount,
+            CardFormat::Currency,
+        )
+        .sub_label = largest.map(|(due, label, _)| format!("{label} • Due {due}"));
+    let upcoming_items = upcoming
+        .into_iter()
+        .map(|(due, label, bill)| TrendListItem {
+            label,
+            value: format!("${:.2}", bill.amount),
+            sub_label: Some(match bill.recurrence {
+                Some(recurrence) => format!("Due {due} • {}", recurrence.label()),
+                None => format!("Due {due}"),
+            }),
+        })
+        .collect();
+    trends.list("upcoming_bills", "Upcoming Bills", upcoming_items);
+
+    let (cards, lists) = trends.finish();
+    DomainTrendSnapshot {
+        domain_id: domain_id.to_string(),
+        range: range.to_string(),
+        updated_at: Utc::now().to_rfc3339(),
+        cards,
+        lists,
+        series: None,
+        roots,
+        previous: None,
+        diagnostics: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_finance_snapshot, projected_due_dates, Recurrence};
+    use crate::obsidian::periods::TrendPeriod;
+    use crate::types::DateOrder;
+    use crate::vault_roots::VaultRoot;
+    use chrono::NaiveDate;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn past_due_dates_roll_forward_by_their_recurrence() {
+        let today = date(2026, 1, 10);
+        assert_eq!(
+            projected_due_dates(date(2025, 11, 15), Some(Recurrence::Monthly), today),
+            [date(2026, 1, 15), date(2026, 2, 15), date(2026, 3, 15)]
+        );
+        assert_eq!(
+            projected_due_dates(date(2025, 12, 31), Some(Recurrence::Monthly), today),
+            [date(2026, 1, 31), date(2026, 2, 28), date(2026, 3, 31)]
+        );
+        assert_eq!(
+            projected_due_dates(date(2026, 1, 10), Some(Recurrence::Monthly), today),
+            [date(2026, 1, 10), date(2026, 2, 10), date(2026, 3, 10)]
+        );
+        assert_eq!(
+            projected_due_dates(date(2026, 1, 1), Some(Recurrence::Weekly), today),
+            [date(2026, 1, 15), date(2026, 1, 22), date(2026, 1, 29)]
+        );
+        assert_eq!(
+            projected_due_dates(date(2024, 2, 29), Some(Recurrence::Yearly), today),
+            [date(2026, 2, 28), date(2027, 2, 28), date(2028, 2, 29)]
+        );
+        assert_eq!(
+            projected_due_dates(date(2026, 1, 9), None, today),
+            Vec::<NaiveDate>::new()
+        );
+        assert_eq!(
+            projected_due_dates(date(2026, 2, 1), None, today),
+            [date(2026, 2, 1)]
+        );
+        assert_eq!(Recurrence::parse(" Annual "), Some(Recurrence::Yearly));
+        assert_eq!(Recurrence::parse("once"), None);
+    }
+
+    #[test]
+    fn snapshot_expands_recurring_bills_into_dated_items() {
+        let dir = tempdir().expect("tempdir");
+        let bills = dir.path().join("Entities").join("Finance").join("Bills");
+        fs::create_dir_all(&bills).unwrap();
+        for (file, frontmatter) in [
+            (
+                "Rent.md",
+                "name: Rent\namount: 1200\nnext_due: 2025-12-01\nrecurrence: monthly",
+            ),
+            (
+                "Gym.md",
+                "amount: 12\nnext_due: 2026-01-05\nrecurrence: weekly",
+            ),
+            (
+                "Domain.md",
+                "amount: 120\nnext_due: 2026-06-01\nfrequency: annual",
+            ),
+            ("Repair.md", "amount: 300\nnext_due: 2026-01-14"),
+        ] {
+            fs::write(bills.join(file), format!("---\n{frontmatter}\n---\n")).unwrap();
+        }
+        let vaults = [VaultRoot {
+            name: "vault".to_string(),
+            path: dir.path().to_path_buf(),
+        }];
+        let period = TrendPeriod::for_range("30d", date(2026, 1, 10));
+        let snapshot =
+            build_finance_snapshot("finance", "30d", period, &vaults, DateOrder::default());
+
+        let card = |id: &str| {
+            let card = snapshot.cards.iter().find(|card| card.id == id).unwrap();
+            (card.value.as_str(), card.sub_label.as_deref())
+        };
+        // 1200 + 12 * 52 / 12 + 120 / 12; the one-off repair is left out.
+        assert_eq!(card("monthly_obligations").0, "$1262.00");
+        // Gym on the 12th and the repair on the 14th.
+        assert_eq!(card("due_7d"), ("$312.00", None));
+        assert_eq!(
+            card("largest_upcoming"),
+            ("$1200.00", Some("Rent • Due 2026-02-01"))
+        );
+
+        let items: Vec<_> = snapshot.lists[0]
+            .items
+            .iter()
+            .map(|item| (item.label.as_str(), item.sub_label.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("Gym", "Due 2026-01-12 • weekly"),
+                ("Repair", "Due 2026-01-14"),
+                ("Gym", "Due 2026-01-19 • weekly"),
+                ("Gym", "Due 2026-01-26 • weekly"),
+                ("Rent", "Due 2026-02-01 • monthly"),
+                ("Rent", "Due 2026-03-01 • monthly"),
+                ("Rent", "Due 2026-04-01 • monthly"),
+                ("Domain", "Due 2026-06-01 • yearly"),
+                ("Domain", "Due 2027-06-01 • yearly"),
+                ("Domain", "Due 2028-06-01 • yearly"),
+            ]
+        );
+    }
+}
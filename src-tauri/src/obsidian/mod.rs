@@ -1,19 +1,56 @@
-use std::collections::{HashMap, HashSet};
+pub(crate) mod cards;
+pub(crate) mod dates;
+mod finance;
+mod periods;
+mod series;
+mod stream_append;
+mod vault_health;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
-use std::sync::{Mutex, OnceLock};
-use std::time::SystemTime;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
 
 use chrono::{Duration, NaiveDate, Utc};
 use serde::Deserialize;
 
-use crate::types::{DomainTrendSnapshot, TrendCard, TrendList, TrendListItem};
+use crate::delivery_platforms::{PlatformBreakdown, TipSplit};
+use crate::nutrition_goals::{goal_cards, goals_report, resolve_nutrition_goals, MacroTotals};
+use crate::types::{
+    CardFormat, DateOrder, DomainTrendSnapshot, NutritionGoals, TrendListItem, TrendPrevious,
+    VaultContribution, WorkspaceEntry, WorkspacePurpose,
+};
+use crate::vault_roots::{
+    display_name, names_in_several_vaults, resolve_vault_roots, vault_contribution, VaultRoot,
+};
+use crate::workouts::{
+    load_workout_entities, stream_workout_minutes, unmatched_stream_workouts, workout_activity,
+    workout_kind, WorkoutEntity, MALFORMED_WORKOUTS_KEY,
+};
+use cards::{TrendBuilder, DELIVERY_LAYOUT, FOOD_LAYOUT, MEDIA_LAYOUT, YOUTUBE_LAYOUT};
+use dates::{DateReader, INVALID_DATES_KEY};
+use finance::{build_finance_snapshot, Recurrence};
+use periods::{apply_card_deltas, apply_change_sub_labels, TrendPeriod};
+use series::trend_series;
+pub(crate) use stream_append::workspace_stream_append;
 
 #[derive(Clone)]
 struct StreamEntry {
     date: NaiveDate,
     text: String,
-    links: Vec<String>,
+    links: Vec<StreamLink>,
+    /// Index into the workspace's vaults.
+    vault: usize,
+}
+
+/// A `[[link]]` in a stream entry, with the servings written next to it:
+/// `x2`, `(x2)` or `x1.5` after the link, or `2x` before it. Defaults to 1.
+#[derive(Clone, Debug, PartialEq)]
+struct StreamLink {
+    target: String,
+    servings: f64,
 }
 
 #[derive(Default, Clone)]
@@ -32,6 +69,9 @@ struct DeliverySession {
     hours: f64,
     mileage: f64,
     orders: f64,
+    platform: Option<String>,
+    tips: Option<f64>,
+    base_pay: Option<f64>,
 }
 
 #[derive(Clone)]
@@ -39,6 +79,8 @@ struct Bill {
     name: String,
     amount: f64,
     next_due: Option<NaiveDate>,
+    /// `None` for one-off bills.
+    recurrence: Option<Recurrence>,
 }
 
 #[derive(Clone)]
@@ -59,86 +101,245 @@ struct YoutubeIdea {
 }
 
 struct TrendCacheEntry {
-    last_mtime: SystemTime,
+    /// `domain_fingerprint` of every vault when the snapshot was built.
+    fingerprint: u64,
+    /// The day the snapshot's ranges end on. It is checked on read rather
+    /// than keyed on, so a new day replaces the entry instead of adding one.
+    today: NaiveDate,
     snapshot: DomainTrendSnapshot,
 }
 
 static TREND_CACHE: OnceLock<Mutex<HashMap<String, TrendCacheEntry>>> = OnceLock::new();
 
+/// Domains with trend builders; Life workspaces without an explicit domain
+/// prewarm all of them.
+const TREND_DOMAINS: &[&str] = &[
+    "delivery_finance",
+    "finance",
+    "food_exercise",
+    "media",
+    "youtube",
+];
+const PREWARM_RANGES: &[&str] = &["7d", "30d"];
+/// Delivery cards whose `subLabel` is their change against the previous
+/// period. `tips` keeps its own sub-label and `bills_due` looks ahead.
+const DELIVERY_CHANGE_CARDS: &[&str] = &[
+    "earnings",
+    "hours",
+    "sessions",
+    "hourly",
+    "per_mile",
+    "orders",
+    "avg_order",
+];
+
+fn trend_cache() -> MutexGuard<'static, HashMap<String, TrendCacheEntry>> {
+    lock_trend_cache(TREND_CACHE.get_or_init(|| Mutex::new(HashMap::new())))
+}
+
+/// A panic while the cache is held would otherwise poison it for the rest of
+/// the process. The cache is only derived data, so drop it and carry on.
+fn lock_trend_cache(
+    cache: &Mutex<HashMap<String, TrendCacheEntry>>,
+) -> MutexGuard<'_, HashMap<String, TrendCacheEntry>> {
+    cache.lock().unwrap_or_else(|err| {
+        let mut guard = err.into_inner();
+        guard.clear();
+        cache.clear_poison();
+        guard
+    })
+}
+
+/// Builds a trend snapshot from every vault of the workspace. Entries from all
+/// vaults are combined; `roots` on the snapshot lists what each vault added,
+/// including how many note dates could not be parsed. With `compare`, the
+/// snapshot also carries the previous period's cards and per-card deltas.
+/// Ranges end on `today`, the client's current date. A cached snapshot is
+/// reused while the domain's files are unchanged, unless `force_refresh`.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn compute_domain_trends(
     workspace_path: &str,
+    obsidian_roots: &[String],
+    date_order: DateOrder,
+    nutrition_goals: Option<&NutritionGoals>,
     domain_id: &str,
     range: &str,
+    compare: bool,
+    force_refresh: bool,
+    today: NaiveDate,
 ) -> Result<DomainTrendSnapshot, String> {
-    let workspace_root = PathBuf::from(workspace_path);
+    let vaults = resolve_vault_roots(workspace_path, obsidian_roots);
     let normalized_domain = normalize_domain_id(domain_id);
-    let cache_key = format!("{}::{}::{}", workspace_path, normalized_domain, range);
-    let latest_mtime = latest_mtime_for_domain(&workspace_root, normalized_domain.as_str())?;
+    let roots_key = vaults
+        .iter()
+        .map(|vault| vault.path.to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("|");
+    let cache_key = format!(
+        "{}::{}::{:?}::{:?}::{}::{}::{}",
+        workspace_path, roots_key, date_order, nutrition_goals, normalized_domain, range, compare
+    );
+    let mut hasher = DefaultHasher::new();
+    for vault in &vaults {
+        domain_fingerprint(&vault.path, normalized_domain.as_str())?.hash(&mut hasher);
+    }
+    let fingerprint = hasher.finish();
 
-    let cache = TREND_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
-    if let Some(entry) = cache.lock().unwrap().get(&cache_key) {
-        if entry.last_mtime >= latest_mtime {
-            return Ok(entry.snapshot.clone());
+    if !force_refresh {
+        if let Some(entry) = trend_cache().get(&cache_key) {
+            if entry.fingerprint == fingerprint && entry.today == today {
+                return Ok(entry.snapshot.clone());
+            }
         }
     }
 
-    let today = Utc::now().date_naive();
-    let start_date = match range {
-        "7d" => Some(today - Duration::days(6)),
-        "30d" => Some(today - Duration::days(29)),
-        _ => None,
+    let period = TrendPeriod::for_range(range, today);
+    let build = |period: TrendPeriod| {
+        build_snapshot(
+            &normalized_domain,
+            range,
+            period,
+            &vaults,
+            date_order,
+            nutrition_goals,
+        )
     };
+    let mut snapshot = build(period);
+    // Delivery cards always show their change against the previous period.
+    let delivery = normalized_domain == "delivery_finance";
+    if let Some(previous_period) = period.previous(range).filter(|_| compare || delivery) {
+        let previous = build(previous_period);
+        if delivery {
+            apply_change_sub_labels(&mut snapshot.cards, &previous.cards, DELIVERY_CHANGE_CARDS);
+        }
+        if compare {
+            apply_card_deltas(&mut snapshot.cards, &previous.cards);
+            snapshot.previous = Some(TrendPrevious {
+                start: previous_period
+                    .start
+                    .map(|start| start.to_string())
+                    .unwrap_or_default(),
+                end: previous_period.end.to_string(),
+                cards: previous.cards,
+            });
+        }
+    }
 
-    let stream_entries = load_stream_entries(&workspace_root);
-    let snapshot = match normalized_domain.as_str() {
-        "delivery_finance" => build_delivery_snapshot(
-            normalized_domain.as_str(),
-            range,
+    trend_cache().insert(
+        cache_key,
+        TrendCacheEntry {
+            fingerprint,
             today,
-            start_date,
-            &workspace_root,
-        ),
+            snapshot: snapshot.clone(),
+        },
+    );
+
+    Ok(snapshot)
+}
+
+/// `compute_domain_trends` with the workspace's vault settings. Both the
+/// desktop command and the daemon RPC go through here.
+pub(crate) fn workspace_domain_trends(
+    entry: &WorkspaceEntry,
+    domain_id: &str,
+    range: &str,
+    compare: bool,
+    force_refresh: bool,
+    today: NaiveDate,
+) -> Result<DomainTrendSnapshot, String> {
+    compute_domain_trends(
+        &entry.path,
+        &entry.settings.effective_obsidian_roots(),
+        entry.settings.date_order,
+        entry.settings.nutrition_goals.as_ref(),
+        domain_id,
+        range,
+        compare,
+        force_refresh,
+        today,
+    )
+}
+
+fn build_snapshot(
+    domain_id: &str,
+    range: &str,
+    period: TrendPeriod,
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
+    nutrition_goals: Option<&NutritionGoals>,
+) -> DomainTrendSnapshot {
+    match domain_id {
+        "delivery_finance" => build_delivery_snapshot(domain_id, range, period, vaults, date_order),
+        "finance" => build_finance_snapshot(domain_id, range, period, vaults, date_order),
         "food_exercise" => build_food_snapshot(
-            normalized_domain.as_str(),
+            domain_id,
             range,
-            today,
-            start_date,
-            &workspace_root,
-            &stream_entries,
-        ),
-        "media" => build_media_snapshot(
-            normalized_domain.as_str(),
-            range,
-            today,
-            start_date,
-            &workspace_root,
-        ),
-        "youtube" => build_youtube_snapshot(
-            normalized_domain.as_str(),
-            range,
-            today,
-            start_date,
-            &workspace_root,
+            period,
+            vaults,
+            date_order,
+            nutrition_goals,
         ),
+        "media" => build_media_snapshot(domain_id, range, period, vaults, date_order),
+        "youtube" => build_youtube_snapshot(domain_id, range, period, vaults, date_order),
+        "vault" => vault_health::build_vault_snapshot(domain_id, range, period, vaults, date_order),
         _ => DomainTrendSnapshot {
-            domain_id: normalized_domain,
+            domain_id: domain_id.to_string(),
             range: range.to_string(),
             updated_at: Utc::now().to_rfc3339(),
             cards: Vec::new(),
             lists: Vec::new(),
             series: None,
+            roots: Vec::new(),
+            previous: None,
+            diagnostics: Vec::new(),
         },
-    };
+    }
+}
 
-    cache.lock().unwrap().insert(
-        cache_key,
-        TrendCacheEntry {
-            last_mtime: latest_mtime,
-            snapshot: snapshot.clone(),
-        },
-    );
+/// Drops cached snapshots of a domain after the app writes to its notes, so
+/// the next request rebuilds even if the file mtime did not move forward.
+pub(crate) fn invalidate_domain_trends(domain_id: &str) {
+    let marker = format!("::{}::", normalize_domain_id(domain_id));
+    trend_cache().retain(|key, _| !key.contains(&marker));
+}
 
-    Ok(snapshot)
+pub(crate) fn trend_domains_for_workspace(entry: &WorkspaceEntry) -> Vec<String> {
+    if entry.settings.purpose != Some(WorkspacePurpose::Life) {
+        return Vec::new();
+    }
+    match entry.settings.domain_id.as_deref() {
+        Some(domain_id) if !domain_id.trim().is_empty() => vec![domain_id.to_string()],
+        _ => TREND_DOMAINS
+            .iter()
+            .map(|domain| domain.to_string())
+            .collect(),
+    }
+}
+
+/// Computes the 7d and 30d snapshots for a Life workspace in the background so
+/// the first dashboard render hits the cache. Failures are only logged.
+pub(crate) fn prewarm_domain_trends(
+    entry: &WorkspaceEntry,
+    today: NaiveDate,
+) -> Vec<tokio::task::JoinHandle<()>> {
+    let mut tasks = Vec::new();
+    for domain_id in trend_domains_for_workspace(entry) {
+        for range in PREWARM_RANGES {
+            let entry = entry.clone();
+            let domain_id = domain_id.clone();
+            tasks.push(tokio::task::spawn_blocking(move || {
+                if let Err(err) =
+                    workspace_domain_trends(&entry, &domain_id, range, false, false, today)
+                {
+                    eprintln!(
+                        "Failed to prewarm {domain_id} {range} trends for workspace {}: {err}",
+                        entry.id
+                    );
+                }
+            }));
+        }
+    }
+    tasks
 }
 
 fn normalize_domain_id(domain_id: &str) -> String {
@@ -149,66 +350,111 @@ fn normalize_domain_id(domain_id: &str) -> String {
         .replace(' ', "_")
 }
 
-fn latest_mtime_for_domain(root: &Path, domain: &str) -> Result<SystemTime, String> {
-    let mut max_time = SystemTime::UNIX_EPOCH;
-    let stream_dir = root.join("Stream");
-    max_time = max_time.max(latest_mtime_in_dir(&stream_dir)?);
+/// Hash of the path, size and mtime of every file the domain reads, so an
+/// edit, a deletion or a rename all change it.
+fn domain_fingerprint(root: &Path, domain: &str) -> Result<u64, String> {
+    if domain == "vault" {
+        return Ok(vault_health::vault_fingerprint(root));
+    }
+    let mut hasher = DefaultHasher::new();
+    hash_dir_files(&root.join("Stream"), &mut hasher)?;
     let entities = root.join("Entities");
     let domain_dir = match domain {
         "delivery_finance" => vec![
             entities.join("Delivery").join("Sessions"),
             entities.join("Finance").join("Bills"),
         ],
+        "finance" => vec![entities.join("Finance")],
+        // Behaviors includes the workout notes in Behaviors/Workouts.
         "food_exercise" => vec![entities.join("Food"), entities.join("Behaviors")],
         "media" => vec![entities.join("Media")],
         "youtube" => vec![entities.join("YouTube")],
         _ => vec![entities],
     };
     for dir in domain_dir {
-        max_time = max_time.max(latest_mtime_in_dir(&dir)?);
+        hash_dir_files(&dir, &mut hasher)?;
     }
-    Ok(max_time)
+    Ok(hasher.finish())
 }
 
-fn latest_mtime_in_dir(path: &Path) -> Result<SystemTime, String> {
-    let mut max_time = SystemTime::UNIX_EPOCH;
+fn hash_dir_files(path: &Path, hasher: &mut DefaultHasher) -> Result<(), String> {
     if !path.exists() {
-        return Ok(max_time);
+        return Ok(());
     }
-    for entry in fs::read_dir(path).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
+    let mut entries = fs::read_dir(path)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
         let metadata = entry.metadata().map_err(|e| e.to_string())?;
         if metadata.is_dir() {
-            max_time = max_time.max(latest_mtime_in_dir(&entry.path())?);
-        } else if let Ok(modified) = metadata.modified() {
-            max_time = max_time.max(modified);
+            hash_dir_files(&entry.path(), hasher)?;
+        } else {
+            (entry.path(), metadata.len(), metadata.modified().ok()).hash(hasher);
         }
     }
-    Ok(max_time)
+    Ok(())
 }
 
 fn build_delivery_snapshot(
     domain_id: &str,
     range: &str,
-    today: NaiveDate,
-    start_date: Option<NaiveDate>,
-    root: &Path,
+    period: TrendPeriod,
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
 ) -> DomainTrendSnapshot {
-    let sessions = load_delivery_sessions(root);
+    let mut sessions = Vec::new();
+    let mut bills = Vec::new();
+    let mut bill_vaults = Vec::new();
+    let mut roots = Vec::new();
+    for vault in vaults {
+        let mut dates = DateReader::new(date_order);
+        let vault_sessions = load_delivery_sessions(&vault.path, &mut dates);
+        let vault_bills = load_bills(&vault.path, &mut dates);
+        roots.push(contribution_with_dates(
+            vault,
+            &[
+                ("sessions", vault_sessions.len()),
+                ("bills", vault_bills.len()),
+            ],
+            &dates,
+        ));
+        sessions.extend(vault_sessions);
+        bill_vaults.extend(vault_bills.iter().map(|_| vault.name.as_str()));
+        bills.extend(vault_bills);
+    }
+    let duplicate_bills = names_in_several_vaults(
+        bill_vaults
+            .iter()
+            .copied()
+            .zip(bills.iter().map(|bill| bill.name.as_str())),
+    );
     let mut total_earnings = 0.0;
     let mut total_hours = 0.0;
     let mut total_miles = 0.0;
     let mut total_orders = 0.0;
     let mut session_items = Vec::new();
     let mut sessions_count = 0;
+    let mut platforms = PlatformBreakdown::default();
+    let mut tip_split = TipSplit::default();
+    let mut daily_earnings = Vec::new();
 
     for session in sessions {
-        if in_range(session.date, start_date, today) {
+        if period.contains(session.date) {
+            daily_earnings.push((session.date, session.earnings));
             total_earnings += session.earnings;
             total_hours += session.hours;
             total_miles += session.mileage;
             total_orders += session.orders;
             sessions_count += 1;
+            platforms.add(
+                session.platform.as_deref(),
+                session.earnings,
+                session.hours,
+                session.orders,
+            );
+            tip_split.add(session.earnings, session.tips, session.base_pay);
             session_items.push(TrendListItem {
                 label: session.date.to_string(),
                 value: format!("${:.2}", session.earnings),
@@ -236,22 +482,22 @@ fn build_delivery_snapshot(
         0.0
     };
 
-    let bills = load_bills(root);
+    let today = period.today;
     let bill_end = match range {
         "7d" => today + Duration::days(7),
-        "30d" => today + Duration::days(30),
+        "30d" | "mtd" | "last_month" => today + Duration::days(30),
         _ => today + Duration::days(3650),
     };
     let mut bill_total = 0.0;
     let mut bill_entries: Vec<(NaiveDate, TrendListItem)> = Vec::new();
-    for bill in bills {
+    for (bill, vault) in bills.iter().zip(bill_vaults.iter().copied()) {
         if let Some(next_due) = bill.next_due {
             if next_due >= today && next_due <= bill_end {
                 bill_total += bill.amount;
                 bill_entries.push((
                     next_due,
                     TrendListItem {
-                        label: bill.name,
+                        label: display_name(vault, &bill.name, &duplicate_bills),
                         value: format!("${:.2}", bill.amount),
                         sub_label: Some(format!("Due {}", next_due)),
                     },
@@ -262,106 +508,205 @@ fn build_delivery_snapshot(
     bill_entries.sort_by_key(|(due, _)| *due);
     let bill_items = bill_entries.into_iter().map(|(_, item)| item).collect();
 
+    let mut trends = TrendBuilder::new(&DELIVERY_LAYOUT);
+    trends.card(
+        "earnings",
+        "Earnings",
+        format!("${:.2}", total_earnings),
+        total_earnings,
+        CardFormat::Currency,
+    );
+    trends.card(
+        "hours",
+        "Hours",
+        format!("{:.1}", total_hours),
+        total_hours * 60.0,
+        CardFormat::Duration,
+    );
+    trends.card(
+        "sessions",
+        "Sessions",
+        format!("{sessions_count}"),
+        sessions_count as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "hourly",
+        "$/hr",
+        format!("${:.2}", hourly),
+        hourly,
+        CardFormat::Currency,
+    );
+    trends.card(
+        "per_mile",
+        "$/mi",
+        format!("${:.2}", per_mile),
+        per_mile,
+        CardFormat::Currency,
+    );
+    trends.card(
+        "orders",
+        "Orders",
+        format!("{:.0}", total_orders),
+        total_orders,
+        CardFormat::Count,
+    );
+    trends.card(
+        "avg_order",
+        "Avg/Order",
+        format!("${:.2}", avg_order),
+        avg_order,
+        CardFormat::Currency,
+    );
+    trends.card(
+        "bills_due",
+        "Bills Due",
+        format!("${:.2}", bill_total),
+        bill_total,
+        CardFormat::Currency,
+    );
+    if let Some(percent) = tip_split.tips_percent() {
+        let card = trends.card(
+            "tips",
+            "Tips",
+            format!("{percent:.0}%"),
+            percent,
+            CardFormat::Percent,
+        );
+        card.sub_label = Some(format!(
+            "${:.2} tips • ${:.2} base",
+            tip_split.tips, tip_split.base_pay
+        ));
+    }
+    trends.list("sessions", "Sessions", session_items);
+    trends.list("bills", "Upcoming Bills", bill_items);
+    let platform_items: Vec<TrendListItem> = platforms
+        .into_sorted()
+        .into_iter()
+        .map(|totals| TrendListItem {
+            label: totals.platform.to_string(),
+            value: format!("${:.2}", totals.earnings),
+            sub_label: Some(format!(
+                "${:.2}/hr • {:.0} orders",
+                totals.hourly(),
+                totals.orders
+            )),
+        })
+        .collect();
+    if !platform_items.is_empty() {
+        trends.list("platforms", "Platforms", platform_items);
+    }
+    let series = vec![trend_series(
+        "earnings",
+        "Earnings",
+        "USD",
+        period,
+        daily_earnings,
+    )];
+
+    let (cards, lists) = trends.finish();
     DomainTrendSnapshot {
         domain_id: domain_id.to_string(),
         range: range.to_string(),
         updated_at: Utc::now().to_rfc3339(),
-        cards: vec![
-            TrendCard {
-                id: "earnings".to_string(),
-                label: "Earnings".to_string(),
-                value: format!("${:.2}", total_earnings),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "hours".to_string(),
-                label: "Hours".to_string(),
-                value: format!("{:.1}", total_hours),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "sessions".to_string(),
-                label: "Sessions".to_string(),
-                value: format!("{sessions_count}"),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "hourly".to_string(),
-                label: "$/hr".to_string(),
-                value: format!("${:.2}", hourly),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "per_mile".to_string(),
-                label: "$/mi".to_string(),
-                value: format!("${:.2}", per_mile),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "orders".to_string(),
-                label: "Orders".to_string(),
-                value: format!("{:.0}", total_orders),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "avg_order".to_string(),
-                label: "Avg/Order".to_string(),
-                value: format!("${:.2}", avg_order),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "bills_due".to_string(),
-                label: "Bills Due".to_string(),
-                value: format!("${:.2}", bill_total),
-                sub_label: None,
-            },
-        ],
-        lists: vec![
-            TrendList {
-                id: "sessions".to_string(),
-                title: "Sessions".to_string(),
-                items: session_items,
-            },
-            TrendList {
-                id: "bills".to_string(),
-                title: "Upcoming Bills".to_string(),
-                items: bill_items,
-            },
-        ],
-        series: None,
+        cards,
+        lists,
+        series: Some(series),
+        roots,
+        previous: None,
+        diagnostics: Vec::new(),
     }
 }
 
 fn build_food_snapshot(
     domain_id: &str,
     range: &str,
-    today: NaiveDate,
-    start_date: Option<NaiveDate>,
-    root: &Path,
-    stream_entries: &[StreamEntry],
+    period: TrendPeriod,
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
+    nutrition_goals: Option<&NutritionGoals>,
 ) -> DomainTrendSnapshot {
-    let food_map = load_food_map(root);
+    let mut stream_entries = Vec::new();
+    let mut food_maps = Vec::new();
+    let mut workouts: Vec<WorkoutEntity> = Vec::new();
+    let mut roots = Vec::new();
+    for (index, vault) in vaults.iter().enumerate() {
+        let mut entries = load_stream_entries(&vault.path);
+        for entry in &mut entries {
+            entry.vault = index;
+        }
+        let food_map = load_food_map(&vault.path);
+        let food_count = food_map
+            .keys()
+            .filter(|key| !key.starts_with("Food/"))
+            .count();
+        let mut dates = DateReader::new(date_order);
+        let loaded = load_workout_entities(&vault.path, &mut |value| dates.read(Some(value)));
+        let mut contribution = contribution_with_dates(
+            vault,
+            &[
+                ("stream_entries", entries.len()),
+                ("foods", food_count),
+                ("workouts", loaded.workouts.len()),
+            ],
+            &dates,
+        );
+        if loaded.malformed > 0 {
+            contribution
+                .counts
+                .insert(MALFORMED_WORKOUTS_KEY.to_string(), loaded.malformed);
+        }
+        roots.push(contribution);
+        stream_entries.extend(entries);
+        food_maps.push(food_map);
+        workouts.extend(
+            loaded
+                .workouts
+                .into_iter()
+                .filter(|workout| period.contains(workout.date)),
+        );
+    }
+    // A food defined in several vaults is counted per vault, not merged.
+    let duplicate_foods =
+        names_in_several_vaults(food_maps.iter().enumerate().flat_map(|(index, map)| {
+            map.keys()
+                .filter(|key| !key.starts_with("Food/"))
+                .map(move |key| (vaults[index].name.as_str(), key.as_str()))
+        }));
     let mut total = Nutrition::default();
     let mut meals_count = 0;
-    let mut workout_count = 0;
-    let mut food_counts: HashMap<String, usize> = HashMap::new();
+    let mut stream_workouts: Vec<(NaiveDate, &str, &str, Option<f64>)> = Vec::new();
+    let mut food_counts: HashMap<String, f64> = HashMap::new();
     let mut entry_dates: HashSet<NaiveDate> = HashSet::new();
+    let mut daily: BTreeMap<NaiveDate, MacroTotals> = BTreeMap::new();
 
-    for entry in stream_entries {
-        if !in_range(entry.date, start_date, today) {
+    for entry in &stream_entries {
+        if !period.contains(entry.date) {
             continue;
         }
         entry_dates.insert(entry.date);
         let mut matched_food = false;
         for link in &entry.links {
-            if let Some(name) = food_link_name(link) {
-                if let Some(nutrition) = food_map.get(&name) {
-                    total.calories += nutrition.calories;
-                    total.protein += nutrition.protein;
-                    total.carbs += nutrition.carbs;
-                    total.fat += nutrition.fat;
-                    total.fiber += nutrition.fiber;
-                    *food_counts.entry(name.clone()).or_default() += 1;
+            if let Some(name) = food_link_name(&link.target) {
+                // Prefer the entry's own vault, then the others in order.
+                let found = std::iter::once(entry.vault)
+                    .chain((0..food_maps.len()).filter(|index| *index != entry.vault))
+                    .find_map(|index| food_maps[index].get(&name).map(|food| (index, food)));
+                if let Some((vault_index, nutrition)) = found {
+                    let servings = link.servings;
+                    total.calories += nutrition.calories * servings;
+                    total.protein += nutrition.protein * servings;
+                    total.carbs += nutrition.carbs * servings;
+                    total.fat += nutrition.fat * servings;
+                    total.fiber += nutrition.fiber * servings;
+                    daily.entry(entry.date).or_default().add(
+                        nutrition.calories * servings,
+                        nutrition.protein * servings,
+                        nutrition.carbs * servings,
+                        nutrition.fat * servings,
+                    );
+                    let label = display_name(&vaults[vault_index].name, &name, &duplicate_foods);
+                    *food_counts.entry(label).or_default() += servings;
                     matched_food = true;
                 }
             }
@@ -369,31 +714,105 @@ fn build_food_snapshot(
         if matched_food {
             meals_count += 1;
         }
-        if entry.text.contains("🏋️") || entry.text.to_lowercase().contains("workout") {
-            workout_count += 1;
-        }
-        if entry.text.contains("🚶") || entry.text.to_lowercase().contains("walk") {
-            workout_count += 1;
+        let lower = entry.text.to_lowercase();
+        let activity = workout_activity(&entry.text);
+        let minutes = stream_workout_minutes(&entry.text);
+        let kind = if entry.text.contains("🏋️") || lower.contains("workout") {
+            Some(if workout_kind(&lower) == "cardio" {
+                "cardio"
+            } else {
+                "strength"
+            })
+        } else if entry.text.contains("🚶") || lower.contains("walk") {
+            Some("walk")
+        } else if minutes.is_some() && activity != "other" {
+            // "Run 30 min" is a workout even without the keyword.
+            Some(match activity {
+                "lift" => "strength",
+                "walk" => "walk",
+                _ => "cardio",
+            })
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            stream_workouts.push((entry.date, kind, activity, minutes));
         }
     }
+    // A stream line counts once, with its minutes when it names them (zero
+    // otherwise); a note for the same session replaces it and adds distance
+    // and calories.
+    let stream_workouts =
+        unmatched_stream_workouts(stream_workouts, &workouts, |(date, kind, _, minutes)| {
+            (*date, kind.to_string(), *minutes)
+        });
+    let workout_count = stream_workouts.len() + workouts.len();
+    let mut by_kind: HashMap<String, (usize, f64)> = HashMap::new();
+    let mut by_activity: HashMap<String, (usize, f64)> = HashMap::new();
+    let mut workout_minutes = 0.0;
+    for (_, kind, activity, minutes) in &stream_workouts {
+        let minutes = minutes.unwrap_or(0.0);
+        let entry = by_kind.entry(kind.to_string()).or_default();
+        entry.0 += 1;
+        entry.1 += minutes;
+        let entry = by_activity.entry(activity.to_string()).or_default();
+        entry.0 += 1;
+        entry.1 += minutes;
+        workout_minutes += minutes;
+    }
+    let mut workout_km = 0.0;
+    let mut workout_calories = 0.0;
+    for workout in &workouts {
+        let entry = by_kind.entry(workout.kind.clone()).or_default();
+        entry.0 += 1;
+        entry.1 += workout.duration_min;
+        let entry = by_activity.entry(workout.activity.clone()).or_default();
+        entry.0 += 1;
+        entry.1 += workout.duration_min;
+        workout_minutes += workout.duration_min;
+        workout_km += workout.distance_km;
+        workout_calories += workout.calories;
+    }
+    let mut by_kind: Vec<_> = by_kind.into_iter().collect();
+    by_kind.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then_with(|| a.0.cmp(&b.0)));
+    let workout_type_items = by_kind
+        .into_iter()
+        .map(|(kind, (count, minutes))| TrendListItem {
+            label: kind,
+            value: format!("{count}"),
+            sub_label: (minutes > 0.0).then(|| format!("{minutes:.0} min")),
+        })
+        .collect();
+    let mut by_activity: Vec<_> = by_activity.into_iter().collect();
+    by_activity.sort_by(|a, b| b.1 .1.total_cmp(&a.1 .1).then_with(|| a.0.cmp(&b.0)));
+    let activity_minute_items = by_activity
+        .into_iter()
+        .map(|(activity, (count, minutes))| TrendListItem {
+            label: activity,
+            value: format!("{minutes:.0} min"),
+            sub_label: Some(if count == 1 {
+                "1 workout".to_string()
+            } else {
+                format!("{count} workouts")
+            }),
+        })
+        .collect();
 
     let mut top_foods: Vec<_> = food_counts.into_iter().collect();
-    top_foods.sort_by(|a, b| b.1.cmp(&a.1));
+    top_foods.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
     let top_food_items = top_foods
         .into_iter()
         .take(5)
-        .map(|(name, count)| TrendListItem {
+        .map(|(name, servings)| TrendListItem {
             label: name,
-            value: format!("{count}"),
+            value: format_servings(servings),
             sub_label: None,
         })
         .collect();
 
-    let range_days = if let Some(start) = start_date {
-        (today - start).num_days().max(0) + 1
-    } else {
-        entry_dates.len().max(1) as i64
-    };
+    let range_days = period
+        .days()
+        .unwrap_or_else(|| entry_dates.len().max(1) as i64);
     let avg_calories = if range_days > 0 {
         total.calories / range_days as f64
     } else {
@@ -404,103 +823,180 @@ fn build_food_snapshot(
     } else {
         0.0
     };
+    let avg_workout_minutes = if range_days > 0 {
+        workout_minutes / range_days as f64
+    } else {
+        0.0
+    };
+    let vault_paths: Vec<&Path> = vaults.iter().map(|vault| vault.path.as_path()).collect();
+    let (goals, goals_source, diagnostics) = resolve_nutrition_goals(&vault_paths, nutrition_goals);
+    let goals = goals_report(
+        goals,
+        goals_source,
+        diagnostics,
+        range,
+        &daily,
+        period.contains(period.today).then_some(period.today),
+    );
 
-    DomainTrendSnapshot {
-        domain_id: domain_id.to_string(),
-        range: range.to_string(),
-        updated_at: Utc::now().to_rfc3339(),
-        cards: vec![
-            TrendCard {
-                id: "calories".to_string(),
-                label: "Calories".to_string(),
-                value: format!("{:.0}", total.calories),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "calories_avg".to_string(),
-                label: "Calories/Day".to_string(),
-                value: format!("{:.0}", avg_calories),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "protein".to_string(),
-                label: "Protein (g)".to_string(),
-                value: format!("{:.0}", total.protein),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "protein_avg".to_string(),
-                label: "Protein/Day".to_string(),
-                value: format!("{:.0}g", avg_protein),
+    let mut trends = TrendBuilder::new(&FOOD_LAYOUT);
+    trends.card(
+        "calories",
+        "Calories",
+        format!("{:.0}", total.calories),
+        total.calories,
+        CardFormat::Number,
+    );
+    trends.card(
+        "calories_avg",
+        "Calories/Day",
+        format!("{:.0}", avg_calories),
+        avg_calories,
+        CardFormat::Number,
+    );
+    trends.card(
+        "protein",
+        "Protein (g)",
+        format!("{:.0}", total.protein),
+        total.protein,
+        CardFormat::Grams,
+    );
+    trends.card(
+        "protein_avg",
+        "Protein/Day",
+        format!("{:.0}g", avg_protein),
+        avg_protein,
+        CardFormat::Grams,
+    );
+    trends.card(
+        "meals",
+        "Meals",
+        format!("{meals_count}"),
+        meals_count as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "workouts",
+        "Workouts",
+        format!("{workout_count}"),
+        workout_count as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "workout_minutes",
+        "Workout Minutes",
+        format!("{workout_minutes:.0}"),
+        workout_minutes,
+        CardFormat::Duration,
+    );
+    trends.card(
+        "workout_minutes_avg",
+        "Minutes/Day",
+        format!("{avg_workout_minutes:.0}"),
+        avg_workout_minutes,
+        CardFormat::Duration,
+    );
+    trends.card(
+        "workout_distance",
+        "Distance (km)",
+        format!("{workout_km:.1}"),
+        workout_km,
+        CardFormat::Number,
+    );
+    trends.card(
+        "calories_burned",
+        "Calories Burned",
+        format!("{workout_calories:.0}"),
+        workout_calories,
+        CardFormat::Number,
+    );
+    goal_cards(&goals, &mut trends);
+    trends.list(
+        "macros",
+        "Macro Totals",
+        vec![
+            TrendListItem {
+                label: "Carbs".to_string(),
+                value: format!("{:.0}g", total.carbs),
                 sub_label: None,
             },
-            TrendCard {
-                id: "meals".to_string(),
-                label: "Meals".to_string(),
-                value: format!("{meals_count}"),
+            TrendListItem {
+                label: "Fat".to_string(),
+                value: format!("{:.0}g", total.fat),
                 sub_label: None,
             },
-            TrendCard {
-                id: "workouts".to_string(),
-                label: "Workouts".to_string(),
-                value: format!("{workout_count}"),
+            TrendListItem {
+                label: "Fiber".to_string(),
+                value: format!("{:.0}g", total.fiber),
                 sub_label: None,
             },
         ],
-        lists: vec![
-            TrendList {
-                id: "macros".to_string(),
-                title: "Macro Totals".to_string(),
-                items: vec![
-                    TrendListItem {
-                        label: "Carbs".to_string(),
-                        value: format!("{:.0}g", total.carbs),
-                        sub_label: None,
-                    },
-                    TrendListItem {
-                        label: "Fat".to_string(),
-                        value: format!("{:.0}g", total.fat),
-                        sub_label: None,
-                    },
-                    TrendListItem {
-                        label: "Fiber".to_string(),
-                        value: format!("{:.0}g", total.fiber),
-                        sub_label: None,
-                    },
-                ],
-            },
-            TrendList {
-                id: "top_foods".to_string(),
-                title: "Top Foods".to_string(),
-                items: top_food_items,
-            },
-        ],
-        series: None,
+    );
+    trends.list("top_foods", "Top Foods", top_food_items);
+    trends.list("workout_types", "Workouts by Type", workout_type_items);
+    trends.list(
+        "activity_minutes",
+        "Minutes by Activity",
+        activity_minute_items,
+    );
+    let series = vec![
+        trend_series(
+            "calories",
+            "Calories",
+            "kcal",
+            period,
+            daily.iter().map(|(date, day)| (*date, day.calories)),
+        ),
+        trend_series(
+            "protein",
+            "Protein",
+            "g",
+            period,
+            daily.iter().map(|(date, day)| (*date, day.protein)),
+        ),
+    ];
+
+    let (cards, lists) = trends.finish();
+    DomainTrendSnapshot {
+        domain_id: domain_id.to_string(),
+        range: range.to_string(),
+        updated_at: Utc::now().to_rfc3339(),
+        cards,
+        lists,
+        series: Some(series),
+        roots,
+        previous: None,
+        diagnostics: goals.diagnostics,
     }
 }
 
 fn build_media_snapshot(
     domain_id: &str,
     range: &str,
-    today: NaiveDate,
-    start_date: Option<NaiveDate>,
-    root: &Path,
+    period: TrendPeriod,
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
 ) -> DomainTrendSnapshot {
-    let items = load_media_items(root);
+    let (items, roots) = load_from_vaults(vaults, date_order, "items", load_media_items, |item| {
+        item.title.as_str()
+    });
+    let items = qualify_duplicate_titles(items, |item| &mut item.title);
     let mut completed = 0;
     let mut rating_sum = 0.0;
     let mut rating_count = 0;
     let mut recent_items = Vec::new();
     let mut top_rated_items = Vec::new();
     let mut backlog = 0;
+    let mut completions = Vec::new();
 
     for item in items {
         if matches!(item.status.as_deref(), Some("Backlog")) {
             backlog += 1;
         }
         if let Some(completed_at) = item.completed_at {
-            if in_range(completed_at, start_date, today) {
+            if period.contains(completed_at) {
                 completed += 1;
+                completions.push((completed_at, 1.0));
                 if let Some(rating) = item.rating {
                     rating_sum += rating;
                     rating_count += 1;
@@ -535,60 +1031,71 @@ fn build_media_snapshot(
         })
         .collect::<Vec<_>>();
 
+    let mut trends = TrendBuilder::new(&MEDIA_LAYOUT);
+    trends.card(
+        "completed",
+        "Completed",
+        format!("{completed}"),
+        completed as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "avg_rating",
+        "Avg Rating",
+        format!("{:.1}", avg_rating),
+        avg_rating,
+        CardFormat::Number,
+    );
+    trends.card(
+        "rated",
+        "Rated",
+        format!("{rating_count}"),
+        rating_count as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "backlog",
+        "Backlog",
+        format!("{backlog}"),
+        backlog as f64,
+        CardFormat::Count,
+    );
+    trends.list("recent_media", "Recent Completions", recent_items);
+    trends.list("top_rated", "Top Rated", top_rated_list);
+    let series = vec![trend_series(
+        "completed",
+        "Completed",
+        "items",
+        period,
+        completions,
+    )];
+
+    let (cards, lists) = trends.finish();
     DomainTrendSnapshot {
         domain_id: domain_id.to_string(),
         range: range.to_string(),
         updated_at: Utc::now().to_rfc3339(),
-        cards: vec![
-            TrendCard {
-                id: "completed".to_string(),
-                label: "Completed".to_string(),
-                value: format!("{completed}"),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "avg_rating".to_string(),
-                label: "Avg Rating".to_string(),
-                value: format!("{:.1}", avg_rating),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "rated".to_string(),
-                label: "Rated".to_string(),
-                value: format!("{rating_count}"),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "backlog".to_string(),
-                label: "Backlog".to_string(),
-                value: format!("{backlog}"),
-                sub_label: None,
-            },
-        ],
-        lists: vec![
-            TrendList {
-                id: "recent_media".to_string(),
-                title: "Recent Completions".to_string(),
-                items: recent_items,
-            },
-            TrendList {
-                id: "top_rated".to_string(),
-                title: "Top Rated".to_string(),
-                items: top_rated_list,
-            },
-        ],
-        series: None,
+        cards,
+        lists,
+        series: Some(series),
+        roots,
+        previous: None,
+        diagnostics: Vec::new(),
     }
 }
 
 fn build_youtube_snapshot(
     domain_id: &str,
     range: &str,
-    today: NaiveDate,
-    start_date: Option<NaiveDate>,
-    root: &Path,
+    period: TrendPeriod,
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
 ) -> DomainTrendSnapshot {
-    let ideas = load_youtube_items(root);
+    let (ideas, roots) =
+        load_from_vaults(vaults, date_order, "ideas", load_youtube_items, |idea| {
+            idea.title.as_str()
+        });
+    let ideas = qualify_duplicate_titles(ideas, |idea| &mut idea.title);
     let mut created_count = 0;
     let mut stage_counts: HashMap<String, usize> = HashMap::new();
     let mut tier_counts: HashMap<String, usize> = HashMap::new();
@@ -596,6 +1103,7 @@ fn build_youtube_snapshot(
     let mut ready_count = 0;
     let mut published_count = 0;
     let mut newest_items: Vec<(NaiveDate, String, Option<String>)> = Vec::new();
+    let mut created_dates = Vec::new();
 
     for idea in ideas {
         total += 1;
@@ -614,8 +1122,9 @@ fn build_youtube_snapshot(
         }
         if let Some(created) = idea.created_at {
             newest_items.push((created, idea.title.clone(), idea.stage.clone()));
-            if in_range(created, start_date, today) {
+            if period.contains(created) {
                 created_count += 1;
+                created_dates.push((created, 1.0));
             }
         }
     }
@@ -653,57 +1162,122 @@ fn build_youtube_snapshot(
         })
         .collect();
 
+    let mut trends = TrendBuilder::new(&YOUTUBE_LAYOUT);
+    trends.card(
+        "created",
+        "Ideas Created",
+        format!("{created_count}"),
+        created_count as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "total",
+        "Total Ideas",
+        format!("{total}"),
+        total as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "ready",
+        "Ready",
+        format!("{ready_count}"),
+        ready_count as f64,
+        CardFormat::Count,
+    );
+    trends.card(
+        "published",
+        "Published",
+        format!("{published_count}"),
+        published_count as f64,
+        CardFormat::Count,
+    );
+    trends.list("stages", "Stages", stage_list);
+    trends.list("tiers", "Tiers", tier_list);
+    trends.list("newest", "Newest Ideas", newest_list);
+    let series = vec![trend_series(
+        "created",
+        "Ideas Created",
+        "ideas",
+        period,
+        created_dates,
+    )];
+
+    let (cards, lists) = trends.finish();
     DomainTrendSnapshot {
         domain_id: domain_id.to_string(),
         range: range.to_string(),
         updated_at: Utc::now().to_rfc3339(),
-        cards: vec![
-            TrendCard {
-                id: "created".to_string(),
-                label: "Ideas Created".to_string(),
-                value: format!("{created_count}"),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "total".to_string(),
-                label: "Total Ideas".to_string(),
-                value: format!("{total}"),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "ready".to_string(),
-                label: "Ready".to_string(),
-                value: format!("{ready_count}"),
-                sub_label: None,
-            },
-            TrendCard {
-                id: "published".to_string(),
-                label: "Published".to_string(),
-                value: format!("{published_count}"),
-                sub_label: None,
-            },
-        ],
-        lists: vec![
-            TrendList {
-                id: "stages".to_string(),
-                title: "Stages".to_string(),
-                items: stage_list,
-            },
-            TrendList {
-                id: "tiers".to_string(),
-                title: "Tiers".to_string(),
-                items: tier_list,
-            },
-            TrendList {
-                id: "newest".to_string(),
-                title: "Newest Ideas".to_string(),
-                items: newest_list,
-            },
-        ],
-        series: None,
+        cards,
+        lists,
+        series: Some(series),
+        roots,
+        previous: None,
+        diagnostics: Vec::new(),
     }
 }
 
+/// Runs `load` for each vault, tagging items with their vault name and
+/// recording per-vault counts under `count_key`.
+fn load_from_vaults<T>(
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
+    count_key: &str,
+    load: fn(&Path, &mut DateReader) -> Vec<T>,
+    title: fn(&T) -> &str,
+) -> (Vec<(String, String, T)>, Vec<VaultContribution>) {
+    let mut items = Vec::new();
+    let mut roots = Vec::new();
+    for vault in vaults {
+        let mut dates = DateReader::new(date_order);
+        let loaded = load(&vault.path, &mut dates);
+        roots.push(contribution_with_dates(
+            vault,
+            &[(count_key, loaded.len())],
+            &dates,
+        ));
+        items.extend(
+            loaded
+                .into_iter()
+                .map(|item| (vault.name.clone(), title(&item).to_string(), item)),
+        );
+    }
+    (items, roots)
+}
+
+/// `vault_contribution` plus the vault's unparseable dates, when there are any.
+fn contribution_with_dates(
+    vault: &VaultRoot,
+    counts: &[(&str, usize)],
+    dates: &DateReader,
+) -> VaultContribution {
+    let mut contribution = vault_contribution(vault, counts);
+    if dates.invalid() > 0 {
+        contribution
+            .counts
+            .insert(INVALID_DATES_KEY.to_string(), dates.invalid());
+    }
+    contribution
+}
+
+/// Prefixes titles that occur in more than one vault with the vault name.
+fn qualify_duplicate_titles<T>(
+    items: Vec<(String, String, T)>,
+    title: fn(&mut T) -> &mut String,
+) -> Vec<T> {
+    let duplicates = names_in_several_vaults(
+        items
+            .iter()
+            .map(|(vault, name, _)| (vault.as_str(), name.as_str())),
+    );
+    items
+        .into_iter()
+        .map(|(vault, name, mut item)| {
+            *title(&mut item) = display_name(&vault, &name, &duplicates);
+            item
+        })
+        .collect()
+}
+
 fn load_stream_entries(root: &Path) -> Vec<StreamEntry> {
     let stream_dir = root.join("Stream");
     if !stream_dir.exists() {
@@ -740,7 +1314,12 @@ fn parse_stream_file(content: &str, year: Option<i32>) -> Vec<StreamEntry> {
         };
         if let Some(text) = extract_entry_text(line) {
             let links = extract_links(&text);
-            entries.push(StreamEntry { date, text, links });
+            entries.push(StreamEntry {
+                date,
+                text,
+                links,
+                vault: 0,
+            });
         }
     }
     entries
@@ -864,15 +1443,21 @@ fn strip_time_prefix(value: &str) -> Option<String> {
     Some(rest)
 }
 
-fn extract_links(text: &str) -> Vec<String> {
+fn extract_links(text: &str) -> Vec<StreamLink> {
     let mut links = Vec::new();
     let mut remaining = text;
     while let Some(start) = remaining.find("[[") {
+        let before = &remaining[..start];
         let after = &remaining[start + 2..];
         if let Some(end) = after.find("]]") {
             let link = &after[..end];
-            links.push(link.trim().to_string());
             remaining = &after[end + 2..];
+            links.push(StreamLink {
+                target: link.trim().to_string(),
+                servings: multiplier_after(remaining)
+                    .or_else(|| multiplier_before(before))
+                    .unwrap_or(1.0),
+            });
         } else {
             break;
         }
@@ -880,6 +1465,57 @@ fn extract_links(text: &str) -> Vec<String> {
     links
 }
 
+const MULTIPLIER_SIGNS: [char; 3] = ['x', 'X', '×'];
+
+/// `x2`, `x1.5` or `(x2)` at the start of `text`.
+fn multiplier_after(text: &str) -> Option<f64> {
+    let text = text.trim_start();
+    let (text, parenthesized) = match text.strip_prefix('(') {
+        Some(inner) => (inner.trim_start(), true),
+        None => (text, false),
+    };
+    let text = text.strip_prefix(MULTIPLIER_SIGNS)?;
+    let end = text
+        .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .unwrap_or(text.len());
+    let (number, rest) = text.split_at(end);
+    if parenthesized {
+        rest.trim_start().strip_prefix(')')?;
+    } else if rest.starts_with(char::is_alphanumeric) {
+        return None;
+    }
+    parse_servings(number)
+}
+
+/// `2x` or `1.5x` at the end of `text`.
+fn multiplier_before(text: &str) -> Option<f64> {
+    let text = text.trim_end().strip_suffix(MULTIPLIER_SIGNS)?;
+    let start = text
+        .rfind(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .map(|index| index + text[index..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let (rest, number) = text.split_at(start);
+    if rest.ends_with(char::is_alphanumeric) {
+        return None;
+    }
+    parse_servings(number)
+}
+
+fn parse_servings(number: &str) -> Option<f64> {
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|servings| servings.is_finite() && *servings > 0.0)
+}
+
+fn format_servings(servings: f64) -> String {
+    if servings.fract() == 0.0 {
+        format!("{servings:.0}")
+    } else {
+        format!("{servings:.1}")
+    }
+}
+
 fn load_food_map(root: &Path) -> HashMap<String, Nutrition> {
     let mut map = HashMap::new();
     let dir = root.join("Entities").join("Food");
@@ -966,7 +1602,7 @@ fn nutrition_from_table(body: &str) -> Nutrition {
     nutrition
 }
 
-fn load_delivery_sessions(root: &Path) -> Vec<DeliverySession> {
+fn load_delivery_sessions(root: &Path, dates: &mut DateReader) -> Vec<DeliverySession> {
     let mut sessions = Vec::new();
     let dir = root.join("Entities").join("Delivery").join("Sessions");
     if !dir.exists() {
@@ -986,13 +1622,18 @@ fn load_delivery_sessions(root: &Path) -> Vec<DeliverySession> {
             if let Some(frontmatter) = frontmatter {
                 if let Ok(parsed) = serde_yaml::from_str::<DeliverySessionFrontmatter>(&frontmatter)
                 {
-                    if let Some(date) = parse_date(&parsed.date) {
+                    // Sessions without a usable date can't be placed in a range;
+                    // the reader counts them.
+                    if let Some(date) = dates.read(Some(&parsed.date)) {
                         sessions.push(DeliverySession {
                             date,
                             earnings: parsed.earnings.unwrap_or(0.0),
                             hours: parsed.hours.unwrap_or(0.0),
                             mileage: parsed.mileage.unwrap_or(0.0),
                             orders: parsed.orders_count.unwrap_or(0.0),
+                            platform: parsed.platform,
+                            tips: parsed.tips,
+                            base_pay: parsed.base_pay,
                         });
                     }
                 }
@@ -1002,7 +1643,7 @@ fn load_delivery_sessions(root: &Path) -> Vec<DeliverySession> {
     sessions
 }
 
-fn load_bills(root: &Path) -> Vec<Bill> {
+fn load_bills(root: &Path, dates: &mut DateReader) -> Vec<Bill> {
     let mut bills = Vec::new();
     let dir = root.join("Entities").join("Finance").join("Bills");
     if !dir.exists() {
@@ -1029,7 +1670,12 @@ fn load_bills(root: &Path) -> Vec<Bill> {
                                 .to_string()
                         }),
                         amount: parsed.amount.unwrap_or(0.0),
-                        next_due: parsed.next_due.and_then(|d| parse_date(&d)),
+                        next_due: dates.read(parsed.next_due.as_deref()),
+                        recurrence: parsed
+                            .recurrence
+                            .or(parsed.frequency)
+                            .as_deref()
+                            .and_then(Recurrence::parse),
                     });
                 }
             }
@@ -1038,7 +1684,7 @@ fn load_bills(root: &Path) -> Vec<Bill> {
     bills
 }
 
-fn load_media_items(root: &Path) -> Vec<MediaItem> {
+fn load_media_items(root: &Path, dates: &mut DateReader) -> Vec<MediaItem> {
     let mut items = Vec::new();
     let dir = root.join("Entities").join("Media");
     if !dir.exists() {
@@ -1066,7 +1712,7 @@ fn load_media_items(root: &Path) -> Vec<MediaItem> {
                         }),
                         status: parsed.status,
                         rating: parsed.rating,
-                        completed_at: parsed.completed_at.and_then(|d| parse_date(&d)),
+                        completed_at: dates.read(parsed.completed_at.as_deref()),
                     });
                 }
             }
@@ -1075,7 +1721,7 @@ fn load_media_items(root: &Path) -> Vec<MediaItem> {
     items
 }
 
-fn load_youtube_items(root: &Path) -> Vec<YoutubeIdea> {
+fn load_youtube_items(root: &Path, dates: &mut DateReader) -> Vec<YoutubeIdea> {
     let mut items = Vec::new();
     let dir = root.join("Entities").join("YouTube");
     if !dir.exists() {
@@ -1103,8 +1749,8 @@ fn load_youtube_items(root: &Path) -> Vec<YoutubeIdea> {
                         }),
                         tier: parsed.tier,
                         stage: parsed.stage,
-                        created_at: parsed.created_at.and_then(|d| parse_date(&d)),
-                        updated_at: parsed.updated_at.and_then(|d| parse_date(&d)),
+                        created_at: dates.read(parsed.created_at.as_deref()),
+                        updated_at: dates.read(parsed.updated_at.as_deref()),
                     });
                 }
             }
@@ -1145,16 +1791,6 @@ fn split_frontmatter(content: &str) -> (Option<String>, String) {
     (frontmatter, body)
 }
 
-fn parse_date(value: &str) -> Option<NaiveDate> {
-    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
-        return Some(date);
-    }
-    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(value) {
-        return Some(datetime.date_naive());
-    }
-    None
-}
-
 fn parse_number(value: &str) -> f64 {
     let cleaned: String = value
         .chars()
@@ -1170,14 +1806,6 @@ fn food_link_name(link: &str) -> Option<String> {
     Some(link.to_string())
 }
 
-fn in_range(date: NaiveDate, start: Option<NaiveDate>, end: NaiveDate) -> bool {
-    if let Some(start) = start {
-        date >= start && date <= end
-    } else {
-        date <= end
-    }
-}
-
 fn month_number(month: &str) -> Option<u32> {
     match month.to_lowercase().as_str() {
         "jan" => Some(1),
@@ -1214,6 +1842,9 @@ struct DeliverySessionFrontmatter {
     mileage: Option<f64>,
     #[serde(rename = "orders_count")]
     orders_count: Option<f64>,
+    platform: Option<String>,
+    tips: Option<f64>,
+    base_pay: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1221,6 +1852,9 @@ struct BillFrontmatter {
     name: Option<String>,
     amount: Option<f64>,
     next_due: Option<String>,
+    recurrence: Option<String>,
+    /// The field the bill editor writes; `recurrence` wins when both are set.
+    frequency: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1239,3 +1873,451 @@ struct YoutubeFrontmatter {
     created_at: Option<String>,
     updated_at: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        extract_links, lock_trend_cache, prewarm_domain_trends, trend_cache,
+        workspace_domain_trends, TrendCacheEntry,
+    };
+    use crate::types::{
+        DomainTrendSnapshot, WorkspaceEntry, WorkspaceKind, WorkspacePurpose, WorkspaceSettings,
+    };
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::panic::AssertUnwindSafe;
+    use std::path::Path;
+    use std::sync::Mutex;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, 10).unwrap()
+    }
+
+    /// A vault with Stream notes, a food and three delivery sessions.
+    fn write_fixture_vault(vault: &Path) {
+        let sessions = vault.join("Entities").join("Delivery").join("Sessions");
+        fs::create_dir_all(vault.join("Stream")).unwrap();
+        fs::create_dir_all(vault.join("Entities").join("Food")).unwrap();
+        fs::create_dir_all(&sessions).unwrap();
+        fs::write(
+            vault.join("Stream").join("2026-01.md"),
+            "## Friday Jan 9\n**9:00am** | Breakfast [[Oatmeal]]\n## Saturday Jan 10\n**8:00am** | Breakfast [[Oatmeal]]\n**1:00pm** | Lunch [[Food/Oatmeal]]\n",
+        )
+        .unwrap();
+        fs::write(
+            vault.join("Entities").join("Food").join("Oatmeal.md"),
+            "---\ncalories: 300\nprotein: 10\n---\n",
+        )
+        .unwrap();
+        for (name, date, earnings) in [
+            ("a", "2026-01-10", 80.5),
+            ("b", "2026-01-08", 40.0),
+            ("c", "2025-12-20", 25.0),
+        ] {
+            fs::write(
+                sessions.join(format!("{name}.md")),
+                format!("---\ndate: {date}\nearnings: {earnings}\n---\n"),
+            )
+            .unwrap();
+        }
+    }
+
+    fn life_entry(workspace: &Path) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: "life".to_string(),
+            name: "Life".to_string(),
+            path: workspace.to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    fn card(snapshot: &DomainTrendSnapshot, id: &str) -> Option<f64> {
+        snapshot
+            .cards
+            .iter()
+            .find(|card| card.id == id)
+            .and_then(|card| card.value_num)
+    }
+
+    fn series(snapshot: &DomainTrendSnapshot, id: &str) -> Vec<(String, f64)> {
+        snapshot
+            .series
+            .iter()
+            .flatten()
+            .find(|series| series.id == id)
+            .map(|series| {
+                series
+                    .points
+                    .iter()
+                    .map(|point| (point.date.clone(), point.value))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn workspace_trends_read_the_obsidian_root() {
+        let dir = tempdir().expect("tempdir");
+        let workspace = dir.path().join("workspace");
+        let vault = dir.path().join("vault");
+        fs::create_dir_all(&workspace).unwrap();
+        write_fixture_vault(&vault);
+
+        let mut entry = life_entry(&workspace);
+        let trends = |entry: &WorkspaceEntry| {
+            workspace_domain_trends(entry, "food_exercise", "7d", false, false, today())
+                .expect("trends")
+        };
+        assert_eq!(card(&trends(&entry), "meals"), Some(0.0));
+
+        entry.settings.obsidian_root = Some(vault.to_string_lossy().to_string());
+        let snapshot = trends(&entry);
+        assert_eq!(card(&snapshot, "meals"), Some(3.0));
+        assert_eq!(card(&snapshot, "calories"), Some(900.0));
+        assert_eq!(card(&snapshot, "protein"), Some(30.0));
+    }
+
+    #[test]
+    fn deleting_a_note_invalidates_the_cached_snapshot() {
+        let dir = tempdir().expect("tempdir");
+        write_fixture_vault(dir.path());
+        let entry = life_entry(dir.path());
+        let trends = |force_refresh: bool| {
+            workspace_domain_trends(
+                &entry,
+                "delivery_finance",
+                "7d",
+                false,
+                force_refresh,
+                today(),
+            )
+            .expect("trends")
+        };
+
+        let first = trends(false);
+        assert_eq!(card(&first, "earnings"), Some(120.5));
+        assert_eq!(trends(false).updated_at, first.updated_at);
+        assert_ne!(trends(true).updated_at, first.updated_at);
+
+        let sessions = dir
+            .path()
+            .join("Entities")
+            .join("Delivery")
+            .join("Sessions");
+        fs::remove_file(sessions.join("a.md")).unwrap();
+        assert_eq!(card(&trends(false), "earnings"), Some(40.0));
+
+        let cached = trends(false);
+        fs::rename(sessions.join("b.md"), sessions.join("renamed.md")).unwrap();
+        assert_ne!(trends(false).updated_at, cached.updated_at);
+
+        // An edit of the same size is only seen through the mtime, which a
+        // coarse-mtime filesystem may leave unchanged; move it explicitly.
+        let renamed = sessions.join("renamed.md");
+        fs::write(&renamed, "---\ndate: 2026-01-08\nearnings: 45\n---\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&renamed)
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(60)))
+            .unwrap();
+        assert_eq!(card(&trends(false), "earnings"), Some(45.0));
+    }
+
+    #[test]
+    fn delivery_cards_show_the_change_against_the_previous_period() {
+        let dir = tempdir().expect("tempdir");
+        let sessions = dir
+            .path()
+            .join("Entities")
+            .join("Delivery")
+            .join("Sessions");
+        fs::create_dir_all(&sessions).unwrap();
+        for (name, frontmatter) in [
+            (
+                "current",
+                "date: 2026-01-09\nearnings: 120\nhours: 4\nmileage: 60\norders_count: 10",
+            ),
+            (
+                "previous",
+                "date: 2026-01-02\nearnings: 100\nhours: 5\nmileage: 40\norders_count: 8",
+            ),
+        ] {
+            fs::write(
+                sessions.join(format!("{name}.md")),
+                format!("---\n{frontmatter}\n---\n"),
+            )
+            .unwrap();
+        }
+        let entry = life_entry(dir.path());
+        let sub_labels = |range: &str| {
+            let snapshot =
+                workspace_domain_trends(&entry, "delivery_finance", range, false, false, today())
+                    .expect("trends");
+            snapshot
+                .cards
+                .into_iter()
+                .map(|card| (card.id, card.sub_label))
+                .collect::<std::collections::HashMap<_, _>>()
+        };
+
+        let week = sub_labels("7d");
+        let label = |id: &str| week[id].as_deref();
+        assert_eq!(label("earnings"), Some("+$20.00 (20%)"));
+        assert_eq!(label("hours"), Some("−1.0 hrs (20%)"));
+        assert_eq!(label("hourly"), Some("+$10.00 (50%)"));
+        assert_eq!(label("per_mile"), Some("−$0.50 (20%)"));
+        assert_eq!(label("orders"), Some("+2 (25%)"));
+        assert_eq!(label("sessions"), Some("no change"));
+        assert_eq!(label("bills_due"), None);
+
+        assert_eq!(sub_labels("30d")["earnings"].as_deref(), Some("new"));
+        assert!(sub_labels("lifetime").values().all(Option::is_none));
+    }
+
+    #[test]
+    fn links_carry_the_servings_written_next_to_them() {
+        let servings = |text: &str| {
+            extract_links(text)
+                .into_iter()
+                .map(|link| (link.target, link.servings))
+                .collect::<Vec<_>>()
+        };
+        let link = |target: &str, servings: f64| (target.to_string(), servings);
+        assert_eq!(
+            servings("Lunch [[Food/Chicken Breast]] x2"),
+            [link("Food/Chicken Breast", 2.0)]
+        );
+        assert_eq!(
+            servings("2x [[Food/Oatmeal]], [[Rice]] (x1.5) and [[Tea]]"),
+            [
+                link("Food/Oatmeal", 2.0),
+                link("Rice", 1.5),
+                link("Tea", 1.0)
+            ]
+        );
+        assert_eq!(
+            servings("[[Eggs]] x3 [[Toast]] xylophone [[Jam]]x0.5"),
+            [link("Eggs", 3.0), link("Toast", 1.0), link("Jam", 0.5)]
+        );
+        assert_eq!(servings("box [[Juice]] x0"), [link("Juice", 1.0)]);
+    }
+
+    #[test]
+    fn multiplied_foods_count_as_several_servings() {
+        let dir = tempdir().expect("tempdir");
+        let foods = dir.path().join("Entities").join("Food");
+        fs::create_dir_all(dir.path().join("Stream")).unwrap();
+        fs::create_dir_all(&foods).unwrap();
+        fs::write(
+            dir.path().join("Stream").join("2026-01.md"),
+            "## Saturday Jan 10\n**12:30pm** | Lunch 2x [[Food/Chicken Breast]] with [[Food/Rice]] (x1.5)\n**6:00pm** | Dinner [[Food/Rice]]\n",
+        )
+        .unwrap();
+        fs::write(
+            foods.join("Chicken Breast.md"),
+            "---\ncalories: 200\nprotein: 40\n---\n",
+        )
+        .unwrap();
+        fs::write(
+            foods.join("Rice.md"),
+            "---\ncalories: 100\nprotein: 2\n---\n",
+        )
+        .unwrap();
+
+        let snapshot = workspace_domain_trends(
+            &life_entry(dir.path()),
+            "food_exercise",
+            "7d",
+            false,
+            false,
+            today(),
+        )
+        .expect("trends");
+        assert_eq!(card(&snapshot, "calories"), Some(650.0));
+        assert_eq!(card(&snapshot, "protein"), Some(85.0));
+        assert_eq!(card(&snapshot, "meals"), Some(2.0));
+        let top_foods: Vec<_> = snapshot
+            .lists
+            .iter()
+            .find(|list| list.id == "top_foods")
+            .expect("top foods")
+            .items
+            .iter()
+            .map(|item| (item.label.as_str(), item.value.as_str()))
+            .collect();
+        assert_eq!(top_foods, [("Rice", "2.5"), ("Chicken Breast", "2")]);
+    }
+
+    #[test]
+    fn stream_workouts_add_their_minutes_by_activity() {
+        let dir = tempdir().expect("tempdir");
+        fs::create_dir_all(dir.path().join("Stream")).unwrap();
+        fs::write(
+            dir.path().join("Stream").join("2026-01.md"),
+            "## Friday Jan 9\n**7:00am** | Morning Run 45 min\n**6:00pm** | 🚶 Walk 1.5 hr\n## Saturday Jan 10\n**9:00am** | 🏋️ Workout: squats 30m\n**5:00pm** | Evening walk\n**8:00pm** | Sunday brunch 40 min\n",
+        )
+        .unwrap();
+
+        let snapshot = workspace_domain_trends(
+            &life_entry(dir.path()),
+            "food_exercise",
+            "7d",
+            false,
+            false,
+            today(),
+        )
+        .expect("trends");
+        assert_eq!(card(&snapshot, "workouts"), Some(4.0));
+        assert_eq!(card(&snapshot, "workout_minutes"), Some(165.0));
+        assert_eq!(card(&snapshot, "workout_minutes_avg"), Some(165.0 / 7.0));
+        let by_activity: Vec<_> = snapshot
+            .lists
+            .iter()
+            .find(|list| list.id == "activity_minutes")
+            .expect("minutes by activity")
+            .items
+            .iter()
+            .map(|item| {
+                (
+                    item.label.as_str(),
+                    item.value.as_str(),
+                    item.sub_label.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            by_activity,
+            [
+                ("walk", "90 min", Some("2 workouts")),
+                ("run", "45 min", Some("1 workout")),
+                ("lift", "30 min", Some("1 workout")),
+            ]
+        );
+    }
+
+    #[test]
+    fn series_have_a_point_for_every_day_of_the_range() {
+        let dir = tempdir().expect("tempdir");
+        write_fixture_vault(dir.path());
+        let entry = life_entry(dir.path());
+        let trends = |domain: &str, range: &str| {
+            workspace_domain_trends(&entry, domain, range, false, false, today()).expect("trends")
+        };
+
+        let week = series(&trends("delivery_finance", "7d"), "earnings");
+        let values: Vec<f64> = week.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, [0.0, 0.0, 0.0, 0.0, 40.0, 0.0, 80.5]);
+        assert_eq!(week[0].0, "2026-01-04");
+        assert_eq!(week[6].0, "2026-01-10");
+
+        let month = series(&trends("delivery_finance", "30d"), "earnings");
+        assert_eq!(month.len(), 30);
+        assert_eq!(month[0].0, "2025-12-12");
+        assert_eq!(month[8], ("2025-12-20".to_string(), 25.0));
+        assert_eq!(month.iter().map(|(_, value)| value).sum::<f64>(), 145.5);
+
+        let food = trends("food_exercise", "7d");
+        let calories = series(&food, "calories");
+        assert_eq!(calories.len(), 7);
+        assert_eq!(calories[5], ("2026-01-09".to_string(), 300.0));
+        assert_eq!(calories[6], ("2026-01-10".to_string(), 600.0));
+        assert_eq!(series(&food, "protein")[6].1, 20.0);
+        assert_eq!(
+            series(&trends("food_exercise", "30d"), "calories").len(),
+            30
+        );
+
+        let media = series(&trends("media", "7d"), "completed");
+        assert_eq!(media.len(), 7);
+        assert!(media.iter().all(|(_, value)| *value == 0.0));
+    }
+
+    #[test]
+    fn a_poisoned_trend_cache_is_cleared_and_usable_again() {
+        let cache = Mutex::new(HashMap::new());
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut guard = lock_trend_cache(&cache);
+            guard.insert(
+                "stale".to_string(),
+                TrendCacheEntry {
+                    fingerprint: 1,
+                    today: today(),
+                    snapshot: DomainTrendSnapshot {
+                        domain_id: "finance".to_string(),
+                        range: "7d".to_string(),
+                        updated_at: String::new(),
+                        cards: Vec::new(),
+                        lists: Vec::new(),
+                        series: None,
+                        roots: Vec::new(),
+                        previous: None,
+                        diagnostics: Vec::new(),
+                    },
+                },
+            );
+            panic!("builder panicked while holding the cache");
+        }));
+        assert!(cache.is_poisoned());
+
+        assert!(lock_trend_cache(&cache).is_empty());
+        assert!(!cache.is_poisoned());
+    }
+
+    #[test]
+    fn a_new_day_replaces_the_cached_snapshot() {
+        let dir = tempdir().expect("tempdir");
+        write_fixture_vault(dir.path());
+        let entry = life_entry(dir.path());
+        let cached_days = || -> Vec<NaiveDate> {
+            trend_cache()
+                .iter()
+                .filter(|(key, _)| key.starts_with(&format!("{}::", entry.path)))
+                .map(|(_, cached)| cached.today)
+                .collect()
+        };
+
+        workspace_domain_trends(&entry, "finance", "7d", false, false, today()).expect("today");
+        assert_eq!(cached_days(), vec![today()]);
+        let tomorrow = today().succ_opt().unwrap();
+        workspace_domain_trends(&entry, "finance", "7d", false, false, tomorrow).expect("tomorrow");
+        assert_eq!(cached_days(), vec![tomorrow]);
+    }
+
+    #[tokio::test]
+    async fn prewarmed_snapshots_serve_the_next_request() {
+        let dir = tempdir().expect("tempdir");
+        write_fixture_vault(dir.path());
+        let mut entry = life_entry(dir.path());
+        entry.settings.purpose = Some(WorkspacePurpose::Life);
+        entry.settings.domain_id = Some("delivery_finance".to_string());
+
+        for task in prewarm_domain_trends(&entry, today()) {
+            task.await.expect("prewarm task");
+        }
+        let prewarmed: HashMap<String, String> = trend_cache()
+            .iter()
+            .filter(|(key, _)| key.starts_with(&format!("{}::", entry.path)))
+            .map(|(_, cached)| {
+                (
+                    cached.snapshot.range.clone(),
+                    cached.snapshot.updated_at.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(prewarmed.len(), 2);
+
+        for range in ["7d", "30d"] {
+            let snapshot =
+                workspace_domain_trends(&entry, "delivery_finance", range, false, false, today())
+                    .expect("trends");
+            assert_eq!(Some(&snapshot.updated_at), prewarmed.get(range));
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Date windows behind the trend range tokens, the period-over-period deltas
+//! added to cards when a snapshot is requested with `compare`, and the
+//! change sub-labels the delivery cards always carry.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::types::{CardFormat, TrendCard, TrendDelta};
+
+/// The dates a snapshot covers. `today` stays the real date so forward-looking
+/// cards (bills due) read the same for a past period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct TrendPeriod {
+    pub(super) start: Option<NaiveDate>,
+    pub(super) end: NaiveDate,
+    pub(super) today: NaiveDate,
+}
+
+impl TrendPeriod {
+    /// `7d`, `30d`, `mtd`, `last_month` and `ytd`; anything else covers all
+    /// notes up to today.
+    pub(super) fn for_range(range: &str, today: NaiveDate) -> Self {
+        let (start, end) = match range {
+            "7d" => (Some(today - Duration::days(6)), today),
+            "30d" => (Some(today - Duration::days(29)), today),
+            "mtd" => (Some(first_of_month(today)), today),
+            "last_month" => {
+                let end = first_of_month(today) - Duration::days(1);
+                (Some(first_of_month(end)), end)
+            }
+            "ytd" => (NaiveDate::from_ymd_opt(today.year(), 1, 1), today),
+            _ => (None, today),
+        };
+        Self { start, end, today }
+    }
+
+    /// The preceding period of the same kind: the 7 or 30 days before, the
+    /// previous month up to the same day, the whole month before last, or
+    /// last year up to the same date. `None` for `all`.
+    pub(super) fn previous(&self, range: &str) -> Option<Self> {
+        let start = self.start?;
+        let (start, end) = match range {
+            "mtd" => {
+                let start = first_of_month(start - Duration::days(1));
+                (start, same_day_in_month(start, self.end.day()))
+            }
+            "last_month" => {
+                let end = start - Duration::days(1);
+                (first_of_month(end), end)
+            }
+            "ytd" => (
+                NaiveDate::from_ymd_opt(start.year() - 1, 1, 1)?,
+                same_date_last_year(self.end),
+            ),
+            _ => {
+                let days = (self.end - start).num_days() + 1;
+                (start - Duration::days(days), start - Duration::days(1))
+            }
+        };
+        Some(Self {
+            start: Some(start),
+            end,
+            today: self.today,
+        })
+    }
+
+    pub(super) fn contains(&self, date: NaiveDate) -> bool {
+        self.start.is_none_or(|start| date >= start) && date <= self.end
+    }
+
+    /// Days covered, or `None` for an open-ended period.
+    pub(super) fn days(&self) -> Option<i64> {
+        self.start
+            .map(|start| (self.end - start).num_days().max(0) + 1)
+    }
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// `day` in the month starting at `month_start`, clamped to its last day.
+fn same_day_in_month(month_start: NaiveDate, day: u32) -> NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|day| month_start.with_day(day))
+        .unwrap_or(month_start)
+}
+
+fn same_date_last_year(date: NaiveDate) -> NaiveDate {
+    date.with_year(date.year() - 1)
+        .or_else(|| NaiveDate::from_ymd_opt(date.year() - 1, date.month(), 28))
+        .unwrap_or(date)
+}
+
+/// Sets `delta` on every card whose current and previous values are both
+/// numbers. A previous value of zero is reported as `new` rather than an
+/// infinite percentage.
+pub(super) fn apply_card_deltas(cards: &mut [TrendCard], previous: &[TrendCard]) {
+    for card in cards {
+        let Some(before) = previous.iter().find(|prior| prior.id == card.id) else {
+            continue;
+        };
+        let (Some(now), Some(then)) = (card_number(card), card_number(before)) else {
+            continue;
+        };
+        let percent = if then == 0.0 {
+            (now == 0.0).then_some(0.0)
+        } else {
+            Some((now - then) / then.abs() * 100.0)
+        };
+        let label = match percent {
+            Some(percent) if percent.round() == 0.0 => "0%".to_string(),
+            Some(percent) => format!("{percent:+.0}%"),
+            None => "new".to_string(),
+        };
+        card.delta = Some(TrendDelta {
+            previous: before.value.clone(),
+            percent,
+            label,
+        });
+    }
+}
+
+/// Sets `sub_label` on the cards named in `ids` to their change against the
+/// previous period, such as `+$42.10 (12%)` or `−1.5 hrs (10%)`.
+pub(super) fn apply_change_sub_labels(
+    cards: &mut [TrendCard],
+    previous: &[TrendCard],
+    ids: &[&str],
+) {
+    for card in cards
+        .iter_mut()
+        .filter(|card| ids.contains(&card.id.as_str()))
+    {
+        let Some(before) = previous.iter().find(|prior| prior.id == card.id) else {
+            continue;
+        };
+        let (Some(now), Some(then)) = (card_number(card), card_number(before)) else {
+            continue;
+        };
+        card.sub_label = Some(change_label(now, then, card.format));
+    }
+}
+
+/// `new` when the previous value was zero, since there is nothing to divide
+/// by.
+fn change_label(now: f64, then: f64, format: Option<CardFormat>) -> String {
+    if now == then {
+        return "no change".to_string();
+    }
+    if then == 0.0 {
+        return "new".to_string();
+    }
+    let change = now - then;
+    let sign = if change < 0.0 { "−" } else { "+" };
+    let amount = match format {
+        Some(CardFormat::Currency) => format!("${:.2}", change.abs()),
+        Some(CardFormat::Duration) => format!("{:.1} hrs", change.abs() / 60.0),
+        _ => format!("{:.0}", change.abs()),
+    };
+    let percent = (change / then.abs() * 100.0).abs();
+    format!("{sign}{amount} ({percent:.0}%)")
+}
+
+/// The card's `value_num`, or the number read out of a value such as
+/// `$1,204.50`, `85%` or `120g` for cards built without one.
+fn card_number(card: &TrendCard) -> Option<f64> {
+    if card.value_num.is_some() {
+        return card.value_num;
+    }
+    let cleaned: String = card
+        .value
+        .trim()
+        .trim_start_matches('$')
+        .trim_end_matches(['%', 'g'])
+        .chars()
+        .filter(|ch| *ch != ',')
+        .collect();
+    cleaned.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_card_deltas, change_label, TrendPeriod};
+    use crate::types::{CardFormat, TrendCard};
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn ranges_and_their_previous_periods() {
+        let today = date(2024, 3, 31);
+        let cases = [
+            (
+                "7d",
+                date(2024, 3, 25),
+                today,
+                date(2024, 3, 18),
+                date(2024, 3, 24),
+            ),
+            (
+                "30d",
+                date(2024, 3, 2),
+                today,
+                date(2024, 2, 1),
+                date(2024, 3, 1),
+            ),
+            (
+                "mtd",
+                date(2024, 3, 1),
+                today,
+                date(2024, 2, 1),
+                date(2024, 2, 29),
+            ),
+            (
+                "last_month",
+                date(2024, 2, 1),
+                date(2024, 2, 29),
+                date(2024, 1, 1),
+                date(2024, 1, 31),
+            ),
+            (
+                "ytd",
+                date(2024, 1, 1),
+                today,
+                date(2023, 1, 1),
+                date(2023, 3, 31),
+            ),
+        ];
+        for (range, start, end, previous_start, previous_end) in cases {
+            let period = TrendPeriod::for_range(range, today);
+            assert_eq!((period.start, period.end), (Some(start), end), "{range}");
+            let previous = period.previous(range).expect("previous period");
+            assert_eq!(
+                (previous.start, previous.end),
+                (Some(previous_start), previous_end),
+                "{range}"
+            );
+            assert_eq!(previous.today, today);
+        }
+        let leap_day = TrendPeriod::for_range("ytd", date(2024, 2, 29));
+        assert_eq!(leap_day.previous("ytd").unwrap().end, date(2023, 2, 28));
+        assert!(TrendPeriod::for_range("all", today)
+            .previous("all")
+            .is_none());
+    }
+
+    #[test]
+    fn deltas_compare_numeric_cards_by_id() {
+        let card = |id: &str, value: &str| TrendCard {
+            id: id.to_string(),
+            label: id.to_string(),
+            value: value.to_string(),
+            sub_label: None,
+            delta: None,
+            order: None,
+            format: None,
+            value_num: None,
+        };
+        let mut cards = vec![
+            card("earnings", "$1,120.00"),
+            card("tips", "12%"),
+            card("workouts", "3"),
+            card("idle", "0"),
+            card("label", "n/a"),
+        ];
+        let previous = vec![
+            card("earnings", "$1,000.00"),
+            card("tips", "16%"),
+            card("workouts", "0"),
+            card("idle", "0"),
+            card("label", "n/a"),
+        ];
+        apply_card_deltas(&mut cards, &previous);
+        let labels: Vec<Option<&str>> = cards
+            .iter()
+            .map(|card| card.delta.as_ref().map(|delta| delta.label.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![Some("+12%"), Some("-25%"), Some("new"), Some("0%"), None]
+        );
+        let earnings = cards[0].delta.as_ref().unwrap();
+        assert_eq!(earnings.previous, "$1,000.00");
+        assert_eq!(cards[2].delta.as_ref().unwrap().percent, None);
+    }
+
+    #[test]
+    fn change_labels_show_amount_and_percent() {
+        let cases = [
+            (142.1, 100.0, Some(CardFormat::Currency), "+$42.10 (42%)"),
+            (90.0, 180.0, Some(CardFormat::Duration), "−1.5 hrs (50%)"),
+            (4.0, 5.0, Some(CardFormat::Count), "−1 (20%)"),
+            (25.0, 0.0, Some(CardFormat::Currency), "new"),
+            (0.0, 0.0, Some(CardFormat::Currency), "no change"),
+            (3.0, 3.0, Some(CardFormat::Count), "no change"),
+        ];
+        for (now, then, format, label) in cases {
+            assert_eq!(change_label(now, then, format), label);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Chart series for trend snapshots: one point per day of the period, with
+//! zeros for days without notes. Open-ended ranges are bucketed by week
+//! instead, starting at the Monday of the earliest dated value.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::types::{TrendPoint, TrendSeries};
+
+use super::periods::TrendPeriod;
+
+/// Sums `values` that fall inside `period` into day (or week) buckets.
+pub(super) fn trend_series(
+    id: &str,
+    label: &str,
+    unit: &str,
+    period: TrendPeriod,
+    values: impl IntoIterator<Item = (NaiveDate, f64)>,
+) -> TrendSeries {
+    let weekly = period.start.is_none();
+    let bucket_of = |date: NaiveDate| {
+        if weekly {
+            date - Duration::days(date.weekday().num_days_from_monday().into())
+        } else {
+            date
+        }
+    };
+    let mut buckets: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for (date, value) in values {
+        if period.contains(date) {
+            *buckets.entry(bucket_of(date)).or_default() += value;
+        }
+    }
+
+    let mut points = Vec::new();
+    let first = period
+        .start
+        .or_else(|| buckets.keys().next().copied())
+        .map(bucket_of);
+    if let Some(mut date) = first {
+        let last = bucket_of(period.end);
+        let step = Duration::days(if weekly { 7 } else { 1 });
+        while date <= last {
+            let value = buckets.get(&date).copied().unwrap_or(0.0);
+            points.push(TrendPoint {
+                date: date.to_string(),
+                value: (value * 100.0).round() / 100.0,
+            });
+            date += step;
+        }
+    }
+
+    TrendSeries {
+        id: id.to_string(),
+        label: label.to_string(),
+        unit: unit.to_string(),
+        bucket: if weekly { "week" } else { "day" }.to_string(),
+        points,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trend_series;
+    use crate::obsidian::periods::TrendPeriod;
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn open_ended_ranges_bucket_by_week() {
+        let period = TrendPeriod::for_range("lifetime", date(2024, 3, 20));
+        let series = trend_series(
+            "earnings",
+            "Earnings",
+            "USD",
+            period,
+            [
+                (date(2024, 3, 5), 40.0),
+                (date(2024, 3, 7), 2.5),
+                (date(2024, 3, 19), 10.0),
+                (date(2024, 3, 21), 99.0),
+            ],
+        );
+        assert_eq!(series.bucket, "week");
+        let points: Vec<_> = series
+            .points
+            .iter()
+            .map(|point| (point.date.as_str(), point.value))
+            .collect();
+        assert_eq!(
+            points,
+            [
+                ("2024-03-04", 42.5),
+                ("2024-03-11", 0.0),
+                ("2024-03-18", 10.0)
+            ]
+        );
+
+        let empty = trend_series("earnings", "Earnings", "USD", period, []);
+        assert!(empty.points.is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+//! Appends entries to `Stream/YYYY-MM.md` in the layout `parse_stream_file`
+//! reads: a `## Saturday Jan 10` header per day, then one line per entry.
+//! A day written as a table gets a table row, anything else a
+//! `**9:00am** | text` timeline line. Appends to the same file are serialized
+//! and the file is replaced by a rename, so readers never see half a write.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::types::{StreamAppend, WorkspaceEntry};
+use crate::vault_roots::resolve_vault_roots;
+
+use super::{extract_entry_text, parse_header_date, strip_time_prefix};
+
+static FILE_LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+
+/// The lock for one stream file. Entries are never removed; there is one per
+/// month written to.
+fn file_lock(path: &Path) -> Arc<Mutex<()>> {
+    let locks = FILE_LOCKS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut locks = locks.lock().unwrap_or_else(|err| err.into_inner());
+    locks.entry(path.to_path_buf()).or_default().clone()
+}
+
+/// `append_stream_entry` into the workspace's first vault. `date` is
+/// `YYYY-MM-DD` and defaults to `today`. Both the desktop command and the
+/// daemon RPC go through here.
+pub(crate) fn workspace_stream_append(
+    entry: &WorkspaceEntry,
+    date: Option<&str>,
+    time: &str,
+    text: &str,
+    today: NaiveDate,
+) -> Result<StreamAppend, String> {
+    let date = match date.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date: {value} (expected YYYY-MM-DD)"))?,
+        None => today,
+    };
+    let vault = resolve_vault_roots(&entry.path, &entry.settings.effective_obsidian_roots())
+        .into_iter()
+        .next()
+        .ok_or("workspace has no Obsidian vault")?;
+    append_stream_entry(&vault.path, date, time, text)
+}
+
+/// Adds `text` at `time` on `date` to the vault's stream, creating the month
+/// file or the day's header when missing. New days go before the first later
+/// day in the file, so headers stay in date order.
+pub(crate) fn append_stream_entry(
+    vault: &Path,
+    date: NaiveDate,
+    time: &str,
+    text: &str,
+) -> Result<StreamAppend, String> {
+    let time = normalize_time(time)?;
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Err("Stream entry text is empty".to_string());
+    }
+    if text.contains('|') {
+        return Err("Stream entry text can't contain `|`".to_string());
+    }
+
+    let dir = vault.join("Stream");
+    let path = dir.join(format!("{}.md", date.format("%Y-%m")));
+    let lock = file_lock(&path);
+    let _guard = lock.lock().unwrap_or_else(|err| err.into_inner());
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+    let (updated, line, created_header) = insert_entry(&content, date, &time, &text);
+
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+    let temp = path.with_extension("md.tmp");
+    fs::write(&temp, updated)
+        .map_err(|err| format!("Failed to write {}: {err}", temp.display()))?;
+    fs::rename(&temp, &path).map_err(|err| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed to write {}: {err}", path.display())
+    })?;
+
+    Ok(StreamAppend {
+        path: path.to_string_lossy().to_string(),
+        date: date.to_string(),
+        line,
+        created_header,
+    })
+}
+
+/// `9:00am`, `9:00 AM` or `17:30`, as `9:00am` or `17:30`.
+fn normalize_time(raw: &str) -> Result<String, String> {
+    let time: String = raw.split_whitespace().collect::<String>().to_lowercase();
+    let invalid = || format!("Invalid time: {raw} (expected e.g. 9:00am or 17:30)");
+    if strip_time_prefix(&time).as_deref() != Some("") {
+        return Err(invalid());
+    }
+    let (hours, rest) = time.split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = rest[..2].parse().map_err(|_| invalid())?;
+    let twelve_hour = rest.len() > 2;
+    let hours_ok = if twelve_hour {
+        (1..=12).contains(&hours)
+    } else {
+        hours <= 23
+    };
+    if !hours_ok || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(time)
+}
+
+/// The file with the entry added, the line written and whether the day's
+/// header was added too.
+fn insert_entry(content: &str, date: NaiveDate, time: &str, text: &str) -> (String, String, bool) {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let headers: Vec<(usize, NaiveDate)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| Some((index, parse_header_date(line, Some(date.year()))?)))
+        .collect();
+
+    if let Some(position) = headers.iter().position(|(_, day)| *day == date) {
+        let start = headers[position].0;
+        let end = section_end(&lines, start);
+        let last_entry = (start + 1..end)
+            .rev()
+            .find(|index| extract_entry_text(&lines[*index]).is_some());
+        let line = match last_entry {
+            Some(index) if lines[index].trim_start().starts_with('|') => {
+                table_row(&lines[index], time, text)
+            }
+            _ => timeline_line(time, text),
+        };
+        // Right after the day's last non-blank line, which keeps a table
+        // contiguous.
+        let after = (start..end)
+            .rev()
+            .find(|index| !lines[*index].trim().is_empty())
+            .unwrap_or(start);
+        lines.insert(after + 1, line.clone());
+        return (join_lines(lines), line, false);
+    }
+
+    let line = timeline_line(time, text);
+    let header = format!("## {}", date.format("%A %b %-d"));
+    let section = vec![header, line.clone()];
+    match headers.iter().find(|(_, day)| *day > date) {
+        Some((index, _)) => {
+            let mut section = section;
+            section.push(String::new());
+            lines.splice(*index..*index, section);
+        }
+        None => {
+            if lines.last().is_some_and(|last| !last.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.extend(section);
+        }
+    }
+    (join_lines(lines), line, true)
+}
+
+/// The index after the last line of the section starting at `start`: the
+/// next `#` or `##` heading, or the end of the file.
+fn section_end(lines: &[String], start: usize) -> usize {
+    (start + 1..lines.len())
+        .find(|index| {
+            let line = lines[*index].trim_start();
+            line.starts_with("# ") || line.starts_with("## ")
+        })
+        .unwrap_or(lines.len())
+}
+
+fn timeline_line(time: &str, text: &str) -> String {
+    format!("**{time}** | {text}")
+}
+
+/// A row shaped like `like`: the entry goes in the cell that held the time
+/// there, and every other cell is `—`.
+fn table_row(like: &str, time: &str, text: &str) -> String {
+    let cells: Vec<&str> = like.trim().trim_matches('|').split('|').collect();
+    let entry_cell = cells
+        .iter()
+        .position(|cell| strip_time_prefix(cell).is_some())
+        .unwrap_or(0);
+    let cells: Vec<String> = (0..cells.len())
+        .map(|index| {
+            if index == entry_cell {
+                format!("{time} {text}")
+            } else {
+                "—".to_string()
+            }
+        })
+        .collect();
+    format!("| {} |", cells.join(" | "))
+}
+
+fn join_lines(lines: Vec<String>) -> String {
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_stream_entry, normalize_time};
+    use crate::obsidian::compute_domain_trends;
+    use crate::types::DateOrder;
+    use chrono::NaiveDate;
+    use std::fs;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn times_are_checked_and_normalized() {
+        assert_eq!(normalize_time("9:00 AM").unwrap(), "9:00am");
+        assert_eq!(normalize_time("17:30").unwrap(), "17:30");
+        for bad in ["9am", "13:00pm", "24:00", "9:75", "noon", "9:0", ""] {
+            assert!(normalize_time(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn appends_keep_days_in_order_and_follow_the_day_format() {
+        let dir = tempdir().expect("tempdir");
+        let stream = dir.path().join("Stream");
+        fs::create_dir_all(&stream).unwrap();
+        fs::write(
+            stream.join("2026-01.md"),
+            "# January\n\n## Wed Jan 7\n| Plan | Actual | Delta |\n| -- | -- | -- |\n| -- | 12:34pm Lunch | + |\n\n## Saturday Jan 10\n**8:00am** | Breakfast\n",
+        )
+        .unwrap();
+
+        let table = append_stream_entry(dir.path(), date(2026, 1, 7), "6:00pm", "Dinner").unwrap();
+        assert!(!table.created_header);
+        assert_eq!(table.line, "| — | 6:00pm Dinner | — |");
+        let new_day =
+            append_stream_entry(dir.path(), date(2026, 1, 8), "7:00am", "Run 30 min").unwrap();
+        assert!(new_day.created_header);
+        append_stream_entry(dir.path(), date(2026, 1, 10), "1:00pm", "Lunch  break\n").unwrap();
+        append_stream_entry(dir.path(), date(2026, 2, 1), "9:00am", "New month").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(stream.join("2026-01.md")).unwrap(),
+            "# January\n\n## Wed Jan 7\n| Plan | Actual | Delta |\n| -- | -- | -- |\n| -- | 12:34pm Lunch | + |\n| — | 6:00pm Dinner | — |\n\n## Thursday Jan 8\n**7:00am** | Run 30 min\n\n## Saturday Jan 10\n**8:00am** | Breakfast\n**1:00pm** | Lunch break\n"
+        );
+        assert_eq!(
+            fs::read_to_string(stream.join("2026-02.md")).unwrap(),
+            "## Sunday Feb 1\n**9:00am** | New month\n"
+        );
+        assert!(append_stream_entry(dir.path(), date(2026, 1, 8), "9:00", "a | b").is_err());
+        assert!(append_stream_entry(dir.path(), date(2026, 1, 8), "9:00", "  ").is_err());
+    }
+
+    #[test]
+    fn appended_entries_show_up_in_trends() {
+        let dir = tempdir().expect("tempdir");
+        let food = dir.path().join("Entities").join("Food");
+        fs::create_dir_all(&food).unwrap();
+        fs::write(
+            food.join("Oatmeal.md"),
+            "---\ncalories: 300\nprotein: 10\n---\n",
+        )
+        .unwrap();
+        let today = date(2026, 1, 10);
+        let trends = || {
+            compute_domain_trends(
+                &dir.path().to_string_lossy(),
+                &[],
+                DateOrder::default(),
+                None,
+                "food_exercise",
+                "7d",
+                false,
+                false,
+                today,
+            )
+            .expect("trends")
+        };
+        let card = |id: &str| {
+            trends()
+                .cards
+                .into_iter()
+                .find(|card| card.id == id)
+                .and_then(|card| card.value_num)
+        };
+        assert_eq!(card("calories"), Some(0.0));
+
+        append_stream_entry(dir.path(), today, "8:00am", "Breakfast [[Food/Oatmeal]] x2").unwrap();
+        append_stream_entry(dir.path(), date(2026, 1, 9), "7:00am", "Run 45 min").unwrap();
+        assert_eq!(card("calories"), Some(600.0));
+        assert_eq!(card("meals"), Some(1.0));
+        assert_eq!(card("workout_minutes"), Some(45.0));
+
+        let root = Arc::new(dir.path().to_path_buf());
+        let writers: Vec<_> = (0..8)
+            .map(|index| {
+                let root = Arc::clone(&root);
+                std::thread::spawn(move || {
+                    append_stream_entry(
+                        &root,
+                        date(2026, 1, 10),
+                        "12:00pm",
+                        &format!("Snack {index} [[Food/Oatmeal]]"),
+                    )
+                    .unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(card("meals"), Some(9.0));
+    }
+}
@@ -0,0 +1,674 @@
+//! The `vault` trend domain: note, tag and link statistics for whole vaults.
+//! Each vault is indexed once per fingerprint (the path, size and mtime of
+//! every file), so a snapshot of an unchanged vault only re-stats files and
+//! counts. Hidden folders such as `.obsidian` and `.trash` are skipped.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::SystemTime;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_yaml::Value as YamlValue;
+
+use super::cards::{TrendBuilder, VAULT_LAYOUT};
+use super::dates::DateReader;
+use super::periods::TrendPeriod;
+use super::{contribution_with_dates, extract_links, split_frontmatter};
+use crate::types::{CardFormat, DateOrder, DomainTrendSnapshot, TrendListItem};
+use crate::vault_roots::{names_in_several_vaults, qualified_name, VaultRoot};
+
+/// Notes indexed per vault. Past this the index stops walking and the
+/// snapshot says so, since links into the unindexed notes read as broken.
+const MAX_VAULT_NOTES: usize = 5_000;
+const TOP_ITEMS: usize = 10;
+/// Frontmatter keys read as the creation date, before the file's birth time.
+const CREATED_KEYS: &[&str] = &["created", "created_at", "date_created"];
+
+struct IndexedNote {
+    /// Vault-relative path without `.md`, `/`-separated.
+    path: String,
+    modified: Option<NaiveDate>,
+    created: Option<NaiveDate>,
+    /// Raw frontmatter creation date; parsed per snapshot so `dateOrder`
+    /// applies.
+    created_value: Option<String>,
+    /// Lowercased, without `#`, each once.
+    tags: Vec<String>,
+    /// Other notes this one links to, each once.
+    links_to: Vec<usize>,
+    /// Link targets, as written, that match no file.
+    broken: Vec<String>,
+}
+
+impl IndexedNote {
+    fn title(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+struct VaultIndex {
+    notes: Vec<IndexedNote>,
+    truncated: bool,
+}
+
+struct VaultFile {
+    /// Vault-relative, `/`-separated, with extension.
+    relative: String,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    created: Option<SystemTime>,
+}
+
+#[derive(Default)]
+struct VaultFiles {
+    notes: Vec<VaultFile>,
+    attachments: Vec<String>,
+    truncated: bool,
+    fingerprint: u64,
+}
+
+/// Vault root to its fingerprint and index.
+type IndexCache = HashMap<PathBuf, (u64, Arc<VaultIndex>)>;
+
+static INDEX_CACHE: OnceLock<Mutex<IndexCache>> = OnceLock::new();
+
+fn index_cache() -> MutexGuard<'static, IndexCache> {
+    let cache = INDEX_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    cache.lock().unwrap_or_else(|err| {
+        let mut guard = err.into_inner();
+        guard.clear();
+        cache.clear_poison();
+        guard
+    })
+}
+
+/// What the trend cache compares against for the `vault` domain.
+pub(super) fn vault_fingerprint(root: &Path) -> u64 {
+    walk_vault(root).fingerprint
+}
+
+pub(super) fn build_vault_snapshot(
+    domain_id: &str,
+    range: &str,
+    period: TrendPeriod,
+    vaults: &[VaultRoot],
+    date_order: DateOrder,
+) -> DomainTrendSnapshot {
+    let indexes: Vec<Arc<VaultIndex>> = vaults
+        .iter()
+        .map(|vault| vault_index(&vault.path))
+        .collect();
+    let duplicate_titles =
+        names_in_several_vaults(vaults.iter().zip(&indexes).flat_map(|(vault, index)| {
+            index
+                .notes
+                .iter()
+                .map(move |note| (vault.name.as_str(), note.title()))
+        }));
+
+    let mut roots = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut notes = 0;
+    let mut created = 0;
+    let mut modified = 0;
+    let mut orphans = 0;
+    let mut broken_count = 0;
+    let mut tag_counts: HashMap<&str, usize> = HashMap::new();
+    let mut linked: Vec<(usize, String, Option<String>)> = Vec::new();
+    let mut broken: HashMap<&str, (usize, &str)> = HashMap::new();
+    for (vault, index) in vaults.iter().zip(&indexes) {
+        let mut dates = DateReader::new(date_order);
+        let mut incoming = vec![0usize; index.notes.len()];
+        for note in &index.notes {
+            for target in &note.links_to {
+                incoming[*target] += 1;
+            }
+            for tag in &note.tags {
+                *tag_counts.entry(tag.as_str()).or_default() += 1;
+            }
+            for target in &note.broken {
+                broken_count += 1;
+                let entry = broken.entry(target.as_str()).or_insert((0, note.title()));
+                entry.0 += 1;
+            }
+            let created_at = dates.read(note.created_value.as_deref()).or(note.created);
+            if created_at.is_some_and(|date| period.contains(date)) {
+                created += 1;
+            }
+            if note.modified.is_some_and(|date| period.contains(date)) {
+                modified += 1;
+            }
+        }
+        for (note, count) in index.notes.iter().zip(&incoming) {
+            if *count == 0 {
+                orphans += 1;
+                continue;
+            }
+            let title = note.title();
+            let label = if duplicate_titles.contains(title) {
+                qualified_name(&vault.name, title)
+            } else {
+                title.to_string()
+            };
+            let folder = (note.path != title).then(|| note.path.clone());
+            linked.push((*count, label, folder));
+        }
+        notes += index.notes.len();
+        roots.push(contribution_with_dates(
+            vault,
+            &[("notes", index.notes.len())],
+            &dates,
+        ));
+        if index.truncated {
+            diagnostics.push(format!(
+                "{}: only the first {MAX_VAULT_NOTES} notes were indexed, so counts are partial and links to other notes show as broken.",
+                vault.name
+            ));
+        }
+    }
+
+    let mut tags: Vec<(&str, usize)> = tag_counts.into_iter().collect();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let tag_items = tags
+        .iter()
+        .take(TOP_ITEMS)
+        .map(|(tag, count)| TrendListItem {
+            label: format!("#{tag}"),
+            value: format!("{count}"),
+            sub_label: None,
+        })
+        .collect();
+    linked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let linked_items = linked
+        .into_iter()
+        .take(TOP_ITEMS)
+        .map(|(count, label, folder)| TrendListItem {
+            label,
+            value: format!("{count}"),
+            sub_label: folder,
+        })
+        .collect();
+    let mut broken: Vec<(&str, (usize, &str))> = broken.into_iter().collect();
+    broken.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.0.cmp(b.0)));
+    let broken_items = broken
+        .into_iter()
+        .take(TOP_ITEMS)
+        .map(|(target, (count, source))| TrendListItem {
+            label: target.to_string(),
+            value: format!("{count}"),
+            sub_label: Some(format!("from {source}")),
+        })
+        .collect();
+
+    let mut trends = TrendBuilder::new(&VAULT_LAYOUT);
+    for (id, label, value) in [
+        ("notes", "Notes", notes),
+        ("created", "Created", created),
+        ("modified", "Modified", modified),
+        ("tags", "Tags", tags.len()),
+        ("orphans", "Orphans", orphans),
+        ("broken_links", "Broken Links", broken_count),
+    ] {
+        trends.card(
+            id,
+            label,
+            format!("{value}"),
+            value as f64,
+            CardFormat::Count,
+        );
+    }
+    trends.list("top_tags", "Top Tags", tag_items);
+    trends.list("most_linked", "Most Linked", linked_items);
+    trends.list("broken_links", "Broken Links", broken_items);
+
+    let (cards, lists) = trends.finish();
+    DomainTrendSnapshot {
+        domain_id: domain_id.to_string(),
+        range: range.to_string(),
+        updated_at: Utc::now().to_rfc3339(),
+        cards,
+        lists,
+        series: None,
+        roots,
+        previous: None,
+        diagnostics,
+    }
+}
+
+/// The cached index of `root`, rebuilt when its fingerprint changes.
+fn vault_index(root: &Path) -> Arc<VaultIndex> {
+    let files = walk_vault(root);
+    if let Some((fingerprint, index)) = index_cache().get(root) {
+        if *fingerprint == files.fingerprint {
+            return Arc::clone(index);
+        }
+    }
+    let fingerprint = files.fingerprint;
+    let index = Arc::new(build_index(files));
+    index_cache().insert(root.to_path_buf(), (fingerprint, Arc::clone(&index)));
+    index
+}
+
+fn walk_vault(root: &Path) -> VaultFiles {
+    let mut files = VaultFiles::default();
+    let mut hasher = DefaultHasher::new();
+    walk_dir(root, "", &mut files, &mut hasher);
+    files.truncated.hash(&mut hasher);
+    files.fingerprint = hasher.finish();
+    files
+}
+
+fn walk_dir(dir: &Path, prefix: &str, files: &mut VaultFiles, hasher: &mut DefaultHasher) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = metadata.modified().ok();
+        let relative = format!("{prefix}{name}");
+        if metadata.is_dir() {
+            walk_dir(&entry.path(), &format!("{relative}/"), files, hasher);
+            continue;
+        }
+        let is_note = name.to_ascii_lowercase().ends_with(".md");
+        if is_note && files.notes.len() >= MAX_VAULT_NOTES {
+            files.truncated = true;
+            continue;
+        }
+        (&relative, metadata.len(), modified).hash(hasher);
+        if is_note {
+            files.notes.push(VaultFile {
+                relative,
+                path: entry.path(),
+                modified,
+                created: metadata.created().ok(),
+            });
+        } else {
+            files.attachments.push(relative);
+        }
+    }
+}
+
+fn build_index(files: VaultFiles) -> VaultIndex {
+    let resolver = LinkResolver::new(&files);
+    let notes = files
+        .notes
+        .iter()
+        .enumerate()
+        .map(|(position, file)| {
+            let content = fs::read_to_string(&file.path).unwrap_or_default();
+            let (frontmatter, body) = split_frontmatter(&content);
+            let frontmatter = frontmatter
+                .and_then(|text| serde_yaml::from_str::<YamlValue>(&text).ok())
+                .unwrap_or(YamlValue::Null);
+            let mut tags = frontmatter_tags(&frontmatter);
+            tags.extend(inline_tags(&body));
+            let mut seen = HashSet::new();
+            tags.retain(|tag| seen.insert(tag.clone()));
+
+            let source = note_path(&file.relative);
+            let folder = source.rsplit_once('/').map_or("", |(folder, _)| folder);
+            let mut links_to = Vec::new();
+            let mut broken = Vec::new();
+            for raw in extract_links(&body) {
+                let Some(target) = link_target(&raw) else {
+                    continue;
+                };
+                match resolver.resolve(folder, &target) {
+                    Some(Resolved::Note(index)) if index != position => {
+                        if !links_to.contains(&index) {
+                            links_to.push(index);
+                        }
+                    }
+                    Some(_) => {}
+                    None => {
+                        if !broken.contains(&target) {
+                            broken.push(target);
+                        }
+                    }
+                }
+            }
+            IndexedNote {
+                path: source,
+                modified: file.modified.map(to_date),
+                created: file.created.map(to_date),
+                created_value: CREATED_KEYS
+                    .iter()
+                    .find_map(|key| frontmatter.get(*key).and_then(yaml_text)),
+                tags,
+                links_to,
+                broken,
+            }
+        })
+        .collect();
+    VaultIndex {
+        notes,
+        truncated: files.truncated,
+    }
+}
+
+fn to_date(time: SystemTime) -> NaiveDate {
+    DateTime::<Utc>::from(time).date_naive()
+}
+
+fn note_path(relative: &str) -> String {
+    let len = relative.len();
+    if relative.to_ascii_lowercase().ends_with(".md") {
+        relative[..len - 3].to_string()
+    } else {
+        relative.to_string()
+    }
+}
+
+fn yaml_text(value: &YamlValue) -> Option<String> {
+    match value {
+        YamlValue::String(text) => Some(text.clone()),
+        YamlValue::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// `tags:` as a list, or as one string separated by commas or spaces.
+fn frontmatter_tags(frontmatter: &YamlValue) -> Vec<String> {
+    let Some(value) = frontmatter.get("tags").or_else(|| frontmatter.get("tag")) else {
+        return Vec::new();
+    };
+    let raw: Vec<String> = match value {
+        YamlValue::Sequence(items) => items.iter().filter_map(yaml_text).collect(),
+        other => yaml_text(other)
+            .map(|text| {
+                text.split([',', ' '])
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default(),
+    };
+    raw.iter()
+        .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+        .filter(|tag| is_tag(tag))
+        .collect()
+}
+
+/// `#tag` tokens outside fenced code. A tag starts after whitespace or at the
+/// start of a line, may nest with `/`, and needs a non-digit, so `#2024` and
+/// headings are not tags.
+fn inline_tags(body: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let mut previous = ' ';
+        for (start, ch) in line.char_indices() {
+            if ch == '#' && previous.is_whitespace() {
+                let rest = &line[start + 1..];
+                let end = rest
+                    .find(|ch: char| !(ch.is_alphanumeric() || matches!(ch, '_' | '-' | '/')))
+                    .unwrap_or(rest.len());
+                let tag = rest[..end].trim_end_matches('/').to_lowercase();
+                if is_tag(&tag) {
+                    tags.push(tag);
+                }
+            }
+            previous = ch;
+        }
+    }
+    tags
+}
+
+fn is_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().any(|ch| !ch.is_ascii_digit() && ch != '/')
+}
+
+/// The file part of a wiki link: `[[Note|shown]]`, `[[Note#Heading]]` and
+/// `[[Note^block]]` all target `Note`. `None` for links into the same note.
+fn link_target(raw: &str) -> Option<String> {
+    let target = raw.split('|').next().unwrap_or(raw);
+    let target = target.split(['#', '^']).next().unwrap_or(target);
+    let target = target.trim().replace('\\', "/");
+    let target = target.trim_start_matches('/');
+    (!target.is_empty()).then(|| target.to_string())
+}
+
+enum Resolved {
+    Note(usize),
+    Attachment,
+}
+
+/// Resolves link targets the way Obsidian does, case-insensitively: a bare
+/// name matches any note with that name, preferring one in the linking note's
+/// folder; a path is tried relative to that folder, then from the vault root,
+/// then as the end of a longer path.
+struct LinkResolver {
+    /// Lowercased note paths, by note index.
+    note_paths: Vec<String>,
+    notes_by_path: HashMap<String, usize>,
+    notes_by_name: HashMap<String, Vec<usize>>,
+    attachment_paths: HashSet<String>,
+    attachment_names: HashSet<String>,
+}
+
+impl LinkResolver {
+    fn new(files: &VaultFiles) -> Self {
+        let note_paths: Vec<String> = files
+            .notes
+            .iter()
+            .map(|file| note_path(&file.relative).to_lowercase())
+            .collect();
+        let mut notes_by_path = HashMap::new();
+        let mut notes_by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, path) in note_paths.iter().enumerate() {
+            let name = path.rsplit('/').next().unwrap_or(path).to_string();
+            notes_by_name.entry(name).or_default().push(index);
+            notes_by_path.insert(path.clone(), index);
+        }
+        let attachment_paths: HashSet<String> = files
+            .attachments
+            .iter()
+            .map(|path| path.to_lowercase())
+            .collect();
+        let attachment_names = attachment_paths
+            .iter()
+            .map(|path| path.rsplit('/').next().unwrap_or(path).to_string())
+            .collect();
+        Self {
+            note_paths,
+            notes_by_path,
+            notes_by_name,
+            attachment_paths,
+            attachment_names,
+        }
+    }
+
+    fn resolve(&self, folder: &str, target: &str) -> Option<Resolved> {
+        let target = target.to_lowercase();
+        let folder = folder.to_lowercase();
+        if !target.contains('/') {
+            let name = note_path(&target);
+            if let Some(candidates) = self.notes_by_name.get(&name) {
+                return Some(Resolved::Note(self.closest(&folder, candidates)));
+            }
+            return self
+                .attachment_names
+                .contains(&target)
+                .then_some(Resolved::Attachment);
+        }
+        let relative = join_relative(&folder, &target);
+        let explicit = target.starts_with("./") || target.starts_with("../");
+        let candidates = relative
+            .into_iter()
+            .chain((!explicit).then(|| target.clone()));
+        for path in candidates {
+            if let Some(index) = self.notes_by_path.get(&note_path(&path)) {
+                return Some(Resolved::Note(*index));
+            }
+            if self.attachment_paths.contains(&path) {
+                return Some(Resolved::Attachment);
+            }
+        }
+        let suffix = format!("/{}", note_path(&target));
+        self.notes_by_path
+            .iter()
+            .filter(|(path, _)| path.ends_with(&suffix))
+            .min_by_key(|(path, _)| (path.len(), path.as_str()))
+            .map(|(_, index)| Resolved::Note(*index))
+    }
+
+    /// The candidate in `folder`, else the one with the shortest path.
+    fn closest(&self, folder: &str, candidates: &[usize]) -> usize {
+        candidates
+            .iter()
+            .copied()
+            .min_by_key(|index| {
+                let path = self.note_paths[*index].as_str();
+                let in_folder = path.rsplit_once('/').map_or("", |(dir, _)| dir) == folder;
+                (!in_folder, path.len(), path)
+            })
+            .unwrap_or(candidates[0])
+    }
+}
+
+/// `target` joined onto `folder`, with `.` and `..` applied. `None` when it
+/// climbs out of the vault.
+fn join_relative(folder: &str, target: &str) -> Option<String> {
+    let mut parts: Vec<&str> = folder.split('/').filter(|part| !part.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_vault_snapshot, link_target, vault_index};
+    use crate::obsidian::periods::TrendPeriod;
+    use crate::types::DateOrder;
+    use crate::vault_roots::VaultRoot;
+    use chrono::Utc;
+    use std::fs;
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    #[test]
+    fn indexes_tags_backlinks_and_broken_links() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        for folder in ["Projects", "Notes", ".obsidian"] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+        let notes = [
+            (
+                "Index.md",
+                "---\ntags: [project, Health]\n---\nSee [[Projects/Alpha|the alpha]], [[beta#Plans]] and [[Missing]].\n#project #2024 ![[diagram.png]]\n```\n#not-a-tag\n```\n",
+            ),
+            ("Projects/Alpha.md", "[[Beta]] [[../Index]] [[./Gamma]]"),
+            ("Projects/Gamma.md", "Sleep notes #health/sleep"),
+            ("Notes/Beta.md", "---\ntags: reading, project\n---\n# Beta\n"),
+            ("Orphan.md", "[[Orphan]] links only to itself."),
+            (".obsidian/hidden.md", "[[Orphan]]"),
+        ];
+        for (path, content) in notes {
+            fs::write(root.join(path), content).unwrap();
+        }
+        fs::write(root.join("diagram.png"), b"png").unwrap();
+
+        let vaults = [VaultRoot {
+            name: "vault".to_string(),
+            path: root.to_path_buf(),
+        }];
+        let period = TrendPeriod::for_range("7d", Utc::now().date_naive());
+        let snapshot = build_vault_snapshot("vault", "7d", period, &vaults, DateOrder::default());
+        let card = |id: &str| {
+            snapshot
+                .cards
+                .iter()
+                .find(|card| card.id == id)
+                .and_then(|card| card.value_num)
+        };
+        assert_eq!(card("notes"), Some(5.0));
+        assert_eq!(card("modified"), Some(5.0));
+        assert_eq!(card("orphans"), Some(1.0));
+        assert_eq!(card("broken_links"), Some(1.0));
+        assert_eq!(card("tags"), Some(4.0));
+
+        let list = |id: &str| {
+            snapshot
+                .lists
+                .iter()
+                .find(|list| list.id == id)
+                .map(|list| {
+                    list.items
+                        .iter()
+                        .map(|item| (item.label.as_str(), item.value.as_str()))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+        assert_eq!(
+            list("top_tags"),
+            vec![
+                ("#project", "2"),
+                ("#health", "1"),
+                ("#health/sleep", "1"),
+                ("#reading", "1")
+            ]
+        );
+        assert_eq!(
+            list("most_linked"),
+            vec![
+                ("Beta", "2"),
+                ("Alpha", "1"),
+                ("Gamma", "1"),
+                ("Index", "1")
+            ]
+        );
+        assert_eq!(list("broken_links"), vec![("Missing", "1")]);
+
+        let first = vault_index(root);
+        assert!(Arc::ptr_eq(&first, &vault_index(root)));
+        // Same size as before, so only the mtime tells the edit apart. Set it
+        // explicitly: a coarse-mtime filesystem may not move it on its own.
+        fs::write(root.join("Orphan.md"), "[[Missing]] links to nothing yet").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(root.join("Orphan.md"))
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(60)))
+            .unwrap();
+        assert!(!Arc::ptr_eq(&first, &vault_index(root)));
+    }
+
+    #[test]
+    fn link_targets_drop_display_text_and_headings() {
+        assert_eq!(link_target("Note|shown").as_deref(), Some("Note"));
+        assert_eq!(
+            link_target("Folder/Note#Heading").as_deref(),
+            Some("Folder/Note")
+        );
+        assert_eq!(link_target("Note^block").as_deref(), Some("Note"));
+        assert_eq!(link_target("#Heading"), None);
+    }
+}
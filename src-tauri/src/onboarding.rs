@@ -0,0 +1,355 @@
+//! First-run checks and the bootstrap sequence behind `onboarding_status` and
+//! `onboarding_bootstrap`. The daemon and the desktop app each run the steps
+//! with their own workspace and prompt calls; this module holds the report
+//! they build, the tool probes and the starter prompt set.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::github_cli::GhAuthStatus;
+
+const TOOL_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(crate) const STEP_ADD_WORKSPACE: &str = "addWorkspace";
+pub(crate) const STEP_CONNECT_WORKSPACE: &str = "connectWorkspace";
+pub(crate) const STEP_WORKSPACE_DOCTOR: &str = "workspaceDoctor";
+pub(crate) const STEP_STARTER_PROMPTS: &str = "starterPrompts";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ToolCheck {
+    pub(crate) installed: bool,
+    /// First line of `--version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DaemonCheck {
+    pub(crate) reachable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OnboardingStatus {
+    /// No codex, or no workspace yet.
+    pub(crate) needs_onboarding: bool,
+    pub(crate) codex_installed: bool,
+    /// The `codex_doctor` report. Missing when the daemon is unreachable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) codex: Option<Value>,
+    pub(crate) workspace_count: usize,
+    /// `settings.json` exists, so settings were saved at least once.
+    pub(crate) settings_saved: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) git: Option<ToolCheck>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) gh: Option<GhAuthStatus>,
+    /// Missing when the app runs its own backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) daemon: Option<DaemonCheck>,
+}
+
+impl OnboardingStatus {
+    /// `codex` is the `codex_doctor` result; a failed doctor counts as codex
+    /// not installed.
+    pub(crate) fn new(
+        codex: Result<Value, String>,
+        workspace_count: usize,
+        settings_saved: bool,
+        git: ToolCheck,
+        gh: GhAuthStatus,
+        daemon: Option<DaemonCheck>,
+    ) -> Self {
+        // Same shape as a report, so clients read one type either way.
+        let codex = codex.unwrap_or_else(|err| {
+            json!({
+                "ok": false,
+                "codexBin": null,
+                "version": null,
+                "appServerOk": false,
+                "details": err,
+                "path": null,
+                "nodeOk": false,
+                "nodeVersion": null,
+                "nodeDetails": null,
+            })
+        });
+        let codex_installed = codex.get("ok").and_then(Value::as_bool).unwrap_or(false);
+        Self {
+            needs_onboarding: !codex_installed || workspace_count == 0,
+            codex_installed,
+            codex: Some(codex),
+            workspace_count,
+            settings_saved,
+            git: Some(git),
+            gh: Some(gh),
+            daemon,
+        }
+    }
+
+    /// What the desktop app reports when its daemon does not answer; the
+    /// checks that run on the daemon's machine are left out.
+    pub(crate) fn daemon_unreachable(error: String) -> Self {
+        Self {
+            needs_onboarding: true,
+            codex_installed: false,
+            codex: None,
+            workspace_count: 0,
+            settings_saved: false,
+            git: None,
+            gh: None,
+            daemon: Some(DaemonCheck {
+                reachable: false,
+                error: Some(error),
+            }),
+        }
+    }
+}
+
+/// Runs `program --version`; a program that is missing, fails or hangs is
+/// not installed.
+pub(crate) async fn check_tool(program: &str) -> ToolCheck {
+    let output = tokio::time::timeout(
+        TOOL_CHECK_TIMEOUT,
+        Command::new(program).arg("--version").output(),
+    )
+    .await;
+    match output {
+        Ok(Ok(output)) if output.status.success() => ToolCheck {
+            installed: true,
+            version: String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty()),
+        },
+        _ => ToolCheck {
+            installed: false,
+            version: None,
+        },
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BootstrapOptions {
+    #[serde(default)]
+    pub(crate) codex_bin: Option<String>,
+    #[serde(default)]
+    pub(crate) starter_prompts: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StepStatus {
+    Done,
+    /// Nothing to do, usually because an earlier run already did it.
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OnboardingStep {
+    pub(crate) id: String,
+    pub(crate) status: StepStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BootstrapReport {
+    /// No step failed.
+    pub(crate) ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) workspace_id: Option<String>,
+    /// Every step, in order, including those skipped after a failure.
+    pub(crate) steps: Vec<OnboardingStep>,
+}
+
+impl BootstrapReport {
+    fn push(
+        &mut self,
+        id: &str,
+        status: StepStatus,
+        detail: Option<String>,
+        error: Option<String>,
+    ) {
+        self.steps.push(OnboardingStep {
+            id: id.to_string(),
+            status,
+            detail,
+            error,
+        });
+    }
+
+    pub(crate) fn done(&mut self, id: &str, detail: impl Into<String>) {
+        self.push(id, StepStatus::Done, Some(detail.into()), None);
+    }
+
+    pub(crate) fn skipped(&mut self, id: &str, detail: impl Into<String>) {
+        self.push(id, StepStatus::Skipped, Some(detail.into()), None);
+    }
+
+    pub(crate) fn failed(&mut self, id: &str, error: String) {
+        self.push(id, StepStatus::Failed, None, Some(error));
+    }
+
+    /// Records the doctor's verdict on a workspace's session.
+    pub(crate) fn doctor(&mut self, result: Result<Value, String>) {
+        let report = match result {
+            Ok(report) => report,
+            Err(err) => return self.failed(STEP_WORKSPACE_DOCTOR, err),
+        };
+        if report.get("connected").and_then(Value::as_bool) == Some(true) {
+            return self.done(STEP_WORKSPACE_DOCTOR, "The workspace session is running.");
+        }
+        let spawn_error = report
+            .pointer("/lastSpawnError/message")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        self.failed(
+            STEP_WORKSPACE_DOCTOR,
+            spawn_error.unwrap_or_else(|| "The workspace is not connected.".to_string()),
+        );
+    }
+
+    /// Marks the steps after `from` as skipped once a step the rest depend on
+    /// has failed.
+    pub(crate) fn skip_remaining(&mut self, from: &str, options: &BootstrapOptions) {
+        let remaining = [
+            STEP_CONNECT_WORKSPACE,
+            STEP_WORKSPACE_DOCTOR,
+            STEP_STARTER_PROMPTS,
+        ];
+        let start = remaining
+            .iter()
+            .position(|id| *id == from)
+            .map_or(0, |index| index + 1);
+        for id in &remaining[start..] {
+            if *id == STEP_STARTER_PROMPTS && !options.starter_prompts {
+                continue;
+            }
+            self.skipped(id, "Skipped because an earlier step failed.");
+        }
+    }
+
+    pub(crate) fn finish(mut self, workspace_id: Option<String>) -> Self {
+        self.ok = self
+            .steps
+            .iter()
+            .all(|step| step.status != StepStatus::Failed);
+        self.workspace_id = workspace_id;
+        self
+    }
+}
+
+pub(crate) struct StarterPrompt {
+    pub(crate) name: &'static str,
+    pub(crate) description: &'static str,
+    pub(crate) argument_hint: Option<&'static str>,
+    pub(crate) content: &'static str,
+}
+
+/// Created in the workspace's prompt folder by `starterPrompts`. A prompt is
+/// left alone when one with its name already exists in either scope.
+pub(crate) const STARTER_PROMPTS: &[StarterPrompt] = &[
+    StarterPrompt {
+        name: "review-changes",
+        description: "Review the uncommitted changes",
+        argument_hint: None,
+        content: "Review the uncommitted changes in this repository. Point out bugs, missing tests and anything that does not match the surrounding code, most important first.",
+    },
+    StarterPrompt {
+        name: "explain",
+        description: "Explain how a part of the code works",
+        argument_hint: Some("FILE_OR_SYMBOL"),
+        content: "Explain how $ARGUMENTS works: what calls it, what it depends on and anything surprising about it.",
+    },
+    StarterPrompt {
+        name: "write-tests",
+        description: "Add tests in the repository's style",
+        argument_hint: Some("FILE_OR_SYMBOL"),
+        content: "Write tests for $ARGUMENTS. Follow the test layout and helpers this repository already uses, and run them.",
+    },
+];
+
+/// Starter prompts not yet present among `existing` prompt names.
+pub(crate) fn missing_starter_prompts<'a>(
+    existing: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'static StarterPrompt> {
+    let existing: Vec<&str> = existing.into_iter().collect();
+    STARTER_PROMPTS
+        .iter()
+        .filter(|prompt| !existing.contains(&prompt.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        missing_starter_prompts, BootstrapOptions, BootstrapReport, StepStatus, STARTER_PROMPTS,
+        STEP_ADD_WORKSPACE, STEP_WORKSPACE_DOCTOR,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn failed_steps_skip_the_rest_and_fail_the_report() {
+        let options = BootstrapOptions {
+            starter_prompts: true,
+            ..BootstrapOptions::default()
+        };
+        let mut report = BootstrapReport::default();
+        report.failed(
+            STEP_ADD_WORKSPACE,
+            "Workspace path must be a folder.".to_string(),
+        );
+        report.skip_remaining(STEP_ADD_WORKSPACE, &options);
+        let report = report.finish(None);
+        assert!(!report.ok);
+        let statuses: Vec<_> = report.steps.iter().map(|step| step.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StepStatus::Failed,
+                StepStatus::Skipped,
+                StepStatus::Skipped,
+                StepStatus::Skipped
+            ]
+        );
+
+        let mut report = BootstrapReport::default();
+        report.doctor(Ok(json!({
+            "connected": false,
+            "lastSpawnError": {
+                "message": "codex not found",
+                "stderr": "",
+                "stdout": "",
+                "exitStatus": null,
+                "occurredAtMs": 0,
+                "attempts": 1
+            }
+        })));
+        assert_eq!(report.steps[0].id, STEP_WORKSPACE_DOCTOR);
+        assert_eq!(report.steps[0].error.as_deref(), Some("codex not found"));
+    }
+
+    #[test]
+    fn starter_prompts_are_only_missing_once() {
+        assert_eq!(missing_starter_prompts([]).len(), STARTER_PROMPTS.len());
+        let names: Vec<&str> = STARTER_PROMPTS.iter().map(|prompt| prompt.name).collect();
+        assert!(missing_starter_prompts(names).is_empty());
+        let missing = missing_starter_prompts(["explain", "unrelated"]);
+        assert!(missing.iter().all(|prompt| prompt.name != "explain"));
+        assert_eq!(missing.len(), STARTER_PROMPTS.len() - 1);
+    }
+}
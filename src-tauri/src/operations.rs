@@ -0,0 +1,386 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::utils::{git_env_path, resolve_git_binary};
+
+/// Finished operations kept around so reconnecting clients can still read the outcome.
+const MAX_FINISHED_OPERATIONS: usize = 50;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OperationStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct OperationSnapshot {
+    pub(crate) id: String,
+    pub(crate) kind: String,
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) status: OperationStatus,
+    pub(crate) phase: String,
+    pub(crate) percent: Option<u8>,
+    pub(crate) message: Option<String>,
+    /// Structured data about the latest step, shaped by `kind`.
+    pub(crate) detail: Option<Value>,
+    pub(crate) error: Option<String>,
+    pub(crate) result: Option<Value>,
+    #[serde(rename = "startedAt")]
+    pub(crate) started_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub(crate) updated_at: i64,
+}
+
+struct OperationEntry {
+    snapshot: OperationSnapshot,
+    cancel_tx: watch::Sender<bool>,
+}
+
+/// Tracks long-running tasks (clones, installs) so clients can follow progress,
+/// cancel them, and recover their state after reconnecting.
+pub(crate) struct OperationsRegistry<E: EventSink> {
+    entries: Mutex<HashMap<String, OperationEntry>>,
+    event_sink: E,
+}
+
+impl<E: EventSink> OperationsRegistry<E> {
+    pub(crate) fn new(event_sink: E) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            event_sink,
+        }
+    }
+
+    pub(crate) fn start(
+        self: &Arc<Self>,
+        kind: &str,
+        workspace_id: Option<String>,
+    ) -> OperationContext<E> {
+        let id = Uuid::new_v4().to_string();
+        let now = now_millis();
+        let snapshot = OperationSnapshot {
+            id: id.clone(),
+            kind: kind.to_string(),
+            workspace_id,
+            status: OperationStatus::Running,
+            phase: "queued".to_string(),
+            percent: None,
+            message: None,
+            detail: None,
+            error: None,
+            result: None,
+            started_at: now,
+            updated_at: now,
+        };
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            prune_finished(&mut entries);
+            entries.insert(
+                id.clone(),
+                OperationEntry {
+                    snapshot: snapshot.clone(),
+                    cancel_tx,
+                },
+            );
+        }
+        self.emit(&snapshot);
+        OperationContext {
+            id,
+            registry: Arc::clone(self),
+            cancel_rx,
+        }
+    }
+
+    pub(crate) fn status(&self, id: &str) -> Option<OperationSnapshot> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(id).map(|entry| entry.snapshot.clone())
+    }
+
+    pub(crate) fn list(&self) -> Vec<OperationSnapshot> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut snapshots: Vec<OperationSnapshot> = entries
+            .values()
+            .map(|entry| entry.snapshot.clone())
+            .collect();
+        snapshots.sort_by_key(|snapshot| snapshot.started_at);
+        snapshots
+    }
+
+    pub(crate) fn cancel(&self, id: &str) -> Result<OperationSnapshot, String> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get(id).ok_or("operation not found")?;
+        if entry.snapshot.status != OperationStatus::Running {
+            return Err("operation is not running".to_string());
+        }
+        let _ = entry.cancel_tx.send(true);
+        Ok(entry.snapshot.clone())
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut OperationSnapshot)) {
+        let snapshot = {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(entry) = entries.get_mut(id) else {
+                return;
+            };
+            apply(&mut entry.snapshot);
+            entry.snapshot.updated_at = now_millis();
+            entry.snapshot.clone()
+        };
+        self.emit(&snapshot);
+    }
+
+    fn emit(&self, snapshot: &OperationSnapshot) {
+        self.event_sink.emit_app_server_event(AppServerEvent {
+            workspace_id: snapshot.workspace_id.clone().unwrap_or_default(),
+            message: json!({
+                "method": "operation/progress",
+                "params": snapshot,
+            }),
+        });
+    }
+}
+
+/// Handle given to the task doing the work; reports progress and observes cancellation.
+pub(crate) struct OperationContext<E: EventSink> {
+    id: String,
+    registry: Arc<OperationsRegistry<E>>,
+    cancel_rx: watch::Receiver<bool>,
+}
+
+impl<E: EventSink> OperationContext<E> {
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(crate) fn progress(&self, phase: &str, percent: Option<u8>, message: Option<String>) {
+        self.progress_detail(phase, percent, message, None);
+    }
+
+    pub(crate) fn progress_detail(
+        &self,
+        phase: &str,
+        percent: Option<u8>,
+        message: Option<String>,
+        detail: Option<Value>,
+    ) {
+        self.registry.update(&self.id, |snapshot| {
+            snapshot.phase = phase.to_string();
+            snapshot.percent = percent.map(|value| value.min(100));
+            snapshot.message = message;
+            snapshot.detail = detail;
+        });
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        *self.cancel_rx.borrow()
+    }
+
+    /// Resolves once `operation_cancel` has been called for this operation.
+    pub(crate) async fn cancelled(&self) {
+        let mut rx = self.cancel_rx.clone();
+        while !*rx.borrow() {
+            if rx.changed().await.is_err() {
+                // Registry dropped the sender; nothing can cancel us anymore.
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+
+    pub(crate) fn finish(&self, result: &Result<Value, String>) {
+        let cancelled = self.is_cancelled();
+        self.registry.update(&self.id, |snapshot| match result {
+            Ok(value) => {
+                snapshot.status = OperationStatus::Completed;
+                snapshot.phase = "done".to_string();
+                snapshot.percent = Some(100);
+                snapshot.result = Some(value.clone());
+            }
+            Err(err) => {
+                snapshot.status = if cancelled {
+                    OperationStatus::Cancelled
+                } else {
+                    OperationStatus::Failed
+                };
+                snapshot.error = Some(err.clone());
+            }
+        });
+    }
+}
+
+fn prune_finished(entries: &mut HashMap<String, OperationEntry>) {
+    let mut finished: Vec<(i64, String)> = entries
+        .values()
+        .filter(|entry| entry.snapshot.status != OperationStatus::Running)
+        .map(|entry| (entry.snapshot.updated_at, entry.snapshot.id.clone()))
+        .collect();
+    if finished.len() < MAX_FINISHED_OPERATIONS {
+        return;
+    }
+    finished.sort();
+    let excess = finished.len() + 1 - MAX_FINISHED_OPERATIONS;
+    for (_, id) in finished.into_iter().take(excess) {
+        entries.remove(&id);
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Parses a `git clone --progress` line such as
+/// `Receiving objects:  45% (123/456), 1.2 MiB | 3.4 MiB/s`.
+pub(crate) fn parse_git_progress_line(line: &str) -> Option<(String, u8)> {
+    let line = line.trim();
+    let line = line.strip_prefix("remote:").map(str::trim).unwrap_or(line);
+    let (phase, rest) = line.split_once(':')?;
+    let phase = phase.trim();
+    if phase.is_empty() {
+        return None;
+    }
+    let percent_end = rest.find('%')?;
+    let percent = rest[..percent_end].trim().parse::<u8>().ok()?;
+    Some((phase.to_string(), percent.min(100)))
+}
+
+/// Overall percent for a clone, weighting the phases git reports in order.
+fn clone_overall_percent(phase: &str, percent: u8) -> u8 {
+    let (start, span) = match phase {
+        "Counting objects" | "Enumerating objects" => (0u32, 5u32),
+        "Compressing objects" => (5, 5),
+        "Receiving objects" => (10, 70),
+        "Resolving deltas" => (80, 15),
+        "Updating files" | "Checking out files" => (95, 5),
+        _ => return percent,
+    };
+    (start + span * percent as u32 / 100).min(100) as u8
+}
+
+/// Runs `git clone --progress`, reporting phases through `ctx`. On cancellation or
+/// failure the child is killed and the partially cloned directory is removed.
+pub(crate) async fn run_git_clone<E: EventSink>(
+    ctx: &OperationContext<E>,
+    source_url: &str,
+    dest: &Path,
+) -> Result<(), String> {
+    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
+    let mut child = Command::new(git_bin)
+        .arg("clone")
+        .arg("--progress")
+        .arg(source_url)
+        .arg(dest)
+        .env("PATH", git_env_path())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    ctx.progress("cloning", Some(0), Some(format!("Cloning {source_url}")));
+
+    let mut stderr = child.stderr.take().ok_or("Failed to capture git output")?;
+    let mut output = String::new();
+    let read_progress = async {
+        let mut buffer = [0u8; 4096];
+        let mut pending = String::new();
+        loop {
+            let read = match stderr.read(&mut buffer).await {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            pending.push_str(&String::from_utf8_lossy(&buffer[..read]));
+            while let Some(index) = pending.find(['\r', '\n']) {
+                let line: String = pending.drain(..=index).collect();
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                if let Some((phase, percent)) = parse_git_progress_line(line) {
+                    ctx.progress(
+                        "cloning",
+                        Some(clone_overall_percent(&phase, percent)),
+                        Some(format!("{phase}: {percent}%")),
+                    );
+                } else {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+        }
+        child.wait().await
+    };
+
+    let status = tokio::select! {
+        status = read_progress => Some(status),
+        _ = ctx.cancelled() => None,
+    };
+    let outcome = match status {
+        None => Err("Operation cancelled".to_string()),
+        Some(Err(err)) => Err(err.to_string()),
+        Some(Ok(status)) if status.success() => Ok(()),
+        Some(Ok(_)) => {
+            let detail = output.trim();
+            if detail.is_empty() {
+                Err("git clone failed".to_string())
+            } else {
+                Err(format!("git clone failed: {detail}"))
+            }
+        }
+    };
+    if outcome.is_err() {
+        let _ = child.kill().await;
+        if dest.exists() {
+            let _ = tokio::fs::remove_dir_all(dest).await;
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clone_overall_percent, parse_git_progress_line};
+
+    #[test]
+    fn parses_receiving_objects_line() {
+        let parsed =
+            parse_git_progress_line("Receiving objects:  45% (123/456), 1.20 MiB | 3.40 MiB/s");
+        assert_eq!(parsed, Some(("Receiving objects".to_string(), 45)));
+    }
+
+    #[test]
+    fn parses_remote_prefixed_line() {
+        let parsed = parse_git_progress_line("remote: Counting objects: 100% (12/12), done.");
+        assert_eq!(parsed, Some(("Counting objects".to_string(), 100)));
+    }
+
+    #[test]
+    fn ignores_lines_without_percent() {
+        assert_eq!(parse_git_progress_line("Cloning into 'skill'..."), None);
+        assert_eq!(parse_git_progress_line("fatal: repository not found"), None);
+    }
+
+    #[test]
+    fn overall_percent_is_monotonic_across_phases() {
+        assert_eq!(clone_overall_percent("Receiving objects", 0), 10);
+        assert_eq!(clone_overall_percent("Receiving objects", 100), 80);
+        assert_eq!(clone_overall_percent("Resolving deltas", 100), 95);
+        assert_eq!(clone_overall_percent("Checking out files", 100), 100);
+    }
+}
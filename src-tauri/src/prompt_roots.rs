@@ -0,0 +1,142 @@
+//! Where a workspace's custom prompts live. A worktree has no prompts of its
+//! own at first, so it also reads its parent workspace's directory; those
+//! prompts are listed with `scope: "parent"`.
+
+use std::path::{Path, PathBuf};
+
+use crate::read_only::ensure_writable;
+use crate::types::WorkspaceEntry;
+
+/// A prompts directory and the `scope` its prompts are listed with.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PromptRoot {
+    pub(crate) scope: &'static str,
+    pub(crate) dir: PathBuf,
+}
+
+pub(crate) fn workspace_prompts_dir(data_dir: &Path, workspace_id: &str) -> PathBuf {
+    data_dir
+        .join("workspaces")
+        .join(workspace_id)
+        .join("prompts")
+}
+
+/// The directories `prompts_list` reads, in listing order: the workspace's
+/// own, its parent's when it is a worktree, then `global_dir`.
+pub(crate) fn prompt_roots(
+    data_dir: &Path,
+    entry: &WorkspaceEntry,
+    global_dir: Option<PathBuf>,
+) -> Vec<PromptRoot> {
+    let mut roots = vec![PromptRoot {
+        scope: "workspace",
+        dir: workspace_prompts_dir(data_dir, &entry.id),
+    }];
+    if let Some(parent_id) = entry
+        .parent_id
+        .as_deref()
+        .filter(|_| entry.kind.is_worktree())
+    {
+        roots.push(PromptRoot {
+            scope: "parent",
+            dir: workspace_prompts_dir(data_dir, parent_id),
+        });
+    }
+    if let Some(dir) = global_dir {
+        roots.push(PromptRoot {
+            scope: "global",
+            dir,
+        });
+    }
+    roots
+}
+
+/// The directory `prompts_create` and `prompts_move` write to for `scope`.
+pub(crate) fn scope_dir(roots: &[PromptRoot], scope: &str) -> Result<PathBuf, String> {
+    if let Some(root) = roots.iter().find(|root| root.scope == scope) {
+        return Ok(root.dir.clone());
+    }
+    match scope {
+        "parent" => Err("Only worktrees have a parent scope.".to_string()),
+        "global" => Err("Unable to resolve CODEX_HOME".to_string()),
+        _ => Err("Invalid scope.".to_string()),
+    }
+}
+
+/// The scope of the root `path` is in. Falls back to `global`, as prompts
+/// outside every root are rejected before this is asked.
+pub(crate) fn scope_of_path(roots: &[PromptRoot], path: &Path) -> &'static str {
+    roots
+        .iter()
+        .find(|root| path.starts_with(&root.dir))
+        .map_or("global", |root| root.scope)
+}
+
+/// Refuses changes to the workspace and parent prompts of a `readOnly`
+/// workspace. Global prompts are shared, so they stay editable.
+pub(crate) fn ensure_scope_writable(entry: &WorkspaceEntry, scope: &str) -> Result<(), String> {
+    if scope == "global" {
+        return Ok(());
+    }
+    ensure_writable(entry, "edit prompts")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prompt_roots, scope_dir, scope_of_path, workspace_prompts_dir};
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::path::{Path, PathBuf};
+
+    fn entry(id: &str, kind: WorkspaceKind, parent_id: Option<&str>) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/repos/{id}"),
+            codex_bin: None,
+            kind,
+            parent_id: parent_id.map(str::to_string),
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn worktrees_share_their_parents_prompts() {
+        let data_dir = Path::new("/data");
+        let global = Some(PathBuf::from("/codex/prompts"));
+        let parent = entry("main", WorkspaceKind::Main, None);
+        let first = entry("wt-a", WorkspaceKind::Worktree, Some("main"));
+        let second = entry("wt-b", WorkspaceKind::Worktree, Some("main"));
+
+        let parent_roots = prompt_roots(data_dir, &parent, global.clone());
+        assert_eq!(
+            parent_roots
+                .iter()
+                .map(|root| root.scope)
+                .collect::<Vec<_>>(),
+            vec!["workspace", "global"]
+        );
+        assert!(scope_dir(&parent_roots, "parent").is_err());
+
+        let shared = workspace_prompts_dir(data_dir, "main");
+        let created = shared.join("review.md");
+        for worktree in [&first, &second] {
+            let roots = prompt_roots(data_dir, worktree, global.clone());
+            assert_eq!(
+                roots.iter().map(|root| root.scope).collect::<Vec<_>>(),
+                vec!["workspace", "parent", "global"]
+            );
+            assert_eq!(scope_dir(&roots, "parent").unwrap(), shared);
+            assert_eq!(scope_of_path(&roots, &created), "parent");
+            assert_eq!(
+                scope_of_path(&roots, &workspace_prompts_dir(data_dir, &worktree.id)),
+                "workspace"
+            );
+        }
+        assert_eq!(scope_of_path(&parent_roots, &created), "workspace");
+        assert_eq!(
+            scope_dir(&parent_roots, "bogus").unwrap_err(),
+            "Invalid scope."
+        );
+    }
+}
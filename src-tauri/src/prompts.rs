@@ -7,6 +7,7 @@ use std::path::{Path, PathBuf};
 use tauri::{AppHandle, State};
 use tokio::task;
 
+use crate::prompt_roots::{self, ensure_scope_writable, scope_dir, scope_of_path, PromptRoot};
 use crate::remote_backend;
 use crate::state::AppState;
 use crate::types::WorkspaceEntry;
@@ -77,27 +78,29 @@ fn workspace_prompts_dir(
     entry: &WorkspaceEntry,
 ) -> Result<PathBuf, String> {
     let data_dir = app_data_dir(state)?;
-    Ok(data_dir.join("workspaces").join(&entry.id).join("prompts"))
+    Ok(prompt_roots::workspace_prompts_dir(&data_dir, &entry.id))
 }
 
+/// The workspace's own prompts, its parent's for a worktree, then the global
+/// ones.
 fn prompt_roots_for_workspace(
     state: &State<'_, AppState>,
     entry: &WorkspaceEntry,
-) -> Result<Vec<PathBuf>, String> {
-    let mut roots = Vec::new();
-    roots.push(workspace_prompts_dir(state, entry)?);
-    if let Some(global_dir) = default_prompts_dir() {
-        roots.push(global_dir);
-    }
-    Ok(roots)
+) -> Result<Vec<PromptRoot>, String> {
+    let data_dir = app_data_dir(state)?;
+    Ok(prompt_roots::prompt_roots(
+        &data_dir,
+        entry,
+        default_prompts_dir(),
+    ))
 }
 
-fn ensure_path_within_roots(path: &Path, roots: &[PathBuf]) -> Result<(), String> {
+fn ensure_path_within_roots(path: &Path, roots: &[PromptRoot]) -> Result<(), String> {
     let canonical_path = path
         .canonicalize()
         .map_err(|_| "Invalid prompt path.".to_string())?;
     for root in roots {
-        if let Ok(canonical_root) = root.canonicalize() {
+        if let Ok(canonical_root) = root.dir.canonicalize() {
             if canonical_path.starts_with(&canonical_root) {
                 return Ok(());
             }
@@ -288,6 +291,16 @@ fn discover_prompts_in(dir: &Path, scope: Option<&str>) -> Vec<CustomPromptEntry
     out
 }
 
+/// Prompts from every root in order, creating missing directories.
+fn discover_prompts(roots: &[PromptRoot]) -> Vec<CustomPromptEntry> {
+    let mut out = Vec::new();
+    for root in roots {
+        let _ = fs::create_dir_all(&root.dir);
+        out.extend(discover_prompts_in(&root.dir, Some(root.scope)));
+    }
+    out
+}
+
 #[tauri::command]
 pub(crate) async fn prompts_list(
     state: State<'_, AppState>,
@@ -304,29 +317,23 @@ pub(crate) async fn prompts_list(
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    let (workspace_dir, global_dir) = {
+    let roots = {
         let workspaces = state.workspaces.lock().await;
-        let entry = workspaces.get(&workspace_id).cloned();
-        let workspace_dir = entry
-            .as_ref()
-            .and_then(|entry| workspace_prompts_dir(&state, entry).ok());
-        (workspace_dir, default_prompts_dir())
+        match workspaces.get(&workspace_id) {
+            Some(entry) => prompt_roots_for_workspace(&state, entry)?,
+            None => default_prompts_dir()
+                .map(|dir| PromptRoot {
+                    scope: "global",
+                    dir,
+                })
+                .into_iter()
+                .collect(),
+        }
     };
 
-    task::spawn_blocking(move || {
-        let mut out = Vec::new();
-        if let Some(dir) = workspace_dir {
-            let _ = fs::create_dir_all(&dir);
-            out.extend(discover_prompts_in(&dir, Some("workspace")));
-        }
-        if let Some(dir) = global_dir {
-            let _ = fs::create_dir_all(&dir);
-            out.extend(discover_prompts_in(&dir, Some("global")));
-        }
-        out
-    })
-    .await
-    .map_err(|_| "prompt discovery failed".to_string())
+    task::spawn_blocking(move || discover_prompts(&roots))
+        .await
+        .map_err(|_| "prompt discovery failed".to_string())
 }
 
 #[tauri::command]
@@ -398,21 +405,11 @@ pub(crate) async fn prompts_create(
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
     let name = sanitize_prompt_name(&name)?;
-    let (target_dir, resolved_scope) = {
+    let target_dir = {
         let workspaces = state.workspaces.lock().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
-        match scope.as_str() {
-            "workspace" => {
-                let dir = workspace_prompts_dir(&state, &entry)?;
-                (dir, "workspace")
-            }
-            "global" => {
-                let dir =
-                    default_prompts_dir().ok_or("Unable to resolve CODEX_HOME".to_string())?;
-                (dir, "global")
-            }
-            _ => return Err("Invalid scope.".to_string()),
-        }
+        ensure_scope_writable(&entry, &scope)?;
+        scope_dir(&prompt_roots_for_workspace(&state, &entry)?, &scope)?
     };
     let path = target_dir.join(format!("{name}.md"));
     if path.exists() {
@@ -429,7 +426,7 @@ pub(crate) async fn prompts_create(
         description,
         argument_hint,
         content,
-        scope: Some(resolved_scope.to_string()),
+        scope: Some(scope),
     })
 }
 
@@ -466,12 +463,14 @@ pub(crate) async fn prompts_update(
     if !target_path.exists() {
         return Err("Prompt not found.".to_string());
     }
-    {
+    let roots = {
         let workspaces = state.workspaces.lock().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         let roots = prompt_roots_for_workspace(&state, &entry)?;
-        ensure_path_within_roots(&target_path, &roots)?;
-    }
+        ensure_scope_writable(&entry, scope_of_path(&roots, &target_path))?;
+        roots
+    };
+    ensure_path_within_roots(&target_path, &roots)?;
     let dir = target_path
         .parent()
         .ok_or("Unable to resolve prompt directory.".to_string())?;
@@ -484,23 +483,13 @@ pub(crate) async fn prompts_update(
     if next_path != target_path {
         fs::remove_file(&target_path).map_err(|err| err.to_string())?;
     }
-    let scope = {
-        let workspaces = state.workspaces.lock().await;
-        let entry = require_workspace_entry(&workspaces, &workspace_id)?;
-        let workspace_dir = workspace_prompts_dir(&state, &entry)?;
-        if next_path.starts_with(&workspace_dir) {
-            Some("workspace".to_string())
-        } else {
-            Some("global".to_string())
-        }
-    };
     Ok(CustomPromptEntry {
         name,
         path: next_path.to_string_lossy().to_string(),
         description,
         argument_hint,
         content,
-        scope,
+        scope: Some(scope_of_path(&roots, &next_path).to_string()),
     })
 }
 
@@ -529,6 +518,7 @@ pub(crate) async fn prompts_delete(
         let workspaces = state.workspaces.lock().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
         let roots = prompt_roots_for_workspace(&state, &entry)?;
+        ensure_scope_writable(&entry, scope_of_path(&roots, &target))?;
         ensure_path_within_roots(&target, &roots)?;
     }
     fs::remove_file(&target).map_err(|err| err.to_string())
@@ -559,22 +549,17 @@ pub(crate) async fn prompts_move(
     let roots = {
         let workspaces = state.workspaces.lock().await;
         let entry = require_workspace_entry(&workspaces, &workspace_id)?;
-        prompt_roots_for_workspace(&state, &entry)?
+        let roots = prompt_roots_for_workspace(&state, &entry)?;
+        ensure_scope_writable(&entry, scope_of_path(&roots, &target_path))?;
+        ensure_scope_writable(&entry, &scope)?;
+        roots
     };
     ensure_path_within_roots(&target_path, &roots)?;
     let file_name = target_path
         .file_name()
         .and_then(|value| value.to_str())
         .ok_or("Invalid prompt path.".to_string())?;
-    let target_dir = {
-        let workspaces = state.workspaces.lock().await;
-        let entry = require_workspace_entry(&workspaces, &workspace_id)?;
-        match scope.as_str() {
-            "workspace" => workspace_prompts_dir(&state, &entry)?,
-            "global" => default_prompts_dir().ok_or("Unable to resolve CODEX_HOME".to_string())?,
-            _ => return Err("Invalid scope.".to_string()),
-        }
-    };
+    let target_dir = scope_dir(&roots, &scope)?;
     let next_path = target_dir.join(file_name);
     if next_path == target_path {
         return Err("Prompt is already in that scope.".to_string());
@@ -602,3 +587,53 @@ pub(crate) async fn prompts_move(
         scope: Some(scope),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::discover_prompts;
+    use crate::prompt_roots::{prompt_roots, workspace_prompts_dir};
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use tempfile::tempdir;
+
+    fn worktree(id: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/repos/{id}"),
+            codex_bin: None,
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some("main".to_string()),
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn worktrees_of_one_parent_list_the_same_parent_prompts() {
+        let data_dir = tempdir().expect("tempdir");
+        let parent_dir = workspace_prompts_dir(data_dir.path(), "main");
+        std::fs::create_dir_all(&parent_dir).unwrap();
+        std::fs::write(parent_dir.join("review.md"), "Review $1").unwrap();
+        let own_dir = workspace_prompts_dir(data_dir.path(), "wt-a");
+        std::fs::create_dir_all(&own_dir).unwrap();
+        std::fs::write(own_dir.join("scratch.md"), "Only in wt-a").unwrap();
+
+        let listed = |id: &str| {
+            discover_prompts(&prompt_roots(data_dir.path(), &worktree(id), None))
+                .into_iter()
+                .map(|prompt| (prompt.name, prompt.scope.unwrap_or_default()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            listed("wt-a"),
+            vec![
+                ("scratch".to_string(), "workspace".to_string()),
+                ("review".to_string(), "parent".to_string()),
+            ]
+        );
+        assert_eq!(
+            listed("wt-b"),
+            vec![("review".to_string(), "parent".to_string())]
+        );
+    }
+}
@@ -0,0 +1,514 @@
+//! Workspace quick commands ("run tests", "build", "lint"), run without a
+//! terminal. Output is streamed as `quick-command-output` events.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::backend::app_server::build_codex_path_env;
+use crate::backend::events::{EventSink, QuickCommandOutput, QuickCommandStream};
+use crate::backend::supervisor::spawn_guarded;
+use crate::terminal_core::Utf8Chunker;
+use crate::types::{QuickCommand, QuickCommandConcurrency};
+
+/// Commands checked into the repo. Workspace settings win on a shared id.
+pub(crate) const REPO_COMMANDS_FILE: &str = ".codexmonitor/commands.json";
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RepoCommandsFile {
+    List(Vec<QuickCommand>),
+    Object { commands: Vec<QuickCommand> },
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuickCommandEntry {
+    #[serde(flatten)]
+    pub(crate) command: QuickCommand,
+    /// `settings` or `repo`.
+    pub(crate) source: &'static str,
+    pub(crate) running: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuickCommandExecution {
+    pub(crate) execution_id: String,
+    pub(crate) command_id: String,
+    /// Waiting for an earlier run of the same command to finish.
+    pub(crate) queued: bool,
+}
+
+/// The workspace's commands followed by the repo's, each tagged with where it
+/// came from.
+pub(crate) fn load_quick_commands(
+    root: &Path,
+    configured: &[QuickCommand],
+) -> Result<Vec<(QuickCommand, &'static str)>, String> {
+    let mut commands: Vec<(QuickCommand, &'static str)> = configured
+        .iter()
+        .map(|command| (command.clone(), "settings"))
+        .collect();
+    let path = root.join(REPO_COMMANDS_FILE);
+    if !path.is_file() {
+        return Ok(commands);
+    }
+    let data = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {REPO_COMMANDS_FILE}: {err}"))?;
+    let repo = match serde_json::from_str(&data)
+        .map_err(|err| format!("Invalid {REPO_COMMANDS_FILE}: {err}"))?
+    {
+        RepoCommandsFile::List(commands) => commands,
+        RepoCommandsFile::Object { commands } => commands,
+    };
+    let configured_ids: HashSet<&str> = configured
+        .iter()
+        .map(|command| command.id.as_str())
+        .collect();
+    commands.extend(
+        repo.into_iter()
+            .filter(|command| !configured_ids.contains(command.id.as_str()))
+            .map(|command| (command, "repo")),
+    );
+    Ok(commands)
+}
+
+fn resolve_cwd(root: &Path, cwd_relative: Option<&str>) -> Result<PathBuf, String> {
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let Some(relative) = cwd_relative
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(canonical_root);
+    };
+    let cwd = canonical_root
+        .join(relative)
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {relative}: {err}"))?;
+    if !cwd.starts_with(&canonical_root) || !cwd.is_dir() {
+        return Err(format!("Invalid working directory: {relative}"));
+    }
+    Ok(cwd)
+}
+
+struct Execution {
+    workspace_id: String,
+    command_id: String,
+    cancel_tx: watch::Sender<bool>,
+}
+
+type Executions = Arc<Mutex<HashMap<String, Execution>>>;
+
+/// Drops the execution from the registry however its task ends.
+struct ExecutionGuard {
+    executions: Executions,
+    execution_id: String,
+}
+
+impl Drop for ExecutionGuard {
+    fn drop(&mut self) {
+        self.executions
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&self.execution_id);
+    }
+}
+
+enum Outcome {
+    Exited(Option<i32>),
+    Failed(String),
+    Cancelled,
+}
+
+struct OutputEmitter<E: EventSink> {
+    sink: E,
+    workspace_id: String,
+    command_id: String,
+    execution_id: String,
+}
+
+impl<E: EventSink> OutputEmitter<E> {
+    fn event(&self, stream: QuickCommandStream) -> QuickCommandOutput {
+        QuickCommandOutput {
+            workspace_id: self.workspace_id.clone(),
+            command_id: self.command_id.clone(),
+            execution_id: self.execution_id.clone(),
+            stream,
+            data: String::new(),
+            exit_code: None,
+            error: None,
+            cancelled: false,
+        }
+    }
+
+    fn output(&self, stream: QuickCommandStream, data: String) {
+        self.sink.emit_quick_command_output(QuickCommandOutput {
+            data,
+            ..self.event(stream)
+        });
+    }
+
+    fn exit(&self, outcome: Outcome) {
+        let mut event = self.event(QuickCommandStream::Exit);
+        match outcome {
+            Outcome::Exited(code) => event.exit_code = code,
+            Outcome::Failed(error) => event.error = Some(error),
+            Outcome::Cancelled => event.cancelled = true,
+        }
+        self.sink.emit_quick_command_output(event);
+    }
+}
+
+async fn pump<E: EventSink, R: AsyncRead + Unpin>(
+    emitter: &OutputEmitter<E>,
+    reader: Option<R>,
+    stream: QuickCommandStream,
+) {
+    let Some(mut reader) = reader else {
+        return;
+    };
+    let mut chunker = Utf8Chunker::default();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = match reader.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        let text = chunker.push(&buffer[..read]);
+        if !text.is_empty() {
+            emitter.output(stream, text);
+        }
+    }
+}
+
+async fn cancelled(mut cancel_rx: watch::Receiver<bool>) {
+    while !*cancel_rx.borrow() {
+        if cancel_rx.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Waits for the command's slot when queued, then runs it to completion.
+/// Cancelling drops the child, which kills it.
+async fn execute<E: EventSink>(
+    emitter: &OutputEmitter<E>,
+    mut process: Command,
+    program: &str,
+    slot: Arc<tokio::sync::Mutex<()>>,
+    cancel_rx: watch::Receiver<bool>,
+) -> Outcome {
+    let run = async {
+        let _slot = slot.lock().await;
+        let mut child = match process.spawn() {
+            Ok(child) => child,
+            Err(err) => return Outcome::Failed(format!("Failed to start {program}: {err}")),
+        };
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        tokio::join!(
+            pump(emitter, stdout, QuickCommandStream::Stdout),
+            pump(emitter, stderr, QuickCommandStream::Stderr)
+        );
+        match child.wait().await {
+            Ok(status) => Outcome::Exited(status.code()),
+            Err(err) => Outcome::Failed(err.to_string()),
+        }
+    };
+    tokio::select! {
+        outcome = run => outcome,
+        _ = cancelled(cancel_rx) => Outcome::Cancelled,
+    }
+}
+
+/// Running quick commands, so that concurrent runs of one command can be
+/// rejected or queued and any run can be cancelled.
+pub(crate) struct QuickCommands<E: EventSink> {
+    sink: E,
+    executions: Executions,
+    /// One lock per `workspace:command`, held while an execution runs.
+    slots: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl<E: EventSink> QuickCommands<E> {
+    pub(crate) fn new(sink: E) -> Self {
+        Self {
+            sink,
+            executions: Arc::new(Mutex::new(HashMap::new())),
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn list(
+        &self,
+        workspace_id: &str,
+        root: &Path,
+        configured: &[QuickCommand],
+    ) -> Result<Vec<QuickCommandEntry>, String> {
+        let running: HashSet<String> = self
+            .executions
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .values()
+            .filter(|execution| execution.workspace_id == workspace_id)
+            .map(|execution| execution.command_id.clone())
+            .collect();
+        Ok(load_quick_commands(root, configured)?
+            .into_iter()
+            .map(|(command, source)| QuickCommandEntry {
+                running: running.contains(&command.id),
+                command,
+                source,
+            })
+            .collect())
+    }
+
+    /// Starts `command_id` under `root`. `codex_bin` picks the same PATH the
+    /// workspace's Codex runs with. Returns once the child is queued or
+    /// spawned; the exit code arrives on the final `exit` event.
+    pub(crate) fn run(
+        &self,
+        workspace_id: &str,
+        root: &Path,
+        configured: &[QuickCommand],
+        command_id: &str,
+        codex_bin: Option<&str>,
+    ) -> Result<QuickCommandExecution, String> {
+        let command = load_quick_commands(root, configured)?
+            .into_iter()
+            .map(|(command, _)| command)
+            .find(|command| command.id == command_id)
+            .ok_or_else(|| format!("Unknown quick command: {command_id}"))?;
+        let cwd = resolve_cwd(root, command.cwd_relative.as_deref())?;
+
+        let mut process = Command::new(&command.command);
+        process
+            .args(&command.args)
+            .current_dir(&cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(path_env) = build_codex_path_env(codex_bin) {
+            process.env("PATH", path_env);
+        }
+        process.envs(&command.env);
+
+        let execution_id = Uuid::new_v4().to_string();
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        let queued = {
+            let mut executions = self
+                .executions
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            let busy = executions.values().any(|execution| {
+                execution.workspace_id == workspace_id && execution.command_id == command.id
+            });
+            if busy && command.concurrency == QuickCommandConcurrency::Reject {
+                return Err(format!("{} is already running", command.label));
+            }
+            executions.insert(
+                execution_id.clone(),
+                Execution {
+                    workspace_id: workspace_id.to_string(),
+                    command_id: command.id.clone(),
+                    cancel_tx,
+                },
+            );
+            busy
+        };
+        let slot = {
+            let mut slots = self.slots.lock().unwrap_or_else(|err| err.into_inner());
+            Arc::clone(
+                slots
+                    .entry(format!("{workspace_id}:{}", command.id))
+                    .or_default(),
+            )
+        };
+
+        let guard = ExecutionGuard {
+            executions: Arc::clone(&self.executions),
+            execution_id: execution_id.clone(),
+        };
+        let emitter = OutputEmitter {
+            sink: self.sink.clone(),
+            workspace_id: workspace_id.to_string(),
+            command_id: command.id.clone(),
+            execution_id: execution_id.clone(),
+        };
+        let program = command.command;
+        spawn_guarded(self.sink.clone(), "quick command", async move {
+            let outcome = execute(&emitter, process, &program, slot, cancel_rx).await;
+            drop(guard);
+            emitter.exit(outcome);
+        });
+
+        Ok(QuickCommandExecution {
+            execution_id,
+            command_id: command.id,
+            queued,
+        })
+    }
+
+    pub(crate) fn cancel(&self, execution_id: &str) -> Result<(), String> {
+        let executions = self
+            .executions
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let execution = executions.get(execution_id).ok_or("execution not found")?;
+        let _ = execution.cancel_tx.send(true);
+        Ok(())
+    }
+
+    /// Cancels every run in the workspace, for when it is removed.
+    pub(crate) fn cancel_workspace(&self, workspace_id: &str) {
+        let executions = self
+            .executions
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        for execution in executions
+            .values()
+            .filter(|execution| execution.workspace_id == workspace_id)
+        {
+            let _ = execution.cancel_tx.send(true);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::QuickCommands;
+    use crate::backend::events::{
+        AppServerEvent, BackendError, EventSink, QuickCommandOutput, QuickCommandStream,
+        TerminalExited, TerminalOutput,
+    };
+    use crate::types::{QuickCommand, QuickCommandConcurrency};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        outputs: Arc<Mutex<Vec<QuickCommandOutput>>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exited(&self, _event: TerminalExited) {}
+        fn emit_quick_command_output(&self, event: QuickCommandOutput) {
+            self.outputs.lock().unwrap().push(event);
+        }
+        fn emit_backend_error(&self, _event: BackendError) {}
+    }
+
+    impl RecordingSink {
+        async fn exit_of(&self, execution_id: &str) -> QuickCommandOutput {
+            for _ in 0..200 {
+                let exit = self.outputs.lock().unwrap().iter().find_map(|event| {
+                    (event.execution_id == execution_id && event.stream == QuickCommandStream::Exit)
+                        .then(|| event.clone())
+                });
+                if let Some(exit) = exit {
+                    return exit;
+                }
+                tokio::time::sleep(Duration::from_millis(25)).await;
+            }
+            panic!("no exit event for {execution_id}");
+        }
+    }
+
+    fn sleep_command(concurrency: QuickCommandConcurrency) -> QuickCommand {
+        QuickCommand {
+            id: "wait".to_string(),
+            label: "Wait".to_string(),
+            command: "sleep".to_string(),
+            args: vec!["5".to_string()],
+            cwd_relative: None,
+            env: Default::default(),
+            concurrency,
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_repo_command_and_streams_output() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(dir.path().join(".codexmonitor/sub")).expect("dirs");
+        std::fs::write(
+            dir.path().join(".codexmonitor/commands.json"),
+            r#"{ "commands": [{
+                "id": "check", "label": "Check", "command": "sh",
+                "args": ["-c", "pwd; echo \"$GREETING\" >&2; exit 3"],
+                "cwdRelative": ".codexmonitor/sub", "env": { "GREETING": "hi" }
+            }] }"#,
+        )
+        .expect("write commands");
+        let sink = RecordingSink::default();
+        let commands = QuickCommands::new(sink.clone());
+
+        let listed = commands.list("ws", dir.path(), &[]).expect("list");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].source, "repo");
+
+        let execution = commands
+            .run("ws", dir.path(), &[], "check", None)
+            .expect("run");
+        assert!(!execution.queued);
+        let exit = sink.exit_of(&execution.execution_id).await;
+        assert_eq!(exit.exit_code, Some(3));
+
+        let outputs = sink.outputs.lock().unwrap();
+        let text = |stream| {
+            outputs
+                .iter()
+                .filter(|event| event.stream == stream)
+                .map(|event| event.data.as_str())
+                .collect::<String>()
+        };
+        assert!(text(QuickCommandStream::Stdout)
+            .trim_end()
+            .ends_with("/.codexmonitor/sub"));
+        assert_eq!(text(QuickCommandStream::Stderr), "hi\n");
+        assert!(commands
+            .run("ws", dir.path(), &[], "missing", None)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_or_queues_concurrent_runs_and_cancels() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let sink = RecordingSink::default();
+        let commands = QuickCommands::new(sink.clone());
+
+        let reject = [sleep_command(QuickCommandConcurrency::Reject)];
+        let first = commands
+            .run("ws", dir.path(), &reject, "wait", None)
+            .expect("first run");
+        assert!(commands
+            .run("ws", dir.path(), &reject, "wait", None)
+            .is_err());
+        assert!(commands.list("ws", dir.path(), &reject).expect("list")[0].running);
+
+        let queue = [sleep_command(QuickCommandConcurrency::Queue)];
+        let second = commands
+            .run("ws", dir.path(), &queue, "wait", None)
+            .expect("queued run");
+        assert!(second.queued);
+
+        commands.cancel(&first.execution_id).expect("cancel first");
+        assert!(sink.exit_of(&first.execution_id).await.cancelled);
+        commands.cancel_workspace("ws");
+        let exit = sink.exit_of(&second.execution_id).await;
+        assert!(exit.cancelled && exit.exit_code.is_none());
+        assert!(commands.cancel(&second.execution_id).is_err());
+    }
+}
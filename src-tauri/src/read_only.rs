@@ -0,0 +1,85 @@
+//! A workspace's `readOnly` setting, for demos and screen sharing. Git
+//! mutations, terminal input, prompt edits and vault write-backs are refused
+//! with `workspace_read_only`, and turns are lowered to read-only access.
+//! Reads keep working.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::types::WorkspaceEntry;
+
+/// Error prefix; the rest of the message is JSON:
+/// `{ "workspaceId", "setting": "readOnly", "action" }`.
+pub(crate) const WORKSPACE_READ_ONLY_ERROR: &str = "workspace_read_only";
+
+/// Refuses `action` when `entry` has `readOnly` on.
+pub(crate) fn ensure_writable(entry: &WorkspaceEntry, action: &str) -> Result<(), String> {
+    if !entry.settings.read_only {
+        return Ok(());
+    }
+    Err(format!(
+        "{WORKSPACE_READ_ONLY_ERROR}: {}",
+        json!({
+            "workspaceId": entry.id,
+            "setting": "readOnly",
+            "action": action,
+        })
+    ))
+}
+
+/// `ensure_writable` for callers that only hold the workspace id.
+pub(crate) async fn ensure_workspace_writable(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    action: &str,
+) -> Result<(), String> {
+    let workspaces = workspaces.lock().await;
+    let entry = workspaces.get(workspace_id).ok_or("workspace not found")?;
+    ensure_writable(entry, action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ensure_writable, WORKSPACE_READ_ONLY_ERROR};
+    use crate::types::{AccessLock, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use serde_json::Value;
+
+    fn entry(read_only: bool) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "Demo".to_string(),
+            path: "/repos/demo".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                read_only,
+                ..WorkspaceSettings::default()
+            },
+        }
+    }
+
+    #[test]
+    fn read_only_workspaces_refuse_writes_and_lock_turns() {
+        let writable = entry(false);
+        assert!(ensure_writable(&writable, "commit").is_ok());
+        assert_eq!(writable.settings.effective_access_lock(), None);
+
+        let demo = entry(true);
+        let err = ensure_writable(&demo, "commit").unwrap_err();
+        let payload = err
+            .strip_prefix(&format!("{WORKSPACE_READ_ONLY_ERROR}: "))
+            .expect("error prefix");
+        let payload: Value = serde_json::from_str(payload).expect("json payload");
+        assert_eq!(payload["workspaceId"], "ws-1");
+        assert_eq!(payload["setting"], "readOnly");
+        assert_eq!(payload["action"], "commit");
+        assert_eq!(
+            demo.settings.effective_access_lock(),
+            Some(AccessLock::ReadOnly)
+        );
+    }
+}
@@ -8,12 +8,28 @@ use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, oneshot, Mutex};
 
+use crate::backend::events::AppServerEvent;
+use crate::event_sink::dispatch_notification_rules;
+use crate::rpc_chunks::{ChunkAssembler, CHUNKED_RESPONSES_CAPABILITY};
 use crate::state::AppState;
 use crate::types::BackendMode;
 
 const DEFAULT_REMOTE_HOST: &str = "127.0.0.1:4732";
 const DISCONNECTED_MESSAGE: &str = "remote backend disconnected";
 
+/// How this app describes itself to the daemon, so its activity feed and
+/// `clients_list` can tell devices apart.
+fn client_info() -> Value {
+    let name = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "CodexMonitor desktop".to_string());
+    json!({
+        "name": name,
+        "platform": std::env::consts::OS,
+        "appVersion": env!("CARGO_PKG_VERSION"),
+    })
+}
+
 type PendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
 
 #[derive(Clone)]
@@ -145,13 +161,22 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
         }),
     };
 
+    let client_info = client_info();
     if let Some(token) = token {
         client
-            .call("auth", json!({ "token": token }))
+            .call("auth", json!({ "token": token, "client": client_info }))
             .await
             .map(|_| ())?;
     }
 
+    // Older daemons reject the method; they simply keep sending whole results.
+    let _ = client
+        .call(
+            "client_capabilities",
+            json!({ "capabilities": [CHUNKED_RESPONSES_CAPABILITY], "client": client_info }),
+        )
+        .await;
+
     {
         let mut guard = state.remote_backend.lock().await;
         *guard = Some(client.clone());
@@ -169,6 +194,7 @@ async fn read_loop(
     connected: Arc<AtomicBool>,
 ) {
     let mut lines = BufReader::new(reader).lines();
+    let mut chunks = ChunkAssembler::default();
 
     while let Ok(Some(line)) = lines.next_line().await {
         let trimmed = line.trim();
@@ -182,6 +208,9 @@ async fn read_loop(
         };
 
         if let Some(id) = message.get("id").and_then(|value| value.as_u64()) {
+            // Take the reassembled chunks even when nobody is waiting, so they
+            // are not kept around.
+            let chunked = message.get("chunked").map(|chunked| chunks.take(chunked));
             let sender = pending.lock().await.remove(&id);
             let Some(sender) = sender else {
                 continue;
@@ -196,6 +225,11 @@ async fn read_loop(
                 continue;
             }
 
+            if let Some(result) = chunked {
+                let _ = sender.send(result);
+                continue;
+            }
+
             let result = message.get("result").cloned().unwrap_or(Value::Null);
             let _ = sender.send(Ok(result));
             continue;
@@ -211,11 +245,51 @@ async fn read_loop(
         let params = message.get("params").cloned().unwrap_or(Value::Null);
         match method {
             "app-server-event" => {
-                let _ = app.emit("app-server-event", params);
+                let _ = app.emit("app-server-event", params.clone());
+                let event = AppServerEvent {
+                    workspace_id: params
+                        .get("workspace_id")
+                        .and_then(|value| value.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    message: params.get("message").cloned().unwrap_or(Value::Null),
+                };
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    dispatch_notification_rules(&app, &event).await;
+                });
             }
             "terminal-output" => {
                 let _ = app.emit("terminal-output", params);
             }
+            "terminal-exit" => {
+                let _ = app.emit("terminal-exit", params);
+            }
+            "quick-command-output" => {
+                let _ = app.emit("quick-command-output", params);
+            }
+            "backend-error" => {
+                let _ = app.emit("backend-error", params);
+            }
+            "daemon/shutdown" => {
+                // Sessions were already reported `codex/disconnected`; fail new
+                // calls right away instead of waiting for the socket to close.
+                connected.store(false, Ordering::SeqCst);
+                let _ = app.emit(
+                    "backend-error",
+                    json!({
+                        "subsystem": "daemon",
+                        "message": "The daemon is shutting down",
+                        "restarted": false,
+                        "degraded": true,
+                    }),
+                );
+            }
+            "chunk" => {
+                if let Err(err) = chunks.push(&params) {
+                    eprintln!("Dropping chunked response: {err}");
+                }
+            }
             _ => {}
         }
     }
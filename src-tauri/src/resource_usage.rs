@@ -0,0 +1,127 @@
+//! Memory held by the app-server process behind each connected workspace, with
+//! the thread-start latencies from `warm_pool`, so users can judge which
+//! workspaces are worth keeping warm.
+
+use serde::Serialize;
+
+use crate::warm_pool::{self, ThreadStartMetrics};
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionResource {
+    pub(crate) workspace_id: String,
+    pub(crate) pid: Option<u32>,
+    /// Resident memory of the app-server process; `None` where it can't be
+    /// read.
+    pub(crate) rss_bytes: Option<u64>,
+    pub(crate) keep_warm: bool,
+    /// The warm pool spawned the current session.
+    pub(crate) prewarmed: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResourceUsage {
+    pub(crate) warm_sessions: bool,
+    /// Largest first.
+    pub(crate) sessions: Vec<SessionResource>,
+    pub(crate) total_rss_bytes: u64,
+    pub(crate) thread_starts: ThreadStartMetrics,
+}
+
+/// One running session: workspace id, process id and its `keepWarm` flag.
+pub(crate) struct RunningSession {
+    pub(crate) workspace_id: String,
+    pub(crate) pid: Option<u32>,
+    pub(crate) keep_warm: bool,
+}
+
+/// Reads each process's memory, so call it on the blocking pool.
+pub(crate) fn collect_resource_usage(
+    sessions: Vec<RunningSession>,
+    warm_sessions: bool,
+) -> ResourceUsage {
+    let mut sessions: Vec<SessionResource> = sessions
+        .into_iter()
+        .map(|session| SessionResource {
+            rss_bytes: session.pid.and_then(process_rss_bytes),
+            prewarmed: warm_pool::is_prewarmed(&session.workspace_id),
+            workspace_id: session.workspace_id,
+            pid: session.pid,
+            keep_warm: session.keep_warm,
+        })
+        .collect();
+    sessions.sort_by(|a, b| {
+        b.rss_bytes
+            .cmp(&a.rss_bytes)
+            .then_with(|| a.workspace_id.cmp(&b.workspace_id))
+    });
+    ResourceUsage {
+        warm_sessions,
+        total_rss_bytes: sessions
+            .iter()
+            .filter_map(|session| session.rss_bytes)
+            .sum(),
+        sessions,
+        thread_starts: warm_pool::thread_start_metrics(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn process_rss_bytes(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn process_rss_bytes(pid: u32) -> Option<u64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let kib: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn process_rss_bytes(_pid: u32) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_resource_usage, RunningSession};
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_the_memory_of_running_processes() {
+        let usage = collect_resource_usage(
+            vec![
+                RunningSession {
+                    workspace_id: "gone".to_string(),
+                    pid: None,
+                    keep_warm: false,
+                },
+                RunningSession {
+                    workspace_id: "self".to_string(),
+                    pid: Some(std::process::id()),
+                    keep_warm: true,
+                },
+            ],
+            true,
+        );
+        assert_eq!(usage.sessions[0].workspace_id, "self");
+        let rss = usage.sessions[0].rss_bytes.expect("own rss");
+        assert!(rss > 0);
+        assert_eq!(usage.total_rss_bytes, rss);
+        assert_eq!(usage.sessions[1].rss_bytes, None);
+    }
+}
@@ -0,0 +1,310 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::git_patch::{collect_changes_patch, list_untracked_files, run_git};
+
+pub(crate) const REVERT_UNDO_DIR: &str = "revert-undo";
+const MANIFEST_FILE: &str = "manifest.json";
+const PATCH_FILE: &str = "changes.patch";
+const FILES_DIR: &str = "files";
+/// Untracked files past this total are left out of the snapshot (and listed
+/// in `skipped`) rather than blocking the revert.
+const MAX_UNTRACKED_BACKUP_BYTES: u64 = 200 * 1024 * 1024;
+const MS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct RevertUndoEntry {
+    pub(crate) id: String,
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    #[serde(rename = "createdAtMs")]
+    pub(crate) created_at_ms: i64,
+    /// `"file"` or `"all"`.
+    pub(crate) kind: String,
+    pub(crate) paths: Vec<String>,
+    #[serde(rename = "untrackedFiles")]
+    pub(crate) untracked_files: Vec<String>,
+    #[serde(default)]
+    pub(crate) skipped: Vec<String>,
+    pub(crate) bytes: u64,
+}
+
+/// `<data dir>/revert-undo/<workspace id>`.
+pub(crate) fn workspace_undo_dir(data_dir: &Path, workspace_id: &str) -> PathBuf {
+    data_dir.join(REVERT_UNDO_DIR).join(workspace_id)
+}
+
+fn validate_undo_id(undo_id: &str) -> Result<(), String> {
+    if undo_id.is_empty()
+        || !undo_id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+    {
+        return Err("Invalid undo id".to_string());
+    }
+    Ok(())
+}
+
+fn read_manifest(entry_dir: &Path) -> Result<RevertUndoEntry, String> {
+    let raw = std::fs::read_to_string(entry_dir.join(MANIFEST_FILE))
+        .map_err(|_| "Undo entry not found".to_string())?;
+    serde_json::from_str(&raw).map_err(|err| format!("Invalid undo manifest: {err}"))
+}
+
+/// Snapshots what a revert of `paths` (or everything, when `None`) would
+/// discard. Returns `None` when there is nothing to lose.
+pub(crate) async fn capture_revert_undo(
+    data_dir: &Path,
+    workspace_id: &str,
+    repo_root: &Path,
+    paths: Option<&[String]>,
+) -> Result<Option<RevertUndoEntry>, String> {
+    let patch = collect_changes_patch(repo_root, paths, false).await?;
+    let untracked = list_untracked_files(repo_root, paths).await?;
+    if patch.iter().all(|byte| byte.is_ascii_whitespace()) && untracked.is_empty() {
+        return Ok(None);
+    }
+
+    let now = chrono::Utc::now();
+    let id = format!(
+        "{}-{}",
+        now.format("%Y%m%dT%H%M%S%3f"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+    let entry_dir = workspace_undo_dir(data_dir, workspace_id).join(&id);
+    let files_dir = entry_dir.join(FILES_DIR);
+    std::fs::create_dir_all(&files_dir)
+        .map_err(|err| format!("Failed to create undo folder: {err}"))?;
+    std::fs::write(entry_dir.join(PATCH_FILE), &patch)
+        .map_err(|err| format!("Failed to write undo patch: {err}"))?;
+
+    let mut bytes = patch.len() as u64;
+    let mut saved = Vec::new();
+    let mut skipped = Vec::new();
+    for relative in untracked {
+        let source = repo_root.join(&relative);
+        let size = std::fs::symlink_metadata(&source)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        if bytes + size > MAX_UNTRACKED_BACKUP_BYTES {
+            skipped.push(relative);
+            continue;
+        }
+        let target = files_dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create undo folder: {err}"))?;
+        }
+        std::fs::copy(&source, &target)
+            .map_err(|err| format!("Failed to back up {relative}: {err}"))?;
+        bytes += size;
+        saved.push(relative);
+    }
+
+    let entry = RevertUndoEntry {
+        id,
+        workspace_id: workspace_id.to_string(),
+        created_at_ms: now.timestamp_millis(),
+        kind: if paths.is_some() { "file" } else { "all" }.to_string(),
+        paths: paths.map(<[String]>::to_vec).unwrap_or_default(),
+        untracked_files: saved,
+        skipped,
+        bytes,
+    };
+    let manifest = serde_json::to_string_pretty(&entry).map_err(|err| err.to_string())?;
+    std::fs::write(entry_dir.join(MANIFEST_FILE), manifest)
+        .map_err(|err| format!("Failed to write undo manifest: {err}"))?;
+    Ok(Some(entry))
+}
+
+/// Newest first. Unreadable entries are skipped.
+pub(crate) fn list_revert_undos(data_dir: &Path, workspace_id: &str) -> Vec<RevertUndoEntry> {
+    let Ok(dir) = std::fs::read_dir(workspace_undo_dir(data_dir, workspace_id)) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<RevertUndoEntry> = dir
+        .flatten()
+        .filter_map(|entry| read_manifest(&entry.path()).ok())
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.created_at_ms));
+    entries
+}
+
+/// Removes snapshots older than `retention_days` across all workspaces.
+pub(crate) fn purge_revert_undos(data_dir: &Path, retention_days: u32, now_ms: i64) -> usize {
+    let cutoff = now_ms - i64::from(retention_days) * MS_PER_DAY;
+    let Ok(workspaces) = std::fs::read_dir(data_dir.join(REVERT_UNDO_DIR)) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for workspace in workspaces.flatten() {
+        let Ok(entries) = std::fs::read_dir(workspace.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let expired = match read_manifest(&path) {
+                Ok(manifest) => manifest.created_at_ms < cutoff,
+                // Half-written snapshot from an interrupted revert.
+                Err(_) => true,
+            };
+            if expired && std::fs::remove_dir_all(&path).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+/// Re-applies a snapshot onto the worktree and deletes it. Fails without
+/// touching anything when the patch no longer applies or an untracked file
+/// would be overwritten.
+pub(crate) async fn restore_revert_undo(
+    data_dir: &Path,
+    workspace_id: &str,
+    undo_id: &str,
+    repo_root: &Path,
+) -> Result<RevertUndoEntry, String> {
+    validate_undo_id(undo_id)?;
+    let entry_dir = workspace_undo_dir(data_dir, workspace_id).join(undo_id);
+    let manifest = read_manifest(&entry_dir)?;
+
+    for relative in &manifest.untracked_files {
+        if repo_root.join(relative).exists() {
+            return Err(format!(
+                "Cannot restore: {relative} already exists in the worktree"
+            ));
+        }
+    }
+    let patch = std::fs::read(entry_dir.join(PATCH_FILE))
+        .map_err(|err| format!("Failed to read undo patch: {err}"))?;
+    let has_patch = !patch.iter().all(|byte| byte.is_ascii_whitespace());
+    if has_patch {
+        run_git(
+            repo_root,
+            &["apply", "--check", "--whitespace=nowarn", "-"],
+            Some(&patch),
+            false,
+        )
+        .await
+        .map_err(|err| {
+            format!("Cannot restore: the worktree has changed since the revert ({err})")
+        })?;
+        run_git(
+            repo_root,
+            &["apply", "--whitespace=nowarn", "-"],
+            Some(&patch),
+            false,
+        )
+        .await?;
+    }
+
+    let files_dir = entry_dir.join(FILES_DIR);
+    for relative in &manifest.untracked_files {
+        let target = repo_root.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        std::fs::copy(files_dir.join(relative), &target)
+            .map_err(|err| format!("Failed to restore {relative}: {err}"))?;
+    }
+
+    let _ = std::fs::remove_dir_all(&entry_dir);
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capture_revert_undo, list_revert_undos, purge_revert_undos, restore_revert_undo};
+    use crate::test_git::{git, repo_with};
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn revert_all(root: &Path) {
+        git(root, &["restore", "--staged", "--worktree", "--", "."]);
+        git(root, &["clean", "-f", "-d"]);
+    }
+
+    #[tokio::test]
+    async fn restores_tracked_and_untracked_changes() {
+        let repo = repo_with("tracked.txt", "one\n");
+        let data = tempdir().expect("tempdir");
+        let root = repo.path();
+        std::fs::write(root.join("tracked.txt"), "one\ntwo\n").unwrap();
+        std::fs::create_dir(root.join("notes")).unwrap();
+        std::fs::write(root.join("notes/new.md"), "draft").unwrap();
+
+        let entry = capture_revert_undo(data.path(), "ws", root, None)
+            .await
+            .unwrap()
+            .expect("snapshot");
+        assert_eq!(entry.untracked_files, vec!["notes/new.md".to_string()]);
+        revert_all(root);
+        assert!(!root.join("notes/new.md").exists());
+
+        assert_eq!(list_revert_undos(data.path(), "ws").len(), 1);
+        restore_revert_undo(data.path(), "ws", &entry.id, root)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("tracked.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("notes/new.md")).unwrap(),
+            "draft"
+        );
+        assert!(list_revert_undos(data.path(), "ws").is_empty());
+    }
+
+    #[tokio::test]
+    async fn refuses_to_restore_onto_diverged_worktree() {
+        let repo = repo_with("tracked.txt", "one\n");
+        let data = tempdir().expect("tempdir");
+        let root = repo.path();
+        std::fs::write(root.join("tracked.txt"), "one\ntwo\n").unwrap();
+        let entry = capture_revert_undo(data.path(), "ws", root, None)
+            .await
+            .unwrap()
+            .expect("snapshot");
+        revert_all(root);
+        std::fs::write(root.join("tracked.txt"), "rewritten\n").unwrap();
+
+        let err = restore_revert_undo(data.path(), "ws", &entry.id, root)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("Cannot restore"), "{err}");
+        assert_eq!(
+            std::fs::read_to_string(root.join("tracked.txt")).unwrap(),
+            "rewritten\n"
+        );
+        assert_eq!(list_revert_undos(data.path(), "ws").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn clean_worktree_captures_nothing_and_purge_expires_entries() {
+        let repo = repo_with("tracked.txt", "one\n");
+        let data = tempdir().expect("tempdir");
+        let root = repo.path();
+        assert!(capture_revert_undo(data.path(), "ws", root, None)
+            .await
+            .unwrap()
+            .is_none());
+
+        std::fs::write(root.join("tracked.txt"), "changed\n").unwrap();
+        let entry =
+            capture_revert_undo(data.path(), "ws", root, Some(&["tracked.txt".to_string()]))
+                .await
+                .unwrap()
+                .expect("snapshot");
+        assert_eq!(entry.kind, "file");
+        assert_eq!(purge_revert_undos(data.path(), 7, entry.created_at_ms), 0);
+        let eight_days = 8 * 24 * 60 * 60 * 1000;
+        assert_eq!(
+            purge_revert_undos(data.path(), 7, entry.created_at_ms + eight_days),
+            1
+        );
+        assert!(list_revert_undos(data.path(), "ws").is_empty());
+    }
+}
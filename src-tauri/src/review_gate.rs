@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use git2::{DiffOptions, Oid, Repository, Tree};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::git_utils::{diff_file_stats, DiffFileStat};
+use crate::types::ReviewGateSettings;
+
+pub(crate) const REVIEW_TOO_LARGE: &str = "review_too_large";
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub(crate) struct ReviewDiffStats {
+    pub(crate) files: u64,
+    pub(crate) additions: u64,
+    pub(crate) deletions: u64,
+    pub(crate) bytes: u64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct ReviewSplitGroup {
+    /// Directory prefix shared by the group; `.` for files at the root.
+    pub(crate) directory: String,
+    pub(crate) files: Vec<String>,
+    pub(crate) additions: u64,
+    pub(crate) deletions: u64,
+}
+
+impl ReviewGateSettings {
+    fn is_enabled(&self) -> bool {
+        self.max_files > 0 || self.max_changed_lines > 0 || self.max_diff_bytes > 0
+    }
+
+    /// Names of the limits `stats` goes over.
+    fn exceeded(&self, stats: &ReviewDiffStats) -> Vec<&'static str> {
+        let mut exceeded = Vec::new();
+        if self.max_files > 0 && stats.files > u64::from(self.max_files) {
+            exceeded.push("maxFiles");
+        }
+        if self.max_changed_lines > 0
+            && stats.additions + stats.deletions > u64::from(self.max_changed_lines)
+        {
+            exceeded.push("maxChangedLines");
+        }
+        if self.max_diff_bytes > 0 && stats.bytes > self.max_diff_bytes {
+            exceeded.push("maxDiffBytes");
+        }
+        exceeded
+    }
+}
+
+fn tree_for_commit<'a>(repo: &'a Repository, oid: Oid) -> Result<Tree<'a>, git2::Error> {
+    repo.find_commit(oid)?.tree()
+}
+
+fn workdir_options() -> DiffOptions {
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    options
+}
+
+/// The diff a `review/start` target covers, the way codex builds it:
+/// uncommitted changes against HEAD, a base branch from the merge base to the
+/// working tree, a commit against its first parent. Custom reviews have no
+/// diff and return `None`.
+pub(crate) fn review_target_file_stats(
+    repo_root: &Path,
+    target: &Value,
+) -> Result<Option<Vec<DiffFileStat>>, String> {
+    let repo = Repository::open(repo_root).map_err(|err| err.to_string())?;
+    let head_oid = || {
+        repo.head()
+            .and_then(|head| head.peel_to_commit())
+            .map(|commit| commit.id())
+    };
+    let diff = match target.get("type").and_then(|value| value.as_str()) {
+        Some("uncommittedChanges") => {
+            let head_tree = head_oid()
+                .ok()
+                .and_then(|oid| tree_for_commit(&repo, oid).ok());
+            repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut workdir_options()))
+        }
+        Some("baseBranch") => {
+            let branch = target
+                .get("branch")
+                .and_then(|value| value.as_str())
+                .ok_or("baseBranch target is missing `branch`")?;
+            let base = repo
+                .revparse_single(branch)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|err| err.to_string())?;
+            let head = head_oid().map_err(|err| err.to_string())?;
+            let merge_base = repo
+                .merge_base(base.id(), head)
+                .map_err(|err| err.to_string())?;
+            let base_tree = tree_for_commit(&repo, merge_base).map_err(|err| err.to_string())?;
+            repo.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut workdir_options()))
+        }
+        Some("commit") => {
+            let sha = target
+                .get("sha")
+                .and_then(|value| value.as_str())
+                .ok_or("commit target is missing `sha`")?;
+            let commit = repo
+                .revparse_single(sha)
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|err| err.to_string())?;
+            let tree = commit.tree().map_err(|err| err.to_string())?;
+            let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+            repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        }
+        _ => return Ok(None),
+    }
+    .map_err(|err| err.to_string())?;
+    diff_file_stats(&diff)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+pub(crate) fn total_stats(files: &[DiffFileStat]) -> ReviewDiffStats {
+    files
+        .iter()
+        .fold(ReviewDiffStats::default(), |mut total, file| {
+            total.files += 1;
+            total.additions += file.additions;
+            total.deletions += file.deletions;
+            total.bytes += file.bytes;
+            total
+        })
+}
+
+fn group_by_directory(files: &[DiffFileStat], depth: usize) -> Vec<ReviewSplitGroup> {
+    let mut groups: BTreeMap<String, ReviewSplitGroup> = BTreeMap::new();
+    for file in files {
+        let parts: Vec<&str> = file.path.split('/').collect();
+        let directory = if parts.len() <= 1 {
+            ".".to_string()
+        } else {
+            parts[..depth.min(parts.len() - 1)].join("/")
+        };
+        let group = groups
+            .entry(directory.clone())
+            .or_insert_with(|| ReviewSplitGroup {
+                directory,
+                files: Vec::new(),
+                additions: 0,
+                deletions: 0,
+            });
+        group.files.push(file.path.clone());
+        group.additions += file.additions;
+        group.deletions += file.deletions;
+    }
+    groups.into_values().collect()
+}
+
+/// Groups changed files by top-level directory, one level deeper when
+/// everything sits under a single directory.
+pub(crate) fn suggest_split(files: &[DiffFileStat]) -> Vec<ReviewSplitGroup> {
+    let groups = group_by_directory(files, 1);
+    if groups.len() == 1 {
+        let deeper = group_by_directory(files, 2);
+        if deeper.len() > 1 {
+            return deeper;
+        }
+    }
+    groups
+}
+
+/// The `review_too_large` response for a target over the configured limits,
+/// or `None` when the review may start. Targets whose diff can't be computed
+/// are let through; the app-server reports those itself.
+pub(crate) fn check_review_size(
+    repo_root: &Path,
+    target: &Value,
+    limits: &ReviewGateSettings,
+) -> Option<Value> {
+    if !limits.is_enabled() {
+        return None;
+    }
+    let files = match review_target_file_stats(repo_root, target) {
+        Ok(Some(files)) => files,
+        Ok(None) => return None,
+        Err(err) => {
+            eprintln!("[review-gate] skipping size check: {err}");
+            return None;
+        }
+    };
+    let stats = total_stats(&files);
+    let exceeded = limits.exceeded(&stats);
+    if exceeded.is_empty() {
+        return None;
+    }
+    Some(json!({
+        "status": REVIEW_TOO_LARGE,
+        "stats": stats,
+        "limits": limits,
+        "exceeded": exceeded,
+        "suggestedSplit": suggest_split(&files),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_review_size, suggest_split, REVIEW_TOO_LARGE};
+    use crate::git_utils::DiffFileStat;
+    use crate::types::ReviewGateSettings;
+    use git2::{Repository, Signature};
+    use serde_json::json;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn stat(path: &str, additions: u64) -> DiffFileStat {
+        DiffFileStat {
+            path: path.to_string(),
+            additions,
+            deletions: 0,
+            bytes: 0,
+        }
+    }
+
+    #[test]
+    fn splits_by_directory_and_descends_into_a_single_root() {
+        let groups = suggest_split(&[
+            stat("src/a.rs", 3),
+            stat("src/b.rs", 2),
+            stat("docs/x.md", 1),
+            stat("README.md", 1),
+        ]);
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|group| (group.directory.as_str(), group.files.len(), group.additions))
+            .collect();
+        assert_eq!(summary, vec![(".", 1, 1), ("docs", 1, 1), ("src", 2, 5)]);
+
+        let nested = suggest_split(&[stat("src/ui/a.ts", 1), stat("src/api/b.ts", 1)]);
+        let directories: Vec<_> = nested
+            .iter()
+            .map(|group| group.directory.as_str())
+            .collect();
+        assert_eq!(directories, vec!["src/api", "src/ui"]);
+    }
+
+    #[test]
+    fn gates_uncommitted_changes_over_the_limit() {
+        let dir = tempdir().expect("tempdir");
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/new.rs"), "fn main() {}\n").unwrap();
+
+        let target = json!({ "type": "uncommittedChanges" });
+        let off = ReviewGateSettings::default();
+        assert!(check_review_size(dir.path(), &target, &off).is_none());
+
+        let limits = ReviewGateSettings {
+            max_changed_lines: 2,
+            ..ReviewGateSettings::default()
+        };
+        let response = check_review_size(dir.path(), &target, &limits).expect("gated");
+        assert_eq!(response["status"], REVIEW_TOO_LARGE);
+        assert_eq!(response["stats"]["files"], 2);
+        assert_eq!(response["stats"]["additions"], 3);
+        assert_eq!(response["exceeded"], json!(["maxChangedLines"]));
+        assert_eq!(response["suggestedSplit"].as_array().unwrap().len(), 2);
+
+        let custom = json!({ "type": "custom", "instructions": "look around" });
+        assert!(check_review_size(dir.path(), &custom, &limits).is_none());
+    }
+}
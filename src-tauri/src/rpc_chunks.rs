@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+/// Capability a client sends in `client_capabilities` to receive chunked results.
+pub(crate) const CHUNKED_RESPONSES_CAPABILITY: &str = "chunkedResponses";
+/// Results larger than this are chunked unless the daemon is started with
+/// `--chunk-threshold`.
+pub(crate) const DEFAULT_CHUNK_THRESHOLD: usize = 512 * 1024;
+/// Size of the JSON fragment carried by a single `chunk` notification.
+pub(crate) const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Methods whose results may be chunked. Anything else is always sent as a
+/// single response line.
+const CHUNKED_METHODS: &[&str] = &[
+    "compare_worktrees",
+    "get_git_commit_diff",
+    "get_git_diffs",
+    "get_git_log",
+    "list_threads",
+    "list_workspace_files",
+    "read_workspace_file",
+];
+
+pub(crate) fn is_chunked_method(method: &str) -> bool {
+    CHUNKED_METHODS.contains(&method)
+}
+
+fn split_at_char_boundaries(payload: &str, chunk_size: usize) -> Vec<&str> {
+    let chunk_size = chunk_size.max(4);
+    let mut fragments = Vec::new();
+    let mut start = 0;
+    while start < payload.len() {
+        let mut end = (start + chunk_size).min(payload.len());
+        while !payload.is_char_boundary(end) {
+            end -= 1;
+        }
+        fragments.push(&payload[start..end]);
+        start = end;
+    }
+    fragments
+}
+
+/// Lines to send in place of `{"id":..,"result":..}`: one `chunk` notification
+/// per fragment of `serialized_result`, then a final response whose `chunked`
+/// field names the operation the client should reassemble.
+pub(crate) fn chunked_response_lines(
+    id: u64,
+    op_id: &str,
+    serialized_result: &str,
+    chunk_size: usize,
+) -> Vec<String> {
+    let fragments = split_at_char_boundaries(serialized_result, chunk_size);
+    let count = fragments.len();
+    let mut lines: Vec<String> = fragments
+        .into_iter()
+        .enumerate()
+        .map(|(seq, data)| {
+            json!({
+                "method": "chunk",
+                "params": {
+                    "opId": op_id,
+                    "seq": seq,
+                    "data": data,
+                    "last": seq + 1 == count,
+                }
+            })
+            .to_string()
+        })
+        .collect();
+    lines.push(
+        json!({
+            "id": id,
+            "chunked": {
+                "opId": op_id,
+                "chunks": count,
+                "bytes": serialized_result.len(),
+            }
+        })
+        .to_string(),
+    );
+    lines
+}
+
+#[derive(Default)]
+struct PartialResult {
+    data: String,
+    next_seq: u64,
+    complete: bool,
+}
+
+/// Client-side buffer for `chunk` notifications, keyed by operation id.
+#[derive(Default)]
+pub(crate) struct ChunkAssembler {
+    partial: HashMap<String, PartialResult>,
+}
+
+impl ChunkAssembler {
+    /// Adds one `chunk` notification. Fragments must arrive in order, which
+    /// the daemon guarantees by writing them on a single connection.
+    pub(crate) fn push(&mut self, params: &Value) -> Result<(), String> {
+        let op_id = params
+            .get("opId")
+            .and_then(|value| value.as_str())
+            .ok_or("chunk is missing opId")?;
+        let seq = params
+            .get("seq")
+            .and_then(|value| value.as_u64())
+            .ok_or("chunk is missing seq")?;
+        let data = params
+            .get("data")
+            .and_then(|value| value.as_str())
+            .ok_or("chunk is missing data")?;
+        let last = params
+            .get("last")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+
+        let partial = self.partial.entry(op_id.to_string()).or_default();
+        if partial.complete || seq != partial.next_seq {
+            self.partial.remove(op_id);
+            return Err(format!("chunk {seq} of {op_id} arrived out of order"));
+        }
+        partial.data.push_str(data);
+        partial.next_seq += 1;
+        partial.complete = last;
+        Ok(())
+    }
+
+    /// Removes the reassembled result for the `chunked` field of a response.
+    pub(crate) fn take(&mut self, chunked: &Value) -> Result<Value, String> {
+        let op_id = chunked
+            .get("opId")
+            .and_then(|value| value.as_str())
+            .ok_or("chunked response is missing opId")?;
+        let partial = self
+            .partial
+            .remove(op_id)
+            .ok_or_else(|| format!("no chunks received for {op_id}"))?;
+        let expected = chunked.get("chunks").and_then(|value| value.as_u64());
+        if !partial.complete || expected.is_some_and(|count| count != partial.next_seq) {
+            return Err(format!("chunked response {op_id} is incomplete"));
+        }
+        serde_json::from_str(&partial.data).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunked_response_lines, is_chunked_method, ChunkAssembler};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn chunked_lines_reassemble_to_the_original_result() {
+        let result = json!({
+            "files": (0..50).map(|i| format!("src/módulo_{i}.rs")).collect::<Vec<_>>(),
+        });
+        let serialized = result.to_string();
+        let lines = chunked_response_lines(7, "op-1", &serialized, 64);
+        assert!(lines.len() > 3);
+
+        let mut assembler = ChunkAssembler::default();
+        let (last, chunks) = lines.split_last().unwrap();
+        for line in chunks {
+            let message: Value = serde_json::from_str(line).unwrap();
+            assert_eq!(message["method"], "chunk");
+            assembler.push(&message["params"]).unwrap();
+        }
+        let response: Value = serde_json::from_str(last).unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["chunked"]["bytes"], serialized.len());
+        assert_eq!(assembler.take(&response["chunked"]).unwrap(), result);
+        assert!(is_chunked_method("get_git_diffs"));
+        assert!(!is_chunked_method("send_user_message"));
+    }
+
+    #[test]
+    fn out_of_order_or_missing_chunks_are_rejected() {
+        let mut assembler = ChunkAssembler::default();
+        let err = assembler
+            .push(&json!({ "opId": "op-2", "seq": 1, "data": "{}", "last": true }))
+            .unwrap_err();
+        assert!(err.contains("out of order"));
+
+        assembler
+            .push(&json!({ "opId": "op-3", "seq": 0, "data": "[1,", "last": false }))
+            .unwrap();
+        let err = assembler
+            .take(&json!({ "opId": "op-3", "chunks": 2 }))
+            .unwrap_err();
+        assert!(err.contains("incomplete"));
+    }
+}
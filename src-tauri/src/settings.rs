@@ -1,11 +1,24 @@
-use serde_json::json;
+use std::collections::HashSet;
+
+use serde_json::{json, Value};
 use tauri::{AppHandle, State, Window};
 
+use crate::backend::blocking::run_blocking;
+use crate::backend::keepalive::configure_keepalive;
+use crate::client_timezone::normalize_timezone_setting;
 use crate::codex_config;
+use crate::data_usage::{collect_data_usage, DataUsage};
+use crate::event_sink::deliver_notification;
+use crate::memory::connection_test::{
+    normalize_supabase_url, test_connection, MemoryConnectionReport,
+};
+use crate::notification_rules::{preview_rule, FiredNotification};
 use crate::remote_backend;
+use crate::resource_usage::{collect_resource_usage, ResourceUsage, RunningSession};
+use crate::settings_audit::{self, SettingsAuditEntry, DEFAULT_AUDIT_LIMIT};
 use crate::state::AppState;
 use crate::storage::write_settings;
-use crate::types::AppSettings;
+use crate::types::{AppSettings, AppSettingsUpdate};
 use crate::window;
 
 #[tauri::command]
@@ -41,30 +54,110 @@ pub(crate) async fn get_app_settings(
 #[tauri::command]
 pub(crate) async fn update_app_settings(
     settings: AppSettings,
+    validate_memory_on_save: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
     window: Window,
-) -> Result<AppSettings, String> {
+) -> Result<AppSettingsUpdate, String> {
+    let validate = validate_memory_on_save.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
             "update_app_settings",
-            json!({ "settings": settings }),
+            json!({ "settings": settings, "validateMemoryOnSave": validate }),
         )
         .await?;
-        let updated: AppSettings =
+        let updated: AppSettingsUpdate =
             serde_json::from_value(response).map_err(|err| err.to_string())?;
         let mut current = state.app_settings.lock().await;
-        *current = updated.clone();
-        let _ = window::apply_window_appearance(&window, updated.theme.as_str());
+        *current = updated.settings.clone();
+        let _ = window::apply_window_appearance(&window, updated.settings.theme.as_str());
         return Ok(updated);
     }
+    save_app_settings(settings, validate, "update_app_settings", &state, &window).await
+}
+
+/// Merges a sparse patch of top-level settings into the current ones, so a
+/// client only sends the fields it changed. Unknown keys are rejected.
+#[tauri::command]
+pub(crate) async fn update_app_settings_partial(
+    patch: Value,
+    validate_memory_on_save: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+    window: Window,
+) -> Result<AppSettingsUpdate, String> {
+    let validate = validate_memory_on_save.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "update_app_settings_partial",
+            json!({ "patch": patch, "validateMemoryOnSave": validate }),
+        )
+        .await?;
+        let updated: AppSettingsUpdate =
+            serde_json::from_value(response).map_err(|err| err.to_string())?;
+        let mut current = state.app_settings.lock().await;
+        *current = updated.settings.clone();
+        let _ = window::apply_window_appearance(&window, updated.settings.theme.as_str());
+        return Ok(updated);
+    }
+    let current = state.app_settings.lock().await.clone();
+    let settings = settings_audit::merge_settings_patch(&current, &patch)?;
+    save_app_settings(
+        settings,
+        validate,
+        "update_app_settings_partial",
+        &state,
+        &window,
+    )
+    .await
+}
+
+/// Recent settings changes, newest first, with secrets masked.
+#[tauri::command]
+pub(crate) async fn settings_audit(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<SettingsAuditEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "settings_audit", json!({ "limit": limit }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(settings_audit::read_settings_audit(
+        &settings_audit::settings_audit_path(&state.data_dir()),
+        limit.unwrap_or(DEFAULT_AUDIT_LIMIT),
+    ))
+}
+
+async fn save_app_settings(
+    mut settings: AppSettings,
+    validate: bool,
+    method: &str,
+    state: &State<'_, AppState>,
+    window: &Window,
+) -> Result<AppSettingsUpdate, String> {
+    settings.supabase_url = normalize_supabase_url(&settings.supabase_url);
+    settings.supabase_anon_key = settings.supabase_anon_key.trim().to_string();
+    settings.timezone = normalize_timezone_setting(settings.timezone.take())?;
     let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
     let _ = codex_config::write_steer_enabled(settings.experimental_steer_enabled);
     let _ = codex_config::write_unified_exec_enabled(settings.experimental_unified_exec_enabled);
     write_settings(&state.settings_path, &settings)?;
+    configure_keepalive(&settings.keepalive);
     let mut current = state.app_settings.lock().await;
+    settings_audit::record_settings_change(
+        &state.data_dir(),
+        method,
+        Some("local".to_string()),
+        &current,
+        &settings,
+    );
     *current = settings.clone();
     let mut memory_lock = state.memory.write().await;
     *memory_lock = if settings.memory_enabled
@@ -84,6 +177,96 @@ pub(crate) async fn update_app_settings(
     } else {
         None
     };
-    let _ = window::apply_window_appearance(&window, settings.theme.as_str());
-    Ok(settings)
+    drop(memory_lock);
+    drop(current);
+    let _ = window::apply_window_appearance(window, settings.theme.as_str());
+    let memory_connection = if validate {
+        Some(test_memory_settings(&settings).await)
+    } else {
+        None
+    };
+    Ok(AppSettingsUpdate {
+        settings,
+        memory_connection,
+    })
+}
+
+/// Runs the memory connection test against `settings`, which need not be saved.
+pub(crate) async fn test_memory_settings(settings: &AppSettings) -> MemoryConnectionReport {
+    test_connection(
+        &settings.supabase_url,
+        &settings.supabase_anon_key,
+        settings
+            .memory_embedding_enabled
+            .then_some(settings.minimax_api_key.as_str()),
+    )
+    .await
+}
+
+/// Fires a notification rule once with sample values so users can check the
+/// title, body and sound without waiting for a real event.
+#[tauri::command]
+pub(crate) async fn notification_rules_test(
+    rule_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FiredNotification, String> {
+    let fired = if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app.clone(),
+            "notification_rules_test",
+            json!({ "ruleId": rule_id }),
+        )
+        .await?;
+        serde_json::from_value(response).map_err(|err| err.to_string())?
+    } else {
+        let settings = state.app_settings.lock().await;
+        preview_rule(&settings.notification_rules, &rule_id)?
+    };
+    deliver_notification(&app, &fired);
+    Ok(fired)
+}
+
+#[tauri::command]
+pub(crate) async fn data_usage(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DataUsage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "data_usage", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(collect_data_usage(&state.data_dir()))
+}
+
+#[tauri::command]
+pub(crate) async fn resource_usage(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ResourceUsage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "resource_usage", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let warm_sessions = state.app_settings.lock().await.warm_sessions;
+    let keep_warm: HashSet<String> = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .filter(|entry| entry.settings.keep_warm)
+        .map(|entry| entry.id.clone())
+        .collect();
+    let sessions: Vec<_> = state.sessions.lock().await.values().cloned().collect();
+    let mut running = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        running.push(RunningSession {
+            keep_warm: keep_warm.contains(&session.entry.id),
+            workspace_id: session.entry.id.clone(),
+            pid: session.child.lock().await.id(),
+        });
+    }
+    run_blocking(move || collect_resource_usage(running, warm_sessions)).await
 }
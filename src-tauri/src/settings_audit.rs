@@ -0,0 +1,289 @@
+//! Audit trail for app settings. Every save is diffed field by field against
+//! the previous settings and appended to `settings_audit.jsonl` in the data
+//! dir, with secrets masked. Also merges the sparse patches sent to
+//! `update_app_settings_partial`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::types::AppSettings;
+use crate::workspace_settings::ensure_known_settings_fields;
+
+pub(crate) const SETTINGS_AUDIT_FILE: &str = "settings_audit.jsonl";
+/// The log is rotated to `settings_audit.jsonl.1` once it reaches this size,
+/// so at most two files' worth of history is kept.
+const MAX_AUDIT_BYTES: u64 = 256 * 1024;
+pub(crate) const DEFAULT_AUDIT_LIMIT: usize = 50;
+const MAX_AUDIT_LIMIT: usize = 500;
+const REDACTED: &str = "<redacted>";
+
+/// Settings whose values never reach the log. Only whether they were set or
+/// cleared is visible.
+const SECRET_KEYS: &[&str] = &[
+    "remoteBackendToken",
+    "supabase_anon_key",
+    "minimax_api_key",
+    "tmdb_api_key",
+    "igdb_client_secret",
+    "exa_api_key",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct SettingChange {
+    /// Serialized setting name; nested fields are dotted, e.g.
+    /// `autoMemory.enabled`.
+    pub(crate) key: String,
+    pub(crate) before: Value,
+    pub(crate) after: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct SettingsAuditEntry {
+    pub(crate) at: i64,
+    pub(crate) method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) client: Option<String>,
+    pub(crate) changes: Vec<SettingChange>,
+}
+
+pub(crate) fn settings_audit_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_AUDIT_FILE)
+}
+
+/// Field-level differences between two settings, secrets masked.
+pub(crate) fn diff_settings(before: &AppSettings, after: &AppSettings) -> Vec<SettingChange> {
+    let (Ok(before), Ok(after)) = (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+    let mut changes = Vec::new();
+    diff_values("", &before, &after, &mut changes);
+    for change in &mut changes {
+        change.before = masked_setting(&change.key, change.before.take());
+        change.after = masked_setting(&change.key, change.after.take());
+    }
+    changes
+}
+
+/// `value`, masked when `key` names a secret setting.
+pub(crate) fn masked_setting(key: &str, value: Value) -> Value {
+    if SECRET_KEYS.contains(&key) {
+        mask(&value)
+    } else {
+        value
+    }
+}
+
+fn diff_values(prefix: &str, before: &Value, after: &Value, changes: &mut Vec<SettingChange>) {
+    if let (Value::Object(before), Value::Object(after)) = (before, after) {
+        let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            diff_values(
+                &path,
+                before.get(key).unwrap_or(&Value::Null),
+                after.get(key).unwrap_or(&Value::Null),
+                changes,
+            );
+        }
+    } else if before != after {
+        changes.push(SettingChange {
+            key: prefix.to_string(),
+            before: before.clone(),
+            after: after.clone(),
+        });
+    }
+}
+
+fn mask(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        Value::String(text) if text.is_empty() => Value::String(String::new()),
+        _ => Value::String(REDACTED.to_string()),
+    }
+}
+
+/// Diffs and logs a settings save. Saves that change nothing are not logged,
+/// and a failed write is only reported on stderr so it never blocks the save.
+pub(crate) fn record_settings_change(
+    data_dir: &Path,
+    method: &str,
+    client: Option<String>,
+    before: &AppSettings,
+    after: &AppSettings,
+) {
+    let changes = diff_settings(before, after);
+    if changes.is_empty() {
+        return;
+    }
+    let entry = SettingsAuditEntry {
+        at: chrono::Utc::now().timestamp_millis(),
+        method: method.to_string(),
+        client,
+        changes,
+    };
+    if let Err(err) = append_audit_entry(&settings_audit_path(data_dir), &entry) {
+        eprintln!("settings audit: failed to record {method}: {err}");
+    }
+}
+
+/// Appends one JSON line to an audit log, rotating it first once it is full.
+/// Shared with `terminal_audit`.
+pub(crate) fn append_audit_entry(path: &Path, entry: &impl Serialize) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    if fs::metadata(path).is_ok_and(|metadata| metadata.len() >= MAX_AUDIT_BYTES) {
+        fs::rename(path, rotated_path(path)).map_err(|err| err.to_string())?;
+    }
+    let mut line = serde_json::to_string(entry).map_err(|err| err.to_string())?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|err| err.to_string())
+}
+
+/// Most recent entries first, across the current and rotated log. Lines that
+/// fail to parse are skipped.
+pub(crate) fn read_settings_audit(path: &Path, limit: usize) -> Vec<SettingsAuditEntry> {
+    let limit = limit.clamp(1, MAX_AUDIT_LIMIT);
+    let mut entries = Vec::new();
+    for file in [path.to_path_buf(), rotated_path(path)] {
+        let Ok(contents) = fs::read_to_string(&file) else {
+            continue;
+        };
+        for line in contents.lines().rev() {
+            if entries.len() >= limit {
+                return entries;
+            }
+            if let Ok(entry) = serde_json::from_str(line) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+/// Applies a sparse patch of top-level settings to `current`. Keys must be
+/// `AppSettings` field names; nested objects such as `autoMemory` are
+/// replaced whole.
+pub(crate) fn merge_settings_patch(
+    current: &AppSettings,
+    patch: &Value,
+) -> Result<AppSettings, String> {
+    let Value::Object(patch) = patch else {
+        return Err("Settings patch must be an object.".to_string());
+    };
+    ensure_known_settings_fields::<AppSettings>(patch)?;
+    let Value::Object(mut merged) = serde_json::to_value(current).map_err(|err| err.to_string())?
+    else {
+        return Err("Settings are not an object.".to_string());
+    };
+    for (key, value) in patch {
+        merged.insert(key.clone(), value.clone());
+    }
+    serde_json::from_value(Value::Object(merged))
+        .map_err(|err| format!("Invalid settings patch: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        append_audit_entry, diff_settings, merge_settings_patch, read_settings_audit,
+        SettingsAuditEntry,
+    };
+    use crate::types::AppSettings;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn diffs_fields_and_masks_secrets() {
+        let before = AppSettings::default();
+        let mut after = before.clone();
+        after.theme = "dark".to_string();
+        after.exa_api_key = "sk-live".to_string();
+        after.auto_memory.enabled = !before.auto_memory.enabled;
+        let changes = diff_settings(&before, &after);
+        let keys: Vec<&str> = changes.iter().map(|change| change.key.as_str()).collect();
+        assert_eq!(keys, vec!["autoMemory.enabled", "exa_api_key", "theme"]);
+        assert_eq!(changes[1].before, json!(""));
+        assert_eq!(changes[1].after, json!("<redacted>"));
+        assert_eq!(changes[2].after, json!("dark"));
+        assert!(diff_settings(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn merges_patches_and_rejects_unknown_keys() {
+        let current = AppSettings::default();
+        let merged = merge_settings_patch(&current, &json!({ "theme": "light", "uiScale": 1.25 }))
+            .expect("merge");
+        assert_eq!(merged.theme, "light");
+        assert_eq!(merged.ui_scale, 1.25);
+        assert_eq!(merged.code_font_size, current.code_font_size);
+
+        let err = merge_settings_patch(&current, &json!({ "themes": "x", "bogus": 1 }))
+            .expect_err("unknown keys");
+        assert_eq!(
+            err,
+            r#"unknown_settings_fields: {"unknownFields":["bogus","themes"]}"#
+        );
+        assert!(merge_settings_patch(&current, &json!({ "uiScale": "big" })).is_err());
+        assert!(merge_settings_patch(&current, &json!(["theme"])).is_err());
+    }
+
+    #[test]
+    fn patches_an_unset_optional_setting() {
+        let current = AppSettings::default();
+        assert!(current.timezone.is_none());
+        let merged = merge_settings_patch(&current, &json!({ "timezone": "Europe/Berlin" }))
+            .expect("first write of an unset field");
+        assert_eq!(merged.timezone.as_deref(), Some("Europe/Berlin"));
+        let cleared = merge_settings_patch(&merged, &json!({ "timezone": null })).expect("clear");
+        assert!(cleared.timezone.is_none());
+    }
+
+    #[test]
+    fn reads_newest_entries_first() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("settings_audit.jsonl");
+        for at in 0..3 {
+            append_audit_entry(
+                &path,
+                &SettingsAuditEntry {
+                    at,
+                    method: "update_app_settings".to_string(),
+                    client: None,
+                    changes: Vec::new(),
+                },
+            )
+            .expect("append");
+        }
+        std::fs::write(
+            dir.path().join("settings_audit.jsonl.1"),
+            "{\"at\":-1,\"method\":\"old\",\"changes\":[]}\nnot json\n",
+        )
+        .expect("write rotated");
+        let entries = read_settings_audit(&path, 10);
+        let at: Vec<i64> = entries.iter().map(|entry| entry.at).collect();
+        assert_eq!(at, vec![2, 1, 0, -1]);
+        assert_eq!(read_settings_audit(&path, 2).len(), 2);
+    }
+}
@@ -1,13 +1,19 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use tauri::{AppHandle, Manager};
 use tokio::sync::{Mutex, RwLock};
 
 use crate::auto_flush::AutoMemoryRuntime;
+use crate::backend::keepalive::configure_keepalive;
+use crate::client_timezone::{resolve_client_timezone, ClientTimezone};
 use crate::dictation::DictationState;
+use crate::event_sink::TauriEventSink;
+use crate::file_tail::FileFollowers;
+use crate::life_transcripts::LifeTranscripts;
 use crate::memory::MemoryService;
+use crate::quick_commands::QuickCommands;
 use crate::storage::{
     read_domains, read_settings, read_workspaces, seed_domains_from_files, write_domains,
 };
@@ -16,7 +22,8 @@ use crate::types::{AppSettings, Domain, WorkspaceEntry};
 pub(crate) struct AppState {
     pub(crate) workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
     pub(crate) sessions: Mutex<HashMap<String, Arc<crate::codex::WorkspaceSession>>>,
-    pub(crate) terminal_sessions: Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
+    pub(crate) terminal_sessions: crate::terminal_core::TerminalSessions,
+    pub(crate) thread_views: crate::thread_activity::ThreadViews,
     pub(crate) remote_backend: Mutex<Option<crate::remote_backend::RemoteBackend>>,
     pub(crate) storage_path: PathBuf,
     pub(crate) settings_path: PathBuf,
@@ -26,6 +33,9 @@ pub(crate) struct AppState {
     pub(crate) dictation: Mutex<DictationState>,
     pub(crate) memory: RwLock<Option<MemoryService>>,
     pub(crate) auto_memory_runtime: Mutex<AutoMemoryRuntime>,
+    pub(crate) file_followers: FileFollowers<TauriEventSink>,
+    pub(crate) quick_commands: QuickCommands<TauriEventSink>,
+    pub(crate) life_transcripts: LifeTranscripts<TauriEventSink>,
 }
 
 impl AppState {
@@ -39,6 +49,7 @@ impl AppState {
         let domains_path = data_dir.join("domains.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        configure_keepalive(&app_settings.keepalive);
         let mut domains = read_domains(&domains_path).unwrap_or_default();
         if domains.is_empty() {
             let seeded = seed_domains_from_files();
@@ -68,7 +79,10 @@ impl AppState {
         Self {
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
-            terminal_sessions: Mutex::new(HashMap::new()),
+            terminal_sessions: Arc::new(Mutex::new(HashMap::new())),
+            thread_views: crate::thread_activity::ThreadViews::load(
+                crate::thread_activity::thread_views_path(&settings_path),
+            ),
             remote_backend: Mutex::new(None),
             storage_path,
             settings_path,
@@ -78,6 +92,27 @@ impl AppState {
             dictation: Mutex::new(DictationState::default()),
             memory: RwLock::new(memory),
             auto_memory_runtime: Mutex::new(AutoMemoryRuntime::default()),
+            file_followers: FileFollowers::new(TauriEventSink::new(app.clone())),
+            quick_commands: QuickCommands::new(TauriEventSink::new(app.clone())),
+            life_transcripts: LifeTranscripts::new(TauriEventSink::new(app.clone())),
         }
     }
+    /// Folder holding `workspaces.json` and the other app data files.
+    pub(crate) fn data_dir(&self) -> PathBuf {
+        self.storage_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// The zone a request counts days in: its `timezone` param, then
+    /// `AppSettings.timezone`, then `fallback`.
+    pub(crate) async fn client_timezone(
+        &self,
+        requested: Option<&str>,
+        fallback: ClientTimezone,
+    ) -> Result<ClientTimezone, String> {
+        let configured = self.app_settings.lock().await.timezone.clone();
+        resolve_client_timezone(requested, configured.as_deref(), fallback)
+    }
 }
@@ -1,5 +1,5 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use crate::types::{AppSettings, Domain, DomainTheme, WorkspaceEntry};
 
@@ -39,6 +39,32 @@ pub(crate) fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(
     std::fs::write(path, data).map_err(|e| e.to_string())
 }
 
+/// Pinned thread keys (`workspaceId:threadId`) live next to the settings file
+/// so background tasks can honor pins the frontend owns.
+pub(crate) fn pinned_threads_path(settings_path: &Path) -> PathBuf {
+    settings_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("pinned_threads.json")
+}
+
+pub(crate) fn read_pinned_threads(path: &PathBuf) -> Result<HashSet<String>, String> {
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let list: Vec<String> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    Ok(list.into_iter().collect())
+}
+
+pub(crate) fn write_pinned_threads(path: &PathBuf, keys: &[String]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(keys).map_err(|e| e.to_string())?;
+    std::fs::write(path, data).map_err(|e| e.to_string())
+}
+
 pub(crate) fn read_domains(path: &PathBuf) -> Result<Vec<Domain>, String> {
     if !path.exists() {
         return Ok(Vec::new());
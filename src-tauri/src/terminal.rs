@@ -1,112 +1,18 @@
-use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::sync::Arc;
 
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
-use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{AppHandle, State};
-use tokio::sync::Mutex;
 
-use crate::backend::events::{EventSink, TerminalOutput};
+use crate::backend::path_health::ensure_path_available;
 use crate::event_sink::TauriEventSink;
+use crate::read_only::ensure_workspace_writable;
 use crate::remote_backend;
 use crate::state::AppState;
-
-pub(crate) struct TerminalSession {
-    pub(crate) id: String,
-    pub(crate) master: Mutex<Box<dyn portable_pty::MasterPty + Send>>,
-    pub(crate) writer: Mutex<Box<dyn Write + Send>>,
-    pub(crate) child: Mutex<Box<dyn portable_pty::Child + Send>>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub(crate) struct TerminalSessionInfo {
-    id: String,
-}
-
-fn terminal_key(workspace_id: &str, terminal_id: &str) -> String {
-    format!("{workspace_id}:{terminal_id}")
-}
-
-fn shell_path() -> String {
-    std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string())
-}
-
-fn resolve_locale() -> String {
-    let candidate = std::env::var("LC_ALL")
-        .or_else(|_| std::env::var("LANG"))
-        .unwrap_or_else(|_| "en_US.UTF-8".to_string());
-    let lower = candidate.to_lowercase();
-    if lower.contains("utf-8") || lower.contains("utf8") {
-        return candidate;
-    }
-    "en_US.UTF-8".to_string()
-}
-
-fn spawn_terminal_reader(
-    event_sink: impl EventSink,
-    workspace_id: String,
-    terminal_id: String,
-    mut reader: Box<dyn Read + Send>,
-) {
-    std::thread::spawn(move || {
-        let mut buffer = [0u8; 8192];
-        let mut pending: Vec<u8> = Vec::new();
-        loop {
-            match reader.read(&mut buffer) {
-                Ok(0) => break,
-                Ok(count) => {
-                    pending.extend_from_slice(&buffer[..count]);
-                    loop {
-                        match std::str::from_utf8(&pending) {
-                            Ok(decoded) => {
-                                if !decoded.is_empty() {
-                                    let payload = TerminalOutput {
-                                        workspace_id: workspace_id.clone(),
-                                        terminal_id: terminal_id.clone(),
-                                        data: decoded.to_string(),
-                                    };
-                                    event_sink.emit_terminal_output(payload);
-                                }
-                                pending.clear();
-                                break;
-                            }
-                            Err(error) => {
-                                let valid_up_to = error.valid_up_to();
-                                if valid_up_to == 0 {
-                                    if error.error_len().is_none() {
-                                        break;
-                                    }
-                                    let invalid_len = error.error_len().unwrap_or(1);
-                                    pending.drain(..invalid_len.min(pending.len()));
-                                    continue;
-                                }
-                                let chunk =
-                                    String::from_utf8_lossy(&pending[..valid_up_to]).to_string();
-                                if !chunk.is_empty() {
-                                    let payload = TerminalOutput {
-                                        workspace_id: workspace_id.clone(),
-                                        terminal_id: terminal_id.clone(),
-                                        data: chunk,
-                                    };
-                                    event_sink.emit_terminal_output(payload);
-                                }
-                                pending.drain(..valid_up_to);
-                                if error.error_len().is_none() {
-                                    break;
-                                }
-                                let invalid_len = error.error_len().unwrap_or(1);
-                                pending.drain(..invalid_len.min(pending.len()));
-                            }
-                        }
-                    }
-                }
-                Err(_) => break,
-            }
-        }
-    });
-}
+use crate::terminal_core::{
+    self, TerminalLaunch, TerminalListEntry, TerminalScrollback, TerminalSessionInfo,
+    TerminalSnapshot,
+};
+use crate::terminal_search::{TerminalSearchOptions, TerminalSearchResult};
 
 async fn get_workspace_path(
     workspace_id: &str,
@@ -120,14 +26,18 @@ async fn get_workspace_path(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn terminal_open(
     workspace_id: String,
     terminal_id: String,
     cols: u16,
     rows: u16,
+    initial_command: Option<String>,
+    login_shell: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<TerminalSessionInfo, String> {
+    let login_shell = login_shell.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -138,81 +48,41 @@ pub(crate) async fn terminal_open(
                 "terminalId": terminal_id,
                 "cols": cols,
                 "rows": rows,
+                "initialCommand": initial_command,
+                "loginShell": login_shell,
             }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-    if terminal_id.is_empty() {
-        return Err("Terminal id is required".to_string());
-    }
-    let key = terminal_key(&workspace_id, &terminal_id);
-    {
-        let sessions = state.terminal_sessions.lock().await;
-        if let Some(existing) = sessions.get(&key) {
-            return Ok(TerminalSessionInfo {
-                id: existing.id.clone(),
-            });
-        }
-    }
-
+    ensure_path_available(&workspace_id)?;
     let cwd = get_workspace_path(&workspace_id, &state).await?;
-    let pty_system = native_pty_system();
-    let size = PtySize {
-        rows: rows.max(2),
-        cols: cols.max(2),
-        pixel_width: 0,
-        pixel_height: 0,
+    let (shell_override, audit_log, scrollback_bytes) = {
+        let settings = state.app_settings.lock().await;
+        (
+            settings.terminal_shell.clone(),
+            settings.terminal_audit_log,
+            settings.terminal_scrollback_bytes,
+        )
     };
-    let pair = pty_system
-        .openpty(size)
-        .map_err(|e| format!("Failed to open pty: {e}"))?;
-
-    let mut cmd = CommandBuilder::new(shell_path());
-    cmd.cwd(cwd);
-    cmd.arg("-i");
-    cmd.env("TERM", "xterm-256color");
-    let locale = resolve_locale();
-    cmd.env("LANG", &locale);
-    cmd.env("LC_ALL", &locale);
-    cmd.env("LC_CTYPE", &locale);
-
-    let child = pair
-        .slave
-        .spawn_command(cmd)
-        .map_err(|e| format!("Failed to spawn shell: {e}"))?;
-    let reader = pair
-        .master
-        .try_clone_reader()
-        .map_err(|e| format!("Failed to open pty reader: {e}"))?;
-    let writer = pair
-        .master
-        .take_writer()
-        .map_err(|e| format!("Failed to open pty writer: {e}"))?;
-
-    let session = Arc::new(TerminalSession {
-        id: terminal_id.clone(),
-        master: Mutex::new(pair.master),
-        writer: Mutex::new(writer),
-        child: Mutex::new(child),
-    });
-    let session_id = session.id.clone();
-
-    {
-        let mut sessions = state.terminal_sessions.lock().await;
-        if let Some(existing) = sessions.get(&key) {
-            let mut child = session.child.lock().await;
-            let _ = child.kill();
-            return Ok(TerminalSessionInfo {
-                id: existing.id.clone(),
-            });
-        }
-        sessions.insert(key, session);
-    }
-    let event_sink = TauriEventSink::new(app);
-    spawn_terminal_reader(event_sink, workspace_id, terminal_id, reader);
-
-    Ok(TerminalSessionInfo { id: session_id })
+    let data_dir = state.data_dir();
+    terminal_core::open_terminal(
+        &state.terminal_sessions,
+        TauriEventSink::new(app),
+        workspace_id,
+        terminal_id,
+        &cwd,
+        TerminalLaunch {
+            shell_override: shell_override.as_deref(),
+            login_shell,
+            initial_command,
+            audit_dir: audit_log.then_some(data_dir.as_path()),
+            scrollback_bytes,
+        },
+        cols,
+        rows,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -233,19 +103,11 @@ pub(crate) async fn terminal_write(
         .await?;
         return Ok(());
     }
-    let key = terminal_key(&workspace_id, &terminal_id);
-    let sessions = state.terminal_sessions.lock().await;
-    let session = sessions
-        .get(&key)
-        .ok_or_else(|| "Terminal session not found".to_string())?;
-    let mut writer = session.writer.lock().await;
-    writer
-        .write_all(data.as_bytes())
-        .map_err(|e| format!("Failed to write to pty: {e}"))?;
-    writer
-        .flush()
-        .map_err(|e| format!("Failed to flush pty: {e}"))?;
-    Ok(())
+    ensure_workspace_writable(&state.workspaces, &workspace_id, "write to a terminal").await?;
+    terminal_core::find_terminal(&state.terminal_sessions, &workspace_id, &terminal_id)
+        .await?
+        .write(&data)
+        .await
 }
 
 #[tauri::command]
@@ -272,22 +134,10 @@ pub(crate) async fn terminal_resize(
         .await?;
         return Ok(());
     }
-    let key = terminal_key(&workspace_id, &terminal_id);
-    let sessions = state.terminal_sessions.lock().await;
-    let session = sessions
-        .get(&key)
-        .ok_or_else(|| "Terminal session not found".to_string())?;
-    let size = PtySize {
-        rows: rows.max(2),
-        cols: cols.max(2),
-        pixel_width: 0,
-        pixel_height: 0,
-    };
-    let master = session.master.lock().await;
-    master
-        .resize(size)
-        .map_err(|e| format!("Failed to resize pty: {e}"))?;
-    Ok(())
+    terminal_core::find_terminal(&state.terminal_sessions, &workspace_id, &terminal_id)
+        .await?
+        .resize(cols, rows)
+        .await
 }
 
 #[tauri::command]
@@ -307,12 +157,98 @@ pub(crate) async fn terminal_close(
         .await?;
         return Ok(());
     }
-    let key = terminal_key(&workspace_id, &terminal_id);
-    let mut sessions = state.terminal_sessions.lock().await;
-    let session = sessions
-        .remove(&key)
-        .ok_or_else(|| "Terminal session not found".to_string())?;
-    let mut child = session.child.lock().await;
-    let _ = child.kill();
-    Ok(())
+    terminal_core::close_terminal(&state.terminal_sessions, &workspace_id, &terminal_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn terminal_list(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<TerminalListEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_list",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    Ok(terminal_core::list_terminals(&state.terminal_sessions, &workspace_id).await)
+}
+
+#[tauri::command]
+pub(crate) async fn terminal_scrollback(
+    workspace_id: String,
+    terminal_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TerminalScrollback, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_scrollback",
+            json!({ "workspaceId": workspace_id, "terminalId": terminal_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    terminal_core::terminal_scrollback(&state.terminal_sessions, &workspace_id, &terminal_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn terminal_snapshot(
+    workspace_id: String,
+    terminal_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TerminalSnapshot, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_snapshot",
+            json!({ "workspaceId": workspace_id, "terminalId": terminal_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    terminal_core::terminal_snapshot(&state.terminal_sessions, &workspace_id, &terminal_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn terminal_search(
+    workspace_id: String,
+    terminal_id: String,
+    query: String,
+    options: Option<TerminalSearchOptions>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TerminalSearchResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "terminal_search",
+            json!({
+                "workspaceId": workspace_id,
+                "terminalId": terminal_id,
+                "query": query,
+                "options": options,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    terminal_core::search_terminal(
+        &state.terminal_sessions,
+        &workspace_id,
+        &terminal_id,
+        &query,
+        &options.unwrap_or_default(),
+    )
+    .await
 }
@@ -0,0 +1,65 @@
+//! Audit trail for commands sent to terminals on open. Enabled by the
+//! `terminalAuditLog` setting; entries go to `terminal_audit.jsonl` in the data
+//! dir, with the same rotation as the settings audit log.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::settings_audit::append_audit_entry;
+
+pub(crate) const TERMINAL_AUDIT_FILE: &str = "terminal_audit.jsonl";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalAuditEntry {
+    pub(crate) at: i64,
+    pub(crate) workspace_id: String,
+    pub(crate) terminal_id: String,
+    pub(crate) command: String,
+}
+
+pub(crate) fn terminal_audit_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(TERMINAL_AUDIT_FILE)
+}
+
+/// Logs a command written to a terminal. A failed write is only reported on
+/// stderr so it never keeps the command from running.
+pub(crate) fn record_terminal_command(
+    data_dir: &Path,
+    workspace_id: &str,
+    terminal_id: &str,
+    command: &str,
+) {
+    let entry = TerminalAuditEntry {
+        at: chrono::Utc::now().timestamp_millis(),
+        workspace_id: workspace_id.to_string(),
+        terminal_id: terminal_id.to_string(),
+        command: command.to_string(),
+    };
+    if let Err(err) = append_audit_entry(&terminal_audit_path(data_dir), &entry) {
+        eprintln!("terminal audit: failed to record command for {terminal_id}: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record_terminal_command, terminal_audit_path, TerminalAuditEntry};
+    use tempfile::tempdir;
+
+    #[test]
+    fn records_one_line_per_command() {
+        let dir = tempdir().expect("tempdir");
+        record_terminal_command(dir.path(), "ws-1", "term-1", "tail -f app.log");
+        record_terminal_command(dir.path(), "ws-1", "term-2", "cargo test");
+
+        let contents = std::fs::read_to_string(terminal_audit_path(dir.path())).expect("log");
+        let entries: Vec<TerminalAuditEntry> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("entry"))
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "tail -f app.log");
+        assert_eq!(entries[1].terminal_id, "term-2");
+        assert!(contents.contains("\"workspaceId\":\"ws-1\""));
+    }
+}
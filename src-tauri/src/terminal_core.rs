@@ -0,0 +1,719 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+
+use portable_pty::{native_pty_system, PtySize};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{oneshot, Mutex};
+
+use crate::backend::events::{EventSink, TerminalExited, TerminalOutput};
+use crate::backend::supervisor::{spawn_guarded, spawn_guarded_thread};
+use crate::terminal_audit::record_terminal_command;
+use crate::terminal_search::{search_scrollback, TerminalSearchOptions, TerminalSearchResult};
+use crate::terminal_shell::build_terminal_command;
+
+/// Output kept per terminal so a client that attaches later can repaint,
+/// unless `terminalScrollbackBytes` says otherwise.
+pub(crate) const SCROLLBACK_BYTES: usize = 256 * 1024;
+const MIN_SCROLLBACK_BYTES: usize = 4 * 1024;
+const MAX_SCROLLBACK_BYTES: usize = 4 * 1024 * 1024;
+/// Scrollback kept by all terminals together; see `ScrollbackBudget`.
+const TOTAL_SCROLLBACK_BYTES: usize = 32 * 1024 * 1024;
+
+/// An initial command waits for the shell's first output, usually its prompt,
+/// and then this long for the prompt to finish drawing.
+const INITIAL_COMMAND_SETTLE: Duration = Duration::from_millis(150);
+/// Shells that print nothing before reading input get the command after this.
+const INITIAL_COMMAND_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long a shell may outlive its PTY closing before it is killed.
+const EXIT_WAIT: Duration = Duration::from_secs(2);
+
+/// Shared with the reader threads, which drop a session once its shell exits.
+pub(crate) type TerminalSessions = Arc<Mutex<HashMap<String, Arc<TerminalSession>>>>;
+
+pub(crate) struct TerminalSession {
+    pub(crate) id: String,
+    pub(crate) master: Mutex<Box<dyn portable_pty::MasterPty + Send>>,
+    pub(crate) writer: Mutex<Box<dyn Write + Send>>,
+    pub(crate) child: Mutex<Box<dyn portable_pty::Child + Send>>,
+    size: std::sync::Mutex<(u16, u16)>,
+    scrollback: Arc<std::sync::Mutex<Scrollback>>,
+}
+
+/// How a new terminal starts. Ignored when the terminal is already open.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct TerminalLaunch<'a> {
+    /// The `terminalShell` setting.
+    pub(crate) shell_override: Option<&'a str>,
+    pub(crate) login_shell: bool,
+    /// Typed into the shell once it is ready, followed by Enter.
+    pub(crate) initial_command: Option<String>,
+    /// Data dir of the terminal audit log; `None` when the log is off.
+    pub(crate) audit_dir: Option<&'a Path>,
+    /// The `terminalScrollbackBytes` setting.
+    pub(crate) scrollback_bytes: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TerminalSessionInfo {
+    pub(crate) id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalListEntry {
+    pub(crate) terminal_id: String,
+    pub(crate) cols: u16,
+    pub(crate) rows: u16,
+    pub(crate) scrollback_bytes: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalScrollback {
+    pub(crate) data: String,
+    /// Set once older output has been dropped to stay under the cap.
+    pub(crate) truncated: bool,
+}
+
+/// The scrollback plus the terminal size, for a client repainting after it
+/// reconnects.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalSnapshot {
+    pub(crate) data: String,
+    pub(crate) truncated: bool,
+    pub(crate) cols: u16,
+    pub(crate) rows: u16,
+}
+
+pub(crate) fn terminal_key(workspace_id: &str, terminal_id: &str) -> String {
+    format!("{workspace_id}:{terminal_id}")
+}
+
+/// The per-terminal cap for a `terminalScrollbackBytes` setting, clamped to
+/// 4 KB..4 MB.
+fn scrollback_cap(setting: Option<usize>) -> usize {
+    setting
+        .unwrap_or(SCROLLBACK_BYTES)
+        .clamp(MIN_SCROLLBACK_BYTES, MAX_SCROLLBACK_BYTES)
+}
+
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows: rows.max(2),
+        cols: cols.max(2),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Turns PTY reads into text. A multi-byte character split across reads is
+/// held until the rest arrives; invalid bytes are dropped.
+#[derive(Default)]
+pub(crate) struct Utf8Chunker {
+    pending: Vec<u8>,
+}
+
+impl Utf8Chunker {
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut output = String::new();
+        loop {
+            match std::str::from_utf8(&self.pending) {
+                Ok(decoded) => {
+                    output.push_str(decoded);
+                    self.pending.clear();
+                    return output;
+                }
+                Err(error) => {
+                    let valid_up_to = error.valid_up_to();
+                    output.push_str(&String::from_utf8_lossy(&self.pending[..valid_up_to]));
+                    match error.error_len() {
+                        Some(invalid_len) => {
+                            self.pending.drain(..valid_up_to + invalid_len);
+                        }
+                        None => {
+                            self.pending.drain(..valid_up_to);
+                            return output;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bytes held by all the scrollbacks drawing on it. Past `limit`, a
+/// terminal that receives output trims itself to an equal share, so busy
+/// terminals give memory back first.
+pub(crate) struct ScrollbackBudget {
+    limit: usize,
+    used: AtomicUsize,
+    buffers: AtomicUsize,
+}
+
+impl ScrollbackBudget {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+            buffers: AtomicUsize::new(0),
+        }
+    }
+
+    /// The budget shared by every terminal in the process.
+    fn shared() -> Arc<Self> {
+        static SHARED: OnceLock<Arc<ScrollbackBudget>> = OnceLock::new();
+        Arc::clone(SHARED.get_or_init(|| Arc::new(Self::new(TOTAL_SCROLLBACK_BYTES))))
+    }
+
+    /// What one scrollback may keep while the budget is exceeded.
+    fn share(&self) -> Option<usize> {
+        if self.used.load(Ordering::Relaxed) <= self.limit {
+            return None;
+        }
+        Some(self.limit / self.buffers.load(Ordering::Relaxed).max(1))
+    }
+}
+
+/// The most recent output of a terminal, at most `cap` bytes.
+pub(crate) struct Scrollback {
+    data: String,
+    cap: usize,
+    truncated: bool,
+    budget: Arc<ScrollbackBudget>,
+}
+
+impl Scrollback {
+    pub(crate) fn new(cap: usize, budget: Arc<ScrollbackBudget>) -> Self {
+        budget.buffers.fetch_add(1, Ordering::Relaxed);
+        Self {
+            data: String::new(),
+            cap,
+            truncated: false,
+            budget,
+        }
+    }
+
+    /// Appends `text` and drops the oldest output past the cap, or past this
+    /// terminal's share while the budget is exceeded, cutting on a character
+    /// boundary.
+    pub(crate) fn push(&mut self, text: &str) {
+        self.data.push_str(text);
+        self.budget.used.fetch_add(text.len(), Ordering::Relaxed);
+        self.trim_to(self.cap);
+        if let Some(share) = self.budget.share() {
+            self.trim_to(share);
+        }
+    }
+
+    fn trim_to(&mut self, limit: usize) {
+        if self.data.len() <= limit {
+            return;
+        }
+        let mut cut = self.data.len() - limit;
+        while !self.data.is_char_boundary(cut) {
+            cut += 1;
+        }
+        self.data.drain(..cut);
+        self.budget.used.fetch_sub(cut, Ordering::Relaxed);
+        self.truncated = true;
+    }
+
+    pub(crate) fn snapshot(&self) -> TerminalScrollback {
+        TerminalScrollback {
+            data: self.data.clone(),
+            truncated: self.truncated,
+        }
+    }
+}
+
+impl Drop for Scrollback {
+    fn drop(&mut self) {
+        self.budget
+            .used
+            .fetch_sub(self.data.len(), Ordering::Relaxed);
+        self.budget.buffers.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl TerminalSession {
+    pub(crate) fn info(&self) -> TerminalSessionInfo {
+        TerminalSessionInfo {
+            id: self.id.clone(),
+        }
+    }
+
+    fn list_entry(&self) -> TerminalListEntry {
+        let (cols, rows) = *self.size.lock().unwrap_or_else(|err| err.into_inner());
+        let scrollback_bytes = self
+            .scrollback
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .data
+            .len();
+        TerminalListEntry {
+            terminal_id: self.id.clone(),
+            cols,
+            rows,
+            scrollback_bytes,
+        }
+    }
+
+    pub(crate) async fn write(&self, data: &str) -> Result<(), String> {
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(data.as_bytes())
+            .map_err(|e| format!("Failed to write to pty: {e}"))?;
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to flush pty: {e}"))
+    }
+
+    pub(crate) async fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        let size = pty_size(cols, rows);
+        let master = self.master.lock().await;
+        master
+            .resize(size)
+            .map_err(|e| format!("Failed to resize pty: {e}"))?;
+        *self.size.lock().unwrap_or_else(|err| err.into_inner()) = (size.cols, size.rows);
+        Ok(())
+    }
+
+    pub(crate) async fn kill(&self) {
+        let mut child = self.child.lock().await;
+        let _ = child.kill();
+    }
+}
+
+/// Spawns the shell in a new PTY. Returns the session and the PTY reader,
+/// which `spawn_terminal_reader` drains.
+fn spawn_session(
+    terminal_id: &str,
+    cwd: &Path,
+    shell_override: Option<&str>,
+    login_shell: bool,
+    scrollback_bytes: usize,
+    cols: u16,
+    rows: u16,
+) -> Result<(TerminalSession, Box<dyn Read + Send>), String> {
+    let size = pty_size(cols, rows);
+    let pair = native_pty_system()
+        .openpty(size)
+        .map_err(|e| format!("Failed to open pty: {e}"))?;
+    let cmd = build_terminal_command(shell_override, login_shell, cwd);
+    let child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to spawn shell: {e}"))?;
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("Failed to open pty reader: {e}"))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| format!("Failed to open pty writer: {e}"))?;
+    let session = TerminalSession {
+        id: terminal_id.to_string(),
+        master: Mutex::new(pair.master),
+        writer: Mutex::new(writer),
+        child: Mutex::new(child),
+        size: std::sync::Mutex::new((size.cols, size.rows)),
+        scrollback: Arc::new(std::sync::Mutex::new(Scrollback::new(
+            scrollback_bytes,
+            ScrollbackBudget::shared(),
+        ))),
+    };
+    Ok((session, reader))
+}
+
+/// Streams PTY output to `event_sink` and into the session's scrollback until
+/// the PTY closes. `ready` fires on the first output.
+///
+/// When the shell exits on its own the session is removed from `sessions`
+/// and `TerminalExited` is emitted. A session that was closed or replaced in
+/// the meantime is left alone and reports nothing.
+fn spawn_terminal_reader(
+    event_sink: impl EventSink,
+    sessions: TerminalSessions,
+    session: &Arc<TerminalSession>,
+    workspace_id: String,
+    terminal_id: String,
+    mut reader: Box<dyn Read + Send>,
+    mut ready: Option<oneshot::Sender<()>>,
+) {
+    let scrollback = Arc::clone(&session.scrollback);
+    let session = Arc::downgrade(session);
+    spawn_guarded_thread(event_sink.clone(), "terminal reader", move || {
+        let mut buffer = [0u8; 8192];
+        let mut chunker = Utf8Chunker::default();
+        loop {
+            let count = match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(count) => count,
+            };
+            let data = chunker.push(&buffer[..count]);
+            if data.is_empty() {
+                continue;
+            }
+            if let Some(ready) = ready.take() {
+                let _ = ready.send(());
+            }
+            scrollback
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .push(&data);
+            event_sink.emit_terminal_output(TerminalOutput {
+                workspace_id: workspace_id.clone(),
+                terminal_id: terminal_id.clone(),
+                data,
+            });
+        }
+        if let Some(exit_code) = reap_exited(&sessions, &session, &workspace_id, &terminal_id) {
+            event_sink.emit_terminal_exited(TerminalExited {
+                workspace_id,
+                terminal_id,
+                exit_code,
+            });
+        }
+    });
+}
+
+/// The shell's exit code once the PTY has closed. A shell still running after
+/// `EXIT_WAIT` has nothing left to talk to and is killed. The child lock is
+/// only held briefly so `kill` never waits on this.
+fn wait_for_exit(session: &TerminalSession) -> Option<i32> {
+    let deadline = std::time::Instant::now() + EXIT_WAIT;
+    loop {
+        let mut child = session.child.blocking_lock();
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status.exit_code() as i32),
+            Ok(None) if std::time::Instant::now() < deadline => {}
+            Ok(None) => {
+                let _ = child.kill();
+                return child.wait().ok().map(|status| status.exit_code() as i32);
+            }
+            Err(_) => return None,
+        }
+        drop(child);
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Waits for the shell behind a closed PTY and removes its session, if it is
+/// still the open one. Returns the exit code when it was removed. Runs on the
+/// reader thread, so it may block.
+fn reap_exited(
+    sessions: &TerminalSessions,
+    session: &Weak<TerminalSession>,
+    workspace_id: &str,
+    terminal_id: &str,
+) -> Option<Option<i32>> {
+    let session = session.upgrade()?;
+    let exit_code = wait_for_exit(&session);
+    let key = terminal_key(workspace_id, terminal_id);
+    let mut sessions = sessions.blocking_lock();
+    if !sessions
+        .get(&key)
+        .is_some_and(|current| Arc::ptr_eq(current, &session))
+    {
+        return None;
+    }
+    sessions.remove(&key);
+    Some(exit_code)
+}
+
+/// Opens a terminal, or returns the existing one with the same id.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn open_terminal(
+    sessions: &TerminalSessions,
+    event_sink: impl EventSink,
+    workspace_id: String,
+    terminal_id: String,
+    cwd: &Path,
+    launch: TerminalLaunch<'_>,
+    cols: u16,
+    rows: u16,
+) -> Result<TerminalSessionInfo, String> {
+    if terminal_id.is_empty() {
+        return Err("Terminal id is required".to_string());
+    }
+    let key = terminal_key(&workspace_id, &terminal_id);
+    if let Some(existing) = sessions.lock().await.get(&key) {
+        return Ok(existing.info());
+    }
+
+    let (session, reader) = spawn_session(
+        &terminal_id,
+        cwd,
+        launch.shell_override,
+        launch.login_shell,
+        scrollback_cap(launch.scrollback_bytes),
+        cols,
+        rows,
+    )?;
+    let session = Arc::new(session);
+    {
+        let mut sessions = sessions.lock().await;
+        if let Some(existing) = sessions.get(&key) {
+            session.kill().await;
+            return Ok(existing.info());
+        }
+        sessions.insert(key, Arc::clone(&session));
+    }
+    let initial_command = launch
+        .initial_command
+        .map(|command| command.trim_end().to_string())
+        .filter(|command| !command.trim().is_empty());
+    let Some(command) = initial_command else {
+        spawn_terminal_reader(
+            event_sink,
+            Arc::clone(sessions),
+            &session,
+            workspace_id,
+            terminal_id,
+            reader,
+            None,
+        );
+        return Ok(session.info());
+    };
+    if let Some(data_dir) = launch.audit_dir {
+        record_terminal_command(data_dir, &workspace_id, &terminal_id, &command);
+    }
+    let (ready_tx, ready_rx) = oneshot::channel();
+    spawn_terminal_reader(
+        event_sink.clone(),
+        Arc::clone(sessions),
+        &session,
+        workspace_id,
+        terminal_id,
+        reader,
+        Some(ready_tx),
+    );
+    let writer = Arc::clone(&session);
+    spawn_guarded(event_sink, "terminal initial command", async move {
+        if let Ok(Ok(())) = tokio::time::timeout(INITIAL_COMMAND_TIMEOUT, ready_rx).await {
+            tokio::time::sleep(INITIAL_COMMAND_SETTLE).await;
+        }
+        // `\r` is what Enter sends; the PTY turns it into a newline for the shell.
+        if let Err(err) = writer.write(&format!("{command}\r")).await {
+            eprintln!("terminal: failed to send initial command: {err}");
+        }
+    });
+    Ok(session.info())
+}
+
+pub(crate) async fn find_terminal(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+) -> Result<Arc<TerminalSession>, String> {
+    sessions
+        .lock()
+        .await
+        .get(&terminal_key(workspace_id, terminal_id))
+        .cloned()
+        .ok_or_else(|| "Terminal session not found".to_string())
+}
+
+/// Kills and forgets a terminal. A terminal whose shell already exited is
+/// gone from `sessions`, so closing an unknown id succeeds too.
+pub(crate) async fn close_terminal(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+) -> Result<(), String> {
+    let session = sessions
+        .lock()
+        .await
+        .remove(&terminal_key(workspace_id, terminal_id));
+    if let Some(session) = session {
+        session.kill().await;
+    }
+    Ok(())
+}
+
+/// Open terminals of a workspace, sorted by id.
+pub(crate) async fn list_terminals(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+) -> Vec<TerminalListEntry> {
+    let prefix = terminal_key(workspace_id, "");
+    let mut entries: Vec<TerminalListEntry> = sessions
+        .lock()
+        .await
+        .iter()
+        .filter(|(key, _)| key.starts_with(&prefix))
+        .map(|(_, session)| session.list_entry())
+        .collect();
+    entries.sort_by(|a, b| a.terminal_id.cmp(&b.terminal_id));
+    entries
+}
+
+pub(crate) async fn terminal_scrollback(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+) -> Result<TerminalScrollback, String> {
+    let session = find_terminal(sessions, workspace_id, terminal_id).await?;
+    let snapshot = session
+        .scrollback
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .snapshot();
+    Ok(snapshot)
+}
+
+pub(crate) async fn terminal_snapshot(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+) -> Result<TerminalSnapshot, String> {
+    let session = find_terminal(sessions, workspace_id, terminal_id).await?;
+    let (cols, rows) = *session.size.lock().unwrap_or_else(|err| err.into_inner());
+    let scrollback = session
+        .scrollback
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .snapshot();
+    Ok(TerminalSnapshot {
+        data: scrollback.data,
+        truncated: scrollback.truncated,
+        cols,
+        rows,
+    })
+}
+
+/// Searches the terminal's scrollback; see `terminal_search`.
+pub(crate) async fn search_terminal(
+    sessions: &TerminalSessions,
+    workspace_id: &str,
+    terminal_id: &str,
+    query: &str,
+    options: &TerminalSearchOptions,
+) -> Result<TerminalSearchResult, String> {
+    let session = find_terminal(sessions, workspace_id, terminal_id).await?;
+    let data = session
+        .scrollback
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .data
+        .clone();
+    search_scrollback(&data, query, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        close_terminal, open_terminal, scrollback_cap, Scrollback, ScrollbackBudget,
+        TerminalLaunch, TerminalSessions, Utf8Chunker, SCROLLBACK_BYTES,
+    };
+    use crate::backend::events::{
+        AppServerEvent, BackendError, EventSink, QuickCommandOutput, TerminalExited, TerminalOutput,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        exited: Arc<Mutex<Vec<TerminalExited>>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exited(&self, event: TerminalExited) {
+            self.exited.lock().unwrap().push(event);
+        }
+        fn emit_quick_command_output(&self, _event: QuickCommandOutput) {}
+        fn emit_backend_error(&self, _event: BackendError) {}
+    }
+
+    #[test]
+    fn chunker_holds_split_characters_and_drops_invalid_bytes() {
+        let mut chunker = Utf8Chunker::default();
+        let bytes = "a€b".as_bytes();
+        assert_eq!(chunker.push(&bytes[..2]), "a");
+        assert_eq!(chunker.push(&bytes[2..3]), "");
+        assert_eq!(chunker.push(&bytes[3..]), "€b");
+
+        assert_eq!(chunker.push(b"x\xffy"), "xy");
+        assert_eq!(chunker.push(b"\xe2\x82"), "");
+        assert_eq!(chunker.push(b"\xffz"), "z");
+    }
+
+    #[test]
+    fn scrollback_keeps_the_tail_on_a_char_boundary() {
+        let mut scrollback = Scrollback::new(5, Arc::new(ScrollbackBudget::new(1024)));
+        scrollback.push("abc");
+        assert!(!scrollback.snapshot().truncated);
+        scrollback.push("dé€");
+        let snapshot = scrollback.snapshot();
+        assert!(snapshot.truncated);
+        assert_eq!(snapshot.data, "é€");
+    }
+
+    #[test]
+    fn scrollbacks_over_the_shared_budget_shrink_to_their_share() {
+        let budget = Arc::new(ScrollbackBudget::new(12));
+        let mut first = Scrollback::new(10, Arc::clone(&budget));
+        let mut second = Scrollback::new(10, Arc::clone(&budget));
+        first.push(&"a".repeat(25));
+        assert_eq!(first.snapshot().data, "a".repeat(10));
+        second.push("0123456789");
+        // 20 bytes held against 12: the writer keeps its share of 6.
+        assert_eq!(second.snapshot().data, "456789");
+        assert!(second.snapshot().truncated);
+        drop(first);
+        second.push("ab");
+        assert_eq!(second.snapshot().data, "456789ab");
+        assert_eq!(budget.used.load(std::sync::atomic::Ordering::Relaxed), 8);
+
+        assert_eq!(scrollback_cap(None), SCROLLBACK_BYTES);
+        assert_eq!(scrollback_cap(Some(1)), 4 * 1024);
+        assert_eq!(scrollback_cap(Some(usize::MAX)), 4 * 1024 * 1024);
+    }
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn a_shell_that_exits_is_dropped_and_reported() {
+        let sessions = TerminalSessions::default();
+        let sink = RecordingSink::default();
+        let cwd = tempfile::tempdir().expect("tempdir");
+        let launch = TerminalLaunch {
+            shell_override: Some("/bin/sh"),
+            initial_command: Some("exit 3".to_string()),
+            ..TerminalLaunch::default()
+        };
+        open_terminal(
+            &sessions,
+            sink.clone(),
+            "ws".to_string(),
+            "t1".to_string(),
+            cwd.path(),
+            launch,
+            80,
+            24,
+        )
+        .await
+        .expect("open terminal");
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while sink.exited.lock().unwrap().is_empty() {
+            assert!(tokio::time::Instant::now() < deadline, "no exit reported");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let exited = sink.exited.lock().unwrap().clone();
+        assert_eq!(exited.len(), 1);
+        assert_eq!(exited[0].terminal_id, "t1");
+        assert_eq!(exited[0].exit_code, Some(3));
+        assert!(sessions.lock().await.is_empty());
+        close_terminal(&sessions, "ws", "t1")
+            .await
+            .expect("closing an exited terminal succeeds");
+    }
+}
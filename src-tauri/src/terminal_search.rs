@@ -0,0 +1,279 @@
+//! Search over a terminal's scrollback. Matching runs on the text with ANSI
+//! escape sequences and other control characters removed, but every offset
+//! in the result points into the scrollback as `terminal_scrollback` returns
+//! it, so the client can highlight matches in what it rendered.
+
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_MAX_MATCHES: usize = 100;
+const MAX_MATCHES_LIMIT: usize = 1000;
+const DEFAULT_CONTEXT_LINES: usize = 2;
+const CONTEXT_LINES_LIMIT: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalSearchOptions {
+    #[serde(default)]
+    pub(crate) case_sensitive: bool,
+    /// Treat the query as a regular expression instead of literal text.
+    #[serde(default)]
+    pub(crate) regex: bool,
+    /// Lines before and after each match to return, at most 10. Defaults to 2.
+    #[serde(default)]
+    pub(crate) context_lines: Option<usize>,
+    /// Defaults to 100, at most 1000.
+    #[serde(default)]
+    pub(crate) max_matches: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalContextLine {
+    /// Zero-based line number in the scrollback.
+    pub(crate) line: usize,
+    /// The line without escape sequences.
+    pub(crate) text: String,
+    /// Byte offset of the start of the line in the scrollback.
+    pub(crate) offset: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalSearchMatch {
+    /// Byte range of the match in the scrollback. Escape sequences inside the
+    /// match are part of the range.
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) line: usize,
+    /// Character column of the match in `text` of its line.
+    pub(crate) column: usize,
+    /// The matched line with the lines around it, in order.
+    pub(crate) context: Vec<TerminalContextLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TerminalSearchResult {
+    pub(crate) matches: Vec<TerminalSearchMatch>,
+    /// More matches exist past `maxMatches`.
+    pub(crate) truncated: bool,
+    /// Length of the searched scrollback. Offsets are stale once the
+    /// scrollback has grown past this.
+    pub(crate) scrollback_bytes: usize,
+}
+
+/// Length of the escape sequence at `start` (an ESC byte). Sequences end on
+/// ASCII bytes, so the returned offset is always a character boundary.
+fn escape_len(bytes: &[u8], start: usize) -> usize {
+    let Some(&kind) = bytes.get(start + 1) else {
+        return 1;
+    };
+    let mut end = start + 2;
+    match kind {
+        // CSI: parameter and intermediate bytes, then a final byte.
+        b'[' => {
+            while end < bytes.len() && (0x20..=0x3f).contains(&bytes[end]) {
+                end += 1;
+            }
+            if end < bytes.len() && (0x40..=0x7e).contains(&bytes[end]) {
+                end += 1;
+            }
+        }
+        // OSC, DCS, SOS, PM and APC run to BEL or ESC \.
+        b']' | b'P' | b'X' | b'^' | b'_' => loop {
+            match bytes.get(end) {
+                None => break,
+                Some(0x07) => {
+                    end += 1;
+                    break;
+                }
+                Some(0x1b) if bytes.get(end + 1) == Some(&b'\\') => {
+                    end += 2;
+                    break;
+                }
+                Some(_) => end += 1,
+            }
+        },
+        // Intermediate bytes such as `ESC ( B`, then a final byte.
+        0x20..=0x2f => {
+            end = start + 1;
+            while end < bytes.len() && (0x20..=0x2f).contains(&bytes[end]) {
+                end += 1;
+            }
+            if end < bytes.len() && (0x30..=0x7e).contains(&bytes[end]) {
+                end += 1;
+            }
+        }
+        0x30..=0x7e => {}
+        _ => end = start + 1,
+    }
+    end - start
+}
+
+/// `data` without escape sequences or control characters other than `\n`
+/// and `\t`, and the offset in `data` of every byte of the result.
+fn strip_ansi(data: &str) -> (String, Vec<usize>) {
+    let bytes = data.as_bytes();
+    let mut plain = String::with_capacity(data.len());
+    let mut offsets = Vec::with_capacity(data.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == 0x1b {
+            index += escape_len(bytes, index);
+            continue;
+        }
+        let Some(ch) = data[index..].chars().next() else {
+            break;
+        };
+        let len = ch.len_utf8();
+        if !ch.is_control() || ch == '\n' || ch == '\t' {
+            plain.push(ch);
+            offsets.extend(index..index + len);
+        }
+        index += len;
+    }
+    (plain, offsets)
+}
+
+pub(crate) fn search_scrollback(
+    data: &str,
+    query: &str,
+    options: &TerminalSearchOptions,
+) -> Result<TerminalSearchResult, String> {
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|err| format!("Invalid regex: {err}"))?;
+    let max_matches = options
+        .max_matches
+        .unwrap_or(DEFAULT_MAX_MATCHES)
+        .clamp(1, MAX_MATCHES_LIMIT);
+    let context_lines = options
+        .context_lines
+        .unwrap_or(DEFAULT_CONTEXT_LINES)
+        .min(CONTEXT_LINES_LIMIT);
+
+    let (plain, offsets) = strip_ansi(data);
+    let mut line_starts = vec![0];
+    line_starts.extend(plain.match_indices('\n').map(|(index, _)| index + 1));
+    let context_line = |line: usize| {
+        let start = line_starts[line];
+        let end = line_starts
+            .get(line + 1)
+            .map(|next| next - 1)
+            .unwrap_or(plain.len());
+        // Right after the original newline, so the line's leading escape
+        // sequences are included.
+        let offset = if start == 0 {
+            0
+        } else {
+            offsets[start - 1] + 1
+        };
+        TerminalContextLine {
+            line,
+            text: plain[start..end].to_string(),
+            offset,
+        }
+    };
+
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    for found in matcher.find_iter(&plain) {
+        if found.is_empty() {
+            continue;
+        }
+        if matches.len() == max_matches {
+            truncated = true;
+            break;
+        }
+        let line = line_starts.partition_point(|start| *start <= found.start()) - 1;
+        let first = line.saturating_sub(context_lines);
+        let last = (line + context_lines).min(line_starts.len() - 1);
+        matches.push(TerminalSearchMatch {
+            start: offsets[found.start()],
+            end: offsets[found.end() - 1] + 1,
+            line,
+            column: plain[line_starts[line]..found.start()].chars().count(),
+            context: (first..=last).map(context_line).collect(),
+        });
+    }
+    Ok(TerminalSearchResult {
+        matches,
+        truncated,
+        scrollback_bytes: data.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search_scrollback, TerminalSearchOptions};
+
+    #[test]
+    fn matches_text_between_escape_sequences_at_original_offsets() {
+        let data = "$ cargo test\r\n\x1b[32mok\x1b[0m: 3 passed\r\n\x1b]0;title\x07\x1b[1;31mERR\x1b[0mOR: boom\r\n";
+        let result =
+            search_scrollback(data, "error", &TerminalSearchOptions::default()).expect("search");
+        assert_eq!(result.matches.len(), 1);
+        let found = &result.matches[0];
+        assert_eq!(&data[found.start..found.end], "ERR\x1b[0mOR");
+        assert_eq!((found.line, found.column), (2, 0));
+        let texts: Vec<&str> = found
+            .context
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect();
+        assert_eq!(
+            texts,
+            vec!["$ cargo test", "ok: 3 passed", "ERROR: boom", ""]
+        );
+        assert_eq!(
+            &data[found.context[1].offset..],
+            "\x1b[32mok\x1b[0m: 3 passed\r\n\x1b]0;title\x07\x1b[1;31mERR\x1b[0mOR: boom\r\n"
+        );
+        assert_eq!(result.scrollback_bytes, data.len());
+
+        let sensitive = TerminalSearchOptions {
+            case_sensitive: true,
+            ..TerminalSearchOptions::default()
+        };
+        assert!(search_scrollback(data, "error", &sensitive)
+            .expect("search")
+            .matches
+            .is_empty());
+    }
+
+    #[test]
+    fn caps_matches_and_rejects_bad_regexes() {
+        let data = "é1 é22 é333\n".repeat(5);
+        let options = TerminalSearchOptions {
+            regex: true,
+            max_matches: Some(4),
+            context_lines: Some(0),
+            ..TerminalSearchOptions::default()
+        };
+        let result = search_scrollback(&data, r"\d+", &options).expect("search");
+        assert_eq!(result.matches.len(), 4);
+        assert!(result.truncated);
+        assert_eq!(result.matches[1].column, 4);
+        assert_eq!(result.matches[3].line, 1);
+        assert_eq!(result.matches[3].context.len(), 1);
+
+        let literal =
+            search_scrollback(&data, "é1 é2", &TerminalSearchOptions::default()).expect("literal");
+        assert_eq!(literal.matches.len(), 5);
+        assert!(!literal.truncated);
+
+        let err = search_scrollback(&data, "(", &options).expect_err("invalid");
+        assert!(err.starts_with("Invalid regex: "));
+        assert!(search_scrollback(&data, "(", &TerminalSearchOptions::default()).is_ok());
+    }
+}
@@ -0,0 +1,441 @@
+use std::path::{Path, PathBuf};
+
+use portable_pty::CommandBuilder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShellFamily {
+    Bash,
+    Zsh,
+    Fish,
+    Nushell,
+    /// `sh`, `dash` and other shells that only promise the POSIX flags.
+    Posix,
+    PowerShell,
+    Cmd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ShellSpec {
+    pub(crate) program: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) family: ShellFamily,
+}
+
+/// Environment lookups are injected so resolution can be tested for either
+/// platform on any host.
+pub(crate) struct ShellEnv<'a> {
+    pub(crate) windows: bool,
+    pub(crate) var: &'a dyn Fn(&str) -> Option<String>,
+    pub(crate) exists: &'a dyn Fn(&Path) -> bool,
+}
+
+impl ShellEnv<'_> {
+    fn get(&self, key: &str) -> Option<String> {
+        (self.var)(key)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Lowercased executable name without directory or `.exe`.
+fn program_stem(program: &str) -> String {
+    let name = program
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(program)
+        .to_ascii_lowercase();
+    name.strip_suffix(".exe").unwrap_or(&name).to_string()
+}
+
+fn shell_family(program: &str) -> ShellFamily {
+    match program_stem(program).as_str() {
+        "bash" => ShellFamily::Bash,
+        "zsh" => ShellFamily::Zsh,
+        "fish" => ShellFamily::Fish,
+        "nu" => ShellFamily::Nushell,
+        "pwsh" | "powershell" => ShellFamily::PowerShell,
+        "cmd" => ShellFamily::Cmd,
+        _ => ShellFamily::Posix,
+    }
+}
+
+/// Startup flags for `family`. A login shell also reads the profile files
+/// (`.zprofile`, `.bash_profile`, fish's and nushell's login config), which is
+/// where many users set `PATH`. Plain `sh` is not guaranteed to accept `-l`.
+/// Only pwsh on Unix has a login mode, and `-Login` must come first.
+fn shell_args(family: ShellFamily, program: &str, login_shell: bool, windows: bool) -> Vec<String> {
+    let args: &[&str] = match family {
+        ShellFamily::Bash | ShellFamily::Zsh | ShellFamily::Fish | ShellFamily::Nushell => {
+            if login_shell {
+                &["-l", "-i"]
+            } else {
+                &["-i"]
+            }
+        }
+        ShellFamily::Posix => &["-i"],
+        ShellFamily::PowerShell => {
+            if login_shell && program_stem(program) == "pwsh" && !windows {
+                &["-Login", "-NoLogo"]
+            } else {
+                &["-NoLogo"]
+            }
+        }
+        ShellFamily::Cmd => &[],
+    };
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// Users often paste paths like `"C:\Program Files\PowerShell\7\pwsh.exe"`
+/// with the quotes; the PTY spawns the program directly, so drop them.
+fn unquote(value: &str) -> &str {
+    let trimmed = value.trim();
+    trimmed
+        .strip_prefix('"')
+        .and_then(|inner| inner.strip_suffix('"'))
+        .unwrap_or(trimmed)
+}
+
+fn find_on_path(env: &ShellEnv<'_>, program: &str) -> Option<String> {
+    let separator = if env.windows { ';' } else { ':' };
+    env.get("PATH")?
+        .split(separator)
+        .map(unquote)
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| PathBuf::from(dir).join(program))
+        .find(|candidate| (env.exists)(candidate))
+        .map(|candidate| candidate.to_string_lossy().into_owned())
+}
+
+fn windows_default_shell(env: &ShellEnv<'_>) -> String {
+    if let Some(pwsh) = find_on_path(env, "pwsh.exe") {
+        return pwsh;
+    }
+    if let Some(root) = env.get("SystemRoot") {
+        let powershell = PathBuf::from(root)
+            .join("System32")
+            .join("WindowsPowerShell")
+            .join("v1.0")
+            .join("powershell.exe");
+        if (env.exists)(&powershell) {
+            return powershell.to_string_lossy().into_owned();
+        }
+    }
+    env.get("COMSPEC")
+        .map(|value| unquote(&value).to_string())
+        .unwrap_or_else(|| "cmd.exe".to_string())
+}
+
+/// Picks the terminal shell: the `terminalShell` override, then `$SHELL` on
+/// Unix or PowerShell/`COMSPEC` on Windows. `$SHELL` is ignored on Windows
+/// because MSYS sets it to paths like `/usr/bin/bash` that cannot be spawned.
+pub(crate) fn resolve_shell(
+    override_shell: Option<&str>,
+    login_shell: bool,
+    env: &ShellEnv<'_>,
+) -> ShellSpec {
+    let program = override_shell
+        .map(unquote)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| {
+            if env.windows {
+                windows_default_shell(env)
+            } else {
+                env.get("SHELL").unwrap_or_else(|| "/bin/zsh".to_string())
+            }
+        });
+    let family = shell_family(&program);
+    let args = shell_args(family, &program, login_shell, env.windows);
+    ShellSpec {
+        program,
+        args,
+        family,
+    }
+}
+
+fn resolve_locale(env: &ShellEnv<'_>) -> String {
+    let candidate = env
+        .get("LC_ALL")
+        .or_else(|| env.get("LANG"))
+        .unwrap_or_else(|| "en_US.UTF-8".to_string());
+    let lower = candidate.to_lowercase();
+    if lower.contains("utf-8") || lower.contains("utf8") {
+        return candidate;
+    }
+    "en_US.UTF-8".to_string()
+}
+
+/// Extra environment for the shell. POSIX locale variables mean nothing to
+/// cmd or PowerShell, so Windows only gets `TERM`.
+pub(crate) fn shell_env_vars(env: &ShellEnv<'_>) -> Vec<(String, String)> {
+    let mut vars = vec![("TERM".to_string(), "xterm-256color".to_string())];
+    if !env.windows {
+        let locale = resolve_locale(env);
+        for key in ["LANG", "LC_ALL", "LC_CTYPE"] {
+            vars.push((key.to_string(), locale.clone()));
+        }
+    }
+    vars
+}
+
+/// Canonicalized Windows paths carry a `\\?\` prefix that cmd.exe rejects as a
+/// working directory; `\\?\UNC\server\share` becomes `\\server\share`.
+pub(crate) fn terminal_cwd(path: &Path, windows: bool) -> PathBuf {
+    if !windows {
+        return path.to_path_buf();
+    }
+    let raw = path.to_string_lossy();
+    if let Some(rest) = raw.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{rest}"));
+    }
+    if let Some(rest) = raw.strip_prefix(r"\\?\") {
+        return PathBuf::from(rest);
+    }
+    path.to_path_buf()
+}
+
+fn host_var(key: &str) -> Option<String> {
+    std::env::var(key).ok()
+}
+
+fn host_exists(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Builds the PTY command for an interactive terminal in `cwd`.
+pub(crate) fn build_terminal_command(
+    override_shell: Option<&str>,
+    login_shell: bool,
+    cwd: &Path,
+) -> CommandBuilder {
+    let env = ShellEnv {
+        windows: cfg!(windows),
+        var: &host_var,
+        exists: &host_exists,
+    };
+    let spec = resolve_shell(override_shell, login_shell, &env);
+    let mut cmd = CommandBuilder::new(&spec.program);
+    cmd.args(&spec.args);
+    cmd.cwd(terminal_cwd(cwd, env.windows));
+    for (key, value) in shell_env_vars(&env) {
+        cmd.env(key, value);
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_shell, shell_env_vars, terminal_cwd, ShellEnv, ShellFamily};
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    fn with_env<T>(
+        windows: bool,
+        vars: &[(&str, &str)],
+        files: &[&str],
+        run: impl FnOnce(&ShellEnv<'_>) -> T,
+    ) -> T {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        let var = |key: &str| vars.get(key).cloned();
+        let exists = |path: &Path| files.iter().any(|file| file == path);
+        run(&ShellEnv {
+            windows,
+            var: &var,
+            exists: &exists,
+        })
+    }
+
+    #[test]
+    fn unix_uses_shell_env_with_interactive_flag() {
+        let spec = with_env(false, &[("SHELL", "/bin/bash")], &[], |env| {
+            resolve_shell(None, false, env)
+        });
+        assert_eq!(spec.program, "/bin/bash");
+        assert_eq!(spec.args, vec!["-i".to_string()]);
+
+        let fallback = with_env(false, &[], &[], |env| resolve_shell(None, false, env));
+        assert_eq!(fallback.program, "/bin/zsh");
+    }
+
+    #[test]
+    fn login_flags_follow_the_shell_family() {
+        let args = |shell: &str, login: bool| {
+            with_env(false, &[], &[], |env| {
+                resolve_shell(Some(shell), login, env)
+            })
+            .args
+        };
+        assert_eq!(args("/bin/zsh", true), vec!["-l", "-i"]);
+        assert_eq!(args("/usr/local/bin/fish", true), vec!["-l", "-i"]);
+        assert_eq!(args("/opt/homebrew/bin/nu", false), vec!["-i"]);
+        assert_eq!(args("/bin/sh", true), vec!["-i"]);
+        assert_eq!(args("/usr/local/bin/pwsh", true), vec!["-Login", "-NoLogo"]);
+
+        let windows = with_env(true, &[], &[], |env| {
+            resolve_shell(Some("pwsh.exe"), true, env)
+        });
+        assert_eq!(windows.args, vec!["-NoLogo"]);
+        let fish = with_env(false, &[], &[], |env| {
+            resolve_shell(Some("fish"), false, env)
+        });
+        assert_eq!(fish.family, ShellFamily::Fish);
+    }
+
+    #[test]
+    fn windows_prefers_pwsh_then_windows_powershell_then_comspec() {
+        let pwsh = PathBuf::from(r"C:\Program Files\PowerShell\7")
+            .join("pwsh.exe")
+            .to_string_lossy()
+            .into_owned();
+        let spec = with_env(
+            true,
+            &[
+                ("PATH", r"C:\Windows;C:\Program Files\PowerShell\7"),
+                ("SHELL", "/usr/bin/bash"),
+            ],
+            &[pwsh.as_str()],
+            |env| resolve_shell(None, false, env),
+        );
+        assert_eq!(spec.program, pwsh);
+        assert_eq!(spec.family, ShellFamily::PowerShell);
+        assert_eq!(spec.args, vec!["-NoLogo".to_string()]);
+
+        let legacy = PathBuf::from(r"C:\Windows")
+            .join("System32")
+            .join("WindowsPowerShell")
+            .join("v1.0")
+            .join("powershell.exe")
+            .to_string_lossy()
+            .into_owned();
+        let spec = with_env(
+            true,
+            &[("SystemRoot", r"C:\Windows")],
+            &[legacy.as_str()],
+            |env| resolve_shell(None, false, env),
+        );
+        assert_eq!(spec.program, legacy);
+
+        let spec = with_env(
+            true,
+            &[("COMSPEC", r"C:\Windows\system32\cmd.exe")],
+            &[],
+            |env| resolve_shell(None, false, env),
+        );
+        assert_eq!(spec.program, r"C:\Windows\system32\cmd.exe");
+        assert_eq!(spec.family, ShellFamily::Cmd);
+        assert!(spec.args.is_empty());
+
+        let spec = with_env(true, &[], &[], |env| resolve_shell(None, false, env));
+        assert_eq!(spec.program, "cmd.exe");
+    }
+
+    #[test]
+    fn override_wins_and_is_unquoted() {
+        let spec = with_env(true, &[("COMSPEC", "cmd.exe")], &[], |env| {
+            resolve_shell(Some(r#" "C:\Program Files\Git\bin\bash.exe" "#), false, env)
+        });
+        assert_eq!(spec.program, r"C:\Program Files\Git\bin\bash.exe");
+        assert_eq!(spec.family, ShellFamily::Bash);
+
+        let spec = with_env(false, &[("SHELL", "/bin/zsh")], &[], |env| {
+            resolve_shell(Some("  "), false, env)
+        });
+        assert_eq!(spec.program, "/bin/zsh");
+    }
+
+    #[test]
+    fn windows_skips_posix_locale_vars() {
+        let unix = with_env(false, &[("LANG", "C")], &[], shell_env_vars);
+        assert!(unix.contains(&("LANG".to_string(), "en_US.UTF-8".to_string())));
+        let windows = with_env(true, &[("LANG", "C")], &[], shell_env_vars);
+        assert_eq!(
+            windows,
+            vec![("TERM".to_string(), "xterm-256color".to_string())]
+        );
+    }
+
+    #[test]
+    fn windows_cwd_drops_verbatim_prefix() {
+        assert_eq!(
+            terminal_cwd(Path::new(r"\\?\C:\Users\me\My Project"), true),
+            PathBuf::from(r"C:\Users\me\My Project")
+        );
+        assert_eq!(
+            terminal_cwd(Path::new(r"\\?\UNC\server\share\repo"), true),
+            PathBuf::from(r"\\server\share\repo")
+        );
+        assert_eq!(
+            terminal_cwd(Path::new("/home/me/My Project"), false),
+            PathBuf::from("/home/me/My Project")
+        );
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn pty_runs_shell_in_cwd_with_spaces() {
+        use portable_pty::{native_pty_system, PtySize};
+        use std::io::{Read, Write};
+        use std::sync::mpsc;
+        use std::time::{Duration, Instant};
+
+        let dir = tempfile::Builder::new()
+            .prefix("codex monitor pty")
+            .tempdir()
+            .expect("tempdir");
+        let (shell, script) = if cfg!(windows) {
+            ("cmd.exe", "echo pty^ok\r\nexit\r\n")
+        } else {
+            ("/bin/sh", "echo pty''ok\nexit\n")
+        };
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: 24,
+                cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .expect("openpty");
+        let mut child = pair
+            .slave
+            .spawn_command(super::build_terminal_command(
+                Some(shell),
+                false,
+                dir.path(),
+            ))
+            .expect("spawn shell");
+        drop(pair.slave);
+        let mut reader = pair.master.try_clone_reader().expect("reader");
+        let mut writer = pair.master.take_writer().expect("writer");
+        writer.write_all(script.as_bytes()).expect("write");
+        writer.flush().expect("flush");
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 1024];
+            while let Ok(count) = reader.read(&mut buffer) {
+                if count == 0 || tx.send(buffer[..count].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut output = String::new();
+        // The escape keeps the echoed command line itself from matching.
+        while !output.contains("ptyok") {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(chunk) => output.push_str(&String::from_utf8_lossy(&chunk)),
+                Err(_) => break,
+            }
+        }
+        let _ = child.kill();
+        assert!(
+            output.contains("ptyok"),
+            "unexpected pty output: {output:?}"
+        );
+    }
+}
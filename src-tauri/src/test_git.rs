@@ -0,0 +1,46 @@
+//! Git fixtures for unit tests that drive the git CLI against a temporary
+//! repository.
+
+use std::path::Path;
+use std::process::Command;
+
+use tempfile::{tempdir, TempDir};
+
+/// Runs git in `root` with a fixed identity and returns its stdout. Panics
+/// when git fails.
+pub(crate) fn git(root: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .env("GIT_AUTHOR_NAME", "t")
+        .env("GIT_AUTHOR_EMAIL", "t@example.com")
+        .env("GIT_COMMITTER_NAME", "t")
+        .env("GIT_COMMITTER_EMAIL", "t@example.com")
+        .output()
+        .expect("git runs");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// `git init` with an identity for commits made by the code under test, and
+/// without line-ending conversion.
+pub(crate) fn init_repo(root: &Path) {
+    git(root, &["init", "-q"]);
+    git(root, &["config", "user.name", "t"]);
+    git(root, &["config", "user.email", "t@example.com"]);
+    git(root, &["config", "core.autocrlf", "false"]);
+}
+
+/// A new repository whose one commit, `init`, adds `path` with `content`.
+pub(crate) fn repo_with(path: &str, content: &str) -> TempDir {
+    let dir = tempdir().expect("tempdir");
+    init_repo(dir.path());
+    std::fs::write(dir.path().join(path), content).unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "init"]);
+    dir
+}
@@ -0,0 +1,569 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::thread_archive::{pin_key, thread_last_activity_ms};
+
+/// Viewer used when a client does not identify itself, and by the desktop app.
+pub(crate) const DEFAULT_VIEWER: &str = "default";
+
+/// App-server notifications that count as new activity on a thread. Streaming
+/// deltas are left out; the `item/completed` that follows them is enough.
+const ACTIVITY_METHODS: &[&str] = &["turn/started", "turn/completed", "item/completed", "error"];
+
+/// How long the index keeps activity and reviews.
+const ACTIVITY_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+pub(crate) const DEFAULT_REVIEW_LIMIT: usize = 50;
+
+/// Last activity per `workspace:thread` seen on app-server notifications since
+/// start. `thread/list` timestamps cover anything older.
+fn activity_index() -> &'static Mutex<HashMap<String, i64>> {
+    static INDEX: OnceLock<Mutex<HashMap<String, i64>>> = OnceLock::new();
+    INDEX.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Reviews started through `start_review` since start, oldest first.
+fn review_index() -> &'static Mutex<Vec<ReviewRecord>> {
+    static REVIEWS: OnceLock<Mutex<Vec<ReviewRecord>>> = OnceLock::new();
+    REVIEWS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Last turn of each `workspace:thread` interrupted for running past its
+/// `maxTurnDurationMinutes` budget.
+fn auto_interrupt_index() -> &'static Mutex<HashMap<String, AutoInterrupt>> {
+    static INTERRUPTS: OnceLock<Mutex<HashMap<String, AutoInterrupt>>> = OnceLock::new();
+    INTERRUPTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Records activity for the thread a notification belongs to.
+pub(crate) fn record_notification(workspace_id: &str, thread_id: &str, method: &str) {
+    if ACTIVITY_METHODS.contains(&method) {
+        record_activity(workspace_id, thread_id, now_millis());
+    }
+}
+
+pub(crate) fn record_activity(workspace_id: &str, thread_id: &str, at_ms: i64) {
+    let mut index = activity_index()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let entry = index
+        .entry(pin_key(workspace_id, thread_id))
+        .or_insert(at_ms);
+    *entry = (*entry).max(at_ms);
+    let cutoff = now_millis() - ACTIVITY_RETENTION_MS;
+    index.retain(|_, last| *last >= cutoff);
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AutoInterrupt {
+    pub(crate) turn_id: String,
+    pub(crate) at: i64,
+    pub(crate) reason: String,
+}
+
+/// Records a turn interrupted by its budget; it counts as activity too.
+pub(crate) fn record_auto_interrupt(
+    workspace_id: &str,
+    thread_id: &str,
+    turn_id: &str,
+    reason: &str,
+) {
+    let at = now_millis();
+    record_activity(workspace_id, thread_id, at);
+    let mut interrupts = auto_interrupt_index()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    interrupts.retain(|_, interrupt| interrupt.at >= at - ACTIVITY_RETENTION_MS);
+    interrupts.insert(
+        pin_key(workspace_id, thread_id),
+        AutoInterrupt {
+            turn_id: turn_id.to_string(),
+            at,
+            reason: reason.to_string(),
+        },
+    );
+}
+
+fn last_auto_interrupt(workspace_id: &str, thread_id: &str) -> Option<AutoInterrupt> {
+    auto_interrupt_index()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(&pin_key(workspace_id, thread_id))
+        .cloned()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ReviewOutcome {
+    Running,
+    Completed,
+    Error,
+    Interrupted,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewRecord {
+    pub(crate) workspace_id: String,
+    /// Where the review runs: the detached review thread, or the thread it was
+    /// started from.
+    pub(crate) thread_id: String,
+    pub(crate) source_thread_id: String,
+    pub(crate) turn_id: Option<String>,
+    pub(crate) target: Value,
+    pub(crate) delivery: Option<String>,
+    pub(crate) started_at: i64,
+    pub(crate) finished_at: Option<i64>,
+    pub(crate) outcome: ReviewOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+/// Adds a review once `review/start` has answered. Error responses are not
+/// recorded.
+pub(crate) fn record_review_started(
+    workspace_id: &str,
+    thread_id: &str,
+    target: Value,
+    delivery: Option<String>,
+    response: &Value,
+) {
+    if response.get("error").is_some() {
+        return;
+    }
+    let result = response.get("result").unwrap_or(response);
+    let review_thread_id = result
+        .get("reviewThreadId")
+        .and_then(Value::as_str)
+        .unwrap_or(thread_id);
+    let turn_id = result
+        .pointer("/turn/id")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let now = now_millis();
+    let mut reviews = review_index().lock().unwrap_or_else(|err| err.into_inner());
+    reviews.retain(|review| review.started_at >= now - ACTIVITY_RETENTION_MS);
+    reviews.push(ReviewRecord {
+        workspace_id: workspace_id.to_string(),
+        thread_id: review_thread_id.to_string(),
+        source_thread_id: thread_id.to_string(),
+        turn_id,
+        target,
+        delivery,
+        started_at: now,
+        finished_at: None,
+        outcome: ReviewOutcome::Running,
+        error: None,
+    });
+}
+
+/// Settles a running review from the `turn/completed` or final `error` of its
+/// turn. Reviews whose turn id is unknown match on the thread.
+pub(crate) fn record_review_event(workspace_id: &str, thread_id: &str, message: &Value) {
+    let params = message.get("params").unwrap_or(&Value::Null);
+    let (turn_id, outcome, error) = match message.get("method").and_then(Value::as_str) {
+        Some("turn/completed") => {
+            let turn = params.get("turn").unwrap_or(&Value::Null);
+            let outcome = match turn.get("status").and_then(Value::as_str) {
+                Some("interrupted") => ReviewOutcome::Interrupted,
+                Some("failed") => ReviewOutcome::Error,
+                _ => ReviewOutcome::Completed,
+            };
+            let error = turn
+                .pointer("/error/message")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            (turn.get("id").and_then(Value::as_str), outcome, error)
+        }
+        Some("error") if params.get("willRetry").and_then(Value::as_bool) != Some(true) => {
+            let error = params
+                .pointer("/error/message")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            (
+                params.get("turnId").and_then(Value::as_str),
+                ReviewOutcome::Error,
+                error,
+            )
+        }
+        _ => return,
+    };
+    let mut reviews = review_index().lock().unwrap_or_else(|err| err.into_inner());
+    let Some(review) = reviews.iter_mut().rev().find(|review| {
+        review.outcome == ReviewOutcome::Running
+            && review.workspace_id == workspace_id
+            && match (review.turn_id.as_deref(), turn_id) {
+                (Some(review_turn), Some(turn)) => review_turn == turn,
+                _ => review.thread_id == thread_id,
+            }
+    }) else {
+        return;
+    };
+    review.outcome = outcome;
+    review.finished_at = Some(now_millis());
+    review.error = error;
+}
+
+/// The workspace's reviews, newest first.
+pub(crate) fn list_reviews(workspace_id: &str, limit: Option<usize>) -> Vec<ReviewRecord> {
+    let cutoff = now_millis() - ACTIVITY_RETENTION_MS;
+    review_index()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .iter()
+        .rev()
+        .filter(|review| review.workspace_id == workspace_id && review.started_at >= cutoff)
+        .take(limit.unwrap_or(DEFAULT_REVIEW_LIMIT))
+        .cloned()
+        .collect()
+}
+
+fn last_activity_ms(workspace_id: &str, thread: &Value, thread_id: &str) -> Option<i64> {
+    let indexed = activity_index()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(&pin_key(workspace_id, thread_id))
+        .copied();
+    indexed.max(thread_last_activity_ms(thread))
+}
+
+fn normalize_root_path(value: &str) -> String {
+    value.replace('\\', "/").trim_end_matches('/').to_string()
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadOverviewEntry {
+    pub(crate) thread_id: String,
+    pub(crate) preview: String,
+    pub(crate) last_activity_at: Option<i64>,
+    pub(crate) last_viewed_at: Option<i64>,
+    pub(crate) unread: bool,
+    /// Set when the thread's last budget interruption is still retained.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) auto_interrupt: Option<AutoInterrupt>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadsOverview {
+    pub(crate) threads: Vec<ThreadOverviewEntry>,
+    pub(crate) unread_count: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceViews {
+    /// Set by `mark_workspace_viewed`; covers every thread up to that time.
+    #[serde(default)]
+    viewed_all_at: Option<i64>,
+    #[serde(default)]
+    threads: HashMap<String, i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ViewsFile {
+    /// viewer -> workspace -> views
+    #[serde(default)]
+    viewers: HashMap<String, HashMap<String, WorkspaceViews>>,
+    /// `workspace:thread` keys archived through CodexMonitor.
+    #[serde(default)]
+    archived: HashSet<String>,
+}
+
+pub(crate) fn thread_views_path(settings_path: &Path) -> PathBuf {
+    settings_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("thread_views.json")
+}
+
+/// When each viewer last looked at each thread, persisted so restarts keep the
+/// unread badges as they were.
+pub(crate) struct ThreadViews {
+    path: PathBuf,
+    views: Mutex<ViewsFile>,
+}
+
+impl ThreadViews {
+    pub(crate) fn load(path: PathBuf) -> Self {
+        let views = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            views: Mutex::new(views),
+        }
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut ViewsFile)) -> Result<(), String> {
+        let data = {
+            let mut views = self.views.lock().unwrap_or_else(|err| err.into_inner());
+            apply(&mut views);
+            serde_json::to_string_pretty(&*views).map_err(|e| e.to_string())?
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&self.path, data).map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn mark_thread_viewed(
+        &self,
+        viewer: &str,
+        workspace_id: &str,
+        thread_id: &str,
+    ) -> Result<i64, String> {
+        let now = now_millis();
+        self.update(|views| {
+            views
+                .viewers
+                .entry(viewer.to_string())
+                .or_default()
+                .entry(workspace_id.to_string())
+                .or_default()
+                .threads
+                .insert(thread_id.to_string(), now);
+        })?;
+        Ok(now)
+    }
+
+    /// Marks every thread of the workspace as viewed now. Per-thread entries
+    /// are dropped since the watermark supersedes them.
+    pub(crate) fn mark_workspace_viewed(
+        &self,
+        viewer: &str,
+        workspace_id: &str,
+    ) -> Result<i64, String> {
+        let now = now_millis();
+        self.update(|views| {
+            views.viewers.entry(viewer.to_string()).or_default().insert(
+                workspace_id.to_string(),
+                WorkspaceViews {
+                    viewed_all_at: Some(now),
+                    threads: HashMap::new(),
+                },
+            );
+        })?;
+        Ok(now)
+    }
+
+    /// Archived threads never count as unread, for any viewer.
+    pub(crate) fn mark_archived(
+        &self,
+        workspace_id: &str,
+        thread_ids: &[String],
+    ) -> Result<(), String> {
+        self.update(|views| {
+            for workspace_views in views
+                .viewers
+                .values_mut()
+                .filter_map(|workspaces| workspaces.get_mut(workspace_id))
+            {
+                for thread_id in thread_ids {
+                    workspace_views.threads.remove(thread_id);
+                }
+            }
+            for thread_id in thread_ids {
+                views.archived.insert(pin_key(workspace_id, thread_id));
+            }
+        })
+    }
+
+    /// Builds the overview for the threads of `thread/list` whose cwd is the
+    /// workspace, most recently active first.
+    pub(crate) fn overview(
+        &self,
+        viewer: &str,
+        workspace_id: &str,
+        workspace_path: &str,
+        threads: &[Value],
+    ) -> ThreadsOverview {
+        let root = normalize_root_path(workspace_path);
+        let views = self.views.lock().unwrap_or_else(|err| err.into_inner());
+        let workspace_views = views
+            .viewers
+            .get(viewer)
+            .and_then(|workspaces| workspaces.get(workspace_id));
+        let mut entries: Vec<ThreadOverviewEntry> = threads
+            .iter()
+            .filter(|thread| {
+                let cwd = thread
+                    .get("cwd")
+                    .and_then(|value| value.as_str())
+                    .unwrap_or("");
+                normalize_root_path(cwd) == root
+            })
+            .filter_map(|thread| {
+                let thread_id = thread.get("id").and_then(|value| value.as_str())?;
+                let last_activity_at = last_activity_ms(workspace_id, thread, thread_id);
+                let last_viewed_at = workspace_views.and_then(|views| {
+                    views
+                        .threads
+                        .get(thread_id)
+                        .copied()
+                        .max(views.viewed_all_at)
+                });
+                let archived = views.archived.contains(&pin_key(workspace_id, thread_id));
+                let unread = !archived
+                    && match (last_activity_at, last_viewed_at) {
+                        (Some(activity), Some(viewed)) => activity > viewed,
+                        (Some(_), None) => true,
+                        (None, _) => false,
+                    };
+                Some(ThreadOverviewEntry {
+                    thread_id: thread_id.to_string(),
+                    preview: thread
+                        .get("preview")
+                        .and_then(|value| value.as_str())
+                        .unwrap_or("")
+                        .chars()
+                        .take(120)
+                        .collect(),
+                    last_activity_at,
+                    last_viewed_at,
+                    unread,
+                    auto_interrupt: last_auto_interrupt(workspace_id, thread_id),
+                })
+            })
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_activity_at));
+        let unread_count = entries.iter().filter(|entry| entry.unread).count();
+        ThreadsOverview {
+            threads: entries,
+            unread_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        list_reviews, record_activity, record_auto_interrupt, record_review_event,
+        record_review_started, ReviewOutcome, ThreadViews,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn reviews_settle_from_the_events_of_their_turn() {
+        let target = json!({ "type": "uncommittedChanges" });
+        record_review_started(
+            "ws-reviews",
+            "t-1",
+            target.clone(),
+            Some("detached".to_string()),
+            &json!({ "id": 7, "result": { "reviewThreadId": "t-review", "turn": { "id": "turn-1" } } }),
+        );
+        record_review_started(
+            "ws-reviews",
+            "t-2",
+            target.clone(),
+            None,
+            &json!({ "id": 8, "result": {} }),
+        );
+        record_review_started(
+            "ws-reviews",
+            "t-3",
+            target,
+            None,
+            &json!({ "id": 9, "error": { "message": "boom" } }),
+        );
+
+        // Another turn on the review thread leaves the review running.
+        record_review_event(
+            "ws-reviews",
+            "t-review",
+            &json!({ "method": "turn/completed", "params": { "turn": { "id": "turn-0", "status": "completed" } } }),
+        );
+        record_review_event(
+            "ws-reviews",
+            "t-2",
+            &json!({ "method": "error", "params": { "willRetry": true, "error": { "message": "retrying" } } }),
+        );
+        let reviews = list_reviews("ws-reviews", None);
+        assert_eq!(reviews.len(), 2);
+        assert!(reviews
+            .iter()
+            .all(|review| review.outcome == ReviewOutcome::Running));
+
+        record_review_event(
+            "ws-reviews",
+            "t-review",
+            &json!({ "method": "turn/completed", "params": { "turn": { "id": "turn-1", "status": "interrupted" } } }),
+        );
+        record_review_event(
+            "ws-reviews",
+            "t-2",
+            &json!({ "method": "error", "params": { "error": { "message": "stream closed" } } }),
+        );
+        let reviews = list_reviews("ws-reviews", Some(5));
+        assert_eq!(reviews[0].source_thread_id, "t-2");
+        assert_eq!(reviews[0].outcome, ReviewOutcome::Error);
+        assert_eq!(reviews[0].error.as_deref(), Some("stream closed"));
+        assert_eq!(reviews[1].thread_id, "t-review");
+        assert_eq!(reviews[1].outcome, ReviewOutcome::Interrupted);
+        assert!(reviews[1].finished_at.is_some());
+        assert_eq!(list_reviews("ws-reviews", Some(1)).len(), 1);
+    }
+
+    #[test]
+    fn overview_flags_threads_with_activity_after_the_last_view() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("thread_views.json");
+        let views = ThreadViews::load(path.clone());
+        let threads = vec![
+            json!({ "id": "t-old", "cwd": "/repo/", "preview": "old", "updatedAt": 1_000 }),
+            json!({ "id": "t-new", "cwd": "/repo", "preview": "new", "updatedAt": 2_000 }),
+            json!({ "id": "t-other", "cwd": "/elsewhere", "updatedAt": 3_000 }),
+            json!({ "id": "t-gone", "cwd": "/repo", "updatedAt": 4_000 }),
+        ];
+
+        let overview = views.overview("phone", "ws-overview", "/repo", &threads);
+        assert_eq!(overview.threads.len(), 3);
+        assert_eq!(overview.unread_count, 3);
+
+        views
+            .mark_workspace_viewed("phone", "ws-overview")
+            .expect("mark workspace");
+        views
+            .mark_archived("ws-overview", &["t-gone".to_string()])
+            .expect("archive");
+        let viewed_at = views
+            .mark_thread_viewed("phone", "ws-overview", "t-new")
+            .expect("mark thread");
+        record_activity("ws-overview", "t-old", viewed_at + 1);
+        record_activity("ws-overview", "t-gone", viewed_at + 2);
+        record_auto_interrupt("ws-overview", "t-gone", "turn-9", "Ran past its budget");
+
+        let reloaded = ThreadViews::load(path);
+        let overview = reloaded.overview("phone", "ws-overview", "/repo", &threads);
+        let unread: Vec<&str> = overview
+            .threads
+            .iter()
+            .filter(|entry| entry.unread)
+            .map(|entry| entry.thread_id.as_str())
+            .collect();
+        assert_eq!(unread, vec!["t-old"]);
+        assert_eq!(overview.threads[0].thread_id, "t-gone");
+        assert_eq!(
+            overview.threads[0]
+                .auto_interrupt
+                .as_ref()
+                .map(|interrupt| interrupt.turn_id.as_str()),
+            Some("turn-9")
+        );
+        assert!(overview.threads[1].auto_interrupt.is_none());
+
+        let other_viewer = reloaded.overview("laptop", "ws-overview", "/repo", &threads);
+        assert_eq!(other_viewer.unread_count, 2);
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::backend::app_server::WorkspaceSession;
+
+const THREAD_LIST_PAGE_SIZE: u32 = 100;
+const MAX_THREAD_LIST_PAGES: usize = 50;
+const MS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ArchiveThreadResult {
+    #[serde(rename = "threadId")]
+    pub(crate) thread_id: String,
+    pub(crate) ok: bool,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct AutoArchiveCandidate {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    #[serde(rename = "threadId")]
+    pub(crate) thread_id: String,
+    pub(crate) preview: String,
+    #[serde(rename = "lastActivityMs")]
+    pub(crate) last_activity_ms: i64,
+    #[serde(rename = "inactiveDays")]
+    pub(crate) inactive_days: i64,
+}
+
+/// Same key format the frontend uses for its pin map.
+pub(crate) fn pin_key(workspace_id: &str, thread_id: &str) -> String {
+    format!("{workspace_id}:{thread_id}")
+}
+
+/// Mirrors `getThreadTimestamp` on the frontend: seconds or milliseconds,
+/// numeric or string, updated time preferred over created time.
+pub(crate) fn thread_last_activity_ms(thread: &Value) -> Option<i64> {
+    let raw = ["updatedAt", "updated_at", "createdAt", "created_at"]
+        .iter()
+        .find_map(|key| thread.get(*key).filter(|value| !value.is_null()))?;
+    let numeric = match raw {
+        Value::Number(number) => number.as_f64()?,
+        Value::String(text) => match text.parse::<f64>() {
+            Ok(value) => value,
+            Err(_) => chrono::DateTime::parse_from_rfc3339(text)
+                .ok()?
+                .timestamp_millis() as f64,
+        },
+        _ => return None,
+    };
+    if !numeric.is_finite() || numeric <= 0.0 {
+        return None;
+    }
+    let millis = if numeric < 1_000_000_000_000.0 {
+        numeric * 1000.0
+    } else {
+        numeric
+    };
+    Some(millis as i64)
+}
+
+/// Threads with no activity for at least `inactive_days`, skipping pinned
+/// threads and threads whose age cannot be determined.
+pub(crate) fn select_auto_archive_candidates(
+    workspace_id: &str,
+    threads: &[Value],
+    pinned: &HashSet<String>,
+    inactive_days: u32,
+    now_ms: i64,
+) -> Vec<AutoArchiveCandidate> {
+    let cutoff_ms = now_ms - i64::from(inactive_days) * MS_PER_DAY;
+    threads
+        .iter()
+        .filter_map(|thread| {
+            let thread_id = thread.get("id").and_then(|value| value.as_str())?;
+            if pinned.contains(&pin_key(workspace_id, thread_id)) {
+                return None;
+            }
+            let last_activity_ms = thread_last_activity_ms(thread)?;
+            if last_activity_ms > cutoff_ms {
+                return None;
+            }
+            Some(AutoArchiveCandidate {
+                workspace_id: workspace_id.to_string(),
+                thread_id: thread_id.to_string(),
+                preview: thread
+                    .get("preview")
+                    .and_then(|value| value.as_str())
+                    .unwrap_or("")
+                    .chars()
+                    .take(120)
+                    .collect(),
+                last_activity_ms,
+                inactive_days: (now_ms - last_activity_ms) / MS_PER_DAY,
+            })
+        })
+        .collect()
+}
+
+/// Pages through `thread/list` until the cursor runs out.
+pub(crate) async fn list_all_threads(session: &WorkspaceSession) -> Result<Vec<Value>, String> {
+    let mut threads = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_THREAD_LIST_PAGES {
+        let response = session
+            .send_request(
+                "thread/list",
+                json!({ "cursor": cursor, "limit": THREAD_LIST_PAGE_SIZE }),
+            )
+            .await?;
+        let result = response.get("result").unwrap_or(&response);
+        if let Some(data) = result.get("data").and_then(|value| value.as_array()) {
+            threads.extend(data.iter().cloned());
+        }
+        cursor = result
+            .get("nextCursor")
+            .or_else(|| result.get("next_cursor"))
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string());
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok(threads)
+}
+
+pub(crate) async fn archive_threads(
+    session: &WorkspaceSession,
+    thread_ids: &[String],
+) -> Vec<ArchiveThreadResult> {
+    let mut results = Vec::with_capacity(thread_ids.len());
+    for thread_id in thread_ids {
+        let outcome = session
+            .send_request("thread/archive", json!({ "threadId": thread_id }))
+            .await
+            .and_then(|response| match response.get("error") {
+                Some(error) => Err(error
+                    .get("message")
+                    .and_then(|value| value.as_str())
+                    .unwrap_or("archive failed")
+                    .to_string()),
+                None => Ok(()),
+            });
+        results.push(ArchiveThreadResult {
+            thread_id: thread_id.clone(),
+            ok: outcome.is_ok(),
+            error: outcome.err(),
+        });
+    }
+    results
+}
+
+pub(crate) async fn auto_archive_candidates(
+    sessions: &[(String, Arc<WorkspaceSession>)],
+    pinned: &HashSet<String>,
+    inactive_days: u32,
+) -> Vec<AutoArchiveCandidate> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let mut candidates = Vec::new();
+    for (workspace_id, session) in sessions {
+        match list_all_threads(session).await {
+            Ok(threads) => candidates.extend(select_auto_archive_candidates(
+                workspace_id,
+                &threads,
+                pinned,
+                inactive_days,
+                now_ms,
+            )),
+            Err(err) => eprintln!("Auto-archive: failed to list threads for {workspace_id}: {err}"),
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pin_key, select_auto_archive_candidates, thread_last_activity_ms, MS_PER_DAY};
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    #[test]
+    fn last_activity_accepts_seconds_millis_and_rfc3339() {
+        assert_eq!(
+            thread_last_activity_ms(&json!({ "updatedAt": 1_700_000_000 })),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(
+            thread_last_activity_ms(&json!({ "createdAt": "1700000000000" })),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(
+            thread_last_activity_ms(&json!({ "updated_at": "2023-11-14T22:13:20Z" })),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(thread_last_activity_ms(&json!({ "updatedAt": null })), None);
+    }
+
+    #[test]
+    fn candidates_skip_pinned_recent_and_undated_threads() {
+        let now_ms = 1_700_000_000_000;
+        let days_ago = |days: i64| now_ms - days * MS_PER_DAY;
+        let threads = vec![
+            json!({ "id": "old", "updatedAt": days_ago(90), "preview": "stale experiment" }),
+            json!({ "id": "pinned", "updatedAt": days_ago(90) }),
+            json!({ "id": "recent", "updatedAt": days_ago(5) }),
+            json!({ "id": "undated" }),
+        ];
+        let pinned: HashSet<String> = [pin_key("ws-1", "pinned")].into_iter().collect();
+
+        let candidates = select_auto_archive_candidates("ws-1", &threads, &pinned, 30, now_ms);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].thread_id, "old");
+        assert_eq!(candidates[0].inactive_days, 90);
+        assert_eq!(candidates[0].preview, "stale experiment");
+    }
+}
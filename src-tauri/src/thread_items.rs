@@ -0,0 +1,151 @@
+use serde_json::Value;
+
+use crate::backend::events::AppServerEvent;
+use crate::types::ThreadItem;
+
+/// App-server notifications whose `params.item` is a thread item.
+const ITEM_METHODS: [&str; 2] = ["item/started", "item/completed"];
+
+pub(crate) fn parse_thread_item(value: Value) -> ThreadItem {
+    serde_json::from_value(value.clone()).unwrap_or(ThreadItem::Unknown(value))
+}
+
+/// Runs `params.item` through the typed model so known kinds reach clients in
+/// one canonical shape (e.g. `exit_code` becomes `exitCode`). Unknown kinds and
+/// other methods are left untouched.
+pub(crate) fn normalize_app_server_message(mut message: Value) -> Value {
+    let is_item_method = message
+        .get("method")
+        .and_then(|value| value.as_str())
+        .is_some_and(|method| ITEM_METHODS.contains(&method));
+    if !is_item_method {
+        return message;
+    }
+    let Some(item) = message.pointer_mut("/params/item") else {
+        return message;
+    };
+    if let ThreadItem::Known(known) = parse_thread_item(item.clone()) {
+        if let Ok(value) = serde_json::to_value(known) {
+            *item = value;
+        }
+    }
+    message
+}
+
+pub(crate) fn normalize_app_server_event(event: AppServerEvent) -> AppServerEvent {
+    AppServerEvent {
+        workspace_id: event.workspace_id,
+        message: normalize_app_server_message(event.message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_app_server_message, parse_thread_item};
+    use crate::types::{KnownThreadItem, ThreadItem};
+    use serde_json::{json, Value};
+
+    fn completed(item: Value) -> Value {
+        json!({
+            "method": "item/completed",
+            "params": { "threadId": "thr_1", "turnId": "turn_1", "item": item }
+        })
+    }
+
+    #[test]
+    fn known_items_round_trip_unchanged() {
+        let items = [
+            json!({ "type": "agentMessage", "id": "msg_1", "text": "Done. Tests pass." }),
+            json!({
+                "type": "userMessage",
+                "id": "msg_0",
+                "content": [{ "type": "text", "text": "run the tests" }]
+            }),
+            json!({
+                "type": "commandExecution",
+                "id": "call_1",
+                "command": "cargo test",
+                "cwd": "/repo",
+                "processId": "12",
+                "status": "completed",
+                "commandActions": [{ "type": "unknown", "command": "cargo test" }],
+                "aggregatedOutput": "test result: ok\n",
+                "exitCode": 0,
+                "durationMs": 5312
+            }),
+            json!({
+                "type": "fileChange",
+                "id": "call_2",
+                "status": "completed",
+                "changes": [
+                    { "path": "src/lib.rs", "kind": { "type": "update", "move_path": null }, "diff": "@@ -1 +1 @@\n-a\n+b\n" },
+                    { "path": "README.md", "kind": "add" }
+                ]
+            }),
+            json!({
+                "type": "reasoning",
+                "id": "rs_1",
+                "summary": ["**Planning** the change"],
+                "content": []
+            }),
+        ];
+        for item in items {
+            let message = completed(item.clone());
+            assert!(matches!(
+                parse_thread_item(item.clone()),
+                ThreadItem::Known(_)
+            ));
+            assert_eq!(normalize_app_server_message(message.clone()), message);
+        }
+    }
+
+    #[test]
+    fn command_execution_exposes_exit_code_and_accepts_snake_case() {
+        let item = json!({
+            "type": "commandExecution",
+            "id": "call_1",
+            "command": ["bash", "-lc", "false"],
+            "aggregated_output": "",
+            "exit_code": 1,
+            "duration_ms": 8
+        });
+        match parse_thread_item(item.clone()) {
+            ThreadItem::Known(KnownThreadItem::CommandExecution(command)) => {
+                assert_eq!(command.exit_code, Some(1));
+                assert_eq!(command.duration_ms, Some(8));
+            }
+            other => panic!("unexpected item: {other:?}"),
+        }
+        let normalized = normalize_app_server_message(completed(item));
+        assert_eq!(normalized.pointer("/params/item/exitCode"), Some(&json!(1)));
+        assert!(normalized.pointer("/params/item/exit_code").is_none());
+    }
+
+    #[test]
+    fn unknown_kinds_and_shapes_pass_through() {
+        let items = [
+            json!({
+                "type": "mcpToolCall",
+                "id": "call_3",
+                "server": "docs",
+                "tool": "search",
+                "arguments": { "q": "serde" },
+                "status": "inProgress"
+            }),
+            json!({ "type": "webSearch", "id": "ws_1", "query": "tokio select" }),
+            // Known kind with a shape this build does not understand.
+            json!({ "type": "reasoning", "id": "rs_2", "summary": "plain string" }),
+        ];
+        for item in items {
+            assert!(matches!(
+                parse_thread_item(item.clone()),
+                ThreadItem::Unknown(_)
+            ));
+            let message = completed(item);
+            assert_eq!(normalize_app_server_message(message.clone()), message);
+        }
+
+        let delta = json!({ "method": "item/agentMessage/delta", "params": { "delta": "hi" } });
+        assert_eq!(normalize_app_server_message(delta.clone()), delta);
+    }
+}
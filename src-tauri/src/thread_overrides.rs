@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::thread_archive::pin_key;
+
+const THREAD_OVERRIDES_FILE: &str = "thread_overrides.json";
+
+/// Models from the last `model/list` per workspace.
+static MODELS: OnceLock<Mutex<HashMap<String, Vec<ListedModel>>>> = OnceLock::new();
+/// Serializes read-modify-write of the overrides file.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// The model/effort a thread was last explicitly sent with.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub(crate) struct ThreadOverrides {
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) effort: Option<String>,
+    #[serde(default, rename = "updatedAt")]
+    pub(crate) updated_at: i64,
+}
+
+pub(crate) fn thread_overrides_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(THREAD_OVERRIDES_FILE)
+}
+
+/// Overrides keyed like pins (`workspaceId:threadId`). A missing or unreadable
+/// file reads as empty: losing a remembered model must not block a send.
+fn read_store(path: &Path) -> BTreeMap<String, ThreadOverrides> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn write_store(path: &Path, store: &BTreeMap<String, ThreadOverrides>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(store).map_err(|err| err.to_string())?;
+    std::fs::write(path, data).map_err(|err| err.to_string())
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+}
+
+pub(crate) fn get_thread_overrides(
+    path: &Path,
+    workspace_id: &str,
+    thread_id: &str,
+) -> Option<ThreadOverrides> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    read_store(path).remove(&pin_key(workspace_id, thread_id))
+}
+
+/// Records the values the caller passed explicitly; `None` leaves the
+/// remembered value alone.
+pub(crate) fn remember_thread_overrides(
+    path: &Path,
+    workspace_id: &str,
+    thread_id: &str,
+    model: Option<&str>,
+    effort: Option<&str>,
+    now_ms: i64,
+) -> Result<(), String> {
+    let model = non_empty(model);
+    let effort = non_empty(effort);
+    if model.is_none() && effort.is_none() {
+        return Ok(());
+    }
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let mut store = read_store(path);
+    let entry = store.entry(pin_key(workspace_id, thread_id)).or_default();
+    if model.is_some() {
+        entry.model = model;
+    }
+    if effort.is_some() {
+        entry.effort = effort;
+    }
+    entry.updated_at = now_ms;
+    write_store(path, &store)
+}
+
+/// Returns whether anything was stored for the thread.
+pub(crate) fn clear_thread_overrides(
+    path: &Path,
+    workspace_id: &str,
+    thread_id: &str,
+) -> Result<bool, String> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let mut store = read_store(path);
+    if store.remove(&pin_key(workspace_id, thread_id)).is_none() {
+        return Ok(false);
+    }
+    write_store(path, &store)?;
+    Ok(true)
+}
+
+fn forget_model(path: &Path, workspace_id: &str, thread_id: &str) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let mut store = read_store(path);
+    let key = pin_key(workspace_id, thread_id);
+    let Some(entry) = store.get_mut(&key) else {
+        return Ok(());
+    };
+    entry.model = None;
+    if entry.effort.is_none() {
+        store.remove(&key);
+    }
+    write_store(path, &store)
+}
+
+/// One entry of a `model/list` response.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ListedModel {
+    pub(crate) id: String,
+    /// Tokens the model accepts, when the server reports it.
+    pub(crate) context_window: Option<u64>,
+    pub(crate) is_default: bool,
+}
+
+/// Models in a `model/list` response (`model`, falling back to `id`).
+pub(crate) fn models_from_response(response: &Value) -> Vec<ListedModel> {
+    let result = response.get("result").unwrap_or(response);
+    result
+        .get("data")
+        .or_else(|| result.get("models"))
+        .and_then(|value| value.as_array())
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|model| {
+                    let id = model
+                        .get("model")
+                        .or_else(|| model.get("id"))
+                        .and_then(|value| value.as_str())?;
+                    Some(ListedModel {
+                        id: id.to_string(),
+                        context_window: model
+                            .get("contextWindow")
+                            .or_else(|| model.get("context_window"))
+                            .and_then(|value| value.as_u64()),
+                        is_default: model
+                            .get("isDefault")
+                            .or_else(|| model.get("is_default"))
+                            .and_then(|value| value.as_bool())
+                            .unwrap_or(false),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Caches the models from a successful `model/list` response.
+pub(crate) fn record_model_list(workspace_id: &str, response: &Value) {
+    let models = models_from_response(response);
+    if models.is_empty() {
+        return;
+    }
+    let cache = MODELS.get_or_init(|| Mutex::new(HashMap::new()));
+    cache
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .insert(workspace_id.to_string(), models);
+}
+
+/// Cached models, fetched once when the UI hasn't listed models yet. `None`
+/// when the list is unavailable.
+async fn cached_models(session: &WorkspaceSession) -> Option<Vec<ListedModel>> {
+    let workspace_id = &session.entry.id;
+    let cache = MODELS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(models) = cache
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(workspace_id)
+    {
+        return Some(models.clone());
+    }
+    let response = session.send_request("model/list", json!({})).await.ok()?;
+    record_model_list(workspace_id, &response);
+    let models = models_from_response(&response);
+    (!models.is_empty()).then_some(models)
+}
+
+/// Cached model ids. `None` when the list is unavailable, in which case
+/// nothing is dropped.
+async fn cached_model_ids(session: &WorkspaceSession) -> Option<Vec<String>> {
+    cached_models(session)
+        .await
+        .map(|models| models.into_iter().map(|model| model.id).collect())
+}
+
+/// The model a send would use and its context window, without remembering
+/// anything: `model`, else what the thread last used, else the listed
+/// default.
+pub(crate) async fn resolve_model_window(
+    path: &Path,
+    session: &WorkspaceSession,
+    thread_id: Option<&str>,
+    model: Option<&str>,
+) -> (Option<String>, Option<u64>) {
+    let models = cached_models(session).await.unwrap_or_default();
+    let model = non_empty(model)
+        .or_else(|| {
+            thread_id
+                .and_then(|thread_id| get_thread_overrides(path, &session.entry.id, thread_id))
+                .and_then(|overrides| overrides.model)
+        })
+        .or_else(|| {
+            models
+                .iter()
+                .find(|listed| listed.is_default)
+                .map(|listed| listed.id.clone())
+        });
+    let window = model.as_deref().and_then(|model| {
+        models
+            .iter()
+            .find(|listed| listed.id == model)
+            .and_then(|listed| listed.context_window)
+    });
+    (model, window)
+}
+
+/// Resolves the model/effort for a `send_user_message`. Explicit values win
+/// and are remembered for the thread; missing ones fall back to what the
+/// thread last used, then to the app-server's defaults. A remembered model
+/// that is no longer listed is forgotten and reported as
+/// `codex/threadOverridesDropped`.
+pub(crate) async fn apply_thread_overrides<E: EventSink>(
+    path: &Path,
+    session: &WorkspaceSession,
+    event_sink: &E,
+    thread_id: &str,
+    model: Option<String>,
+    effort: Option<String>,
+) -> (Option<String>, Option<String>) {
+    let workspace_id = session.entry.id.as_str();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    if let Err(err) = remember_thread_overrides(
+        path,
+        workspace_id,
+        thread_id,
+        model.as_deref(),
+        effort.as_deref(),
+        now_ms,
+    ) {
+        eprintln!("[thread-overrides] failed to save overrides for {thread_id}: {err}");
+    }
+    let remembered = get_thread_overrides(path, workspace_id, thread_id).unwrap_or_default();
+
+    let mut model = model.filter(|value| !value.trim().is_empty());
+    if model.is_none() {
+        if let Some(remembered_model) = remembered.model {
+            let available = cached_model_ids(session).await;
+            if available.is_some_and(|ids| !ids.contains(&remembered_model)) {
+                let _ = forget_model(path, workspace_id, thread_id);
+                event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id: workspace_id.to_string(),
+                    message: json!({
+                        "method": "codex/threadOverridesDropped",
+                        "params": {
+                            "workspaceId": workspace_id,
+                            "threadId": thread_id,
+                            "model": remembered_model,
+                            "reason": "model is no longer available",
+                        },
+                    }),
+                });
+            } else {
+                model = Some(remembered_model);
+            }
+        }
+    }
+    let effort = effort
+        .filter(|value| !value.trim().is_empty())
+        .or(remembered.effort);
+    (model, effort)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clear_thread_overrides, get_thread_overrides, models_from_response,
+        remember_thread_overrides, thread_overrides_path,
+    };
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn remembers_explicit_values_per_thread() {
+        let data = tempdir().expect("tempdir");
+        let path = thread_overrides_path(data.path());
+        assert_eq!(get_thread_overrides(&path, "ws", "t1"), None);
+
+        remember_thread_overrides(&path, "ws", "t1", Some("gpt-5"), Some("high"), 1).unwrap();
+        remember_thread_overrides(&path, "ws", "t1", Some("o3"), None, 2).unwrap();
+        remember_thread_overrides(&path, "ws", "t2", None, None, 3).unwrap();
+
+        let stored = get_thread_overrides(&path, "ws", "t1").expect("stored");
+        assert_eq!(stored.model.as_deref(), Some("o3"));
+        assert_eq!(stored.effort.as_deref(), Some("high"));
+        assert_eq!(stored.updated_at, 2);
+        assert_eq!(get_thread_overrides(&path, "ws", "t2"), None);
+        assert_eq!(get_thread_overrides(&path, "other", "t1"), None);
+
+        assert!(clear_thread_overrides(&path, "ws", "t1").unwrap());
+        assert!(!clear_thread_overrides(&path, "ws", "t1").unwrap());
+        assert_eq!(get_thread_overrides(&path, "ws", "t1"), None);
+    }
+
+    #[test]
+    fn reads_model_ids_from_model_list() {
+        let response = json!({ "id": 4, "result": { "data": [
+            { "id": "gpt-5-codex", "model": "gpt-5-codex" },
+            { "id": "o3" }
+        ]}});
+        let ids: Vec<String> = models_from_response(&response)
+            .into_iter()
+            .map(|model| model.id)
+            .collect();
+        assert_eq!(ids, vec!["gpt-5-codex".to_string(), "o3".to_string()]);
+        assert!(models_from_response(&json!({ "error": {} })).is_empty());
+    }
+
+    #[test]
+    fn reads_context_window_and_default_from_model_list() {
+        let models = models_from_response(&json!({ "result": { "data": [
+            { "model": "gpt-5-codex", "contextWindow": 272000, "isDefault": true },
+            { "model": "o3" }
+        ]}}));
+        assert_eq!(models[0].context_window, Some(272000));
+        assert!(models[0].is_default);
+        assert_eq!(models[1].context_window, None);
+        assert!(!models[1].is_default);
+    }
+}
@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::background_prompt::run_background_prompt;
+use crate::thread_items::parse_thread_item;
+use crate::types::{KnownThreadItem, ThreadItem, ThreadSummary};
+
+/// How much of the conversation is sent to the summarizer, newest items first.
+pub(crate) const SUMMARY_TRANSCRIPT_BUDGET_CHARS: usize = 48_000;
+/// Longest excerpt kept from a single item, so one huge tool output cannot
+/// crowd out the rest of the conversation.
+const MAX_ITEM_CHARS: usize = 4_000;
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(180);
+
+pub(crate) struct Transcript {
+    pub(crate) text: String,
+    pub(crate) items_included: usize,
+    pub(crate) items_total: usize,
+}
+
+/// Rough token count used in the response; about four characters per token.
+pub(crate) fn approx_tokens(chars: usize) -> usize {
+    chars.div_ceil(4)
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_ITEM_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn render_item(item: &KnownThreadItem) -> Option<String> {
+    let line = match item {
+        KnownThreadItem::UserMessage(message) => {
+            let text = message
+                .content
+                .iter()
+                .flatten()
+                .filter_map(|part| part.get("text").and_then(|text| text.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("User: {}", excerpt(&text))
+        }
+        KnownThreadItem::AgentMessage(message) => {
+            format!("Assistant: {}", excerpt(&message.text))
+        }
+        KnownThreadItem::CommandExecution(command) => {
+            let command_text = match &command.command {
+                Some(Value::String(text)) => text.clone(),
+                Some(Value::Array(parts)) => parts
+                    .iter()
+                    .filter_map(|part| part.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                _ => return None,
+            };
+            match command.exit_code {
+                Some(code) => format!("Ran: {} (exit {code})", excerpt(&command_text)),
+                None => format!("Ran: {}", excerpt(&command_text)),
+            }
+        }
+        KnownThreadItem::FileChange(change) => {
+            let paths = change
+                .changes
+                .iter()
+                .flatten()
+                .map(|entry| entry.path.as_str())
+                .collect::<Vec<_>>();
+            if paths.is_empty() {
+                return None;
+            }
+            format!("Changed files: {}", paths.join(", "))
+        }
+        KnownThreadItem::Reasoning(_) => return None,
+    };
+    Some(line)
+}
+
+/// Renders the thread from a `thread/resume` response as plain text. Items are
+/// taken from the end until `budget` characters are used, so the most recent
+/// part of a long conversation is kept.
+pub(crate) fn build_transcript(resume_response: &Value, budget: usize) -> Transcript {
+    let thread = resume_response
+        .pointer("/result/thread")
+        .or_else(|| resume_response.get("thread"))
+        .unwrap_or(&Value::Null);
+    let lines: Vec<String> = thread
+        .get("turns")
+        .and_then(|turns| turns.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|turn| turn.get("items").and_then(|items| items.as_array()))
+        .flatten()
+        .filter_map(|item| match parse_thread_item(item.clone()) {
+            ThreadItem::Known(known) => render_item(&known),
+            ThreadItem::Unknown(_) => None,
+        })
+        .collect();
+
+    let mut used = 0;
+    let mut kept = Vec::new();
+    for line in lines.iter().rev() {
+        let cost = line.chars().count() + 2;
+        if used + cost > budget && !kept.is_empty() {
+            break;
+        }
+        used += cost;
+        kept.push(line.as_str());
+    }
+    kept.reverse();
+    Transcript {
+        text: kept.join("\n\n"),
+        items_included: kept.len(),
+        items_total: lines.len(),
+    }
+}
+
+pub(crate) fn build_summary_prompt(transcript: &Transcript) -> String {
+    let omitted = transcript.items_total - transcript.items_included;
+    let note = if omitted > 0 {
+        format!("The {omitted} earliest items were left out to fit the budget.\n")
+    } else {
+        String::new()
+    };
+    format!(
+        "Summarize the following coding conversation so it can be continued in a new thread. \
+Cover the goal, decisions made, files touched, commands that matter, the current state, \
+and open questions or next steps. Be specific (names, paths, errors) and concise. \
+Only output the summary, nothing else.\n\
+{note}\nConversation:\n{}",
+        transcript.text
+    )
+}
+
+/// First message of a thread started from a summary.
+pub(crate) fn seed_message(summary: &str) -> String {
+    format!(
+        "We are continuing work from an earlier thread. Here is a summary of it:\n\n{summary}\n\n\
+Use this as context for what comes next."
+    )
+}
+
+/// Reads the thread and summarizes it in a background thread. The caller
+/// decides whether to start a new thread from the summary.
+pub(crate) async fn summarize_thread(
+    session: &WorkspaceSession,
+    thread_id: &str,
+) -> Result<ThreadSummary, String> {
+    let resumed = session
+        .send_request("thread/resume", json!({ "threadId": thread_id }))
+        .await?;
+    if let Some(error) = resumed.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("Unknown error reading thread");
+        return Err(message.to_string());
+    }
+    let transcript = build_transcript(&resumed, SUMMARY_TRANSCRIPT_BUDGET_CHARS);
+    if transcript.items_included == 0 {
+        return Err("Thread has nothing to summarize".to_string());
+    }
+    let prompt = build_summary_prompt(&transcript);
+    let summary =
+        run_background_prompt(session, &prompt, SUMMARY_TIMEOUT, "thread summary").await?;
+
+    let source_chars = transcript.text.chars().count();
+    let summary_chars = summary.chars().count();
+    Ok(ThreadSummary {
+        thread_id: thread_id.to_string(),
+        summary,
+        items_included: transcript.items_included,
+        items_total: transcript.items_total,
+        source_chars,
+        summary_chars,
+        approx_source_tokens: approx_tokens(source_chars),
+        approx_summary_tokens: approx_tokens(summary_chars),
+        new_thread_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_summary_prompt, build_transcript};
+    use serde_json::json;
+
+    fn resume_response() -> serde_json::Value {
+        json!({
+            "result": {
+                "thread": {
+                    "id": "thr_1",
+                    "turns": [
+                        { "items": [
+                            { "type": "userMessage", "id": "u1",
+                              "content": [{ "type": "text", "text": "fix the flaky test" }] },
+                            { "type": "reasoning", "id": "r1", "summary": ["thinking"] },
+                            { "type": "commandExecution", "id": "c1",
+                              "command": ["cargo", "test"], "exitCode": 101 },
+                        ]},
+                        { "items": [
+                            { "type": "fileChange", "id": "f1",
+                              "changes": [{ "path": "src/lib.rs", "kind": "update" }] },
+                            { "type": "agentMessage", "id": "a1", "text": "Fixed the race." },
+                            { "type": "webSearch", "id": "w1" },
+                        ]}
+                    ]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn transcript_renders_known_items_in_order() {
+        let transcript = build_transcript(&resume_response(), 10_000);
+        assert_eq!(transcript.items_total, 4);
+        assert_eq!(transcript.items_included, 4);
+        assert_eq!(
+            transcript.text,
+            "User: fix the flaky test\n\nRan: cargo test (exit 101)\n\n\
+             Changed files: src/lib.rs\n\nAssistant: Fixed the race."
+        );
+    }
+
+    #[test]
+    fn budget_keeps_the_most_recent_items() {
+        let transcript = build_transcript(&resume_response(), 60);
+        assert_eq!(transcript.items_included, 2);
+        assert!(transcript.text.starts_with("Changed files"));
+        let prompt = build_summary_prompt(&transcript);
+        assert!(prompt.contains("The 2 earliest items were left out"));
+        assert!(prompt.ends_with("Assistant: Fixed the race."));
+    }
+}
@@ -0,0 +1,367 @@
+//! Files each turn changed, recorded from `fileChange` items as they complete,
+//! and the staging half of `commit_turn_changes`, which commits just those
+//! files. Every file is fingerprinted with the blob id of what the agent left
+//! behind, so edits made afterwards can be told apart from the agent's.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use git2::{ObjectType, Oid, Repository};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::git_patch::run_git;
+use crate::git_status_cache;
+use crate::git_utils::action_paths_for_file;
+use crate::thread_items::parse_thread_item;
+use crate::types::{FileChangeEntry, KnownThreadItem, ThreadItem};
+use crate::utils::normalize_git_path;
+
+/// Error prefix when files of the turn changed after the agent wrote them.
+/// The rest of the message is JSON: `{ "paths" }`, relative to the repo.
+pub(crate) const TURN_FILES_MODIFIED_ERROR: &str = "turn_files_modified";
+
+/// Oldest turns are forgotten past this many.
+const MAX_TRACKED_TURNS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+struct TurnFile {
+    path: PathBuf,
+    /// Blob id of the content the agent left, `None` when it removed the file.
+    fingerprint: Option<String>,
+}
+
+#[derive(Default)]
+struct TurnIndex {
+    /// `workspace:thread:turn` to the files changed, in the order first seen.
+    turns: HashMap<String, Vec<TurnFile>>,
+    order: VecDeque<String>,
+}
+
+static INDEX: OnceLock<Mutex<TurnIndex>> = OnceLock::new();
+
+fn index() -> MutexGuard<'static, TurnIndex> {
+    INDEX
+        .get_or_init(|| Mutex::new(TurnIndex::default()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+fn turn_key(workspace_id: &str, thread_id: &str, turn_id: &str) -> String {
+    format!("{workspace_id}:{thread_id}:{turn_id}")
+}
+
+fn fingerprint(path: &Path) -> Option<String> {
+    if !path.is_file() {
+        return None;
+    }
+    Oid::hash_file(ObjectType::Blob, path)
+        .ok()
+        .map(|oid| oid.to_string())
+}
+
+/// The paths a change touched: the file itself, and for a move the
+/// destination too.
+fn change_paths(workspace_path: &Path, change: &FileChangeEntry) -> Vec<PathBuf> {
+    let mut paths = vec![workspace_path.join(&change.path)];
+    let move_path = change.kind.as_ref().and_then(|kind| {
+        kind.get("move_path")
+            .or_else(|| kind.get("movePath"))
+            .and_then(|value| value.as_str())
+    });
+    if let Some(move_path) = move_path {
+        paths.push(workspace_path.join(move_path));
+    }
+    paths
+}
+
+fn record_files(key: String, files: Vec<TurnFile>) {
+    let mut index = index();
+    if !index.turns.contains_key(&key) {
+        index.order.push_back(key.clone());
+        while index.order.len() > MAX_TRACKED_TURNS {
+            if let Some(oldest) = index.order.pop_front() {
+                index.turns.remove(&oldest);
+            }
+        }
+    }
+    let recorded = index.turns.entry(key).or_default();
+    for file in files {
+        match recorded.iter_mut().find(|entry| entry.path == file.path) {
+            Some(entry) => entry.fingerprint = file.fingerprint,
+            None => recorded.push(file),
+        }
+    }
+}
+
+/// Records the files of a completed `fileChange` item. Every other message is
+/// ignored, as are changes that were declined or failed.
+pub(crate) fn record_turn_message(workspace_id: &str, workspace_path: &str, message: &Value) {
+    if message.get("method").and_then(|value| value.as_str()) != Some("item/completed") {
+        return;
+    }
+    let Some(params) = message.get("params") else {
+        return;
+    };
+    let param = |camel: &str, snake: &str| {
+        params
+            .get(camel)
+            .or_else(|| params.get(snake))
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+    };
+    let (Some(thread_id), Some(turn_id)) =
+        (param("threadId", "thread_id"), param("turnId", "turn_id"))
+    else {
+        return;
+    };
+    let Some(item) = params.get("item") else {
+        return;
+    };
+    let ThreadItem::Known(KnownThreadItem::FileChange(item)) = parse_thread_item(item.clone())
+    else {
+        return;
+    };
+    if item
+        .status
+        .as_deref()
+        .is_some_and(|status| status != "completed")
+    {
+        return;
+    }
+    let workspace_path = Path::new(workspace_path);
+    let files: Vec<TurnFile> = item
+        .changes
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|change| change_paths(workspace_path, change))
+        .map(|path| TurnFile {
+            fingerprint: fingerprint(&path),
+            path,
+        })
+        .collect();
+    if !files.is_empty() {
+        record_files(turn_key(workspace_id, &thread_id, &turn_id), files);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnCommitResult {
+    pub(crate) sha: String,
+    /// Repo-relative paths in the commit, including both sides of renames.
+    pub(crate) files: Vec<String>,
+    pub(crate) message: String,
+}
+
+/// Files of a turn, staged and ready to commit.
+pub(crate) struct StagedTurnChanges {
+    pub(crate) paths: Vec<String>,
+    /// `git diff --cached` of `paths`, unredacted.
+    pub(crate) diff: String,
+}
+
+fn repo_relative_path(repo_root: &Path, path: &Path) -> Option<String> {
+    if let Ok(relative) = path.strip_prefix(repo_root) {
+        return Some(normalize_git_path(&relative.to_string_lossy()));
+    }
+    // The workspace path and the repo root may spell the same directory
+    // differently (symlinks, `/private` on macOS). The file itself may be
+    // gone, so canonicalize its parent.
+    let root = repo_root.canonicalize().ok()?;
+    let parent = path.parent()?.canonicalize().ok()?;
+    let relative = parent.join(path.file_name()?);
+    let relative = relative.strip_prefix(&root).ok()?;
+    Some(normalize_git_path(&relative.to_string_lossy()))
+}
+
+/// Whether git knows `path` in the index or the working tree. Anything else,
+/// like a file the agent created and deleted again, is left out of staging.
+fn path_exists_for_git(repo: &Repository, repo_root: &Path, path: &str) -> bool {
+    repo_root.join(path).symlink_metadata().is_ok()
+        || repo
+            .index()
+            .ok()
+            .is_some_and(|index| index.get_path(Path::new(path), 0).is_some())
+}
+
+/// Stages the files recorded for a turn, and nothing else. Unless
+/// `allow_dirty`, refuses with `turn_files_modified` when any of them changed
+/// since the agent wrote it.
+pub(crate) async fn stage_turn_changes(
+    repo_root: &Path,
+    workspace_id: &str,
+    thread_id: &str,
+    turn_id: &str,
+    allow_dirty: bool,
+) -> Result<StagedTurnChanges, String> {
+    let files = index()
+        .turns
+        .get(&turn_key(workspace_id, thread_id, turn_id))
+        .cloned()
+        .ok_or_else(|| format!("No file changes recorded for turn {turn_id}"))?;
+
+    let mut relative = Vec::new();
+    let mut modified = Vec::new();
+    for file in &files {
+        let path = repo_relative_path(repo_root, &file.path).ok_or_else(|| {
+            format!(
+                "{} is outside the repository at {}",
+                file.path.display(),
+                repo_root.display()
+            )
+        })?;
+        if fingerprint(&file.path) != file.fingerprint {
+            modified.push(path.clone());
+        }
+        relative.push(path);
+    }
+    if !allow_dirty && !modified.is_empty() {
+        return Err(format!(
+            "{TURN_FILES_MODIFIED_ERROR}: {}",
+            json!({ "paths": modified })
+        ));
+    }
+
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let mut paths: Vec<String> = Vec::new();
+    for path in relative {
+        for expanded in action_paths_for_file(repo_root, &path) {
+            if !paths.contains(&expanded) && path_exists_for_git(&repo, repo_root, &expanded) {
+                paths.push(expanded);
+            }
+        }
+    }
+    if paths.is_empty() {
+        return Err("No changes to commit for this turn".to_string());
+    }
+
+    let mut args = vec!["add", "-A", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let staged = run_git(repo_root, &args, None, false).await;
+    git_status_cache::invalidate(repo_root);
+    staged?;
+
+    let mut args = vec!["diff", "--cached", "--no-color", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let diff = run_git(repo_root, &args, None, false).await?;
+    let diff = String::from_utf8_lossy(&diff).to_string();
+    if diff.trim().is_empty() {
+        return Err("No changes to commit for this turn".to_string());
+    }
+    Ok(StagedTurnChanges { paths, diff })
+}
+
+/// Commits only `staged.paths`, leaving anything else in the index staged.
+pub(crate) async fn commit_staged_turn_changes(
+    repo_root: &Path,
+    staged: StagedTurnChanges,
+    message: String,
+) -> Result<TurnCommitResult, String> {
+    let message = message.trim().to_string();
+    if message.is_empty() {
+        return Err("Commit message is empty".to_string());
+    }
+    let mut args = vec!["commit", "-m", message.as_str(), "--"];
+    args.extend(staged.paths.iter().map(String::as_str));
+    let committed = run_git(repo_root, &args, None, false).await;
+    git_status_cache::invalidate(repo_root);
+    committed?;
+    let sha = run_git(repo_root, &["rev-parse", "HEAD"], None, false).await?;
+    Ok(TurnCommitResult {
+        sha: String::from_utf8_lossy(&sha).trim().to_string(),
+        files: staged.paths,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{commit_staged_turn_changes, record_turn_message, stage_turn_changes};
+    use crate::test_git::{git, init_repo};
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn file_change(turn_id: &str, changes: serde_json::Value) -> serde_json::Value {
+        json!({
+            "method": "item/completed",
+            "params": {
+                "threadId": "thread-1",
+                "turnId": turn_id,
+                "item": { "type": "fileChange", "id": "fc-1", "status": "completed", "changes": changes },
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn commits_only_the_turns_files_including_deletions_and_moves() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        init_repo(root);
+        for name in ["keep.txt", "gone.txt", "old.txt", "mine.txt"] {
+            std::fs::write(root.join(name), format!("{name}\n")).unwrap();
+        }
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "init"]);
+
+        std::fs::write(root.join("keep.txt"), "agent\n").unwrap();
+        std::fs::write(root.join("added.txt"), "new\n").unwrap();
+        std::fs::remove_file(root.join("gone.txt")).unwrap();
+        std::fs::rename(root.join("old.txt"), root.join("new.txt")).unwrap();
+        std::fs::write(root.join("mine.txt"), "user\n").unwrap();
+        let workspace = root.to_string_lossy().to_string();
+        record_turn_message(
+            "turn-ws",
+            &workspace,
+            &file_change(
+                "turn-1",
+                json!([
+                    { "path": "keep.txt", "kind": { "type": "update" } },
+                    { "path": "added.txt", "kind": { "type": "add" } },
+                    { "path": "gone.txt", "kind": { "type": "delete" } },
+                    { "path": "old.txt", "kind": { "type": "update", "move_path": "new.txt" } },
+                ]),
+            ),
+        );
+
+        std::fs::write(root.join("keep.txt"), "agent\nuser\n").unwrap();
+        let err = stage_turn_changes(root, "turn-ws", "thread-1", "turn-1", false)
+            .await
+            .err()
+            .expect("dirty");
+        assert_eq!(err, r#"turn_files_modified: {"paths":["keep.txt"]}"#);
+        std::fs::write(root.join("keep.txt"), "agent\n").unwrap();
+
+        let staged = stage_turn_changes(root, "turn-ws", "thread-1", "turn-1", false)
+            .await
+            .expect("stage");
+        assert!(staged.diff.contains("+new"));
+        let result = commit_staged_turn_changes(root, staged, "Agent changes".to_string())
+            .await
+            .expect("commit");
+        let mut files = result.files.clone();
+        files.sort();
+        assert_eq!(
+            files,
+            vec!["added.txt", "gone.txt", "keep.txt", "new.txt", "old.txt"]
+        );
+        assert_eq!(git(root, &["rev-parse", "HEAD"]).trim(), result.sha);
+        let mut committed: Vec<String> = git(
+            root,
+            &["show", "--name-only", "--no-renames", "--format=", "HEAD"],
+        )
+        .lines()
+        .map(str::to_string)
+        .collect();
+        committed.sort();
+        assert_eq!(committed, files);
+        assert_eq!(git(root, &["status", "--porcelain"]).trim(), "M mine.txt");
+
+        assert!(
+            stage_turn_changes(root, "turn-ws", "thread-1", "turn-2", false)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::memory::connection_test::MemoryConnectionReport;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitFileStatus {
     pub(crate) path: String,
@@ -24,6 +28,13 @@ pub(crate) struct GitFileDiff {
     pub(crate) old_image_mime: Option<String>,
     #[serde(rename = "newImageMime")]
     pub(crate) new_image_mime: Option<String>,
+    /// Only whitespace changed, e.g. CRLF line endings. With
+    /// `diffIgnoreWhitespace` such a file comes back without hunks.
+    #[serde(default, rename = "whitespaceOnly")]
+    pub(crate) whitespace_only: bool,
+    /// Word-level changes, present when requested with `intraline`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) intraline: Option<Vec<IntralineHunk>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -43,6 +54,58 @@ pub(crate) struct GitCommitDiff {
     pub(crate) old_image_mime: Option<String>,
     #[serde(rename = "newImageMime")]
     pub(crate) new_image_mime: Option<String>,
+    /// Only whitespace changed, e.g. CRLF line endings. With
+    /// `diffIgnoreWhitespace` such a file comes back without hunks.
+    #[serde(default, rename = "whitespaceOnly")]
+    pub(crate) whitespace_only: bool,
+    /// Word-level changes, present when requested with `intraline`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) intraline: Option<Vec<IntralineHunk>>,
+    /// Staged hunks then unstaged ones, present when requested with `hunks`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) hunks: Option<Vec<GitDiffHunk>>,
+}
+
+/// One hunk of a file's staged (HEAD to index) or unstaged (index to
+/// worktree) diff, as `stage_git_hunk` and `unstage_git_hunk` take it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitDiffHunk {
+    /// Position among the file's hunks on the same side, from 0.
+    pub(crate) index: usize,
+    pub(crate) staged: bool,
+    /// The `@@ -a,b +c,d @@` line, without its newline.
+    pub(crate) header: String,
+    pub(crate) old_start: u32,
+    pub(crate) old_lines: u32,
+    pub(crate) new_start: u32,
+    pub(crate) new_lines: u32,
+    /// The header and the hunk's lines.
+    pub(crate) content: String,
+}
+
+/// The paired lines of one hunk that changed within the line.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct IntralineHunk {
+    /// Position of the hunk in the file's diff, from 0.
+    pub(crate) hunk: usize,
+    pub(crate) pairs: Vec<IntralinePair>,
+}
+
+/// A removed line and the added line that replaced it. Ranges are
+/// `[offset, length]` in UTF-16 code units of the line without its `-`/`+`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IntralinePair {
+    pub(crate) old_line: u32,
+    pub(crate) new_line: u32,
+    #[serde(default)]
+    pub(crate) old_ranges: Vec<[u32; 2]>,
+    #[serde(default)]
+    pub(crate) new_ranges: Vec<[u32; 2]>,
+    /// The lines were too long to compare; they come without ranges.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) skipped: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -121,6 +184,9 @@ pub(crate) struct GitHubPullRequestDiff {
     pub(crate) path: String,
     pub(crate) status: String,
     pub(crate) diff: String,
+    /// Word-level changes, present when requested with `intraline`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) intraline: Option<Vec<IntralineHunk>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -179,12 +245,58 @@ pub(crate) struct LocalUsageSnapshot {
     pub(crate) top_models: Vec<LocalUsageModel>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkspaceTodo {
+    pub(crate) path: String,
+    pub(crate) line: usize,
+    pub(crate) marker: String,
+    pub(crate) text: String,
+    #[serde(default)]
+    pub(crate) author: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct BranchInfo {
     pub(crate) name: String,
     pub(crate) last_commit: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct GitTagInfo {
+    pub(crate) name: String,
+    /// Commit the tag points at, after peeling annotated tags.
+    pub(crate) target: String,
+    pub(crate) annotated: bool,
+    #[serde(default)]
+    pub(crate) tagger: Option<String>,
+    #[serde(default)]
+    pub(crate) message: Option<String>,
+    /// Tagger time for annotated tags, commit time for lightweight ones.
+    pub(crate) date: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitStashEntry {
+    /// Position in the stash list; `0` is the newest.
+    pub(crate) index: usize,
+    /// The stash subject, e.g. `On main: message`.
+    pub(crate) message: String,
+    /// Unix seconds.
+    pub(crate) date: i64,
+    /// As git prints it, e.g. `2 hours ago`.
+    pub(crate) relative_date: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitCommitOutcome {
+    /// An amend rewrote a commit that is already on the upstream, so the
+    /// next push has to be forced.
+    #[serde(default)]
+    pub(crate) amended_pushed_commit: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorkspaceEntry {
     pub(crate) id: String,
@@ -206,7 +318,16 @@ pub(crate) struct WorkspaceInfo {
     pub(crate) id: String,
     pub(crate) name: String,
     pub(crate) path: String,
-    pub(crate) connected: bool,
+    pub(crate) connected: ConnectionState,
+    /// The path did not answer within a short timeout, e.g. a network mount
+    /// that went offline. Git, terminal and session methods fail fast with
+    /// `path_unavailable` until a background check sees it again.
+    #[serde(default, rename = "pathUnavailable")]
+    pub(crate) path_unavailable: bool,
+    /// Mirrors `settings.readOnly`, so clients can disable write actions
+    /// without reading the settings.
+    #[serde(default, rename = "readOnly")]
+    pub(crate) read_only: bool,
     pub(crate) codex_bin: Option<String>,
     #[serde(default)]
     pub(crate) kind: WorkspaceKind,
@@ -218,6 +339,52 @@ pub(crate) struct WorkspaceInfo {
     pub(crate) settings: WorkspaceSettings,
 }
 
+/// `connected` in `list_workspaces`: `true`, `false`, `"unhealthy"` while
+/// the app-server process is running but has stopped answering probes, or
+/// `"stale"` when the process has exited but its session was still registered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ConnectionState {
+    Connected,
+    #[default]
+    Disconnected,
+    Unhealthy,
+    Stale,
+}
+
+impl From<bool> for ConnectionState {
+    fn from(connected: bool) -> Self {
+        if connected {
+            Self::Connected
+        } else {
+            Self::Disconnected
+        }
+    }
+}
+
+impl Serialize for ConnectionState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Connected => serializer.serialize_bool(true),
+            Self::Disconnected => serializer.serialize_bool(false),
+            Self::Unhealthy => serializer.serialize_str("unhealthy"),
+            Self::Stale => serializer.serialize_str("stale"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ConnectionState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Bool(connected) => Ok(connected.into()),
+            serde_json::Value::String(state) if state == "unhealthy" => Ok(Self::Unhealthy),
+            serde_json::Value::String(state) if state == "stale" => Ok(Self::Stale),
+            other => Err(serde::de::Error::custom(format!(
+                "invalid connection state: {other}"
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub(crate) enum WorkspaceKind {
@@ -244,11 +411,108 @@ pub(crate) enum WorkspacePurpose {
     Life,
 }
 
+/// How ambiguous slash dates (`03/06/2024`) in vault notes are read.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub(crate) enum DateOrder {
+    #[default]
+    #[serde(rename = "mdy")]
+    MonthFirst,
+    #[serde(rename = "dmy")]
+    DayFirst,
+}
+
+/// The most permissive access mode turns in a workspace may use.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) enum AccessLock {
+    #[serde(rename = "read-only")]
+    ReadOnly,
+    #[serde(rename = "current")]
+    Current,
+}
+
+impl AccessLock {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            AccessLock::ReadOnly => "read-only",
+            AccessLock::Current => "current",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorktreeInfo {
     pub(crate) branch: String,
 }
 
+/// One row of `worktree_report`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeReportEntry {
+    pub(crate) workspace_id: String,
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) branch: Option<String>,
+    /// False when the worktree folder is gone.
+    pub(crate) exists: bool,
+    pub(crate) disk_bytes: u64,
+    pub(crate) disk_files: u64,
+    /// Set when the scan hit its cap; the sizes are then a lower bound.
+    pub(crate) disk_usage_truncated: bool,
+    /// Unix seconds of the branch tip.
+    pub(crate) last_commit_at: Option<i64>,
+    /// Whether the branch is fully merged into the parent's primary branch.
+    pub(crate) merged: Option<bool>,
+    pub(crate) ahead: Option<usize>,
+    pub(crate) behind: Option<usize>,
+    pub(crate) dirty: bool,
+    pub(crate) suggested_action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) removed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) remove_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeReport {
+    pub(crate) parent_workspace_id: String,
+    pub(crate) primary_branch: Option<String>,
+    pub(crate) worktrees: Vec<WorktreeReportEntry>,
+}
+
+/// One changed file of `compare_worktrees`, going from A to B.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeCompareFile {
+    pub(crate) path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) old_path: Option<String>,
+    pub(crate) status: String,
+    pub(crate) additions: usize,
+    pub(crate) deletions: usize,
+    pub(crate) is_binary: bool,
+    /// Missing for binary files and patches over the size budget.
+    pub(crate) patch: Option<String>,
+    pub(crate) patch_truncated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeComparison {
+    pub(crate) mode: String,
+    pub(crate) workspace_id_a: String,
+    pub(crate) workspace_id_b: String,
+    /// Branch tips compared in `branches` mode.
+    pub(crate) ref_a: Option<String>,
+    pub(crate) ref_b: Option<String>,
+    pub(crate) files: Vec<WorktreeCompareFile>,
+    pub(crate) file_count: usize,
+    pub(crate) additions: usize,
+    pub(crate) deletions: usize,
+    /// Set when the total patch budget ran out; later files have no patch.
+    pub(crate) truncated: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorkspaceGroup {
     pub(crate) id: String,
@@ -279,8 +543,159 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) apply_domain_instructions: Option<bool>,
     #[serde(default)]
     pub(crate) purpose: Option<WorkspacePurpose>,
+    /// Single-vault form, kept for settings written before `obsidianRoots`.
     #[serde(default, rename = "obsidianRoot")]
     pub(crate) obsidian_root: Option<String>,
+    #[serde(
+        default,
+        rename = "obsidianRoots",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub(crate) obsidian_roots: Vec<String>,
+    /// Overrides for the entity browser's folders, keyed `domain/kind` and
+    /// relative to each vault.
+    #[serde(
+        default,
+        rename = "entityDirs",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub(crate) entity_dirs: BTreeMap<String, String>,
+    /// Day/month order of slash dates in the workspace's vault notes.
+    #[serde(default, rename = "dateOrder")]
+    pub(crate) date_order: DateOrder,
+    #[serde(default, rename = "protectedBranches")]
+    pub(crate) protected_branches: Vec<String>,
+    /// Compare diffs shown in the commit panel and sent for commit messages
+    /// the way `git diff -w` does. Staging and commits are unaffected.
+    #[serde(default, rename = "diffIgnoreWhitespace")]
+    pub(crate) diff_ignore_whitespace: bool,
+    #[serde(
+        default,
+        rename = "quickCommands",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub(crate) quick_commands: Vec<QuickCommand>,
+    /// Used when the vault has no `Entities/Food/Goals.md`.
+    #[serde(
+        default,
+        rename = "nutritionGoals",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) nutrition_goals: Option<NutritionGoals>,
+    /// Lowercase `#rrggbb`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) color: Option<String>,
+    /// An emoji or a built-in icon name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) icon: Option<String>,
+    /// Keep the daemon's GitHub issues, pull requests and checks warm while
+    /// the workspace is connected.
+    #[serde(default, rename = "githubPrefetch")]
+    pub(crate) github_prefetch: bool,
+    /// Stronger access modes requested for turns here are lowered to this.
+    #[serde(
+        default,
+        rename = "accessLock",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) access_lock: Option<AccessLock>,
+    /// Append finished turns to `Stream/Transcripts/` in a Life workspace's
+    /// vault.
+    #[serde(default, rename = "lifeTranscriptEnabled")]
+    pub(crate) life_transcript_enabled: bool,
+    /// Extra `turn/start` fields sent with every turn; a call's own `extras`
+    /// win key by key.
+    #[serde(
+        default,
+        rename = "turnExtras",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) turn_extras: Option<serde_json::Map<String, serde_json::Value>>,
+    /// Keep an app-server session running here while `warmSessions` is on.
+    #[serde(default, rename = "keepWarm")]
+    pub(crate) keep_warm: bool,
+    /// Turns started here are interrupted after this many minutes.
+    #[serde(
+        default,
+        rename = "maxTurnDurationMinutes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) max_turn_duration_minutes: Option<u32>,
+    /// Budget for read-only turns, which `maxTurnDurationMinutes` does not
+    /// cover. Unset means no limit.
+    #[serde(
+        default,
+        rename = "readOnlyMaxTurnDurationMinutes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) read_only_max_turn_duration_minutes: Option<u32>,
+    /// Demo mode: refuse git mutations, terminal input, prompt edits and
+    /// vault write-backs here, and run every turn read-only.
+    #[serde(default, rename = "readOnly")]
+    pub(crate) read_only: bool,
+}
+
+impl WorkspaceSettings {
+    /// `obsidianRoots` when set, otherwise the legacy `obsidianRoot`.
+    pub(crate) fn effective_obsidian_roots(&self) -> Vec<String> {
+        if !self.obsidian_roots.is_empty() {
+            return self.obsidian_roots.clone();
+        }
+        self.obsidian_root.iter().cloned().collect()
+    }
+
+    pub(crate) fn has_obsidian_root(&self) -> bool {
+        !self.effective_obsidian_roots().is_empty()
+    }
+
+    /// `accessLock`, tightened to read-only while `readOnly` is on.
+    pub(crate) fn effective_access_lock(&self) -> Option<AccessLock> {
+        if self.read_only {
+            return Some(AccessLock::ReadOnly);
+        }
+        self.access_lock
+    }
+}
+
+/// What `run_quick_command` does when the command is already running.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum QuickCommandConcurrency {
+    #[default]
+    Reject,
+    Queue,
+}
+
+/// Daily targets; a missing macro has no goal.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub(crate) struct NutritionGoals {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) calories: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) protein: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) carbs: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) fat: Option<f64>,
+}
+
+/// A command runnable from the workspace without a terminal. It is spawned
+/// directly, not through a shell.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct QuickCommand {
+    pub(crate) id: String,
+    pub(crate) label: String,
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    /// Working directory relative to the workspace; the workspace itself when
+    /// unset.
+    #[serde(default, rename = "cwdRelative")]
+    pub(crate) cwd_relative: Option<String>,
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) concurrency: QuickCommandConcurrency,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -314,6 +729,16 @@ pub(crate) struct Domain {
     pub(crate) default_approval_policy: Option<String>,
 }
 
+/// `domains_create`/`domains_update` response: the saved domain plus
+/// non-fatal problems such as unresolved `@include(...)` directives.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct DomainSaveResult {
+    #[serde(flatten)]
+    pub(crate) domain: Domain,
+    #[serde(default)]
+    pub(crate) warnings: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct TrendCard {
     pub(crate) id: String,
@@ -321,6 +746,50 @@ pub(crate) struct TrendCard {
     pub(crate) value: String,
     #[serde(default, rename = "subLabel")]
     pub(crate) sub_label: Option<String>,
+    /// Change against the previous period; only set on `compare` snapshots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) delta: Option<TrendDelta>,
+    /// Position in the domain's card layout; stays the same when other cards
+    /// are left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) order: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) format: Option<CardFormat>,
+    /// `value` as a number, in the unit `format` names.
+    #[serde(default, rename = "valueNum", skip_serializing_if = "Option::is_none")]
+    pub(crate) value_num: Option<f64>,
+}
+
+/// How to read a card's `valueNum`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CardFormat {
+    /// Dollars.
+    Currency,
+    Count,
+    Grams,
+    /// Minutes.
+    Duration,
+    Percent,
+    Number,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct TrendDelta {
+    /// The card's value for the previous period, as displayed.
+    pub(crate) previous: String,
+    /// `None` when the previous value was zero and the current one is not.
+    pub(crate) percent: Option<f64>,
+    /// `+12%`, `-3%`, `0%` or `new`.
+    pub(crate) label: String,
+}
+
+/// The cards of a `compare` snapshot, computed for the preceding period.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct TrendPrevious {
+    pub(crate) start: String,
+    pub(crate) end: String,
+    pub(crate) cards: Vec<TrendCard>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -336,15 +805,39 @@ pub(crate) struct TrendList {
     pub(crate) id: String,
     pub(crate) title: String,
     pub(crate) items: Vec<TrendListItem>,
+    /// Position in the domain's list layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) order: Option<u32>,
+}
+
+/// One bucket of a trend series; `date` is the day, or the Monday of the week.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct TrendPoint {
+    pub(crate) date: String,
+    pub(crate) value: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct TrendSeries {
     pub(crate) id: String,
     pub(crate) label: String,
-    pub(crate) points: Vec<f64>,
-    #[serde(default)]
-    pub(crate) labels: Option<Vec<String>>,
+    pub(crate) unit: String,
+    /// `day`, or `week` for open-ended ranges.
+    pub(crate) bucket: String,
+    pub(crate) points: Vec<TrendPoint>,
+}
+
+/// An entry written by `stream_append`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StreamAppend {
+    /// The `Stream/YYYY-MM.md` file written to.
+    pub(crate) path: String,
+    pub(crate) date: String,
+    /// The line as written, e.g. `**9:00am** | Breakfast`.
+    pub(crate) line: String,
+    /// Whether the day had no header yet.
+    pub(crate) created_header: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -358,6 +851,21 @@ pub(crate) struct DomainTrendSnapshot {
     pub(crate) lists: Vec<TrendList>,
     #[serde(default)]
     pub(crate) series: Option<Vec<TrendSeries>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) roots: Vec<VaultContribution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) previous: Option<TrendPrevious>,
+    /// Why part of the snapshot was left out, e.g. unreadable goals.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) diagnostics: Vec<String>,
+}
+
+/// What one vault contributed to a multi-vault Life dashboard or trend.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct VaultContribution {
+    pub(crate) vault: String,
+    pub(crate) root: String,
+    pub(crate) counts: BTreeMap<String, usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -509,6 +1017,12 @@ pub(crate) struct AppSettings {
     pub(crate) igdb_client_secret: String,
     #[serde(default)]
     pub(crate) exa_api_key: String,
+    /// Look up film, TV and anime covers on TMDB. Off keeps the key.
+    #[serde(default = "default_media_provider_enabled")]
+    pub(crate) tmdb_enabled: bool,
+    /// Look up game covers on IGDB. Off keeps the client id and secret.
+    #[serde(default = "default_media_provider_enabled")]
+    pub(crate) igdb_enabled: bool,
     #[serde(default = "default_memory_embedding_enabled")]
     pub(crate) memory_embedding_enabled: bool,
     #[serde(default, rename = "autoMemory")]
@@ -560,6 +1074,335 @@ pub(crate) struct AppSettings {
     pub(crate) composer_code_block_copy_use_modifier: bool,
     #[serde(default = "default_workspace_groups", rename = "workspaceGroups")]
     pub(crate) workspace_groups: Vec<WorkspaceGroup>,
+    #[serde(default, rename = "protectedBranches")]
+    pub(crate) protected_branches: Vec<String>,
+    #[serde(default, rename = "notificationRules")]
+    pub(crate) notification_rules: Vec<NotificationRule>,
+    #[serde(default, rename = "autoArchive")]
+    pub(crate) auto_archive: AutoArchiveSettings,
+    /// Shell program for integrated terminals; platform default when unset.
+    #[serde(default, rename = "terminalShell")]
+    pub(crate) terminal_shell: Option<String>,
+    /// Record commands sent to terminals on open in `terminal_audit.jsonl`.
+    #[serde(default, rename = "terminalAuditLog")]
+    pub(crate) terminal_audit_log: bool,
+    /// Output kept per terminal for `terminal_snapshot`; 256 KB when unset.
+    #[serde(default, rename = "terminalScrollbackBytes")]
+    pub(crate) terminal_scrollback_bytes: Option<usize>,
+    /// Days to keep revert undo snapshots before they are purged.
+    #[serde(
+        default = "default_revert_undo_retention_days",
+        rename = "revertUndoRetentionDays"
+    )]
+    pub(crate) revert_undo_retention_days: u32,
+    #[serde(default, rename = "reviewGate")]
+    pub(crate) review_gate: ReviewGateSettings,
+    #[serde(default)]
+    pub(crate) redaction: RedactionSettings,
+    #[serde(default)]
+    pub(crate) keepalive: KeepaliveSettings,
+    #[serde(default, rename = "githubPrefetch")]
+    pub(crate) github_prefetch: GithubPrefetchSettings,
+    /// Rates `usage_overview` estimates cost with. Unset means no estimate.
+    #[serde(
+        default,
+        rename = "usagePricing",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) usage_pricing: Option<UsagePricing>,
+    /// Zone dashboards, trends and local usage count days in when a request
+    /// names none: an IANA name or a UTC offset. Unset means UTC, or the host
+    /// zone for local usage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) timezone: Option<String>,
+    /// Let the daemon keep sessions running for workspaces with `keepWarm`.
+    #[serde(default, rename = "warmSessions")]
+    pub(crate) warm_sessions: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct AutoArchiveSettings {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    #[serde(
+        default = "default_auto_archive_inactive_days",
+        rename = "inactiveDays"
+    )]
+    pub(crate) inactive_days: u32,
+}
+
+impl Default for AutoArchiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            inactive_days: default_auto_archive_inactive_days(),
+        }
+    }
+}
+
+/// Liveness probe for app-server sessions.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct KeepaliveSettings {
+    /// Seconds of silence before a session is probed. Zero turns probing off.
+    #[serde(default = "default_keepalive_interval_secs", rename = "intervalSecs")]
+    pub(crate) interval_secs: u64,
+    /// Consecutive failed probes before the session is marked unhealthy.
+    #[serde(
+        default = "default_keepalive_failure_threshold",
+        rename = "failureThreshold"
+    )]
+    pub(crate) failure_threshold: u32,
+    /// Restart an unhealthy session instead of waiting for the user to
+    /// reconnect it.
+    #[serde(default, rename = "autoReconnect")]
+    pub(crate) auto_reconnect: bool,
+}
+
+impl Default for KeepaliveSettings {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_keepalive_interval_secs(),
+            failure_threshold: default_keepalive_failure_threshold(),
+            auto_reconnect: false,
+        }
+    }
+}
+
+/// Background refresh of GitHub data for workspaces with `githubPrefetch`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct GithubPrefetchSettings {
+    #[serde(
+        default = "default_github_prefetch_interval_minutes",
+        rename = "intervalMinutes"
+    )]
+    pub(crate) interval_minutes: u32,
+    /// Workspaces fetched at the same time, across the whole daemon.
+    #[serde(
+        default = "default_github_prefetch_max_concurrent",
+        rename = "maxConcurrent"
+    )]
+    pub(crate) max_concurrent: u32,
+}
+
+impl Default for GithubPrefetchSettings {
+    fn default() -> Self {
+        Self {
+            interval_minutes: default_github_prefetch_interval_minutes(),
+            max_concurrent: default_github_prefetch_max_concurrent(),
+        }
+    }
+}
+
+/// USD per million tokens.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsagePricing {
+    pub(crate) input_per_million: f64,
+    /// Defaults to `inputPerMillion`.
+    #[serde(default)]
+    pub(crate) cached_input_per_million: Option<f64>,
+    pub(crate) output_per_million: f64,
+}
+
+/// Size limits above which `start_review` asks for confirmation. Zero turns a
+/// limit off.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub(crate) struct ReviewGateSettings {
+    #[serde(default, rename = "maxFiles")]
+    pub(crate) max_files: u32,
+    /// Added plus removed lines.
+    #[serde(default, rename = "maxChangedLines")]
+    pub(crate) max_changed_lines: u32,
+    #[serde(default, rename = "maxDiffBytes")]
+    pub(crate) max_diff_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct CommitMessagePrompt {
+    pub(crate) prompt: String,
+    /// Redactions applied to the diff in `prompt`.
+    pub(crate) redactions: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct CommitMessageResult {
+    pub(crate) message: String,
+    /// Redactions applied to the diff the message was generated from.
+    pub(crate) redactions: usize,
+}
+
+/// Scrubbing applied to diffs and file bodies before they go into a prompt.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct RedactionSettings {
+    #[serde(default = "default_redaction_enabled")]
+    pub(crate) enabled: bool,
+    /// Globs matched against the file name and relative path; matching files
+    /// are withheld entirely.
+    #[serde(default = "default_redaction_file_patterns", rename = "filePatterns")]
+    pub(crate) file_patterns: Vec<String>,
+    /// Regexes for secret values; a `secret` group limits what is replaced.
+    #[serde(default = "default_redaction_value_patterns", rename = "valuePatterns")]
+    pub(crate) value_patterns: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_redaction_enabled(),
+            file_patterns: default_redaction_file_patterns(),
+            value_patterns: default_redaction_value_patterns(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum NotificationAction {
+    SystemNotification {
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+        #[serde(default, rename = "soundId")]
+        sound_id: Option<String>,
+    },
+    Sound {
+        #[serde(rename = "soundId")]
+        sound_id: String,
+    },
+    None,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct NotificationRule {
+    pub(crate) id: String,
+    pub(crate) event: String,
+    #[serde(default, rename = "workspaceId")]
+    pub(crate) workspace_id: Option<String>,
+    #[serde(default = "default_notification_rule_enabled")]
+    pub(crate) enabled: bool,
+    pub(crate) action: NotificationAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadSummary {
+    pub(crate) thread_id: String,
+    pub(crate) summary: String,
+    /// Items sent to the summarizer; older ones are dropped past the budget.
+    pub(crate) items_included: usize,
+    pub(crate) items_total: usize,
+    pub(crate) source_chars: usize,
+    pub(crate) summary_chars: usize,
+    pub(crate) approx_source_tokens: usize,
+    pub(crate) approx_summary_tokens: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) new_thread_id: Option<String>,
+}
+
+/// Typed view of the app-server thread items the UI renders. Fields the UI
+/// does not read are kept in `extra` so re-serializing is lossless.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum ThreadItem {
+    Known(KnownThreadItem),
+    /// Item kinds (or shapes) this build does not model, passed through as-is.
+    Unknown(serde_json::Value),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum KnownThreadItem {
+    AgentMessage(AgentMessageItem),
+    UserMessage(UserMessageItem),
+    CommandExecution(CommandExecutionItem),
+    FileChange(FileChangeItem),
+    Reasoning(ReasoningItem),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct AgentMessageItem {
+    pub(crate) id: String,
+    pub(crate) text: String,
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct UserMessageItem {
+    pub(crate) id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content: Option<Vec<serde_json::Value>>,
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct CommandExecutionItem {
+    pub(crate) id: String,
+    /// A shell string or an argv array, depending on the app-server version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) command: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) status: Option<String>,
+    #[serde(
+        default,
+        rename = "aggregatedOutput",
+        alias = "aggregated_output",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) aggregated_output: Option<String>,
+    #[serde(
+        default,
+        rename = "exitCode",
+        alias = "exit_code",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) exit_code: Option<i64>,
+    #[serde(
+        default,
+        rename = "durationMs",
+        alias = "duration_ms",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) duration_ms: Option<i64>,
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct FileChangeItem {
+    pub(crate) id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) changes: Option<Vec<FileChangeEntry>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) status: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct FileChangeEntry {
+    pub(crate) path: String,
+    /// `"add"`/`"delete"`/`"update"`, or an object with a `type` field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) kind: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) diff: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct ReasoningItem {
+    pub(crate) id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) summary: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -583,6 +1426,15 @@ pub(crate) struct AutoMemorySettings {
     pub(crate) write_daily: bool,
     #[serde(rename = "writeCurated")]
     pub(crate) write_curated: bool,
+    /// Searches memory with each sent message and prepends the top results
+    /// to the turn's instructions.
+    #[serde(default, rename = "recallEnabled")]
+    pub(crate) recall_enabled: bool,
+    #[serde(default = "default_recall_limit", rename = "recallLimit")]
+    pub(crate) recall_limit: usize,
+    /// Byte budget of the recall block, header included.
+    #[serde(default = "default_recall_max_bytes", rename = "recallMaxBytes")]
+    pub(crate) recall_max_bytes: usize,
 }
 
 impl Default for AutoMemorySettings {
@@ -720,10 +1572,60 @@ fn default_memory_embedding_enabled() -> bool {
     false
 }
 
+fn default_notification_rule_enabled() -> bool {
+    true
+}
+
 fn default_memory_enabled() -> bool {
     true
 }
 
+fn default_media_provider_enabled() -> bool {
+    true
+}
+
+fn default_auto_archive_inactive_days() -> u32 {
+    30
+}
+
+fn default_github_prefetch_interval_minutes() -> u32 {
+    5
+}
+
+fn default_github_prefetch_max_concurrent() -> u32 {
+    2
+}
+
+fn default_revert_undo_retention_days() -> u32 {
+    7
+}
+
+fn default_keepalive_interval_secs() -> u64 {
+    60
+}
+
+fn default_keepalive_failure_threshold() -> u32 {
+    3
+}
+
+fn default_redaction_enabled() -> bool {
+    true
+}
+
+fn default_redaction_file_patterns() -> Vec<String> {
+    crate::redaction::DEFAULT_FILE_PATTERNS
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
+
+fn default_redaction_value_patterns() -> Vec<String> {
+    crate::redaction::DEFAULT_VALUE_PATTERNS
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
+
 fn default_auto_memory_settings() -> AutoMemorySettings {
     AutoMemorySettings {
         enabled: false,
@@ -736,9 +1638,20 @@ fn default_auto_memory_settings() -> AutoMemorySettings {
         include_git_status: false,
         write_daily: true,
         write_curated: true,
+        recall_enabled: false,
+        recall_limit: default_recall_limit(),
+        recall_max_bytes: default_recall_max_bytes(),
     }
 }
 
+fn default_recall_limit() -> usize {
+    5
+}
+
+fn default_recall_max_bytes() -> usize {
+    2_000
+}
+
 fn default_dictation_enabled() -> bool {
     false
 }
@@ -839,6 +1752,8 @@ impl Default for AppSettings {
             igdb_client_id: String::new(),
             igdb_client_secret: String::new(),
             exa_api_key: String::new(),
+            tmdb_enabled: default_media_provider_enabled(),
+            igdb_enabled: default_media_provider_enabled(),
             memory_embedding_enabled: default_memory_embedding_enabled(),
             auto_memory: default_auto_memory_settings(),
             composer_editor_preset: default_composer_editor_preset(),
@@ -853,15 +1768,43 @@ impl Default for AppSettings {
             composer_list_continuation: default_composer_list_continuation(),
             composer_code_block_copy_use_modifier: default_composer_code_block_copy_use_modifier(),
             workspace_groups: default_workspace_groups(),
+            protected_branches: Vec::new(),
+            notification_rules: Vec::new(),
+            auto_archive: AutoArchiveSettings::default(),
+            terminal_shell: None,
+            terminal_audit_log: false,
+            terminal_scrollback_bytes: None,
+            revert_undo_retention_days: default_revert_undo_retention_days(),
+            review_gate: ReviewGateSettings::default(),
+            redaction: RedactionSettings::default(),
+            keepalive: KeepaliveSettings::default(),
+            github_prefetch: GithubPrefetchSettings::default(),
+            usage_pricing: None,
+            timezone: None,
+            warm_sessions: false,
         }
     }
 }
 
+/// Response of `update_app_settings`: the saved settings, plus the memory
+/// connection test when `validateMemoryOnSave` was set.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct AppSettingsUpdate {
+    #[serde(flatten)]
+    pub(crate) settings: AppSettings,
+    #[serde(
+        default,
+        rename = "memoryConnection",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) memory_connection: Option<MemoryConnectionReport>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        AppSettings, AutoMemorySettings, BackendMode, WorkspaceEntry, WorkspaceGroup,
-        WorkspaceKind, WorkspaceSettings,
+        AppSettings, AutoMemorySettings, BackendMode, ConnectionState, WorkspaceEntry,
+        WorkspaceGroup, WorkspaceKind, WorkspaceSettings,
     };
 
     #[test]
@@ -947,6 +1890,9 @@ mod tests {
                 include_git_status: false,
                 write_daily: true,
                 write_curated: true,
+                recall_enabled: false,
+                recall_limit: 5,
+                recall_max_bytes: 2000,
             }
         ));
         assert_eq!(settings.composer_editor_preset, "default");
@@ -959,6 +1905,35 @@ mod tests {
         assert!(!settings.composer_list_continuation);
         assert!(!settings.composer_code_block_copy_use_modifier);
         assert!(settings.workspace_groups.is_empty());
+        assert!(settings.protected_branches.is_empty());
+        assert!(settings.notification_rules.is_empty());
+        assert!(!settings.auto_archive.enabled);
+        assert_eq!(settings.auto_archive.inactive_days, 30);
+        assert!(settings.terminal_shell.is_none());
+        assert!(!settings.terminal_audit_log);
+        assert!(settings.timezone.is_none());
+        assert!(!settings.warm_sessions);
+        assert_eq!(settings.revert_undo_retention_days, 7);
+        assert_eq!(settings.keepalive.interval_secs, 60);
+        assert_eq!(settings.keepalive.failure_threshold, 3);
+        assert!(!settings.keepalive.auto_reconnect);
+    }
+
+    #[test]
+    fn connection_state_serializes_as_bool_or_string() {
+        for (state, json) in [
+            (ConnectionState::Connected, "true"),
+            (ConnectionState::Disconnected, "false"),
+            (ConnectionState::Unhealthy, "\"unhealthy\""),
+            (ConnectionState::Stale, "\"stale\""),
+        ] {
+            assert_eq!(serde_json::to_string(&state).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<ConnectionState>(json).unwrap(),
+                state
+            );
+        }
+        assert!(serde_json::from_str::<ConnectionState>("\"maybe\"").is_err());
     }
 
     #[test]
@@ -1011,5 +1986,24 @@ mod tests {
         assert!(settings.git_root.is_none());
         assert!(settings.purpose.is_none());
         assert!(settings.obsidian_root.is_none());
+        assert!(settings.obsidian_roots.is_empty());
+        assert!(settings.protected_branches.is_empty());
+    }
+
+    #[test]
+    fn obsidian_roots_fall_back_to_legacy_field() {
+        let legacy: WorkspaceSettings =
+            serde_json::from_str(r#"{"obsidianRoot":"/vaults/Personal"}"#).expect("settings");
+        assert_eq!(
+            legacy.effective_obsidian_roots(),
+            vec!["/vaults/Personal".to_string()]
+        );
+
+        let multi: WorkspaceSettings = serde_json::from_str(
+            r#"{"obsidianRoot":"/vaults/Personal","obsidianRoots":["/vaults/Personal","/vaults/Household"]}"#,
+        )
+        .expect("settings");
+        assert_eq!(multi.effective_obsidian_roots().len(), 2);
+        assert!(!WorkspaceSettings::default().has_obsidian_root());
     }
 }
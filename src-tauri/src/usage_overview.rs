@@ -0,0 +1,377 @@
+//! `usage_overview`: the account's rate limits next to local token usage and
+//! its estimated cost. Each part is optional; what could not be filled in is
+//! listed in `missing` instead of failing the call.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::client_timezone::ClientTimezone;
+use crate::local_usage_core::local_usage_snapshot_core;
+use crate::types::{LocalUsageDay, LocalUsageSnapshot, UsagePricing, WorkspaceEntry};
+
+/// Days scanned for the week totals, today included.
+const WEEK_DAYS: u32 = 7;
+
+#[derive(Debug, Clone)]
+struct CachedRateLimits {
+    rate_limits: Value,
+    fetched_at: i64,
+}
+
+static RATE_LIMITS: OnceLock<Mutex<HashMap<String, CachedRateLimits>>> = OnceLock::new();
+
+fn rate_limit_cache() -> std::sync::MutexGuard<'static, HashMap<String, CachedRateLimits>> {
+    RATE_LIMITS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+/// Caches the rate limits carried by an `account/rateLimits/read` response or
+/// an `account/rateLimits/updated` notification. Other messages are ignored.
+pub(crate) fn record_rate_limits(workspace_id: &str, message: &Value) {
+    let Some(rate_limits) = ["/result/rateLimits", "/result/rate_limits"]
+        .iter()
+        .chain(&["/params/rateLimits", "/params/rate_limits"])
+        .find_map(|pointer| message.pointer(pointer))
+        .filter(|value| value.is_object())
+    else {
+        return;
+    };
+    rate_limit_cache().insert(
+        workspace_id.to_string(),
+        CachedRateLimits {
+            rate_limits: rate_limits.clone(),
+            fetched_at: Utc::now().timestamp_millis(),
+        },
+    );
+}
+
+/// The workspace's cached rate limits, or the newest of any workspace.
+fn cached_rate_limits(workspace_id: Option<&str>) -> Option<(String, CachedRateLimits)> {
+    let cache = rate_limit_cache();
+    match workspace_id {
+        Some(id) => cache.get(id).map(|cached| (id.to_string(), cached.clone())),
+        None => cache
+            .iter()
+            .max_by_key(|(_, cached)| cached.fetched_at)
+            .map(|(id, cached)| (id.clone(), cached.clone())),
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RateLimitsOverview {
+    pub(crate) workspace_id: String,
+    /// As the app-server reported it.
+    pub(crate) snapshot: Value,
+    pub(crate) fetched_at: i64,
+    pub(crate) age_ms: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsagePeriod {
+    pub(crate) input_tokens: i64,
+    pub(crate) cached_input_tokens: i64,
+    pub(crate) output_tokens: i64,
+    pub(crate) total_tokens: i64,
+    /// USD; `None` without `usagePricing`.
+    pub(crate) estimated_cost: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceUsageShare {
+    pub(crate) workspace_id: String,
+    pub(crate) today: UsagePeriod,
+    pub(crate) week: UsagePeriod,
+    /// Share of all local tokens, `None` when there were none.
+    pub(crate) today_share_percent: Option<f64>,
+    pub(crate) week_share_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct MissingPart {
+    pub(crate) part: &'static str,
+    pub(crate) reason: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsageOverview {
+    pub(crate) generated_at: i64,
+    pub(crate) rate_limits: Option<RateLimitsOverview>,
+    pub(crate) today: Option<UsagePeriod>,
+    /// The last seven days, today included.
+    pub(crate) week: Option<UsagePeriod>,
+    pub(crate) workspace: Option<WorkspaceUsageShare>,
+    pub(crate) missing: Vec<MissingPart>,
+}
+
+/// Builds the overview. `session` is used to read rate limits when none are
+/// cached yet; without one, they are reported missing.
+pub(crate) async fn usage_overview(
+    workspace: Option<&WorkspaceEntry>,
+    session: Option<Arc<WorkspaceSession>>,
+    pricing: Option<UsagePricing>,
+) -> UsageOverview {
+    let workspace_id = workspace.map(|entry| entry.id.as_str());
+    let mut missing = Vec::new();
+    let mut rate_limits = cached_rate_limits(workspace_id);
+    if rate_limits.is_none() {
+        match session {
+            Some(session) => match session
+                .send_request("account/rateLimits/read", Value::Null)
+                .await
+            {
+                Ok(response) => {
+                    record_rate_limits(&session.entry.id, &response);
+                    rate_limits = cached_rate_limits(Some(&session.entry.id));
+                    if rate_limits.is_none() {
+                        missing.push(MissingPart {
+                            part: "rateLimits",
+                            reason: "The server did not report rate limits".to_string(),
+                        });
+                    }
+                }
+                Err(err) => missing.push(MissingPart {
+                    part: "rateLimits",
+                    reason: err,
+                }),
+            },
+            None => missing.push(MissingPart {
+                part: "rateLimits",
+                reason: "No connected session to read rate limits from".to_string(),
+            }),
+        }
+    }
+    let all = local_usage_snapshot_core(Some(WEEK_DAYS), None, ClientTimezone::Local).await;
+    let scoped = match workspace {
+        Some(entry) => Some((
+            entry.id.clone(),
+            local_usage_snapshot_core(
+                Some(WEEK_DAYS),
+                Some(entry.path.clone()),
+                ClientTimezone::Local,
+            )
+            .await,
+        )),
+        None => None,
+    };
+    build_overview(
+        rate_limits,
+        all,
+        scoped,
+        pricing.as_ref(),
+        missing,
+        Utc::now().timestamp_millis(),
+    )
+}
+
+fn build_overview(
+    rate_limits: Option<(String, CachedRateLimits)>,
+    all: Result<LocalUsageSnapshot, String>,
+    scoped: Option<(String, Result<LocalUsageSnapshot, String>)>,
+    pricing: Option<&UsagePricing>,
+    mut missing: Vec<MissingPart>,
+    now: i64,
+) -> UsageOverview {
+    if pricing.is_none() {
+        missing.push(MissingPart {
+            part: "pricing",
+            reason: "Set usagePricing in the app settings to estimate cost".to_string(),
+        });
+    }
+    let (today, week) = match all {
+        Ok(snapshot) => {
+            let (today, week) = periods(&snapshot, pricing);
+            (Some(today), Some(week))
+        }
+        Err(err) => {
+            missing.push(MissingPart {
+                part: "localUsage",
+                reason: err,
+            });
+            (None, None)
+        }
+    };
+    let workspace = scoped.and_then(|(workspace_id, snapshot)| match snapshot {
+        Ok(snapshot) => {
+            let (scoped_today, scoped_week) = periods(&snapshot, pricing);
+            Some(WorkspaceUsageShare {
+                workspace_id,
+                today_share_percent: share(&scoped_today, today.as_ref()),
+                week_share_percent: share(&scoped_week, week.as_ref()),
+                today: scoped_today,
+                week: scoped_week,
+            })
+        }
+        Err(err) => {
+            missing.push(MissingPart {
+                part: "workspaceUsage",
+                reason: err,
+            });
+            None
+        }
+    });
+    UsageOverview {
+        generated_at: now,
+        rate_limits: rate_limits.map(|(workspace_id, cached)| RateLimitsOverview {
+            workspace_id,
+            snapshot: cached.rate_limits,
+            fetched_at: cached.fetched_at,
+            age_ms: (now - cached.fetched_at).max(0),
+        }),
+        today,
+        week,
+        workspace,
+        missing,
+    }
+}
+
+/// Today (the snapshot's last day) and the whole snapshot.
+fn periods(
+    snapshot: &LocalUsageSnapshot,
+    pricing: Option<&UsagePricing>,
+) -> (UsagePeriod, UsagePeriod) {
+    let today = period(snapshot.days.last().into_iter(), pricing);
+    let week = period(snapshot.days.iter(), pricing);
+    (today, week)
+}
+
+fn period<'a>(
+    days: impl Iterator<Item = &'a LocalUsageDay>,
+    pricing: Option<&UsagePricing>,
+) -> UsagePeriod {
+    let mut period = days.fold(UsagePeriod::default(), |mut period, day| {
+        period.input_tokens += day.input_tokens;
+        period.cached_input_tokens += day.cached_input_tokens;
+        period.output_tokens += day.output_tokens;
+        period.total_tokens += day.total_tokens;
+        period
+    });
+    period.estimated_cost = pricing.map(|pricing| estimate_cost(&period, pricing));
+    period
+}
+
+/// Input tokens include the cached ones, which are billed at their own rate.
+fn estimate_cost(period: &UsagePeriod, pricing: &UsagePricing) -> f64 {
+    let uncached = (period.input_tokens - period.cached_input_tokens).max(0) as f64;
+    let cached_rate = pricing
+        .cached_input_per_million
+        .unwrap_or(pricing.input_per_million);
+    let cost = (uncached * pricing.input_per_million
+        + period.cached_input_tokens as f64 * cached_rate
+        + period.output_tokens as f64 * pricing.output_per_million)
+        / 1_000_000.0;
+    (cost * 10_000.0).round() / 10_000.0
+}
+
+fn share(part: &UsagePeriod, whole: Option<&UsagePeriod>) -> Option<f64> {
+    let whole = whole?.total_tokens;
+    (whole > 0).then(|| ((part.total_tokens as f64) / (whole as f64) * 1000.0).round() / 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_overview, cached_rate_limits, record_rate_limits, MissingPart};
+    use crate::types::{LocalUsageDay, LocalUsageSnapshot, LocalUsageTotals, UsagePricing};
+    use serde_json::json;
+
+    fn snapshot(days: &[(i64, i64, i64)]) -> LocalUsageSnapshot {
+        LocalUsageSnapshot {
+            updated_at: 0,
+            days: days
+                .iter()
+                .enumerate()
+                .map(|(index, (input, cached, output))| LocalUsageDay {
+                    day: format!("2026-10-{:02}", index + 1),
+                    input_tokens: *input,
+                    cached_input_tokens: *cached,
+                    output_tokens: *output,
+                    total_tokens: input + output,
+                    agent_time_ms: 0,
+                    agent_runs: 0,
+                })
+                .collect(),
+            totals: LocalUsageTotals {
+                last7_days_tokens: 0,
+                last30_days_tokens: 0,
+                average_daily_tokens: 0,
+                cache_hit_rate_percent: 0.0,
+                peak_day: None,
+                peak_day_tokens: 0,
+            },
+            top_models: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn records_rate_limits_from_responses_and_notifications() {
+        record_rate_limits(
+            "usage-ws-1",
+            &json!({ "id": 3, "result": { "rateLimits": { "primary": null } } }),
+        );
+        record_rate_limits(
+            "usage-ws-2",
+            &json!({ "method": "account/rateLimits/updated", "params": { "rateLimits": { "primary": { "usedPercent": 40 } } } }),
+        );
+        record_rate_limits(
+            "usage-ws-3",
+            &json!({ "method": "turn/started", "params": {} }),
+        );
+        let (_, cached) = cached_rate_limits(Some("usage-ws-2")).expect("cached");
+        assert_eq!(cached.rate_limits["primary"]["usedPercent"], 40);
+        assert!(cached_rate_limits(Some("usage-ws-1")).is_some());
+        assert!(cached_rate_limits(Some("usage-ws-3")).is_none());
+    }
+
+    #[test]
+    fn combines_usage_with_cost_and_flags_what_is_missing() {
+        let pricing = UsagePricing {
+            input_per_million: 1.0,
+            cached_input_per_million: Some(0.1),
+            output_per_million: 10.0,
+        };
+        let all = snapshot(&[(1_000_000, 0, 0), (2_000_000, 1_000_000, 500_000)]);
+        let scoped = snapshot(&[(0, 0, 0), (1_000_000, 1_000_000, 250_000)]);
+        let overview = build_overview(
+            None,
+            Ok(all),
+            Some(("ws".to_string(), Ok(scoped))),
+            Some(&pricing),
+            vec![MissingPart {
+                part: "rateLimits",
+                reason: "No connected session to read rate limits from".to_string(),
+            }],
+            1_000,
+        );
+        let today = overview.today.expect("today");
+        assert_eq!(today.total_tokens, 2_500_000);
+        // 1M uncached at $1, 1M cached at $0.10, 0.5M output at $10.
+        assert_eq!(today.estimated_cost, Some(6.1));
+        assert_eq!(overview.week.expect("week").estimated_cost, Some(7.1));
+        let workspace = overview.workspace.expect("workspace");
+        assert_eq!(workspace.today_share_percent, Some(50.0));
+        assert_eq!(workspace.week_share_percent, Some(35.7));
+        assert_eq!(overview.missing.len(), 1);
+
+        let degraded = build_overview(
+            None,
+            Err("sessions folder unreadable".to_string()),
+            None,
+            None,
+            Vec::new(),
+            1_000,
+        );
+        assert!(degraded.today.is_none());
+        let parts: Vec<&str> = degraded.missing.iter().map(|part| part.part).collect();
+        assert_eq!(parts, ["pricing", "localUsage"]);
+    }
+}
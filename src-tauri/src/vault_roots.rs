@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::types::VaultContribution;
+
+/// One Obsidian vault of a Life workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct VaultRoot {
+    /// Folder name of the vault, made unique within the workspace. Entity
+    /// names that exist in more than one vault are prefixed with it.
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+}
+
+/// Vaults to read for a workspace, in configured order. Without any
+/// configured roots the workspace folder itself is the vault.
+pub(crate) fn resolve_vault_roots(
+    workspace_path: &str,
+    obsidian_roots: &[String],
+) -> Vec<VaultRoot> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for root in obsidian_roots {
+        let trimmed = root.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let path = PathBuf::from(trimmed);
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from(workspace_path));
+    }
+
+    let mut used = HashSet::new();
+    paths
+        .into_iter()
+        .map(|path| {
+            let base = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| !name.is_empty())
+                .unwrap_or("vault")
+                .to_string();
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while !used.insert(name.clone()) {
+                name = format!("{base}-{suffix}");
+                suffix += 1;
+            }
+            VaultRoot { name, path }
+        })
+        .collect()
+}
+
+/// `Vault/Name`, used when `name` is not unique across vaults.
+pub(crate) fn qualified_name(vault: &str, name: &str) -> String {
+    format!("{vault}/{name}")
+}
+
+/// Names that appear in more than one vault, from `(vault, name)` pairs.
+pub(crate) fn names_in_several_vaults<'a>(
+    names: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> HashSet<String> {
+    let mut vaults_by_name: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (vault, name) in names {
+        vaults_by_name.entry(name).or_default().insert(vault);
+    }
+    vaults_by_name
+        .into_iter()
+        .filter(|(_, vaults)| vaults.len() > 1)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+/// The name to show for an entity: qualified only when another vault has an
+/// entity with the same name.
+pub(crate) fn display_name(vault: &str, name: &str, duplicates: &HashSet<String>) -> String {
+    if duplicates.contains(name) {
+        qualified_name(vault, name)
+    } else {
+        name.to_string()
+    }
+}
+
+pub(crate) fn vault_contribution(vault: &VaultRoot, counts: &[(&str, usize)]) -> VaultContribution {
+    VaultContribution {
+        vault: vault.name.clone(),
+        root: vault.path.to_string_lossy().to_string(),
+        counts: counts
+            .iter()
+            .map(|(key, count)| (key.to_string(), *count))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{display_name, names_in_several_vaults, resolve_vault_roots};
+
+    #[test]
+    fn resolves_roots_with_unique_names() {
+        let roots = resolve_vault_roots(
+            "/work/life",
+            &[
+                "/vaults/Personal".to_string(),
+                " ".to_string(),
+                "/shared/Personal".to_string(),
+                "/vaults/Personal".to_string(),
+            ],
+        );
+        let names: Vec<_> = roots.iter().map(|root| root.name.as_str()).collect();
+        assert_eq!(names, vec!["Personal", "Personal-2"]);
+
+        let fallback = resolve_vault_roots("/work/life", &[]);
+        assert_eq!(fallback.len(), 1);
+        assert_eq!(fallback[0].name, "life");
+    }
+
+    #[test]
+    fn only_names_shared_across_vaults_are_qualified() {
+        let duplicates = names_in_several_vaults([
+            ("Personal", "Oatmeal"),
+            ("Household", "Oatmeal"),
+            ("Household", "Rice"),
+            ("Household", "Rice"),
+        ]);
+        assert_eq!(
+            display_name("Household", "Oatmeal", &duplicates),
+            "Household/Oatmeal"
+        );
+        assert_eq!(display_name("Household", "Rice", &duplicates), "Rice");
+    }
+}
@@ -0,0 +1,262 @@
+//! Keeps app-server sessions running for workspaces with `keepWarm` while
+//! `AppSettings.warmSessions` is on, so starting a thread there skips the
+//! spawn. The daemon checks the pool every tick and respawns a session whose
+//! process exited, backing off per workspace when spawning keeps failing.
+//!
+//! Thread starts are timed here too. A start is cold when its session was
+//! spawned on demand shortly before and the spawn is counted in its latency;
+//! every other start, including the first on a prewarmed session, is warm.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::types::{AppSettings, WorkspaceEntry};
+
+/// How often the daemon checks that warm sessions are running.
+pub(crate) const WARM_POOL_TICK: Duration = Duration::from_secs(15);
+const FIRST_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// An on-demand spawn only counts against a thread start this soon after it.
+const COLD_START_WINDOW: Duration = Duration::from_secs(60);
+
+/// Workspaces the pool keeps warm; none while `warmSessions` is off.
+pub(crate) fn warm_workspace_ids<'a>(
+    settings: &AppSettings,
+    workspaces: impl IntoIterator<Item = &'a WorkspaceEntry>,
+) -> HashSet<String> {
+    if !settings.warm_sessions {
+        return HashSet::new();
+    }
+    workspaces
+        .into_iter()
+        .filter(|entry| entry.settings.keep_warm)
+        .map(|entry| entry.id.clone())
+        .collect()
+}
+
+/// When the pool may next try to spawn each workspace's session.
+#[derive(Debug, Default)]
+pub(crate) struct RespawnSchedule {
+    /// Next attempt and consecutive failures.
+    entries: HashMap<String, (Instant, u32)>,
+}
+
+impl RespawnSchedule {
+    /// Forgets workspaces outside `workspace_ids`.
+    pub(crate) fn retain(&mut self, workspace_ids: &HashSet<String>) {
+        self.entries.retain(|id, _| workspace_ids.contains(id));
+    }
+
+    pub(crate) fn is_due(&self, workspace_id: &str, now: Instant) -> bool {
+        self.entries
+            .get(workspace_id)
+            .is_none_or(|(next, _)| *next <= now)
+    }
+
+    pub(crate) fn succeeded(&mut self, workspace_id: &str) {
+        self.entries.remove(workspace_id);
+    }
+
+    /// Waits `FIRST_BACKOFF`, doubling for each failure in a row up to
+    /// `MAX_BACKOFF`.
+    pub(crate) fn failed(&mut self, workspace_id: &str, now: Instant) {
+        let failures = self
+            .entries
+            .get(workspace_id)
+            .map_or(0, |(_, failures)| *failures)
+            + 1;
+        let backoff = FIRST_BACKOFF
+            .saturating_mul(1 << (failures - 1).min(10))
+            .min(MAX_BACKOFF);
+        self.entries
+            .insert(workspace_id.to_string(), (now + backoff, failures));
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StartLatency {
+    pub(crate) count: u64,
+    pub(crate) average_ms: u64,
+    pub(crate) max_ms: u64,
+    pub(crate) last_ms: Option<u64>,
+    #[serde(skip)]
+    total_ms: u64,
+}
+
+impl StartLatency {
+    fn record(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        self.count += 1;
+        self.total_ms += ms;
+        self.average_ms = self.total_ms / self.count;
+        self.max_ms = self.max_ms.max(ms);
+        self.last_ms = Some(ms);
+    }
+}
+
+/// `thread/start` latency since the backend started, split by whether the
+/// start waited for a spawn.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub(crate) struct ThreadStartMetrics {
+    pub(crate) cold: StartLatency,
+    pub(crate) warm: StartLatency,
+}
+
+#[derive(Debug, Default)]
+struct SessionOrigins {
+    metrics: ThreadStartMetrics,
+    /// On-demand spawns not yet charged to a thread start: how long the
+    /// spawn took and when it finished.
+    pending_spawns: HashMap<String, (Duration, Instant)>,
+    /// Workspaces whose current session the pool spawned.
+    prewarmed: HashSet<String>,
+}
+
+impl SessionOrigins {
+    fn record_spawn(&mut self, workspace_id: &str, elapsed: Duration, now: Instant) {
+        self.prewarmed.remove(workspace_id);
+        self.pending_spawns
+            .insert(workspace_id.to_string(), (elapsed, now));
+    }
+
+    fn mark_prewarmed(&mut self, workspace_id: &str) {
+        self.pending_spawns.remove(workspace_id);
+        self.prewarmed.insert(workspace_id.to_string());
+    }
+
+    fn forget(&mut self, workspace_id: &str) {
+        self.pending_spawns.remove(workspace_id);
+        self.prewarmed.remove(workspace_id);
+    }
+
+    fn record_thread_start(&mut self, workspace_id: &str, elapsed: Duration, now: Instant) {
+        match self.pending_spawns.remove(workspace_id) {
+            Some((spawn, spawned_at)) if now.duration_since(spawned_at) <= COLD_START_WINDOW => {
+                self.metrics.cold.record(spawn + elapsed)
+            }
+            _ => self.metrics.warm.record(elapsed),
+        }
+    }
+}
+
+static ORIGINS: OnceLock<Mutex<SessionOrigins>> = OnceLock::new();
+
+fn origins() -> MutexGuard<'static, SessionOrigins> {
+    ORIGINS
+        .get_or_init(|| Mutex::new(SessionOrigins::default()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+/// A session was spawned on demand, taking `elapsed`.
+pub(crate) fn record_spawn(workspace_id: &str, elapsed: Duration) {
+    origins().record_spawn(workspace_id, elapsed, Instant::now());
+}
+
+/// The session just spawned for `workspace_id` was the pool's doing.
+pub(crate) fn mark_prewarmed(workspace_id: &str) {
+    origins().mark_prewarmed(workspace_id);
+}
+
+/// The workspace's session was stopped.
+pub(crate) fn forget_session(workspace_id: &str) {
+    origins().forget(workspace_id);
+}
+
+pub(crate) fn is_prewarmed(workspace_id: &str) -> bool {
+    origins().prewarmed.contains(workspace_id)
+}
+
+/// A `thread/start` on `workspace_id` answered after `elapsed`.
+pub(crate) fn record_thread_start(workspace_id: &str, elapsed: Duration) {
+    origins().record_thread_start(workspace_id, elapsed, Instant::now());
+}
+
+pub(crate) fn thread_start_metrics() -> ThreadStartMetrics {
+    origins().metrics.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        warm_workspace_ids, RespawnSchedule, SessionOrigins, COLD_START_WINDOW, FIRST_BACKOFF,
+        MAX_BACKOFF,
+    };
+    use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::collections::HashSet;
+    use std::time::{Duration, Instant};
+
+    fn workspace(id: &str, keep_warm: bool) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: String::new(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                keep_warm,
+                ..WorkspaceSettings::default()
+            },
+        }
+    }
+
+    #[test]
+    fn only_flagged_workspaces_are_warm_while_enabled() {
+        let workspaces = [workspace("a", true), workspace("b", false)];
+        let mut settings = AppSettings::default();
+        assert!(warm_workspace_ids(&settings, &workspaces).is_empty());
+        settings.warm_sessions = true;
+        assert_eq!(
+            warm_workspace_ids(&settings, &workspaces),
+            HashSet::from(["a".to_string()])
+        );
+    }
+
+    #[test]
+    fn failed_spawns_back_off_until_one_succeeds() {
+        let mut schedule = RespawnSchedule::default();
+        let now = Instant::now();
+        assert!(schedule.is_due("ws", now));
+        schedule.failed("ws", now);
+        assert!(!schedule.is_due("ws", now + FIRST_BACKOFF / 2));
+        assert!(schedule.is_due("ws", now + FIRST_BACKOFF));
+        schedule.failed("ws", now);
+        assert!(!schedule.is_due("ws", now + FIRST_BACKOFF));
+        for _ in 0..20 {
+            schedule.failed("ws", now);
+        }
+        assert!(schedule.is_due("ws", now + MAX_BACKOFF));
+        schedule.succeeded("ws");
+        assert!(schedule.is_due("ws", now));
+    }
+
+    #[test]
+    fn on_demand_spawns_make_the_next_start_cold() {
+        let mut origins = SessionOrigins::default();
+        let now = Instant::now();
+        let ms = Duration::from_millis;
+
+        origins.record_spawn("cold", ms(2000), now);
+        origins.record_thread_start("cold", ms(100), now);
+        origins.record_thread_start("cold", ms(80), now);
+        origins.record_spawn("pool", ms(2500), now);
+        origins.mark_prewarmed("pool");
+        origins.record_thread_start("pool", ms(120), now);
+        origins.record_spawn("stale", ms(3000), now);
+        origins.record_thread_start("stale", ms(90), now + COLD_START_WINDOW * 2);
+
+        assert_eq!(origins.metrics.cold.count, 1);
+        assert_eq!(origins.metrics.cold.last_ms, Some(2100));
+        assert_eq!(origins.metrics.warm.count, 3);
+        assert_eq!(origins.metrics.warm.max_ms, 120);
+        assert_eq!(origins.metrics.warm.average_ms, 96);
+        assert!(origins.prewarmed.contains("pool"));
+        origins.forget("pool");
+        assert!(!origins.prewarmed.contains("pool"));
+    }
+}
@@ -0,0 +1,397 @@
+//! Workouts kept as notes under `Entities/Behaviors/Workouts`, with
+//! `date`, `type`, `duration_min`, `distance_km` and `calories` frontmatter.
+//! Both the exercise dashboard and the food/exercise trends merge them with
+//! the workouts found in stream text, so the same session logged both ways
+//! counts once.
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter::FrontmatterNote;
+
+/// Count key on a vault's contribution for workout notes that could not be
+/// read fully.
+pub(crate) const MALFORMED_WORKOUTS_KEY: &str = "malformedWorkouts";
+/// A stream entry and a workout note on the same day and of the same type are
+/// the same session when their durations differ by at most this much, or when
+/// either has no duration.
+const DURATION_TOLERANCE_MIN: f64 = 15.0;
+
+const CARDIO_WORDS: &[&str] = &[
+    "run",
+    "jog",
+    "cycl",
+    "bike",
+    "ride",
+    "swim",
+    "row",
+    "cardio",
+    "hiit",
+    "elliptical",
+];
+
+/// Word prefixes (and emoji) behind each activity of `workout_activity`, in
+/// the order they are tried.
+const ACTIVITY_WORDS: &[(&str, &[&str])] = &[
+    ("walk", &["walk", "hike", "hiking", "🚶"]),
+    ("run", &["run", "jog", "🏃"]),
+    ("bike", &["bike", "biking", "cycl", "ride", "riding", "🚴"]),
+    (
+        "lift",
+        &[
+            "lift", "weight", "strength", "gym", "squat", "deadlift", "bench", "🏋",
+        ],
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WorkoutEntity {
+    pub(crate) title: String,
+    pub(crate) date: NaiveDate,
+    /// `walk`, `cardio`, `strength` or `other`, as for stream entries.
+    pub(crate) kind: String,
+    /// `walk`, `run`, `bike`, `lift` or `other`; see `workout_activity`.
+    pub(crate) activity: String,
+    pub(crate) duration_min: f64,
+    pub(crate) distance_km: f64,
+    pub(crate) calories: f64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct WorkoutLoad {
+    pub(crate) workouts: Vec<WorkoutEntity>,
+    /// Notes skipped for a missing or unreadable date or frontmatter, plus
+    /// notes kept with a non-numeric value read as zero.
+    pub(crate) malformed: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkoutFrontmatter {
+    title: Option<String>,
+    date: Option<Value>,
+    #[serde(rename = "type")]
+    workout_type: Option<String>,
+    duration_min: Option<Value>,
+    distance_km: Option<Value>,
+    calories: Option<Value>,
+}
+
+pub(crate) fn workouts_dir(root: &Path) -> PathBuf {
+    root.join("Entities").join("Behaviors").join("Workouts")
+}
+
+/// Reads every workout note under `root`. Missing numbers count as zero.
+pub(crate) fn load_workout_entities(
+    root: &Path,
+    read_date: &mut dyn FnMut(&str) -> Option<NaiveDate>,
+) -> WorkoutLoad {
+    let mut load = WorkoutLoad::default();
+    let Ok(entries) = std::fs::read_dir(workouts_dir(root)) else {
+        return load;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("md"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("");
+        if stem.starts_with('_') {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let parsed = FrontmatterNote::parse(&content)
+            .ok()
+            .flatten()
+            .and_then(|note| note.deserialize::<WorkoutFrontmatter>().ok());
+        let Some(parsed) = parsed else {
+            load.malformed += 1;
+            continue;
+        };
+        let date = parsed
+            .date
+            .as_ref()
+            .and_then(yaml_text)
+            .and_then(|text| read_date(&text));
+        let Some(date) = date else {
+            load.malformed += 1;
+            continue;
+        };
+        let mut bad_number = false;
+        let mut number = |value: &Option<Value>| match value {
+            None | Some(Value::Null) => 0.0,
+            Some(value) => yaml_number(value).unwrap_or_else(|| {
+                bad_number = true;
+                0.0
+            }),
+        };
+        let title = parsed
+            .title
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| stem.to_string());
+        let workout_type = parsed.workout_type.as_deref().unwrap_or("");
+        let activity = match workout_activity(workout_type) {
+            "other" => workout_activity(&title),
+            activity => activity,
+        };
+        let workout = WorkoutEntity {
+            title,
+            date,
+            kind: workout_kind(workout_type),
+            activity: activity.to_string(),
+            duration_min: number(&parsed.duration_min),
+            distance_km: number(&parsed.distance_km),
+            calories: number(&parsed.calories),
+        };
+        if bad_number {
+            load.malformed += 1;
+        }
+        load.workouts.push(workout);
+    }
+    load
+}
+
+fn yaml_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn yaml_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Maps a free-form workout type onto the stream categories.
+pub(crate) fn workout_kind(raw: &str) -> String {
+    let lower = raw.trim().to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| lower.contains(word));
+    let kind = if has(&["walk", "hike"]) {
+        "walk"
+    } else if has(CARDIO_WORDS) {
+        "cardio"
+    } else if has(&["strength", "lift", "weight", "gym", "workout"]) {
+        "strength"
+    } else {
+        "other"
+    };
+    kind.to_string()
+}
+
+/// The activity named in a stream line or workout type: `walk`, `run`,
+/// `bike`, `lift` or `other`. Words match by prefix and ignore case, so
+/// `Jogging` is a run but `brunch` is not.
+pub(crate) fn workout_activity(raw: &str) -> &'static str {
+    let lower = raw.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    ACTIVITY_WORDS
+        .iter()
+        .find(|(_, prefixes)| {
+            prefixes.iter().any(|prefix| {
+                if prefix.is_ascii() {
+                    words.iter().any(|word| word.starts_with(prefix))
+                } else {
+                    lower.contains(prefix)
+                }
+            })
+        })
+        .map_or("other", |(activity, _)| activity)
+}
+
+/// Minutes written in a stream line, such as `45 min`, `1.5 hr`, `30m`,
+/// `2 hours` or `1h 30m`; every amount found is added up. `None` when the
+/// line names no duration.
+pub(crate) fn stream_workout_minutes(text: &str) -> Option<f64> {
+    let lower = text.to_lowercase();
+    let mut total = None;
+    let mut rest = lower.as_str();
+    while let Some(start) = rest.find(|ch: char| ch.is_ascii_digit()) {
+        let after = &rest[start..];
+        let end = after
+            .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+            .unwrap_or(after.len());
+        let (number, tail) = after.split_at(end);
+        rest = tail;
+        let unit = tail.trim_start();
+        let unit = &unit[..unit
+            .find(|ch: char| !ch.is_alphabetic())
+            .unwrap_or(unit.len())];
+        let factor = match unit {
+            "m" | "min" | "mins" | "minute" | "minutes" => 1.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60.0,
+            _ => continue,
+        };
+        if let Ok(amount) = number.parse::<f64>() {
+            *total.get_or_insert(0.0) += amount * factor;
+        }
+    }
+    total
+}
+
+/// Drops the stream workouts that a workout note already covers. Each note
+/// absorbs at most one stream entry; `key` gives an entry's date, kind and
+/// duration in minutes.
+pub(crate) fn unmatched_stream_workouts<T>(
+    stream: Vec<T>,
+    entities: &[WorkoutEntity],
+    key: impl Fn(&T) -> (NaiveDate, String, Option<f64>),
+) -> Vec<T> {
+    let mut used = vec![false; entities.len()];
+    stream
+        .into_iter()
+        .filter(|item| {
+            let (date, kind, duration) = key(item);
+            let found = entities.iter().enumerate().position(|(index, entity)| {
+                !used[index]
+                    && entity.date == date
+                    && entity.kind == kind
+                    && durations_match(entity.duration_min, duration)
+            });
+            match found {
+                Some(index) => {
+                    used[index] = true;
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect()
+}
+
+fn durations_match(entity_minutes: f64, stream_minutes: Option<f64>) -> bool {
+    match stream_minutes {
+        Some(minutes) if minutes > 0.0 && entity_minutes > 0.0 => {
+            (minutes - entity_minutes).abs() <= DURATION_TOLERANCE_MIN
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        load_workout_entities, stream_workout_minutes, unmatched_stream_workouts, workout_activity,
+        workout_kind, workouts_dir,
+    };
+    use chrono::NaiveDate;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    #[test]
+    fn loads_workout_notes_and_counts_malformed_ones() {
+        let dir = tempdir().expect("tempdir");
+        let workouts = workouts_dir(dir.path());
+        fs::create_dir_all(&workouts).expect("workouts dir");
+        fs::write(
+            workouts.join("Morning Run.md"),
+            "---\ndate: 2026-03-02\ntype: Run\nduration_min: 32\ndistance_km: \"5.2\"\ncalories: 410\n---\n",
+        )
+        .expect("write run");
+        fs::write(
+            workouts.join("Lift.md"),
+            "---\ntitle: Push day\ndate: 2026-03-03\ntype: strength\ncalories: lots\n---\n",
+        )
+        .expect("write lift");
+        fs::write(workouts.join("No Date.md"), "---\ntype: walk\n---\n").expect("write");
+        fs::write(workouts.join("Plain.md"), "just text\n").expect("write");
+
+        let load = load_workout_entities(dir.path(), &mut |value| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+        });
+        assert_eq!(load.malformed, 3);
+        let titles: Vec<&str> = load.workouts.iter().map(|w| w.title.as_str()).collect();
+        assert_eq!(titles, vec!["Push day", "Morning Run"]);
+        let run = &load.workouts[1];
+        assert_eq!((run.kind.as_str(), run.distance_km), ("cardio", 5.2));
+        assert_eq!(run.activity, "run");
+        assert_eq!(load.workouts[0].activity, "lift");
+        assert_eq!(load.workouts[0].calories, 0.0);
+        assert_eq!(load.workouts[0].duration_min, 0.0);
+    }
+
+    #[test]
+    fn stream_entries_covered_by_a_note_are_dropped() {
+        let load = {
+            let dir = tempdir().expect("tempdir");
+            let workouts = workouts_dir(dir.path());
+            fs::create_dir_all(&workouts).expect("workouts dir");
+            fs::write(
+                workouts.join("Run.md"),
+                "---\ndate: 2026-03-02\ntype: run\nduration_min: 30\n---\n",
+            )
+            .expect("write");
+            load_workout_entities(dir.path(), &mut |value| {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+            })
+        };
+        let stream = vec![
+            (date(2), "cardio", Some(40.0)),
+            (date(2), "cardio", Some(35.0)),
+            (date(2), "walk", None),
+            (date(3), "cardio", None),
+        ];
+        let left = unmatched_stream_workouts(stream, &load.workouts, |(day, kind, minutes)| {
+            (*day, kind.to_string(), *minutes)
+        });
+        assert_eq!(
+            left,
+            vec![
+                (date(2), "cardio", Some(35.0)),
+                (date(2), "walk", None),
+                (date(3), "cardio", None),
+            ]
+        );
+        assert_eq!(workout_kind("Yoga"), "other");
+        assert_eq!(workout_kind("Evening hike"), "walk");
+    }
+
+    #[test]
+    fn stream_lines_give_duration_and_activity() {
+        let cases = [
+            ("Morning run 45 min", Some(45.0)),
+            ("Bike ride 1.5 hr", Some(90.0)),
+            ("Walked the dog 30m", Some(30.0)),
+            ("Gym 2 Hours", Some(120.0)),
+            ("Hike 1h 30m", Some(90.0)),
+            ("45mins of lifting", Some(45.0)),
+            ("Ran 5 miles", None),
+            ("🏋️ Workout", None),
+        ];
+        for (text, minutes) in cases {
+            assert_eq!(stream_workout_minutes(text), minutes, "{text}");
+        }
+
+        let activities = [
+            ("Evening WALK", "walk"),
+            ("Jogging by the river", "run"),
+            ("CYCLING class", "bike"),
+            ("Deadlifts and Bench", "lift"),
+            ("🏋️ Workout", "lift"),
+            ("Sunday brunch", "other"),
+            ("Yoga", "other"),
+        ];
+        for (text, activity) in activities {
+            assert_eq!(workout_activity(text), activity, "{text}");
+        }
+    }
+}
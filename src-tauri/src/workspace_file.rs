@@ -0,0 +1,142 @@
+//! `read_workspace_file`: a file inside a workspace, read one chunk at a time
+//! so large logs and generated files can be paged through.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_tail::resolve_workspace_file;
+
+/// Bytes read when the request gives no `length`.
+const DEFAULT_CHUNK_BYTES: u64 = 400_000;
+const MAX_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+/// Enough for any one UTF-8 character, so every chunk makes progress.
+const MIN_CHUNK_BYTES: u64 = 4;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct WorkspaceFileResponse {
+    pub(crate) content: String,
+    /// More of the file remains past `nextOffset`.
+    pub(crate) truncated: bool,
+    /// Where `content` starts. Moved past a character the requested offset
+    /// fell inside of, and clamped to `size`.
+    pub(crate) offset: u64,
+    /// Where the next chunk starts.
+    #[serde(rename = "nextOffset")]
+    pub(crate) next_offset: u64,
+    pub(crate) size: u64,
+}
+
+/// Reads up to `length` bytes from `offset`. The chunk ends before a character
+/// it would split; an offset at or past the end gives an empty chunk.
+pub(crate) fn read_workspace_file_chunk(
+    root: &Path,
+    relative_path: &str,
+    offset: u64,
+    length: Option<u64>,
+) -> Result<WorkspaceFileResponse, String> {
+    let path = resolve_workspace_file(root, relative_path)?;
+    let length = length
+        .unwrap_or(DEFAULT_CHUNK_BYTES)
+        .clamp(MIN_CHUNK_BYTES, MAX_CHUNK_BYTES);
+    let mut file = File::open(&path).map_err(|err| format!("Failed to open file: {err}"))?;
+    let size = file
+        .metadata()
+        .map_err(|err| format!("Failed to read file metadata: {err}"))?
+        .len();
+    let offset = offset.min(size);
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|err| format!("Failed to seek file: {err}"))?;
+    // Three extra bytes cover a character the offset fell inside of.
+    let mut buffer = Vec::new();
+    file.take(length + 3)
+        .read_to_end(&mut buffer)
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+
+    let skipped = if offset == 0 {
+        0
+    } else {
+        buffer
+            .iter()
+            .take(3)
+            .take_while(|byte| is_continuation(**byte))
+            .count()
+    };
+    let available = &buffer[skipped..];
+    let window = &available[..available.len().min(length as usize)];
+    let at_end = offset + (skipped + window.len()) as u64 >= size;
+    let content = decode_chunk(window, at_end)?;
+    let offset = offset + skipped as u64;
+    let next_offset = offset + content.len() as u64;
+    Ok(WorkspaceFileResponse {
+        content,
+        truncated: next_offset < size,
+        offset,
+        next_offset,
+        size,
+    })
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+/// Decodes a chunk, dropping a character cut off at its end unless the file
+/// ends there too.
+fn decode_chunk(window: &[u8], at_end: bool) -> Result<String, String> {
+    match std::str::from_utf8(window) {
+        Ok(text) => Ok(text.to_string()),
+        Err(err) if err.error_len().is_none() && !at_end => {
+            Ok(String::from_utf8_lossy(&window[..err.valid_up_to()]).into_owned())
+        }
+        Err(_) => Err("File is not valid UTF-8".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_workspace_file_chunk;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn chunks_back_off_instead_of_splitting_characters() {
+        let dir = tempdir().expect("tempdir");
+        // "é" is 2 bytes, "€" 3 and "🙂" 4, so 5-byte chunks land inside them.
+        let text = "abcd€é€xyz🙂";
+        fs::write(dir.path().join("log.txt"), text).unwrap();
+
+        let mut pages = Vec::new();
+        let mut offset = 0;
+        loop {
+            let chunk =
+                read_workspace_file_chunk(dir.path(), "log.txt", offset, Some(5)).expect("chunk");
+            assert_eq!(chunk.offset, offset);
+            assert_eq!(chunk.size, text.len() as u64);
+            assert!(chunk.content.len() <= 5);
+            pages.push(chunk.content.clone());
+            offset = chunk.next_offset;
+            if !chunk.truncated {
+                break;
+            }
+        }
+        assert_eq!(pages, ["abcd", "€é", "€xy", "z🙂"]);
+        assert_eq!(pages.concat(), text);
+
+        // An offset inside "€" starts at the next character.
+        let chunk = read_workspace_file_chunk(dir.path(), "log.txt", 5, Some(5)).expect("chunk");
+        assert_eq!((chunk.offset, chunk.content.as_str()), (7, "é€"));
+
+        let past_end =
+            read_workspace_file_chunk(dir.path(), "log.txt", 1_000, None).expect("past end");
+        assert_eq!(past_end.content, "");
+        assert_eq!(past_end.offset, text.len() as u64);
+        assert!(!past_end.truncated);
+
+        assert!(read_workspace_file_chunk(dir.path(), "../log.txt", 0, None).is_err());
+        fs::write(dir.path().join("binary.bin"), [b'a', 0xff, b'b']).unwrap();
+        assert!(read_workspace_file_chunk(dir.path(), "binary.bin", 0, None).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! A workspace's color and icon, shown next to its name in the sidebar and on
+//! secondary surfaces (activity feed, notifications). Values are normalized on
+//! save, and a worktree's color is derived here from its parent's so every
+//! client shows the same shade.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{WorkspaceEntry, WorkspaceSettings};
+
+/// An emoji can span several chars (skin tones, ZWJ sequences, flags).
+const MAX_EMOJI_CHARS: usize = 10;
+const MAX_ICON_NAME_CHARS: usize = 32;
+/// Worktree colors keep the parent's hue at this share of its saturation.
+const WORKTREE_SATURATION: f64 = 0.6;
+
+/// What an event needs to render a workspace without looking it up.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct WorkspaceIdentity {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) icon: Option<String>,
+}
+
+impl WorkspaceIdentity {
+    pub(crate) fn of(entry: &WorkspaceEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            name: entry.name.clone(),
+            color: entry.settings.color.clone(),
+            icon: entry.settings.icon.clone(),
+        }
+    }
+}
+
+/// Normalizes `color` to lowercase `#rrggbb` and trims `icon`; blank values
+/// clear them.
+pub(crate) fn normalize_identity(settings: &mut WorkspaceSettings) -> Result<(), String> {
+    settings.color = match settings.color.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(color) => Some(normalize_color(color)?),
+    };
+    settings.icon = match settings.icon.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(icon) => Some(validate_icon(icon)?),
+    };
+    Ok(())
+}
+
+fn normalize_color(color: &str) -> Result<String, String> {
+    let invalid = || format!("Invalid workspace color {color:?}: use #rgb or #rrggbb.");
+    let hex = color.strip_prefix('#').ok_or_else(invalid)?;
+    if !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    match hex.len() {
+        6 => Ok(format!("#{}", hex.to_ascii_lowercase())),
+        3 => Ok(format!(
+            "#{}",
+            hex.chars()
+                .flat_map(|ch| [ch, ch])
+                .collect::<String>()
+                .to_ascii_lowercase()
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+/// An emoji, or the name of a built-in icon such as `rocket` or `git-branch`.
+fn validate_icon(icon: &str) -> Result<String, String> {
+    if icon.is_ascii() {
+        let valid = icon.len() <= MAX_ICON_NAME_CHARS
+            && icon
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-');
+        return if valid {
+            Ok(icon.to_string())
+        } else {
+            Err(format!(
+                "Invalid workspace icon {icon:?}: use an emoji or a lowercase icon name of up to {MAX_ICON_NAME_CHARS} characters."
+            ))
+        };
+    }
+    if icon.chars().count() > MAX_EMOJI_CHARS || icon.chars().any(char::is_whitespace) {
+        return Err(format!(
+            "Invalid workspace icon {icon:?}: use a single emoji."
+        ));
+    }
+    Ok(icon.to_string())
+}
+
+/// Settings for a new worktree: its parent's icon, access lock, read-only
+/// mode and a muted version of its color.
+pub(crate) fn worktree_settings(parent: &WorkspaceSettings) -> WorkspaceSettings {
+    WorkspaceSettings {
+        color: parent.color.as_deref().and_then(worktree_color),
+        icon: parent.icon.clone(),
+        access_lock: parent.access_lock,
+        read_only: parent.read_only,
+        ..WorkspaceSettings::default()
+    }
+}
+
+fn worktree_color(color: &str) -> Option<String> {
+    let hex = normalize_color(color).ok()?;
+    let channel = |index: usize| {
+        u8::from_str_radix(&hex[index..index + 2], 16)
+            .ok()
+            .map(|value| f64::from(value) / 255.0)
+    };
+    let (hue, saturation, lightness) = rgb_to_hsl(channel(1)?, channel(3)?, channel(5)?);
+    let (r, g, b) = hsl_to_rgb(hue, saturation * WORKTREE_SATURATION, lightness);
+    let byte = |value: f64| (value * 255.0).round().clamp(0.0, 255.0) as u8;
+    Some(format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b)))
+}
+
+fn rgb_to_hsl(r: f64, g: f64, b: f64) -> (f64, f64, f64) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (f64, f64, f64) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = lightness - chroma / 2.0;
+    let (r, g, b) = match hue {
+        h if h < 60.0 => (chroma, x, 0.0),
+        h if h < 120.0 => (x, chroma, 0.0),
+        h if h < 180.0 => (0.0, chroma, x),
+        h if h < 240.0 => (0.0, x, chroma),
+        h if h < 300.0 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    (r + m, g + m, b + m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_identity, worktree_settings};
+    use crate::types::WorkspaceSettings;
+
+    fn settings(color: Option<&str>, icon: Option<&str>) -> WorkspaceSettings {
+        WorkspaceSettings {
+            color: color.map(str::to_string),
+            icon: icon.map(str::to_string),
+            ..WorkspaceSettings::default()
+        }
+    }
+
+    #[test]
+    fn normalizes_colors_and_icons_and_mutes_worktree_colors() {
+        let mut valid = settings(Some(" #3B8 "), Some("🚀"));
+        normalize_identity(&mut valid).expect("valid");
+        assert_eq!(valid.color.as_deref(), Some("#33bb88"));
+        assert_eq!(valid.icon.as_deref(), Some("🚀"));
+
+        let mut named = settings(Some(""), Some("git-branch"));
+        normalize_identity(&mut named).expect("named icon");
+        assert_eq!(named.color, None);
+
+        for (color, icon) in [
+            (Some("red"), None),
+            (Some("#12345"), None),
+            (None, Some("Rocket Ship")),
+            (None, Some("🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀")),
+        ] {
+            assert!(normalize_identity(&mut settings(color, icon)).is_err());
+        }
+
+        let worktree = worktree_settings(&settings(Some("#ff0000"), Some("🚀")));
+        assert_eq!(worktree.color.as_deref(), Some("#cc3333"));
+        assert_eq!(worktree.icon.as_deref(), Some("🚀"));
+        let gray = worktree_settings(&settings(Some("#808080"), None));
+        assert_eq!(gray.color.as_deref(), Some("#808080"));
+    }
+}
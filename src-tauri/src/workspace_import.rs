@@ -0,0 +1,293 @@
+//! Bulk import of the git repositories under a folder. The scan stops at each
+//! repository it finds, so submodules and vendored checkouts are not offered
+//! separately. Imported workspaces are registered disconnected; their
+//! app-server starts when the user connects them.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::backend::path_health::is_path_unavailable;
+use crate::git_utils::{has_git_marker, should_skip_dir};
+use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+pub(crate) const DEFAULT_SCAN_DEPTH: usize = 3;
+const MAX_SCAN_DEPTH: usize = 6;
+/// The scan stops after this many repositories and reports `truncated`.
+const MAX_CANDIDATES: usize = 500;
+
+/// Not worth descending into when looking for repositories, on top of what
+/// `list_git_roots` skips.
+fn should_skip_scan_dir(name: &str) -> bool {
+    should_skip_dir(name)
+        || matches!(
+            name,
+            ".venv" | "venv" | "vendor" | "__pycache__" | ".cache" | ".Trash"
+        )
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum WorkspaceImportStatus {
+    /// Would be added; only returned for a dry run.
+    Candidate,
+    Added,
+    SkippedDuplicate,
+    Error,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceImportResult {
+    pub(crate) path: String,
+    pub(crate) name: String,
+    pub(crate) status: WorkspaceImportStatus,
+    /// The new workspace, or the registered one for a duplicate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) workspace_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceImportReport {
+    pub(crate) dry_run: bool,
+    /// In scan order.
+    pub(crate) results: Vec<WorkspaceImportResult>,
+    /// The scan stopped at its limit of repositories.
+    pub(crate) truncated: bool,
+}
+
+impl WorkspaceImportReport {
+    pub(crate) fn added(&self) -> bool {
+        self.results
+            .iter()
+            .any(|result| result.status == WorkspaceImportStatus::Added)
+    }
+}
+
+/// The entry `add_workspace` registers for a folder.
+pub(crate) fn main_workspace_entry(
+    path: &str,
+    codex_bin: Option<String>,
+) -> Result<WorkspaceEntry, String> {
+    if !PathBuf::from(path).is_dir() {
+        return Err("Workspace path must be a folder.".to_string());
+    }
+    let name = PathBuf::from(path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Workspace")
+        .to_string();
+    Ok(WorkspaceEntry {
+        id: Uuid::new_v4().to_string(),
+        name,
+        path: path.to_string(),
+        codex_bin,
+        kind: WorkspaceKind::Main,
+        parent_id: None,
+        worktree: None,
+        settings: WorkspaceSettings::default(),
+    })
+}
+
+/// Git repositories under `root`, at most `max_depth` levels down, in walk
+/// order. `root` itself counts when it is a repository.
+pub(crate) fn scan_git_repositories(
+    root: &Path,
+    max_depth: Option<usize>,
+) -> Result<(Vec<PathBuf>, bool), String> {
+    if !root.is_dir() {
+        return Err(format!("Not a folder: {}", root.display()));
+    }
+    let max_depth = max_depth
+        .unwrap_or(DEFAULT_SCAN_DEPTH)
+        .clamp(1, MAX_SCAN_DEPTH);
+    let mut found: Vec<PathBuf> = Vec::new();
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .git_ignore(false)
+        .git_global(false)
+        .git_exclude(false)
+        .ignore(false)
+        .follow_links(false)
+        .max_depth(Some(max_depth))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if !entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                return false;
+            }
+            let name = entry.file_name().to_string_lossy();
+            if should_skip_scan_dir(&name) {
+                return false;
+            }
+            // Don't descend into a repository: its parent is one.
+            entry
+                .path()
+                .parent()
+                .is_none_or(|parent| !has_git_marker(parent))
+        })
+        .build();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|ft| ft.is_dir()) || !has_git_marker(entry.path()) {
+            continue;
+        }
+        if found.len() == MAX_CANDIDATES {
+            return Ok((found, true));
+        }
+        found.push(entry.into_path());
+    }
+    Ok((found, false))
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Workspace ids by canonical folder. Unreachable workspaces are keyed as
+/// stored; canonicalizing them could hang.
+fn registered_paths(workspaces: &HashMap<String, WorkspaceEntry>) -> HashMap<PathBuf, String> {
+    workspaces
+        .values()
+        .map(|entry| {
+            let path = Path::new(&entry.path);
+            let key = if is_path_unavailable(&entry.id) {
+                path.to_path_buf()
+            } else {
+                canonical_path(path)
+            };
+            (key, entry.id.clone())
+        })
+        .collect()
+}
+
+/// The workspace already registered for `path`, compared like an import.
+pub(crate) fn registered_workspace_id(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    path: &str,
+) -> Option<String> {
+    registered_paths(workspaces).remove(&canonical_path(Path::new(path)))
+}
+
+/// Registers each repository in `repos` that is not a workspace yet. Paths
+/// are compared canonically, so a repository reached through a symlink is
+/// still a duplicate. With `dry_run`, `workspaces` is left alone and the new
+/// ones come back as candidates.
+pub(crate) fn import_repositories(
+    workspaces: &mut HashMap<String, WorkspaceEntry>,
+    repos: Vec<PathBuf>,
+    dry_run: bool,
+    truncated: bool,
+) -> WorkspaceImportReport {
+    let mut registered = registered_paths(workspaces);
+    let mut seen = HashSet::new();
+    let mut results = Vec::with_capacity(repos.len());
+    for repo in repos {
+        let path = repo.to_string_lossy().to_string();
+        let key = canonical_path(&repo);
+        let name = repo
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Workspace")
+            .to_string();
+        let mut result = WorkspaceImportResult {
+            path: path.clone(),
+            name,
+            status: WorkspaceImportStatus::SkippedDuplicate,
+            workspace_id: registered.get(&key).cloned(),
+            error: None,
+        };
+        if result.workspace_id.is_some() || !seen.insert(key.clone()) {
+            results.push(result);
+            continue;
+        }
+        if dry_run {
+            result.status = WorkspaceImportStatus::Candidate;
+            results.push(result);
+            continue;
+        }
+        match main_workspace_entry(&path, None) {
+            Ok(entry) => {
+                result.status = WorkspaceImportStatus::Added;
+                result.workspace_id = Some(entry.id.clone());
+                registered.insert(key, entry.id.clone());
+                workspaces.insert(entry.id.clone(), entry);
+            }
+            Err(error) => {
+                result.status = WorkspaceImportStatus::Error;
+                result.error = Some(error);
+            }
+        }
+        results.push(result);
+    }
+    WorkspaceImportReport {
+        dry_run,
+        results,
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import_repositories, scan_git_repositories, WorkspaceImportStatus};
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    #[test]
+    fn scans_repositories_and_skips_registered_ones() {
+        let dir = tempdir().expect("tempdir");
+        let root = dir.path();
+        for repo in ["alpha", "group/beta", "group/beta/vendored", "gamma"] {
+            std::fs::create_dir_all(root.join(repo).join(".git")).unwrap();
+        }
+        std::fs::create_dir_all(root.join("node_modules/pkg/.git")).unwrap();
+        std::fs::create_dir_all(root.join("deep/a/b/c/.git")).unwrap();
+
+        let (repos, truncated) = scan_git_repositories(root, None).expect("scan");
+        assert!(!truncated);
+        let relative: Vec<String> = repos
+            .iter()
+            .map(|repo| {
+                repo.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        assert_eq!(relative, vec!["alpha", "gamma", "group/beta"]);
+
+        let mut workspaces = HashMap::new();
+        let first = import_repositories(&mut workspaces, repos.clone(), false, false);
+        assert!(first.added());
+        assert_eq!(workspaces.len(), 3);
+
+        let mut with_extra = repos.clone();
+        with_extra.push(root.join("group/../alpha"));
+        let dry = import_repositories(&mut workspaces, with_extra, true, false);
+        assert!(dry
+            .results
+            .iter()
+            .all(|result| result.status == WorkspaceImportStatus::SkippedDuplicate));
+        assert_eq!(dry.results.len(), 4);
+
+        let (deep, _) = scan_git_repositories(root, Some(5)).expect("deep scan");
+        let report = import_repositories(&mut workspaces, deep, true, false);
+        let candidates: Vec<&str> = report
+            .results
+            .iter()
+            .filter(|result| result.status == WorkspaceImportStatus::Candidate)
+            .map(|result| result.name.as_str())
+            .collect();
+        assert_eq!(candidates, vec!["c"]);
+        assert_eq!(workspaces.len(), 3);
+
+        assert!(scan_git_repositories(&root.join("missing"), None).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! `search_workspace_files`: a literal text search over the files of a
+//! workspace, walked the way `list_workspace_files` walks them, with results
+//! grouped per file like ripgrep prints them.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::git_utils::should_skip_dir;
+use crate::utils::normalize_git_path;
+use crate::workspace_todos::read_text_file;
+
+const DEFAULT_MAX_RESULTS: usize = 200;
+const MAX_RESULTS_LIMIT: usize = 2000;
+const DEFAULT_CONTEXT_LINES: usize = 1;
+const CONTEXT_LINES_LIMIT: usize = 5;
+const MAX_LINE_CHARS: usize = 240;
+/// Wall-clock budget for one search; what was found by then is returned.
+pub(crate) const SEARCH_TIME_BUDGET: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceSearchOptions {
+    #[serde(default)]
+    pub(crate) case_sensitive: bool,
+    /// ripgrep-style globs such as `*.rs` or `src/**`; a leading `!`
+    /// excludes. Without any, every file is searched.
+    #[serde(default)]
+    pub(crate) globs: Vec<String>,
+    /// Lines before and after each match to return, at most 5. Defaults to 1.
+    #[serde(default)]
+    pub(crate) context_lines: Option<usize>,
+    /// Defaults to 200, at most 2000.
+    #[serde(default)]
+    pub(crate) max_results: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceSearchMatch {
+    /// One-based line number.
+    pub(crate) line: usize,
+    /// Character column of the match in the untrimmed line.
+    pub(crate) column: usize,
+    /// The matching line, trimmed.
+    pub(crate) text: String,
+    pub(crate) before: Vec<String>,
+    pub(crate) after: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceSearchFile {
+    pub(crate) path: String,
+    pub(crate) matches: Vec<WorkspaceSearchMatch>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceSearchResult {
+    pub(crate) files: Vec<WorkspaceSearchFile>,
+    /// Matches across all files.
+    pub(crate) total_matches: usize,
+    /// More matches exist past `maxResults`.
+    pub(crate) truncated: bool,
+    /// The search stopped at its time budget before walking every file.
+    pub(crate) timed_out: bool,
+}
+
+pub(crate) fn search_workspace_files(
+    root: &Path,
+    query: &str,
+    options: &WorkspaceSearchOptions,
+    budget: Duration,
+) -> Result<WorkspaceSearchResult, String> {
+    if query.is_empty() {
+        return Err("Query must not be empty".to_string());
+    }
+    let matcher = RegexBuilder::new(&regex::escape(query))
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in options.globs.iter().map(|glob| glob.trim()) {
+        if glob.is_empty() {
+            continue;
+        }
+        overrides
+            .add(glob)
+            .map_err(|err| format!("Invalid glob `{glob}`: {err}"))?;
+    }
+    let overrides = overrides.build().map_err(|err| err.to_string())?;
+    let max_results = options
+        .max_results
+        .unwrap_or(DEFAULT_MAX_RESULTS)
+        .clamp(1, MAX_RESULTS_LIMIT);
+    let context_lines = options
+        .context_lines
+        .unwrap_or(DEFAULT_CONTEXT_LINES)
+        .min(CONTEXT_LINES_LIMIT);
+
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .overrides(overrides)
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name);
+            }
+            true
+        })
+        .build();
+
+    let started = Instant::now();
+    let mut result = WorkspaceSearchResult {
+        files: Vec::new(),
+        total_matches: 0,
+        truncated: false,
+        timed_out: false,
+    };
+    'walk: for entry in walker {
+        if started.elapsed() >= budget {
+            result.timed_out = true;
+            break;
+        }
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        let Ok(rel_path) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let Some(content) = read_text_file(entry.path()) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let mut matches = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            let Some(found) = matcher.find(line) else {
+                continue;
+            };
+            if result.total_matches == max_results {
+                result.truncated = true;
+                push_file(&mut result, rel_path, matches);
+                break 'walk;
+            }
+            let context = |range: &[&str]| range.iter().copied().map(clip).collect();
+            matches.push(WorkspaceSearchMatch {
+                line: index + 1,
+                column: line[..found.start()].chars().count(),
+                text: clip(line.trim()),
+                before: context(&lines[index.saturating_sub(context_lines)..index]),
+                after: context(&lines[index + 1..(index + 1 + context_lines).min(lines.len())]),
+            });
+            result.total_matches += 1;
+        }
+        push_file(&mut result, rel_path, matches);
+    }
+    Ok(result)
+}
+
+fn push_file(
+    result: &mut WorkspaceSearchResult,
+    rel_path: &Path,
+    matches: Vec<WorkspaceSearchMatch>,
+) {
+    if matches.is_empty() {
+        return;
+    }
+    result.files.push(WorkspaceSearchFile {
+        path: normalize_git_path(&rel_path.to_string_lossy()),
+        matches,
+    });
+}
+
+fn clip(line: &str) -> String {
+    line.chars().take(MAX_LINE_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search_workspace_files, WorkspaceSearchOptions, SEARCH_TIME_BUDGET};
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn matched_lines(result: &super::WorkspaceSearchResult) -> Vec<(String, usize)> {
+        result
+            .files
+            .iter()
+            .flat_map(|file| {
+                file.matches
+                    .iter()
+                    .map(|item| (file.path.clone(), item.line))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn search_filters_by_glob_and_ignores_case_by_default() {
+        let dir = tempdir().expect("tempdir");
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/dep")).unwrap();
+        fs::write(
+            dir.path().join("src/main.rs"),
+            "fn main() {\n    let Needle = 1;\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.md"), "a needle here\n").unwrap();
+        fs::write(dir.path().join("node_modules/dep/index.js"), "needle\n").unwrap();
+        fs::write(dir.path().join("blob.bin"), b"needle\0\x01").unwrap();
+
+        let options = WorkspaceSearchOptions::default();
+        let result =
+            search_workspace_files(dir.path(), "needle", &options, SEARCH_TIME_BUDGET).unwrap();
+        assert_eq!(
+            matched_lines(&result),
+            vec![("notes.md".to_string(), 1), ("src/main.rs".to_string(), 2)]
+        );
+        let hit = &result.files[1].matches[0];
+        assert_eq!(hit.text, "let Needle = 1;");
+        assert_eq!(hit.column, 8);
+        assert_eq!(hit.before, vec!["fn main() {"]);
+        assert_eq!(hit.after, vec!["}"]);
+
+        let options = WorkspaceSearchOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let result =
+            search_workspace_files(dir.path(), "needle", &options, SEARCH_TIME_BUDGET).unwrap();
+        assert_eq!(matched_lines(&result), vec![("notes.md".to_string(), 1)]);
+
+        let options = WorkspaceSearchOptions {
+            globs: vec!["*.rs".to_string()],
+            ..Default::default()
+        };
+        let result =
+            search_workspace_files(dir.path(), "needle", &options, SEARCH_TIME_BUDGET).unwrap();
+        assert_eq!(matched_lines(&result), vec![("src/main.rs".to_string(), 2)]);
+
+        let options = WorkspaceSearchOptions {
+            globs: vec!["!src/**".to_string()],
+            ..Default::default()
+        };
+        let result =
+            search_workspace_files(dir.path(), "needle", &options, SEARCH_TIME_BUDGET).unwrap();
+        assert_eq!(matched_lines(&result), vec![("notes.md".to_string(), 1)]);
+    }
+
+    #[test]
+    fn search_stops_at_the_result_cap_and_the_time_budget() {
+        let dir = tempdir().expect("tempdir");
+        fs::write(dir.path().join("a.txt"), "hit\nhit\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "hit\n").unwrap();
+
+        let options = WorkspaceSearchOptions {
+            max_results: Some(2),
+            ..Default::default()
+        };
+        let result =
+            search_workspace_files(dir.path(), "hit", &options, SEARCH_TIME_BUDGET).unwrap();
+        assert_eq!(result.total_matches, 2);
+        assert!(result.truncated);
+        assert_eq!(
+            matched_lines(&result),
+            vec![("a.txt".to_string(), 1), ("a.txt".to_string(), 2)]
+        );
+
+        let result = search_workspace_files(
+            dir.path(),
+            "hit",
+            &WorkspaceSearchOptions::default(),
+            Duration::ZERO,
+        )
+        .unwrap();
+        assert!(result.timed_out);
+        assert!(result.files.is_empty());
+
+        assert!(search_workspace_files(
+            dir.path(),
+            "",
+            &WorkspaceSearchOptions::default(),
+            SEARCH_TIME_BUDGET
+        )
+        .is_err());
+    }
+}
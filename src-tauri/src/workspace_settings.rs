@@ -0,0 +1,143 @@
+//! Validation shared by the full and partial workspace settings updates, and
+//! the merge behind `update_workspace_settings_partial`.
+
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde_json::{json, Value};
+
+use crate::codex_params::validate_turn_extras;
+use crate::life::default_obsidian_root;
+use crate::types::{WorkspacePurpose, WorkspaceSettings};
+use crate::workspace_identity::normalize_identity;
+
+pub(crate) const UNKNOWN_SETTINGS_FIELDS_ERROR: &str = "unknown_settings_fields";
+
+/// Checks and fills in settings before they are stored: identity, turn extras,
+/// and the default vault of a Life workspace.
+pub(crate) fn normalize_workspace_settings(settings: &mut WorkspaceSettings) -> Result<(), String> {
+    normalize_identity(settings)?;
+    if let Some(extras) = settings.turn_extras.as_ref() {
+        validate_turn_extras(extras)?;
+    }
+    if matches!(settings.purpose, Some(WorkspacePurpose::Life)) && !settings.has_obsidian_root() {
+        settings.obsidian_root = default_obsidian_root();
+    }
+    Ok(())
+}
+
+/// Lays the fields present in `patch` over `current`. `null` clears an
+/// optional field. The result still needs `normalize_workspace_settings`.
+pub(crate) fn patch_workspace_settings(
+    current: &WorkspaceSettings,
+    patch: &Value,
+) -> Result<WorkspaceSettings, String> {
+    let Value::Object(patch) = patch else {
+        return Err("patch must be an object".to_string());
+    };
+    ensure_known_settings_fields::<WorkspaceSettings>(patch)?;
+    let mut merged = match serde_json::to_value(current).map_err(|err| err.to_string())? {
+        Value::Object(map) => map,
+        _ => return Err("workspace settings are not an object".to_string()),
+    };
+    for (key, value) in patch {
+        merged.insert(key.clone(), value.clone());
+    }
+    serde_json::from_value(Value::Object(merged)).map_err(|err| err.to_string())
+}
+
+/// Rejects a patch naming fields `T` does not have, with the
+/// `unknown_settings_fields` error both settings patch endpoints return.
+pub(crate) fn ensure_known_settings_fields<T: DeserializeOwned>(
+    patch: &serde_json::Map<String, Value>,
+) -> Result<(), String> {
+    let fields = settings_fields::<T>();
+    let mut unknown: Vec<&str> = patch
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !fields.contains(key))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort_unstable();
+    Err(format!(
+        "{UNKNOWN_SETTINGS_FIELDS_ERROR}: {}",
+        json!({ "unknownFields": unknown })
+    ))
+}
+
+/// The names `T` deserializes, as its derive reports them to
+/// `deserialize_struct`. Unlike the keys of the serialized value, this
+/// includes optional fields that are unset and skipped when serializing.
+fn settings_fields<T: DeserializeOwned>() -> &'static [&'static str] {
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("only structs report their fields"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("fields recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_workspace_settings, patch_workspace_settings};
+    use crate::types::WorkspaceSettings;
+    use serde_json::json;
+
+    #[test]
+    fn patch_keeps_fields_it_does_not_name() {
+        let current = WorkspaceSettings {
+            sort_order: Some(2),
+            domain_id: Some("writing".to_string()),
+            ..WorkspaceSettings::default()
+        };
+
+        let sorted =
+            patch_workspace_settings(&current, &json!({ "sortOrder": 5 })).expect("sort patch");
+        assert_eq!(sorted.sort_order, Some(5));
+        assert_eq!(sorted.domain_id.as_deref(), Some("writing"));
+
+        let mut cleared = patch_workspace_settings(
+            &sorted,
+            &json!({ "domainId": null, "accessLock": "read-only", "color": "#ABC" }),
+        )
+        .expect("clear patch");
+        normalize_workspace_settings(&mut cleared).expect("normalize");
+        assert_eq!(cleared.sort_order, Some(5));
+        assert_eq!(cleared.domain_id, None);
+        assert_eq!(cleared.color.as_deref(), Some("#aabbcc"));
+
+        assert_eq!(
+            patch_workspace_settings(&current, &json!({ "sort_order": 1, "bogus": true }))
+                .unwrap_err(),
+            r#"unknown_settings_fields: {"unknownFields":["bogus","sort_order"]}"#
+        );
+        assert!(
+            patch_workspace_settings(&current, &json!({ "accessLock": "full-access" }))
+                .unwrap_err()
+                .contains("unknown variant")
+        );
+    }
+}
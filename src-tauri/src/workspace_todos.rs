@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+
+use git2::{BlameOptions, Repository, StatusOptions};
+use ignore::WalkBuilder;
+
+use crate::git_utils::should_skip_dir;
+use crate::types::WorkspaceTodo;
+use crate::utils::normalize_git_path;
+
+pub(crate) const DEFAULT_TODO_MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+pub(crate) const DEFAULT_TODO_LIMIT: usize = 500;
+const MAX_SCAN_FILE_BYTES: u64 = 1_000_000;
+const BINARY_SNIFF_BYTES: usize = 8_000;
+const MAX_TODO_TEXT_CHARS: usize = 240;
+
+struct TodoCacheEntry {
+    fingerprint: String,
+    items: Vec<WorkspaceTodo>,
+}
+
+static TODO_CACHE: OnceLock<Mutex<HashMap<String, TodoCacheEntry>>> = OnceLock::new();
+
+pub(crate) fn normalize_todo_markers(markers: Option<Vec<String>>) -> Vec<String> {
+    let mut markers: Vec<String> = markers
+        .unwrap_or_default()
+        .into_iter()
+        .map(|marker| marker.trim().to_string())
+        .filter(|marker| !marker.is_empty())
+        .collect();
+    if markers.is_empty() {
+        markers = DEFAULT_TODO_MARKERS
+            .iter()
+            .map(|marker| marker.to_string())
+            .collect();
+    }
+    markers.sort();
+    markers.dedup();
+    markers
+}
+
+pub(crate) fn scan_workspace_todos(
+    root: &Path,
+    markers: &[String],
+    limit: usize,
+    with_authors: bool,
+) -> Vec<WorkspaceTodo> {
+    let cache_key = format!(
+        "{}|{}|{limit}|{with_authors}",
+        root.display(),
+        markers.join(",")
+    );
+    let repo = Repository::discover(root).ok();
+    let fingerprint = repo.as_ref().and_then(repo_fingerprint);
+    let cache = TODO_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(fingerprint) = fingerprint.as_ref() {
+        let cache = cache.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(entry) = cache.get(&cache_key) {
+            if &entry.fingerprint == fingerprint {
+                return entry.items.clone();
+            }
+        }
+    }
+
+    let mut items = collect_todos(root, markers, limit);
+    if with_authors {
+        if let Some(repo) = repo.as_ref() {
+            attach_authors(repo, root, &mut items);
+        }
+    }
+
+    if let Some(fingerprint) = fingerprint {
+        let mut cache = cache.lock().unwrap_or_else(|err| err.into_inner());
+        cache.insert(
+            cache_key,
+            TodoCacheEntry {
+                fingerprint,
+                items: items.clone(),
+            },
+        );
+    }
+    items
+}
+
+/// HEAD oid plus the mtime of every dirty file: cheap to compute and changes
+/// whenever a scan could return something different.
+fn repo_fingerprint(repo: &Repository) -> Option<String> {
+    let workdir = repo.workdir()?;
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.to_string())
+        .unwrap_or_default();
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo.statuses(Some(&mut options)).ok()?;
+    let mut dirty: Vec<String> = statuses
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path()?.to_string();
+            let mtime = std::fs::metadata(workdir.join(&path))
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_nanos())
+                .unwrap_or(0);
+            Some(format!("{path}:{mtime}"))
+        })
+        .collect();
+    dirty.sort();
+    Some(format!("{head}|{}", dirty.join("|")))
+}
+
+fn collect_todos(root: &Path, markers: &[String], limit: usize) -> Vec<WorkspaceTodo> {
+    let mut results = Vec::new();
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name);
+            }
+            true
+        })
+        .build();
+
+    for entry in walker {
+        if results.len() >= limit {
+            break;
+        }
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        let Ok(rel_path) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let rel_path = normalize_git_path(&rel_path.to_string_lossy());
+        let Some(content) = read_text_file(entry.path()) else {
+            continue;
+        };
+        for (index, line) in content.lines().enumerate() {
+            let Some(marker) = find_marker(line, markers) else {
+                continue;
+            };
+            results.push(WorkspaceTodo {
+                path: rel_path.clone(),
+                line: index + 1,
+                marker: marker.to_string(),
+                text: line.trim().chars().take(MAX_TODO_TEXT_CHARS).collect(),
+                author: None,
+            });
+            if results.len() >= limit {
+                break;
+            }
+        }
+    }
+
+    results.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    results
+}
+
+/// Reads a file of at most 1 MB, or `None` for larger and binary files.
+pub(crate) fn read_text_file(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let mut buffer = Vec::new();
+    file.take(MAX_SCAN_FILE_BYTES + 1)
+        .read_to_end(&mut buffer)
+        .ok()?;
+    if buffer.len() as u64 > MAX_SCAN_FILE_BYTES {
+        return None;
+    }
+    let sniff = &buffer[..buffer.len().min(BINARY_SNIFF_BYTES)];
+    if sniff.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+/// Finds the first marker that appears as a standalone word (`TODO:`,
+/// `FIXME(name)`), so identifiers like `TODOS` or `xxx_id` are not matched.
+pub(crate) fn find_marker<'a>(line: &str, markers: &'a [String]) -> Option<&'a str> {
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    markers
+        .iter()
+        .filter_map(|marker| {
+            line.match_indices(marker.as_str())
+                .find(|(start, _)| {
+                    let before = line[..*start].chars().next_back();
+                    let after = line[start + marker.len()..].chars().next();
+                    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+                })
+                .map(|(start, _)| (start, marker.as_str()))
+        })
+        .min_by_key(|(start, _)| *start)
+        .map(|(_, marker)| marker)
+}
+
+fn attach_authors(repo: &Repository, root: &Path, items: &mut [WorkspaceTodo]) {
+    let Some(workdir) = repo.workdir().and_then(|dir| dir.canonicalize().ok()) else {
+        return;
+    };
+    let Ok(root) = root.canonicalize() else {
+        return;
+    };
+    let mut index = 0;
+    while index < items.len() {
+        let path = items[index].path.clone();
+        let end = items[index..]
+            .iter()
+            .position(|item| item.path != path)
+            .map(|offset| index + offset)
+            .unwrap_or(items.len());
+        let blame = root
+            .join(&path)
+            .strip_prefix(&workdir)
+            .ok()
+            .and_then(|repo_path| {
+                let mut options = BlameOptions::new();
+                repo.blame_file(repo_path, Some(&mut options)).ok()
+            });
+        if let Some(blame) = blame {
+            for item in &mut items[index..end] {
+                item.author = blame
+                    .get_line(item.line)
+                    .and_then(|hunk| hunk.final_signature().name().map(|name| name.to_string()));
+            }
+        }
+        index = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_todos, find_marker, normalize_todo_markers};
+    use tempfile::tempdir;
+
+    #[test]
+    fn find_marker_requires_word_boundaries() {
+        let markers = normalize_todo_markers(None);
+        assert_eq!(find_marker("// TODO: fix this", &markers), Some("TODO"));
+        assert_eq!(find_marker("# FIXME(alex) later", &markers), Some("FIXME"));
+        assert_eq!(find_marker("let todos = TODOS;", &markers), None);
+        assert_eq!(find_marker("let xxx_id = 1;", &markers), None);
+    }
+
+    #[test]
+    fn find_marker_returns_earliest_match() {
+        let markers = normalize_todo_markers(None);
+        assert_eq!(find_marker("HACK around TODO", &markers), Some("HACK"));
+    }
+
+    #[test]
+    fn custom_markers_replace_defaults() {
+        let markers = normalize_todo_markers(Some(vec![" NOTE ".to_string(), String::new()]));
+        assert_eq!(markers, vec!["NOTE".to_string()]);
+    }
+
+    #[test]
+    fn collect_todos_skips_binary_files_and_sorts() {
+        let dir = tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("b.rs"), "fn main() {}\n// FIXME: b\n").unwrap();
+        std::fs::write(dir.path().join("a.rs"), "// TODO: a\n").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), b"TODO\0\x01").unwrap();
+        let markers = normalize_todo_markers(None);
+
+        let items = collect_todos(dir.path(), &markers, 10);
+        let summary: Vec<(String, usize, String)> = items
+            .iter()
+            .map(|item| (item.path.clone(), item.line, item.marker.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a.rs".to_string(), 1, "TODO".to_string()),
+                ("b.rs".to_string(), 2, "FIXME".to_string()),
+            ]
+        );
+
+        assert_eq!(collect_todos(dir.path(), &markers, 1).len(), 1);
+    }
+}
@@ -1,27 +1,58 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
 use std::path::PathBuf;
-use std::process::Stdio;
 
 use ignore::WalkBuilder;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
 use tauri::{AppHandle, Manager, State};
-use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 use uuid::Uuid;
 
-use crate::codex::spawn_workspace_session;
+use crate::backend::blocking::run_blocking;
+use crate::backend::keepalive::{connection_state, remove_stale_sessions};
+use crate::backend::path_health::{
+    check_workspace_paths, ensure_path_available, is_path_unavailable,
+};
+use crate::branch_protection::{effective_protected_branches, ensure_branch_not_protected};
+use crate::client_timezone::ClientTimezone;
+use crate::codex::{self, spawn_workspace_session};
 use crate::codex_args;
 use crate::codex_home::resolve_workspace_codex_home;
+use crate::event_sink::TauriEventSink;
+use crate::file_tail::{self, TailChunk};
 use crate::git_utils::resolve_git_root;
-use crate::life_core::default_obsidian_root;
+use crate::github_cli;
+use crate::obsidian::prewarm_domain_trends;
+use crate::onboarding::{
+    self, BootstrapOptions, BootstrapReport, OnboardingStatus, STEP_ADD_WORKSPACE,
+    STEP_CONNECT_WORKSPACE, STEP_STARTER_PROMPTS,
+};
+use crate::prompts;
+use crate::read_only::ensure_writable;
 use crate::remote_backend;
 use crate::state::AppState;
 use crate::storage::write_workspaces;
-use crate::types::{WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo};
+use crate::types::{
+    ConnectionState, QuickCommand, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
+    WorkspaceTodo, WorktreeComparison, WorktreeInfo, WorktreeReport,
+};
 use crate::utils::{git_env_path, normalize_git_path, resolve_git_binary};
+use crate::warm_pool;
+use crate::workspace_file::{read_workspace_file_chunk, WorkspaceFileResponse};
+use crate::workspace_import::{
+    import_repositories, main_workspace_entry, registered_workspace_id, scan_git_repositories,
+    WorkspaceImportReport,
+};
+use crate::workspace_search::{
+    self, WorkspaceSearchOptions, WorkspaceSearchResult, SEARCH_TIME_BUDGET,
+};
+use crate::workspace_settings::{normalize_workspace_settings, patch_workspace_settings};
+use crate::workspace_todos::{self, DEFAULT_TODO_LIMIT};
+use crate::worktree_apply;
+use crate::worktree_compare;
+use crate::worktree_guard::ensure_no_unpushed_commits;
+use crate::worktree_report;
+use crate::worktree_setup::{self, CreatedWorktree};
+use crate::worktree_upstream::{self, RenamedWorktree};
 
 fn should_skip_dir(name: &str) -> bool {
     matches!(
@@ -108,53 +139,12 @@ fn list_workspace_files_inner(root: &PathBuf, max_files: usize) -> Vec<String> {
     results
 }
 
-const MAX_WORKSPACE_FILE_BYTES: u64 = 400_000;
-
-#[derive(Serialize, Deserialize, Clone)]
-pub(crate) struct WorkspaceFileResponse {
-    content: String,
-    truncated: bool,
-}
-
-fn read_workspace_file_inner(
-    root: &PathBuf,
-    relative_path: &str,
-) -> Result<WorkspaceFileResponse, String> {
-    let canonical_root = root
-        .canonicalize()
-        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
-    let candidate = canonical_root.join(relative_path);
-    let canonical_path = candidate
-        .canonicalize()
-        .map_err(|err| format!("Failed to open file: {err}"))?;
-    if !canonical_path.starts_with(&canonical_root) {
-        return Err("Invalid file path".to_string());
-    }
-    let metadata = std::fs::metadata(&canonical_path)
-        .map_err(|err| format!("Failed to read file metadata: {err}"))?;
-    if !metadata.is_file() {
-        return Err("Path is not a file".to_string());
-    }
-
-    let file = File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
-    let mut buffer = Vec::new();
-    file.take(MAX_WORKSPACE_FILE_BYTES + 1)
-        .read_to_end(&mut buffer)
-        .map_err(|err| format!("Failed to read file: {err}"))?;
-
-    let truncated = buffer.len() > MAX_WORKSPACE_FILE_BYTES as usize;
-    if truncated {
-        buffer.truncate(MAX_WORKSPACE_FILE_BYTES as usize);
-    }
-
-    let content = String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8".to_string())?;
-    Ok(WorkspaceFileResponse { content, truncated })
-}
-
 #[tauri::command]
 pub(crate) async fn read_workspace_file(
     workspace_id: String,
     path: String,
+    offset: Option<u64>,
+    length: Option<u64>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<WorkspaceFileResponse, String> {
@@ -163,7 +153,12 @@ pub(crate) async fn read_workspace_file(
             &*state,
             app,
             "read_workspace_file",
-            json!({ "workspaceId": workspace_id, "path": path }),
+            json!({
+                "workspaceId": workspace_id,
+                "path": path,
+                "offset": offset,
+                "length": length,
+            }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
@@ -172,7 +167,189 @@ pub(crate) async fn read_workspace_file(
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
     let root = PathBuf::from(&entry.path);
-    read_workspace_file_inner(&root, &path)
+    drop(workspaces);
+    read_workspace_file_chunk(&root, &path, offset.unwrap_or(0), length)
+}
+
+async fn workspace_root(state: &AppState, workspace_id: &str) -> Result<PathBuf, String> {
+    let workspaces = state.workspaces.lock().await;
+    workspaces
+        .get(workspace_id)
+        .map(|entry| PathBuf::from(&entry.path))
+        .ok_or_else(|| "workspace not found".to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn tail_workspace_file(
+    workspace_id: String,
+    path: String,
+    from_offset: Option<u64>,
+    max_bytes: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TailChunk, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "tail_workspace_file",
+            json!({
+                "workspaceId": workspace_id,
+                "path": path,
+                "fromOffset": from_offset,
+                "maxBytes": max_bytes,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let root = workspace_root(&state, &workspace_id).await?;
+    tokio::task::spawn_blocking(move || {
+        file_tail::tail_workspace_file(&root, &path, from_offset.unwrap_or(0), max_bytes)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub(crate) async fn follow_file(
+    workspace_id: String,
+    path: String,
+    from_offset: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "follow_file",
+            json!({ "workspaceId": workspace_id, "path": path, "fromOffset": from_offset }),
+        )
+        .await;
+    }
+
+    let root = workspace_root(&state, &workspace_id).await?;
+    let offset = state
+        .file_followers
+        .follow(workspace_id, &root, path, from_offset)?;
+    Ok(json!({ "ok": true, "offset": offset }))
+}
+
+#[tauri::command]
+pub(crate) async fn unfollow_file(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "unfollow_file",
+            json!({ "workspaceId": workspace_id, "path": path }),
+        )
+        .await;
+    }
+
+    let removed = state.file_followers.unfollow(&workspace_id, &path);
+    Ok(json!({ "ok": true, "removed": removed }))
+}
+
+/// The workspace root, its quick commands and the Codex binary whose PATH they
+/// run with.
+async fn quick_command_context(
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<(PathBuf, Vec<QuickCommand>, Option<String>), String> {
+    let default_bin = state.app_settings.lock().await.codex_bin.clone();
+    let workspaces = state.workspaces.lock().await;
+    let entry = workspaces
+        .get(workspace_id)
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let codex_bin = entry
+        .codex_bin
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .or(default_bin);
+    Ok((
+        PathBuf::from(&entry.path),
+        entry.settings.quick_commands.clone(),
+        codex_bin,
+    ))
+}
+
+#[tauri::command]
+pub(crate) async fn list_quick_commands(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_quick_commands",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    let (root, configured, _) = quick_command_context(&state, &workspace_id).await?;
+    let commands = state
+        .quick_commands
+        .list(&workspace_id, &root, &configured)?;
+    serde_json::to_value(commands).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn run_quick_command(
+    workspace_id: String,
+    command_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "run_quick_command",
+            json!({ "workspaceId": workspace_id, "commandId": command_id }),
+        )
+        .await;
+    }
+
+    let (root, configured, codex_bin) = quick_command_context(&state, &workspace_id).await?;
+    let execution = state.quick_commands.run(
+        &workspace_id,
+        &root,
+        &configured,
+        &command_id,
+        codex_bin.as_deref(),
+    )?;
+    serde_json::to_value(execution).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn cancel_quick_command(
+    execution_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_quick_command",
+            json!({ "executionId": execution_id }),
+        )
+        .await;
+    }
+
+    state.quick_commands.cancel(&execution_id)?;
+    Ok(json!({ "ok": true }))
 }
 
 fn sort_workspaces(list: &mut Vec<WorkspaceInfo>) {
@@ -192,11 +369,7 @@ fn apply_workspace_settings_update(
     settings: WorkspaceSettings,
 ) -> Result<WorkspaceEntry, String> {
     let mut settings = settings;
-    if matches!(settings.purpose, Some(crate::types::WorkspacePurpose::Life))
-        && settings.obsidian_root.is_none()
-    {
-        settings.obsidian_root = default_obsidian_root();
-    }
+    normalize_workspace_settings(&mut settings)?;
 
     match workspaces.get_mut(id) {
         Some(entry) => {
@@ -238,60 +411,6 @@ fn is_missing_worktree_error(error: &str) -> bool {
     error.contains("is not a working tree")
 }
 
-async fn run_git_command_bytes(repo_path: &PathBuf, args: &[&str]) -> Result<Vec<u8>, String> {
-    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-    let output = Command::new(git_bin)
-        .args(args)
-        .current_dir(repo_path)
-        .env("PATH", git_env_path())
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-    if output.status.success() {
-        Ok(output.stdout)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            Err("Git command failed.".to_string())
-        } else {
-            Err(detail.to_string())
-        }
-    }
-}
-
-async fn run_git_diff(repo_path: &PathBuf, args: &[&str]) -> Result<Vec<u8>, String> {
-    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-    let output = Command::new(git_bin)
-        .args(args)
-        .current_dir(repo_path)
-        .env("PATH", git_env_path())
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-    if output.status.success() || output.status.code() == Some(1) {
-        Ok(output.stdout)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let detail = if stderr.trim().is_empty() {
-            stdout.trim()
-        } else {
-            stderr.trim()
-        };
-        if detail.is_empty() {
-            Err("Git command failed.".to_string())
-        } else {
-            Err(detail.to_string())
-        }
-    }
-}
-
 async fn git_branch_exists(repo_path: &PathBuf, branch: &str) -> Result<bool, String> {
     let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
     let status = Command::new(git_bin)
@@ -466,14 +585,6 @@ fn build_clone_destination_path(copies_folder: &PathBuf, copy_name: &str) -> Pat
     unique_worktree_path(copies_folder, &safe_name)
 }
 
-fn null_device_path() -> &'static str {
-    if cfg!(windows) {
-        "NUL"
-    } else {
-        "/dev/null"
-    }
-}
-
 #[tauri::command]
 pub(crate) async fn list_workspaces(
     state: State<'_, AppState>,
@@ -485,8 +596,16 @@ pub(crate) async fn list_workspaces(
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
+    let paths = state
+        .workspaces
+        .lock()
+        .await
+        .values()
+        .map(|entry| (entry.id.clone(), entry.path.clone()))
+        .collect();
+    let unavailable = check_workspace_paths(paths).await;
     let workspaces = state.workspaces.lock().await;
-    let sessions = state.sessions.lock().await;
+    let mut sessions = state.sessions.lock().await;
     let mut result = Vec::new();
     for entry in workspaces.values() {
         result.push(WorkspaceInfo {
@@ -494,13 +613,16 @@ pub(crate) async fn list_workspaces(
             name: entry.name.clone(),
             path: entry.path.clone(),
             codex_bin: entry.codex_bin.clone(),
-            connected: sessions.contains_key(&entry.id),
+            connected: connection_state(sessions.get(&entry.id)),
+            path_unavailable: unavailable.contains(&entry.id),
+            read_only: entry.settings.read_only,
             kind: entry.kind.clone(),
             parent_id: entry.parent_id.clone(),
             worktree: entry.worktree.clone(),
             settings: entry.settings.clone(),
         });
     }
+    remove_stale_sessions(&mut sessions, &TauriEventSink::new(app));
     sort_workspaces(&mut result);
     Ok(result)
 }
@@ -524,6 +646,42 @@ pub(crate) async fn is_workspace_path_dir(
     Ok(PathBuf::from(&path).is_dir())
 }
 
+/// Registers the git repositories under `root_path` without starting their
+/// sessions, writing `workspaces.json` once for the whole batch.
+#[tauri::command]
+pub(crate) async fn scan_and_import_workspaces(
+    root_path: String,
+    max_depth: Option<usize>,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceImportReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "scan_and_import_workspaces",
+            json!({ "rootPath": root_path, "maxDepth": max_depth, "dryRun": dry_run }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let root = PathBuf::from(&root_path);
+    let (repos, truncated) =
+        tokio::task::spawn_blocking(move || scan_git_repositories(&root, max_depth))
+            .await
+            .map_err(|err| err.to_string())??;
+    let mut workspaces = state.workspaces.lock().await;
+    let mut updated = workspaces.clone();
+    let report = import_repositories(&mut updated, repos, dry_run.unwrap_or(false), truncated);
+    if report.added() {
+        let list: Vec<_> = updated.values().cloned().collect();
+        write_workspaces(&state.storage_path, &list)?;
+        *workspaces = updated;
+    }
+    Ok(report)
+}
+
 #[tauri::command]
 pub(crate) async fn add_workspace(
     path: String,
@@ -542,25 +700,7 @@ pub(crate) async fn add_workspace(
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    if !PathBuf::from(&path).is_dir() {
-        return Err("Workspace path must be a folder.".to_string());
-    }
-
-    let name = PathBuf::from(&path)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("Workspace")
-        .to_string();
-    let entry = WorkspaceEntry {
-        id: Uuid::new_v4().to_string(),
-        name: name.clone(),
-        path: path.clone(),
-        codex_bin,
-        kind: WorkspaceKind::Main,
-        parent_id: None,
-        worktree: None,
-        settings: WorkspaceSettings::default(),
-    };
+    let entry = main_workspace_entry(&path, codex_bin)?;
 
     let default_bin = {
         let settings = state.app_settings.lock().await;
@@ -600,7 +740,9 @@ pub(crate) async fn add_workspace(
         name: entry.name,
         path: entry.path,
         codex_bin: entry.codex_bin,
-        connected: true,
+        connected: ConnectionState::Connected,
+        path_unavailable: false,
+        read_only: entry.settings.read_only,
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
@@ -695,6 +837,8 @@ pub(crate) async fn add_clone(
         worktree: None,
         settings: WorkspaceSettings {
             group_id: inherited_group_id,
+            color: source_entry.settings.color.clone(),
+            icon: source_entry.settings.icon.clone(),
             ..WorkspaceSettings::default()
         },
     };
@@ -751,7 +895,9 @@ pub(crate) async fn add_clone(
         name: entry.name,
         path: entry.path,
         codex_bin: entry.codex_bin,
-        connected: true,
+        connected: ConnectionState::Connected,
+        path_unavailable: false,
+        read_only: entry.settings.read_only,
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
@@ -759,19 +905,28 @@ pub(crate) async fn add_clone(
     })
 }
 
+/// Creates the worktree and starts its session. When the session fails, the
+/// worktree and any branch created for it are removed, unless
+/// `keep_on_spawn_failure` registers it disconnected instead.
 #[tauri::command]
 pub(crate) async fn add_worktree(
     parent_id: String,
     branch: String,
+    keep_on_spawn_failure: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<WorkspaceInfo, String> {
+    let keep_on_spawn_failure = keep_on_spawn_failure.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
             "add_worktree",
-            json!({ "parentId": parent_id, "branch": branch }),
+            json!({
+                "parentId": parent_id,
+                "branch": branch,
+                "keepOnSpawnFailure": keep_on_spawn_failure,
+            }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
@@ -806,32 +961,39 @@ pub(crate) async fn add_worktree(
     let worktree_path = unique_worktree_path(&worktree_root, &safe_name);
     let worktree_path_string = worktree_path.to_string_lossy().to_string();
 
-    let branch_exists = git_branch_exists(&PathBuf::from(&parent_entry.path), branch).await?;
+    let repo_path = PathBuf::from(&parent_entry.path);
+    let branch_exists = git_branch_exists(&repo_path, branch).await?;
     if branch_exists {
         run_git_command(
-            &PathBuf::from(&parent_entry.path),
+            &repo_path,
             &["worktree", "add", &worktree_path_string, branch],
         )
         .await?;
     } else {
         run_git_command(
-            &PathBuf::from(&parent_entry.path),
+            &repo_path,
             &["worktree", "add", "-b", branch, &worktree_path_string],
         )
         .await?;
     }
+    let created = CreatedWorktree {
+        repo_path: &repo_path,
+        worktree_path: &worktree_path_string,
+        branch,
+        created_branch: !branch_exists,
+    };
 
     let entry = WorkspaceEntry {
         id: Uuid::new_v4().to_string(),
         name: branch.to_string(),
-        path: worktree_path_string,
+        path: worktree_path_string.clone(),
         codex_bin: parent_entry.codex_bin.clone(),
         kind: WorkspaceKind::Worktree,
         parent_id: Some(parent_entry.id.clone()),
         worktree: Some(WorktreeInfo {
             branch: branch.to_string(),
         }),
-        settings: WorkspaceSettings::default(),
+        settings: crate::workspace_identity::worktree_settings(&parent_entry.settings),
     };
 
     let default_bin = {
@@ -843,13 +1005,32 @@ pub(crate) async fn add_worktree(
         let settings = state.app_settings.lock().await;
         codex_args::resolve_workspace_codex_args(&entry, Some(&parent_entry), Some(&settings))
     };
-    let session =
-        spawn_workspace_session(entry.clone(), default_bin, codex_args, codex_home, app).await?;
+    let session = match spawn_workspace_session(
+        entry.clone(),
+        default_bin,
+        codex_args,
+        codex_home,
+        app,
+    )
+    .await
     {
-        let mut workspaces = state.workspaces.lock().await;
-        workspaces.insert(entry.id.clone(), entry.clone());
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(&state.storage_path, &list)?;
+        Ok(session) => session,
+        Err(err) if keep_on_spawn_failure => {
+            if let Err(save_err) = register_workspace_entry(&state, &entry).await {
+                let cause = format!("{err}; saving the workspace also failed: {save_err}");
+                return Err(worktree_setup::roll_back_worktree(&created, &cause).await);
+            }
+            return Err(worktree_setup::kept_worktree_error(
+                &created, &entry.id, &err,
+            ));
+        }
+        Err(err) => return Err(worktree_setup::roll_back_worktree(&created, &err).await),
+    };
+    if let Err(err) = register_workspace_entry(&state, &entry).await {
+        let mut child = session.child.lock().await;
+        let _ = child.kill().await;
+        let cause = format!("saving the workspace failed: {err}");
+        return Err(worktree_setup::roll_back_worktree(&created, &cause).await);
     }
     state
         .sessions
@@ -862,7 +1043,9 @@ pub(crate) async fn add_worktree(
         name: entry.name,
         path: entry.path,
         codex_bin: entry.codex_bin,
-        connected: true,
+        connected: ConnectionState::Connected,
+        path_unavailable: false,
+        read_only: entry.settings.read_only,
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
@@ -870,14 +1053,40 @@ pub(crate) async fn add_worktree(
     })
 }
 
+/// Adds `entry` and writes `workspaces.json`; the entry is dropped again when
+/// the write fails.
+async fn register_workspace_entry(
+    state: &State<'_, AppState>,
+    entry: &WorkspaceEntry,
+) -> Result<(), String> {
+    let mut workspaces = state.workspaces.lock().await;
+    workspaces.insert(entry.id.clone(), entry.clone());
+    let list: Vec<_> = workspaces.values().cloned().collect();
+    let result = write_workspaces(&state.storage_path, &list);
+    if result.is_err() {
+        workspaces.remove(&entry.id);
+    }
+    result
+}
+
 #[tauri::command]
 pub(crate) async fn remove_workspace(
     id: String,
+    override_protection: Option<bool>,
+    force: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
+    let override_protection = override_protection.unwrap_or(false);
+    let force = force.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(&*state, app, "remove_workspace", json!({ "id": id })).await?;
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "remove_workspace",
+            json!({ "id": id, "overrideProtection": override_protection, "force": force }),
+        )
+        .await?;
         return Ok(());
     }
     let (entry, child_worktrees) = {
@@ -894,6 +1103,23 @@ pub(crate) async fn remove_workspace(
         (entry, children)
     };
 
+    {
+        let settings = state.app_settings.lock().await;
+        for child in &child_worktrees {
+            let patterns = effective_protected_branches(child, Some(&entry), &settings);
+            ensure_branch_not_protected(
+                child
+                    .worktree
+                    .as_ref()
+                    .map(|worktree| worktree.branch.as_str()),
+                &patterns,
+                override_protection,
+                "remove the worktree",
+            )?;
+        }
+    }
+    ensure_no_unpushed_commits(&entry, child_worktrees.clone(), force).await?;
+
     let parent_path = PathBuf::from(&entry.path);
     for child in &child_worktrees {
         if let Some(session) = state.sessions.lock().await.remove(&child.id) {
@@ -925,6 +1151,8 @@ pub(crate) async fn remove_workspace(
         let mut child = session.child.lock().await;
         let _ = child.kill().await;
     }
+    state.file_followers.unfollow_workspace(&id);
+    state.quick_commands.cancel_workspace(&id);
 
     {
         let mut workspaces = state.workspaces.lock().await;
@@ -942,11 +1170,21 @@ pub(crate) async fn remove_workspace(
 #[tauri::command]
 pub(crate) async fn remove_worktree(
     id: String,
+    override_protection: Option<bool>,
+    force: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
+    let override_protection = override_protection.unwrap_or(false);
+    let force = force.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(&*state, app, "remove_worktree", json!({ "id": id })).await?;
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "remove_worktree",
+            json!({ "id": id, "overrideProtection": override_protection, "force": force }),
+        )
+        .await?;
         return Ok(());
     }
     let (entry, parent) = {
@@ -963,6 +1201,21 @@ pub(crate) async fn remove_worktree(
         (entry, parent)
     };
 
+    {
+        let settings = state.app_settings.lock().await;
+        let patterns = effective_protected_branches(&entry, Some(&parent), &settings);
+        ensure_branch_not_protected(
+            entry
+                .worktree
+                .as_ref()
+                .map(|worktree| worktree.branch.as_str()),
+            &patterns,
+            override_protection,
+            "remove the worktree",
+        )?;
+    }
+    ensure_no_unpushed_commits(&parent, vec![entry.clone()], force).await?;
+
     if let Some(session) = state.sessions.lock().await.remove(&entry.id) {
         let mut child = session.child.lock().await;
         let _ = child.kill().await;
@@ -1000,39 +1253,139 @@ pub(crate) async fn remove_worktree(
 }
 
 #[tauri::command]
-pub(crate) async fn rename_worktree(
-    id: String,
-    branch: String,
+pub(crate) async fn worktree_report(
+    parent_workspace_id: String,
+    remove_safe: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> Result<WorktreeReport, String> {
+    let remove_safe = remove_safe.unwrap_or(false);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
             app,
-            "rename_worktree",
-            json!({ "id": id, "branch": branch }),
+            "worktree_report",
+            json!({ "parentWorkspaceId": parent_workspace_id, "removeSafe": remove_safe }),
         )
         .await?;
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
-
-    let trimmed = branch.trim();
-    if trimmed.is_empty() {
-        return Err("Branch name is required.".to_string());
-    }
-
-    let (entry, parent) = {
+    let (parent, children) = {
         let workspaces = state.workspaces.lock().await;
-        let entry = workspaces.get(&id).cloned().ok_or("workspace not found")?;
-        if !entry.kind.is_worktree() {
-            return Err("Not a worktree workspace.".to_string());
-        }
-        let parent_id = entry.parent_id.clone().ok_or("worktree parent not found")?;
         let parent = workspaces
-            .get(&parent_id)
+            .get(&parent_workspace_id)
             .cloned()
-            .ok_or("worktree parent not found")?;
+            .ok_or("workspace not found")?;
+        if parent.kind.is_worktree() {
+            return Err("worktree_report needs the parent workspace.".to_string());
+        }
+        let mut children = workspaces
+            .values()
+            .filter(|workspace| workspace.parent_id.as_deref() == Some(&parent_workspace_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        (parent, children)
+    };
+
+    let mut report = worktree_report::build_worktree_report(&parent, children).await?;
+    if remove_safe {
+        for worktree in &mut report.worktrees {
+            if worktree.suggested_action != worktree_report::ACTION_SAFE_TO_REMOVE {
+                continue;
+            }
+            match remove_worktree(
+                worktree.workspace_id.clone(),
+                None,
+                None,
+                state.clone(),
+                app.clone(),
+            )
+            .await
+            {
+                Ok(()) => worktree.removed = Some(true),
+                Err(error) => {
+                    worktree.removed = Some(false);
+                    worktree.remove_error = Some(error);
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub(crate) async fn compare_worktrees(
+    workspace_id_a: String,
+    workspace_id_b: String,
+    mode: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorktreeComparison, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "compare_worktrees",
+            json!({
+                "workspaceIdA": workspace_id_a,
+                "workspaceIdB": workspace_id_b,
+                "mode": mode,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let (a, b) = {
+        let workspaces = state.workspaces.lock().await;
+        let with_parent = |id: &str| -> Result<(WorkspaceEntry, Option<WorkspaceEntry>), String> {
+            let entry = workspaces.get(id).cloned().ok_or("workspace not found")?;
+            let parent = entry
+                .parent_id
+                .as_ref()
+                .and_then(|parent_id| workspaces.get(parent_id))
+                .cloned();
+            Ok((entry, parent))
+        };
+        (with_parent(&workspace_id_a)?, with_parent(&workspace_id_b)?)
+    };
+    worktree_compare::compare_worktrees(a, b, &mode).await
+}
+
+#[tauri::command]
+pub(crate) async fn rename_worktree(
+    id: String,
+    branch: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RenamedWorktree, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "rename_worktree",
+            json!({ "id": id, "branch": branch }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let trimmed = branch.trim();
+    if trimmed.is_empty() {
+        return Err("Branch name is required.".to_string());
+    }
+
+    let (entry, parent) = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces.get(&id).cloned().ok_or("workspace not found")?;
+        if !entry.kind.is_worktree() {
+            return Err("Not a worktree workspace.".to_string());
+        }
+        let parent_id = entry.parent_id.clone().ok_or("worktree parent not found")?;
+        let parent = workspaces
+            .get(&parent_id)
+            .cloned()
+            .ok_or("worktree parent not found")?;
         (entry, parent)
     };
 
@@ -1051,6 +1404,10 @@ pub(crate) async fn rename_worktree(
         return Err("Branch name is unchanged.".to_string());
     }
 
+    // Read before the rename so tracking can be checked against it afterwards.
+    let upstream = worktree_upstream::branch_upstream(&parent_root, &old_branch)
+        .ok()
+        .flatten();
     run_git_command(&parent_root, &["branch", "-m", &old_branch, &final_branch]).await?;
 
     let worktree_root = app
@@ -1102,6 +1459,13 @@ pub(crate) async fn rename_worktree(
         (snapshot, list)
     };
     write_workspaces(&state.storage_path, &list)?;
+    let upstream = worktree_upstream::carry_over_upstream(
+        &parent_root,
+        &entry_snapshot.id,
+        &old_branch,
+        &final_branch,
+        upstream,
+    );
 
     let was_connected = state.sessions.lock().await.contains_key(&entry_snapshot.id);
     if was_connected {
@@ -1147,17 +1511,23 @@ pub(crate) async fn rename_worktree(
         }
     }
 
-    let connected = state.sessions.lock().await.contains_key(&entry_snapshot.id);
-    Ok(WorkspaceInfo {
+    let connected = connection_state(state.sessions.lock().await.get(&entry_snapshot.id));
+    let workspace = WorkspaceInfo {
         id: entry_snapshot.id,
         name: entry_snapshot.name,
         path: entry_snapshot.path,
         codex_bin: entry_snapshot.codex_bin,
         connected,
+        path_unavailable: false,
+        read_only: entry_snapshot.settings.read_only,
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
         settings: entry_snapshot.settings,
+    };
+    Ok(RenamedWorktree {
+        workspace,
+        upstream,
     })
 }
 
@@ -1256,18 +1626,23 @@ pub(crate) async fn rename_worktree_upstream(
 #[tauri::command]
 pub(crate) async fn apply_worktree_changes(
     workspace_id: String,
+    dry_run: Option<bool>,
+    only_paths: Option<Vec<String>>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> Result<Value, String> {
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(
+        return remote_backend::call_remote(
             &*state,
             app,
             "apply_worktree_changes",
-            json!({ "workspaceId": workspace_id }),
+            json!({
+                "workspaceId": workspace_id,
+                "dryRun": dry_run,
+                "onlyPaths": only_paths,
+            }),
         )
-        .await?;
-        return Ok(());
+        .await;
     }
     let (entry, parent) = {
         let workspaces = state.workspaces.lock().await;
@@ -1289,106 +1664,19 @@ pub(crate) async fn apply_worktree_changes(
     let worktree_root = resolve_git_root(&entry)?;
     let parent_root = resolve_git_root(&parent)?;
 
-    let parent_status = run_git_command_bytes(&parent_root, &["status", "--porcelain"]).await?;
-    if !String::from_utf8_lossy(&parent_status).trim().is_empty() {
-        return Err(
-            "Your current branch has uncommitted changes. Please commit, stash, or discard them before applying worktree changes."
-                .to_string(),
-        );
-    }
-
-    let mut patch: Vec<u8> = Vec::new();
-    let staged_patch = run_git_diff(
-        &worktree_root,
-        &["diff", "--binary", "--no-color", "--cached"],
-    )
-    .await?;
-    patch.extend_from_slice(&staged_patch);
-    let unstaged_patch = run_git_diff(&worktree_root, &["diff", "--binary", "--no-color"]).await?;
-    patch.extend_from_slice(&unstaged_patch);
-
-    let untracked_output = run_git_command_bytes(
-        &worktree_root,
-        &["ls-files", "--others", "--exclude-standard", "-z"],
-    )
-    .await?;
-    for raw_path in untracked_output.split(|byte| *byte == 0) {
-        if raw_path.is_empty() {
-            continue;
-        }
-        let path = String::from_utf8_lossy(raw_path).to_string();
-        let diff = run_git_diff(
+    if dry_run.unwrap_or(false) {
+        let preview = worktree_apply::preview_worktree_changes(
             &worktree_root,
-            &[
-                "diff",
-                "--binary",
-                "--no-color",
-                "--no-index",
-                "--",
-                null_device_path(),
-                &path,
-            ],
+            &parent_root,
+            only_paths.as_deref(),
         )
         .await?;
-        patch.extend_from_slice(&diff);
-    }
-
-    if String::from_utf8_lossy(&patch).trim().is_empty() {
-        return Err("No changes to apply.".to_string());
-    }
-
-    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-    let mut child = Command::new(git_bin)
-        .args(["apply", "--3way", "--whitespace=nowarn", "-"])
-        .current_dir(&parent_root)
-        .env("PATH", git_env_path())
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin
-            .write_all(&patch)
-            .await
-            .map_err(|e| format!("Failed to write git apply input: {e}"))?;
-    }
-
-    let output = child
-        .wait_with_output()
-        .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
-
-    if output.status.success() {
-        return Ok(());
+        return serde_json::to_value(preview).map_err(|err| err.to_string());
     }
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let detail = if stderr.trim().is_empty() {
-        stdout.trim()
-    } else {
-        stderr.trim()
-    };
-    if detail.is_empty() {
-        return Err("Git apply failed.".to_string());
-    }
-
-    if detail.contains("Applied patch to") {
-        if detail.contains("with conflicts") {
-            return Err(
-                "Applied with conflicts. Resolve conflicts in the parent repo before retrying."
-                    .to_string(),
-            );
-        }
-        return Err(
-            "Patch applied partially. Resolve changes in the parent repo before retrying."
-                .to_string(),
-        );
-    }
-
-    Err(detail.to_string())
+    ensure_writable(&parent, "apply worktree changes")?;
+    worktree_apply::apply_worktree_changes(&worktree_root, &parent_root, only_paths.as_deref())
+        .await?;
+    Ok(json!({ "ok": true }))
 }
 
 #[tauri::command]
@@ -1416,13 +1704,60 @@ pub(crate) async fn update_workspace_settings(
     };
     write_workspaces(&state.storage_path, &list)?;
 
-    let connected = state.sessions.lock().await.contains_key(&id);
+    let connected = connection_state(state.sessions.lock().await.get(&id));
     Ok(WorkspaceInfo {
         id: entry_snapshot.id,
         name: entry_snapshot.name,
         path: entry_snapshot.path,
         codex_bin: entry_snapshot.codex_bin,
         connected,
+        path_unavailable: is_path_unavailable(&id),
+        read_only: entry_snapshot.settings.read_only,
+        kind: entry_snapshot.kind,
+        parent_id: entry_snapshot.parent_id,
+        worktree: entry_snapshot.worktree,
+        settings: entry_snapshot.settings,
+    })
+}
+
+/// Applies only the fields in `patch`, read and written under one lock so
+/// concurrent updates of other fields are kept.
+#[tauri::command]
+pub(crate) async fn update_workspace_settings_partial(
+    id: String,
+    patch: Value,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "update_workspace_settings_partial",
+            json!({ "id": id, "patch": patch }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let (entry_snapshot, list) = {
+        let mut workspaces = state.workspaces.lock().await;
+        let current = workspaces.get(&id).ok_or("workspace not found")?;
+        let settings = patch_workspace_settings(&current.settings, &patch)?;
+        let entry_snapshot = apply_workspace_settings_update(&mut workspaces, &id, settings)?;
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        (entry_snapshot, list)
+    };
+    write_workspaces(&state.storage_path, &list)?;
+
+    let connected = connection_state(state.sessions.lock().await.get(&id));
+    Ok(WorkspaceInfo {
+        id: entry_snapshot.id,
+        name: entry_snapshot.name,
+        path: entry_snapshot.path,
+        codex_bin: entry_snapshot.codex_bin,
+        connected,
+        path_unavailable: is_path_unavailable(&id),
+        read_only: entry_snapshot.settings.read_only,
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
@@ -1461,13 +1796,15 @@ pub(crate) async fn update_workspace_codex_bin(
     };
     write_workspaces(&state.storage_path, &list)?;
 
-    let connected = state.sessions.lock().await.contains_key(&id);
+    let connected = connection_state(state.sessions.lock().await.get(&id));
     Ok(WorkspaceInfo {
         id: entry_snapshot.id,
         name: entry_snapshot.name,
         path: entry_snapshot.path,
         codex_bin: entry_snapshot.codex_bin,
         connected,
+        path_unavailable: is_path_unavailable(&id),
+        read_only: entry_snapshot.settings.read_only,
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
@@ -1475,6 +1812,86 @@ pub(crate) async fn update_workspace_codex_bin(
     })
 }
 
+#[tauri::command]
+pub(crate) async fn get_effective_codex_args(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<codex_args::EffectiveCodexArgs, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_effective_codex_args",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    let (entry, parent_entry) = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or("workspace not found")?;
+        let parent_entry = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned();
+        (entry, parent_entry)
+    };
+    let spawned_with = state
+        .sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .map(|session| session.codex_args.clone());
+    let settings = state.app_settings.lock().await;
+    codex_args::effective_codex_args(
+        &entry,
+        parent_entry.as_ref(),
+        Some(&settings),
+        spawned_with.as_ref().map(|args| args.as_deref()),
+    )
+}
+
+#[tauri::command]
+pub(crate) async fn set_workspace_codex_args(
+    workspace_id: String,
+    args: Vec<String>,
+    confirm_dangerous: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<codex_args::EffectiveCodexArgs, String> {
+    let confirm_dangerous = confirm_dangerous.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_workspace_codex_args",
+            json!({
+                "workspaceId": workspace_id,
+                "args": args,
+                "confirmDangerous": confirm_dangerous,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+    codex_args::validate_codex_args(&args, confirm_dangerous)?;
+    let list: Vec<_> = {
+        let mut workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get_mut(&workspace_id)
+            .ok_or("workspace not found")?;
+        entry.settings.codex_args = codex_args::join_codex_args(&args);
+        workspaces.values().cloned().collect()
+    };
+    write_workspaces(&state.storage_path, &list)?;
+    get_effective_codex_args(workspace_id, state, app).await
+}
+
 #[tauri::command]
 pub(crate) async fn connect_workspace(
     id: String,
@@ -1485,6 +1902,7 @@ pub(crate) async fn connect_workspace(
         remote_backend::call_remote(&*state, app, "connect_workspace", json!({ "id": id })).await?;
         return Ok(());
     }
+    ensure_path_available(&id)?;
 
     let (entry, parent_entry) = {
         let workspaces = state.workspaces.lock().await;
@@ -1502,6 +1920,19 @@ pub(crate) async fn connect_workspace(
             .ok_or("workspace not found")?
     };
 
+    // A session the keep-alive marked unhealthy is replaced.
+    let unhealthy = {
+        let mut sessions = state.sessions.lock().await;
+        match sessions.get(&id) {
+            Some(session) if session.health.is_unhealthy() => sessions.remove(&id),
+            _ => None,
+        }
+    };
+    if let Some(session) = unhealthy {
+        let mut child = session.child.lock().await;
+        let _ = child.kill().await;
+    }
+
     let default_bin = {
         let settings = state.app_settings.lock().await;
         settings.codex_bin.clone()
@@ -1511,12 +1942,174 @@ pub(crate) async fn connect_workspace(
         let settings = state.app_settings.lock().await;
         codex_args::resolve_workspace_codex_args(&entry, parent_entry.as_ref(), Some(&settings))
     };
+    let spawn_started = std::time::Instant::now();
     let session =
         spawn_workspace_session(entry.clone(), default_bin, codex_args, codex_home, app).await?;
-    state.sessions.lock().await.insert(entry.id, session);
+    state
+        .sessions
+        .lock()
+        .await
+        .insert(entry.id.clone(), session);
+    warm_pool::record_spawn(&entry.id, spawn_started.elapsed());
+    let timezone = state
+        .client_timezone(None, ClientTimezone::Utc)
+        .await
+        .unwrap_or(ClientTimezone::Utc);
+    prewarm_domain_trends(&entry, timezone.today());
     Ok(())
 }
 
+/// Replaces a session the keep-alive gave up on with a new app-server
+/// process, unless it was already reconnected.
+pub(crate) async fn restart_unhealthy_session(app: AppHandle, workspace_id: String) {
+    let state = app.state::<AppState>();
+    let unhealthy = state
+        .sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .is_some_and(|session| session.health.is_unhealthy());
+    if !unhealthy {
+        return;
+    }
+    if let Err(err) = connect_workspace(workspace_id.clone(), state, app.clone()).await {
+        eprintln!("failed to restart unhealthy session for {workspace_id}: {err}");
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn onboarding_status(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<OnboardingStatus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return match remote_backend::call_remote(&*state, app, "onboarding_status", json!({})).await
+        {
+            Ok(response) => serde_json::from_value(response).map_err(|err| err.to_string()),
+            Err(err) => Ok(OnboardingStatus::daemon_unreachable(err)),
+        };
+    }
+    let (codex, git, gh) = tokio::join!(
+        codex::codex_doctor(None, state.clone(), app.clone()),
+        onboarding::check_tool("git"),
+        github_cli::gh_auth_status(false),
+    );
+    let workspace_count = state.workspaces.lock().await.len();
+    Ok(OnboardingStatus::new(
+        codex,
+        workspace_count,
+        state.settings_path.exists(),
+        git,
+        gh,
+        None,
+    ))
+}
+
+/// Adds and connects the workspace for `path`, checks its session and
+/// optionally seeds starter prompts. Steps that an earlier run already did
+/// are skipped, so running it again is safe.
+#[tauri::command]
+pub(crate) async fn onboarding_bootstrap(
+    path: String,
+    options: Option<BootstrapOptions>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BootstrapReport, String> {
+    let options = options.unwrap_or_default();
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "onboarding_bootstrap",
+            json!({
+                "path": path,
+                "options": {
+                    "codexBin": options.codex_bin,
+                    "starterPrompts": options.starter_prompts,
+                },
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let mut report = BootstrapReport::default();
+    let existing = registered_workspace_id(&*state.workspaces.lock().await, &path);
+    let workspace_id = match existing {
+        Some(id) => {
+            report.skipped(STEP_ADD_WORKSPACE, "Already a workspace.");
+            id
+        }
+        None => {
+            match add_workspace(path, options.codex_bin.clone(), state.clone(), app.clone()).await {
+                Ok(workspace) => {
+                    report.done(STEP_ADD_WORKSPACE, format!("Added {}.", workspace.name));
+                    workspace.id
+                }
+                Err(err) => {
+                    report.failed(STEP_ADD_WORKSPACE, err);
+                    report.skip_remaining(STEP_ADD_WORKSPACE, &options);
+                    return Ok(report.finish(None));
+                }
+            }
+        }
+    };
+
+    let connected = connection_state(state.sessions.lock().await.get(&workspace_id))
+        == ConnectionState::Connected;
+    if connected {
+        report.skipped(STEP_CONNECT_WORKSPACE, "Already connected.");
+    } else {
+        match connect_workspace(workspace_id.clone(), state.clone(), app.clone()).await {
+            Ok(()) => report.done(STEP_CONNECT_WORKSPACE, "Connected."),
+            Err(err) => report.failed(STEP_CONNECT_WORKSPACE, err),
+        }
+    }
+    // Runs even when connecting failed; it reports why.
+    report.doctor(codex::workspace_doctor(workspace_id.clone(), state.clone(), app.clone()).await);
+
+    if options.starter_prompts {
+        match create_starter_prompts(&workspace_id, state, app).await {
+            Ok(created) if created.is_empty() => {
+                report.skipped(STEP_STARTER_PROMPTS, "The starter prompts already exist.")
+            }
+            Ok(created) => report.done(
+                STEP_STARTER_PROMPTS,
+                format!("Created {}.", created.join(", ")),
+            ),
+            Err(err) => report.failed(STEP_STARTER_PROMPTS, err),
+        }
+    }
+    Ok(report.finish(Some(workspace_id)))
+}
+
+async fn create_starter_prompts(
+    workspace_id: &str,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<&'static str>, String> {
+    let existing =
+        prompts::prompts_list(state.clone(), workspace_id.to_string(), app.clone()).await?;
+    let mut created = Vec::new();
+    for prompt in
+        onboarding::missing_starter_prompts(existing.iter().map(|entry| entry.name.as_str()))
+    {
+        prompts::prompts_create(
+            state.clone(),
+            workspace_id.to_string(),
+            "workspace".to_string(),
+            prompt.name.to_string(),
+            Some(prompt.description.to_string()),
+            prompt.argument_hint.map(str::to_string),
+            prompt.content.to_string(),
+            app.clone(),
+        )
+        .await?;
+        created.push(prompt.name);
+    }
+    Ok(created)
+}
+
 #[tauri::command]
 pub(crate) async fn list_workspace_files(
     workspace_id: String,
@@ -1534,10 +2127,87 @@ pub(crate) async fn list_workspace_files(
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    let workspaces = state.workspaces.lock().await;
-    let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
-    let root = PathBuf::from(&entry.path);
-    Ok(list_workspace_files_inner(&root, usize::MAX))
+    let root = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+        PathBuf::from(&entry.path)
+    };
+    run_blocking(move || list_workspace_files_inner(&root, usize::MAX)).await
+}
+
+#[tauri::command]
+pub(crate) async fn scan_workspace_todos(
+    workspace_id: String,
+    markers: Option<Vec<String>>,
+    limit: Option<usize>,
+    with_authors: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceTodo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "scan_workspace_todos",
+            json!({
+                "workspaceId": workspace_id,
+                "markers": markers,
+                "limit": limit,
+                "withAuthors": with_authors,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let root = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+        PathBuf::from(&entry.path)
+    };
+    let markers = workspace_todos::normalize_todo_markers(markers);
+    let limit = limit.unwrap_or(DEFAULT_TODO_LIMIT);
+    let with_authors = with_authors.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        workspace_todos::scan_workspace_todos(&root, &markers, limit, with_authors)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn search_workspace_files(
+    workspace_id: String,
+    query: String,
+    options: Option<WorkspaceSearchOptions>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceSearchResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "search_workspace_files",
+            json!({
+                "workspaceId": workspace_id,
+                "query": query,
+                "options": options,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let root = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces.get(&workspace_id).ok_or("workspace not found")?;
+        PathBuf::from(&entry.path)
+    };
+    let options = options.unwrap_or_default();
+    run_blocking(move || {
+        workspace_search::search_workspace_files(&root, &query, &options, SEARCH_TIME_BUDGET)
+    })
+    .await?
 }
 
 #[tauri::command]
@@ -1594,7 +2264,9 @@ mod tests {
             id: id.to_string(),
             name: name.to_string(),
             path: "/tmp".to_string(),
-            connected: false,
+            connected: ConnectionState::Disconnected,
+            path_unavailable: false,
+            read_only: false,
             codex_bin: None,
             kind,
             parent_id,
@@ -1610,6 +2282,15 @@ mod tests {
                 apply_domain_instructions: None,
                 purpose: None,
                 obsidian_root: None,
+                obsidian_roots: Vec::new(),
+                entity_dirs: Default::default(),
+                protected_branches: Vec::new(),
+                diff_ignore_whitespace: false,
+                nutrition_goals: None,
+                quick_commands: Vec::new(),
+                color: None,
+                icon: None,
+                github_prefetch: false,
             },
         }
     }
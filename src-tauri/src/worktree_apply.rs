@@ -0,0 +1,392 @@
+//! Applies a worktree's changes to its parent checkout, or previews that
+//! apply without touching the parent. Both build the patch through
+//! `worktree_patch`, so a preview always describes what apply would do.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::git_patch::{collect_changes_patch, run_git, run_git_output};
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeApplyPreview {
+    /// True when every file would apply without conflicts or failures.
+    pub(crate) clean: bool,
+    pub(crate) files: Vec<WorktreeApplyFile>,
+    /// Files a three-way merge would leave with conflict markers.
+    pub(crate) conflicts: Vec<String>,
+    /// Files that would not apply at all.
+    pub(crate) failed: Vec<WorktreeApplyFailure>,
+    /// Git's output when the check failed for a reason not tied to a file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeApplyFile {
+    pub(crate) path: String,
+    /// `added`, `deleted`, `renamed` or `modified`.
+    pub(crate) status: &'static str,
+    /// Missing for binary files.
+    pub(crate) additions: Option<u64>,
+    pub(crate) deletions: Option<u64>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeApplyFailure {
+    pub(crate) path: String,
+    pub(crate) error: String,
+    /// Start lines, in the parent's file, of the hunks that failed.
+    pub(crate) lines: Vec<u32>,
+}
+
+/// Staged, unstaged and untracked changes of the worktree, limited to
+/// `only_paths` when given.
+async fn worktree_patch(
+    worktree_root: &Path,
+    only_paths: Option<&[String]>,
+) -> Result<Vec<u8>, String> {
+    if only_paths.is_some_and(|paths| paths.is_empty()) {
+        return Err("No changes to apply.".to_string());
+    }
+    let patch = collect_changes_patch(worktree_root, only_paths, true).await?;
+    if String::from_utf8_lossy(&patch).trim().is_empty() {
+        return Err("No changes to apply.".to_string());
+    }
+    Ok(patch)
+}
+
+async fn ensure_parent_clean(parent_root: &Path) -> Result<(), String> {
+    let status = run_git(parent_root, &["status", "--porcelain"], None, false).await?;
+    if !String::from_utf8_lossy(&status).trim().is_empty() {
+        return Err(
+            "Your current branch has uncommitted changes. Please commit, stash, or discard them before applying worktree changes."
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+pub(crate) async fn apply_worktree_changes(
+    worktree_root: &Path,
+    parent_root: &Path,
+    only_paths: Option<&[String]>,
+) -> Result<(), String> {
+    ensure_parent_clean(parent_root).await?;
+    let patch = worktree_patch(worktree_root, only_paths).await?;
+    let output = run_git_output(
+        parent_root,
+        &["apply", "--3way", "--whitespace=nowarn", "-"],
+        Some(&patch),
+    )
+    .await?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if detail.is_empty() {
+        return Err("Git apply failed.".to_string());
+    }
+
+    if detail.contains("Applied patch to") {
+        if detail.contains("with conflicts") {
+            return Err(
+                "Applied with conflicts. Resolve conflicts in the parent repo before retrying."
+                    .to_string(),
+            );
+        }
+        return Err(
+            "Patch applied partially. Resolve changes in the parent repo before retrying."
+                .to_string(),
+        );
+    }
+
+    Err(detail.to_string())
+}
+
+/// Runs `git apply --3way --check` and `git apply --numstat --summary` with
+/// the patch apply would use. Neither touches the parent's index or files.
+pub(crate) async fn preview_worktree_changes(
+    worktree_root: &Path,
+    parent_root: &Path,
+    only_paths: Option<&[String]>,
+) -> Result<WorktreeApplyPreview, String> {
+    ensure_parent_clean(parent_root).await?;
+    let patch = worktree_patch(worktree_root, only_paths).await?;
+    let check = run_git_output(
+        parent_root,
+        &["apply", "--3way", "--check", "--whitespace=nowarn", "-"],
+        Some(&patch),
+    )
+    .await?;
+    let summary = run_git(
+        parent_root,
+        &["apply", "--numstat", "--summary", "-z", "-"],
+        Some(&patch),
+        false,
+    )
+    .await?;
+
+    let stderr = String::from_utf8_lossy(&check.stderr);
+    let (conflicts, failed) = parse_check_output(&stderr);
+    let error = (!check.status.success() && conflicts.is_empty() && failed.is_empty())
+        .then(|| stderr.trim().to_string())
+        .map(|detail| {
+            if detail.is_empty() {
+                "Git apply check failed.".to_string()
+            } else {
+                detail
+            }
+        });
+    Ok(WorktreeApplyPreview {
+        clean: check.status.success() && conflicts.is_empty() && failed.is_empty(),
+        files: parse_apply_summary(&summary),
+        conflicts,
+        failed,
+        error,
+    })
+}
+
+/// Reads the per-file lines of `git apply --3way --check`. A file that falls
+/// back to a three-way merge reports its hunk errors first, so only files that
+/// never reach "Applied patch to" count as failed.
+fn parse_check_output(stderr: &str) -> (Vec<String>, Vec<WorktreeApplyFailure>) {
+    let mut conflicts = BTreeSet::new();
+    let mut merged = BTreeSet::new();
+    let mut failures: BTreeMap<String, WorktreeApplyFailure> = BTreeMap::new();
+    for line in stderr.lines() {
+        if let Some(rest) = line.strip_prefix("Applied patch to '") {
+            if let Some(path) = rest.strip_suffix("' with conflicts.") {
+                conflicts.insert(path.to_string());
+            } else if let Some(path) = rest.strip_suffix("' cleanly.") {
+                merged.insert(path.to_string());
+            }
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("error: ") else {
+            continue;
+        };
+        if let Some(location) = rest.strip_prefix("patch failed: ") {
+            if let Some((path, line)) = location.rsplit_once(':') {
+                let failure = failure_entry(&mut failures, path);
+                if let Ok(line) = line.parse() {
+                    failure.lines.push(line);
+                }
+            }
+        } else if let Some((path, message)) = rest.split_once(": ") {
+            failure_entry(&mut failures, path).error = message.to_string();
+        }
+    }
+    let failed = failures
+        .into_values()
+        .filter(|failure| !conflicts.contains(&failure.path) && !merged.contains(&failure.path))
+        .map(|mut failure| {
+            if failure.error.is_empty() {
+                failure.error = "patch does not apply".to_string();
+            }
+            failure
+        })
+        .collect();
+    (conflicts.into_iter().collect(), failed)
+}
+
+fn failure_entry<'a>(
+    failures: &'a mut BTreeMap<String, WorktreeApplyFailure>,
+    path: &str,
+) -> &'a mut WorktreeApplyFailure {
+    failures
+        .entry(path.to_string())
+        .or_insert_with(|| WorktreeApplyFailure {
+            path: path.to_string(),
+            error: String::new(),
+            lines: Vec::new(),
+        })
+}
+
+/// Reads `git apply --numstat --summary -z`: NUL-terminated
+/// `added\tdeleted\tpath` records followed by the summary lines.
+fn parse_apply_summary(output: &[u8]) -> Vec<WorktreeApplyFile> {
+    let text = String::from_utf8_lossy(output);
+    let mut statuses: HashMap<String, &'static str> = HashMap::new();
+    let mut files = Vec::new();
+    for record in text.split('\0') {
+        let mut fields = record.splitn(3, '\t');
+        if let (Some(added), Some(deleted), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        {
+            files.push(WorktreeApplyFile {
+                path: path.to_string(),
+                status: "modified",
+                additions: added.parse().ok(),
+                deletions: deleted.parse().ok(),
+            });
+            continue;
+        }
+        for line in record.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("create mode ") {
+                if let Some((_, path)) = rest.split_once(' ') {
+                    statuses.insert(path.to_string(), "added");
+                }
+            } else if let Some(rest) = line.strip_prefix("delete mode ") {
+                if let Some((_, path)) = rest.split_once(' ') {
+                    statuses.insert(path.to_string(), "deleted");
+                }
+            } else if let Some(rest) = line.strip_prefix("rename ") {
+                if let Some(path) = renamed_path(rest) {
+                    statuses.insert(path, "renamed");
+                }
+            }
+        }
+    }
+    for file in &mut files {
+        if let Some(status) = statuses.get(&file.path) {
+            file.status = status;
+        }
+    }
+    files
+}
+
+/// New path of a summary rename, `old => new (90%)` or `dir/{old => new}.rs (90%)`.
+fn renamed_path(rest: &str) -> Option<String> {
+    let rename = rest.rsplit_once(" (").map_or(rest, |(rename, _)| rename);
+    if let (Some(open), Some(close)) = (rename.find('{'), rename.rfind('}')) {
+        let (_, new) = rename[open + 1..close].split_once(" => ")?;
+        let path = format!("{}{}{}", &rename[..open], new, &rename[close + 1..]);
+        return Some(path.replace("//", "/"));
+    }
+    rename.split_once(" => ").map(|(_, new)| new.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_worktree_changes, parse_apply_summary, parse_check_output, preview_worktree_changes,
+        WorktreeApplyFailure,
+    };
+    use crate::test_git::git;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reads_check_output() {
+        let stderr = "error: patch failed: a.txt:3\n\
+            Falling back to three-way merge...\n\
+            Applied patch to 'a.txt' with conflicts.\n\
+            error: patch failed: b.txt:1\n\
+            error: b.txt: patch does not apply\n\
+            error: c.txt: does not exist in index\n\
+            error: repository lacks the necessary blob to perform 3-way merge.\n";
+        let (conflicts, failed) = parse_check_output(stderr);
+        assert_eq!(conflicts, vec!["a.txt".to_string()]);
+        assert_eq!(
+            failed,
+            vec![
+                WorktreeApplyFailure {
+                    path: "b.txt".to_string(),
+                    error: "patch does not apply".to_string(),
+                    lines: vec![1],
+                },
+                WorktreeApplyFailure {
+                    path: "c.txt".to_string(),
+                    error: "does not exist in index".to_string(),
+                    lines: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_numstat_and_summary() {
+        let output = b"1\t1\tf.txt\x000\t1\th.txt\x00-\t-\tlogo.png\x003\t0\tsrc/new.rs\x00 delete mode 100644 h.txt\n create mode 100644 logo.png\n rename src/{old.rs => new.rs} (90%)\n";
+        let files = parse_apply_summary(output);
+        let summary: Vec<_> = files
+            .iter()
+            .map(|file| (file.path.as_str(), file.status, file.additions))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("f.txt", "modified", Some(1)),
+                ("h.txt", "deleted", Some(0)),
+                ("logo.png", "added", None),
+                ("src/new.rs", "renamed", Some(3)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn preview_matches_apply_and_leaves_parent_untouched() {
+        let dir = tempdir().expect("tempdir");
+        let parent = dir.path().join("parent");
+        let worktree = dir.path().join("worktree");
+        std::fs::create_dir(&parent).unwrap();
+        git(&parent, &["init", "-q"]);
+        std::fs::write(parent.join("shared.txt"), "a\nb\nc\n").unwrap();
+        std::fs::write(parent.join("clean.txt"), "one\n").unwrap();
+        git(&parent, &["add", "."]);
+        git(&parent, &["commit", "-q", "-m", "init"]);
+        git(
+            &parent,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "wt",
+                worktree.to_str().unwrap(),
+            ],
+        );
+        std::fs::write(worktree.join("shared.txt"), "a\nworktree\nc\n").unwrap();
+        std::fs::write(worktree.join("clean.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(worktree.join("new.txt"), "fresh\n").unwrap();
+        std::fs::write(parent.join("shared.txt"), "a\nparent\nc\n").unwrap();
+        git(&parent, &["commit", "-q", "-am", "diverge"]);
+
+        let preview = preview_worktree_changes(&worktree, &parent, None)
+            .await
+            .expect("preview");
+        assert!(!preview.clean);
+        assert_eq!(preview.conflicts, vec!["shared.txt".to_string()]);
+        assert!(preview.failed.is_empty());
+        let mut paths: Vec<_> = preview
+            .files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["clean.txt", "new.txt", "shared.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(parent.join("clean.txt")).unwrap(),
+            "one\n"
+        );
+
+        let clean_subset = vec!["clean.txt".to_string(), "new.txt".to_string()];
+        let subset = preview_worktree_changes(&worktree, &parent, Some(&clean_subset))
+            .await
+            .expect("subset preview");
+        assert!(subset.clean);
+        apply_worktree_changes(&worktree, &parent, Some(&clean_subset))
+            .await
+            .expect("apply subset");
+        assert_eq!(
+            std::fs::read_to_string(parent.join("clean.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+        assert!(parent.join("new.txt").exists());
+        assert_eq!(
+            std::fs::read_to_string(parent.join("shared.txt")).unwrap(),
+            "a\nparent\nc\n"
+        );
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use git2::{Delta, DiffFindOptions, Patch, Repository, StatusOptions};
+
+use crate::git_utils::{diff_patch_to_string, resolve_git_root};
+use crate::types::{WorkspaceEntry, WorktreeCompareFile, WorktreeComparison};
+use crate::utils::normalize_git_path;
+
+pub(crate) const MODE_WORKING_TREE: &str = "working-tree";
+pub(crate) const MODE_BRANCHES: &str = "branches";
+
+/// Comparing two large checkouts reads every file on both sides.
+pub(crate) const COMPARE_TIMEOUT: Duration = Duration::from_secs(60);
+pub(crate) const MAX_FILE_PATCH_BYTES: usize = 256 * 1024;
+pub(crate) const MAX_TOTAL_PATCH_BYTES: usize = 4 * 1024 * 1024;
+/// Larger files are compared but reported as binary, without a patch.
+const MAX_PATCHED_FILE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Patch text budget shared by all files of one comparison. Files past the
+/// budget keep their stats but lose the patch.
+struct PatchBudget {
+    remaining: usize,
+    exhausted: bool,
+}
+
+impl PatchBudget {
+    fn new() -> Self {
+        Self {
+            remaining: MAX_TOTAL_PATCH_BYTES,
+            exhausted: false,
+        }
+    }
+
+    /// Returns the patch to send and whether it was dropped.
+    fn take(&mut self, patch: String) -> (Option<String>, bool) {
+        if patch.len() > MAX_FILE_PATCH_BYTES {
+            return (None, true);
+        }
+        if patch.len() > self.remaining {
+            self.exhausted = true;
+            return (None, true);
+        }
+        self.remaining -= patch.len();
+        (Some(patch), false)
+    }
+}
+
+/// The repository a workspace belongs to: its parent for worktrees, itself
+/// otherwise.
+fn repo_owner(entry: &WorkspaceEntry) -> &str {
+    entry.parent_id.as_deref().unwrap_or(&entry.id)
+}
+
+fn side_branch(entry: &WorkspaceEntry) -> Result<String, String> {
+    if let Some(worktree) = entry.worktree.as_ref() {
+        return Ok(worktree.branch.clone());
+    }
+    let root = resolve_git_root(entry)?;
+    let repo = Repository::open(&root).map_err(|err| err.to_string())?;
+    let head = repo.head().map_err(|err| err.to_string())?;
+    if !head.is_branch() {
+        return Err(format!("{} is not on a branch", entry.name));
+    }
+    head.shorthand()
+        .map(|name| name.to_string())
+        .ok_or_else(|| format!("{} is not on a branch", entry.name))
+}
+
+fn delta_status(delta: Delta) -> Option<&'static str> {
+    match delta {
+        Delta::Added | Delta::Untracked => Some("A"),
+        Delta::Deleted => Some("D"),
+        Delta::Modified => Some("M"),
+        Delta::Renamed => Some("R"),
+        Delta::Copied => Some("C"),
+        Delta::Typechange => Some("T"),
+        _ => None,
+    }
+}
+
+fn patch_entry(
+    path: String,
+    old_path: Option<String>,
+    status: &str,
+    patch: Option<Patch>,
+    budget: &mut PatchBudget,
+) -> Result<WorktreeCompareFile, String> {
+    let Some(mut patch) = patch else {
+        return Ok(WorktreeCompareFile {
+            path,
+            old_path,
+            status: status.to_string(),
+            additions: 0,
+            deletions: 0,
+            is_binary: true,
+            patch: None,
+            patch_truncated: false,
+        });
+    };
+    let (_, additions, deletions) = patch.line_stats().map_err(|err| err.to_string())?;
+    let is_binary = patch.delta().flags().is_binary();
+    let text = diff_patch_to_string(&mut patch).map_err(|err| err.to_string())?;
+    let (patch, patch_truncated) = if is_binary {
+        (None, false)
+    } else {
+        budget.take(text)
+    };
+    Ok(WorktreeCompareFile {
+        path,
+        old_path,
+        status: status.to_string(),
+        additions,
+        deletions,
+        is_binary,
+        patch,
+        patch_truncated,
+    })
+}
+
+/// Tree-to-tree diff of two local branch tips, with rename detection.
+fn diff_branch_tips(
+    parent_root: &Path,
+    branch_a: &str,
+    branch_b: &str,
+    budget: &mut PatchBudget,
+) -> Result<Vec<WorktreeCompareFile>, String> {
+    let repo = Repository::open(parent_root).map_err(|err| err.to_string())?;
+    let tree_for = |branch: &str| {
+        repo.find_branch(branch, git2::BranchType::Local)
+            .and_then(|branch| branch.get().peel_to_tree())
+            .map_err(|err| format!("Branch {branch}: {err}"))
+    };
+    let tree_a = tree_for(branch_a)?;
+    let tree_b = tree_for(branch_b)?;
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&tree_a), Some(&tree_b), None)
+        .map_err(|err| err.to_string())?;
+    let mut find = DiffFindOptions::new();
+    find.renames(true);
+    diff.find_similar(Some(&mut find))
+        .map_err(|err| err.to_string())?;
+
+    let mut files = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let Some(status) = delta_status(delta.status()) else {
+            continue;
+        };
+        let path_of = |file: git2::DiffFile| {
+            file.path()
+                .map(|path| normalize_git_path(&path.to_string_lossy()))
+        };
+        let Some(path) = path_of(delta.new_file()).or_else(|| path_of(delta.old_file())) else {
+            continue;
+        };
+        let old_path = path_of(delta.old_file()).filter(|old| *old != path);
+        let patch = Patch::from_diff(&diff, index).map_err(|err| err.to_string())?;
+        files.push(patch_entry(path, old_path, status, patch, budget)?);
+    }
+    Ok(files)
+}
+
+/// Tracked files present on disk plus untracked files that aren't ignored.
+fn listed_files(root: &Path) -> Result<BTreeSet<String>, String> {
+    let repo = Repository::open(root).map_err(|err| err.to_string())?;
+    let mut files = BTreeSet::new();
+    let index = repo.index().map_err(|err| err.to_string())?;
+    for entry in index.iter() {
+        let path = String::from_utf8_lossy(&entry.path).to_string();
+        if root.join(&path).is_file() {
+            files.insert(path);
+        }
+    }
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|err| err.to_string())?;
+    for entry in statuses.iter() {
+        if entry.status().contains(git2::Status::WT_NEW) {
+            if let Some(path) = entry.path() {
+                files.insert(path.to_string());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Diffs the checked-out files of two worktrees, A to B. Checks `cancel`
+/// between files so a timed-out comparison stops reading.
+fn diff_directories(
+    root_a: &Path,
+    root_b: &Path,
+    cancel: &AtomicBool,
+    budget: &mut PatchBudget,
+) -> Result<Vec<WorktreeCompareFile>, String> {
+    let files_a = listed_files(root_a)?;
+    let files_b = listed_files(root_b)?;
+    let read = |root: &Path, path: &str, listed: &BTreeSet<String>| {
+        if !listed.contains(path) {
+            return Ok(None);
+        }
+        std::fs::read(root.join(path))
+            .map(Some)
+            .map_err(|err| format!("{path}: {err}"))
+    };
+
+    let mut files = Vec::new();
+    for path in files_a.union(&files_b) {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("Comparison cancelled".to_string());
+        }
+        let old = read(root_a, path, &files_a)?;
+        let new = read(root_b, path, &files_b)?;
+        if old == new {
+            continue;
+        }
+        let status = match (&old, &new) {
+            (None, Some(_)) => "A",
+            (Some(_), None) => "D",
+            _ => "M",
+        };
+        let too_large = [&old, &new].iter().any(|side| {
+            side.as_ref()
+                .is_some_and(|bytes| bytes.len() > MAX_PATCHED_FILE_BYTES)
+        });
+        let patch = if too_large {
+            None
+        } else {
+            let path_ref = Path::new(path);
+            Patch::from_buffers(
+                old.as_deref().unwrap_or_default(),
+                old.as_ref().map(|_| path_ref),
+                new.as_deref().unwrap_or_default(),
+                new.as_ref().map(|_| path_ref),
+                None,
+            )
+            .map(Some)
+            .map_err(|err| format!("{path}: {err}"))?
+        };
+        files.push(patch_entry(
+            normalize_git_path(path),
+            None,
+            status,
+            patch,
+            budget,
+        )?);
+    }
+    Ok(files)
+}
+
+/// Runs `work` on a blocking thread, giving up after `COMPARE_TIMEOUT`.
+async fn run_with_timeout<F>(work: F) -> Result<(Vec<WorktreeCompareFile>, bool), String>
+where
+    F: FnOnce(&AtomicBool, &mut PatchBudget) -> Result<Vec<WorktreeCompareFile>, String>
+        + Send
+        + 'static,
+{
+    let cancel = Arc::new(AtomicBool::new(false));
+    let task_cancel = Arc::clone(&cancel);
+    let task = tokio::task::spawn_blocking(move || {
+        let mut budget = PatchBudget::new();
+        work(&task_cancel, &mut budget).map(|files| (files, budget.exhausted))
+    });
+    match tokio::time::timeout(COMPARE_TIMEOUT, task).await {
+        Ok(joined) => joined.map_err(|err| format!("Worktree comparison failed: {err}"))?,
+        Err(_) => {
+            cancel.store(true, Ordering::Relaxed);
+            Err(format!(
+                "Worktree comparison timed out after {}s",
+                COMPARE_TIMEOUT.as_secs()
+            ))
+        }
+    }
+}
+
+/// Compares workspace A to workspace B. Each side is the workspace and its
+/// parent (for worktrees).
+pub(crate) async fn compare_worktrees(
+    a: (WorkspaceEntry, Option<WorkspaceEntry>),
+    b: (WorkspaceEntry, Option<WorkspaceEntry>),
+    mode: &str,
+) -> Result<WorktreeComparison, String> {
+    let ((entry_a, parent_a), (entry_b, _)) = (a, b);
+    if entry_a.id == entry_b.id {
+        return Err("Pick two different workspaces to compare".to_string());
+    }
+    let (ref_a, ref_b, (files, truncated)) = match mode {
+        MODE_WORKING_TREE => {
+            let root_a: PathBuf = resolve_git_root(&entry_a)?;
+            let root_b: PathBuf = resolve_git_root(&entry_b)?;
+            let result = run_with_timeout(move |cancel, budget| {
+                diff_directories(&root_a, &root_b, cancel, budget)
+            })
+            .await?;
+            (None, None, result)
+        }
+        MODE_BRANCHES => {
+            if repo_owner(&entry_a) != repo_owner(&entry_b) {
+                return Err(
+                    "Branch comparison needs two worktrees of the same repository".to_string(),
+                );
+            }
+            let parent_root = resolve_git_root(parent_a.as_ref().unwrap_or(&entry_a))?;
+            let branch_a = side_branch(&entry_a)?;
+            let branch_b = side_branch(&entry_b)?;
+            let (task_a, task_b) = (branch_a.clone(), branch_b.clone());
+            let result = run_with_timeout(move |_, budget| {
+                diff_branch_tips(&parent_root, &task_a, &task_b, budget)
+            })
+            .await?;
+            (Some(branch_a), Some(branch_b), result)
+        }
+        other => {
+            return Err(format!(
+            "Unknown compare mode `{other}` (expected `{MODE_WORKING_TREE}` or `{MODE_BRANCHES}`)"
+        ))
+        }
+    };
+    Ok(WorktreeComparison {
+        mode: mode.to_string(),
+        workspace_id_a: entry_a.id,
+        workspace_id_b: entry_b.id,
+        ref_a,
+        ref_b,
+        additions: files.iter().map(|file| file.additions).sum(),
+        deletions: files.iter().map(|file| file.deletions).sum(),
+        file_count: files.len(),
+        files,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_branch_tips, diff_directories, PatchBudget};
+    use git2::{Repository, Signature};
+    use std::path::Path;
+    use std::sync::atomic::AtomicBool;
+    use tempfile::tempdir;
+
+    fn commit(repo: &Repository, root: &Path, files: &[(&str, &str)], message: &str) {
+        let mut index = repo.index().unwrap();
+        for (name, content) in files {
+            std::fs::write(root.join(name), content).unwrap();
+            index.add_path(Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn directory_diff_skips_ignored_files_and_reports_changes() {
+        let a = tempdir().unwrap();
+        let b = tempdir().unwrap();
+        for dir in [&a, &b] {
+            let repo = Repository::init(dir.path()).unwrap();
+            commit(
+                &repo,
+                dir.path(),
+                &[(".gitignore", "*.log\n"), ("same.txt", "same\n")],
+                "init",
+            );
+        }
+        std::fs::write(a.path().join("only_a.txt"), "a\n").unwrap();
+        std::fs::write(a.path().join("changed.txt"), "one\n").unwrap();
+        std::fs::write(b.path().join("changed.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(b.path().join("debug.log"), "noise\n").unwrap();
+
+        let mut budget = PatchBudget::new();
+        let files =
+            diff_directories(a.path(), b.path(), &AtomicBool::new(false), &mut budget).unwrap();
+        let summary: Vec<_> = files
+            .iter()
+            .map(|file| (file.path.as_str(), file.status.as_str(), file.additions))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("changed.txt", "M", 1), ("only_a.txt", "D", 0)]
+        );
+        assert!(files[0].patch.as_deref().unwrap().contains("+two"));
+
+        assert!(diff_directories(a.path(), b.path(), &AtomicBool::new(true), &mut budget).is_err());
+    }
+
+    #[test]
+    fn branch_diff_compares_tips_in_the_parent_repo() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit(&repo, dir.path(), &[("a.txt", "base\n")], "init");
+        let base = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("task-a", &base, false).unwrap();
+        repo.branch("task-b", &base, false).unwrap();
+        repo.set_head("refs/heads/task-b").unwrap();
+        commit(
+            &repo,
+            dir.path(),
+            &[("a.txt", "base\nmore\n"), ("b.txt", "new\n")],
+            "work",
+        );
+
+        let mut budget = PatchBudget::new();
+        let files = diff_branch_tips(dir.path(), "task-a", "task-b", &mut budget).unwrap();
+        let summary: Vec<_> = files
+            .iter()
+            .map(|file| (file.path.as_str(), file.status.as_str(), file.additions))
+            .collect();
+        assert_eq!(summary, vec![("a.txt", "M", 1), ("b.txt", "A", 1)]);
+        assert!(diff_branch_tips(dir.path(), "task-a", "missing", &mut budget).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Refuses to remove worktrees whose branch holds commits that exist nowhere
+//! else. With an upstream, that is what the branch is ahead of it; without
+//! one, the commits no remote-tracking ref and no other local branch reaches.
+//! `force` skips the check.
+
+use std::path::{Path, PathBuf};
+
+use git2::{BranchType, Repository, Sort};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::backend::blocking::run_git_blocking;
+use crate::git_utils::resolve_git_root;
+use crate::types::WorkspaceEntry;
+
+pub(crate) const UNPUSHED_COMMITS_ERROR: &str = "unpushed_commits";
+/// Commits listed per worktree in the error; `ahead` still counts all of them.
+const MAX_LISTED_COMMITS: usize = 20;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub(crate) struct AtRiskCommit {
+    pub(crate) sha: String,
+    pub(crate) title: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UnpushedWorktree {
+    pub(crate) workspace_id: String,
+    pub(crate) branch: String,
+    /// Remote-tracking branch, e.g. `origin/feature`; `None` when the branch
+    /// tracks nothing.
+    pub(crate) upstream: Option<String>,
+    pub(crate) ahead: usize,
+    pub(crate) behind: Option<usize>,
+    pub(crate) commits: Vec<AtRiskCommit>,
+}
+
+/// The commits `entry`'s branch would leave without another copy, read from
+/// the parent repository. `None` when there are none or the branch is gone.
+pub(crate) fn unpushed_commits(
+    repo_root: &Path,
+    entry: &WorkspaceEntry,
+) -> Option<UnpushedWorktree> {
+    let branch = entry.worktree.as_ref()?.branch.clone();
+    let repo = Repository::open(repo_root).ok()?;
+    let local = repo.find_branch(&branch, BranchType::Local).ok()?;
+    let local_ref = local.get().name()?.to_string();
+    let tip = local.get().target()?;
+    let mut walk = repo.revwalk().ok()?;
+    walk.set_sorting(Sort::TOPOLOGICAL).ok()?;
+    walk.push(tip).ok()?;
+
+    let upstream = local.upstream().ok();
+    let upstream_tip = upstream
+        .as_ref()
+        .and_then(|upstream| upstream.get().target());
+    let (upstream, behind) = match (upstream.as_ref(), upstream_tip) {
+        (Some(upstream), Some(upstream_tip)) => {
+            walk.hide(upstream_tip).ok()?;
+            let behind = repo
+                .graph_ahead_behind(tip, upstream_tip)
+                .ok()
+                .map(|(_, behind)| behind);
+            let name = upstream.name().ok().flatten().map(str::to_string);
+            (name, behind)
+        }
+        _ => {
+            for reference in repo.references().ok()?.flatten() {
+                let other_branch =
+                    reference.is_branch() && reference.name() != Some(local_ref.as_str());
+                if reference.is_remote() || other_branch {
+                    if let Some(target) = reference.target() {
+                        walk.hide(target).ok()?;
+                    }
+                }
+            }
+            (None, None)
+        }
+    };
+
+    let mut ahead = 0;
+    let mut commits = Vec::new();
+    for oid in walk.flatten() {
+        ahead += 1;
+        if commits.len() < MAX_LISTED_COMMITS {
+            let title = repo
+                .find_commit(oid)
+                .ok()
+                .and_then(|commit| commit.summary().map(str::to_string))
+                .unwrap_or_default();
+            let mut sha = oid.to_string();
+            sha.truncate(7);
+            commits.push(AtRiskCommit { sha, title });
+        }
+    }
+    (ahead > 0).then(|| UnpushedWorktree {
+        workspace_id: entry.id.clone(),
+        branch,
+        upstream,
+        ahead,
+        behind,
+        commits,
+    })
+}
+
+/// `unpushed_commits: {"count":…,"worktrees":[…]}` listing every worktree of
+/// `worktrees` with commits at risk, or `Ok` when there are none.
+pub(crate) fn check_unpushed(repo_root: &Path, worktrees: &[WorkspaceEntry]) -> Result<(), String> {
+    let at_risk: Vec<UnpushedWorktree> = worktrees
+        .iter()
+        .filter_map(|entry| unpushed_commits(repo_root, entry))
+        .collect();
+    if at_risk.is_empty() {
+        return Ok(());
+    }
+    let count: usize = at_risk.iter().map(|worktree| worktree.ahead).sum();
+    Err(format!(
+        "{UNPUSHED_COMMITS_ERROR}: {}",
+        json!({ "count": count, "worktrees": at_risk })
+    ))
+}
+
+/// Runs `check_unpushed` for worktrees of `parent` on the git pool, unless
+/// `force` is set.
+pub(crate) async fn ensure_no_unpushed_commits(
+    parent: &WorkspaceEntry,
+    worktrees: Vec<WorkspaceEntry>,
+    force: bool,
+) -> Result<(), String> {
+    if force || worktrees.is_empty() {
+        return Ok(());
+    }
+    let repo_root = resolve_git_root(parent).unwrap_or_else(|_| PathBuf::from(&parent.path));
+    run_git_blocking(move || check_unpushed(&repo_root, &worktrees)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_unpushed, unpushed_commits, UNPUSHED_COMMITS_ERROR};
+    use crate::test_git::git;
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings, WorktreeInfo};
+    use serde_json::Value;
+    use tempfile::tempdir;
+
+    fn worktree(id: &str, branch: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: branch.to_string(),
+            path: String::new(),
+            codex_bin: None,
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some("parent".to_string()),
+            worktree: Some(WorktreeInfo {
+                branch: branch.to_string(),
+            }),
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
+    #[test]
+    fn local_only_and_ahead_commits_are_at_risk() {
+        let dir = tempdir().expect("tempdir");
+        let remote = tempdir().expect("remote");
+        let repo = dir.path();
+        git(remote.path(), &["init", "-q", "--bare"]);
+        git(repo, &["init", "-q", "-b", "main"]);
+        git(repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(repo, &["checkout", "-q", "-b", "feature"]);
+        git(repo, &["commit", "-q", "--allow-empty", "-m", "first"]);
+        git(repo, &["commit", "-q", "--allow-empty", "-m", "second"]);
+        git(repo, &["branch", "merged", "main"]);
+
+        let feature = worktree("wt-feature", "feature");
+        let local_only = unpushed_commits(repo, &feature).expect("local-only commits");
+        assert_eq!(local_only.ahead, 2);
+        assert_eq!(local_only.upstream, None);
+        assert_eq!(local_only.commits[0].title, "second");
+        assert_eq!(local_only.commits[0].sha.len(), 7);
+        assert_eq!(
+            unpushed_commits(repo, &worktree("wt-merged", "merged")),
+            None
+        );
+
+        let remote_path = remote.path().to_string_lossy().to_string();
+        git(repo, &["remote", "add", "origin", &remote_path]);
+        git(repo, &["push", "-q", "-u", "origin", "feature"]);
+        assert_eq!(unpushed_commits(repo, &feature), None);
+
+        git(repo, &["commit", "-q", "--allow-empty", "-m", "third"]);
+        let ahead = unpushed_commits(repo, &feature).expect("ahead of upstream");
+        assert_eq!(ahead.ahead, 1);
+        assert_eq!(ahead.behind, Some(0));
+        assert_eq!(ahead.upstream.as_deref(), Some("origin/feature"));
+
+        let error = check_unpushed(repo, &[feature, worktree("wt-merged", "merged")])
+            .expect_err("feature is ahead");
+        let payload = error
+            .strip_prefix(&format!("{UNPUSHED_COMMITS_ERROR}: "))
+            .expect("structured error");
+        let payload: Value = serde_json::from_str(payload).expect("json");
+        assert_eq!(payload["count"], 1);
+        assert_eq!(payload["worktrees"][0]["workspaceId"], "wt-feature");
+        assert_eq!(payload["worktrees"][0]["commits"][0]["title"], "third");
+    }
+}
@@ -0,0 +1,295 @@
+use std::path::Path;
+
+use git2::{BranchType, Repository, StatusOptions};
+
+use crate::git_utils::resolve_git_root;
+use crate::types::{WorkspaceEntry, WorktreeReport, WorktreeReportEntry};
+
+/// Directory entries visited per worktree before disk usage is reported as a
+/// lower bound; `node_modules` and build output can hold millions of files.
+pub(crate) const MAX_USAGE_ENTRIES: usize = 200_000;
+
+pub(crate) const ACTION_SAFE_TO_REMOVE: &str = "safe to remove";
+pub(crate) const ACTION_UNMERGED: &str = "has unmerged commits";
+pub(crate) const ACTION_DIRTY: &str = "dirty";
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct DiskUsage {
+    pub(crate) files: u64,
+    pub(crate) bytes: u64,
+    pub(crate) truncated: bool,
+}
+
+/// Sums file sizes under `path` without following symlinks, stopping after
+/// `max_entries` entries.
+pub(crate) fn disk_usage(path: &Path, max_entries: usize) -> DiskUsage {
+    let mut usage = DiskUsage::default();
+    let mut visited = 0usize;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            visited += 1;
+            if visited > max_entries {
+                usage.truncated = true;
+                return usage;
+            }
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                usage.files += 1;
+                usage.bytes += metadata.len();
+            }
+        }
+    }
+    usage
+}
+
+/// The branch worktrees are merged back into: what `origin/HEAD` points at,
+/// else `main` or `master`, else whatever the parent has checked out.
+pub(crate) fn primary_branch(repo: &Repository) -> Option<String> {
+    if let Ok(reference) = repo.find_reference("refs/remotes/origin/HEAD") {
+        if let Some(target) = reference.symbolic_target() {
+            if let Some(name) = target.strip_prefix("refs/remotes/origin/") {
+                if repo.find_branch(name, BranchType::Local).is_ok() {
+                    return Some(name.to_string());
+                }
+            }
+        }
+    }
+    for name in ["main", "master"] {
+        if repo.find_branch(name, BranchType::Local).is_ok() {
+            return Some(name.to_string());
+        }
+    }
+    repo.head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(|name| name.to_string()))
+}
+
+pub(crate) fn suggested_action(dirty: bool, merged: Option<bool>) -> &'static str {
+    if dirty {
+        ACTION_DIRTY
+    } else if merged == Some(true) {
+        ACTION_SAFE_TO_REMOVE
+    } else {
+        ACTION_UNMERGED
+    }
+}
+
+fn is_dirty(worktree_path: &Path) -> bool {
+    let Ok(repo) = Repository::open(worktree_path) else {
+        return false;
+    };
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    repo.statuses(Some(&mut options))
+        .map(|statuses| !statuses.is_empty())
+        .unwrap_or(false)
+}
+
+/// Builds the report row for one worktree. Branch questions are answered from
+/// the parent repository, which shares refs with its worktrees. "Merged" is
+/// `git merge-base --is-ancestor <branch> <primary>`.
+pub(crate) fn inspect_worktree(
+    parent_root: &Path,
+    primary: Option<&str>,
+    entry: &WorkspaceEntry,
+    max_usage_entries: usize,
+) -> WorktreeReportEntry {
+    let branch = entry.worktree.as_ref().map(|info| info.branch.clone());
+    let path = Path::new(&entry.path);
+    let exists = path.exists();
+    let usage = if exists {
+        disk_usage(path, max_usage_entries)
+    } else {
+        DiskUsage::default()
+    };
+    let dirty = exists && is_dirty(path);
+
+    let mut last_commit_at = None;
+    let mut merged = None;
+    let mut ahead = None;
+    let mut behind = None;
+    if let (Ok(repo), Some(branch_name)) = (Repository::open(parent_root), branch.as_deref()) {
+        if let Ok(local) = repo.find_branch(branch_name, BranchType::Local) {
+            if let Some(tip) = local.get().target() {
+                last_commit_at = repo
+                    .find_commit(tip)
+                    .ok()
+                    .map(|commit| commit.time().seconds());
+                merged = primary
+                    .and_then(|name| repo.find_branch(name, BranchType::Local).ok())
+                    .and_then(|primary| primary.get().target())
+                    .and_then(|primary_tip| {
+                        if primary_tip == tip {
+                            return Some(true);
+                        }
+                        repo.graph_descendant_of(primary_tip, tip).ok()
+                    });
+                if let Some(upstream_tip) = local
+                    .upstream()
+                    .ok()
+                    .and_then(|upstream| upstream.get().target())
+                {
+                    if let Ok((ahead_count, behind_count)) =
+                        repo.graph_ahead_behind(tip, upstream_tip)
+                    {
+                        ahead = Some(ahead_count);
+                        behind = Some(behind_count);
+                    }
+                }
+            }
+        }
+    }
+
+    WorktreeReportEntry {
+        workspace_id: entry.id.clone(),
+        name: entry.name.clone(),
+        path: entry.path.clone(),
+        branch,
+        exists,
+        disk_bytes: usage.bytes,
+        disk_files: usage.files,
+        disk_usage_truncated: usage.truncated,
+        last_commit_at,
+        merged,
+        ahead,
+        behind,
+        dirty,
+        suggested_action: suggested_action(dirty, merged).to_string(),
+        removed: None,
+        remove_error: None,
+    }
+}
+
+/// Inspects every child worktree of `parent` on a blocking thread.
+pub(crate) async fn build_worktree_report(
+    parent: &WorkspaceEntry,
+    children: Vec<WorkspaceEntry>,
+) -> Result<WorktreeReport, String> {
+    let parent_root = resolve_git_root(parent)?;
+    let parent_workspace_id = parent.id.clone();
+    tokio::task::spawn_blocking(move || {
+        let primary = Repository::open(&parent_root)
+            .ok()
+            .and_then(|repo| primary_branch(&repo));
+        let worktrees = children
+            .iter()
+            .map(|child| {
+                inspect_worktree(&parent_root, primary.as_deref(), child, MAX_USAGE_ENTRIES)
+            })
+            .collect();
+        WorktreeReport {
+            parent_workspace_id,
+            primary_branch: primary,
+            worktrees,
+        }
+    })
+    .await
+    .map_err(|err| format!("Worktree report failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disk_usage, inspect_worktree, primary_branch, ACTION_SAFE_TO_REMOVE};
+    use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings, WorktreeInfo};
+    use git2::{Repository, Signature};
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn commit_file(repo: &Repository, root: &Path, name: &str, message: &str) {
+        std::fs::write(root.join(name), message).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parents: Vec<_> = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<_> = parents.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent_refs,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn disk_usage_stops_at_the_cap() {
+        let dir = tempdir().unwrap();
+        for index in 0..5 {
+            std::fs::write(dir.path().join(format!("{index}.txt")), "abc").unwrap();
+        }
+        let full = disk_usage(dir.path(), 100);
+        assert_eq!((full.files, full.bytes, full.truncated), (5, 15, false));
+        assert!(disk_usage(dir.path(), 3).truncated);
+    }
+
+    #[test]
+    fn merged_clean_worktree_is_safe_and_dirty_one_is_not() {
+        let parent = tempdir().unwrap();
+        let repo = Repository::init(parent.path()).unwrap();
+        commit_file(&repo, parent.path(), "a.txt", "first");
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+        let primary = primary_branch(&repo);
+
+        let worktrees = tempdir().unwrap();
+        let worktree_path = worktrees.path().join("feature");
+        let reference = repo.find_reference("refs/heads/feature").unwrap();
+        let mut options = git2::WorktreeAddOptions::new();
+        options.reference(Some(&reference));
+        repo.worktree("feature", &worktree_path, Some(&options))
+            .unwrap();
+
+        let entry = WorkspaceEntry {
+            id: "wt".to_string(),
+            name: "feature".to_string(),
+            path: worktree_path.to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some("parent".to_string()),
+            worktree: Some(WorktreeInfo {
+                branch: "feature".to_string(),
+            }),
+            settings: WorkspaceSettings::default(),
+        };
+        let report = inspect_worktree(parent.path(), primary.as_deref(), &entry, 1000);
+        assert_eq!(report.merged, Some(true));
+        assert!(!report.dirty);
+        assert!(report.disk_bytes > 0);
+        assert!(report.last_commit_at.is_some());
+        assert_eq!(report.suggested_action, ACTION_SAFE_TO_REMOVE);
+
+        std::fs::write(worktree_path.join("scratch.txt"), "wip").unwrap();
+        let report = inspect_worktree(parent.path(), primary.as_deref(), &entry, 1000);
+        assert!(report.dirty);
+        assert_eq!(report.suggested_action, "dirty");
+
+        let worktree_repo = Repository::open(&worktree_path).unwrap();
+        commit_file(
+            &worktree_repo,
+            &worktree_path,
+            "scratch.txt",
+            "feature work",
+        );
+        let report = inspect_worktree(parent.path(), primary.as_deref(), &entry, 1000);
+        assert_eq!(report.merged, Some(false));
+        assert_eq!(report.suggested_action, "has unmerged commits");
+    }
+}
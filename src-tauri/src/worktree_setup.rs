@@ -0,0 +1,177 @@
+//! What `add_worktree` does when the new worktree's session fails to start.
+//! By then git has created the worktree, and usually its branch. They are
+//! removed again, or, with `keepOnSpawnFailure`, kept behind a disconnected
+//! workspace; either way the error names what was left on disk.
+
+use std::path::Path;
+
+use crate::git_patch::run_git;
+
+/// The git state `add_worktree` created before starting the session.
+pub(crate) struct CreatedWorktree<'a> {
+    pub(crate) repo_path: &'a Path,
+    pub(crate) worktree_path: &'a str,
+    pub(crate) branch: &'a str,
+    /// False when the worktree checked out a branch that already existed.
+    pub(crate) created_branch: bool,
+}
+
+impl CreatedWorktree<'_> {
+    fn failure(&self, cause: &str) -> String {
+        format!(
+            "Failed to start the session for worktree \"{}\": {}.",
+            self.branch,
+            cause.trim_end_matches('.')
+        )
+    }
+}
+
+/// Removes the worktree and the branch it created, and returns the error for
+/// the caller, listing anything that could not be removed.
+pub(crate) async fn roll_back_worktree(created: &CreatedWorktree<'_>, cause: &str) -> String {
+    let removed = run_git(
+        created.repo_path,
+        &["worktree", "remove", "--force", created.worktree_path],
+        None,
+        false,
+    )
+    .await;
+    let branch_deleted = if created.created_branch {
+        Some(
+            run_git(
+                created.repo_path,
+                &["branch", "-D", created.branch],
+                None,
+                false,
+            )
+            .await,
+        )
+    } else {
+        None
+    };
+    rollback_message(
+        created,
+        cause,
+        removed.err(),
+        branch_deleted.map(Result::err),
+    )
+}
+
+/// `branch_error` is `None` when the branch existed before and was not
+/// touched, `Some(None)` when it was deleted.
+fn rollback_message(
+    created: &CreatedWorktree<'_>,
+    cause: &str,
+    worktree_error: Option<String>,
+    branch_error: Option<Option<String>>,
+) -> String {
+    let mut removed = Vec::new();
+    let mut left = Vec::new();
+    match worktree_error {
+        None => removed.push(format!("the worktree at {}", created.worktree_path)),
+        Some(err) => left.push(format!(
+            "the worktree at {} ({err}; remove it with `git worktree remove --force {}`)",
+            created.worktree_path, created.worktree_path
+        )),
+    }
+    match branch_error {
+        None => left.push(format!("branch {}, which existed before", created.branch)),
+        Some(None) => removed.push(format!("branch {}", created.branch)),
+        Some(Some(err)) => left.push(format!(
+            "branch {} ({err}; delete it with `git branch -D {}`)",
+            created.branch, created.branch
+        )),
+    }
+    let mut message = created.failure(cause);
+    if !removed.is_empty() {
+        message.push_str(&format!(" Removed {}.", removed.join(" and ")));
+    }
+    if !left.is_empty() {
+        message.push_str(&format!(" Left in place: {}.", left.join("; ")));
+    }
+    message
+}
+
+/// The error for a worktree kept as a disconnected workspace.
+pub(crate) fn kept_worktree_error(
+    created: &CreatedWorktree<'_>,
+    workspace_id: &str,
+    cause: &str,
+) -> String {
+    format!(
+        "{} Kept the worktree at {} on branch {} as disconnected workspace {workspace_id}; fix its settings and connect it.",
+        created.failure(cause),
+        created.worktree_path,
+        created.branch
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kept_worktree_error, roll_back_worktree, CreatedWorktree};
+    use crate::test_git::git;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn rolls_back_the_worktree_and_only_a_created_branch() {
+        let repo = tempdir().expect("repo");
+        let worktrees = tempdir().expect("worktrees");
+        git(repo.path(), &["init", "-q"]);
+        git(
+            repo.path(),
+            &["commit", "-q", "--allow-empty", "-m", "init"],
+        );
+        git(repo.path(), &["branch", "existing"]);
+
+        let new_path = worktrees
+            .path()
+            .join("feature")
+            .to_string_lossy()
+            .to_string();
+        git(
+            repo.path(),
+            &["worktree", "add", "-b", "feature", &new_path],
+        );
+        let created = CreatedWorktree {
+            repo_path: repo.path(),
+            worktree_path: &new_path,
+            branch: "feature",
+            created_branch: true,
+        };
+        let message = roll_back_worktree(&created, "codex not found").await;
+        assert!(message.contains("codex not found"), "{message}");
+        assert!(message.contains("Removed the worktree at"), "{message}");
+        assert!(message.contains("and branch feature."), "{message}");
+        assert!(!Path::new(&new_path).exists());
+        assert!(git(repo.path(), &["branch", "--list", "feature"]).is_empty());
+
+        let existing_path = worktrees
+            .path()
+            .join("existing")
+            .to_string_lossy()
+            .to_string();
+        git(
+            repo.path(),
+            &["worktree", "add", &existing_path, "existing"],
+        );
+        let created = CreatedWorktree {
+            repo_path: repo.path(),
+            worktree_path: &existing_path,
+            branch: "existing",
+            created_branch: false,
+        };
+        let message = roll_back_worktree(&created, "codex not found").await;
+        assert!(message.contains("Left in place: branch existing, which existed before."));
+        assert!(!git(repo.path(), &["branch", "--list", "existing"]).is_empty());
+        assert_eq!(
+            git(repo.path(), &["worktree", "list", "--porcelain"])
+                .matches("worktree ")
+                .count(),
+            1
+        );
+
+        let message = kept_worktree_error(&created, "ws-1", "codex not found");
+        assert!(message.contains("disconnected workspace ws-1"), "{message}");
+    }
+}
@@ -0,0 +1,194 @@
+//! Upstream tracking across `rename_worktree`. The branch config is read
+//! before the rename and written back under the new name, so the worktree keeps
+//! tracking its remote branch; renaming the remote branch itself is left to
+//! `rename_worktree_upstream`, which the result describes how to call.
+
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::types::WorkspaceInfo;
+
+/// Remote name and remote branch a local branch tracks.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BranchUpstream {
+    pub(crate) remote: String,
+    pub(crate) branch: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum UpstreamStatus {
+    /// The renamed branch tracks the same remote branch as before.
+    Preserved,
+    /// Tracking could not be carried over; run the upstream rename.
+    NeedsRename,
+    /// The old branch tracked nothing.
+    None,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RenameUpstreamParams {
+    pub(crate) id: String,
+    pub(crate) old_branch: String,
+    pub(crate) new_branch: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeUpstream {
+    pub(crate) status: UpstreamStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) remote: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) remote_branch: Option<String>,
+    /// Params for `rename_worktree_upstream` when the remote branch should
+    /// follow the new name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) rename_upstream: Option<RenameUpstreamParams>,
+}
+
+/// `rename_worktree`'s result: the workspace plus what happened to tracking.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct RenamedWorktree {
+    #[serde(flatten)]
+    pub(crate) workspace: WorkspaceInfo,
+    pub(crate) upstream: WorktreeUpstream,
+}
+
+/// Reads `branch.<name>.remote` and `branch.<name>.merge`. Works even when the
+/// remote-tracking ref was never fetched.
+pub(crate) fn branch_upstream(
+    repo_root: &Path,
+    branch: &str,
+) -> Result<Option<BranchUpstream>, String> {
+    let repo = Repository::open(repo_root).map_err(|err| err.to_string())?;
+    let config = repo.config().map_err(|err| err.to_string())?;
+    let remote = config.get_string(&format!("branch.{branch}.remote")).ok();
+    let merge = config.get_string(&format!("branch.{branch}.merge")).ok();
+    let (Some(remote), Some(merge)) = (remote, merge) else {
+        return Ok(None);
+    };
+    let remote_branch = merge.strip_prefix("refs/heads/").unwrap_or(&merge);
+    if remote.is_empty() || remote == "." || remote_branch.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(BranchUpstream {
+        remote,
+        branch: remote_branch.to_string(),
+    }))
+}
+
+/// Makes `new_branch` track what the old branch tracked. `git branch -m`
+/// normally moves the config itself; this covers setups where it did not.
+pub(crate) fn carry_over_upstream(
+    repo_root: &Path,
+    worktree_id: &str,
+    old_branch: &str,
+    new_branch: &str,
+    upstream: Option<BranchUpstream>,
+) -> WorktreeUpstream {
+    let Some(upstream) = upstream else {
+        return WorktreeUpstream {
+            status: UpstreamStatus::None,
+            remote: None,
+            remote_branch: None,
+            rename_upstream: None,
+        };
+    };
+    let status = match restore_upstream(repo_root, new_branch, &upstream) {
+        Ok(()) => UpstreamStatus::Preserved,
+        Err(err) => {
+            eprintln!("rename_worktree: could not keep upstream for {new_branch}: {err}");
+            UpstreamStatus::NeedsRename
+        }
+    };
+    // A remote branch named after the old local branch should be renamed too.
+    let follow_up = status == UpstreamStatus::NeedsRename
+        || (upstream.branch == old_branch && upstream.branch != new_branch);
+    WorktreeUpstream {
+        status,
+        rename_upstream: follow_up.then(|| RenameUpstreamParams {
+            id: worktree_id.to_string(),
+            old_branch: upstream.branch.clone(),
+            new_branch: new_branch.to_string(),
+        }),
+        remote: Some(upstream.remote),
+        remote_branch: Some(upstream.branch),
+    }
+}
+
+fn restore_upstream(
+    repo_root: &Path,
+    branch: &str,
+    upstream: &BranchUpstream,
+) -> Result<(), String> {
+    if branch_upstream(repo_root, branch)?.as_ref() == Some(upstream) {
+        return Ok(());
+    }
+    let repo = Repository::open(repo_root).map_err(|err| err.to_string())?;
+    let mut config = repo.config().map_err(|err| err.to_string())?;
+    config
+        .set_str(&format!("branch.{branch}.remote"), &upstream.remote)
+        .map_err(|err| err.to_string())?;
+    config
+        .set_str(
+            &format!("branch.{branch}.merge"),
+            &format!("refs/heads/{}", upstream.branch),
+        )
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{branch_upstream, carry_over_upstream, BranchUpstream, UpstreamStatus};
+    use crate::test_git::git;
+    use tempfile::tempdir;
+
+    #[test]
+    fn carries_tracking_over_to_the_renamed_branch() {
+        let dir = tempdir().expect("tempdir");
+        let repo = dir.path();
+        git(repo, &["init", "-q", "-b", "main"]);
+        git(repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(repo, &["branch", "feature"]);
+        git(repo, &["config", "branch.feature.remote", "origin"]);
+        git(
+            repo,
+            &["config", "branch.feature.merge", "refs/heads/feature"],
+        );
+        let upstream = branch_upstream(repo, "feature").expect("read config");
+        assert_eq!(
+            upstream,
+            Some(BranchUpstream {
+                remote: "origin".to_string(),
+                branch: "feature".to_string(),
+            })
+        );
+        assert_eq!(branch_upstream(repo, "main").expect("read config"), None);
+
+        // Simulate a rename that lost the config.
+        git(repo, &["branch", "-m", "feature", "renamed"]);
+        git(repo, &["config", "--remove-section", "branch.renamed"]);
+        let result = carry_over_upstream(repo, "wt-1", "feature", "renamed", upstream);
+        assert_eq!(result.status, UpstreamStatus::Preserved);
+        assert_eq!(result.remote_branch.as_deref(), Some("feature"));
+        let follow_up = result.rename_upstream.expect("follow-up params");
+        assert_eq!(
+            (follow_up.old_branch.as_str(), follow_up.new_branch.as_str()),
+            ("feature", "renamed")
+        );
+        assert_eq!(
+            branch_upstream(repo, "renamed").expect("read config"),
+            Some(BranchUpstream {
+                remote: "origin".to_string(),
+                branch: "feature".to_string(),
+            })
+        );
+
+        let untracked = carry_over_upstream(repo, "wt-1", "a", "b", None);
+        assert_eq!(untracked.status, UpstreamStatus::None);
+        assert!(untracked.rename_upstream.is_none());
+    }
+}
@@ -28,6 +28,27 @@ fn spawn_daemon(port: u16, data_dir: &std::path::Path, token: &str) -> Child {
         .expect("spawn daemon")
 }
 
+/// A daemon with the shared token `shared` plus the tokens in `tokens_file`.
+fn spawn_daemon_with_tokens(
+    port: u16,
+    data_dir: &std::path::Path,
+    tokens_file: &std::path::Path,
+) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_codex_monitor_daemon"))
+        .arg("--listen")
+        .arg(format!("127.0.0.1:{port}"))
+        .arg("--data-dir")
+        .arg(data_dir)
+        .arg("--token")
+        .arg("shared")
+        .arg("--tokens-file")
+        .arg(tokens_file)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn daemon")
+}
+
 fn wait_for_port(port: u16, timeout: Duration) {
     let deadline = Instant::now() + timeout;
     loop {
@@ -164,3 +185,1089 @@ fn daemon_rpc_smoke() {
 
     let _ = child.kill();
 }
+
+#[test]
+fn daemon_ready_file_and_unauth_health() {
+    let data_dir = tempdir().expect("tempdir");
+    let ready_file = data_dir.path().join("run").join("daemon.ready");
+    let port = pick_free_port();
+    let daemon = env!("CARGO_BIN_EXE_codex_monitor_daemon");
+    let mut child = Command::new(daemon)
+        .arg("--listen")
+        .arg(format!("127.0.0.1:{port}"))
+        .arg("--data-dir")
+        .arg(data_dir.path())
+        .arg("--token")
+        .arg("test-token")
+        .arg("--ready-file")
+        .arg(&ready_file)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn daemon");
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !ready_file.exists() {
+        if Instant::now() > deadline {
+            let _ = child.kill();
+            panic!("daemon did not write its ready file in time");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let ready: Value =
+        serde_json::from_str(&std::fs::read_to_string(&ready_file).unwrap()).unwrap();
+    assert_eq!(
+        ready.get("address").and_then(|v| v.as_str()),
+        Some(format!("127.0.0.1:{port}").as_str())
+    );
+    assert_eq!(
+        ready.get("pid").and_then(|v| v.as_u64()),
+        Some(u64::from(child.id()))
+    );
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).expect("connect");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+
+    // health is served before auth; everything else is not
+    let health = rpc_call(&mut reader, &mut writer, 1, "health", Value::Null).unwrap();
+    assert_eq!(health.get("status").and_then(|v| v.as_str()), Some("ok"));
+    assert_eq!(health.get("workspaces").and_then(|v| v.as_u64()), Some(0));
+    let err = rpc_call(&mut reader, &mut writer, 2, "list_workspaces", Value::Null).unwrap_err();
+    assert_eq!(err, "unauthorized");
+
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .arg("-TERM")
+            .arg(child.id().to_string())
+            .status();
+        let _ = child.wait();
+        assert!(
+            !ready_file.exists(),
+            "ready file should be removed on shutdown"
+        );
+    }
+    let _ = child.kill();
+}
+
+#[test]
+fn daemon_reload_config_rotates_token_and_keeps_config_on_bad_file() {
+    let data_dir = tempdir().expect("tempdir");
+    let config_path = data_dir.path().join("daemon.json");
+    std::fs::write(&config_path, r#"{"token":"first"}"#).unwrap();
+    let port = pick_free_port();
+    let daemon = env!("CARGO_BIN_EXE_codex_monitor_daemon");
+    let mut child = Command::new(daemon)
+        .arg("--listen")
+        .arg(format!("127.0.0.1:{port}"))
+        .arg("--data-dir")
+        .arg(data_dir.path())
+        .env_remove("CODEX_MONITOR_DAEMON_TOKEN")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn daemon");
+    wait_for_port(port, Duration::from_secs(5));
+
+    let connect = || {
+        let stream = TcpStream::connect(("127.0.0.1", port)).expect("connect");
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        (BufReader::new(stream.try_clone().unwrap()), stream)
+    };
+    let (mut reader, mut writer) = connect();
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        1,
+        "auth",
+        serde_json::json!({"token": "first"}),
+    )
+    .expect("auth with the file token");
+
+    std::fs::write(&config_path, r#"{"token":"second"}"#).unwrap();
+    let report = rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "daemon_reload_config",
+        Value::Null,
+    )
+    .expect("reload");
+    assert_eq!(report["applied"], serde_json::json!(["token"]));
+    assert_eq!(report["requiresRestart"], serde_json::json!([]));
+
+    // The rotated-out token stops working on the open connection too.
+    assert_eq!(
+        rpc_call(&mut reader, &mut writer, 3, "list_workspaces", Value::Null),
+        Err("invalid token".to_string())
+    );
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        4,
+        "auth",
+        serde_json::json!({"token": "second"}),
+    )
+    .expect("auth again with the rotated token");
+
+    // A half-written file is rejected and the running token stays.
+    std::fs::write(&config_path, r#"{"token":"thi"#).unwrap();
+    let err = rpc_call(
+        &mut reader,
+        &mut writer,
+        5,
+        "daemon_reload_config",
+        Value::Null,
+    )
+    .unwrap_err();
+    assert!(err.contains("unchanged"), "unexpected error: {err}");
+
+    let (mut reader, mut writer) = connect();
+    let err = rpc_call(
+        &mut reader,
+        &mut writer,
+        1,
+        "auth",
+        serde_json::json!({"token": "first"}),
+    )
+    .unwrap_err();
+    assert_eq!(err, "invalid token");
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "auth",
+        serde_json::json!({"token": "second"}),
+    )
+    .expect("auth with the rotated token");
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn git(root: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .env("GIT_AUTHOR_NAME", "t")
+        .env("GIT_AUTHOR_EMAIL", "t@example.com")
+        .env("GIT_COMMITTER_NAME", "t")
+        .env("GIT_COMMITTER_EMAIL", "t@example.com")
+        .output()
+        .expect("git runs");
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn add_worktree_cleans_up_when_the_session_fails_to_start() {
+    let data_dir = tempdir().expect("tempdir");
+    let repo = tempdir().expect("repo");
+    git(repo.path(), &["init", "-q"]);
+    git(
+        repo.path(),
+        &["commit", "-q", "--allow-empty", "-m", "init"],
+    );
+    // The worktree inherits the parent's codex binary, which does not exist.
+    let workspaces = serde_json::json!([{
+        "id": "parent",
+        "name": "repo",
+        "path": repo.path().to_string_lossy(),
+        "codex_bin": data_dir.path().join("missing-codex").to_string_lossy(),
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let stream = TcpStream::connect(("127.0.0.1", port)).expect("connect");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        1,
+        "auth",
+        serde_json::json!({"token": token}),
+    )
+    .expect("auth");
+
+    let worktree_count = || {
+        git(repo.path(), &["worktree", "list", "--porcelain"])
+            .matches("worktree ")
+            .count()
+    };
+
+    // Rolled back: no worktree, no branch, and a retry is not blocked.
+    for id in [2, 3] {
+        let err = rpc_call(
+            &mut reader,
+            &mut writer,
+            id,
+            "add_worktree",
+            serde_json::json!({"parentId": "parent", "branch": "feature"}),
+        )
+        .unwrap_err();
+        assert!(err.contains("Removed the worktree at"), "{err}");
+        assert!(err.contains("and branch feature."), "{err}");
+        assert_eq!(worktree_count(), 1);
+        assert!(git(repo.path(), &["branch", "--list", "feature"]).is_empty());
+    }
+
+    let err = rpc_call(
+        &mut reader,
+        &mut writer,
+        4,
+        "add_worktree",
+        serde_json::json!({
+            "parentId": "parent",
+            "branch": "feature",
+            "keepOnSpawnFailure": true,
+        }),
+    )
+    .unwrap_err();
+    assert!(err.contains("as disconnected workspace"), "{err}");
+    assert_eq!(worktree_count(), 2);
+    let listed = rpc_call(&mut reader, &mut writer, 5, "list_workspaces", Value::Null).unwrap();
+    let kept = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|workspace| workspace["kind"] == "worktree")
+        .expect("kept worktree is listed");
+    assert_eq!(kept["connected"], serde_json::json!(false));
+    assert_eq!(kept["worktree"]["branch"], "feature");
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn connect_authed(port: u16, token: &str) -> (BufReader<TcpStream>, TcpStream) {
+    let stream = TcpStream::connect(("127.0.0.1", port)).expect("connect");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(60)))
+        .unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        1,
+        "auth",
+        serde_json::json!({"token": token}),
+    )
+    .expect("auth");
+    (reader, writer)
+}
+
+#[test]
+fn ping_stays_fast_during_a_heavy_git_status() {
+    let data_dir = tempdir().expect("tempdir");
+    let repo = tempdir().expect("repo");
+    git(repo.path(), &["init", "-q"]);
+    // Thousands of untracked files, each diffed for its line counts.
+    for dir in 0..40 {
+        let dir_path = repo.path().join(format!("dir-{dir}"));
+        std::fs::create_dir(&dir_path).unwrap();
+        for file in 0..100 {
+            std::fs::write(
+                dir_path.join(format!("file-{file}.txt")),
+                "line\n".repeat(50),
+            )
+            .unwrap();
+        }
+    }
+    let workspaces = serde_json::json!([{
+        "id": "repo",
+        "name": "repo",
+        "path": repo.path().to_string_lossy(),
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let (mut status_reader, mut status_writer) = connect_authed(port, token);
+    let (mut ping_reader, mut ping_writer) = connect_authed(port, token);
+
+    let started = Instant::now();
+    let status = std::thread::spawn(move || {
+        let result = rpc_call(
+            &mut status_reader,
+            &mut status_writer,
+            2,
+            "get_git_status",
+            serde_json::json!({"workspaceId": "repo", "forceRefresh": true}),
+        );
+        (result, started.elapsed())
+    });
+    std::thread::sleep(Duration::from_millis(100));
+    let ping_started = Instant::now();
+    rpc_call(&mut ping_reader, &mut ping_writer, 2, "ping", Value::Null).expect("ping");
+    let ping_elapsed = ping_started.elapsed();
+
+    let (result, status_elapsed) = status.join().unwrap();
+    let result = result.expect("git status");
+    assert_eq!(result["files"].as_array().map(Vec::len), Some(4000));
+    assert!(
+        ping_elapsed < Duration::from_millis(500),
+        "ping took {ping_elapsed:?} while git status took {status_elapsed:?}"
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(unix)]
+#[test]
+fn terminal_open_runs_the_initial_command_and_audits_it() {
+    let data_dir = tempdir().expect("tempdir");
+    let workspace = tempdir().expect("workspace");
+    let workspaces = serde_json::json!([{
+        "id": "ws",
+        "name": "ws",
+        "path": workspace.path().to_string_lossy(),
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.path().join("settings.json"),
+        serde_json::json!({"terminalShell": "/bin/sh", "terminalAuditLog": true}).to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let (mut reader, mut writer) = connect_authed(port, token);
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "terminal_open",
+        serde_json::json!({
+            "workspaceId": "ws",
+            "terminalId": "t1",
+            "cols": 80,
+            "rows": 24,
+            "initialCommand": "echo started > marker.txt",
+            "loginShell": true,
+        }),
+    )
+    .expect("terminal_open");
+
+    let marker = workspace.path().join("marker.txt");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !marker.exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(marker.exists(), "initial command did not run");
+    let audit = std::fs::read_to_string(data_dir.path().join("terminal_audit.jsonl"))
+        .expect("terminal audit log");
+    assert!(audit.contains("echo started > marker.txt"), "{audit}");
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn a_terminal_whose_shell_exits_is_dropped_and_announced() {
+    let data_dir = tempdir().expect("tempdir");
+    let workspace = tempdir().expect("workspace");
+    let workspaces = serde_json::json!([{
+        "id": "ws",
+        "name": "ws",
+        "path": workspace.path().to_string_lossy(),
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.path().join("settings.json"),
+        serde_json::json!({"terminalShell": "/bin/sh"}).to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let (mut reader, mut writer) = connect_authed(port, token);
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "terminal_open",
+        serde_json::json!({
+            "workspaceId": "ws",
+            "terminalId": "t1",
+            "cols": 80,
+            "rows": 24,
+            "initialCommand": "exit 3",
+        }),
+    )
+    .expect("terminal_open");
+
+    let exited = loop {
+        let mut line = String::new();
+        assert!(
+            reader.read_line(&mut line).expect("read") > 0,
+            "connection closed"
+        );
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if message["method"] == "terminal-exit" {
+            break message["params"].clone();
+        }
+    };
+    assert_eq!(
+        exited,
+        serde_json::json!({"workspaceId": "ws", "terminalId": "t1", "exitCode": 3})
+    );
+    let terminals = rpc_call(
+        &mut reader,
+        &mut writer,
+        3,
+        "terminal_list",
+        serde_json::json!({"workspaceId": "ws"}),
+    )
+    .expect("terminal_list");
+    assert_eq!(terminals, serde_json::json!([]));
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        4,
+        "terminal_close",
+        serde_json::json!({"workspaceId": "ws", "terminalId": "t1"}),
+    )
+    .expect("closing an exited terminal succeeds");
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn terminal_snapshot_returns_the_tail_of_the_output_and_the_size() {
+    let data_dir = tempdir().expect("tempdir");
+    let workspace = tempdir().expect("workspace");
+    let workspaces = serde_json::json!([{
+        "id": "ws",
+        "name": "ws",
+        "path": workspace.path().to_string_lossy(),
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.path().join("settings.json"),
+        serde_json::json!({"terminalShell": "/bin/sh", "terminalScrollbackBytes": 4096})
+            .to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let (mut reader, mut writer) = connect_authed(port, token);
+    // About 12 KB of output; the quotes keep the echoed command from
+    // matching `DONE`.
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "terminal_open",
+        serde_json::json!({
+            "workspaceId": "ws",
+            "terminalId": "t1",
+            "cols": 100,
+            "rows": 30,
+            "initialCommand": "i=0; while [ $i -lt 300 ]; do echo line-$i-abcdefghijklmnopqrstuvwxyz; i=$((i+1)); done; echo DO\"\"NE",
+        }),
+    )
+    .expect("terminal_open");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut id = 3;
+    let snapshot = loop {
+        let snapshot = rpc_call(
+            &mut reader,
+            &mut writer,
+            id,
+            "terminal_snapshot",
+            serde_json::json!({"workspaceId": "ws", "terminalId": "t1"}),
+        )
+        .expect("terminal_snapshot");
+        if snapshot["data"].as_str().unwrap().contains("DONE") {
+            break snapshot;
+        }
+        assert!(Instant::now() < deadline, "output never finished");
+        id += 1;
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    let data = snapshot["data"].as_str().unwrap();
+    assert!(data.len() <= 4096, "{} bytes kept", data.len());
+    assert!(data.contains("line-299-"), "{data}");
+    assert!(!data.contains("line-0-"), "{data}");
+    assert_eq!(snapshot["truncated"], true);
+    assert_eq!(
+        (snapshot["cols"].as_u64(), snapshot["rows"].as_u64()),
+        (Some(100), Some(30))
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Running and not a zombie waiting to be reaped.
+#[cfg(unix)]
+fn process_alive(pid: &str) -> bool {
+    Command::new("ps")
+        .args(["-o", "stat=", "-p", pid])
+        .output()
+        .map(|output| {
+            let stat = String::from_utf8_lossy(&output.stdout);
+            output.status.success() && !stat.trim().is_empty() && !stat.trim().starts_with('Z')
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+fn wait_for_file(path: &std::path::Path) -> String {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            if contents.ends_with('\n') {
+                return contents.trim().to_string();
+            }
+        }
+        if Instant::now() > deadline {
+            panic!("{} was not written in time", path.display());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn sigterm_kills_sessions_and_terminals_and_tells_clients() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let data_dir = tempdir().expect("tempdir");
+    let workspace = tempdir().expect("workspace");
+    // Answers `initialize`, then idles like an app-server that ignores SIGTERM.
+    let fake_codex = data_dir.path().join("fake-codex");
+    std::fs::write(
+        &fake_codex,
+        "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo 'codex-cli 0.0.0'; exit 0; fi\ntrap '' TERM\necho $$ > codex.pid\nread -r line\necho '{\"id\":1,\"result\":{}}'\nexec sleep 600\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&fake_codex, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let workspaces = serde_json::json!([{
+        "id": "ws",
+        "name": "ws",
+        "path": workspace.path().to_string_lossy(),
+        "codex_bin": fake_codex.to_string_lossy(),
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.path().join("settings.json"),
+        serde_json::json!({"terminalShell": "/bin/sh"}).to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let (mut reader, mut writer) = connect_authed(port, token);
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "connect_workspace",
+        serde_json::json!({"id": "ws"}),
+    )
+    .expect("connect_workspace");
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        3,
+        "terminal_open",
+        serde_json::json!({
+            "workspaceId": "ws",
+            "terminalId": "t1",
+            "cols": 80,
+            "rows": 24,
+            "initialCommand": "echo $$ > shell.pid",
+        }),
+    )
+    .expect("terminal_open");
+    let codex_pid = wait_for_file(&workspace.path().join("codex.pid"));
+    let shell_pid = wait_for_file(&workspace.path().join("shell.pid"));
+    assert!(process_alive(&codex_pid) && process_alive(&shell_pid));
+
+    let _ = Command::new("kill")
+        .arg("-TERM")
+        .arg(child.id().to_string())
+        .status();
+    let mut methods = Vec::new();
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        match message["method"].as_str() {
+            Some("app-server-event") => {
+                if let Some(method) = message["params"]["message"]["method"].as_str() {
+                    methods.push(method.to_string());
+                }
+            }
+            Some(method) => methods.push(method.to_string()),
+            None => {}
+        }
+    }
+    let disconnected = methods
+        .iter()
+        .position(|method| method == "codex/disconnected");
+    let shutdown = methods
+        .iter()
+        .position(|method| method == "daemon/shutdown");
+    assert!(
+        matches!((disconnected, shutdown), (Some(a), Some(b)) if a < b),
+        "{methods:?}"
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(15);
+    while child.try_wait().unwrap().is_none() {
+        if Instant::now() > deadline {
+            let _ = child.kill();
+            panic!("daemon did not exit after SIGTERM");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while (process_alive(&codex_pid) || process_alive(&shell_pid)) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(
+        !process_alive(&codex_pid),
+        "codex app-server {codex_pid} survived"
+    );
+    assert!(
+        !process_alive(&shell_pid),
+        "terminal shell {shell_pid} survived"
+    );
+}
+
+#[test]
+fn read_only_tokens_are_refused_write_methods() {
+    let data_dir = tempdir().expect("tempdir");
+    let tokens_file = data_dir.path().join("tokens.json");
+    std::fs::write(&tokens_file, r#"{"tablet":"read-only","laptop":"full"}"#).unwrap();
+    let port = pick_free_port();
+    let mut child = spawn_daemon_with_tokens(port, data_dir.path(), &tokens_file);
+    wait_for_port(port, Duration::from_secs(5));
+
+    let (mut reader, mut writer) = connect_authed(port, "tablet");
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "list_workspaces",
+        serde_json::json!({}),
+    )
+    .expect("reads are allowed");
+    for (id, method, params) in [
+        (3, "daemon_reload_config", serde_json::json!({})),
+        (
+            4,
+            "terminal_write",
+            serde_json::json!({"workspaceId": "ws", "terminalId": "t1", "data": "ls\n"}),
+        ),
+        (
+            5,
+            "revert_git_all",
+            serde_json::json!({"workspaceId": "ws"}),
+        ),
+        (
+            6,
+            "worktree_report",
+            serde_json::json!({"parentWorkspaceId": "ws", "removeSafe": true}),
+        ),
+        (
+            7,
+            "compose_message",
+            serde_json::json!({"workspaceId": "ws", "parts": [], "send": true}),
+        ),
+        (
+            8,
+            "resume_thread",
+            serde_json::json!({"workspaceId": "ws", "threadId": "t"}),
+        ),
+    ] {
+        assert_eq!(
+            rpc_call(&mut reader, &mut writer, id, method, params),
+            Err("forbidden: read-only token".to_string()),
+            "{method}"
+        );
+    }
+
+    for token in ["laptop", "shared"] {
+        let (mut reader, mut writer) = connect_authed(port, token);
+        rpc_call(
+            &mut reader,
+            &mut writer,
+            2,
+            "daemon_reload_config",
+            serde_json::json!({}),
+        )
+        .expect("full scope can write");
+    }
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).expect("connect");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    assert_eq!(
+        rpc_call(
+            &mut reader,
+            &mut writer,
+            1,
+            "auth",
+            serde_json::json!({"token": "unknown"}),
+        ),
+        Err("invalid token".to_string())
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn reloaded_tokens_apply_to_open_connections() {
+    let data_dir = tempdir().expect("tempdir");
+    let tokens_file = data_dir.path().join("tokens.json");
+    std::fs::write(&tokens_file, r#"{"tablet":"full","phone":"full"}"#).unwrap();
+    let port = pick_free_port();
+    let mut child = spawn_daemon_with_tokens(port, data_dir.path(), &tokens_file);
+    wait_for_port(port, Duration::from_secs(5));
+
+    let (mut tablet_reader, mut tablet_writer) = connect_authed(port, "tablet");
+    let (mut phone_reader, mut phone_writer) = connect_authed(port, "phone");
+    std::fs::write(&tokens_file, r#"{"tablet":"read-only"}"#).unwrap();
+    let (mut reader, mut writer) = connect_authed(port, "shared");
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "daemon_reload_config",
+        Value::Null,
+    )
+    .expect("reload");
+
+    // Downgraded: reads still work, writes are refused.
+    rpc_call(
+        &mut tablet_reader,
+        &mut tablet_writer,
+        2,
+        "list_workspaces",
+        Value::Null,
+    )
+    .expect("reads are allowed");
+    assert_eq!(
+        rpc_call(
+            &mut tablet_reader,
+            &mut tablet_writer,
+            3,
+            "daemon_reload_config",
+            Value::Null,
+        ),
+        Err("forbidden: read-only token".to_string())
+    );
+
+    // Revoked: refused, then treated as unauthenticated.
+    assert_eq!(
+        rpc_call(
+            &mut phone_reader,
+            &mut phone_writer,
+            2,
+            "list_workspaces",
+            Value::Null,
+        ),
+        Err("invalid token".to_string())
+    );
+    assert_eq!(
+        rpc_call(
+            &mut phone_reader,
+            &mut phone_writer,
+            3,
+            "list_workspaces",
+            Value::Null,
+        ),
+        Err("unauthorized".to_string())
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn read_only_tokens_never_see_settings_secrets() {
+    let data_dir = tempdir().expect("tempdir");
+    let tokens_file = data_dir.path().join("tokens.json");
+    std::fs::write(&tokens_file, r#"{"tablet":"read-only"}"#).unwrap();
+    std::fs::write(
+        data_dir.path().join("settings.json"),
+        serde_json::json!({
+            "remoteBackendToken": "shared",
+            "exa_api_key": "exa-secret",
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let port = pick_free_port();
+    let mut child = spawn_daemon_with_tokens(port, data_dir.path(), &tokens_file);
+    wait_for_port(port, Duration::from_secs(5));
+
+    let (mut reader, mut writer) = connect_authed(port, "tablet");
+    let settings = rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "get_app_settings",
+        serde_json::json!({}),
+    )
+    .expect("settings");
+    assert_eq!(settings["remoteBackendToken"], "<redacted>");
+    assert_eq!(settings["exa_api_key"], "<redacted>");
+    for (id, method, params) in [
+        (3, "read_global_config_toml", serde_json::json!({})),
+        (
+            4,
+            "memory_test_connection",
+            serde_json::json!({"settings": {"supabase_url": "http://169.254.169.254"}}),
+        ),
+    ] {
+        assert_eq!(
+            rpc_call(&mut reader, &mut writer, id, method, params),
+            Err("forbidden: read-only token".to_string()),
+            "{method}"
+        );
+    }
+
+    let (mut reader, mut writer) = connect_authed(port, "shared");
+    let settings = rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "get_app_settings",
+        serde_json::json!({}),
+    )
+    .expect("settings");
+    assert_eq!(settings["remoteBackendToken"], "shared");
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn staging_one_hunk_leaves_the_file_partly_staged() {
+    let data_dir = tempdir().expect("tempdir");
+    let repo = tempdir().expect("repo");
+    git(repo.path(), &["init", "-q"]);
+    let original: String = (1..=30).map(|line| format!("line {line}\n")).collect();
+    std::fs::write(repo.path().join("file.txt"), &original).unwrap();
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "init"]);
+    let edited = original
+        .replace("line 2\n", "line two\n")
+        .replace("line 28\n", "line twenty-eight\n");
+    std::fs::write(repo.path().join("file.txt"), edited).unwrap();
+    let workspaces = serde_json::json!([{
+        "id": "repo",
+        "name": "repo",
+        "path": repo.path().to_string_lossy(),
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let (mut reader, mut writer) = connect_authed(port, token);
+
+    let diffs = rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "get_git_diffs",
+        serde_json::json!({"workspaceId": "repo", "hunks": true}),
+    )
+    .expect("diffs");
+    let hunks = diffs[0]["hunks"].as_array().expect("hunks").clone();
+    assert_eq!(hunks.len(), 2);
+    assert_eq!(hunks[1]["staged"], serde_json::json!(false));
+
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        3,
+        "stage_git_hunk",
+        serde_json::json!({
+            "workspaceId": "repo",
+            "path": "file.txt",
+            "hunkIndex": 1,
+            "header": hunks[1]["header"],
+        }),
+    )
+    .expect("stage hunk");
+    let status = rpc_call(
+        &mut reader,
+        &mut writer,
+        4,
+        "get_git_status",
+        serde_json::json!({"workspaceId": "repo"}),
+    )
+    .expect("status");
+    for side in ["stagedFiles", "unstagedFiles"] {
+        let paths: Vec<&str> = status[side]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|file| file["path"].as_str())
+            .collect();
+        assert_eq!(paths, vec!["file.txt"], "{side}");
+    }
+
+    // The same hunk again is stale: the worktree side only has hunk 0 now.
+    assert_eq!(
+        rpc_call(
+            &mut reader,
+            &mut writer,
+            5,
+            "stage_git_hunk",
+            serde_json::json!({
+                "workspaceId": "repo",
+                "path": "file.txt",
+                "hunkIndex": 1,
+            }),
+        ),
+        Err("hunk is stale, refresh diffs".to_string())
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn amending_on_a_protected_branch_needs_an_override() {
+    let data_dir = tempdir().expect("tempdir");
+    let repo = tempdir().expect("repo");
+    git(repo.path(), &["init", "-q", "-b", "main"]);
+    git(repo.path(), &["config", "user.name", "t"]);
+    git(repo.path(), &["config", "user.email", "t@example.com"]);
+    std::fs::write(repo.path().join("a.txt"), "one\n").unwrap();
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "first"]);
+    std::fs::write(repo.path().join("a.txt"), "two\n").unwrap();
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "second"]);
+    let tip = git(repo.path(), &["rev-parse", "HEAD"]);
+    let workspaces = serde_json::json!([{
+        "id": "repo",
+        "name": "repo",
+        "path": repo.path().to_string_lossy(),
+        "settings": {"protectedBranches": ["main"]},
+    }]);
+    std::fs::write(
+        data_dir.path().join("workspaces.json"),
+        workspaces.to_string(),
+    )
+    .unwrap();
+
+    let port = pick_free_port();
+    let token = "test-token";
+    let mut child = spawn_daemon(port, data_dir.path(), token);
+    wait_for_port(port, Duration::from_secs(5));
+    let (mut reader, mut writer) = connect_authed(port, token);
+
+    let err = rpc_call(
+        &mut reader,
+        &mut writer,
+        2,
+        "commit_git",
+        serde_json::json!({
+            "workspaceId": "repo",
+            "message": "second, reworded",
+            "amend": true,
+        }),
+    )
+    .unwrap_err();
+    assert!(err.starts_with("branch_protected:"), "{err}");
+    assert_eq!(git(repo.path(), &["rev-parse", "HEAD"]), tip);
+
+    rpc_call(
+        &mut reader,
+        &mut writer,
+        3,
+        "commit_git",
+        serde_json::json!({
+            "workspaceId": "repo",
+            "message": "second, reworded",
+            "amend": true,
+            "overrideProtection": true,
+        }),
+    )
+    .expect("amend with override");
+    assert_ne!(git(repo.path(), &["rev-parse", "HEAD"]), tip);
+    assert_eq!(
+        git(repo.path(), &["log", "-1", "--format=%s"]).trim(),
+        "second, reworded"
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
@@ -42,6 +42,7 @@ import { useWorkspaceDropZone } from "./features/workspaces/hooks/useWorkspaceDr
 import { useThreads } from "./features/threads/hooks/useThreads";
 import { useThreadUserInput } from "./features/threads/hooks/useThreadUserInput";
 import { useThreadUserInputEvents } from "./features/threads/hooks/useThreadUserInputEvents";
+import { useAppServerEvents } from "./features/app/hooks/useAppServerEvents";
 import { useWindowDrag } from "./features/layout/hooks/useWindowDrag";
 import { useGitPanelController } from "./features/app/hooks/useGitPanelController";
 import { useGitRemote } from "./features/git/hooks/useGitRemote";
@@ -94,10 +95,15 @@ import { useLiquidGlassEffect } from "./features/app/hooks/useLiquidGlassEffect"
 import { useCopyThread } from "./features/threads/hooks/useCopyThread";
 import { useTerminalController } from "./features/terminal/hooks/useTerminalController";
 import { useGitCommitController } from "./features/app/hooks/useGitCommitController";
-import { listSessionThreads, pickWorkspacePath } from "./services/tauri";
+import {
+  getDomainUsage,
+  listSessionThreads,
+  pickWorkspacePath,
+} from "./services/tauri";
 import type {
   AccessMode,
   ComposerEditorSettings,
+  PromptScope,
   WorkspaceInfo,
 } from "./types";
 
@@ -177,6 +183,9 @@ function MainApp() {
     addWorktreeAgent,
     connectWorkspace,
     markWorkspaceConnected,
+    markWorkspaceHealth,
+    markWorkspaceDisconnected,
+    markWorkspacePathAvailable,
     updateWorkspaceSettings,
     updateWorkspaceCodexBin,
     createWorkspaceGroup,
@@ -655,6 +664,31 @@ function MainApp() {
     removeRequest: removeUserInputRequest,
   } = useThreadUserInput();
   useThreadUserInputEvents(addUserInputRequest);
+  const workspaceHealthHandlers = useMemo(
+    () => ({
+      onWorkspaceHealthChanged: markWorkspaceHealth,
+      onWorkspaceDisconnected: markWorkspaceDisconnected,
+      onWorkspacePathAvailable: markWorkspacePathAvailable,
+      onTurnBudget: (
+        _workspaceId: string,
+        event: { method: string; params: Record<string, unknown> },
+      ) =>
+        addDebugEntry({
+          id: `${Date.now()}-event-turn-budget`,
+          timestamp: Date.now(),
+          source: event.method === "codex/turnAutoInterrupted" ? "error" : "event",
+          label: event.method,
+          payload: event.params,
+        }),
+    }),
+    [
+      addDebugEntry,
+      markWorkspaceDisconnected,
+      markWorkspaceHealth,
+      markWorkspacePathAvailable,
+    ],
+  );
+  useAppServerEvents(workspaceHealthHandlers);
   const activeUserInputRequests = useMemo(() => {
     if (!activeWorkspaceId || !activeThreadId) {
       return [];
@@ -1009,7 +1043,7 @@ function MainApp() {
 
   const handleCreatePrompt = useCallback(
     async (data: {
-      scope: "workspace" | "global";
+      scope: PromptScope;
       name: string;
       description?: string | null;
       argumentHint?: string | null;
@@ -1053,7 +1087,7 @@ function MainApp() {
   );
 
   const handleMovePrompt = useCallback(
-    async (data: { path: string; scope: "workspace" | "global" }) => {
+    async (data: { path: string; scope: PromptScope }) => {
       try {
         await movePrompt(data);
       } catch (error) {
@@ -1883,7 +1917,13 @@ function MainApp() {
           domains,
           onCreateDomain: createDomain,
           onUpdateDomain: updateDomain,
-          onDeleteDomain: deleteDomain,
+          onDeleteDomain: async (domainId, options) => {
+            const result = await deleteDomain(domainId, options);
+            if (result.updatedWorkspaceIds.length > 0) {
+              await refreshWorkspaces();
+            }
+          },
+          onGetDomainUsage: getDomainUsage,
           scaleShortcutTitle,
           scaleShortcutText,
           onTestNotificationSound: handleTestNotificationSound,
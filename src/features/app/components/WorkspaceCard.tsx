@@ -37,7 +37,9 @@ export function WorkspaceCard({
   children,
 }: WorkspaceCardProps) {
   return (
-    <div className="workspace-card">
+    <div
+      className={`workspace-card${workspace.pathUnavailable ? " path-unavailable" : ""}`}
+    >
       <div
         className={`workspace-row ${isActive ? "active" : ""}`}
         role="button"
@@ -54,7 +56,31 @@ export function WorkspaceCard({
         <div>
           <div className="workspace-name-row">
             <div className="workspace-title">
+              {(workspace.settings.color || workspace.settings.icon) && (
+                <span
+                  className="workspace-identity"
+                  style={
+                    workspace.settings.color
+                      ? { backgroundColor: workspace.settings.color }
+                      : undefined
+                  }
+                  title={workspace.settings.icon ?? undefined}
+                  aria-hidden
+                >
+                  {workspace.settings.icon && !/^[a-z0-9-]+$/.test(workspace.settings.icon)
+                    ? workspace.settings.icon
+                    : null}
+                </span>
+              )}
               <span className="workspace-name">{workspaceName ?? workspace.name}</span>
+              {workspace.readOnly && (
+                <span
+                  className="workspace-read-only-label"
+                  title="Read-only: git, terminal, prompt and vault writes are turned off"
+                >
+                  read-only
+                </span>
+              )}
               <button
                 className={`workspace-toggle ${isCollapsed ? "" : "expanded"}`}
                 onClick={(event) => {
@@ -97,7 +123,11 @@ export function WorkspaceCard({
             </button>
           </div>
         </div>
-        {!workspace.connected && (
+        {workspace.pathUnavailable ? (
+          <span className="path-unavailable-label" title={`${workspace.path} is not reachable`}>
+            offline
+          </span>
+        ) : workspace.connected !== true && (
           <span
             className="connect"
             onClick={(event) => {
@@ -105,7 +135,7 @@ export function WorkspaceCard({
               onConnectWorkspace(workspace);
             }}
           >
-            connect
+            {workspace.connected === false ? "connect" : "reconnect"}
           </span>
         )}
       </div>
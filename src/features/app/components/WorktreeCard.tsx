@@ -27,7 +27,11 @@ export function WorktreeCard({
   const worktreeBranch = worktree.worktree?.branch ?? "";
 
   return (
-    <div className={`worktree-card${isDeleting ? " deleting" : ""}`}>
+    <div
+      className={`worktree-card${isDeleting ? " deleting" : ""}${
+        worktree.pathUnavailable ? " path-unavailable" : ""
+      }`}
+    >
       <div
         className={`worktree-row ${isActive ? "active" : ""}${isDeleting ? " deleting" : ""}`}
         role="button"
@@ -74,7 +78,14 @@ export function WorktreeCard({
               >
                 <span className="worktree-toggle-icon">›</span>
               </button>
-              {!worktree.connected && (
+              {worktree.pathUnavailable ? (
+                <span
+                  className="path-unavailable-label"
+                  title={`${worktree.path} is not reachable`}
+                >
+                  offline
+                </span>
+              ) : worktree.connected !== true && (
                 <span
                   className="connect"
                   onClick={(event) => {
@@ -82,7 +93,7 @@ export function WorktreeCard({
                     onConnectWorkspace(worktree);
                   }}
                 >
-                  connect
+                  {worktree.connected === false ? "connect" : "reconnect"}
                 </span>
               )}
             </>
@@ -3,6 +3,7 @@ import { emit } from "@tauri-apps/api/event";
 import type {
   AppServerEvent,
   ApprovalRequest,
+  GithubDataUpdated,
   RequestUserInputRequest,
 } from "../../../types";
 import { subscribeAppServerEvents } from "../../../services/events";
@@ -23,6 +24,13 @@ type AgentCompleted = {
 
 type AppServerEventHandlers = {
   onWorkspaceConnected?: (workspaceId: string) => void;
+  onWorkspaceHealthChanged?: (workspaceId: string, healthy: boolean) => void;
+  onWorkspaceDisconnected?: (workspaceId: string) => void;
+  onWorkspacePathAvailable?: (workspaceId: string) => void;
+  onTurnBudget?: (
+    workspaceId: string,
+    event: { method: string; params: Record<string, unknown> },
+  ) => void;
   onApprovalRequest?: (request: ApprovalRequest) => void;
   onAgentMessageDelta?: (event: AgentDelta) => void;
   onAgentMessageCompleted?: (event: AgentCompleted) => void;
@@ -63,6 +71,15 @@ type AppServerEventHandlers = {
     workspaceId: string,
     rateLimits: Record<string, unknown>,
   ) => void;
+  onThreadsAutoArchived?: (workspaceId: string, threadIds: string[]) => void;
+  onGithubDataUpdated?: (workspaceId: string, update: GithubDataUpdated) => void;
+  onFileAppended?: (
+    workspaceId: string,
+    path: string,
+    data: string,
+    nextOffset: number,
+  ) => void;
+  onFileRotated?: (workspaceId: string, path: string) => void;
 };
 
 export function useAppServerEvents(handlers: AppServerEventHandlers) {
@@ -79,6 +96,29 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "codex/unhealthy" || method === "codex/healthy") {
+        handlers.onWorkspaceHealthChanged?.(workspace_id, method === "codex/healthy");
+        return;
+      }
+
+      if (method === "codex/disconnected") {
+        handlers.onWorkspaceDisconnected?.(workspace_id);
+        return;
+      }
+
+      if (method === "codex/turnBudgetWarning" || method === "codex/turnAutoInterrupted") {
+        handlers.onTurnBudget?.(workspace_id, {
+          method,
+          params: (message.params as Record<string, unknown>) ?? {},
+        });
+        return;
+      }
+
+      if (method === "workspace/pathAvailable") {
+        handlers.onWorkspacePathAvailable?.(workspace_id);
+        return;
+      }
+
       if (method.includes("requestApproval") && (typeof message.id === "number" || typeof message.id === "string")) {
         handlers.onApprovalRequest?.({
           workspace_id,
@@ -210,6 +250,53 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "thread/autoArchived") {
+        const params = message.params as Record<string, unknown>;
+        const threadIds = Array.isArray(params.threadIds)
+          ? params.threadIds.map((value) => String(value))
+          : [];
+        if (threadIds.length > 0) {
+          handlers.onThreadsAutoArchived?.(workspace_id, threadIds);
+        }
+        return;
+      }
+
+      if (method === "github/dataUpdated") {
+        const params = message.params as Record<string, unknown>;
+        handlers.onGithubDataUpdated?.(workspace_id, {
+          openIssues: Number(params.openIssues ?? 0),
+          openPullRequests: Number(params.openPullRequests ?? 0),
+          newIssues: Number(params.newIssues ?? 0),
+          newPullRequests: Number(params.newPullRequests ?? 0),
+          failingChecks: Number(params.failingChecks ?? 0),
+          fetchedAt: Number(params.fetchedAt ?? 0),
+        });
+        return;
+      }
+
+      if (method === "file/appended") {
+        const params = message.params as Record<string, unknown>;
+        const path = String(params.path ?? "");
+        if (path) {
+          handlers.onFileAppended?.(
+            workspace_id,
+            path,
+            String(params.data ?? ""),
+            Number(params.nextOffset ?? 0),
+          );
+        }
+        return;
+      }
+
+      if (method === "file/rotated") {
+        const params = message.params as Record<string, unknown>;
+        const path = String(params.path ?? "");
+        if (path) {
+          handlers.onFileRotated?.(workspace_id, path);
+        }
+        return;
+      }
+
       if (method === "item/completed") {
         const params = message.params as Record<string, unknown>;
         const threadId = String(params.threadId ?? params.thread_id ?? "");
@@ -1,6 +1,7 @@
 import { useCallback, useRef } from "react";
 import { useUpdater } from "../../update/hooks/useUpdater";
 import { useAgentSoundNotifications } from "../../notifications/hooks/useAgentSoundNotifications";
+import { useNotificationRuleSounds } from "../../notifications/hooks/useNotificationRuleSounds";
 import { useWindowFocusState } from "../../layout/hooks/useWindowFocusState";
 import { useTauriEvent } from "./useTauriEvent";
 import { playNotificationSound } from "../../../utils/notificationSounds";
@@ -52,6 +53,8 @@ export function useUpdaterController({
     onDebug,
   });
 
+  useNotificationRuleSounds({ onDebug });
+
   const handleTestNotificationSound = useCallback(() => {
     const useError = nextTestSoundIsError.current;
     nextTestSoundIsError.current = !useError;
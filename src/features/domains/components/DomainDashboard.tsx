@@ -53,6 +53,11 @@ export function DomainDashboard({ snapshot }: DomainDashboardProps) {
         ) : (
           <div className="domain-empty">No summary available.</div>
         )}
+        {snapshot.diagnostics?.map((note) => (
+          <div key={note} className="domain-empty">
+            {note}
+          </div>
+        ))}
       </DomainSection>
 
       {snapshot.lists.map((list) => (
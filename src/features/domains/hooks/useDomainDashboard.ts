@@ -13,7 +13,7 @@ export function useDomainDashboard(
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
-  const refresh = useCallback(async () => {
+  const load = useCallback(async (forceRefresh: boolean) => {
     if (!workspaceId || !domainId) {
       setSnapshot(null);
       return;
@@ -21,7 +21,13 @@ export function useDomainDashboard(
     setLoading(true);
     setError(null);
     try {
-      const data = await getDomainTrends(workspaceId, domainId, range);
+      const data = await getDomainTrends(
+        workspaceId,
+        domainId,
+        range,
+        false,
+        forceRefresh,
+      );
       setSnapshot(data);
     } catch (err) {
       setError(err instanceof Error ? err.message : String(err));
@@ -31,8 +37,10 @@ export function useDomainDashboard(
   }, [workspaceId, domainId, range]);
 
   useEffect(() => {
-    void refresh();
-  }, [refresh]);
+    void load(false);
+  }, [load]);
+
+  const refresh = useCallback(() => load(true), [load]);
 
   return { snapshot, loading, error, refresh };
 }
@@ -1,5 +1,5 @@
 import { useCallback, useEffect, useMemo, useState } from "react";
-import type { Domain } from "../../../types";
+import type { DeleteDomainOptions, Domain } from "../../../types";
 import {
   listDomains,
   createDomain as createDomainService,
@@ -11,6 +11,7 @@ export function useDomains() {
   const [domains, setDomains] = useState<Domain[]>([]);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [warnings, setWarnings] = useState<string[]>([]);
 
   const refresh = useCallback(async () => {
     setLoading(true);
@@ -30,23 +31,29 @@ export function useDomains() {
   }, [refresh]);
 
   const createDomain = useCallback(async (domain: Domain) => {
-    const created = await createDomainService(domain);
+    const { warnings = [], ...created } = await createDomainService(domain);
     setDomains((prev) => [...prev, created]);
+    setWarnings(warnings);
     return created;
   }, []);
 
   const updateDomain = useCallback(async (domain: Domain) => {
-    const updated = await updateDomainService(domain);
+    const { warnings = [], ...updated } = await updateDomainService(domain);
     setDomains((prev) =>
       prev.map((entry) => (entry.id === updated.id ? updated : entry)),
     );
+    setWarnings(warnings);
     return updated;
   }, []);
 
-  const deleteDomain = useCallback(async (domainId: string) => {
-    await deleteDomainService(domainId);
-    setDomains((prev) => prev.filter((entry) => entry.id !== domainId));
-  }, []);
+  const deleteDomain = useCallback(
+    async (domainId: string, options?: DeleteDomainOptions) => {
+      const result = await deleteDomainService(domainId, options);
+      setDomains((prev) => prev.filter((entry) => entry.id !== domainId));
+      return result;
+    },
+    [],
+  );
 
   const domainsById = useMemo(
     () =>
@@ -62,6 +69,7 @@ export function useDomains() {
     domainsById,
     loading,
     error,
+    warnings,
     refresh,
     createDomain,
     updateDomain,
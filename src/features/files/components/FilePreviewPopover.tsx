@@ -17,6 +17,9 @@ type FilePreviewPopoverProps = {
   style?: CSSProperties;
   isLoading?: boolean;
   error?: string | null;
+  /** Reads the next chunk of a truncated file. */
+  onLoadMore?: () => void;
+  isLoadingMore?: boolean;
 };
 
 export function FilePreviewPopover({
@@ -32,6 +35,8 @@ export function FilePreviewPopover({
   style,
   isLoading = false,
   error = null,
+  onLoadMore,
+  isLoadingMore = false,
 }: FilePreviewPopoverProps) {
   const lines = useMemo(() => content.split("\n"), [content]);
   const language = useMemo(() => languageFromPath(path), [path]);
@@ -52,9 +57,19 @@ export function FilePreviewPopover({
       <div className="file-preview-header">
         <div className="file-preview-title">
           <span className="file-preview-path">{path}</span>
-          {truncated && (
-            <span className="file-preview-warning">Truncated</span>
-          )}
+          {truncated &&
+            (onLoadMore ? (
+              <button
+                type="button"
+                className="ghost file-preview-load-more"
+                onClick={onLoadMore}
+                disabled={isLoadingMore}
+              >
+                {isLoadingMore ? "Loading..." : "Load more"}
+              </button>
+            ) : (
+              <span className="file-preview-warning">Truncated</span>
+            ))}
         </div>
         <button
           type="button"
@@ -206,6 +206,10 @@ export function FileTreePanel({
   } | null>(null);
   const [previewContent, setPreviewContent] = useState<string>("");
   const [previewTruncated, setPreviewTruncated] = useState(false);
+  const [previewNextOffset, setPreviewNextOffset] = useState(0);
+  const [previewLoadingMore, setPreviewLoadingMore] = useState(false);
+  const previewPathRef = useRef<string | null>(null);
+  previewPathRef.current = previewPath;
   const [previewLoading, setPreviewLoading] = useState(false);
   const [previewError, setPreviewError] = useState<string | null>(null);
   const [previewSelection, setPreviewSelection] = useState<{
@@ -365,6 +369,7 @@ export function FileTreePanel({
         }
         setPreviewContent(response.content ?? "");
         setPreviewTruncated(Boolean(response.truncated));
+        setPreviewNextOffset(response.nextOffset ?? 0);
       })
       .catch((error) => {
         if (cancelled) {
@@ -382,6 +387,29 @@ export function FileTreePanel({
     };
   }, [previewPath, workspaceId]);
 
+  const handleLoadMorePreview = useCallback(() => {
+    if (!previewPath || previewLoadingMore) {
+      return;
+    }
+    const path = previewPath;
+    setPreviewLoadingMore(true);
+    readWorkspaceFile(workspaceId, path, previewNextOffset)
+      .then((response) => {
+        if (path !== previewPathRef.current) {
+          return;
+        }
+        setPreviewContent((current) => current + response.content);
+        setPreviewTruncated(response.truncated);
+        setPreviewNextOffset(response.nextOffset);
+      })
+      .catch((error) => {
+        setPreviewError(error instanceof Error ? error.message : String(error));
+      })
+      .finally(() => {
+        setPreviewLoadingMore(false);
+      });
+  }, [previewLoadingMore, previewNextOffset, previewPath, workspaceId]);
+
   const handleSelectLine = useCallback(
     (index: number, event: MouseEvent<HTMLButtonElement>) => {
       if (event.shiftKey && previewSelection) {
@@ -572,6 +600,8 @@ export function FileTreePanel({
               }}
               isLoading={previewLoading}
               error={previewError}
+              onLoadMore={handleLoadMorePreview}
+              isLoadingMore={previewLoadingMore}
             />,
             document.body,
           )
@@ -18,6 +18,7 @@ type GitDiffViewerItem = {
   newImageData?: string | null;
   oldImageMime?: string | null;
   newImageMime?: string | null;
+  whitespaceOnly?: boolean;
 };
 
 type GitDiffViewerProps = {
@@ -109,6 +110,8 @@ const DiffCard = memo(function DiffCard({
   isSelected,
   diffStyle,
 }: DiffCardProps) {
+  // Whitespace-only files (e.g. CRLF line endings) start collapsed.
+  const [showWhitespace, setShowWhitespace] = useState(false);
   const diffOptions = useMemo(
     () => ({
       diffStyle,
@@ -150,8 +153,24 @@ const DiffCard = memo(function DiffCard({
           {entry.status}
         </span>
         <span className="diff-viewer-path">{entry.path}</span>
+        {entry.whitespaceOnly && (
+          <span className="diff-viewer-whitespace-badge">whitespace only</span>
+        )}
       </div>
-      {entry.diff.trim().length > 0 && fileDiff ? (
+      {entry.whitespaceOnly && !(showWhitespace && fileDiff) ? (
+        <div className="diff-viewer-placeholder">
+          Only whitespace or line endings changed.
+          {fileDiff && (
+            <button
+              type="button"
+              className="ghost diff-viewer-whitespace-toggle"
+              onClick={() => setShowWhitespace(true)}
+            >
+              Show changes
+            </button>
+          )}
+        </div>
+      ) : entry.diff.trim().length > 0 && fileDiff ? (
         <div className="diff-viewer-output diff-viewer-output-flat">
           <FileDiff
             fileDiff={fileDiff}
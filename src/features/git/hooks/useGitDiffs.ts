@@ -112,6 +112,7 @@ export function useGitDiffs(
         newImageData: entry?.newImageData ?? null,
         oldImageMime: entry?.oldImageMime ?? null,
         newImageMime: entry?.newImageMime ?? null,
+        whitespaceOnly: entry?.whitespaceOnly ?? false,
       };
     });
   }, [files, state.diffs]);
@@ -1,13 +1,20 @@
 // @vitest-environment jsdom
-import { renderHook, waitFor } from "@testing-library/react";
+import { act, renderHook, waitFor } from "@testing-library/react";
 import { afterEach, describe, expect, it, vi } from "vitest";
-import type { WorkspaceInfo } from "../../../types";
+import type { AppServerEvent, WorkspaceInfo } from "../../../types";
+import { subscribeAppServerEvents } from "../../../services/events";
 import { getGitHubIssues } from "../../../services/tauri";
 import { useGitHubIssues } from "./useGitHubIssues";
 
 vi.mock("../../../services/tauri", () => ({
   getGitHubIssues: vi.fn(),
 }));
+vi.mock("../../../services/events", () => ({
+  subscribeAppServerEvents: vi.fn(() => () => {}),
+}));
+vi.mock("@tauri-apps/api/event", () => ({
+  emit: vi.fn(),
+}));
 
 const workspace: WorkspaceInfo = {
   id: "workspace-1",
@@ -91,4 +98,34 @@ describe("useGitHubIssues", () => {
     errorSpy.mockRestore();
     unmount();
   });
+
+  it("reloads when the daemon prefetches the active workspace", async () => {
+    let listener: ((event: AppServerEvent) => void) | null = null;
+    vi.mocked(subscribeAppServerEvents).mockImplementation((cb) => {
+      listener = cb;
+      return () => {};
+    });
+    const getGitHubIssuesMock = vi.mocked(getGitHubIssues);
+    getGitHubIssuesMock.mockResolvedValue({ total: 0, issues: [] });
+
+    const { result, unmount } = renderHook(
+      ({ active, enabled }: { active: WorkspaceInfo | null; enabled: boolean }) =>
+        useGitHubIssues(active, enabled),
+      { initialProps: { active: workspace, enabled: true } },
+    );
+    await waitFor(() => expect(result.current.isLoading).toBe(false));
+    expect(getGitHubIssuesMock).toHaveBeenCalledTimes(1);
+
+    const dataUpdated = (workspaceId: string): AppServerEvent => ({
+      workspace_id: workspaceId,
+      message: { method: "github/dataUpdated", params: { openIssues: 0 } },
+    });
+    await act(async () => {
+      listener?.(dataUpdated("workspace-2"));
+      listener?.(dataUpdated("workspace-1"));
+    });
+
+    await waitFor(() => expect(getGitHubIssuesMock).toHaveBeenCalledTimes(2));
+    unmount();
+  });
 });
@@ -1,5 +1,6 @@
-import { useCallback, useEffect, useRef, useState } from "react";
+import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import type { GitHubIssue, WorkspaceInfo } from "../../../types";
+import { useAppServerEvents } from "../../app/hooks/useAppServerEvents";
 import { getGitHubIssues } from "../../../services/tauri";
 
 type GitHubIssuesState = {
@@ -80,6 +81,20 @@ export function useGitHubIssues(
     void refresh();
   }, [enabled, refresh]);
 
+  // The daemon prefetches opted-in workspaces; reload when a fetch lands.
+  const activeWorkspaceId = activeWorkspace?.id ?? null;
+  const prefetchHandlers = useMemo(
+    () => ({
+      onGithubDataUpdated: (workspaceId: string) => {
+        if (enabled && workspaceId === activeWorkspaceId) {
+          void refresh();
+        }
+      },
+    }),
+    [activeWorkspaceId, enabled, refresh],
+  );
+  useAppServerEvents(prefetchHandlers);
+
   return {
     issues: state.issues,
     total: state.total,
@@ -45,6 +45,7 @@ import type {
   GitLogEntry,
   LocalUsageSnapshot,
   ModelOption,
+  PromptScope,
   QueuedMessage,
   RateLimitSnapshot,
   RequestUserInputRequest,
@@ -292,7 +293,7 @@ type LayoutNodesOptions = {
   onSendPrompt: (text: string) => void | Promise<void>;
   onSendPromptToNewAgent: (text: string) => void | Promise<void>;
   onCreatePrompt: (data: {
-    scope: "workspace" | "global";
+    scope: PromptScope;
     name: string;
     description?: string | null;
     argumentHint?: string | null;
@@ -306,7 +307,7 @@ type LayoutNodesOptions = {
     content: string;
   }) => void | Promise<void>;
   onDeletePrompt: (path: string) => void | Promise<void>;
-  onMovePrompt: (data: { path: string; scope: "workspace" | "global" }) => void | Promise<void>;
+  onMovePrompt: (data: { path: string; scope: PromptScope }) => void | Promise<void>;
   onRevealWorkspacePrompts: () => void | Promise<void>;
   onRevealGeneralPrompts: () => void | Promise<void>;
   onSend: (text: string, images: string[]) => void | Promise<void>;
@@ -645,6 +646,7 @@ export function useLayoutNodes(options: LayoutNodesOptions): LayoutNodesResult {
       <PromptPanel
         prompts={options.prompts}
         workspacePath={options.activeWorkspace?.path ?? null}
+        hasParentScope={options.isWorktreeWorkspace}
         filePanelMode={options.filePanelMode}
         onFilePanelModeChange={options.onFilePanelModeChange}
         onSendPrompt={options.onSendPrompt}
@@ -22,6 +22,7 @@ export function DeliveryDashboard({
   const stats = dashboard?.stats;
   const orders = dashboard?.orders ?? [];
   const topMerchants = dashboard?.topMerchants ?? [];
+  const platforms = dashboard?.platforms ?? [];
 
   const earningsValue = stats?.totalEarnings ?? 0;
   const earnings = stats ? `$${stats.totalEarnings.toFixed(2)}` : "--";
@@ -34,6 +35,8 @@ export function DeliveryDashboard({
       : "--";
   const whales =
     stats?.whaleCatches !== undefined ? String(stats.whaleCatches) : "--";
+  const tipsPercent =
+    stats?.tipsPercent !== undefined ? `${stats.tipsPercent.toFixed(0)}%` : null;
   const totalMiles = useMemo(() => {
     if (stats?.totalMiles !== undefined) {
       return stats.totalMiles;
@@ -126,8 +129,32 @@ export function DeliveryDashboard({
                 {whales}
               </div>
             </div>
+            {tipsPercent ? (
+              <div className="life-card">
+                <div className="delivery-stat-label">Tips</div>
+                <div className="delivery-stat-value delivery-stat-value--neutral">
+                  {tipsPercent}
+                </div>
+              </div>
+            ) : null}
           </div>
 
+          {platforms.length ? (
+            <section className="life-section">
+              <div className="life-section-title">Platforms</div>
+              <div className="life-merchant-grid">
+                {platforms.map((platform) => (
+                  <div key={platform.platform} className="life-card">
+                    <div className="life-merchant-name">{platform.platform}</div>
+                    <div className="life-merchant-meta">
+                      {`$${platform.earnings.toFixed(2)} · $${platform.hourlyRate.toFixed(2)}/hr · ${platform.orderCount} orders`}
+                    </div>
+                  </div>
+                ))}
+              </div>
+            </section>
+          ) : null}
+
           <section className="life-section">
             <div className="life-section-title">Top Merchants</div>
             {topMerchants.length ? (
@@ -48,15 +48,18 @@ export function ExerciseDashboard({
       (sum, entry) => sum + (entry.duration ?? 0),
       0,
     );
+    // Workout notes record calories; stream lines are estimated.
     const moveCalories = entries.reduce(
-      (sum, entry) => sum + (entry.miles ?? 0) * 100,
+      (sum, entry) =>
+        sum +
+        (entry.calories ??
+          (entry.miles ?? 0) * 100 + (entry.type === "walk" ? 0 : 150)),
       0,
     );
-    const derivedMove = moveCalories + (stats?.workoutCount ?? 0) * 150;
     const minutes =
       totalMinutes > 0 ? totalMinutes : (stats?.workoutCount ?? 0) * 30;
     return {
-      move: derivedMove,
+      move: moveCalories,
       minutes,
       miles: stats?.walkingMiles ?? 0,
     };
@@ -32,29 +32,42 @@ export function NutritionDashboard({
   const trend = dashboard?.weeklyTrend;
   const mealTitle = range === "today" ? "Today's Meals" : "Meals";
 
+  const goalsReport = dashboard?.goals;
+  const goals = useMemo(
+    () => ({
+      calories: goalsReport?.goals?.calories ?? NUTRITION_GOALS.calories,
+      protein: goalsReport?.goals?.protein ?? NUTRITION_GOALS.protein,
+      carbs: goalsReport?.goals?.carbs ?? NUTRITION_GOALS.carbs,
+      fat: goalsReport?.goals?.fat ?? NUTRITION_GOALS.fat,
+    }),
+    [goalsReport],
+  );
+  const adherence = goalsReport?.adherence;
   const caloriesValue = stats?.calories ?? 0;
   const calories = stats ? stats.calories.toFixed(0) : "--";
-  const remaining = stats
-    ? Math.max(0, NUTRITION_GOALS.calories - stats.calories)
-    : 0;
+  // The backend's figure only counts today's entries; fall back to the range
+  // total when no calorie goal is configured.
+  const remaining =
+    goalsReport?.remainingToday?.calories ??
+    (stats ? Math.max(0, goals.calories - stats.calories) : 0);
   const macroRows = useMemo(
     () => [
       {
         label: "Protein",
         value: stats?.protein ?? 0,
-        goal: NUTRITION_GOALS.protein,
+        goal: goals.protein,
         className: "nutrition-macro-fill--protein",
       },
       {
         label: "Carbs",
         value: stats?.carbs ?? 0,
-        goal: NUTRITION_GOALS.carbs,
+        goal: goals.carbs,
         className: "nutrition-macro-fill--carbs",
       },
       {
         label: "Fat",
         value: stats?.fat ?? 0,
-        goal: NUTRITION_GOALS.fat,
+        goal: goals.fat,
         className: "nutrition-macro-fill--fat",
       },
       {
@@ -64,14 +77,14 @@ export function NutritionDashboard({
         className: "nutrition-macro-fill--fiber",
       },
     ],
-    [stats],
+    [stats, goals],
   );
 
   const trendRows = useMemo(() => {
     if (!trend) return [];
     const rows = Object.entries(trend).sort(([a], [b]) => a.localeCompare(b));
     const maxValue = Math.max(
-      NUTRITION_GOALS.calories,
+      goals.calories,
       ...rows.map(([, value]) => value ?? 0),
     );
     return rows.map(([date, value]) => ({
@@ -79,7 +92,7 @@ export function NutritionDashboard({
       value,
       percent: maxValue > 0 ? (value / maxValue) * 100 : 0,
     }));
-  }, [trend]);
+  }, [trend, goals]);
 
   return (
     <div className="life-dashboard life-nutrition-dashboard">
@@ -104,6 +117,11 @@ export function NutritionDashboard({
       </div>
 
       {error && <div className="life-dashboard-error">{error}</div>}
+      {goalsReport?.diagnostics?.map((note) => (
+        <div key={note} className="life-dashboard-status">
+          {note}
+        </div>
+      ))}
       {loading && !dashboard && (
         <div className="life-dashboard-status">Loading nutrition data…</div>
       )}
@@ -120,8 +138,14 @@ export function NutritionDashboard({
                 </div>
               </div>
               <div className="life-stat-sub">
-                Target: {NUTRITION_GOALS.calories} cal
-                {caloriesValue > NUTRITION_GOALS.calories ? " · over goal" : ""}
+                Target: {goals.calories} cal
+                {caloriesValue > goals.calories ? " · over goal" : ""}
+                {adherence?.calorieGoalDays !== undefined
+                  ? ` · ${adherence.calorieGoalDays}/${adherence.loggedDays} days on goal`
+                  : ""}
+                {adherence?.proteinGoalPercent !== undefined
+                  ? ` · protein ${adherence.proteinGoalPercent.toFixed(0)}% of goal`
+                  : ""}
               </div>
             </div>
           </section>
@@ -42,6 +42,13 @@ export interface DashboardMeta {
   generatedAt: string;
   sources: Array<"obsidian" | "supabase">;
   cacheHit?: boolean;
+  roots?: VaultContribution[];
+}
+
+export interface VaultContribution {
+  vault: string;
+  root: string;
+  counts: Record<string, number>;
 }
 
 // -----------------------------
@@ -59,6 +66,9 @@ export interface DeliveryStats {
   startingAr?: number;
   endingAr?: number;
   whaleCatches?: number;
+  tipsTotal?: number;
+  basePayTotal?: number;
+  tipsPercent?: number;
 }
 
 export interface DeliveryOrder {
@@ -84,11 +94,21 @@ export interface MerchantStats {
   tier?: "S" | "A" | "B" | "C" | "D";
 }
 
+export interface PlatformStats {
+  platform: string;
+  earnings: number;
+  hours: number;
+  orderCount: number;
+  sessionCount: number;
+  hourlyRate: number;
+}
+
 export interface DeliveryDashboard {
   meta: DashboardMeta;
   stats: DeliveryStats;
   orders: DeliveryOrder[];
   topMerchants: MerchantStats[];
+  platforms?: PlatformStats[];
 }
 
 // -----------------------------
@@ -113,13 +133,39 @@ export interface MealEntry {
   estimatedCalories?: number;
 }
 
+export interface NutritionGoals {
+  calories?: number;
+  protein?: number;
+  carbs?: number;
+  fat?: number;
+}
+
+export interface NutritionAdherence {
+  loggedDays: number;
+  calorieGoalDays?: number;
+  avgProtein: number;
+  proteinGoalPercent?: number;
+}
+
+export interface NutritionGoalsReport {
+  goals?: NutritionGoals;
+  /** Path of `Entities/Food/Goals.md`, or `settings`. */
+  source?: string;
+  adherence?: NutritionAdherence;
+  /** Goal minus today's intake; negative once over. */
+  remainingToday?: NutritionGoals;
+  diagnostics?: string[];
+}
+
 export interface NutritionDashboard {
   meta: DashboardMeta;
   stats: NutritionStats;
   meals: MealEntry[];
   weeklyTrend?: Record<string, number>;
+  goals?: NutritionGoalsReport;
 }
 
+/** Display defaults for macros without a configured goal. */
 export const NUTRITION_GOALS = {
   calories: 2000,
   protein: 180,
@@ -137,6 +183,9 @@ export interface ExerciseStats {
   walkingMiles: number;
   activeDays: number;
   currentStreak: number;
+  totalDurationMin: number;
+  distanceKm: number;
+  caloriesBurned: number;
 }
 
 export interface ExerciseEntry {
@@ -146,6 +195,9 @@ export interface ExerciseEntry {
   description: string;
   miles?: number;
   duration?: number;
+  distanceKm?: number;
+  calories?: number;
+  source: "stream" | "entity";
 }
 
 export interface ExerciseDashboard {
@@ -164,7 +216,7 @@ export interface Bill {
   name: string;
   amount: number;
   dueDay: number;
-  frequency: "monthly" | "weekly" | "annual";
+  frequency: "monthly" | "weekly" | "annual" | "once";
   category: string;
   autoPay: boolean;
   nextDueDate: string;
@@ -184,6 +236,20 @@ export interface FinanceDashboard {
   statusMessage?: string;
 }
 
+// -----------------------------
+// Reports
+// -----------------------------
+
+export type LifeReportDomain = "delivery" | "nutrition" | "exercise" | "finance";
+export type LifeReportFormat = "markdown" | "html";
+
+export interface LifeReport {
+  format: LifeReportFormat;
+  fileName: string;
+  content: string;
+  path?: string;
+}
+
 // -----------------------------
 // Media
 // -----------------------------
@@ -222,11 +288,35 @@ export interface MediaFilterState {
   viewMode: MediaViewMode;
 }
 
+export interface MediaCoverFailure {
+  title: string;
+  provider: string;
+  reason: string;
+}
+
+export interface MediaCoverQuery {
+  title: string;
+  mediaType: string;
+  provider: string;
+}
+
 export interface MediaCoverSummary {
   total: number;
   found: number;
   skipped: number;
   failed: number;
+  failures: MediaCoverFailure[];
+  dryRun: boolean;
+  queries: MediaCoverQuery[];
+  operationId?: string;
+}
+
+export interface MediaProviderStatus {
+  provider: "tmdb" | "igdb";
+  enabled: boolean;
+  configured: boolean;
+  valid: boolean;
+  error?: string;
 }
 
 export interface CoverOverride {
@@ -274,3 +364,32 @@ export interface YouTubeFilterState {
   sort: "tier" | "stage" | "title" | "updated";
   viewMode: "grid" | "list";
 }
+
+// -----------------------------
+// Entity browser
+// -----------------------------
+
+export interface DomainEntitySummary {
+  path: string;
+  vault: string;
+  title: string;
+  frontmatter: Record<string, unknown> | null;
+}
+
+export interface DomainEntityPage {
+  domainId: string;
+  kind: string;
+  entities: DomainEntitySummary[];
+  total: number;
+  offset: number;
+  nextOffset: number | null;
+}
+
+export interface DomainEntity {
+  path: string;
+  vault: string;
+  title: string;
+  kind: string | null;
+  frontmatter: Record<string, unknown> | null;
+  body: string;
+}
@@ -0,0 +1,42 @@
+import { useCallback } from "react";
+import errorSoundUrl from "../../../assets/error-notification.mp3";
+import successSoundUrl from "../../../assets/success-notification.mp3";
+import type { DebugEntry, FiredNotification } from "../../../types";
+import { subscribeNotificationRuleFired } from "../../../services/events";
+import { playNotificationSound } from "../../../utils/notificationSounds";
+import { useTauriEvent } from "../../app/hooks/useTauriEvent";
+
+const SOUND_URLS: Record<string, { url: string; label: "success" | "error" }> = {
+  success: { url: successSoundUrl, label: "success" },
+  error: { url: errorSoundUrl, label: "error" },
+};
+
+type Options = {
+  onDebug?: (entry: DebugEntry) => void;
+};
+
+// System notifications are shown by the backend; the frontend only owns audio.
+export function useNotificationRuleSounds({ onDebug }: Options) {
+  const handleFired = useCallback(
+    (event: FiredNotification) => {
+      if (!event.soundId) {
+        return;
+      }
+      const sound = SOUND_URLS[event.soundId];
+      if (!sound) {
+        onDebug?.({
+          id: `${Date.now()}-notification-rule-unknown-sound`,
+          timestamp: Date.now(),
+          source: "error",
+          label: "notification-rule/unknown-sound",
+          payload: `Unknown sound "${event.soundId}" for rule ${event.ruleId}`,
+        });
+        return;
+      }
+      playNotificationSound(sound.url, sound.label, onDebug);
+    },
+    [onDebug],
+  );
+
+  useTauriEvent(subscribeNotificationRuleFired, handleFired);
+}
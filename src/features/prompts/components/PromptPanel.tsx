@@ -5,7 +5,7 @@ import {
   useState,
   type MouseEvent as ReactMouseEvent,
 } from "react";
-import type { CustomPromptOption } from "../../../types";
+import type { CustomPromptOption, PromptScope } from "../../../types";
 import { expandCustomPromptText, getPromptArgumentHint } from "../../../utils/customPrompts";
 import { PanelTabs, type PanelTabId } from "../../layout/components/PanelTabs";
 import { Menu, MenuItem } from "@tauri-apps/api/menu";
@@ -19,12 +19,13 @@ import Search from "lucide-react/dist/esm/icons/search";
 type PromptPanelProps = {
   prompts: CustomPromptOption[];
   workspacePath: string | null;
+  hasParentScope: boolean;
   filePanelMode: PanelTabId;
   onFilePanelModeChange: (mode: PanelTabId) => void;
   onSendPrompt: (text: string) => void | Promise<void>;
   onSendPromptToNewAgent: (text: string) => void | Promise<void>;
   onCreatePrompt: (data: {
-    scope: "workspace" | "global";
+    scope: PromptScope;
     name: string;
     description?: string | null;
     argumentHint?: string | null;
@@ -38,7 +39,7 @@ type PromptPanelProps = {
     content: string;
   }) => void | Promise<void>;
   onDeletePrompt: (path: string) => void | Promise<void>;
-  onMovePrompt: (data: { path: string; scope: "workspace" | "global" }) => void | Promise<void>;
+  onMovePrompt: (data: { path: string; scope: PromptScope }) => void | Promise<void>;
   onRevealWorkspacePrompts: () => void | Promise<void>;
   onRevealGeneralPrompts: () => void | Promise<void>;
 };
@@ -47,7 +48,7 @@ const PROMPTS_PREFIX = "prompts:";
 
 type PromptEditorState = {
   mode: "create" | "edit";
-  scope: "workspace" | "global";
+  scope: PromptScope;
   name: string;
   description: string;
   argumentHint: string;
@@ -60,13 +61,20 @@ function buildPromptCommand(name: string, args: string) {
   return `/${PROMPTS_PREFIX}${name}${trimmedArgs ? ` ${trimmedArgs}` : ""}`;
 }
 
-function isWorkspacePrompt(prompt: CustomPromptOption) {
-  return prompt.scope === "workspace";
+const SCOPE_LABELS: Record<PromptScope, string> = {
+  workspace: "workspace",
+  parent: "parent workspace",
+  global: "general",
+};
+
+function promptScope(prompt: CustomPromptOption): PromptScope {
+  return prompt.scope ?? "global";
 }
 
 export function PromptPanel({
   prompts,
   workspacePath,
+  hasParentScope,
   filePanelMode,
   onFilePanelModeChange,
   onSendPrompt,
@@ -155,19 +163,31 @@ export function PromptPanel({
     });
   }, [normalizedQuery, prompts]);
 
-  const { workspacePrompts, globalPrompts } = useMemo(() => {
+  const { workspacePrompts, parentPrompts, globalPrompts } = useMemo(() => {
     const workspaceEntries: CustomPromptOption[] = [];
+    const parentEntries: CustomPromptOption[] = [];
     const globalEntries: CustomPromptOption[] = [];
     filteredPrompts.forEach((prompt) => {
-      if (isWorkspacePrompt(prompt)) {
+      const scope = promptScope(prompt);
+      if (scope === "workspace") {
         workspaceEntries.push(prompt);
+      } else if (scope === "parent") {
+        parentEntries.push(prompt);
       } else {
         globalEntries.push(prompt);
       }
     });
-    return { workspacePrompts: workspaceEntries, globalPrompts: globalEntries };
+    return {
+      workspacePrompts: workspaceEntries,
+      parentPrompts: parentEntries,
+      globalPrompts: globalEntries,
+    };
   }, [filteredPrompts]);
 
+  const scopes: PromptScope[] = hasParentScope
+    ? ["workspace", "parent", "global"]
+    : ["workspace", "global"];
+
   const totalCount = filteredPrompts.length;
   const hasPrompts = totalCount > 0;
 
@@ -175,7 +195,7 @@ export function PromptPanel({
     setArgsByPrompt((prev) => ({ ...prev, [key]: value }));
   };
 
-  const startCreate = (scope: "workspace" | "global") => {
+  const startCreate = (scope: PromptScope) => {
     resetEditorState();
     setEditor({
       mode: "create",
@@ -188,7 +208,7 @@ export function PromptPanel({
   };
 
   const startEdit = (prompt: CustomPromptOption) => {
-    const scope = isWorkspacePrompt(prompt) ? "workspace" : "global";
+    const scope = promptScope(prompt);
     resetEditorState();
     setEditor({
       mode: "edit",
@@ -268,7 +288,7 @@ export function PromptPanel({
     }
   };
 
-  const handleMove = async (prompt: CustomPromptOption, scope: "workspace" | "global") => {
+  const handleMove = async (prompt: CustomPromptOption, scope: PromptScope) => {
     if (!prompt.path) {
       return;
     }
@@ -286,18 +306,24 @@ export function PromptPanel({
   ) => {
     event.preventDefault();
     event.stopPropagation();
-    const scope = isWorkspacePrompt(prompt) ? "workspace" : "global";
-    const nextScope = scope === "workspace" ? "global" : "workspace";
+    const scope = promptScope(prompt);
+    const moveItems = await Promise.all(
+      scopes
+        .filter((nextScope) => nextScope !== scope)
+        .map((nextScope) =>
+          MenuItem.new({
+            text: `Move to ${SCOPE_LABELS[nextScope]}`,
+            action: () => void handleMove(prompt, nextScope),
+          }),
+        ),
+    );
     const menu = await Menu.new({
       items: [
         await MenuItem.new({
           text: "Edit",
           action: () => startEdit(prompt),
         }),
-        await MenuItem.new({
-          text: `Move to ${nextScope === "workspace" ? "workspace" : "general"}`,
-          action: () => void handleMove(prompt, nextScope),
-        }),
+        ...moveItems,
         await MenuItem.new({
           text: "Delete",
           action: () => handleDeleteRequest(prompt),
@@ -443,6 +469,9 @@ export function PromptPanel({
                   disabled={editor.mode === "edit"}
                 >
                   <option value="workspace">Workspace</option>
+                  {(hasParentScope || editor.scope === "parent") && (
+                    <option value="parent">Parent workspace</option>
+                  )}
                   <option value="global">General</option>
                 </select>
               </label>
@@ -543,6 +572,38 @@ export function PromptPanel({
             </div>
           )}
         </div>
+        {hasParentScope && (
+          <div className="prompt-section">
+            <div className="prompt-section-header">
+              <div className="prompt-section-title">Parent workspace prompts</div>
+              <button
+                type="button"
+                className="ghost icon-button prompt-section-add"
+                onClick={() => startCreate("parent")}
+                aria-label="Add parent workspace prompt"
+                title="Add parent workspace prompt"
+              >
+                <Plus aria-hidden />
+              </button>
+            </div>
+            {parentPrompts.length > 0 ? (
+              <div className="prompt-list">
+                {parentPrompts.map((prompt) => renderPromptRow(prompt))}
+              </div>
+            ) : (
+              <div className="prompt-empty-card">
+                <ScrollText className="prompt-empty-icon" aria-hidden />
+                <div className="prompt-empty-text">
+                  <div className="prompt-empty-title">No parent workspace prompts yet</div>
+                  <div className="prompt-empty-subtitle">
+                    Prompts added here are shared by every worktree of the parent
+                    workspace.
+                  </div>
+                </div>
+              </div>
+            )}
+          </div>
+        )}
         <div className="prompt-section">
           <div className="prompt-section-header">
             <div className="prompt-section-title">General prompts</div>
@@ -1,5 +1,10 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
-import type { CustomPromptOption, DebugEntry, WorkspaceInfo } from "../../../types";
+import type {
+  CustomPromptOption,
+  DebugEntry,
+  PromptScope,
+  WorkspaceInfo,
+} from "../../../types";
 import {
   createPrompt as createPromptService,
   deletePrompt as deletePromptService,
@@ -127,7 +132,7 @@ export function useCustomPrompts({ activeWorkspace, onDebug }: UseCustomPromptsO
 
   const createPrompt = useCallback(
     async (data: {
-      scope: "workspace" | "global";
+      scope: PromptScope;
       name: string;
       description?: string | null;
       argumentHint?: string | null;
@@ -180,7 +185,7 @@ export function useCustomPrompts({ activeWorkspace, onDebug }: UseCustomPromptsO
   );
 
   const movePrompt = useCallback(
-    async (data: { path: string; scope: "workspace" | "global" }) => {
+    async (data: { path: string; scope: PromptScope }) => {
       const id = requireWorkspaceId();
       try {
         await movePromptService(id, data);
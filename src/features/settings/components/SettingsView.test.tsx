@@ -77,6 +77,9 @@ const baseSettings: AppSettings = {
     includeGitStatus: false,
     writeDaily: true,
     writeCurated: true,
+    recallEnabled: false,
+    recallLimit: 5,
+    recallMaxBytes: 2000,
   },
   workspaceGroups: [],
 };
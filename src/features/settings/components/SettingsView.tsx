@@ -20,8 +20,10 @@ import { useGlobalCodexConfigToml } from "../hooks/useGlobalCodexConfigToml";
 import type {
   AppSettings,
   CodexDoctorResult,
+  DeleteDomainOptions,
   DictationModelStatus,
   Domain,
+  DomainUsage,
   WorkspaceGroup,
   WorkspaceInfo,
   WorkspaceSettings,
@@ -136,7 +138,8 @@ export type SettingsViewProps = {
   domains: Domain[];
   onCreateDomain: (domain: Domain) => Promise<Domain>;
   onUpdateDomain: (domain: Domain) => Promise<Domain>;
-  onDeleteDomain: (domainId: string) => Promise<void>;
+  onDeleteDomain: (domainId: string, options?: DeleteDomainOptions) => Promise<void>;
+  onGetDomainUsage?: (domainId: string) => Promise<DomainUsage>;
   scaleShortcutTitle: string;
   scaleShortcutText: string;
   onTestNotificationSound: () => void;
@@ -228,6 +231,7 @@ export function SettingsView({
   onCreateDomain,
   onUpdateDomain,
   onDeleteDomain,
+  onGetDomainUsage,
   scaleShortcutTitle,
   scaleShortcutText,
   onTestNotificationSound,
@@ -701,14 +705,31 @@ export function SettingsView({
 
   const handleDeleteDomain = useCallback(
     async (id: string) => {
-      await onDeleteDomain(id);
+      const usage = onGetDomainUsage ? await onGetDomainUsage(id) : null;
+      const using = usage?.workspaces ?? [];
+      if (using.length > 0) {
+        const names = using.map((workspace) => workspace.name).join(", ");
+        const confirmed = await ask(
+          `${using.length === 1 ? "1 project uses" : `${using.length} projects use`} this domain: ${names}.\n\nDeleting it removes the domain from ${using.length === 1 ? "that project" : "those projects"}.`,
+          {
+            title: "Delete Domain",
+            kind: "warning",
+            okLabel: "Delete",
+            cancelLabel: "Cancel",
+          },
+        );
+        if (!confirmed) {
+          return;
+        }
+      }
+      await onDeleteDomain(id, using.length > 0 ? { force: true } : undefined);
       setDomainDrafts((prev) => {
         const next = { ...prev };
         delete next[id];
         return next;
       });
     },
-    [onDeleteDomain],
+    [onDeleteDomain, onGetDomainUsage],
   );
 
   const handleImportPrompt = useCallback(
@@ -2712,6 +2733,18 @@ export function SettingsView({
                     }
                   />
                 </div>
+                <div className="settings-field">
+                  <label className="settings-field-label">
+                    Recall memory when sending
+                  </label>
+                  <input
+                    type="checkbox"
+                    checked={appSettings.autoMemory.recallEnabled}
+                    onChange={(event) =>
+                      updateAutoMemory({ recallEnabled: event.target.checked })
+                    }
+                  />
+                </div>
 
                 <FileEditorCard
                   title="Global AGENTS.md"
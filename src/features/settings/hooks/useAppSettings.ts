@@ -70,6 +70,9 @@ const defaultSettings: AppSettings = {
     includeGitStatus: false,
     writeDaily: true,
     writeCurated: true,
+    recallEnabled: false,
+    recallLimit: 5,
+    recallMaxBytes: 2000,
   },
   workspaceGroups: [],
 };
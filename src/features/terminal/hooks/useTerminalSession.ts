@@ -5,8 +5,14 @@ import { FitAddon } from "@xterm/addon-fit";
 import "@xterm/xterm/css/xterm.css";
 import type { DebugEntry, TerminalStatus, WorkspaceInfo } from "../../../types";
 import { buildErrorDebugEntry } from "../../../utils/debugEntries";
-import { subscribeTerminalOutput, type TerminalOutputEvent } from "../../../services/events";
 import {
+  subscribeTerminalExit,
+  subscribeTerminalOutput,
+  type TerminalExitEvent,
+  type TerminalOutputEvent,
+} from "../../../services/events";
+import {
+  getTerminalSnapshot,
   openTerminalSession,
   resizeTerminalSession,
   writeTerminalSession,
@@ -37,6 +43,11 @@ function appendBuffer(existing: string | undefined, data: string): string {
   return next.slice(next.length - MAX_BUFFER_CHARS);
 }
 
+function exitNotice(exitCode: number | null) {
+  const status = exitCode === null ? "" : ` with code ${exitCode}`;
+  return `\r\n[Process exited${status}. Press Enter to restart.]\r\n`;
+}
+
 function shouldIgnoreTerminalError(error: unknown) {
   const message = error instanceof Error ? error.message : String(error);
   return message.includes("Terminal session not found");
@@ -53,6 +64,8 @@ export function useTerminalSession({
   const fitAddonRef = useRef<FitAddon | null>(null);
   const inputDisposableRef = useRef<{ dispose: () => void } | null>(null);
   const openedSessionsRef = useRef<Set<string>>(new Set());
+  // Sessions whose shell exited; Enter starts a new one.
+  const exitedSessionsRef = useRef<Set<string>>(new Set());
   const outputBuffersRef = useRef<Map<string, string>>(new Map());
   const activeKeyRef = useRef<string | null>(null);
   const renderedKeyRef = useRef<string | null>(null);
@@ -65,6 +78,7 @@ export function useTerminalSession({
     const key = `${workspaceId}:${terminalId}`;
     outputBuffersRef.current.delete(key);
     openedSessionsRef.current.delete(key);
+    exitedSessionsRef.current.delete(key);
     if (activeKeyRef.current === key) {
       terminalRef.current?.reset();
     }
@@ -135,6 +149,35 @@ export function useTerminalSession({
     };
   }, [onDebug, writeToTerminal]);
 
+  useEffect(() => {
+    const unlisten = subscribeTerminalExit(
+      (payload: TerminalExitEvent) => {
+        const { workspaceId, terminalId, exitCode } = payload;
+        const key = `${workspaceId}:${terminalId}`;
+        if (!openedSessionsRef.current.delete(key)) {
+          return;
+        }
+        exitedSessionsRef.current.add(key);
+        const notice = exitNotice(exitCode);
+        outputBuffersRef.current.set(
+          key,
+          appendBuffer(outputBuffersRef.current.get(key), notice),
+        );
+        if (activeKeyRef.current === key) {
+          writeToTerminal(notice);
+        }
+      },
+      {
+        onError: (error) => {
+          onDebug?.(buildErrorDebugEntry("terminal listen error", error));
+        },
+      },
+    );
+    return () => {
+      unlisten();
+    };
+  }, [onDebug, writeToTerminal]);
+
   useEffect(() => {
     if (!isVisible) {
       inputDisposableRef.current?.dispose();
@@ -175,6 +218,21 @@ export function useTerminalSession({
           return;
         }
         const key = `${workspace.id}:${terminalId}`;
+        if (exitedSessionsRef.current.has(key)) {
+          if (!data.includes("\r")) {
+            return;
+          }
+          exitedSessionsRef.current.delete(key);
+          void openTerminalSession(workspace.id, terminalId, terminal.cols, terminal.rows)
+            .then(() => {
+              openedSessionsRef.current.add(key);
+            })
+            .catch((error) => {
+              exitedSessionsRef.current.add(key);
+              onDebug?.(buildErrorDebugEntry("terminal open error", error));
+            });
+          return;
+        }
         if (!openedSessionsRef.current.has(key)) {
           return;
         }
@@ -229,6 +287,16 @@ export function useTerminalSession({
       if (!openedSessionsRef.current.has(key)) {
         await openTerminalSession(activeWorkspace.id, activeTerminalId, cols, rows);
         openedSessionsRef.current.add(key);
+        // The terminal may have been running before this client (re)attached;
+        // repaint what it printed meanwhile.
+        if (!outputBuffersRef.current.has(key)) {
+          const snapshot = await getTerminalSnapshot(activeWorkspace.id, activeTerminalId)
+            .catch(() => null);
+          if (snapshot?.data && !outputBuffersRef.current.has(key)) {
+            outputBuffersRef.current.set(key, appendBuffer(undefined, snapshot.data));
+            renderedKeyRef.current = null;
+          }
+        }
       }
       setStatus("ready");
       setMessage("Terminal ready.");
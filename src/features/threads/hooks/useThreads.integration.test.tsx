@@ -31,6 +31,7 @@ vi.mock("../../../services/tauri", () => ({
   listThreads: vi.fn(),
   resumeThread: vi.fn(),
   archiveThread: vi.fn(),
+  setPinnedThreads: vi.fn(() => Promise.resolve()),
   getAccountRateLimits: vi.fn(),
   interruptTurn: vi.fn(),
 }));
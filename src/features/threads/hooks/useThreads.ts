@@ -28,6 +28,7 @@ import {
   listThreads as listThreadsService,
   resumeThread as resumeThreadService,
   archiveThread as archiveThreadService,
+  setPinnedThreads,
   getAccountRateLimits,
   interruptTurn as interruptTurnService,
 } from "../../../services/tauri";
@@ -150,6 +151,7 @@ function loadPinnedThreads(): PinnedThreadsMap {
 }
 
 function savePinnedThreads(pinned: PinnedThreadsMap) {
+  syncPinnedThreads(pinned);
   if (typeof window === "undefined") {
     return;
   }
@@ -163,6 +165,13 @@ function savePinnedThreads(pinned: PinnedThreadsMap) {
   }
 }
 
+// The backend only needs pins to keep auto-archive away from them.
+function syncPinnedThreads(pinned: PinnedThreadsMap) {
+  setPinnedThreads(Object.keys(pinned)).catch(() => {
+    // Best-effort; the next pin change retries.
+  });
+}
+
 type UseThreadsOptions = {
   activeWorkspace: WorkspaceInfo | null;
   onWorkspaceConnected: (id: string) => void;
@@ -469,6 +478,7 @@ export function useThreads({
       return;
     }
     pinnedThreadsRef.current = loadPinnedThreads();
+    syncPinnedThreads(pinnedThreadsRef.current);
     const handleStorage = (event: StorageEvent) => {
       if (event.key !== STORAGE_KEY_PINNED_THREADS) {
         return;
@@ -987,6 +997,18 @@ export function useThreads({
           rateLimits: normalizeRateLimits(rateLimits),
         });
       },
+      onThreadsAutoArchived: (workspaceId: string, threadIds: string[]) => {
+        threadIds.forEach((threadId) => {
+          dispatch({ type: "removeThread", workspaceId, threadId });
+        });
+        onDebug?.({
+          id: `${Date.now()}-server-thread-auto-archived`,
+          timestamp: Date.now(),
+          source: "server",
+          label: "thread/autoArchived",
+          payload: { workspaceId, threadIds },
+        });
+      },
       onTurnError: (
         workspaceId: string,
         threadId: string,
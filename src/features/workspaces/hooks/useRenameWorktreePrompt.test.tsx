@@ -64,6 +64,37 @@ describe("useRenameWorktreePrompt", () => {
     expect(result.current.notice).toBe("Upstream branch updated.");
   });
 
+  it("skips the upstream prompt when the branch tracked nothing", async () => {
+    const renameWorktree = vi.fn().mockResolvedValue({
+      ...worktree,
+      name: "feature/new",
+      worktree: { branch: "feature/new" },
+      upstream: { status: "none" },
+    });
+    const renameWorktreeUpstream = vi.fn().mockResolvedValue(undefined);
+
+    const { result } = renderHook(() =>
+      useRenameWorktreePrompt({
+        workspaces: [worktree],
+        activeWorkspaceId: worktree.id,
+        renameWorktree,
+        renameWorktreeUpstream,
+      }),
+    );
+
+    act(() => {
+      result.current.openRenamePrompt(worktree.id);
+      result.current.handleRenameChange("feature/new");
+    });
+
+    await act(async () => {
+      await result.current.handleRenameConfirm();
+    });
+
+    expect(result.current.upstreamPrompt).toBeNull();
+    expect(result.current.notice).toBe("Worktree renamed.");
+  });
+
   it("surfaces rename errors", async () => {
     const renameWorktree = vi
       .fn()
@@ -1,5 +1,5 @@
 import { useCallback, useEffect, useRef, useState } from "react";
-import type { WorkspaceInfo } from "../../../types";
+import type { RenamedWorktree, WorkspaceInfo } from "../../../types";
 
 type RenamePromptState = {
   workspaceId: string;
@@ -20,7 +20,7 @@ type UpstreamPromptState = {
 type UseRenameWorktreePromptOptions = {
   workspaces: WorkspaceInfo[];
   activeWorkspaceId: string | null;
-  renameWorktree: (workspaceId: string, branch: string) => Promise<WorkspaceInfo>;
+  renameWorktree: (workspaceId: string, branch: string) => Promise<RenamedWorktree>;
   renameWorktreeUpstream: (
     workspaceId: string,
     oldBranch: string,
@@ -152,11 +152,21 @@ export function useRenameWorktreePrompt({
       const updated = await renameWorktree(target.workspaceId, trimmed);
       const actualName = updated.worktree?.branch ?? updated.name;
       onRenameSuccess?.(updated);
-      if (actualName !== target.originalName) {
+      // Older daemons don't report upstream tracking; offer the rename then.
+      const followUp = updated.upstream
+        ? updated.upstream.renameUpstream
+        : actualName !== target.originalName
+          ? {
+              id: target.workspaceId,
+              oldBranch: target.originalName,
+              newBranch: actualName,
+            }
+          : undefined;
+      if (followUp) {
         setUpstreamPrompt({
-          workspaceId: target.workspaceId,
-          oldBranch: target.originalName,
-          newBranch: actualName,
+          workspaceId: followUp.id,
+          oldBranch: followUp.oldBranch,
+          newBranch: followUp.newBranch,
           isSubmitting: false,
           error: null,
         });
@@ -380,6 +380,30 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     );
   }
 
+  const markWorkspaceHealth = useCallback((id: string, healthy: boolean) => {
+    setWorkspaces((prev) =>
+      prev.map((entry) =>
+        entry.id === id && entry.connected
+          ? { ...entry, connected: healthy ? true : "unhealthy" }
+          : entry,
+      ),
+    );
+  }, []);
+
+  const markWorkspaceDisconnected = useCallback((id: string) => {
+    setWorkspaces((prev) =>
+      prev.map((entry) => (entry.id === id ? { ...entry, connected: false } : entry)),
+    );
+  }, []);
+
+  const markWorkspacePathAvailable = useCallback((id: string) => {
+    setWorkspaces((prev) =>
+      prev.map((entry) =>
+        entry.id === id ? { ...entry, pathUnavailable: false } : entry,
+      ),
+    );
+  }, []);
+
   const updateWorkspaceSettings = useCallback(
     async (workspaceId: string, settings: WorkspaceSettings) => {
       onDebug?.({
@@ -829,6 +853,9 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     addWorktreeAgent,
     connectWorkspace,
     markWorkspaceConnected,
+    markWorkspaceHealth,
+    markWorkspaceDisconnected,
+    markWorkspacePathAvailable,
     updateWorkspaceSettings,
     updateWorkspaceCodexBin,
     createWorkspaceGroup,
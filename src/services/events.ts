@@ -1,5 +1,12 @@
 import { listen } from "@tauri-apps/api/event";
-import type { AppServerEvent, DictationEvent, DictationModelStatus } from "../types";
+import type {
+  AppServerEvent,
+  BackendErrorEvent,
+  DictationEvent,
+  DictationModelStatus,
+  FiredNotification,
+  QuickCommandOutputEvent,
+} from "../types";
 
 export type Unsubscribe = () => void;
 
@@ -9,6 +16,12 @@ export type TerminalOutputEvent = {
   data: string;
 };
 
+export type TerminalExitEvent = {
+  workspaceId: string;
+  terminalId: string;
+  exitCode: number | null;
+};
+
 type SubscriptionOptions = {
   onError?: (error: unknown) => void;
 };
@@ -80,6 +93,14 @@ const appServerHub = createEventHub<AppServerEvent>("app-server-event");
 const dictationDownloadHub = createEventHub<DictationModelStatus>("dictation-download");
 const dictationEventHub = createEventHub<DictationEvent>("dictation-event");
 const terminalOutputHub = createEventHub<TerminalOutputEvent>("terminal-output");
+const terminalExitHub = createEventHub<TerminalExitEvent>("terminal-exit");
+const backendErrorHub = createEventHub<BackendErrorEvent>("backend-error");
+const quickCommandOutputHub = createEventHub<QuickCommandOutputEvent>(
+  "quick-command-output",
+);
+const notificationRuleFiredHub = createEventHub<FiredNotification>(
+  "notification-rule-fired",
+);
 const updaterCheckHub = createEventHub<void>("updater-check");
 const menuNewAgentHub = createEventHub<void>("menu-new-agent");
 const menuNewWorktreeAgentHub = createEventHub<void>("menu-new-worktree-agent");
@@ -129,6 +150,34 @@ export function subscribeTerminalOutput(
   return terminalOutputHub.subscribe(onEvent, options);
 }
 
+export function subscribeTerminalExit(
+  onEvent: (event: TerminalExitEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return terminalExitHub.subscribe(onEvent, options);
+}
+
+export function subscribeBackendErrors(
+  onEvent: (event: BackendErrorEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return backendErrorHub.subscribe(onEvent, options);
+}
+
+export function subscribeQuickCommandOutput(
+  onEvent: (event: QuickCommandOutputEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return quickCommandOutputHub.subscribe(onEvent, options);
+}
+
+export function subscribeNotificationRuleFired(
+  onEvent: (event: FiredNotification) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return notificationRuleFiredHub.subscribe(onEvent, options);
+}
+
 export function subscribeUpdaterCheck(
   onEvent: () => void,
   options?: SubscriptionOptions,
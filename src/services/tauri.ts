@@ -2,37 +2,102 @@ import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import type {
   AppSettings,
+  AppSettingsUpdate,
+  ArchiveThreadResult,
+  AutoArchiveCandidate,
   CodexDoctorResult,
+  CommitMessagePrompt,
+  CommitMessageResult,
+  ComposedMessage,
   DictationModelStatus,
   DictationSessionState,
+  EffectiveCodexArgs,
+  FiredNotification,
   LocalUsageSnapshot,
+  MemoryConnectionReport,
   MemoryEntry,
   MemorySearchResult,
   MemoryStatus,
+  MessagePart,
+  MessageSizeEstimate,
+  OnboardingBootstrapOptions,
+  OnboardingBootstrapReport,
+  OnboardingStatus,
+  SessionCapabilities,
   SessionThreadInfo,
+  SettingsAuditEntry,
+  TailChunk,
+  TerminalListEntry,
+  TerminalScrollback,
+  TerminalSearchOptions,
+  TerminalSearchResult,
+  TerminalSnapshot,
+  ThreadOverrides,
+  ThreadsOverview,
+  TurnCommitResult,
+  TurnPolicy,
+  UsageOverview,
+  WorkspaceDoctorResult,
+  WorkspaceFileChunk,
+  WorkspaceImportReport,
   WorkspaceInfo,
+  WorkspaceSearchOptions,
+  WorkspaceSearchResult,
   WorkspaceSettings,
+  WorktreeCompareMode,
+  WorktreeComparison,
+  WorktreeReport,
+  RenamedWorktree,
+  DeleteDomainOptions,
   Domain,
+  DomainDeleteResult,
+  DomainSaveResult,
   DomainTrendSnapshot,
+  DomainUsage,
+  StreamAppend,
+  TrendRange,
+  Bill,
   DeliveryDashboard,
+  DomainEntity,
+  DomainEntityPage,
   ExerciseDashboard,
   FinanceDashboard,
+  MediaItem,
   MediaLibrary,
   MediaCoverSummary,
+  MediaProviderStatus,
+  LifeReport,
+  LifeReportDomain,
+  LifeReportFormat,
   LifeTimeRange,
   NutritionDashboard,
   YouTubeLibrary,
 } from "../types";
 import type {
+  AttachmentInfo,
+  DataUsage,
+  GhAuthStatus,
   GitFileDiff,
   GitFileStatus,
   GitCommitDiff,
+  GitCommitOutcome,
+  GitDiffHunk,
   GitHubIssuesResponse,
   GitHubPullRequestComment,
   GitHubPullRequestDiff,
   GitHubPullRequestsResponse,
   GitLogResponse,
+  GitStashEntry,
+  GitTagInfo,
+  PromptScope,
+  QuickCommandEntry,
+  QuickCommandExecution,
+  ResourceUsage,
+  RevertUndoEntry,
+  ReviewRecord,
   ReviewTarget,
+  ThreadSummary,
+  WorktreeApplyPreview,
 } from "../types";
 
 function isMissingTauriInvokeError(error: unknown) {
@@ -89,45 +154,89 @@ export async function listDomains(): Promise<Domain[]> {
   return invoke<Domain[]>("domains_list");
 }
 
-export async function createDomain(domain: Domain): Promise<Domain> {
-  return invoke<Domain>("domains_create", domain);
+export async function createDomain(domain: Domain): Promise<DomainSaveResult> {
+  return invoke<DomainSaveResult>("domains_create", domain);
 }
 
-export async function updateDomain(domain: Domain): Promise<Domain> {
-  return invoke<Domain>("domains_update", domain);
+export async function updateDomain(domain: Domain): Promise<DomainSaveResult> {
+  return invoke<DomainSaveResult>("domains_update", domain);
 }
 
-export async function deleteDomain(domainId: string): Promise<void> {
-  return invoke("domains_delete", { domainId });
+/** Fails with `domain_in_use: {...}` when workspaces use the domain and
+ * neither `reassignTo` nor `force` is given. */
+export async function deleteDomain(
+  domainId: string,
+  options: DeleteDomainOptions = {},
+): Promise<DomainDeleteResult> {
+  return invoke<DomainDeleteResult>("domains_delete", {
+    domainId,
+    reassignTo: options.reassignTo ?? null,
+    force: options.force ?? null,
+  });
+}
+
+export async function getDomainUsage(domainId: string): Promise<DomainUsage> {
+  return invoke<DomainUsage>("domain_usage", { domainId });
+}
+
+/** The zone this client counts days in, sent so "today" and date ranges
+ * follow it rather than UTC. */
+export function clientTimezone(): string | null {
+  try {
+    return Intl.DateTimeFormat().resolvedOptions().timeZone ?? null;
+  } catch {
+    return null;
+  }
 }
 
 export async function getDomainTrends(
   workspaceId: string,
   domainId: string,
-  range: "7d" | "30d" | "lifetime",
+  range: TrendRange,
+  compare = false,
+  forceRefresh = false,
 ): Promise<DomainTrendSnapshot> {
-  return invoke<DomainTrendSnapshot>("domain_trends", { workspaceId, domainId, range });
+  return invoke<DomainTrendSnapshot>("domain_trends", {
+    workspaceId,
+    domainId,
+    range,
+    compare,
+    forceRefresh,
+    timezone: clientTimezone(),
+  });
 }
 
 export async function getDeliveryDashboard(
   workspaceId: string,
   range: LifeTimeRange,
 ): Promise<DeliveryDashboard> {
-  return invoke<DeliveryDashboard>("get_delivery_dashboard", { workspaceId, range });
+  return invoke<DeliveryDashboard>("get_delivery_dashboard", {
+    workspaceId,
+    range,
+    timezone: clientTimezone(),
+  });
 }
 
 export async function getNutritionDashboard(
   workspaceId: string,
   range: LifeTimeRange,
 ): Promise<NutritionDashboard> {
-  return invoke<NutritionDashboard>("get_nutrition_dashboard", { workspaceId, range });
+  return invoke<NutritionDashboard>("get_nutrition_dashboard", {
+    workspaceId,
+    range,
+    timezone: clientTimezone(),
+  });
 }
 
 export async function getExerciseDashboard(
   workspaceId: string,
   range: LifeTimeRange,
 ): Promise<ExerciseDashboard> {
-  return invoke<ExerciseDashboard>("get_exercise_dashboard", { workspaceId, range });
+  return invoke<ExerciseDashboard>("get_exercise_dashboard", {
+    workspaceId,
+    range,
+    timezone: clientTimezone(),
+  });
 }
 
 export async function getMediaDashboard(
@@ -146,14 +255,103 @@ export async function getFinanceDashboard(
   workspaceId: string,
   range: LifeTimeRange,
 ): Promise<FinanceDashboard> {
-  return invoke<FinanceDashboard>("get_finance_dashboard", { workspaceId, range });
+  return invoke<FinanceDashboard>("get_finance_dashboard", {
+    workspaceId,
+    range,
+    timezone: clientTimezone(),
+  });
+}
+
+export async function exportLifeReport(
+  workspaceId: string,
+  range: LifeTimeRange,
+  options: {
+    domains?: LifeReportDomain[];
+    format?: LifeReportFormat;
+    save?: boolean;
+    overwrite?: boolean;
+  } = {},
+): Promise<LifeReport> {
+  return invoke<LifeReport>("export_life_report", {
+    workspaceId,
+    range,
+    timezone: clientTimezone(),
+    ...options,
+  });
+}
+
+export async function markBillPaid(
+  workspaceId: string,
+  billPath: string,
+  paidDate?: string,
+): Promise<Bill> {
+  return invoke<Bill>("mark_bill_paid", { workspaceId, billPath, paidDate });
+}
+
+export async function appendStreamEntry(
+  workspaceId: string,
+  time: string,
+  text: string,
+  date?: string,
+): Promise<StreamAppend> {
+  return invoke<StreamAppend>("stream_append", {
+    workspaceId,
+    date,
+    time,
+    text,
+    timezone: clientTimezone(),
+  });
+}
+
+export async function updateMediaItem(
+  workspaceId: string,
+  path: string,
+  update: { status?: "completed" | "backlog"; rating?: number; completedAt?: string },
+): Promise<MediaItem> {
+  return invoke<MediaItem>("update_media_item", {
+    workspaceId,
+    path,
+    status: update.status ?? null,
+    rating: update.rating ?? null,
+    completedAt: update.completedAt ?? null,
+  });
 }
 
 export async function enrichMediaCovers(
   workspaceId: string,
   force = false,
+  dryRun = false,
 ): Promise<MediaCoverSummary> {
-  return invoke<MediaCoverSummary>("enrich_media_covers", { workspaceId, force });
+  return invoke<MediaCoverSummary>("enrich_media_covers", {
+    workspaceId,
+    force,
+    dryRun,
+  });
+}
+
+export async function mediaProvidersStatus(): Promise<MediaProviderStatus[]> {
+  return invoke<MediaProviderStatus[]>("media_providers_status");
+}
+
+export async function listDomainEntities(
+  workspaceId: string,
+  domainId: string,
+  options?: { kind?: string | null; offset?: number; limit?: number },
+): Promise<DomainEntityPage> {
+  return invoke<DomainEntityPage>("list_domain_entities", {
+    workspaceId,
+    domainId,
+    kind: options?.kind ?? null,
+    offset: options?.offset ?? null,
+    limit: options?.limit ?? null,
+  });
+}
+
+export async function readDomainEntity(
+  workspaceId: string,
+  path: string,
+): Promise<DomainEntity> {
+  return invoke<DomainEntity>("read_domain_entity", { workspaceId, path });
 }
 
 export async function addWorkspace(
@@ -163,6 +361,18 @@ export async function addWorkspace(
   return invoke<WorkspaceInfo>("add_workspace", { path, codex_bin });
 }
 
+export async function scanAndImportWorkspaces(
+  rootPath: string,
+  maxDepth?: number,
+  dryRun = false,
+): Promise<WorkspaceImportReport> {
+  return invoke<WorkspaceImportReport>("scan_and_import_workspaces", {
+    rootPath,
+    maxDepth: maxDepth ?? null,
+    dryRun,
+  });
+}
+
 export async function isWorkspacePathDir(path: string): Promise<boolean> {
   return invoke<boolean>("is_workspace_path_dir", { path });
 }
@@ -182,8 +392,13 @@ export async function addClone(
 export async function addWorktree(
   parentId: string,
   branch: string,
+  keepOnSpawnFailure = false,
 ): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("add_worktree", { parentId, branch });
+  return invoke<WorkspaceInfo>("add_worktree", {
+    parentId,
+    branch,
+    keepOnSpawnFailure,
+  });
 }
 
 export async function updateWorkspaceSettings(
@@ -193,6 +408,31 @@ export async function updateWorkspaceSettings(
   return invoke<WorkspaceInfo>("update_workspace_settings", { id, settings });
 }
 
+export async function updateWorkspaceSettingsPartial(
+  id: string,
+  patch: Partial<WorkspaceSettings>,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("update_workspace_settings_partial", { id, patch });
+}
+
+export async function getEffectiveCodexArgs(
+  workspaceId: string,
+): Promise<EffectiveCodexArgs> {
+  return invoke<EffectiveCodexArgs>("get_effective_codex_args", { workspaceId });
+}
+
+export async function setWorkspaceCodexArgs(
+  workspaceId: string,
+  args: string[],
+  confirmDangerous = false,
+): Promise<EffectiveCodexArgs> {
+  return invoke<EffectiveCodexArgs>("set_workspace_codex_args", {
+    workspaceId,
+    args,
+    confirmDangerous,
+  });
+}
+
 export async function updateWorkspaceCodexBin(
   id: string,
   codex_bin: string | null,
@@ -200,19 +440,44 @@ export async function updateWorkspaceCodexBin(
   return invoke<WorkspaceInfo>("update_workspace_codex_bin", { id, codex_bin });
 }
 
-export async function removeWorkspace(id: string): Promise<void> {
-  return invoke("remove_workspace", { id });
+export async function removeWorkspace(
+  id: string,
+  force = false,
+): Promise<void> {
+  return invoke("remove_workspace", { id, force });
+}
+
+export async function removeWorktree(id: string, force = false): Promise<void> {
+  return invoke("remove_worktree", { id, force });
+}
+
+export async function getWorktreeReport(
+  parentWorkspaceId: string,
+  removeSafe = false,
+): Promise<WorktreeReport> {
+  return invoke<WorktreeReport>("worktree_report", {
+    parentWorkspaceId,
+    removeSafe,
+  });
 }
 
-export async function removeWorktree(id: string): Promise<void> {
-  return invoke("remove_worktree", { id });
+export async function compareWorktrees(
+  workspaceIdA: string,
+  workspaceIdB: string,
+  mode: WorktreeCompareMode,
+): Promise<WorktreeComparison> {
+  return invoke<WorktreeComparison>("compare_worktrees", {
+    workspaceIdA,
+    workspaceIdB,
+    mode,
+  });
 }
 
 export async function renameWorktree(
   id: string,
   branch: string,
-): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("rename_worktree", { id, branch });
+): Promise<RenamedWorktree> {
+  return invoke<RenamedWorktree>("rename_worktree", { id, branch });
 }
 
 export async function renameWorktreeUpstream(
@@ -223,8 +488,25 @@ export async function renameWorktreeUpstream(
   return invoke("rename_worktree_upstream", { id, oldBranch, newBranch });
 }
 
-export async function applyWorktreeChanges(workspaceId: string): Promise<void> {
-  return invoke("apply_worktree_changes", { workspaceId });
+export async function applyWorktreeChanges(
+  workspaceId: string,
+  onlyPaths?: string[],
+): Promise<void> {
+  await invoke("apply_worktree_changes", {
+    workspaceId,
+    onlyPaths: onlyPaths ?? null,
+  });
+}
+
+export async function previewWorktreeChanges(
+  workspaceId: string,
+  onlyPaths?: string[],
+): Promise<WorktreeApplyPreview> {
+  return invoke<WorktreeApplyPreview>("apply_worktree_changes", {
+    workspaceId,
+    dryRun: true,
+    onlyPaths: onlyPaths ?? null,
+  });
 }
 
 export async function openWorkspaceIn(path: string, app: string): Promise<void> {
@@ -239,6 +521,18 @@ export async function startThread(workspaceId: string) {
   return invoke<any>("start_thread", { workspaceId });
 }
 
+export async function uploadAttachment(
+  workspaceId: string,
+  filename: string,
+  dataBase64: string,
+): Promise<AttachmentInfo> {
+  return invoke<AttachmentInfo>("upload_attachment", {
+    workspaceId,
+    filename,
+    dataBase64,
+  });
+}
+
 export async function sendUserMessage(
   workspaceId: string,
   threadId: string,
@@ -249,6 +543,11 @@ export async function sendUserMessage(
     accessMode?: "read-only" | "current" | "full-access";
     images?: string[];
     collaborationMode?: Record<string, unknown> | null;
+    fileRefs?: string[];
+    bestEffort?: boolean;
+    disableRedaction?: boolean;
+    disableRecall?: boolean;
+    extras?: Record<string, unknown> | null;
   },
 ) {
   return invoke("send_user_message", {
@@ -260,6 +559,79 @@ export async function sendUserMessage(
     accessMode: options?.accessMode ?? null,
     images: options?.images ?? null,
     collaborationMode: options?.collaborationMode ?? null,
+    fileRefs: options?.fileRefs ?? null,
+    bestEffort: options?.bestEffort ?? null,
+    disableRedaction: options?.disableRedaction,
+    disableRecall: options?.disableRecall,
+    extras: options?.extras ?? null,
+  });
+}
+
+export async function previewTurnPolicy(
+  workspaceId: string,
+  accessMode?: "read-only" | "current" | "full-access",
+): Promise<TurnPolicy> {
+  return invoke<TurnPolicy>("preview_turn_policy", {
+    workspaceId,
+    accessMode: accessMode ?? null,
+  });
+}
+
+export async function estimateMessageSize(
+  workspaceId: string,
+  text: string,
+  options?: {
+    threadId?: string | null;
+    model?: string | null;
+    images?: string[];
+    collaborationMode?: Record<string, unknown> | null;
+    fileRefs?: string[];
+    bestEffort?: boolean;
+    disableRedaction?: boolean;
+    disableRecall?: boolean;
+  },
+): Promise<MessageSizeEstimate> {
+  return invoke<MessageSizeEstimate>("estimate_message_size", {
+    workspaceId,
+    threadId: options?.threadId ?? null,
+    text,
+    model: options?.model ?? null,
+    images: options?.images ?? null,
+    collaborationMode: options?.collaborationMode ?? null,
+    fileRefs: options?.fileRefs ?? null,
+    bestEffort: options?.bestEffort ?? null,
+    disableRedaction: options?.disableRedaction,
+    disableRecall: options?.disableRecall,
+  });
+}
+
+export async function composeMessage(
+  workspaceId: string,
+  parts: MessagePart[],
+  options?: {
+    maxBytes?: number;
+    send?: boolean;
+    threadId?: string | null;
+    model?: string | null;
+    effort?: string | null;
+    accessMode?: "read-only" | "current" | "full-access";
+    collaborationMode?: Record<string, unknown> | null;
+    disableRedaction?: boolean;
+    disableRecall?: boolean;
+  },
+): Promise<ComposedMessage> {
+  return invoke<ComposedMessage>("compose_message", {
+    workspaceId,
+    parts,
+    maxBytes: options?.maxBytes ?? null,
+    send: options?.send ?? null,
+    threadId: options?.threadId ?? null,
+    model: options?.model ?? null,
+    effort: options?.effort ?? null,
+    accessMode: options?.accessMode ?? null,
+    collaborationMode: options?.collaborationMode ?? null,
+    disableRedaction: options?.disableRedaction,
+    disableRecall: options?.disableRecall,
   });
 }
 
@@ -276,14 +648,25 @@ export async function startReview(
   threadId: string,
   target: ReviewTarget,
   delivery?: "inline" | "detached",
+  acknowledgeLarge?: boolean,
 ) {
   const payload: Record<string, unknown> = { workspaceId, threadId, target };
   if (delivery) {
     payload.delivery = delivery;
   }
+  if (acknowledgeLarge) {
+    payload.acknowledgeLarge = true;
+  }
   return invoke("start_review", payload);
 }
 
+export async function listReviews(
+  workspaceId: string,
+  limit?: number,
+): Promise<ReviewRecord[]> {
+  return invoke("list_reviews", { workspaceId, limit: limit ?? null });
+}
+
 export async function respondToServerRequest(
   workspaceId: string,
   requestId: number | string,
@@ -304,7 +687,11 @@ export async function rememberApprovalRule(
   return invoke("remember_approval_rule", { workspaceId, command });
 }
 
-export async function getGitStatus(workspace_id: string): Promise<{
+export async function getGitStatus(
+  workspace_id: string,
+  gitRoot?: string | null,
+  forceRefresh?: boolean,
+): Promise<{
   branchName: string;
   files: GitFileStatus[];
   stagedFiles: GitFileStatus[];
@@ -312,7 +699,11 @@ export async function getGitStatus(workspace_id: string): Promise<{
   totalAdditions: number;
   totalDeletions: number;
 }> {
-  return invoke("get_git_status", { workspaceId: workspace_id });
+  return invoke("get_git_status", {
+    workspaceId: workspace_id,
+    gitRoot,
+    forceRefresh,
+  });
 }
 
 export async function listGitRoots(
@@ -324,22 +715,38 @@ export async function listGitRoots(
 
 export async function getGitDiffs(
   workspace_id: string,
+  gitRoot?: string | null,
+  intraline = false,
+  hunks = false,
 ): Promise<GitFileDiff[]> {
-  return invoke("get_git_diffs", { workspaceId: workspace_id });
+  return invoke("get_git_diffs", {
+    workspaceId: workspace_id,
+    gitRoot,
+    intraline,
+    hunks,
+  });
 }
 
 export async function getGitLog(
   workspace_id: string,
   limit = 40,
+  gitRoot?: string | null,
 ): Promise<GitLogResponse> {
-  return invoke("get_git_log", { workspaceId: workspace_id, limit });
+  return invoke("get_git_log", { workspaceId: workspace_id, limit, gitRoot });
 }
 
 export async function getGitCommitDiff(
   workspace_id: string,
   sha: string,
+  gitRoot?: string | null,
+  intraline = false,
 ): Promise<GitCommitDiff[]> {
-  return invoke("get_git_commit_diff", { workspaceId: workspace_id, sha });
+  return invoke("get_git_commit_diff", {
+    workspaceId: workspace_id,
+    sha,
+    gitRoot,
+    intraline,
+  });
 }
 
 export async function getGitRemote(workspace_id: string): Promise<string | null> {
@@ -350,6 +757,14 @@ export async function memoryStatus(): Promise<MemoryStatus> {
   return invoke<MemoryStatus>("memory_status");
 }
 
+export async function memoryTestConnection(
+  settings?: AppSettings,
+): Promise<MemoryConnectionReport> {
+  return invoke<MemoryConnectionReport>("memory_test_connection", {
+    settings: settings ?? null,
+  });
+}
+
 export async function memorySearch(
   query: string,
   limit = 10,
@@ -462,43 +877,143 @@ export async function skillsUninstall(
   });
 }
 
-export async function stageGitFile(workspaceId: string, path: string) {
-  return invoke("stage_git_file", { workspaceId, path });
+export async function stageGitFile(
+  workspaceId: string,
+  path: string,
+  gitRoot?: string | null,
+) {
+  return invoke("stage_git_file", { workspaceId, path, gitRoot });
+}
+
+/** Stages one unstaged hunk, picked by `index`, `header` or both. Rejects
+ * with "hunk is stale, refresh diffs" when it no longer matches. */
+export async function stageGitHunk(
+  workspaceId: string,
+  path: string,
+  hunk: Pick<GitDiffHunk, "index" | "header">,
+  gitRoot?: string | null,
+) {
+  return invoke("stage_git_hunk", {
+    workspaceId,
+    path,
+    hunkIndex: hunk.index,
+    header: hunk.header,
+    gitRoot,
+  });
+}
+
+export async function stageGitAll(
+  workspaceId: string,
+  gitRoot?: string | null,
+): Promise<void> {
+  return invoke("stage_git_all", { workspaceId, gitRoot });
+}
+
+export async function unstageGitFile(
+  workspaceId: string,
+  path: string,
+  gitRoot?: string | null,
+) {
+  return invoke("unstage_git_file", { workspaceId, path, gitRoot });
+}
+
+export async function unstageGitHunk(
+  workspaceId: string,
+  path: string,
+  hunk: Pick<GitDiffHunk, "index" | "header">,
+  gitRoot?: string | null,
+) {
+  return invoke("unstage_git_hunk", {
+    workspaceId,
+    path,
+    hunkIndex: hunk.index,
+    header: hunk.header,
+    gitRoot,
+  });
 }
 
-export async function stageGitAll(workspaceId: string): Promise<void> {
-  return invoke("stage_git_all", { workspaceId });
+export async function revertGitFile(
+  workspaceId: string,
+  path: string,
+  gitRoot?: string | null,
+) {
+  return invoke("revert_git_file", { workspaceId, path, gitRoot });
 }
 
-export async function unstageGitFile(workspaceId: string, path: string) {
-  return invoke("unstage_git_file", { workspaceId, path });
+export async function revertGitAll(
+  workspaceId: string,
+  gitRoot?: string | null,
+) {
+  return invoke("revert_git_all", { workspaceId, gitRoot });
 }
 
-export async function revertGitFile(workspaceId: string, path: string) {
-  return invoke("revert_git_file", { workspaceId, path });
+export async function listRevertUndos(workspaceId: string) {
+  return invoke<RevertUndoEntry[]>("list_revert_undos", { workspaceId });
 }
 
-export async function revertGitAll(workspaceId: string) {
-  return invoke("revert_git_all", { workspaceId });
+export async function restoreRevertUndo(
+  workspaceId: string,
+  undoId: string,
+  gitRoot?: string | null,
+) {
+  return invoke<RevertUndoEntry>("restore_revert_undo", {
+    workspaceId,
+    undoId,
+    gitRoot,
+  });
 }
 
 export async function commitGit(
   workspaceId: string,
   message: string,
-): Promise<void> {
-  return invoke("commit_git", { workspaceId, message });
+  gitRoot?: string | null,
+  options?: {
+    amend?: boolean;
+    signoff?: boolean;
+    noVerify?: boolean;
+    overrideProtection?: boolean;
+  },
+): Promise<GitCommitOutcome> {
+  return invoke<GitCommitOutcome>("commit_git", {
+    workspaceId,
+    message,
+    gitRoot,
+    amend: options?.amend ?? false,
+    signoff: options?.signoff ?? false,
+    noVerify: options?.noVerify ?? false,
+    overrideProtection: options?.overrideProtection ?? false,
+  });
 }
 
-export async function pushGit(workspaceId: string): Promise<void> {
-  return invoke("push_git", { workspaceId });
+export async function getLastCommitMessage(
+  workspaceId: string,
+  gitRoot?: string | null,
+): Promise<string | null> {
+  return invoke<string | null>("get_last_commit_message", {
+    workspaceId,
+    gitRoot,
+  });
 }
 
-export async function pullGit(workspaceId: string): Promise<void> {
-  return invoke("pull_git", { workspaceId });
+export async function pushGit(
+  workspaceId: string,
+  gitRoot?: string | null,
+): Promise<void> {
+  return invoke("push_git", { workspaceId, gitRoot });
 }
 
-export async function syncGit(workspaceId: string): Promise<void> {
-  return invoke("sync_git", { workspaceId });
+export async function pullGit(
+  workspaceId: string,
+  gitRoot?: string | null,
+): Promise<void> {
+  return invoke("pull_git", { workspaceId, gitRoot });
+}
+
+export async function syncGit(
+  workspaceId: string,
+  gitRoot?: string | null,
+): Promise<void> {
+  return invoke("sync_git", { workspaceId, gitRoot });
 }
 
 export async function getGitHubIssues(
@@ -516,10 +1031,12 @@ export async function getGitHubPullRequests(
 export async function getGitHubPullRequestDiff(
   workspace_id: string,
   prNumber: number,
+  intraline = false,
 ): Promise<GitHubPullRequestDiff[]> {
   return invoke("get_github_pull_request_diff", {
     workspaceId: workspace_id,
     prNumber,
+    intraline,
   });
 }
 
@@ -533,25 +1050,67 @@ export async function getGitHubPullRequestComments(
   });
 }
 
+export async function getGhAuthStatus(refresh = false): Promise<GhAuthStatus> {
+  return invoke("gh_auth_status", { refresh });
+}
+
 export async function localUsageSnapshot(
   days?: number,
   workspacePath?: string | null,
 ): Promise<LocalUsageSnapshot> {
-  const payload: { days: number; workspacePath?: string } = { days: days ?? 30 };
+  const payload: {
+    days: number;
+    workspacePath?: string;
+    timezone: string | null;
+  } = { days: days ?? 30, timezone: clientTimezone() };
   if (workspacePath) {
     payload.workspacePath = workspacePath;
   }
   return invoke("local_usage_snapshot", payload);
 }
 
+export async function getUsageOverview(
+  workspaceId?: string | null,
+): Promise<UsageOverview> {
+  return invoke<UsageOverview>("usage_overview", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
 export async function getModelList(workspaceId: string) {
   return invoke<any>("model_list", { workspaceId });
 }
 
+export async function getThreadOverrides(
+  workspaceId: string,
+  threadId: string,
+): Promise<ThreadOverrides | null> {
+  return invoke<ThreadOverrides | null>("get_thread_overrides", {
+    workspaceId,
+    threadId,
+  });
+}
+
+export async function clearThreadOverrides(
+  workspaceId: string,
+  threadId: string,
+): Promise<{ cleared: boolean }> {
+  return invoke<{ cleared: boolean }>("clear_thread_overrides", {
+    workspaceId,
+    threadId,
+  });
+}
+
 export async function getCollaborationModes(workspaceId: string) {
   return invoke<any>("collaboration_mode_list", { workspaceId });
 }
 
+export async function getSessionCapabilities(
+  workspaceId: string,
+): Promise<SessionCapabilities> {
+  return invoke<SessionCapabilities>("session_capabilities", { workspaceId });
+}
+
 export async function getAccountRateLimits(workspaceId: string) {
   return invoke<any>("account_rate_limits", { workspaceId });
 }
@@ -575,7 +1134,7 @@ export async function getGlobalPromptsDir() {
 export async function createPrompt(
   workspaceId: string,
   data: {
-    scope: "workspace" | "global";
+    scope: PromptScope;
     name: string;
     description?: string | null;
     argumentHint?: string | null;
@@ -618,7 +1177,7 @@ export async function deletePrompt(workspaceId: string, path: string) {
 
 export async function movePrompt(
   workspaceId: string,
-  data: { path: string; scope: "workspace" | "global" },
+  data: { path: string; scope: PromptScope },
 ) {
   return invoke<any>("prompts_move", {
     workspaceId,
@@ -635,6 +1194,41 @@ export async function updateAppSettings(settings: AppSettings): Promise<AppSetti
   return invoke<AppSettings>("update_app_settings", { settings });
 }
 
+export async function updateAppSettingsWithMemoryCheck(
+  settings: AppSettings,
+): Promise<AppSettingsUpdate> {
+  return invoke<AppSettingsUpdate>("update_app_settings", {
+    settings,
+    validateMemoryOnSave: true,
+  });
+}
+
+export async function updateAppSettingsPartial(
+  patch: Partial<AppSettings>,
+  validateMemoryOnSave = false,
+): Promise<AppSettingsUpdate> {
+  return invoke<AppSettingsUpdate>("update_app_settings_partial", {
+    patch,
+    validateMemoryOnSave,
+  });
+}
+
+export async function getSettingsAudit(limit?: number): Promise<SettingsAuditEntry[]> {
+  return invoke<SettingsAuditEntry[]>("settings_audit", { limit: limit ?? null });
+}
+
+export async function testNotificationRule(ruleId: string): Promise<FiredNotification> {
+  return invoke<FiredNotification>("notification_rules_test", { ruleId });
+}
+
+export async function getDataUsage(): Promise<DataUsage> {
+  return invoke<DataUsage>("data_usage");
+}
+
+export async function getResourceUsage(): Promise<ResourceUsage> {
+  return invoke<ResourceUsage>("resource_usage");
+}
+
 type MenuAcceleratorUpdate = {
   id: string;
   accelerator: string | null;
@@ -652,20 +1246,116 @@ export async function runCodexDoctor(
   return invoke<CodexDoctorResult>("codex_doctor", { codexBin });
 }
 
+export async function workspaceDoctor(
+  workspaceId: string,
+): Promise<WorkspaceDoctorResult> {
+  return invoke<WorkspaceDoctorResult>("workspace_doctor", { workspaceId });
+}
+
+export async function getOnboardingStatus(): Promise<OnboardingStatus> {
+  return invoke<OnboardingStatus>("onboarding_status");
+}
+
+export async function onboardingBootstrap(
+  path: string,
+  options: OnboardingBootstrapOptions = {},
+): Promise<OnboardingBootstrapReport> {
+  return invoke<OnboardingBootstrapReport>("onboarding_bootstrap", {
+    path,
+    options,
+  });
+}
+
 export async function getWorkspaceFiles(workspaceId: string) {
   return invoke<string[]>("list_workspace_files", { workspaceId });
 }
 
+/** Reads up to `length` bytes (400 KB by default) from byte `offset`. Page on
+ * with `nextOffset` while `truncated` is set. */
 export async function readWorkspaceFile(
   workspaceId: string,
   path: string,
-): Promise<{ content: string; truncated: boolean }> {
-  return invoke<{ content: string; truncated: boolean }>("read_workspace_file", {
+  offset?: number,
+  length?: number,
+): Promise<WorkspaceFileChunk> {
+  return invoke<WorkspaceFileChunk>("read_workspace_file", {
+    workspaceId,
+    path,
+    offset: offset ?? null,
+    length: length ?? null,
+  });
+}
+
+export async function searchWorkspaceFiles(
+  workspaceId: string,
+  query: string,
+  options: WorkspaceSearchOptions = {},
+): Promise<WorkspaceSearchResult> {
+  return invoke<WorkspaceSearchResult>("search_workspace_files", {
+    workspaceId,
+    query,
+    options,
+  });
+}
+
+export async function tailWorkspaceFile(
+  workspaceId: string,
+  path: string,
+  fromOffset = 0,
+  maxBytes?: number,
+): Promise<TailChunk> {
+  return invoke<TailChunk>("tail_workspace_file", {
+    workspaceId,
+    path,
+    fromOffset,
+    maxBytes: maxBytes ?? null,
+  });
+}
+
+export async function followFile(
+  workspaceId: string,
+  path: string,
+  fromOffset?: number,
+): Promise<{ ok: boolean; offset: number }> {
+  return invoke<{ ok: boolean; offset: number }>("follow_file", {
+    workspaceId,
+    path,
+    fromOffset: fromOffset ?? null,
+  });
+}
+
+export async function unfollowFile(
+  workspaceId: string,
+  path: string,
+): Promise<{ ok: boolean; removed: boolean }> {
+  return invoke<{ ok: boolean; removed: boolean }>("unfollow_file", {
     workspaceId,
     path,
   });
 }
 
+export async function listQuickCommands(
+  workspaceId: string,
+): Promise<QuickCommandEntry[]> {
+  return invoke<QuickCommandEntry[]>("list_quick_commands", { workspaceId });
+}
+
+export async function runQuickCommand(
+  workspaceId: string,
+  commandId: string,
+): Promise<QuickCommandExecution> {
+  return invoke<QuickCommandExecution>("run_quick_command", {
+    workspaceId,
+    commandId,
+  });
+}
+
+export async function cancelQuickCommand(
+  executionId: string,
+): Promise<{ ok: boolean }> {
+  return invoke<{ ok: boolean }>("cancel_quick_command", { executionId });
+}
+
 export async function readGlobalAgentsMd(): Promise<TextFileResponse> {
   return invoke<TextFileResponse>("read_global_agents_md");
 }
@@ -682,16 +1372,101 @@ export async function writeGlobalCodexConfigToml(content: string): Promise<void>
   return invoke("write_global_config_toml", { content });
 }
 
-export async function listGitBranches(workspaceId: string) {
-  return invoke<any>("list_git_branches", { workspaceId });
+export async function listGitBranches(
+  workspaceId: string,
+  gitRoot?: string | null,
+) {
+  return invoke<any>("list_git_branches", { workspaceId, gitRoot });
 }
 
-export async function checkoutGitBranch(workspaceId: string, name: string) {
-  return invoke("checkout_git_branch", { workspaceId, name });
+export async function checkoutGitBranch(
+  workspaceId: string,
+  name: string,
+  gitRoot?: string | null,
+) {
+  return invoke("checkout_git_branch", { workspaceId, name, gitRoot });
 }
 
-export async function createGitBranch(workspaceId: string, name: string) {
-  return invoke("create_git_branch", { workspaceId, name });
+export async function createGitBranch(
+  workspaceId: string,
+  name: string,
+  gitRoot?: string | null,
+) {
+  return invoke("create_git_branch", { workspaceId, name, gitRoot });
+}
+
+export async function listGitTags(
+  workspaceId: string,
+  options?: { pattern?: string | null; limit?: number | null },
+): Promise<GitTagInfo[]> {
+  return invoke<GitTagInfo[]>("list_git_tags", {
+    workspaceId,
+    pattern: options?.pattern ?? null,
+    limit: options?.limit ?? null,
+  });
+}
+
+export async function createGitTag(
+  workspaceId: string,
+  name: string,
+  options?: { message?: string | null; target?: string | null; push?: boolean },
+): Promise<GitTagInfo> {
+  return invoke<GitTagInfo>("create_git_tag", {
+    workspaceId,
+    name,
+    message: options?.message ?? null,
+    target: options?.target ?? null,
+    push: options?.push ?? false,
+  });
+}
+
+export async function deleteGitTag(
+  workspaceId: string,
+  name: string,
+  overrideProtection = false,
+) {
+  return invoke("delete_git_tag", { workspaceId, name, overrideProtection });
+}
+
+export async function stashGit(
+  workspaceId: string,
+  options?: {
+    message?: string | null;
+    includeUntracked?: boolean;
+    gitRoot?: string | null;
+  },
+): Promise<GitStashEntry> {
+  return invoke<GitStashEntry>("stash_git", {
+    workspaceId,
+    gitRoot: options?.gitRoot ?? null,
+    message: options?.message ?? null,
+    includeUntracked: options?.includeUntracked ?? false,
+  });
+}
+
+export async function listGitStashes(
+  workspaceId: string,
+  gitRoot?: string | null,
+): Promise<GitStashEntry[]> {
+  return invoke<GitStashEntry[]>("list_git_stashes", { workspaceId, gitRoot });
+}
+
+/** Rejects with an error starting with `stash_conflict:` when the stash does
+ * not apply cleanly; the stash is kept then. */
+export async function popGitStash(
+  workspaceId: string,
+  index: number,
+  gitRoot?: string | null,
+) {
+  return invoke("pop_git_stash", { workspaceId, index, gitRoot });
+}
+
+export async function dropGitStash(
+  workspaceId: string,
+  index: number,
+  gitRoot?: string | null,
+) {
+  return invoke("drop_git_stash", { workspaceId, index, gitRoot });
 }
 
 function withModelId(modelId?: string | null) {
@@ -753,8 +1528,16 @@ export async function openTerminalSession(
   terminalId: string,
   cols: number,
   rows: number,
+  options?: { initialCommand?: string | null; loginShell?: boolean },
 ): Promise<{ id: string }> {
-  return invoke("terminal_open", { workspaceId, terminalId, cols, rows });
+  return invoke("terminal_open", {
+    workspaceId,
+    terminalId,
+    cols,
+    rows,
+    initialCommand: options?.initialCommand ?? null,
+    loginShell: options?.loginShell ?? false,
+  });
 }
 
 export async function writeTerminalSession(
@@ -781,6 +1564,49 @@ export async function closeTerminalSession(
   return invoke("terminal_close", { workspaceId, terminalId });
 }
 
+export async function listTerminalSessions(
+  workspaceId: string,
+): Promise<TerminalListEntry[]> {
+  return invoke<TerminalListEntry[]>("terminal_list", { workspaceId });
+}
+
+export async function getTerminalScrollback(
+  workspaceId: string,
+  terminalId: string,
+): Promise<TerminalScrollback> {
+  return invoke<TerminalScrollback>("terminal_scrollback", {
+    workspaceId,
+    terminalId,
+  });
+}
+
+/** Scrollback plus the terminal size, to repaint after reconnecting. */
+export async function getTerminalSnapshot(
+  workspaceId: string,
+  terminalId: string,
+): Promise<TerminalSnapshot> {
+  return invoke<TerminalSnapshot>("terminal_snapshot", {
+    workspaceId,
+    terminalId,
+  });
+}
+
+/** Fails with `Invalid regex: ...` when `options.regex` is set and the query
+ * doesn't parse. */
+export async function searchTerminal(
+  workspaceId: string,
+  terminalId: string,
+  query: string,
+  options: TerminalSearchOptions = {},
+): Promise<TerminalSearchResult> {
+  return invoke<TerminalSearchResult>("terminal_search", {
+    workspaceId,
+    terminalId,
+    query,
+    options,
+  });
+}
+
 export async function listThreads(
   workspaceId: string,
   cursor?: string | null,
@@ -809,14 +1635,80 @@ export async function archiveThread(workspaceId: string, threadId: string) {
   return invoke<any>("archive_thread", { workspaceId, threadId });
 }
 
+export async function archiveThreads(
+  workspaceId: string,
+  threadIds: string[],
+): Promise<ArchiveThreadResult[]> {
+  return invoke<ArchiveThreadResult[]>("archive_threads", { workspaceId, threadIds });
+}
+
+export async function getAutoArchivePreview(
+  inactiveDays?: number,
+): Promise<AutoArchiveCandidate[]> {
+  return invoke<AutoArchiveCandidate[]>("auto_archive_preview", {
+    inactiveDays: inactiveDays ?? null,
+  });
+}
+
+export async function setPinnedThreads(keys: string[]): Promise<void> {
+  return invoke("set_pinned_threads", { keys });
+}
+
+export async function getThreadsOverview(
+  workspaceId: string,
+): Promise<ThreadsOverview> {
+  return invoke<ThreadsOverview>("threads_overview", { workspaceId });
+}
+
+export async function markThreadViewed(
+  workspaceId: string,
+  threadId: string,
+): Promise<void> {
+  return invoke("mark_thread_viewed", { workspaceId, threadId });
+}
+
+export async function markWorkspaceViewed(workspaceId: string): Promise<void> {
+  return invoke("mark_workspace_viewed", { workspaceId });
+}
+
 export async function getCommitMessagePrompt(
   workspaceId: string,
-): Promise<string> {
-  return invoke("get_commit_message_prompt", { workspaceId });
+  disableRedaction?: boolean,
+): Promise<CommitMessagePrompt> {
+  return invoke("get_commit_message_prompt", { workspaceId, disableRedaction });
 }
 
 export async function generateCommitMessage(
   workspaceId: string,
-): Promise<string> {
-  return invoke("generate_commit_message", { workspaceId });
+  disableRedaction?: boolean,
+): Promise<CommitMessageResult> {
+  return invoke("generate_commit_message", { workspaceId, disableRedaction });
+}
+
+export async function commitTurnChanges(
+  workspaceId: string,
+  threadId: string,
+  turnId: string,
+  message?: string | null,
+  allowDirty?: boolean,
+): Promise<TurnCommitResult> {
+  return invoke<TurnCommitResult>("commit_turn_changes", {
+    workspaceId,
+    threadId,
+    turnId,
+    message: message ?? null,
+    allowDirty,
+  });
+}
+
+export async function summarizeThread(
+  workspaceId: string,
+  threadId: string,
+  startNew = false,
+): Promise<ThreadSummary> {
+  return invoke<ThreadSummary>("summarize_thread", {
+    workspaceId,
+    threadId,
+    startNew,
+  });
 }
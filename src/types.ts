@@ -1,3 +1,5 @@
+import type { NutritionGoals, VaultContribution } from "./features/life/types";
+
 export type WorkspacePurpose = "coding" | "life";
 
 export type WorkspaceSettings = {
@@ -11,6 +13,76 @@ export type WorkspaceSettings = {
   applyDomainInstructions?: boolean | null;
   purpose?: WorkspacePurpose | null;
   obsidianRoot?: string | null;
+  obsidianRoots?: string[];
+  entityDirs?: Record<string, string>;
+  dateOrder?: DateOrder;
+  protectedBranches?: string[];
+  diffIgnoreWhitespace?: boolean;
+  quickCommands?: QuickCommand[];
+  /** Used when the vault has no `Entities/Food/Goals.md`. */
+  nutritionGoals?: NutritionGoals | null;
+  /** Lowercase `#rrggbb`; worktrees get a muted shade of their parent's. */
+  color?: string | null;
+  /** An emoji or a built-in icon name. */
+  icon?: string | null;
+  /** Refresh GitHub issues, pull requests and checks in the background. */
+  githubPrefetch?: boolean;
+  /** Stronger access modes requested for turns here are lowered to this. */
+  accessLock?: Exclude<AccessMode, "full-access"> | null;
+  /** Life workspaces: append finished turns to `Stream/Transcripts/`. */
+  lifeTranscriptEnabled?: boolean;
+  /** Extra `turn/start` fields for every turn: `reasoning`, `includePlan`, `tools`. */
+  turnExtras?: Record<string, unknown> | null;
+  /** Keep an app-server session running here while `warmSessions` is on. */
+  keepWarm?: boolean;
+  /** Interrupt turns after this many minutes; read-only turns excluded. */
+  maxTurnDurationMinutes?: number | null;
+  /** Budget for read-only turns; unset means no limit. */
+  readOnlyMaxTurnDurationMinutes?: number | null;
+  /** Demo mode: refuse git, terminal, prompt and vault writes; turns run read-only. */
+  readOnly?: boolean;
+};
+
+/** Carried by events that reference a workspace. */
+export type WorkspaceIdentity = {
+  id: string;
+  name: string;
+  color?: string;
+  icon?: string;
+};
+
+export type DateOrder = "mdy" | "dmy";
+
+export type QuickCommand = {
+  id: string;
+  label: string;
+  command: string;
+  args?: string[];
+  cwdRelative?: string | null;
+  env?: Record<string, string>;
+  concurrency?: "reject" | "queue";
+};
+
+export type QuickCommandEntry = QuickCommand & {
+  source: "settings" | "repo";
+  running: boolean;
+};
+
+export type QuickCommandExecution = {
+  executionId: string;
+  commandId: string;
+  queued: boolean;
+};
+
+export type QuickCommandOutputEvent = {
+  workspaceId: string;
+  commandId: string;
+  executionId: string;
+  stream: "stdout" | "stderr" | "exit";
+  data?: string;
+  exitCode?: number;
+  error?: string;
+  cancelled?: boolean;
 };
 
 export * from "./features/life/types";
@@ -28,11 +100,153 @@ export type WorktreeInfo = {
   branch: string;
 };
 
+export type WorktreeReportEntry = {
+  workspaceId: string;
+  name: string;
+  path: string;
+  branch: string | null;
+  exists: boolean;
+  diskBytes: number;
+  diskFiles: number;
+  diskUsageTruncated: boolean;
+  lastCommitAt: number | null;
+  merged: boolean | null;
+  ahead: number | null;
+  behind: number | null;
+  dirty: boolean;
+  suggestedAction: "safe to remove" | "has unmerged commits" | "dirty";
+  removed?: boolean;
+  removeError?: string;
+};
+
+export type WorktreeReport = {
+  parentWorkspaceId: string;
+  primaryBranch: string | null;
+  worktrees: WorktreeReportEntry[];
+};
+
+export type TerminalListEntry = {
+  terminalId: string;
+  cols: number;
+  rows: number;
+  scrollbackBytes: number;
+};
+
+export type TerminalScrollback = {
+  data: string;
+  truncated: boolean;
+};
+
+export type TerminalSnapshot = TerminalScrollback & {
+  cols: number;
+  rows: number;
+};
+
+export type TerminalSearchOptions = {
+  caseSensitive?: boolean;
+  /** Treat the query as a regular expression instead of literal text. */
+  regex?: boolean;
+  contextLines?: number;
+  maxMatches?: number;
+};
+
+export type TerminalContextLine = {
+  line: number;
+  /** The line without escape sequences. */
+  text: string;
+  /** Byte offset of the line in the scrollback. */
+  offset: number;
+};
+
+export type TerminalSearchMatch = {
+  /** Byte range of the match in the scrollback. */
+  start: number;
+  end: number;
+  line: number;
+  column: number;
+  context: TerminalContextLine[];
+};
+
+export type TerminalSearchResult = {
+  matches: TerminalSearchMatch[];
+  truncated: boolean;
+  scrollbackBytes: number;
+};
+
+export type WorktreeCompareMode = "working-tree" | "branches";
+
+export type WorktreeCompareFile = {
+  path: string;
+  oldPath?: string;
+  status: "A" | "M" | "D" | "R" | "C" | "T";
+  additions: number;
+  deletions: number;
+  isBinary: boolean;
+  patch: string | null;
+  patchTruncated: boolean;
+};
+
+export type WorktreeComparison = {
+  mode: WorktreeCompareMode;
+  workspaceIdA: string;
+  workspaceIdB: string;
+  refA: string | null;
+  refB: string | null;
+  files: WorktreeCompareFile[];
+  fileCount: number;
+  additions: number;
+  deletions: number;
+  truncated: boolean;
+};
+
+export type WorktreeApplyFile = {
+  path: string;
+  status: "added" | "deleted" | "renamed" | "modified";
+  additions: number | null;
+  deletions: number | null;
+};
+
+export type WorktreeApplyPreview = {
+  clean: boolean;
+  files: WorktreeApplyFile[];
+  conflicts: string[];
+  failed: { path: string; error: string; lines: number[] }[];
+  error?: string;
+};
+
+export type WorkspaceImportStatus =
+  | "candidate"
+  | "added"
+  | "skippedDuplicate"
+  | "error";
+
+export type WorkspaceImportResult = {
+  path: string;
+  name: string;
+  status: WorkspaceImportStatus;
+  workspaceId?: string;
+  error?: string;
+};
+
+export type WorkspaceImportReport = {
+  dryRun: boolean;
+  results: WorkspaceImportResult[];
+  truncated: boolean;
+};
+
 export type WorkspaceInfo = {
   id: string;
   name: string;
   path: string;
-  connected: boolean;
+  /**
+   * `"unhealthy"` while the app-server runs but stopped answering probes;
+   * `"stale"` once when its process exited without a disconnect.
+   */
+  connected: boolean | "unhealthy" | "stale";
+  /** The path did not answer in time, e.g. an offline network mount. */
+  pathUnavailable?: boolean;
+  /** Mirrors `settings.readOnly`; write actions fail with `workspace_read_only`. */
+  readOnly?: boolean;
   codex_bin?: string | null;
   kind?: WorkspaceKind;
   parentId?: string | null;
@@ -40,11 +254,35 @@ export type WorkspaceInfo = {
   settings: WorkspaceSettings;
 };
 
+export type WorktreeUpstreamStatus = "preserved" | "needs_rename" | "none";
+
+export type WorktreeUpstream = {
+  status: WorktreeUpstreamStatus;
+  remote?: string;
+  remoteBranch?: string;
+  renameUpstream?: {
+    id: string;
+    oldBranch: string;
+    newBranch: string;
+  };
+};
+
+export type RenamedWorktree = WorkspaceInfo & {
+  upstream?: WorktreeUpstream;
+};
+
 export type AppServerEvent = {
   workspace_id: string;
   message: Record<string, unknown>;
 };
 
+export type BackendErrorEvent = {
+  subsystem: string;
+  message: string;
+  restarted: boolean;
+  degraded: boolean;
+};
+
 export type Message = {
   id: string;
   role: "user" | "assistant";
@@ -86,6 +324,22 @@ export type ReviewTarget =
   | { type: "commit"; sha: string; title?: string }
   | { type: "custom"; instructions: string };
 
+export type ReviewOutcome = "running" | "completed" | "error" | "interrupted";
+
+export type ReviewRecord = {
+  workspaceId: string;
+  /** The detached review thread, or the thread the review started from. */
+  threadId: string;
+  sourceThreadId: string;
+  turnId: string | null;
+  target: ReviewTarget;
+  delivery: string | null;
+  startedAt: number;
+  finishedAt: number | null;
+  outcome: ReviewOutcome;
+  error?: string;
+};
+
 export type AccessMode = "read-only" | "current" | "full-access";
 export type BackendMode = "local" | "remote";
 export type ThemePreference = "system" | "light" | "dark";
@@ -150,6 +404,8 @@ export type AppSettings = {
   igdb_client_id?: string;
   igdb_client_secret?: string;
   exa_api_key?: string;
+  tmdb_enabled?: boolean;
+  igdb_enabled?: boolean;
   memory_embedding_enabled?: boolean;
   autoMemory: AutoMemorySettings;
   composerEditorPreset: ComposerEditorPreset;
@@ -162,6 +418,242 @@ export type AppSettings = {
   composerListContinuation: boolean;
   composerCodeBlockCopyUseModifier: boolean;
   workspaceGroups: WorkspaceGroup[];
+  protectedBranches?: string[];
+  notificationRules?: NotificationRule[];
+  autoArchive?: AutoArchiveSettings;
+  terminalShell?: string | null;
+  terminalAuditLog?: boolean;
+  terminalScrollbackBytes?: number | null;
+  revertUndoRetentionDays?: number;
+  reviewGate?: ReviewGateSettings;
+  redaction?: RedactionSettings;
+  keepalive?: KeepaliveSettings;
+  githubPrefetch?: GithubPrefetchSettings;
+  /** Rates `usage_overview` estimates cost with. */
+  usagePricing?: UsagePricing | null;
+  /** IANA zone or UTC offset days are counted in when a request names none. */
+  timezone?: string | null;
+  /** Let the daemon keep sessions running for workspaces with `keepWarm`. */
+  warmSessions?: boolean;
+};
+
+export type AutoArchiveSettings = {
+  enabled: boolean;
+  inactiveDays: number;
+};
+
+export type KeepaliveSettings = {
+  intervalSecs: number;
+  failureThreshold: number;
+  autoReconnect: boolean;
+};
+
+/** USD per million tokens. */
+export type UsagePricing = {
+  inputPerMillion: number;
+  /** Defaults to `inputPerMillion`. */
+  cachedInputPerMillion?: number | null;
+  outputPerMillion: number;
+};
+
+export type GithubPrefetchSettings = {
+  intervalMinutes: number;
+  maxConcurrent: number;
+};
+
+/** Params of the `github/dataUpdated` event. */
+export type GithubDataUpdated = {
+  openIssues: number;
+  openPullRequests: number;
+  newIssues: number;
+  newPullRequests: number;
+  failingChecks: number;
+  fetchedAt: number;
+};
+
+export type RedactionSettings = {
+  enabled: boolean;
+  filePatterns: string[];
+  valuePatterns: string[];
+};
+
+export type CommitMessagePrompt = {
+  prompt: string;
+  redactions: number;
+};
+
+export type CommitMessageResult = {
+  message: string;
+  redactions: number;
+};
+
+export type TurnCommitResult = {
+  sha: string;
+  /** Repo-relative, including both sides of renames. */
+  files: string[];
+  message: string;
+};
+
+export type ReviewGateSettings = {
+  maxFiles: number;
+  maxChangedLines: number;
+  maxDiffBytes: number;
+};
+
+export type ReviewTooLarge = {
+  status: "review_too_large";
+  stats: {
+    files: number;
+    additions: number;
+    deletions: number;
+    bytes: number;
+  };
+  limits: ReviewGateSettings;
+  exceeded: Array<"maxFiles" | "maxChangedLines" | "maxDiffBytes">;
+  suggestedSplit: Array<{
+    directory: string;
+    files: string[];
+    additions: number;
+    deletions: number;
+  }>;
+};
+
+export type ArchiveThreadResult = {
+  threadId: string;
+  ok: boolean;
+  error: string | null;
+};
+
+export type AutoArchiveCandidate = {
+  workspaceId: string;
+  threadId: string;
+  preview: string;
+  lastActivityMs: number;
+  inactiveDays: number;
+};
+
+export type ThreadOverviewEntry = {
+  threadId: string;
+  preview: string;
+  lastActivityAt: number | null;
+  lastViewedAt: number | null;
+  unread: boolean;
+  /** The thread's last turn stopped for running past its time budget. */
+  autoInterrupt?: { turnId: string; at: number; reason: string };
+};
+
+export type ThreadsOverview = {
+  threads: ThreadOverviewEntry[];
+  unreadCount: number;
+};
+
+export type ThreadItem =
+  | { type: "agentMessage"; id: string; text: string }
+  | { type: "userMessage"; id: string; content?: Record<string, unknown>[] }
+  | {
+      type: "commandExecution";
+      id: string;
+      command?: string | string[];
+      cwd?: string;
+      status?: string;
+      aggregatedOutput?: string;
+      exitCode?: number;
+      durationMs?: number;
+    }
+  | {
+      type: "fileChange";
+      id: string;
+      status?: string;
+      changes?: {
+        path: string;
+        kind?: string | { type: string };
+        diff?: string;
+      }[];
+    }
+  | { type: "reasoning"; id: string; summary?: string[]; content?: string[] }
+  | ({ type: string; id: string } & Record<string, unknown>);
+
+export type WorkspaceFileChunk = {
+  content: string;
+  /** More of the file remains past `nextOffset`. */
+  truncated: boolean;
+  offset: number;
+  nextOffset: number;
+  size: number;
+};
+
+export type WorkspaceSearchOptions = {
+  caseSensitive?: boolean;
+  /** ripgrep-style globs such as `*.rs`; a leading `!` excludes. */
+  globs?: string[];
+  contextLines?: number;
+  maxResults?: number;
+};
+
+export type WorkspaceSearchMatch = {
+  /** One-based line number. */
+  line: number;
+  column: number;
+  /** The matching line, trimmed. */
+  text: string;
+  before: string[];
+  after: string[];
+};
+
+export type WorkspaceSearchResult = {
+  files: { path: string; matches: WorkspaceSearchMatch[] }[];
+  totalMatches: number;
+  /** More matches exist past `maxResults`. */
+  truncated: boolean;
+  /** The search hit its time budget before walking every file. */
+  timedOut: boolean;
+};
+
+export type TailChunk = {
+  path: string;
+  offset: number;
+  nextOffset: number;
+  size: number;
+  data: string;
+  rotated: boolean;
+  truncated: boolean;
+};
+
+export type RevertUndoEntry = {
+  id: string;
+  workspaceId: string;
+  createdAtMs: number;
+  kind: "file" | "all";
+  paths: string[];
+  untrackedFiles: string[];
+  skipped: string[];
+  bytes: number;
+};
+
+export type NotificationRuleEvent = "turnCompleted" | "approvalRequested" | "error";
+
+export type NotificationAction =
+  | { type: "systemNotification"; title: string; body?: string | null; soundId?: string | null }
+  | { type: "sound"; soundId: string }
+  | { type: "none" };
+
+export type NotificationRule = {
+  id: string;
+  event: NotificationRuleEvent;
+  workspaceId?: string | null;
+  enabled?: boolean;
+  action: NotificationAction;
+};
+
+export type FiredNotification = {
+  ruleId: string;
+  workspaceId: string;
+  workspaceColor?: string;
+  workspaceIcon?: string;
+  event: NotificationRuleEvent;
+  title: string | null;
+  body: string | null;
+  soundId: string | null;
 };
 
 export type DomainTheme = {
@@ -184,11 +676,45 @@ export type Domain = {
   defaultApprovalPolicy?: "on-request" | "never" | null;
 };
 
+export type DomainSaveResult = Domain & {
+  warnings?: string[];
+};
+
+export type DomainUsage = {
+  domainId: string;
+  workspaces: { id: string; name: string; path: string }[];
+};
+
+export type DeleteDomainOptions = {
+  /** Move workspaces using the domain to this one. */
+  reassignTo?: string;
+  /** Clear the domain of workspaces using it instead of failing. */
+  force?: boolean;
+};
+
+export type DomainDeleteResult = {
+  updatedWorkspaceIds: string[];
+};
+
+export type TrendDelta = {
+  previous: string;
+  percent: number | null;
+  label: string;
+};
+
+/** How to read `TrendCard.valueNum`; `duration` is in minutes. */
+export type CardFormat = "currency" | "count" | "grams" | "duration" | "percent" | "number";
+
 export type TrendCard = {
   id: string;
   label: string;
   value: string;
   subLabel?: string | null;
+  delta?: TrendDelta;
+  /** Position in the domain's card layout; unchanged when other cards are left out. */
+  order?: number;
+  format?: CardFormat;
+  valueNum?: number;
 };
 
 export type TrendListItem = {
@@ -201,22 +727,48 @@ export type TrendList = {
   id: string;
   title: string;
   items: TrendListItem[];
+  order?: number;
+};
+
+export type TrendPoint = {
+  /** The day, or the Monday of the week when `bucket` is `week`. */
+  date: string;
+  value: number;
 };
 
 export type TrendSeries = {
   id: string;
   label: string;
-  points: number[];
-  labels?: string[] | null;
+  unit: string;
+  bucket: "day" | "week";
+  points: TrendPoint[];
+};
+
+export type StreamAppend = {
+  path: string;
+  date: string;
+  /** The line as written, e.g. `**9:00am** | Breakfast`. */
+  line: string;
+  createdHeader: boolean;
 };
 
+export type TrendRange = "7d" | "30d" | "mtd" | "last_month" | "ytd" | "lifetime";
+
 export type DomainTrendSnapshot = {
   domainId: string;
-  range: "7d" | "30d" | "lifetime";
+  range: TrendRange;
   updatedAt: string;
   cards: TrendCard[];
   lists: TrendList[];
   series?: TrendSeries[] | null;
+  roots?: VaultContribution[];
+  previous?: {
+    start: string;
+    end: string;
+    cards: TrendCard[];
+  };
+  /** Why part of the snapshot was left out, e.g. unreadable goals. */
+  diagnostics?: string[];
 };
 
 export type AutoMemorySettings = {
@@ -230,6 +782,80 @@ export type AutoMemorySettings = {
   includeGitStatus: boolean;
   writeDaily: boolean;
   writeCurated: boolean;
+  recallEnabled: boolean;
+  recallLimit: number;
+  recallMaxBytes: number;
+};
+
+export type MemoryRecallReport = {
+  included: boolean;
+  entries: number;
+  skipped?: "disabled" | "timeout" | "error" | "empty";
+};
+
+export type FileContextReport = {
+  attachedBytes: number;
+  budgetBytes: number;
+  files: { path: string; bytes: number; truncated: boolean }[];
+  errors: { path: string; error: string }[];
+  redactions: number;
+};
+
+/** What `send_user_message` starts a turn with for an access mode. */
+export type TurnPolicy = {
+  requestedAccessMode: string;
+  accessMode: AccessMode;
+  /** The workspace's `accessLock` when it lowered the requested mode. */
+  lockedBy: Exclude<AccessMode, "full-access"> | null;
+  approvalPolicy: "never" | "on-request";
+  sandboxPolicy: Record<string, unknown>;
+};
+
+export type MessageComponentSize = {
+  bytes: number;
+  tokens: number;
+};
+
+export type MessageSizeEstimate = {
+  text: MessageComponentSize;
+  fileContext: MessageComponentSize;
+  images: MessageComponentSize;
+  instructions: MessageComponentSize;
+  totalBytes: number;
+  estimatedTokens: number;
+  model: string | null;
+  contextWindow: number | null;
+  /** `null` when the model's context window is unknown. */
+  fits: boolean | null;
+  files?: FileContextReport;
+  memoryRecall?: MemoryRecallReport;
+};
+
+export type MessagePart =
+  | { type: "text"; text: string }
+  | { type: "prompt"; path: string; args?: string[] | Record<string, string> }
+  | { type: "file"; path: string }
+  | { type: "diff"; scope: "staged" | "unstaged" | "commit"; sha?: string }
+  | { type: "image"; image: string };
+
+export type ComposedPart = {
+  type: MessagePart["type"];
+  bytes: number;
+  originalBytes: number;
+  truncated: boolean;
+  redactions: number;
+};
+
+export type ComposedMessage = {
+  text: string;
+  images: string[];
+  parts: ComposedPart[];
+  budgetBytes: number;
+  usedBytes: number;
+  truncated: boolean;
+  estimate: MessageSizeEstimate;
+  /** The `turn/start` response when the call had `send: true`. */
+  sent?: Record<string, unknown>;
 };
 
 export type MemoryStatus = {
@@ -241,6 +867,36 @@ export type MemoryStatus = {
   error: number;
 };
 
+export type MemoryConnectionCheck = {
+  name: "config" | "reachable" | "auth" | "table" | "embeddings";
+  status: "ok" | "failed" | "skipped";
+  message: string;
+  sql?: string;
+};
+
+export type MemoryConnectionReport = {
+  ok: boolean;
+  supabaseUrl: string;
+  checks: MemoryConnectionCheck[];
+};
+
+export type AppSettingsUpdate = AppSettings & {
+  memoryConnection?: MemoryConnectionReport;
+};
+
+export type SettingChange = {
+  key: string;
+  before: unknown;
+  after: unknown;
+};
+
+export type SettingsAuditEntry = {
+  at: number;
+  method: string;
+  client?: string;
+  changes: SettingChange[];
+};
+
 export type MemorySearchResult = {
   id: string;
   content: string;
@@ -282,6 +938,89 @@ export type CodexDoctorResult = {
   nodeDetails: string | null;
 };
 
+export type SpawnFailure = {
+  message: string;
+  stderr: string;
+  stdout: string;
+  exitStatus: string | null;
+  occurredAtMs: number;
+  attempts: number;
+};
+
+export type WorkspaceDoctorResult = {
+  workspaceId: string;
+  connected: boolean;
+  lastSpawnError: SpawnFailure | null;
+};
+
+export type ToolCheck = {
+  installed: boolean;
+  version?: string;
+};
+
+export type OnboardingStatus = {
+  /** No codex, or no workspace yet. */
+  needsOnboarding: boolean;
+  codexInstalled: boolean;
+  /** Missing when the daemon is unreachable. */
+  codex?: CodexDoctorResult;
+  workspaceCount: number;
+  settingsSaved: boolean;
+  git?: ToolCheck;
+  gh?: GhAuthStatus;
+  /** Missing when the app runs its own backend. */
+  daemon?: { reachable: boolean; error?: string };
+};
+
+export type OnboardingBootstrapOptions = {
+  codexBin?: string | null;
+  starterPrompts?: boolean;
+};
+
+export type OnboardingStepId =
+  | "addWorkspace"
+  | "connectWorkspace"
+  | "workspaceDoctor"
+  | "starterPrompts";
+
+export type OnboardingStep = {
+  id: OnboardingStepId;
+  status: "done" | "skipped" | "failed";
+  detail?: string;
+  error?: string;
+};
+
+export type OnboardingBootstrapReport = {
+  ok: boolean;
+  workspaceId?: string;
+  steps: OnboardingStep[];
+};
+
+export type SessionCapabilities = {
+  codexVersion: string | null;
+  userAgent: string | null;
+  methods: Record<string, boolean>;
+  /** `review/start` delivery modes; missing until probed. */
+  reviewDeliveryModes?: string[];
+};
+
+export type EffectiveCodexArg = {
+  value: string;
+  source: "settings" | "workspace" | "parent";
+};
+
+export type EffectiveCodexArgs = {
+  workspaceId: string;
+  args: EffectiveCodexArg[];
+  needsReconnect: boolean;
+};
+
+export type ThreadOverrides = {
+  model: string | null;
+  effort: string | null;
+  updatedAt: number;
+};
+
 export type ApprovalRequest = {
   workspace_id: string;
   request_id: number | string;
@@ -330,6 +1069,24 @@ export type GitFileStatus = {
   deletions: number;
 };
 
+/** `[offset, length]` in UTF-16 code units of the line without its `-`/`+`. */
+export type IntralineRange = [number, number];
+
+export type IntralinePair = {
+  oldLine: number;
+  newLine: number;
+  oldRanges: IntralineRange[];
+  newRanges: IntralineRange[];
+  /** The lines were too long to compare; no ranges are given. */
+  skipped?: boolean;
+};
+
+export type IntralineHunk = {
+  /** Position of the hunk in the file's diff, from 0. */
+  hunk: number;
+  pairs: IntralinePair[];
+};
+
 export type GitFileDiff = {
   path: string;
   diff: string;
@@ -339,6 +1096,27 @@ export type GitFileDiff = {
   newImageData?: string | null;
   oldImageMime?: string | null;
   newImageMime?: string | null;
+  /** Only whitespace changed, e.g. CRLF line endings. */
+  whitespaceOnly?: boolean;
+  /** Word-level changes; only present when requested with `intraline`. */
+  intraline?: IntralineHunk[];
+  /** Staged hunks then unstaged ones; only present when requested with `hunks`. */
+  hunks?: GitDiffHunk[];
+};
+
+/** A hunk of the staged (HEAD to index) or unstaged (index to worktree) diff. */
+export type GitDiffHunk = {
+  /** Position among the file's hunks on the same side, from 0. */
+  index: number;
+  staged: boolean;
+  /** The `@@ -a,b +c,d @@` line. */
+  header: string;
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
+  /** The header and the hunk's lines. */
+  content: string;
 };
 
 export type GitCommitDiff = {
@@ -351,6 +1129,10 @@ export type GitCommitDiff = {
   newImageData?: string | null;
   oldImageMime?: string | null;
   newImageMime?: string | null;
+  /** Only whitespace changed, e.g. CRLF line endings. */
+  whitespaceOnly?: boolean;
+  /** Word-level changes; only present when requested with `intraline`. */
+  intraline?: IntralineHunk[];
 };
 
 export type GitLogEntry = {
@@ -408,6 +1190,7 @@ export type GitHubPullRequestDiff = {
   path: string;
   status: string;
   diff: string;
+  intraline?: IntralineHunk[];
 };
 
 export type GitHubPullRequestComment = {
@@ -418,6 +1201,16 @@ export type GitHubPullRequestComment = {
   author: GitHubUser | null;
 };
 
+export type GhAuthState = "authenticated" | "unauthenticated" | "missing";
+
+export type GhAuthStatus = {
+  state: GhAuthState;
+  version?: string;
+  host: string;
+  fixCommand?: string;
+  checkedAt: number;
+};
+
 export type TokenUsageBreakdown = {
   totalTokens: number;
   inputTokens: number;
@@ -464,6 +1257,40 @@ export type LocalUsageSnapshot = {
   topModels: LocalUsageModel[];
 };
 
+export type UsagePeriod = {
+  inputTokens: number;
+  cachedInputTokens: number;
+  outputTokens: number;
+  totalTokens: number;
+  /** USD; `null` without `usagePricing`. */
+  estimatedCost: number | null;
+};
+
+export type UsageOverview = {
+  generatedAt: number;
+  rateLimits: {
+    workspaceId: string;
+    /** As the app-server reported it. */
+    snapshot: Record<string, unknown>;
+    fetchedAt: number;
+    ageMs: number;
+  } | null;
+  today: UsagePeriod | null;
+  /** The last seven days, today included. */
+  week: UsagePeriod | null;
+  workspace: {
+    workspaceId: string;
+    today: UsagePeriod;
+    week: UsagePeriod;
+    todaySharePercent: number | null;
+    weekSharePercent: number | null;
+  } | null;
+  missing: {
+    part: "rateLimits" | "pricing" | "localUsage" | "workspaceUsage";
+    reason: string;
+  }[];
+};
+
 export type TurnPlanStepStatus = "pending" | "inProgress" | "completed";
 
 export type TurnPlanStep = {
@@ -529,13 +1356,16 @@ export type SkillOption = {
   description?: string;
 };
 
+/** `parent` prompts belong to the worktree's parent workspace. */
+export type PromptScope = "workspace" | "parent" | "global";
+
 export type CustomPromptOption = {
   name: string;
   path: string;
   description?: string;
   argumentHint?: string;
   content: string;
-  scope?: "workspace" | "global";
+  scope?: PromptScope;
 };
 
 export type BranchInfo = {
@@ -543,6 +1373,82 @@ export type BranchInfo = {
   lastCommit: number;
 };
 
+export type ThreadSummary = {
+  threadId: string;
+  summary: string;
+  itemsIncluded: number;
+  itemsTotal: number;
+  sourceChars: number;
+  summaryChars: number;
+  approxSourceTokens: number;
+  approxSummaryTokens: number;
+  newThreadId?: string;
+};
+
+export type GitTagInfo = {
+  name: string;
+  target: string;
+  annotated: boolean;
+  tagger?: string | null;
+  message?: string | null;
+  date: number;
+};
+
+export type GitStashEntry = {
+  /** Position in the stash list; `0` is the newest. */
+  index: number;
+  message: string;
+  /** Unix seconds. */
+  date: number;
+  /** As git prints it, e.g. "2 hours ago". */
+  relativeDate: string;
+};
+
+export type GitCommitOutcome = {
+  /** An amend rewrote a commit already on the upstream; the next push has to be forced. */
+  amendedPushedCommit: boolean;
+};
+
+export type AttachmentInfo = {
+  id: string;
+  path: string;
+  filename: string;
+  bytes: number;
+};
+
+export type DataUsageCategory = {
+  id: "attachments" | "revertUndo";
+  files: number;
+  bytes: number;
+};
+
+export type DataUsage = {
+  categories: DataUsageCategory[];
+  totalBytes: number;
+};
+
+export type SessionResource = {
+  workspaceId: string;
+  pid: number | null;
+  rssBytes: number | null;
+  keepWarm: boolean;
+  prewarmed: boolean;
+};
+
+export type StartLatency = {
+  count: number;
+  averageMs: number;
+  maxMs: number;
+  lastMs: number | null;
+};
+
+export type ResourceUsage = {
+  warmSessions: boolean;
+  sessions: SessionResource[];
+  totalRssBytes: number;
+  threadStarts: { cold: StartLatency; warm: StartLatency };
+};
+
 export type DebugEntry = {
   id: string;
   timestamp: number;